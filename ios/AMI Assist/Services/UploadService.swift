@@ -112,7 +112,7 @@ class UploadService: ObservableObject {
                     case "complete":
                         rec.status = .complete
                         rec.errorMessage = nil
-                    case "failed":
+                    case "failed", "dead_letter":
                         rec.status = .failed
                         rec.errorMessage = job.error
                     default:
@@ -0,0 +1,222 @@
+use crate::error::ApiError;
+use crate::store::AppState;
+use crate::types::{AuditOutcome, AuditRecord, Caller};
+use axum::{
+    body::Body,
+    http::Request,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::Utc;
+use std::sync::Arc;
+use tracing::error;
+
+/// Route template and identifiers of a PHI-bearing request
+#[derive(Debug, Default, PartialEq)]
+pub struct AuditTarget {
+    pub route: String,
+    pub physician_id: Option<String>,
+    pub session_id: Option<String>,
+    pub patient_id: Option<String>,
+    pub job_id: Option<String>,
+    pub upload_id: Option<String>,
+    pub date: Option<String>,
+}
+
+/// PHI access audit middleware.
+///
+/// Runs outside auth so rejected attempts are recorded too. Session, audio,
+/// upload, search, patient and day-log routes get one entry each in the hash-chained
+/// audit log (see `store::audit_log`) after the handler responds; everything
+/// else passes straight through. The caller comes from the `Caller` the auth
+/// middleware attaches to the response.
+///
+/// Fails closed. While appends are failing, PHI requests are refused before
+/// the handler runs. If the entry for a request can't be written, its
+/// response is dropped for a 500 — for a write, the change has still landed.
+/// `/health` and `/admin/audit/verify` report the failure.
+pub async fn record_phi_access(state: Arc<AppState>, req: Request<Body>, next: Next) -> Response {
+    let method = req.method().to_string();
+    let Some(target) = classify(req.uri().path()) else {
+        return next.run(req).await;
+    };
+
+    if let Err(e) = state.audit_log.write().await.check_writable() {
+        error!("Audit log unavailable, refusing PHI request: {e}");
+        return audit_unavailable();
+    }
+
+    let resp = next.run(req).await;
+    let status = resp.status().as_u16();
+    let record = AuditRecord {
+        seq: 0,
+        timestamp: Utc::now().to_rfc3339(),
+        caller: resp
+            .extensions()
+            .get::<Caller>()
+            .cloned()
+            .unwrap_or(Caller::Anonymous),
+        method,
+        route: target.route,
+        physician_id: target.physician_id,
+        session_id: target.session_id,
+        patient_id: target.patient_id,
+        job_id: target.job_id,
+        upload_id: target.upload_id,
+        date: target.date,
+        status,
+        outcome: AuditOutcome::from_status(status),
+    };
+    if let Err(e) = state.audit_log.write().await.append(record) {
+        error!("Failed to write audit entry, withholding response: {e}");
+        return audit_unavailable();
+    }
+    resp
+}
+
+fn audit_unavailable() -> Response {
+    ApiError::Internal("Audit log unavailable".into()).into_response()
+}
+
+/// Map a request path to its audit target, or None for routes that don't
+/// touch PHI. Path parameters are lifted out into fields and replaced by
+/// their names so entries group by route. Query strings are never seen.
+pub fn classify(path: &str) -> Option<AuditTarget> {
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let owned = |s: &str| Some(s.to_string());
+
+    match segments.as_slice() {
+        ["mobile", "uploads", job_id] => Some(AuditTarget {
+            route: "/mobile/uploads/:job_id".into(),
+            job_id: owned(job_id),
+            ..Default::default()
+        }),
+        // Recording bytes, whole or in resumable chunks
+        ["mobile", "upload"] | ["mobile", "upload", "resumable"] => Some(AuditTarget {
+            route: format!("/{}", segments.join("/")),
+            ..Default::default()
+        }),
+        ["uploads", upload_id] => Some(AuditTarget {
+            route: "/uploads/:upload_id".into(),
+            upload_id: owned(upload_id),
+            ..Default::default()
+        }),
+        ["physicians", physician_id, rest @ ..] => {
+            let physician_id = owned(physician_id);
+            let target = match rest {
+                ["sessions"] => AuditTarget {
+                    route: "/physicians/:physician_id/sessions".into(),
+                    ..Default::default()
+                },
+                ["sessions", action @ ("dates" | "merge" | "renumber")] => AuditTarget {
+                    route: format!("/physicians/:physician_id/sessions/{action}"),
+                    ..Default::default()
+                },
+                ["sessions", session_id, tail @ ..] => AuditTarget {
+                    route: format!(
+                        "/physicians/:physician_id/sessions/:session_id{}",
+                        session_tail(tail)
+                    ),
+                    session_id: owned(session_id),
+                    ..Default::default()
+                },
+                ["search"] => AuditTarget {
+                    route: "/physicians/:physician_id/search".into(),
+                    ..Default::default()
+                },
+                ["day-log", date] => AuditTarget {
+                    route: "/physicians/:physician_id/day-log/:date".into(),
+                    date: owned(date),
+                    ..Default::default()
+                },
+                ["patients"] => AuditTarget {
+                    route: "/physicians/:physician_id/patients".into(),
+                    ..Default::default()
+                },
+                ["patients", "confirm"] => AuditTarget {
+                    route: "/physicians/:physician_id/patients/confirm".into(),
+                    ..Default::default()
+                },
+                ["patients", patient_id] => AuditTarget {
+                    route: "/physicians/:physician_id/patients/:patient_id".into(),
+                    patient_id: owned(patient_id),
+                    ..Default::default()
+                },
+                _ => return None,
+            };
+            Some(AuditTarget {
+                physician_id,
+                ..target
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Route suffix below a session. File names are templated — they're ours
+/// (`transcript.txt`, screenshot timestamps), but keep entries uniform.
+fn session_tail(tail: &[&str]) -> String {
+    match tail {
+        [] => String::new(),
+        ["files", "screenshots", _] => "/files/screenshots/:screenshot".into(),
+        ["files", _] => "/files/:filename".into(),
+        other => format!("/{}", other.join("/")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_session_audio() {
+        let t = classify("/physicians/p1/sessions/s1/audio").unwrap();
+        assert_eq!(t.route, "/physicians/:physician_id/sessions/:session_id/audio");
+        assert_eq!(t.physician_id.as_deref(), Some("p1"));
+        assert_eq!(t.session_id.as_deref(), Some("s1"));
+    }
+
+    #[test]
+    fn collection_routes_are_not_session_ids() {
+        let t = classify("/physicians/p1/sessions/dates").unwrap();
+        assert_eq!(t.route, "/physicians/:physician_id/sessions/dates");
+        assert_eq!(t.session_id, None);
+        let t = classify("/physicians/p1/patients/confirm").unwrap();
+        assert_eq!(t.patient_id, None);
+    }
+
+    #[test]
+    fn templates_file_names() {
+        let t = classify("/physicians/p1/sessions/s1/files/screenshots/123.jpg").unwrap();
+        assert_eq!(
+            t.route,
+            "/physicians/:physician_id/sessions/:session_id/files/screenshots/:screenshot"
+        );
+    }
+
+    #[test]
+    fn classifies_upload_routes() {
+        let t = classify("/uploads/u1").unwrap();
+        assert_eq!(t.route, "/uploads/:upload_id");
+        assert_eq!(t.upload_id.as_deref(), Some("u1"));
+        assert_eq!(
+            classify("/mobile/upload/resumable").unwrap().route,
+            "/mobile/upload/resumable"
+        );
+        assert_eq!(classify("/mobile/upload").unwrap().route, "/mobile/upload");
+        let t = classify("/physicians/p1/sessions/s1/audio/resumable").unwrap();
+        assert_eq!(
+            t.route,
+            "/physicians/:physician_id/sessions/:session_id/audio/resumable"
+        );
+    }
+
+    #[test]
+    fn ignores_non_phi_routes() {
+        assert_eq!(classify("/physicians/p1"), None);
+        assert_eq!(classify("/physicians"), None);
+        assert_eq!(classify("/config/prompts"), None);
+        assert_eq!(classify("/health"), None);
+        assert_eq!(classify("/mobile/jobs/j1"), None);
+    }
+}
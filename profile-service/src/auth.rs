@@ -1,40 +1,152 @@
+use crate::error::ApiError;
+use crate::store::api_keys::ApiKeyManager;
+use crate::store::AppState;
+use crate::types::{ApiKeyScope, Caller};
 use axum::{
     body::Body,
-    http::{Request, StatusCode},
+    http::{Method, Request},
     middleware::Next,
     response::{IntoResponse, Response},
-    Json,
 };
-use serde_json::json;
+use std::sync::Arc;
+use tracing::warn;
 
 /// API key authentication middleware.
 ///
-/// - If `expected_key` is `None`, all requests pass through (backward compatible / dev mode).
 /// - The `/health` endpoint is always exempt from auth.
-/// - All other requests must provide a matching `X-API-Key` header.
+/// - `legacy_key` is the single shared key (`--api-key` / `PROFILE_API_KEY`).
+///   It keeps full access so existing single-key deployments work unchanged.
+/// - Keys from the registry (`/admin/keys`) must carry a scope that covers
+///   the route (see [`required_scope`]); revoked keys are rejected.
+/// - With no legacy key and no active registry key, all requests pass
+///   through (backward compatible / dev mode). The first registry key
+///   created that way must carry `admin` (enforced in `api_keys::create`).
+/// - The resolved [`Caller`] is attached to the request and to the response,
+///   rejected or not, for the PHI audit log.
 pub async fn check_api_key(
-    expected_key: Option<String>,
+    state: Arc<AppState>,
+    legacy_key: Option<String>,
     req: Request<Body>,
     next: Next,
 ) -> Response {
-    // No key configured — pass everything through (dev / backward compat)
-    let expected = match expected_key {
-        Some(k) => k,
-        None => return next.run(req).await,
-    };
-
     // Health endpoint is always exempt
     if req.uri().path() == "/health" {
         return next.run(req).await;
     }
 
-    // Check the X-API-Key header
-    match req.headers().get("X-API-Key").and_then(|v| v.to_str().ok()) {
-        Some(provided) if provided == expected => next.run(req).await,
-        _ => (
-            StatusCode::UNAUTHORIZED,
-            Json(json!({ "error": "Invalid or missing API key" })),
-        )
-            .into_response(),
+    // No key configured anywhere — pass everything through, even requests
+    // that carry a key
+    if legacy_key.is_none() && !state.api_keys.read().await.has_active_keys() {
+        return with_caller(Caller::Anonymous, req, next).await;
+    }
+
+    let provided = req
+        .headers()
+        .get("X-API-Key")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+
+    let Some(provided) = provided else {
+        return tag(Caller::Anonymous, unauthorized());
+    };
+
+    if legacy_key.as_deref() == Some(provided.as_str()) {
+        return with_caller(Caller::LegacyKey, req, next).await;
+    }
+
+    let required = required_scope(req.method(), req.uri().path());
+    let key = state.api_keys.read().await.authenticate(&provided);
+    let Some(key) = key else {
+        return tag(Caller::Anonymous, unauthorized());
+    };
+    if ApiKeyManager::last_use_is_stale(&key) {
+        record_use(&state, &key.id).await;
+    }
+    let caller = Caller::Key {
+        id: key.id.clone(),
+        name: key.name.clone(),
+    };
+    if key.allows(required) {
+        return with_caller(caller, req, next).await;
+    }
+    let rejection = if key.revoked_at.is_some() {
+        ApiError::Unauthorized("API key has been revoked".into())
+    } else {
+        ApiError::Forbidden(format!(
+            "API key '{}' lacks the {} scope",
+            key.name,
+            required.as_str()
+        ))
+    };
+    tag(caller, rejection.into_response())
+}
+
+/// Stamp the key's last use in memory and write the registry on the
+/// blocking pool without waiting for it.
+async fn record_use(state: &AppState, id: &str) {
+    let Some(pending) = state.api_keys.write().await.record_use(id) else {
+        return;
+    };
+    tokio::task::spawn_blocking(move || {
+        if let Err(e) = pending.write() {
+            warn!("Failed to persist API key last-used time: {e}");
+        }
+    });
+}
+
+/// Run the request with `caller` visible to handlers, and echo it on the
+/// response for the audit middleware outside this layer.
+async fn with_caller(caller: Caller, mut req: Request<Body>, next: Next) -> Response {
+    req.extensions_mut().insert(caller.clone());
+    tag(caller, next.run(req).await)
+}
+
+fn tag(caller: Caller, mut resp: Response) -> Response {
+    resp.extensions_mut().insert(caller);
+    resp
+}
+
+fn unauthorized() -> Response {
+    ApiError::Unauthorized("Invalid or missing API key".into()).into_response()
+}
+
+/// Least scope a route needs.
+///
+/// - `admin`: key management, config writes (prompts, thresholds, rollback,
+///   promote…) and infrastructure settings.
+/// - `mobile_upload`: what the iOS app calls — upload (including resumable
+///   chunks under `/uploads/:upload_id`), job status, and the physician list.
+/// - `room`: everything else.
+pub fn required_scope(method: &Method, path: &str) -> ApiKeyScope {
+    let is_read = *method == Method::GET || *method == Method::HEAD;
+
+    if path.starts_with("/admin/")
+        || (!is_read && (path.starts_with("/config/") || path == "/infrastructure"))
+    {
+        return ApiKeyScope::Admin;
+    }
+
+    // `/mobile/jobs`, `/mobile/jobs/stream` or `/mobile/jobs/:job_id`
+    let mobile_job = match path.strip_prefix("/mobile/jobs") {
+        Some("") => true,
+        Some(rest) => rest
+            .strip_prefix('/')
+            .is_some_and(|id| !id.is_empty() && !id.contains('/')),
+        None => false,
+    };
+    // `/uploads/:upload_id` — chunks for a resumable upload; the handler
+    // also checks the key is the one that started it
+    let upload_chunk = path
+        .strip_prefix("/uploads/")
+        .is_some_and(|id| !id.is_empty() && !id.contains('/'));
+    let mobile = ((path == "/mobile/upload" || path == "/mobile/upload/resumable")
+        && *method == Method::POST)
+        || (upload_chunk && (is_read || *method == Method::PATCH))
+        || (mobile_job && (is_read || *method == Method::DELETE))
+        || (path == "/physicians" && is_read);
+    if mobile {
+        ApiKeyScope::MobileUpload
+    } else {
+        ApiKeyScope::Room
     }
 }
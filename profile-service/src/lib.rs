@@ -1,3 +1,4 @@
+pub mod audit;
 pub mod auth;
 pub mod error;
 pub mod routes;
@@ -15,6 +16,18 @@ use store::AppState;
 /// Create an `AppState` from a data directory path.
 /// The directory (and all store files) will be created if they don't exist.
 pub fn create_app_state(data_dir: &Path) -> Arc<AppState> {
+    let master_key = store::encryption::MasterKeyConfig::from_env()
+        .expect("Failed to load session master key");
+    create_app_state_with_master_key(data_dir, master_key)
+}
+
+/// Like [`create_app_state`], with the session encryption master key given
+/// explicitly instead of read from the environment. `None` stores session
+/// files in plaintext.
+pub fn create_app_state_with_master_key(
+    data_dir: &Path,
+    master_key: Option<store::encryption::MasterKeyConfig>,
+) -> Arc<AppState> {
     std::fs::create_dir_all(data_dir).expect("Failed to create data directory");
 
     let physicians =
@@ -28,14 +41,23 @@ pub fn create_app_state(data_dir: &Path) -> Arc<AppState> {
     let infrastructure =
         store::infrastructure::InfrastructureStore::load(data_dir.join("infrastructure.json"))
             .expect("Failed to load infrastructure settings");
-    let sessions = store::sessions::SessionStore::new(data_dir.join("sessions"));
+    let sessions = store::sessions::SessionStore::new(
+        data_dir.join("sessions"),
+        master_key.map(store::encryption::SessionCipher::new),
+    );
     let mobile_jobs = store::mobile_jobs::MobileJobStore::load(
         data_dir.join("mobile_jobs.json"),
         data_dir.join("mobile_uploads"),
     )
     .expect("Failed to load mobile jobs");
+    let uploads = store::uploads::UploadStore::load(data_dir.join("uploads"))
+        .expect("Failed to load resumable uploads");
     let config_data = store::config_data::ConfigDataStore::load(data_dir)
         .expect("Failed to load config data");
+    let api_keys = store::api_keys::ApiKeyManager::load(data_dir.join("api_keys.json"))
+        .expect("Failed to load API keys");
+    let audit_log = store::audit_log::AuditLog::load(store::audit_log::log_path(data_dir))
+        .expect("Failed to load audit log");
     let patients = store::patients::PatientManager::load(data_dir.join("patients.json"))
         .expect("Failed to load patients");
     let medplum_auth = store::medplum_auth::MedplumAuthProxy::new(
@@ -52,7 +74,10 @@ pub fn create_app_state(data_dir: &Path) -> Arc<AppState> {
         infrastructure: RwLock::new(infrastructure),
         sessions,
         mobile_jobs: RwLock::new(mobile_jobs),
+        uploads: RwLock::new(uploads),
         config_data: RwLock::new(config_data),
+        api_keys: RwLock::new(api_keys),
+        audit_log: RwLock::new(audit_log),
         patients: RwLock::new(patients),
         medplum_auth,
         openai_image,
@@ -62,18 +87,25 @@ pub fn create_app_state(data_dir: &Path) -> Arc<AppState> {
 
 /// Build the full application router with middleware layers.
 ///
-/// Layer order (outermost → innermost): CORS → body limit → auth.
-/// Requests flow: CORS check → body limit → auth → route handler.
+/// Layer order (outermost → innermost): CORS → body limit → audit → auth.
+/// Requests flow: CORS check → body limit → audit → auth → route handler.
+/// Audit sits outside auth so rejected PHI requests are logged too.
 pub fn build_app(
     state: Arc<AppState>,
     api_key: Option<String>,
 ) -> axum::Router {
-    let router = routes::build_router(state);
+    let router = routes::build_router(state.clone());
 
     // Auth middleware (innermost — runs closest to handlers)
     let key = api_key;
+    let auth_state = state.clone();
+    let router = router.layer(axum::middleware::from_fn(move |req, next| {
+        auth::check_api_key(auth_state.clone(), key.clone(), req, next)
+    }));
+
+    // PHI access audit (wraps auth)
     let router = router.layer(axum::middleware::from_fn(move |req, next| {
-        auth::check_api_key(key.clone(), req, next)
+        audit::record_phi_access(state.clone(), req, next)
     }));
 
     // Body limit layer
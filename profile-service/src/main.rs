@@ -19,6 +19,7 @@ async fn main() {
         .init();
 
     // CLI args: --port PORT --data-dir PATH --api-key KEY
+    //           [--verify-audit | --encrypt-sessions | --rotate-master-key]
     let args: Vec<String> = std::env::args().collect();
     let port = find_arg(&args, "--port")
         .and_then(|s| s.parse::<u16>().ok())
@@ -27,18 +28,48 @@ async fn main() {
         .map(PathBuf::from)
         .unwrap_or_else(default_data_dir);
 
+    // --verify-audit: check the PHI audit log hash chain and exit
+    if args.iter().any(|a| a == "--verify-audit") {
+        verify_audit(&data_dir);
+    }
+
+    // One-shot session encryption maintenance; run with the server stopped
+    if args.iter().any(|a| a == "--encrypt-sessions") {
+        let state = profile_service::create_app_state(&data_dir);
+        exit_with(state.sessions.encrypt_existing());
+    }
+    if args.iter().any(|a| a == "--rotate-master-key") {
+        let state = profile_service::create_app_state(&data_dir);
+        exit_with(
+            state
+                .sessions
+                .rotate_master_key()
+                .map(|rewrapped| serde_json::json!({ "rewrapped": rewrapped })),
+        );
+    }
+
     // API key: CLI arg takes precedence, then env var
     let api_key = find_arg(&args, "--api-key")
         .or_else(|| std::env::var("PROFILE_API_KEY").ok());
 
-    if api_key.is_some() {
-        info!("API key authentication enabled");
+    // Build state and app via library functions
+    let state = profile_service::create_app_state(&data_dir);
+
+    if state.sessions.is_encrypted() {
+        info!("Session encryption at rest enabled");
     } else {
-        info!("API key authentication disabled (no key configured)");
+        info!("Session encryption at rest disabled (no PROFILE_MASTER_KEY configured)");
     }
 
-    // Build state and app via library functions
-    let state = profile_service::create_app_state(&data_dir);
+    let registry_keys = state.api_keys.read().await.has_active_keys();
+    if api_key.is_some() || registry_keys {
+        info!(
+            legacy_key = api_key.is_some(),
+            registry_keys, "API key authentication enabled"
+        );
+    } else {
+        info!("API key authentication disabled (no key configured)");
+    }
     let app = profile_service::build_app(state, api_key);
 
     let addr = SocketAddr::from(([0, 0, 0, 0], port));
@@ -63,6 +94,39 @@ async fn shutdown_signal() {
     info!("Shutting down...");
 }
 
+fn verify_audit(data_dir: &std::path::Path) -> ! {
+    let path = profile_service::store::audit_log::log_path(data_dir);
+    match profile_service::store::audit_log::verify(&path) {
+        Ok(report) => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&report).expect("Failed to serialize report")
+            );
+            std::process::exit(if report.valid { 0 } else { 1 });
+        }
+        Err(e) => {
+            eprintln!("Failed to verify {}: {e}", path.display());
+            std::process::exit(2);
+        }
+    }
+}
+
+fn exit_with<T: serde::Serialize>(result: Result<T, profile_service::error::ApiError>) -> ! {
+    match result {
+        Ok(report) => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&report).expect("Failed to serialize report")
+            );
+            std::process::exit(0);
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    }
+}
+
 fn find_arg(args: &[String], flag: &str) -> Option<String> {
     args.iter()
         .position(|a| a == flag)
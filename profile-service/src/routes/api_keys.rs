@@ -0,0 +1,44 @@
+use crate::error::ApiError;
+use crate::store::AppState;
+use crate::types::{ApiKey, ApiKeyScope, Caller, CreateApiKeyRequest, CreatedApiKey};
+use axum::extract::{Extension, Path, State};
+use axum::Json;
+use std::sync::Arc;
+
+/// GET /admin/keys — All registered keys, including revoked ones.
+pub async fn list(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<ApiKey>>, ApiError> {
+    let mgr = state.api_keys.read().await;
+    Ok(Json(mgr.list()))
+}
+
+/// POST /admin/keys — Register a named key. The response carries the
+/// secret; it cannot be retrieved again.
+///
+/// An anonymous caller only gets here while auth is open (no legacy key, no
+/// active registry key). That bootstrap key must hold `admin`, or nobody
+/// could manage keys once it switches auth on.
+pub async fn create(
+    State(state): State<Arc<AppState>>,
+    Extension(caller): Extension<Caller>,
+    Json(req): Json<CreateApiKeyRequest>,
+) -> Result<Json<CreatedApiKey>, ApiError> {
+    req.validate()?;
+    if matches!(caller, Caller::Anonymous) && !req.scopes.contains(&ApiKeyScope::Admin) {
+        return Err(ApiError::Forbidden(
+            "The first API key must have the admin scope".into(),
+        ));
+    }
+    let mut mgr = state.api_keys.write().await;
+    Ok(Json(mgr.create(req)?))
+}
+
+/// POST /admin/keys/:id/revoke — Revoke a key; takes effect on its next request.
+pub async fn revoke(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<ApiKey>, ApiError> {
+    let mut mgr = state.api_keys.write().await;
+    Ok(Json(mgr.revoke(&id)?))
+}
@@ -0,0 +1,26 @@
+use crate::error::ApiError;
+use crate::store::{audit_log, AppState};
+use crate::types::{AuditEntry, AuditQuery, AuditVerifyReport};
+use axum::extract::{Query, State};
+use axum::Json;
+use std::sync::Arc;
+
+/// GET /admin/audit — PHI access entries matching the filters, oldest first.
+pub async fn query(
+    State(state): State<Arc<AppState>>,
+    Query(q): Query<AuditQuery>,
+) -> Result<Json<Vec<AuditEntry>>, ApiError> {
+    let log = state.audit_log.read().await;
+    Ok(Json(log.query(&q)?))
+}
+
+/// GET /admin/audit/verify — Walk the hash chain and report the first break.
+pub async fn verify(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<AuditVerifyReport>, ApiError> {
+    // Hold the lock so no append lands mid-walk
+    let log = state.audit_log.read().await;
+    let mut report = audit_log::verify(log.path())?;
+    report.last_write_error = log.last_error().map(str::to_string);
+    Ok(Json(report))
+}
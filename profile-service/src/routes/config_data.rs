@@ -1,77 +1,224 @@
 use crate::error::ApiError;
 use crate::store::AppState;
 use crate::types::{
-    BillingData, ConfigVersion, DetectionThresholds, OperationalDefaults, PromptTemplates,
+    BillingData, ConfigChannel, ConfigDiff, ConfigKind, ConfigRevision, ConfigRevisionMeta,
+    ConfigVersion, DetectionThresholds, OperationalDefaults, PromptTemplates,
 };
-use axum::extract::State;
+use axum::extract::{Path, Query, State};
 use axum::Json;
+use serde::Deserialize;
 use std::sync::Arc;
+use tracing::warn;
+
+/// Channel selection for config reads. An explicit `channel` wins;
+/// otherwise `room_id` resolves to the room's assigned channel.
+#[derive(Deserialize)]
+pub struct ChannelQuery {
+    pub channel: Option<ConfigChannel>,
+    pub room_id: Option<String>,
+}
+
+/// Target channel for config writes (default: stable)
+#[derive(Deserialize)]
+pub struct WriteChannelQuery {
+    #[serde(default)]
+    pub channel: ConfigChannel,
+}
+
+#[derive(Deserialize)]
+pub struct HistoryQuery {
+    pub kind: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct DiffQuery {
+    pub from: Option<u64>,
+    pub to: Option<u64>,
+}
+
+/// Resolve which channel a config read is for. An unknown room falls back
+/// to stable rather than failing, so a workstation whose room was deleted
+/// keeps receiving config.
+async fn resolve_channel(state: &AppState, query: &ChannelQuery) -> ConfigChannel {
+    if let Some(channel) = query.channel {
+        return channel;
+    }
+    let Some(room_id) = query.room_id.as_deref() else {
+        return ConfigChannel::Stable;
+    };
+    match state.rooms.read().await.get(room_id) {
+        Ok(room) => room.config_channel.unwrap_or_default(),
+        Err(_) => {
+            warn!(room_id, "Config requested for unknown room, serving stable");
+            ConfigChannel::Stable
+        }
+    }
+}
 
 pub async fn get_version(
     State(state): State<Arc<AppState>>,
+    Query(query): Query<ChannelQuery>,
 ) -> Result<Json<ConfigVersion>, ApiError> {
+    let channel = resolve_channel(&state, &query).await;
     let store = state.config_data.read().await;
-    Ok(Json(store.get_version()))
+    Ok(Json(store.get_version(channel)))
 }
 
 pub async fn get_prompts(
     State(state): State<Arc<AppState>>,
+    Query(query): Query<ChannelQuery>,
 ) -> Result<Json<PromptTemplates>, ApiError> {
+    let channel = resolve_channel(&state, &query).await;
     let store = state.config_data.read().await;
-    Ok(Json(store.get_prompts()))
+    Ok(Json(store.get_prompts(channel)))
 }
 
 pub async fn update_prompts(
     State(state): State<Arc<AppState>>,
+    Query(query): Query<WriteChannelQuery>,
     Json(req): Json<PromptTemplates>,
 ) -> Result<Json<PromptTemplates>, ApiError> {
     let mut store = state.config_data.write().await;
-    Ok(Json(store.update_prompts(req)?))
+    match query.channel {
+        ConfigChannel::Stable => Ok(Json(store.update_prompts(req)?)),
+        ConfigChannel::Candidate => Ok(Json(store.stage_prompts(req)?)),
+    }
 }
 
 pub async fn get_billing(
     State(state): State<Arc<AppState>>,
+    Query(query): Query<ChannelQuery>,
 ) -> Result<Json<BillingData>, ApiError> {
+    let channel = resolve_channel(&state, &query).await;
     let store = state.config_data.read().await;
-    Ok(Json(store.get_billing()))
+    Ok(Json(store.get_billing(channel)))
 }
 
 pub async fn update_billing(
     State(state): State<Arc<AppState>>,
+    Query(query): Query<WriteChannelQuery>,
     Json(req): Json<BillingData>,
 ) -> Result<Json<BillingData>, ApiError> {
     let mut store = state.config_data.write().await;
-    Ok(Json(store.update_billing(req)?))
+    match query.channel {
+        ConfigChannel::Stable => Ok(Json(store.update_billing(req)?)),
+        ConfigChannel::Candidate => Ok(Json(store.stage_billing(req)?)),
+    }
 }
 
 pub async fn get_thresholds(
     State(state): State<Arc<AppState>>,
+    Query(query): Query<ChannelQuery>,
 ) -> Result<Json<DetectionThresholds>, ApiError> {
+    let channel = resolve_channel(&state, &query).await;
     let store = state.config_data.read().await;
-    Ok(Json(store.get_thresholds()))
+    Ok(Json(store.get_thresholds(channel)))
 }
 
 pub async fn update_thresholds(
     State(state): State<Arc<AppState>>,
+    Query(query): Query<WriteChannelQuery>,
     Json(req): Json<DetectionThresholds>,
 ) -> Result<Json<DetectionThresholds>, ApiError> {
     let mut store = state.config_data.write().await;
-    Ok(Json(store.update_thresholds(req)?))
+    match query.channel {
+        ConfigChannel::Stable => Ok(Json(store.update_thresholds(req)?)),
+        ConfigChannel::Candidate => Ok(Json(store.stage_thresholds(req)?)),
+    }
 }
 
 pub async fn get_defaults(
     State(state): State<Arc<AppState>>,
+    Query(query): Query<ChannelQuery>,
 ) -> Result<Json<OperationalDefaults>, ApiError> {
+    let channel = resolve_channel(&state, &query).await;
     let store = state.config_data.read().await;
-    Ok(Json(store.get_defaults()))
+    Ok(Json(store.get_defaults(channel)))
 }
 
 pub async fn update_defaults(
     State(state): State<Arc<AppState>>,
+    Query(query): Query<WriteChannelQuery>,
     Json(req): Json<OperationalDefaults>,
 ) -> Result<Json<OperationalDefaults>, ApiError> {
     let mut store = state.config_data.write().await;
-    // `update_defaults` validates internally and returns ApiError::BadRequest
-    // on violation, which `IntoResponse` maps to 400.
-    Ok(Json(store.update_defaults(req)?))
+    // `update_defaults` / `stage_defaults` validate internally and return
+    // ApiError::BadRequest on violation, which `IntoResponse` maps to 400.
+    match query.channel {
+        ConfigChannel::Stable => Ok(Json(store.update_defaults(req)?)),
+        ConfigChannel::Candidate => Ok(Json(store.stage_defaults(req)?)),
+    }
+}
+
+/// GET /config/candidate — Kinds currently staged on the candidate channel.
+pub async fn get_candidate_kinds(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<ConfigKind>>, ApiError> {
+    let store = state.config_data.read().await;
+    Ok(Json(store.candidate_kinds()))
+}
+
+/// POST /config/:kind/promote — Move the staged candidate to stable.
+pub async fn promote(
+    State(state): State<Arc<AppState>>,
+    Path(kind): Path<String>,
+) -> Result<Json<ConfigRevision>, ApiError> {
+    let kind = ConfigKind::parse(&kind)?;
+    let mut store = state.config_data.write().await;
+    Ok(Json(store.promote(kind)?))
+}
+
+/// DELETE /config/:kind/candidate — Discard the staged candidate.
+pub async fn discard_candidate(
+    State(state): State<Arc<AppState>>,
+    Path(kind): Path<String>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let kind = ConfigKind::parse(&kind)?;
+    let mut store = state.config_data.write().await;
+    store.discard_candidate(kind)?;
+    Ok(Json(serde_json::json!({ "discarded": kind.as_str() })))
+}
+
+/// GET /config/history — All config revisions, newest first, optionally
+/// filtered by `?kind=prompts|billing|thresholds|defaults`.
+pub async fn get_history(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<HistoryQuery>,
+) -> Result<Json<Vec<ConfigRevisionMeta>>, ApiError> {
+    let kind = query.kind.as_deref().map(ConfigKind::parse).transpose()?;
+    let store = state.config_data.read().await;
+    Ok(Json(store.list_history(kind)))
+}
+
+/// GET /config/:kind/revisions/:n — One revision with its full content.
+pub async fn get_revision(
+    State(state): State<Arc<AppState>>,
+    Path((kind, revision)): Path<(String, u64)>,
+) -> Result<Json<ConfigRevision>, ApiError> {
+    let kind = ConfigKind::parse(&kind)?;
+    let store = state.config_data.read().await;
+    Ok(Json(store.get_revision(kind, revision)?))
+}
+
+/// GET /config/:kind/diff?from=N&to=M — Structural diff between two
+/// revisions. Defaults to the latest revision against the one before it.
+pub async fn get_diff(
+    State(state): State<Arc<AppState>>,
+    Path(kind): Path<String>,
+    Query(query): Query<DiffQuery>,
+) -> Result<Json<ConfigDiff>, ApiError> {
+    let kind = ConfigKind::parse(&kind)?;
+    let store = state.config_data.read().await;
+    Ok(Json(store.diff_revisions(kind, query.from, query.to)?))
+}
+
+/// POST /config/:kind/rollback/:n — Republish revision N as a new revision.
+/// Bumps the global config version like any other update.
+pub async fn rollback(
+    State(state): State<Arc<AppState>>,
+    Path((kind, revision)): Path<(String, u64)>,
+) -> Result<Json<ConfigRevision>, ApiError> {
+    let kind = ConfigKind::parse(&kind)?;
+    let mut store = state.config_data.write().await;
+    Ok(Json(store.rollback(kind, revision)?))
 }
@@ -8,8 +8,11 @@ pub async fn health(State(state): State<Arc<AppState>>) -> Json<Value> {
     let physicians = state.physicians.read().await.count();
     let rooms = state.rooms.read().await.count();
     let speakers = state.speakers.read().await.count();
+    // PHI routes refuse to serve while audit entries can't be written
+    let audit_error = state.audit_log.read().await.last_error().map(str::to_string);
     Json(json!({
-        "healthy": true,
+        "healthy": audit_error.is_none(),
+        "audit_error": audit_error,
         "service": "profile-service",
         "physicians": physicians,
         "rooms": rooms,
@@ -1,10 +1,19 @@
 use crate::error::ApiError;
-use crate::store::mobile_jobs::{MobileJob, MobileJobStore, UpdateJobRequest};
+use crate::store::mobile_jobs::{
+    ClaimJobRequest, HeartbeatRequest, JobEvent, MobileJob, MobileJobStore, UpdateJobRequest,
+    DEFAULT_LEASE_SECS, MAX_LEASE_SECS,
+};
 use crate::store::AppState;
 use axum::extract::{Multipart, Path, Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
 use axum::Json;
+use futures_util::stream::{self, Stream};
 use serde::Deserialize;
+use std::collections::VecDeque;
 use std::sync::Arc;
+use tokio::sync::broadcast::{self, error::RecvError};
 
 #[derive(Deserialize)]
 pub struct ListJobsQuery {
@@ -12,6 +21,11 @@ pub struct ListJobsQuery {
     pub status: Option<String>,
 }
 
+#[derive(Deserialize)]
+pub struct JobStreamQuery {
+    pub physician_id: Option<String>,
+}
+
 /// POST /mobile/upload — Upload audio + metadata, create a processing job.
 ///
 /// Accepts multipart/form-data with fields:
@@ -152,6 +166,160 @@ pub async fn list_jobs(
     Ok(Json(jobs))
 }
 
+/// POST /mobile/jobs/claim — Lease the oldest queued job to a worker.
+///
+/// Returns the job with its `lease`, or 204 when nothing is queued. The
+/// worker passes `lease.lease_id` on every status update and renews the
+/// lease via `/heartbeat` before `expires_at`; a lapsed lease is reclaimed
+/// by the next claim.
+pub async fn claim_job(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<ClaimJobRequest>,
+) -> Result<Response, ApiError> {
+    if req.worker_id.trim().is_empty() {
+        return Err(ApiError::BadRequest("worker_id must not be empty".into()));
+    }
+    let lease_secs = validate_lease_secs(req.lease_secs)?;
+    let mut store = state.mobile_jobs.write().await;
+    match store.claim_job(&req.worker_id, lease_secs, chrono::Utc::now())? {
+        Some(job) => Ok(Json(job).into_response()),
+        None => Ok(StatusCode::NO_CONTENT.into_response()),
+    }
+}
+
+/// POST /mobile/jobs/:job_id/heartbeat — Extend a held lease. 409 if the
+/// lease has lapsed or been reclaimed; the worker should stop processing.
+pub async fn heartbeat(
+    State(state): State<Arc<AppState>>,
+    Path(job_id): Path<String>,
+    Json(req): Json<HeartbeatRequest>,
+) -> Result<Json<MobileJob>, ApiError> {
+    MobileJobStore::validate_job_id(&job_id)?;
+    let lease_secs = validate_lease_secs(req.lease_secs)?;
+    let mut store = state.mobile_jobs.write().await;
+    let job = store.heartbeat(&job_id, &req.lease_id, lease_secs, chrono::Utc::now())?;
+    Ok(Json(job))
+}
+
+fn validate_lease_secs(lease_secs: Option<u64>) -> Result<u64, ApiError> {
+    match lease_secs.unwrap_or(DEFAULT_LEASE_SECS) {
+        secs @ 1..=MAX_LEASE_SECS => Ok(secs),
+        _ => Err(ApiError::BadRequest(format!(
+            "lease_secs must be between 1 and {MAX_LEASE_SECS}"
+        ))),
+    }
+}
+
+/// GET /mobile/jobs/stream — Server-sent job status events.
+///
+/// Each status change is sent as a `job` event whose id is the event's
+/// `event_id` and whose data is the `JobEvent` JSON. A reconnecting client
+/// sends `Last-Event-ID` and gets everything it missed replayed first; if
+/// its cursor has aged out of the retained log it gets a single `resync`
+/// event instead and should refetch `GET /mobile/jobs`. Without the header
+/// only new events are sent. `physician_id` narrows the stream to one
+/// physician's jobs.
+pub async fn stream_jobs(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<JobStreamQuery>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<Event, axum::Error>>>, ApiError> {
+    let last_event_id = headers
+        .get("last-event-id")
+        .map(|v| {
+            v.to_str()
+                .ok()
+                .and_then(|s| s.trim().parse::<u64>().ok())
+                .ok_or_else(|| ApiError::BadRequest("Invalid Last-Event-ID".into()))
+        })
+        .transpose()?;
+
+    // Subscribe and read the backlog under one lock so no event slips
+    // between the two
+    let job_stream = {
+        let store = state.mobile_jobs.read().await;
+        let mut job_stream = JobStream {
+            state: state.clone(),
+            rx: store.subscribe(),
+            cursor: store.latest_event_id(),
+            physician_id: query.physician_id,
+            pending: VecDeque::new(),
+        };
+        if let Some(cursor) = last_event_id {
+            job_stream.cursor = cursor;
+            job_stream.catch_up(&store);
+        }
+        job_stream
+    };
+
+    let events = stream::unfold(job_stream, |mut s| async move {
+        loop {
+            if let Some(event) = s.pending.pop_front() {
+                return Some((event, s));
+            }
+            match s.rx.recv().await {
+                Ok(event) => s.push(event),
+                Err(RecvError::Lagged(_)) => {
+                    let state = s.state.clone();
+                    let store = state.mobile_jobs.read().await;
+                    s.catch_up(&store);
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+/// Per-connection state behind `stream_jobs`.
+struct JobStream {
+    state: Arc<AppState>,
+    rx: broadcast::Receiver<JobEvent>,
+    /// Id of the last event this client has seen, whether or not it passed
+    /// the physician filter.
+    cursor: u64,
+    physician_id: Option<String>,
+    pending: VecDeque<Result<Event, axum::Error>>,
+}
+
+impl JobStream {
+    /// Queue everything after the cursor from the retained log, or a
+    /// `resync` if the cursor is outside it.
+    fn catch_up(&mut self, store: &MobileJobStore) {
+        match store.events_since(self.cursor) {
+            Some(events) => events.into_iter().for_each(|e| self.push(e)),
+            None => {
+                self.cursor = store.latest_event_id();
+                self.pending.push_back(
+                    Event::default()
+                        .event("resync")
+                        .id(self.cursor.to_string())
+                        .json_data(serde_json::json!({ "latest_event_id": self.cursor })),
+                );
+            }
+        }
+    }
+
+    fn push(&mut self, event: JobEvent) {
+        if event.event_id <= self.cursor {
+            return;
+        }
+        self.cursor = event.event_id;
+        if self
+            .physician_id
+            .as_ref()
+            .is_none_or(|pid| *pid == event.physician_id)
+        {
+            self.pending.push_back(
+                Event::default()
+                    .event("job")
+                    .id(event.event_id.to_string())
+                    .json_data(&event),
+            );
+        }
+    }
+}
+
 /// PUT /mobile/jobs/:job_id — Update a job's status (used by the processing CLI).
 pub async fn update_job(
     State(state): State<Arc<AppState>>,
@@ -1,3 +1,5 @@
+pub mod api_keys;
+pub mod audit;
 pub mod config_data;
 pub mod health;
 pub mod infrastructure;
@@ -9,9 +11,11 @@ pub mod physicians;
 pub mod rooms;
 pub mod sessions;
 pub mod speakers;
+pub mod uploads;
 
 use crate::store::AppState;
-use axum::routing::{get, post, put};
+use axum::extract::DefaultBodyLimit;
+use axum::routing::{delete, get, post, put};
 use axum::Router;
 use std::sync::Arc;
 
@@ -21,20 +25,37 @@ pub fn build_router(state: Arc<AppState>) -> Router {
         .route("/health", get(health::health))
         // Mobile — upload & job tracking
         .route("/mobile/upload", post(mobile::upload))
+        .route("/mobile/upload/resumable", post(uploads::create_mobile))
+        // Resumable uploads — chunks for both mobile recordings and session audio
+        .route(
+            "/uploads/:upload_id",
+            get(uploads::get).patch(uploads::append).layer(DefaultBodyLimit::max(
+                crate::store::uploads::MAX_CHUNK_BYTES,
+            )),
+        )
         .route(
             "/mobile/jobs",
             get(mobile::list_jobs),
         )
+        // Must be registered before /:job_id or they will be shadowed
+        .route("/mobile/jobs/stream", get(mobile::stream_jobs))
+        .route("/mobile/jobs/claim", post(mobile::claim_job))
         .route(
             "/mobile/jobs/:job_id",
             get(mobile::get_job)
                 .put(mobile::update_job)
                 .delete(mobile::delete_job),
         )
+        .route("/mobile/jobs/:job_id/heartbeat", post(mobile::heartbeat))
         .route(
             "/mobile/uploads/:job_id",
             get(mobile::download_audio),
         )
+        // API key registry (admin scope)
+        .route("/admin/keys", get(api_keys::list).post(api_keys::create))
+        .route("/admin/keys/:id/revoke", post(api_keys::revoke))
+        .route("/admin/audit", get(audit::query))
+        .route("/admin/audit/verify", get(audit::verify))
         // Infrastructure settings (singleton)
         .route("/infrastructure", get(infrastructure::get).put(infrastructure::update))
         // Server-configurable data (prompts, billing rules, detection thresholds)
@@ -43,6 +64,13 @@ pub fn build_router(state: Arc<AppState>) -> Router {
         .route("/config/billing", get(config_data::get_billing).put(config_data::update_billing))
         .route("/config/thresholds", get(config_data::get_thresholds).put(config_data::update_thresholds))
         .route("/config/defaults", get(config_data::get_defaults).put(config_data::update_defaults))
+        .route("/config/history", get(config_data::get_history))
+        .route("/config/candidate", get(config_data::get_candidate_kinds))
+        .route("/config/:kind/promote", post(config_data::promote))
+        .route("/config/:kind/candidate", delete(config_data::discard_candidate))
+        .route("/config/:kind/revisions/:n", get(config_data::get_revision))
+        .route("/config/:kind/diff", get(config_data::get_diff))
+        .route("/config/:kind/rollback/:n", post(config_data::rollback))
         // Physicians
         .route("/physicians", get(physicians::list).post(physicians::create))
         .route(
@@ -76,6 +104,7 @@ pub fn build_router(state: Arc<AppState>) -> Router {
             "/physicians/:id/sessions",
             get(sessions::list_sessions),
         )
+        .route("/physicians/:id/search", get(sessions::search))
         // Sessions — merge & renumber (collection-level, must be before :sid routes)
         .route(
             "/physicians/:id/sessions/merge",
@@ -120,6 +149,10 @@ pub fn build_router(state: Arc<AppState>) -> Router {
             "/physicians/:physician_id/sessions/:session_id/audio",
             post(sessions::upload_audio).get(sessions::download_audio),
         )
+        .route(
+            "/physicians/:physician_id/sessions/:session_id/audio/resumable",
+            post(uploads::create_session_audio),
+        )
         // Session auxiliary files
         .route(
             "/physicians/:physician_id/sessions/:session_id/files/:filename",
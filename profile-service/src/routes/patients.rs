@@ -36,6 +36,7 @@ pub async fn confirm(
         &req.dob,
         &req.session_id,
         req.medplum_patient_id,
+        req.session_date.as_deref(),
     )?;
     info!(
         event = "patient_confirmed",
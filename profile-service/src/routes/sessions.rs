@@ -1,8 +1,9 @@
 use crate::error::ApiError;
-use crate::store::AppState;
+use crate::store::{search_index, AppState};
 use crate::types::{
-    ArchiveDetails, ArchiveSummary, MergeSessionsRequest, RenumberRequest, SessionFeedback,
-    SplitSessionRequest, UpdatePatientNameRequest, UpdateSoapRequest, UploadSessionRequest,
+    ArchiveDetails, ArchiveSummary, MergeSessionsRequest, RenumberRequest, SearchHit,
+    SessionFeedback, SplitSessionRequest, UpdatePatientNameRequest, UpdateSoapRequest,
+    UploadSessionRequest,
 };
 use axum::extract::{Multipart, Path, Query, State};
 use axum::Json;
@@ -22,6 +23,17 @@ pub struct DateRangeQuery {
     pub to: Option<String>,
 }
 
+#[derive(Deserialize)]
+pub struct SearchQuery {
+    pub q: String,
+    #[serde(default)]
+    pub from: Option<String>,
+    #[serde(default)]
+    pub to: Option<String>,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
 pub async fn list_dates(
     State(state): State<Arc<AppState>>,
     Path(physician_id): Path<String>,
@@ -43,6 +55,27 @@ pub async fn list_sessions(
     Ok(Json(sessions))
 }
 
+/// GET /physicians/:id/search?q=&from=&to=&limit= — Ranked full-text
+/// search over transcripts and SOAP notes.
+pub async fn search(
+    State(state): State<Arc<AppState>>,
+    Path(physician_id): Path<String>,
+    Query(q): Query<SearchQuery>,
+) -> Result<Json<Vec<SearchHit>>, ApiError> {
+    if q.q.trim().is_empty() {
+        return Err(ApiError::BadRequest("Query must not be empty".to_string()));
+    }
+    let limit = q
+        .limit
+        .unwrap_or(search_index::DEFAULT_LIMIT)
+        .clamp(1, search_index::MAX_LIMIT);
+    let hits = state
+        .sessions
+        .search(&physician_id, &q.q, q.from.as_deref(), q.to.as_deref(), limit)
+        .await?;
+    Ok(Json(hits))
+}
+
 pub async fn get_session(
     State(state): State<Arc<AppState>>,
     Path((physician_id, session_id)): Path<(String, String)>,
@@ -208,13 +241,10 @@ pub async fn download_audio(
     State(state): State<Arc<AppState>>,
     Path((physician_id, session_id)): Path<(String, String)>,
 ) -> Result<(axum::http::HeaderMap, Vec<u8>), ApiError> {
-    let path = state
+    let data = state
         .sessions
-        .get_audio_path(&physician_id, &session_id)
+        .get_audio(&physician_id, &session_id)
         .await?;
-    let data = tokio::fs::read(&path)
-        .await
-        .map_err(|e| ApiError::Internal(format!("Failed to read audio: {e}")))?;
     let mut headers = axum::http::HeaderMap::new();
     headers.insert("content-type", "audio/wav".parse().unwrap());
     headers.insert(
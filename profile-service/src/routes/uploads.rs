@@ -0,0 +1,174 @@
+use crate::error::ApiError;
+use crate::store::uploads::{self, validate_upload_id, Upload, UploadTarget};
+use crate::store::AppState;
+use crate::types::Caller;
+use axum::body::Bytes;
+use axum::extract::{Extension, Path, State};
+use axum::http::HeaderMap;
+use axum::Json;
+use serde::Deserialize;
+use std::sync::Arc;
+
+#[derive(Deserialize)]
+pub struct CreateUploadRequest {
+    /// Total file size in bytes
+    pub length: u64,
+    /// Hex SHA-256 of the whole file
+    pub sha256: String,
+}
+
+#[derive(Deserialize)]
+pub struct CreateMobileUploadRequest {
+    pub physician_id: String,
+    pub recording_id: String,
+    pub started_at: String,
+    pub duration_ms: u64,
+    #[serde(default)]
+    pub device_info: Option<String>,
+    pub length: u64,
+    pub sha256: String,
+}
+
+/// POST /physicians/:physician_id/sessions/:session_id/audio/resumable —
+/// Start (or look up) a resumable upload of a session's audio.
+pub async fn create_session_audio(
+    State(state): State<Arc<AppState>>,
+    Extension(caller): Extension<Caller>,
+    Path((physician_id, session_id)): Path<(String, String)>,
+    Json(req): Json<CreateUploadRequest>,
+) -> Result<Json<Upload>, ApiError> {
+    state
+        .sessions
+        .require_session(&physician_id, &session_id)
+        .await?;
+    let target = UploadTarget::SessionAudio {
+        physician_id,
+        session_id,
+    };
+    let upload = state
+        .uploads
+        .write()
+        .await
+        .create(target, req.length, &req.sha256, &caller)?;
+    Ok(Json(upload))
+}
+
+/// POST /mobile/upload/resumable — Start (or look up) a resumable upload of
+/// a mobile recording. Takes the same metadata as `/mobile/upload`; the job
+/// is created once the last chunk lands.
+pub async fn create_mobile(
+    State(state): State<Arc<AppState>>,
+    Extension(caller): Extension<Caller>,
+    Json(req): Json<CreateMobileUploadRequest>,
+) -> Result<Json<Upload>, ApiError> {
+    state.physicians.read().await.get(&req.physician_id)?;
+    let target = UploadTarget::MobileRecording {
+        physician_id: req.physician_id,
+        recording_id: req.recording_id,
+        started_at: req.started_at,
+        duration_ms: req.duration_ms,
+        device_info: req.device_info,
+    };
+    let upload = state
+        .uploads
+        .write()
+        .await
+        .create(target, req.length, &req.sha256, &caller)?;
+    Ok(Json(upload))
+}
+
+/// GET /uploads/:upload_id — Current offset, for resuming after a dropped
+/// connection or restart. Registry keys only see uploads they started.
+pub async fn get(
+    State(state): State<Arc<AppState>>,
+    Extension(caller): Extension<Caller>,
+    Path(upload_id): Path<String>,
+) -> Result<Json<Upload>, ApiError> {
+    validate_upload_id(&upload_id)?;
+    let upload = state.uploads.read().await.get(&upload_id)?;
+    upload.check_caller(&caller)?;
+    Ok(Json(upload))
+}
+
+/// PATCH /uploads/:upload_id — Append the request body at the offset in the
+/// `Upload-Offset` header.
+///
+/// The offset must match the upload's; on a 409 the client refetches it with
+/// GET and resumes from there. The chunk that completes the file is checked
+/// against the declared SHA-256 (400 and a restart from zero on mismatch)
+/// and handed to the target before responding with `completed_at` set. An
+/// empty PATCH at the full length retries a hand-off that failed. Registry
+/// keys may only append to uploads they started.
+pub async fn append(
+    State(state): State<Arc<AppState>>,
+    Extension(caller): Extension<Caller>,
+    Path(upload_id): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<Upload>, ApiError> {
+    validate_upload_id(&upload_id)?;
+    let offset = headers
+        .get("upload-offset")
+        .ok_or_else(|| ApiError::BadRequest("Missing Upload-Offset header".into()))?
+        .to_str()
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .ok_or_else(|| ApiError::BadRequest("Invalid Upload-Offset header".into()))?;
+
+    // The upload's lock is held through the hand-off so a retried final
+    // chunk can't race it
+    let (upload, _part) =
+        uploads::append(&state.uploads, &upload_id, offset, body, &caller).await?;
+    if !upload.needs_finalize() {
+        return Ok(Json(upload));
+    }
+    let part = state.uploads.read().await.part_path(&upload_id);
+    let job_id = finalize(&state, &upload, &part).await?;
+    let completed = state.uploads.write().await.complete(&upload_id, job_id)?;
+    Ok(Json(completed))
+}
+
+/// Hand a fully received file to its target. Returns the created job ID for
+/// mobile recordings.
+async fn finalize(
+    state: &AppState,
+    upload: &Upload,
+    part: &std::path::Path,
+) -> Result<Option<String>, ApiError> {
+    match &upload.target {
+        UploadTarget::SessionAudio {
+            physician_id,
+            session_id,
+        } => {
+            state
+                .sessions
+                .save_audio_file(physician_id, session_id, part)
+                .await?;
+            Ok(None)
+        }
+        UploadTarget::MobileRecording {
+            physician_id,
+            recording_id,
+            started_at,
+            duration_ms,
+            device_info,
+        } => {
+            let mut jobs = state.mobile_jobs.write().await;
+            // Idempotent on recording_id, like /mobile/upload
+            let job = jobs.create_job(
+                physician_id.clone(),
+                recording_id.clone(),
+                started_at.clone(),
+                *duration_ms,
+                device_info.clone(),
+            )?;
+            let audio_path = jobs.upload_path(&job.job_id);
+            if !audio_path.exists() {
+                tokio::fs::rename(part, &audio_path)
+                    .await
+                    .map_err(|e| ApiError::Internal(format!("Failed to save audio: {e}")))?;
+            }
+            Ok(Some(job.job_id))
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use crate::error::ApiError;
+use crate::types::{ApiKey, CreateApiKeyRequest, CreatedApiKey};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// `last_used_at` resolution. Refreshing it at most once a minute keeps busy
+/// keys from rewriting the registry on every request.
+const LAST_USED_PERSIST_SECS: i64 = 60;
+
+/// Characters of the secret kept for display (`psk_` + 8)
+const KEY_PREFIX_LEN: usize = 12;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredApiKey {
+    #[serde(flatten)]
+    key: ApiKey,
+    /// Hex SHA-256 of the secret
+    key_hash: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ApiKeyStore {
+    schema_version: u32,
+    keys: Vec<StoredApiKey>,
+}
+
+impl Default for ApiKeyStore {
+    fn default() -> Self {
+        Self {
+            schema_version: 1,
+            keys: Vec::new(),
+        }
+    }
+}
+
+/// Registry of named, scoped API keys (`api_keys.json`).
+///
+/// Runs alongside the single `--api-key` / `PROFILE_API_KEY` secret, which
+/// keeps full access so existing deployments work unchanged.
+pub struct ApiKeyManager {
+    store: ApiKeyStore,
+    path: PathBuf,
+    /// Bumped on every change so snapshots written out of order never
+    /// replace a newer file.
+    version: u64,
+    /// Version of the last snapshot written; also serializes writers.
+    persisted: Arc<Mutex<u64>>,
+}
+
+/// Serialized registry waiting to be written, so the write can happen
+/// after the registry lock is released.
+pub struct PendingSave {
+    content: String,
+    path: PathBuf,
+    version: u64,
+    persisted: Arc<Mutex<u64>>,
+}
+
+impl PendingSave {
+    /// Write the snapshot unless a newer one is already on disk. Blocking.
+    pub fn write(self) -> Result<(), ApiError> {
+        let mut persisted = self.persisted.lock().unwrap_or_else(|e| e.into_inner());
+        if *persisted >= self.version {
+            return Ok(());
+        }
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| ApiError::Internal(format!("Failed to create directory: {e}")))?;
+        }
+        let temp_path = self.path.with_extension("json.tmp");
+        std::fs::write(&temp_path, &self.content)
+            .map_err(|e| ApiError::Internal(format!("Failed to write temp file: {e}")))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let _ = std::fs::set_permissions(&temp_path, std::fs::Permissions::from_mode(0o600));
+        }
+        std::fs::rename(&temp_path, &self.path)
+            .map_err(|e| ApiError::Internal(format!("Failed to rename: {e}")))?;
+        *persisted = self.version;
+        Ok(())
+    }
+}
+
+impl ApiKeyManager {
+    pub fn load(path: PathBuf) -> Result<Self, ApiError> {
+        let store = if path.exists() {
+            let content = std::fs::read_to_string(&path)
+                .map_err(|e| ApiError::Internal(format!("Failed to read API keys: {e}")))?;
+            serde_json::from_str(&content)
+                .map_err(|e| ApiError::Internal(format!("Failed to parse API keys: {e}")))?
+        } else {
+            ApiKeyStore::default()
+        };
+        info!(count = store.keys.len(), "Loaded API keys");
+        Ok(Self {
+            store,
+            path,
+            version: 0,
+            persisted: Arc::new(Mutex::new(0)),
+        })
+    }
+
+    /// Record a change and snapshot the registry for writing.
+    fn snapshot(&mut self) -> Result<PendingSave, ApiError> {
+        self.version += 1;
+        let content = serde_json::to_string_pretty(&self.store)
+            .map_err(|e| ApiError::Internal(format!("Failed to serialize: {e}")))?;
+        Ok(PendingSave {
+            content,
+            path: self.path.clone(),
+            version: self.version,
+            persisted: self.persisted.clone(),
+        })
+    }
+
+    fn save(&mut self) -> Result<(), ApiError> {
+        self.snapshot()?.write()
+    }
+
+    fn hash_secret(secret: &str) -> String {
+        Sha256::digest(secret.as_bytes())
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect()
+    }
+
+    pub fn list(&self) -> Vec<ApiKey> {
+        self.store.keys.iter().map(|k| k.key.clone()).collect()
+    }
+
+    /// True once any unrevoked key exists. From then on requests need a key
+    /// even when no legacy key is configured.
+    pub fn has_active_keys(&self) -> bool {
+        self.store.keys.iter().any(|k| k.key.revoked_at.is_none())
+    }
+
+    /// Register a key and return it with its secret (shown once).
+    pub fn create(&mut self, req: CreateApiKeyRequest) -> Result<CreatedApiKey, ApiError> {
+        let secret = format!(
+            "psk_{}{}",
+            Uuid::new_v4().simple(),
+            Uuid::new_v4().simple()
+        );
+        let key = ApiKey {
+            id: Uuid::new_v4().to_string(),
+            name: req.name,
+            scopes: req.scopes,
+            key_prefix: secret[..KEY_PREFIX_LEN].to_string(),
+            created_at: Utc::now().to_rfc3339(),
+            last_used_at: None,
+            revoked_at: None,
+        };
+        self.store.keys.push(StoredApiKey {
+            key: key.clone(),
+            key_hash: Self::hash_secret(&secret),
+        });
+        self.save()?;
+        info!(id = %key.id, name = %key.name, "Created API key");
+        Ok(CreatedApiKey { key, secret })
+    }
+
+    /// Revoke a key. The record stays for auditing; revoking twice is a no-op.
+    pub fn revoke(&mut self, id: &str) -> Result<ApiKey, ApiError> {
+        let stored = self
+            .store
+            .keys
+            .iter_mut()
+            .find(|k| k.key.id == id)
+            .ok_or_else(|| ApiError::NotFound(format!("API key not found: {id}")))?;
+        if stored.key.revoked_at.is_none() {
+            stored.key.revoked_at = Some(Utc::now().to_rfc3339());
+        }
+        let revoked = stored.key.clone();
+        self.save()?;
+        info!(id = %id, "Revoked API key");
+        Ok(revoked)
+    }
+
+    /// Look up the key for a presented secret. Revoked keys are returned
+    /// too so the caller can tell "revoked" from "unknown"; check
+    /// [`ApiKey::allows`] before granting access.
+    pub fn authenticate(&self, secret: &str) -> Option<ApiKey> {
+        let hash = Self::hash_secret(secret);
+        self.store
+            .keys
+            .iter()
+            .find(|k| k.key_hash == hash)
+            .map(|k| k.key.clone())
+    }
+
+    /// True when `key` was last stamped more than [`LAST_USED_PERSIST_SECS`]
+    /// ago, so [`Self::record_use`] is worth taking the write lock for.
+    pub fn last_use_is_stale(key: &ApiKey) -> bool {
+        key.revoked_at.is_none()
+            && key
+                .last_used_at
+                .as_deref()
+                .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+                .is_none_or(|prev| {
+                    (Utc::now() - prev.with_timezone(&Utc)).num_seconds()
+                        >= LAST_USED_PERSIST_SECS
+                })
+    }
+
+    /// Stamp a key's last use in memory. Returns the registry snapshot to
+    /// write, or `None` when another request already stamped it.
+    pub fn record_use(&mut self, id: &str) -> Option<PendingSave> {
+        let stored = self.store.keys.iter_mut().find(|k| k.key.id == id)?;
+        if !Self::last_use_is_stale(&stored.key) {
+            return None;
+        }
+        stored.key.last_used_at = Some(Utc::now().to_rfc3339());
+        self.snapshot()
+            .map_err(|e| warn!("Failed to snapshot API keys after use: {e}"))
+            .ok()
+    }
+}
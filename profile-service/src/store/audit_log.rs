@@ -0,0 +1,245 @@
+use crate::error::ApiError;
+use crate::types::{AuditEntry, AuditQuery, AuditRecord, AuditVerifyReport, Caller};
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+/// `prev_hash` of the first entry
+pub const GENESIS_HASH: &str =
+    "0000000000000000000000000000000000000000000000000000000000000000";
+
+const DEFAULT_QUERY_LIMIT: usize = 500;
+
+/// Location of the audit log under a data directory
+pub fn log_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("audit").join("audit_log.jsonl")
+}
+
+/// Append-only, hash-chained PHI access log (`audit/audit_log.jsonl`).
+///
+/// One JSON entry per line. Entries are only ever appended; each carries
+/// the previous entry's hash, so [`verify`] detects any edit, insertion or
+/// deletion short of rewriting the whole tail.
+pub struct AuditLog {
+    path: PathBuf,
+    next_seq: u64,
+    last_hash: String,
+    /// Why the last append failed; cleared by the next one that lands
+    last_error: Option<String>,
+}
+
+impl AuditLog {
+    pub fn load(path: PathBuf) -> Result<Self, ApiError> {
+        let (next_seq, last_hash) = if path.exists() {
+            let content = std::fs::read_to_string(&path)
+                .map_err(|e| ApiError::Internal(format!("Failed to read audit log: {e}")))?;
+            match content.lines().rev().find(|l| !l.trim().is_empty()) {
+                Some(line) => {
+                    let last: AuditEntry = serde_json::from_str(line).map_err(|e| {
+                        ApiError::Internal(format!("Failed to parse last audit entry: {e}"))
+                    })?;
+                    (last.record.seq + 1, last.hash)
+                }
+                None => (1, GENESIS_HASH.to_string()),
+            }
+        } else {
+            (1, GENESIS_HASH.to_string())
+        };
+        info!(entries = next_seq - 1, "Loaded audit log");
+        Ok(Self {
+            path,
+            next_seq,
+            last_hash,
+            last_error: None,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Why the last append failed, while the log is failing
+    pub fn last_error(&self) -> Option<&str> {
+        self.last_error.as_deref()
+    }
+
+    /// Fail fast while appends are failing: re-open the file, and clear the
+    /// error once that works again.
+    pub fn check_writable(&mut self) -> Result<(), ApiError> {
+        if self.last_error.is_none() {
+            return Ok(());
+        }
+        self.open().map(|_| self.last_error = None)
+    }
+
+    /// Chain and append one record. `seq` is assigned here; whatever the
+    /// caller put in it is overwritten.
+    pub fn append(&mut self, record: AuditRecord) -> Result<AuditEntry, ApiError> {
+        let result = self.write_entry(record);
+        self.last_error = result.as_ref().err().map(|e| e.to_string());
+        result
+    }
+
+    fn write_entry(&mut self, mut record: AuditRecord) -> Result<AuditEntry, ApiError> {
+        record.seq = self.next_seq;
+        let hash = entry_hash(&self.last_hash, &record)?;
+        let entry = AuditEntry {
+            record,
+            prev_hash: self.last_hash.clone(),
+            hash,
+        };
+        let line = serde_json::to_string(&entry)
+            .map_err(|e| ApiError::Internal(format!("Failed to serialize: {e}")))?;
+
+        let mut file = self.open()?;
+        writeln!(file, "{line}")
+            .and_then(|_| file.sync_data())
+            .map_err(|e| ApiError::Internal(format!("Failed to write audit log: {e}")))?;
+
+        self.next_seq += 1;
+        self.last_hash = entry.hash.clone();
+        Ok(entry)
+    }
+
+    fn open(&self) -> Result<std::fs::File, ApiError> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| ApiError::Internal(format!("Failed to create directory: {e}")))?;
+        }
+        let mut options = std::fs::OpenOptions::new();
+        options.create(true).append(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        options
+            .open(&self.path)
+            .map_err(|e| ApiError::Internal(format!("Failed to open audit log: {e}")))
+    }
+
+    /// Matching entries, oldest first, capped to the most recent `limit`.
+    pub fn query(&self, q: &AuditQuery) -> Result<Vec<AuditEntry>, ApiError> {
+        let since = parse_bound(q.since.as_deref(), "since")?;
+        let until = parse_bound(q.until.as_deref(), "until")?;
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let content = std::fs::read_to_string(&self.path)
+            .map_err(|e| ApiError::Internal(format!("Failed to read audit log: {e}")))?;
+
+        let mut matches: Vec<AuditEntry> = content
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .filter_map(|l| serde_json::from_str::<AuditEntry>(l).ok())
+            .filter(|e| {
+                let r = &e.record;
+                let ts = DateTime::parse_from_rfc3339(&r.timestamp).ok();
+                eq_opt(&q.physician_id, &r.physician_id)
+                    && eq_opt(&q.session_id, &r.session_id)
+                    && eq_opt(&q.patient_id, &r.patient_id)
+                    && q.caller.as_deref().is_none_or(|c| caller_matches(&r.caller, c))
+                    && q.outcome.is_none_or(|o| o == r.outcome)
+                    && since.is_none_or(|s| ts.is_some_and(|t| t >= s))
+                    && until.is_none_or(|u| ts.is_some_and(|t| t < u))
+            })
+            .collect();
+
+        let limit = q.limit.unwrap_or(DEFAULT_QUERY_LIMIT);
+        if matches.len() > limit {
+            matches.drain(..matches.len() - limit);
+        }
+        Ok(matches)
+    }
+}
+
+fn entry_hash(prev_hash: &str, record: &AuditRecord) -> Result<String, ApiError> {
+    let body = serde_json::to_string(record)
+        .map_err(|e| ApiError::Internal(format!("Failed to serialize: {e}")))?;
+    let mut hasher = Sha256::new();
+    hasher.update(prev_hash.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(body.as_bytes());
+    Ok(hasher.finalize().iter().map(|b| format!("{b:02x}")).collect())
+}
+
+fn eq_opt(filter: &Option<String>, value: &Option<String>) -> bool {
+    filter.is_none() || filter == value
+}
+
+fn caller_matches(caller: &Caller, filter: &str) -> bool {
+    match caller {
+        Caller::Anonymous => filter == "anonymous",
+        Caller::LegacyKey => filter == "legacy",
+        Caller::Key { id, .. } => filter == id,
+    }
+}
+
+fn parse_bound(value: Option<&str>, label: &str) -> Result<Option<DateTime<Utc>>, ApiError> {
+    value
+        .map(|v| {
+            DateTime::parse_from_rfc3339(v)
+                .map(|t| t.with_timezone(&Utc))
+                .map_err(|e| ApiError::BadRequest(format!("Invalid {label} timestamp: {e}")))
+        })
+        .transpose()
+}
+
+/// Walk the chain from the genesis hash and report the first entry whose
+/// sequence, link or hash doesn't check out.
+pub fn verify(path: &Path) -> Result<AuditVerifyReport, ApiError> {
+    let content = if path.exists() {
+        std::fs::read_to_string(path)
+            .map_err(|e| ApiError::Internal(format!("Failed to read audit log: {e}")))?
+    } else {
+        String::new()
+    };
+
+    let mut prev_hash = GENESIS_HASH.to_string();
+    let mut entries = 0u64;
+    let broken = |line: usize, entries: u64, reason: String| AuditVerifyReport {
+        valid: false,
+        entries,
+        head_hash: None,
+        broken_at_line: Some(line),
+        reason: Some(reason),
+        last_write_error: None,
+    };
+
+    for (i, line) in content.lines().enumerate() {
+        let line_no = i + 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry: AuditEntry = match serde_json::from_str(line) {
+            Ok(e) => e,
+            Err(e) => return Ok(broken(line_no, entries, format!("Unparseable entry: {e}"))),
+        };
+        if entry.record.seq != entries + 1 {
+            return Ok(broken(
+                line_no,
+                entries,
+                format!("Expected seq {}, found {}", entries + 1, entry.record.seq),
+            ));
+        }
+        if entry.prev_hash != prev_hash {
+            return Ok(broken(line_no, entries, "prev_hash does not match previous entry".into()));
+        }
+        if entry_hash(&prev_hash, &entry.record)? != entry.hash {
+            return Ok(broken(line_no, entries, "Entry hash mismatch (contents altered)".into()));
+        }
+        prev_hash = entry.hash;
+        entries += 1;
+    }
+
+    Ok(AuditVerifyReport {
+        valid: true,
+        entries,
+        head_hash: (entries > 0).then_some(prev_hash),
+        broken_at_line: None,
+        reason: None,
+        last_write_error: None,
+    })
+}
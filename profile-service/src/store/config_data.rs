@@ -1,8 +1,11 @@
 use crate::error::ApiError;
 use crate::types::{
-    BillingData, ConfigVersion, DetectionThresholds, OperationalDefaults, PromptTemplates,
+    BillingData, ConfigChange, ConfigChangeOp, ConfigDiff, ConfigKind, ConfigRevision,
+    ConfigChannel, ConfigRevisionMeta, ConfigRevisionSource, ConfigVersion, DetectionThresholds,
+    OperationalDefaults, PromptTemplates,
 };
 use chrono::Utc;
+use serde_json::Value;
 use std::path::PathBuf;
 use tracing::info;
 
@@ -10,6 +13,15 @@ use tracing::info;
 /// detection thresholds, and operational defaults. Each category persists
 /// to its own JSON file. A shared version counter bumps on any update for
 /// client staleness checks.
+///
+/// Every update is also kept as an immutable revision under
+/// `config_history/<kind>/<n>.json`, indexed by `config_history/index.json`.
+/// A rollback republishes an old revision's content as a new revision, so
+/// the version counter only ever moves forward.
+///
+/// Config can also be staged on the candidate channel (`config_candidate.json`)
+/// for rooms assigned to it, then promoted to stable. Candidate changes bump
+/// the same version counter so candidate rooms notice them.
 pub struct ConfigDataStore {
     prompts: PromptTemplates,
     billing: BillingData,
@@ -22,6 +34,23 @@ pub struct ConfigDataStore {
     thresholds_path: PathBuf,
     defaults_path: PathBuf,
     version_path: PathBuf,
+    history: Vec<ConfigRevisionMeta>,
+    history_dir: PathBuf,
+    candidate: CandidateConfig,
+    candidate_path: PathBuf,
+}
+
+/// Staged config per kind; None = candidate rooms use stable
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+struct CandidateConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    prompts: Option<PromptTemplates>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    billing: Option<BillingData>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    thresholds: Option<DetectionThresholds>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    defaults: Option<OperationalDefaults>,
 }
 
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -37,6 +66,8 @@ impl ConfigDataStore {
         let thresholds_path = data_dir.join("detection_thresholds.json");
         let defaults_path = data_dir.join("operational_defaults.json");
         let version_path = data_dir.join("config_version.json");
+        let history_dir = data_dir.join("config_history");
+        let candidate_path = data_dir.join("config_candidate.json");
 
         let prompts = Self::load_or_default::<PromptTemplates>(&prompts_path, "prompts")?;
         let billing = Self::load_or_default::<BillingData>(&billing_path, "billing")?;
@@ -44,6 +75,7 @@ impl ConfigDataStore {
             Self::load_or_default::<DetectionThresholds>(&thresholds_path, "thresholds")?;
         let defaults =
             Self::load_or_default::<OperationalDefaults>(&defaults_path, "operational defaults")?;
+        let candidate = Self::load_or_default::<CandidateConfig>(&candidate_path, "candidate")?;
 
         let (version, updated_at) = if version_path.exists() {
             let content = std::fs::read_to_string(&version_path)
@@ -56,7 +88,17 @@ impl ConfigDataStore {
             (1, now)
         };
 
-        let store = Self {
+        let index_path = history_dir.join("index.json");
+        let history = if index_path.exists() {
+            let content = std::fs::read_to_string(&index_path)
+                .map_err(|e| ApiError::Internal(format!("Failed to read config history: {e}")))?;
+            serde_json::from_str(&content)
+                .map_err(|e| ApiError::Internal(format!("Failed to parse config history: {e}")))?
+        } else {
+            Vec::new()
+        };
+
+        let mut store = Self {
             prompts,
             billing,
             thresholds,
@@ -68,6 +110,10 @@ impl ConfigDataStore {
             thresholds_path,
             defaults_path,
             version_path,
+            history,
+            history_dir,
+            candidate,
+            candidate_path,
         };
 
         // Only write files that didn't exist on disk (first-run seeding)
@@ -81,6 +127,15 @@ impl ConfigDataStore {
             })?;
         }
 
+        // Seed a base revision for any kind without history (first run, or
+        // upgrading a deployment that predates revision tracking)
+        for kind in ConfigKind::ALL {
+            if store.latest_revision(kind).is_none() {
+                let data = store.current_value(kind)?;
+                store.record_revision(kind, data, ConfigRevisionSource::Initial, None)?;
+            }
+        }
+
         info!(
             version = store.version,
             "Loaded config data (prompts, billing, thresholds, operational defaults)"
@@ -130,90 +185,393 @@ impl ConfigDataStore {
         self.updated_at = Utc::now().to_rfc3339();
     }
 
+    fn save_version(&self) -> Result<(), ApiError> {
+        Self::save_json(
+            &self.version_path,
+            &VersionFile {
+                version: self.version,
+                updated_at: self.updated_at.clone(),
+            },
+        )
+    }
+
+    fn to_value<T: serde::Serialize>(data: &T) -> Result<Value, ApiError> {
+        serde_json::to_value(data)
+            .map_err(|e| ApiError::Internal(format!("Failed to serialize: {e}")))
+    }
+
+    fn current_value(&self, kind: ConfigKind) -> Result<Value, ApiError> {
+        match kind {
+            ConfigKind::Prompts => Self::to_value(&self.prompts),
+            ConfigKind::Billing => Self::to_value(&self.billing),
+            ConfigKind::Thresholds => Self::to_value(&self.thresholds),
+            ConfigKind::Defaults => Self::to_value(&self.defaults),
+        }
+    }
+
+    fn revision_path(&self, kind: ConfigKind, revision: u64) -> PathBuf {
+        self.history_dir
+            .join(kind.as_str())
+            .join(format!("{revision}.json"))
+    }
+
+    fn latest_revision(&self, kind: ConfigKind) -> Option<u64> {
+        self.history
+            .iter()
+            .filter(|m| m.kind == kind)
+            .map(|m| m.revision)
+            .max()
+    }
+
+    /// Append a revision for `kind` at the current version. The revision
+    /// file is written before the index so a crash never indexes a missing file.
+    fn record_revision(
+        &mut self,
+        kind: ConfigKind,
+        data: Value,
+        source: ConfigRevisionSource,
+        rolled_back_from: Option<u64>,
+    ) -> Result<ConfigRevision, ApiError> {
+        let revision = ConfigRevision {
+            meta: ConfigRevisionMeta {
+                kind,
+                revision: self.latest_revision(kind).unwrap_or(0) + 1,
+                version: self.version,
+                created_at: self.updated_at.clone(),
+                source,
+                rolled_back_from,
+            },
+            data,
+        };
+        Self::save_json(&self.revision_path(kind, revision.meta.revision), &revision)?;
+        self.history.push(revision.meta.clone());
+        Self::save_json(&self.history_dir.join("index.json"), &self.history)?;
+        Ok(revision)
+    }
+
     // ── Getters ──
 
-    pub fn get_version(&self) -> ConfigVersion {
+    pub fn get_version(&self, channel: ConfigChannel) -> ConfigVersion {
         ConfigVersion {
             version: self.version,
             updated_at: self.updated_at.clone(),
+            channel,
         }
     }
 
-    pub fn get_prompts(&self) -> PromptTemplates {
-        self.prompts.clone()
+    pub fn get_prompts(&self, channel: ConfigChannel) -> PromptTemplates {
+        match (channel, &self.candidate.prompts) {
+            (ConfigChannel::Candidate, Some(staged)) => staged.clone(),
+            _ => self.prompts.clone(),
+        }
     }
 
-    pub fn get_billing(&self) -> BillingData {
-        self.billing.clone()
+    pub fn get_billing(&self, channel: ConfigChannel) -> BillingData {
+        match (channel, &self.candidate.billing) {
+            (ConfigChannel::Candidate, Some(staged)) => staged.clone(),
+            _ => self.billing.clone(),
+        }
     }
 
-    pub fn get_thresholds(&self) -> DetectionThresholds {
-        self.thresholds.clone()
+    pub fn get_thresholds(&self, channel: ConfigChannel) -> DetectionThresholds {
+        match (channel, &self.candidate.thresholds) {
+            (ConfigChannel::Candidate, Some(staged)) => staged.clone(),
+            _ => self.thresholds.clone(),
+        }
     }
 
-    pub fn get_defaults(&self) -> OperationalDefaults {
-        self.defaults.clone()
+    pub fn get_defaults(&self, channel: ConfigChannel) -> OperationalDefaults {
+        match (channel, &self.candidate.defaults) {
+            (ConfigChannel::Candidate, Some(staged)) => staged.clone(),
+            _ => self.defaults.clone(),
+        }
     }
 
-    // ── Updaters (full replace) ──
+    /// Kinds with config staged on the candidate channel
+    pub fn candidate_kinds(&self) -> Vec<ConfigKind> {
+        let c = &self.candidate;
+        ConfigKind::ALL
+            .into_iter()
+            .filter(|k| match k {
+                ConfigKind::Prompts => c.prompts.is_some(),
+                ConfigKind::Billing => c.billing.is_some(),
+                ConfigKind::Thresholds => c.thresholds.is_some(),
+                ConfigKind::Defaults => c.defaults.is_some(),
+            })
+            .collect()
+    }
+
+    /// Revision history, newest first, optionally for one kind.
+    pub fn list_history(&self, kind: Option<ConfigKind>) -> Vec<ConfigRevisionMeta> {
+        self.history
+            .iter()
+            .rev()
+            .filter(|m| kind.is_none_or(|k| m.kind == k))
+            .cloned()
+            .collect()
+    }
+
+    pub fn get_revision(&self, kind: ConfigKind, revision: u64) -> Result<ConfigRevision, ApiError> {
+        if !self.history.iter().any(|m| m.kind == kind && m.revision == revision) {
+            return Err(ApiError::NotFound(format!(
+                "No {} revision {revision}",
+                kind.as_str()
+            )));
+        }
+        let path = self.revision_path(kind, revision);
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| ApiError::Internal(format!("Failed to read config revision: {e}")))?;
+        serde_json::from_str(&content)
+            .map_err(|e| ApiError::Internal(format!("Failed to parse config revision: {e}")))
+    }
+
+    /// Structural diff from revision `from` to revision `to`. `to` defaults
+    /// to the latest revision and `from` to the one before it.
+    pub fn diff_revisions(
+        &self,
+        kind: ConfigKind,
+        from: Option<u64>,
+        to: Option<u64>,
+    ) -> Result<ConfigDiff, ApiError> {
+        let latest = self.latest_revision(kind).unwrap_or(0);
+        let to = to.unwrap_or(latest);
+        let from = from.unwrap_or(to.saturating_sub(1).max(1));
+        let old = self.get_revision(kind, from)?;
+        let new = self.get_revision(kind, to)?;
+        Ok(ConfigDiff {
+            kind,
+            from,
+            to,
+            changes: diff_config(&old.data, &new.data),
+        })
+    }
+
+    // ── Updaters ──
 
     pub fn update_prompts(
         &mut self,
         prompts: PromptTemplates,
+    ) -> Result<PromptTemplates, ApiError> {
+        self.publish_prompts(prompts, ConfigRevisionSource::Update, None)
+    }
+
+    pub fn update_billing(&mut self, billing: BillingData) -> Result<BillingData, ApiError> {
+        self.publish_billing(billing, ConfigRevisionSource::Update, None)
+    }
+
+    pub fn update_thresholds(
+        &mut self,
+        thresholds: DetectionThresholds,
+    ) -> Result<DetectionThresholds, ApiError> {
+        self.publish_thresholds(thresholds, ConfigRevisionSource::Update, None)
+    }
+
+    pub fn update_defaults(
+        &mut self,
+        defaults: OperationalDefaults,
+    ) -> Result<OperationalDefaults, ApiError> {
+        self.publish_defaults(defaults, ConfigRevisionSource::Update, None)
+    }
+
+    /// Restore revision `revision` of `kind` by publishing its content as a
+    /// new revision under a new version. Returns the new revision.
+    pub fn rollback(
+        &mut self,
+        kind: ConfigKind,
+        revision: u64,
+    ) -> Result<ConfigRevision, ApiError> {
+        let target = self.get_revision(kind, revision)?;
+        let source = ConfigRevisionSource::Rollback;
+        let from = Some(revision);
+        match kind {
+            ConfigKind::Prompts => {
+                self.publish_prompts(Self::from_revision(target.data)?, source, from)?;
+            }
+            ConfigKind::Billing => {
+                self.publish_billing(Self::from_revision(target.data)?, source, from)?;
+            }
+            ConfigKind::Thresholds => {
+                self.publish_thresholds(Self::from_revision(target.data)?, source, from)?;
+            }
+            ConfigKind::Defaults => {
+                self.publish_defaults(Self::from_revision(target.data)?, source, from)?;
+            }
+        }
+        let latest = self.latest_revision(kind).unwrap_or(0);
+        info!(kind = kind.as_str(), revision, latest, "Rolled back config");
+        self.get_revision(kind, latest)
+    }
+
+    // ── Candidate channel ──
+
+    pub fn stage_prompts(
+        &mut self,
+        mut prompts: PromptTemplates,
+    ) -> Result<PromptTemplates, ApiError> {
+        self.bump_version();
+        prompts.version = self.version;
+        self.candidate.prompts = Some(prompts.clone());
+        self.save_candidate()?;
+        info!(version = self.version, "Staged candidate prompt templates");
+        Ok(prompts)
+    }
+
+    pub fn stage_billing(&mut self, mut billing: BillingData) -> Result<BillingData, ApiError> {
+        self.bump_version();
+        billing.version = self.version;
+        self.candidate.billing = Some(billing.clone());
+        self.save_candidate()?;
+        info!(version = self.version, "Staged candidate billing data");
+        Ok(billing)
+    }
+
+    pub fn stage_thresholds(
+        &mut self,
+        mut thresholds: DetectionThresholds,
+    ) -> Result<DetectionThresholds, ApiError> {
+        self.bump_version();
+        thresholds.version = self.version;
+        self.candidate.thresholds = Some(thresholds.clone());
+        self.save_candidate()?;
+        info!(version = self.version, "Staged candidate detection thresholds");
+        Ok(thresholds)
+    }
+
+    pub fn stage_defaults(
+        &mut self,
+        mut defaults: OperationalDefaults,
+    ) -> Result<OperationalDefaults, ApiError> {
+        defaults.validate()?;
+        self.bump_version();
+        defaults.version = self.version;
+        self.candidate.defaults = Some(defaults.clone());
+        self.save_candidate()?;
+        info!(version = self.version, "Staged candidate operational defaults");
+        Ok(defaults)
+    }
+
+    /// Publish the staged candidate for `kind` to stable as a new revision
+    /// and clear it. Returns the new revision.
+    pub fn promote(&mut self, kind: ConfigKind) -> Result<ConfigRevision, ApiError> {
+        let missing = || ApiError::NotFound(format!("No {} candidate staged", kind.as_str()));
+        let source = ConfigRevisionSource::Promote;
+        match kind {
+            ConfigKind::Prompts => {
+                let staged = self.candidate.prompts.clone().ok_or_else(missing)?;
+                self.publish_prompts(staged, source, None)?;
+                self.candidate.prompts = None;
+            }
+            ConfigKind::Billing => {
+                let staged = self.candidate.billing.clone().ok_or_else(missing)?;
+                self.publish_billing(staged, source, None)?;
+                self.candidate.billing = None;
+            }
+            ConfigKind::Thresholds => {
+                let staged = self.candidate.thresholds.clone().ok_or_else(missing)?;
+                self.publish_thresholds(staged, source, None)?;
+                self.candidate.thresholds = None;
+            }
+            ConfigKind::Defaults => {
+                let staged = self.candidate.defaults.clone().ok_or_else(missing)?;
+                self.publish_defaults(staged, source, None)?;
+                self.candidate.defaults = None;
+            }
+        }
+        self.save_candidate()?;
+        let latest = self.latest_revision(kind).unwrap_or(0);
+        info!(kind = kind.as_str(), revision = latest, "Promoted candidate config");
+        self.get_revision(kind, latest)
+    }
+
+    /// Drop the staged candidate for `kind`; candidate rooms go back to stable.
+    pub fn discard_candidate(&mut self, kind: ConfigKind) -> Result<(), ApiError> {
+        let had_candidate = match kind {
+            ConfigKind::Prompts => self.candidate.prompts.take().is_some(),
+            ConfigKind::Billing => self.candidate.billing.take().is_some(),
+            ConfigKind::Thresholds => self.candidate.thresholds.take().is_some(),
+            ConfigKind::Defaults => self.candidate.defaults.take().is_some(),
+        };
+        if !had_candidate {
+            return Err(ApiError::NotFound(format!(
+                "No {} candidate staged",
+                kind.as_str()
+            )));
+        }
+        self.bump_version();
+        self.save_candidate()?;
+        info!(kind = kind.as_str(), version = self.version, "Discarded candidate config");
+        Ok(())
+    }
+
+    /// Candidate file and the version it bumped
+    fn save_candidate(&self) -> Result<(), ApiError> {
+        Self::save_json(&self.candidate_path, &self.candidate)?;
+        self.save_version()
+    }
+
+    fn from_revision<T: serde::de::DeserializeOwned>(data: Value) -> Result<T, ApiError> {
+        serde_json::from_value(data)
+            .map_err(|e| ApiError::Internal(format!("Failed to parse config revision: {e}")))
+    }
+
+    // ── Publishing (full replace + revision) ──
+
+    fn publish_prompts(
+        &mut self,
+        prompts: PromptTemplates,
+        source: ConfigRevisionSource,
+        rolled_back_from: Option<u64>,
     ) -> Result<PromptTemplates, ApiError> {
         self.prompts = prompts;
         self.bump_version();
         self.prompts.version = self.version;
         Self::save_json(&self.prompts_path, &self.prompts)?;
-        Self::save_json(
-            &self.version_path,
-            &VersionFile {
-                version: self.version,
-                updated_at: self.updated_at.clone(),
-            },
-        )?;
+        self.save_version()?;
+        let data = Self::to_value(&self.prompts)?;
+        self.record_revision(ConfigKind::Prompts, data, source, rolled_back_from)?;
         info!(version = self.version, "Updated prompt templates");
         Ok(self.prompts.clone())
     }
 
-    pub fn update_billing(&mut self, billing: BillingData) -> Result<BillingData, ApiError> {
+    fn publish_billing(
+        &mut self,
+        billing: BillingData,
+        source: ConfigRevisionSource,
+        rolled_back_from: Option<u64>,
+    ) -> Result<BillingData, ApiError> {
         self.billing = billing;
         self.bump_version();
         self.billing.version = self.version;
         Self::save_json(&self.billing_path, &self.billing)?;
-        Self::save_json(
-            &self.version_path,
-            &VersionFile {
-                version: self.version,
-                updated_at: self.updated_at.clone(),
-            },
-        )?;
+        self.save_version()?;
+        let data = Self::to_value(&self.billing)?;
+        self.record_revision(ConfigKind::Billing, data, source, rolled_back_from)?;
         info!(version = self.version, "Updated billing data");
         Ok(self.billing.clone())
     }
 
-    pub fn update_thresholds(
+    fn publish_thresholds(
         &mut self,
         thresholds: DetectionThresholds,
+        source: ConfigRevisionSource,
+        rolled_back_from: Option<u64>,
     ) -> Result<DetectionThresholds, ApiError> {
         self.thresholds = thresholds;
         self.bump_version();
         self.thresholds.version = self.version;
         Self::save_json(&self.thresholds_path, &self.thresholds)?;
-        Self::save_json(
-            &self.version_path,
-            &VersionFile {
-                version: self.version,
-                updated_at: self.updated_at.clone(),
-            },
-        )?;
+        self.save_version()?;
+        let data = Self::to_value(&self.thresholds)?;
+        self.record_revision(ConfigKind::Thresholds, data, source, rolled_back_from)?;
         info!(version = self.version, "Updated detection thresholds");
         Ok(self.thresholds.clone())
     }
 
-    pub fn update_defaults(
+    fn publish_defaults(
         &mut self,
         defaults: OperationalDefaults,
+        source: ConfigRevisionSource,
+        rolled_back_from: Option<u64>,
     ) -> Result<OperationalDefaults, ApiError> {
         // Validate before mutating in-memory state so a bad PUT can't
         // stomp a valid config even transiently. `validate()` returns
@@ -223,14 +581,72 @@ impl ConfigDataStore {
         self.bump_version();
         self.defaults.version = self.version;
         Self::save_json(&self.defaults_path, &self.defaults)?;
-        Self::save_json(
-            &self.version_path,
-            &VersionFile {
-                version: self.version,
-                updated_at: self.updated_at.clone(),
-            },
-        )?;
+        self.save_version()?;
+        let data = Self::to_value(&self.defaults)?;
+        self.record_revision(ConfigKind::Defaults, data, source, rolled_back_from)?;
         info!(version = self.version, "Updated operational defaults");
         Ok(self.defaults.clone())
     }
 }
+
+/// Leaf-level structural diff between two config documents. Objects are
+/// compared key by key and arrays index by index; the top-level `version`
+/// stamp is ignored since it differs between every pair of revisions.
+pub fn diff_config(old: &Value, new: &Value) -> Vec<ConfigChange> {
+    let mut changes = Vec::new();
+    match (old, new) {
+        (Value::Object(a), Value::Object(b)) => {
+            let mut keys: Vec<&String> = a.keys().chain(b.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys.into_iter().filter(|k| k.as_str() != "version") {
+                diff_at(&pointer_child("", key), a.get(key), b.get(key), &mut changes);
+            }
+        }
+        _ => diff_at("", Some(old), Some(new), &mut changes),
+    }
+    changes
+}
+
+fn diff_at(path: &str, old: Option<&Value>, new: Option<&Value>, out: &mut Vec<ConfigChange>) {
+    match (old, new) {
+        (None, None) => {}
+        (None, Some(n)) => out.push(ConfigChange {
+            path: path.to_string(),
+            op: ConfigChangeOp::Added,
+            old: None,
+            new: Some(n.clone()),
+        }),
+        (Some(o), None) => out.push(ConfigChange {
+            path: path.to_string(),
+            op: ConfigChangeOp::Removed,
+            old: Some(o.clone()),
+            new: None,
+        }),
+        (Some(Value::Object(a)), Some(Value::Object(b))) => {
+            let mut keys: Vec<&String> = a.keys().chain(b.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                diff_at(&pointer_child(path, key), a.get(key), b.get(key), out);
+            }
+        }
+        (Some(Value::Array(a)), Some(Value::Array(b))) => {
+            for i in 0..a.len().max(b.len()) {
+                diff_at(&pointer_child(path, &i.to_string()), a.get(i), b.get(i), out);
+            }
+        }
+        (Some(o), Some(n)) if o != n => out.push(ConfigChange {
+            path: path.to_string(),
+            op: ConfigChangeOp::Changed,
+            old: Some(o.clone()),
+            new: Some(n.clone()),
+        }),
+        _ => {}
+    }
+}
+
+/// Append a reference token to a JSON pointer, escaping `~` and `/`.
+fn pointer_child(parent: &str, token: &str) -> String {
+    format!("{parent}/{}", token.replace('~', "~0").replace('/', "~1"))
+}
@@ -0,0 +1,354 @@
+//! Envelope encryption for the session store.
+//!
+//! Every file under `sessions/{physician}/` is sealed with AES-256-GCM under
+//! that physician's data key. The data key is generated on first write and
+//! stored next to the tree in `sessions/{physician}/data_key.json`, wrapped
+//! by the server master key (`PROFILE_MASTER_KEY` / `PROFILE_MASTER_KEY_FILE`).
+//!
+//! Rotating the master key only rewraps the small `data_key.json` files:
+//! set the new key as `PROFILE_MASTER_KEY`, move the old one to
+//! `PROFILE_MASTER_KEY_PREVIOUS`, and run `--rotate-master-key`. Audio and
+//! transcripts are never rewritten.
+//!
+//! Sealed files start with [`MAGIC`]; anything else is read back as
+//! plaintext so trees written before encryption was enabled keep working
+//! until `--encrypt-sessions` migrates them.
+
+use crate::error::ApiError;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::info;
+
+/// Header of every sealed file, followed by the 12-byte nonce and the
+/// ciphertext + tag.
+pub const MAGIC: &[u8] = b"FSENC1";
+
+/// Wrapped data key file inside each physician directory
+pub const DATA_KEY_FILE: &str = "data_key.json";
+
+const NONCE_LEN: usize = 12;
+const KEY_LEN: usize = 32;
+
+/// True if `data` was written by [`SessionCipher::seal`].
+pub fn is_sealed(data: &[u8]) -> bool {
+    data.len() >= MAGIC.len() + NONCE_LEN && data.starts_with(MAGIC)
+}
+
+/// Env-configured master key(s). Secrets are never serialized or logged —
+/// only the short [`key_id`] fingerprint is.
+#[derive(Clone)]
+pub struct MasterKeyConfig {
+    current: [u8; KEY_LEN],
+    /// Retired keys, still accepted for unwrapping until `--rotate-master-key`
+    /// has rewrapped every data key.
+    previous: Vec<[u8; KEY_LEN]>,
+}
+
+impl MasterKeyConfig {
+    /// Reads `PROFILE_MASTER_KEY` (64 hex chars) or, failing that, the file
+    /// named by `PROFILE_MASTER_KEY_FILE`, plus the comma-separated
+    /// `PROFILE_MASTER_KEY_PREVIOUS`. `Ok(None)` when no key is configured —
+    /// sessions are then stored in plaintext. A malformed key is an error
+    /// rather than a silent fallback to plaintext.
+    pub fn from_env() -> Result<Option<Self>, ApiError> {
+        let file_key = match std::env::var("PROFILE_MASTER_KEY_FILE") {
+            Ok(path) if !path.is_empty() => Some(std::fs::read_to_string(&path).map_err(|e| {
+                ApiError::Internal(format!("Failed to read master key file {path}: {e}"))
+            })?),
+            _ => None,
+        };
+        Self::from_values(
+            std::env::var("PROFILE_MASTER_KEY").ok().or(file_key),
+            std::env::var("PROFILE_MASTER_KEY_PREVIOUS").ok(),
+        )
+    }
+
+    /// Pure parser, split out so unit tests don't race `std::env`.
+    pub fn from_values(
+        current: Option<String>,
+        previous: Option<String>,
+    ) -> Result<Option<Self>, ApiError> {
+        let Some(current) = current.filter(|k| !k.trim().is_empty()) else {
+            return Ok(None);
+        };
+        let previous = previous
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|k| !k.is_empty())
+            .map(parse_master_key)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Some(Self {
+            current: parse_master_key(&current)?,
+            previous,
+        }))
+    }
+
+    pub fn current_id(&self) -> String {
+        key_id(&self.current)
+    }
+
+    fn find(&self, id: &str) -> Option<&[u8; KEY_LEN]> {
+        std::iter::once(&self.current)
+            .chain(&self.previous)
+            .find(|k| key_id(*k) == id)
+    }
+}
+
+fn parse_master_key(hex: &str) -> Result<[u8; KEY_LEN], ApiError> {
+    from_hex(hex.trim())
+        .and_then(|bytes| <[u8; KEY_LEN]>::try_from(bytes).ok())
+        .ok_or_else(|| ApiError::Internal("Master key must be 64 hex characters".into()))
+}
+
+/// Fingerprint stored alongside wrapped keys to pick the right master key
+fn key_id(key: &[u8]) -> String {
+    to_hex(&Sha256::digest(key)[..8])
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn from_hex(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// On-disk form of a physician's data key
+#[derive(Debug, Serialize, Deserialize)]
+struct WrappedDataKey {
+    schema_version: u32,
+    master_key_id: String,
+    nonce: String,
+    wrapped_key: String,
+    created_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rewrapped_at: Option<String>,
+}
+
+/// Seals and opens session files with per-physician data keys.
+pub struct SessionCipher {
+    master: MasterKeyConfig,
+    /// Unwrapped data keys by physician directory
+    data_keys: Mutex<HashMap<PathBuf, Key<Aes256Gcm>>>,
+}
+
+impl SessionCipher {
+    pub fn new(master: MasterKeyConfig) -> Self {
+        Self {
+            master,
+            data_keys: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn master_key_id(&self) -> String {
+        self.master.current_id()
+    }
+
+    /// Encrypt `plaintext` under the data key of `physician_dir`, creating
+    /// the key on first use.
+    pub fn seal(&self, physician_dir: &Path, plaintext: &[u8]) -> Result<Vec<u8>, ApiError> {
+        let key = self.data_key(physician_dir, true)?;
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = Aes256Gcm::new(&key)
+            .encrypt(&nonce, plaintext)
+            .map_err(|_| ApiError::Internal("Failed to encrypt session file".into()))?;
+        let mut out = Vec::with_capacity(MAGIC.len() + NONCE_LEN + ciphertext.len());
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&nonce);
+        out.extend_from_slice(&ciphertext);
+        Ok(out)
+    }
+
+    /// Decrypt a file produced by [`seal`](Self::seal).
+    pub fn open(&self, physician_dir: &Path, sealed: &[u8]) -> Result<Vec<u8>, ApiError> {
+        if !is_sealed(sealed) {
+            return Err(ApiError::Internal("Not an encrypted session file".into()));
+        }
+        let key = self.data_key(physician_dir, false)?;
+        let (nonce, ciphertext) = sealed[MAGIC.len()..].split_at(NONCE_LEN);
+        Aes256Gcm::new(&key)
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| ApiError::Internal("Failed to decrypt session file".into()))
+    }
+
+    /// Rewrap a physician's data key under the current master key. Returns
+    /// false when it already was (or the physician has no key yet).
+    pub fn rewrap(&self, physician_dir: &Path) -> Result<bool, ApiError> {
+        let path = physician_dir.join(DATA_KEY_FILE);
+        if !path.exists() {
+            return Ok(false);
+        }
+        let stored = read_wrapped(&path)?;
+        if stored.master_key_id == self.master.current_id() {
+            return Ok(false);
+        }
+        let key = self.unwrap_key(&stored)?;
+        let mut rewrapped = self.wrap_key(&key, stored.created_at)?;
+        rewrapped.rewrapped_at = Some(Utc::now().to_rfc3339());
+        write_wrapped(&path, &rewrapped)?;
+        info!(
+            dir = %physician_dir.display(),
+            from = %stored.master_key_id,
+            to = %rewrapped.master_key_id,
+            "Data key rewrapped"
+        );
+        Ok(true)
+    }
+
+    fn data_key(&self, physician_dir: &Path, create: bool) -> Result<Key<Aes256Gcm>, ApiError> {
+        let mut cache = self.data_keys.lock().expect("data key cache poisoned");
+        if let Some(key) = cache.get(physician_dir) {
+            return Ok(*key);
+        }
+
+        let path = physician_dir.join(DATA_KEY_FILE);
+        let key = if path.exists() {
+            self.unwrap_key(&read_wrapped(&path)?)?
+        } else if create {
+            let key = Aes256Gcm::generate_key(OsRng);
+            std::fs::create_dir_all(physician_dir)
+                .map_err(|e| ApiError::Internal(format!("Failed to create directory: {e}")))?;
+            write_wrapped(&path, &self.wrap_key(&key, Utc::now().to_rfc3339())?)?;
+            info!(dir = %physician_dir.display(), "Created session data key");
+            key
+        } else {
+            return Err(ApiError::Internal(format!(
+                "No data key for encrypted files in {}",
+                physician_dir.display()
+            )));
+        };
+        cache.insert(physician_dir.to_path_buf(), key);
+        Ok(key)
+    }
+
+    fn wrap_key(&self, key: &Key<Aes256Gcm>, created_at: String) -> Result<WrappedDataKey, ApiError> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let wrapped = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&self.master.current))
+            .encrypt(&nonce, key.as_slice())
+            .map_err(|_| ApiError::Internal("Failed to wrap data key".into()))?;
+        Ok(WrappedDataKey {
+            schema_version: 1,
+            master_key_id: self.master.current_id(),
+            nonce: to_hex(&nonce),
+            wrapped_key: to_hex(&wrapped),
+            created_at,
+            rewrapped_at: None,
+        })
+    }
+
+    fn unwrap_key(&self, stored: &WrappedDataKey) -> Result<Key<Aes256Gcm>, ApiError> {
+        let master = self.master.find(&stored.master_key_id).ok_or_else(|| {
+            ApiError::Internal(format!(
+                "Data key is wrapped by unknown master key {}",
+                stored.master_key_id
+            ))
+        })?;
+        let nonce = from_hex(&stored.nonce)
+            .filter(|n| n.len() == NONCE_LEN)
+            .ok_or_else(|| ApiError::Internal("Malformed data key nonce".into()))?;
+        let wrapped = from_hex(&stored.wrapped_key)
+            .ok_or_else(|| ApiError::Internal("Malformed wrapped data key".into()))?;
+        let key = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(master))
+            .decrypt(Nonce::from_slice(&nonce), wrapped.as_slice())
+            .map_err(|_| ApiError::Internal("Failed to unwrap data key".into()))?;
+        if key.len() != KEY_LEN {
+            return Err(ApiError::Internal("Unwrapped data key has wrong length".into()));
+        }
+        Ok(*Key::<Aes256Gcm>::from_slice(&key))
+    }
+}
+
+fn read_wrapped(path: &Path) -> Result<WrappedDataKey, ApiError> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| ApiError::Internal(format!("Failed to read data key: {e}")))?;
+    serde_json::from_str(&content)
+        .map_err(|e| ApiError::Internal(format!("Failed to parse data key: {e}")))
+}
+
+fn write_wrapped(path: &Path, stored: &WrappedDataKey) -> Result<(), ApiError> {
+    let content = serde_json::to_string_pretty(stored)
+        .map_err(|e| ApiError::Internal(format!("Failed to serialize: {e}")))?;
+    let temp_path = path.with_extension("json.tmp");
+    std::fs::write(&temp_path, &content)
+        .map_err(|e| ApiError::Internal(format!("Failed to write temp file: {e}")))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = std::fs::set_permissions(&temp_path, std::fs::Permissions::from_mode(0o600));
+    }
+    std::fs::rename(&temp_path, path)
+        .map_err(|e| ApiError::Internal(format!("Failed to rename: {e}")))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY_A: &str = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+    const KEY_B: &str = "1f1e1d1c1b1a191817161514131211100f0e0d0c0b0a09080706050403020100";
+
+    fn cipher(current: &str, previous: Option<&str>) -> SessionCipher {
+        let master = MasterKeyConfig::from_values(
+            Some(current.to_string()),
+            previous.map(str::to_string),
+        )
+        .unwrap()
+        .unwrap();
+        SessionCipher::new(master)
+    }
+
+    #[test]
+    fn from_values_none_without_key() {
+        assert!(MasterKeyConfig::from_values(None, None).unwrap().is_none());
+        assert!(MasterKeyConfig::from_values(Some("  ".into()), None).unwrap().is_none());
+    }
+
+    #[test]
+    fn from_values_rejects_malformed_key() {
+        assert!(MasterKeyConfig::from_values(Some("abc".into()), None).is_err());
+        assert!(MasterKeyConfig::from_values(Some(KEY_A.into()), Some("zz".into())).is_err());
+    }
+
+    #[test]
+    fn seal_open_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let c = cipher(KEY_A, None);
+        let sealed = c.seal(dir.path(), b"transcript").unwrap();
+        assert!(is_sealed(&sealed));
+        assert!(!sealed.windows(10).any(|w| w == b"transcript"));
+
+        // Fresh cipher has to unwrap the stored key
+        let c = cipher(KEY_A, None);
+        assert_eq!(c.open(dir.path(), &sealed).unwrap(), b"transcript");
+    }
+
+    #[test]
+    fn rotation_rewraps_without_touching_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let sealed = cipher(KEY_A, None).seal(dir.path(), b"audio").unwrap();
+
+        // New master, old one retired but still accepted
+        let rotated = cipher(KEY_B, Some(KEY_A));
+        assert!(rotated.rewrap(dir.path()).unwrap());
+        assert!(!rotated.rewrap(dir.path()).unwrap());
+
+        // Old key no longer needed
+        let c = cipher(KEY_B, None);
+        assert_eq!(c.open(dir.path(), &sealed).unwrap(), b"audio");
+        assert!(cipher(KEY_A, None).open(dir.path(), &sealed).is_err());
+    }
+}
@@ -1,9 +1,24 @@
 use crate::error::ApiError;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use tokio::sync::broadcast;
 use uuid::Uuid;
 
+/// Job events kept on disk for `Last-Event-ID` replay. Older events are
+/// dropped; a client whose cursor falls behind this window gets a resync.
+const EVENT_RETENTION: usize = 1000;
+/// Live events buffered per stream subscriber before it lags and has to
+/// catch up from the retained log.
+const EVENT_CHANNEL_CAPACITY: usize = 256;
+const EVENTS_SCHEMA_VERSION: u32 = 1;
+
+/// Lease length when a worker doesn't ask for one.
+pub const DEFAULT_LEASE_SECS: u64 = 300;
+pub const MAX_LEASE_SECS: u64 = 3600;
+/// Claims a job gets before a failure dead-letters it instead of requeueing.
+pub const MAX_ATTEMPTS: u32 = 3;
+
 /// Status of a mobile processing job.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
@@ -15,6 +30,14 @@ pub enum JobStatus {
     GeneratingSoap,
     Complete,
     Failed,
+    /// Failed or lost its lease `MAX_ATTEMPTS` times; needs a manual requeue.
+    DeadLetter,
+}
+
+impl JobStatus {
+    fn is_terminal(&self) -> bool {
+        matches!(self, Self::Complete | Self::Failed | Self::DeadLetter)
+    }
 }
 
 /// A session created by the processing CLI after splitting/processing.
@@ -43,6 +66,21 @@ pub struct MobileJob {
     pub updated_at: String,
     #[serde(default)]
     pub device_info: Option<String>,
+    /// Times a worker has claimed this job.
+    #[serde(default)]
+    pub attempts: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lease: Option<JobLease>,
+}
+
+/// A worker's claim on a job. Lapses at `expires_at` unless renewed by a
+/// heartbeat; the job is then requeued (or dead-lettered) on the next claim.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobLease {
+    pub lease_id: String,
+    pub worker_id: String,
+    pub claimed_at: String,
+    pub expires_at: String,
 }
 
 /// Request to update a job's status (used by the processing CLI).
@@ -53,6 +91,48 @@ pub struct UpdateJobRequest {
     pub error: Option<String>,
     #[serde(default)]
     pub sessions_created: Option<Vec<CreatedSession>>,
+    /// Required while the job is leased, so a worker that lost its lease
+    /// can't overwrite the new holder's progress.
+    #[serde(default)]
+    pub lease_id: Option<String>,
+}
+
+/// Request to lease the oldest queued job.
+#[derive(Debug, Deserialize)]
+pub struct ClaimJobRequest {
+    pub worker_id: String,
+    #[serde(default)]
+    pub lease_secs: Option<u64>,
+}
+
+/// Request to extend a held lease.
+#[derive(Debug, Deserialize)]
+pub struct HeartbeatRequest {
+    pub lease_id: String,
+    #[serde(default)]
+    pub lease_secs: Option<u64>,
+}
+
+/// A job status change, as pushed to `GET /mobile/jobs/stream`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobEvent {
+    /// Monotonic across restarts; doubles as the SSE event id.
+    pub event_id: u64,
+    pub job_id: String,
+    pub physician_id: String,
+    pub status: JobStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Only populated on the transition to `Complete`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sessions_created: Vec<CreatedSession>,
+    pub updated_at: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct EventLogFile {
+    schema_version: u32,
+    events: VecDeque<JobEvent>,
 }
 
 /// In-memory job store backed by a JSON file on disk.
@@ -62,6 +142,10 @@ pub struct MobileJobStore {
     recording_index: HashMap<String, String>,
     persist_path: PathBuf,
     uploads_dir: PathBuf,
+    /// Most recent status changes, oldest first.
+    events: VecDeque<JobEvent>,
+    events_path: PathBuf,
+    event_tx: broadcast::Sender<JobEvent>,
 }
 
 impl MobileJobStore {
@@ -84,37 +168,45 @@ impl MobileJobStore {
             .map(|(_, job)| (job.recording_id.clone(), job.job_id.clone()))
             .collect();
 
+        let events_path = persist_path.with_extension("events.json");
+        let events = if events_path.exists() {
+            let data = std::fs::read_to_string(&events_path)
+                .map_err(|e| ApiError::Internal(format!("Failed to read job events file: {e}")))?;
+            serde_json::from_str::<EventLogFile>(&data)
+                .map_err(|e| ApiError::Internal(format!("Failed to parse job events file: {e}")))?
+                .events
+        } else {
+            VecDeque::new()
+        };
+        let (event_tx, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+
         Ok(Self {
             jobs,
             recording_index,
             persist_path,
             uploads_dir,
+            events,
+            events_path,
+            event_tx,
         })
     }
 
     /// Persist all jobs to disk atomically.
     fn save(&self) -> Result<(), ApiError> {
-        if let Some(parent) = self.persist_path.parent() {
-            std::fs::create_dir_all(parent)
-                .map_err(|e| ApiError::Internal(format!("Failed to create parent dir: {e}")))?;
-        }
         let content = serde_json::to_string_pretty(&self.jobs)
             .map_err(|e| ApiError::Internal(format!("Failed to serialize jobs: {e}")))?;
-        let tmp = self
-            .persist_path
-            .with_extension(format!("{}.tmp", Uuid::new_v4()));
-        std::fs::write(&tmp, &content)
-            .map_err(|e| ApiError::Internal(format!("Failed to write temp file: {e}")))?;
-        #[cfg(unix)]
-        {
-            use std::os::unix::fs::PermissionsExt;
-            let _ = std::fs::set_permissions(&tmp, std::fs::Permissions::from_mode(0o600));
-        }
-        std::fs::rename(&tmp, &self.persist_path).map_err(|e| {
-            let _ = std::fs::remove_file(&tmp);
-            ApiError::Internal(format!("Failed to rename: {e}"))
-        })?;
-        Ok(())
+        write_atomic(&self.persist_path, &content)
+    }
+
+    /// Persist the retained event log to disk atomically.
+    fn save_events(&self) -> Result<(), ApiError> {
+        let file = EventLogFile {
+            schema_version: EVENTS_SCHEMA_VERSION,
+            events: self.events.clone(),
+        };
+        let content = serde_json::to_string_pretty(&file)
+            .map_err(|e| ApiError::Internal(format!("Failed to serialize job events: {e}")))?;
+        write_atomic(&self.events_path, &content)
     }
 
     /// Create a new job. Returns existing job if recording_id is a duplicate.
@@ -146,6 +238,8 @@ impl MobileJobStore {
             created_at: now.clone(),
             updated_at: now,
             device_info,
+            attempts: 0,
+            lease: None,
         };
 
         self.recording_index
@@ -190,6 +284,11 @@ impl MobileJobStore {
     }
 
     /// Update a job's status and optionally its error/sessions.
+    ///
+    /// A worker holding the lease passes its `lease_id`; a terminal status
+    /// releases the lease, and `Failed` requeues the job until it is out of
+    /// attempts. Updates without a lease (operators, older workers) are
+    /// refused while a live lease is held.
     pub fn update_job(
         &mut self,
         job_id: &str,
@@ -199,8 +298,27 @@ impl MobileJobStore {
             .jobs
             .get_mut(job_id)
             .ok_or_else(|| ApiError::NotFound(format!("Job not found: {job_id}")))?;
+        check_lease(job, req.lease_id.as_deref(), chrono::Utc::now())?;
 
-        job.status = req.status;
+        let mut status = req.status;
+        if req.lease_id.is_some() {
+            if status.is_terminal() {
+                job.lease = None;
+                if status == JobStatus::Failed {
+                    status = retry_status(job.attempts);
+                }
+            }
+        } else {
+            // Any lease left here has lapsed. Pulling a job out of dead
+            // letter gives it a fresh set of attempts.
+            job.lease = None;
+            if job.status == JobStatus::DeadLetter && status == JobStatus::Queued {
+                job.attempts = 0;
+            }
+        }
+
+        let status_changed = job.status != status;
+        job.status = status;
         job.error = req.error;
         if let Some(sessions) = req.sessions_created {
             job.sessions_created = sessions;
@@ -209,9 +327,156 @@ impl MobileJobStore {
 
         let result = job.clone();
         self.save()?;
+        if status_changed {
+            self.push_event(&result)?;
+        }
         Ok(result)
     }
 
+    /// Lease the oldest queued job to `worker_id`, first reclaiming jobs
+    /// whose leases have lapsed. None when nothing is queued.
+    pub fn claim_job(
+        &mut self,
+        worker_id: &str,
+        lease_secs: u64,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Option<MobileJob>, ApiError> {
+        let reclaimed = self.reclaim_expired(now);
+
+        let next = self
+            .jobs
+            .values()
+            .filter(|j| j.status == JobStatus::Queued && j.lease.is_none())
+            .min_by(|a, b| {
+                a.created_at
+                    .cmp(&b.created_at)
+                    .then_with(|| a.job_id.cmp(&b.job_id))
+            })
+            .map(|j| j.job_id.clone());
+        let claimed = next.and_then(|id| self.jobs.get_mut(&id)).map(|job| {
+            job.attempts += 1;
+            job.lease = Some(JobLease {
+                lease_id: Uuid::new_v4().to_string(),
+                worker_id: worker_id.to_string(),
+                claimed_at: now.to_rfc3339(),
+                expires_at: lease_expiry(now, lease_secs),
+            });
+            job.updated_at = now.to_rfc3339();
+            job.clone()
+        });
+
+        if claimed.is_some() || !reclaimed.is_empty() {
+            self.save()?;
+        }
+        for job in reclaimed.iter().filter(|j| j.status_changed) {
+            self.push_event(&job.job)?;
+        }
+        Ok(claimed)
+    }
+
+    /// Extend a held lease by `lease_secs` from now.
+    pub fn heartbeat(
+        &mut self,
+        job_id: &str,
+        lease_id: &str,
+        lease_secs: u64,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<MobileJob, ApiError> {
+        let job = self
+            .jobs
+            .get_mut(job_id)
+            .ok_or_else(|| ApiError::NotFound(format!("Job not found: {job_id}")))?;
+        check_lease(job, Some(lease_id), now)?;
+        if let Some(lease) = job.lease.as_mut() {
+            lease.expires_at = lease_expiry(now, lease_secs);
+        }
+        let result = job.clone();
+        self.save()?;
+        Ok(result)
+    }
+
+    /// Drop lapsed leases. Unfinished jobs go back to the queue, or to dead
+    /// letter if that was their last attempt. Callers persist the store.
+    fn reclaim_expired(&mut self, now: chrono::DateTime<chrono::Utc>) -> Vec<Reclaimed> {
+        let mut reclaimed = Vec::new();
+        for job in self.jobs.values_mut() {
+            let Some(lease) = job.lease.as_ref().filter(|l| lease_expired(l, now)) else {
+                continue;
+            };
+            let worker_id = lease.worker_id.clone();
+            job.lease = None;
+            let mut status_changed = false;
+            if !job.status.is_terminal() {
+                let status = retry_status(job.attempts);
+                status_changed = job.status != status;
+                job.status = status;
+                job.error = Some(format!("Lease held by worker {worker_id} expired"));
+            }
+            job.updated_at = now.to_rfc3339();
+            reclaimed.push(Reclaimed {
+                job: job.clone(),
+                status_changed,
+            });
+        }
+        reclaimed
+    }
+
+    /// Record a status change and wake any stream subscribers.
+    fn push_event(&mut self, job: &MobileJob) -> Result<(), ApiError> {
+        let event = JobEvent {
+            event_id: self.latest_event_id() + 1,
+            job_id: job.job_id.clone(),
+            physician_id: job.physician_id.clone(),
+            status: job.status.clone(),
+            error: job.error.clone(),
+            sessions_created: if job.status == JobStatus::Complete {
+                job.sessions_created.clone()
+            } else {
+                Vec::new()
+            },
+            updated_at: job.updated_at.clone(),
+        };
+        self.events.push_back(event.clone());
+        while self.events.len() > EVENT_RETENTION {
+            self.events.pop_front();
+        }
+        self.save_events()?;
+        // No receivers is the normal case when no app is connected
+        let _ = self.event_tx.send(event);
+        Ok(())
+    }
+
+    /// Id of the newest job event, or 0 if none has been recorded.
+    pub fn latest_event_id(&self) -> u64 {
+        self.events.back().map_or(0, |e| e.event_id)
+    }
+
+    /// Events after `cursor`, oldest first. None when the cursor is outside
+    /// the retained window — older than the oldest event kept, or ahead of
+    /// the newest — and the client has to refetch job state instead.
+    pub fn events_since(&self, cursor: u64) -> Option<Vec<JobEvent>> {
+        if cursor > self.latest_event_id() {
+            return None;
+        }
+        if let Some(oldest) = self.events.front() {
+            if cursor + 1 < oldest.event_id {
+                return None;
+            }
+        }
+        Some(
+            self.events
+                .iter()
+                .filter(|e| e.event_id > cursor)
+                .cloned()
+                .collect(),
+        )
+    }
+
+    /// Subscribe to job events recorded from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<JobEvent> {
+        self.event_tx.subscribe()
+    }
+
     /// Delete a job and its uploaded audio file.
     pub fn delete_job(&mut self, job_id: &str) -> Result<(), ApiError> {
         let job = self
@@ -264,3 +529,268 @@ impl MobileJobStore {
         self.upload_path(job_id).exists()
     }
 }
+
+/// A job whose lapsed lease was dropped by `reclaim_expired`.
+struct Reclaimed {
+    job: MobileJob,
+    status_changed: bool,
+}
+
+/// Where a job goes after a failed attempt.
+fn retry_status(attempts: u32) -> JobStatus {
+    if attempts >= MAX_ATTEMPTS {
+        JobStatus::DeadLetter
+    } else {
+        JobStatus::Queued
+    }
+}
+
+fn lease_expiry(now: chrono::DateTime<chrono::Utc>, lease_secs: u64) -> String {
+    (now + chrono::Duration::seconds(lease_secs as i64)).to_rfc3339()
+}
+
+/// A lease with an unparseable expiry counts as lapsed.
+fn lease_expired(lease: &JobLease, now: chrono::DateTime<chrono::Utc>) -> bool {
+    chrono::DateTime::parse_from_rfc3339(&lease.expires_at).map_or(true, |t| t <= now)
+}
+
+/// Check that `lease_id` may act on `job`: it must be the job's live lease,
+/// and without one there must be no live lease at all.
+fn check_lease(
+    job: &MobileJob,
+    lease_id: Option<&str>,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<(), ApiError> {
+    match (&job.lease, lease_id) {
+        (None, None) => Ok(()),
+        (Some(lease), None) if lease_expired(lease, now) => Ok(()),
+        (Some(lease), None) => Err(ApiError::Conflict(format!(
+            "Job is leased to worker {}",
+            lease.worker_id
+        ))),
+        (Some(lease), Some(id)) if lease.lease_id == id && !lease_expired(lease, now) => Ok(()),
+        (_, Some(_)) => Err(ApiError::Conflict("Lease is no longer held".into())),
+    }
+}
+
+/// Write `content` to `path` via a temp file and rename, owner-only.
+fn write_atomic(path: &Path, content: &str) -> Result<(), ApiError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| ApiError::Internal(format!("Failed to create parent dir: {e}")))?;
+    }
+    let tmp = path.with_extension(format!("{}.tmp", Uuid::new_v4()));
+    std::fs::write(&tmp, content)
+        .map_err(|e| ApiError::Internal(format!("Failed to write temp file: {e}")))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = std::fs::set_permissions(&tmp, std::fs::Permissions::from_mode(0o600));
+    }
+    std::fs::rename(&tmp, path).map_err(|e| {
+        let _ = std::fs::remove_file(&tmp);
+        ApiError::Internal(format!("Failed to rename: {e}"))
+    })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store(dir: &Path) -> MobileJobStore {
+        MobileJobStore::load(dir.join("mobile_jobs.json"), dir.join("uploads")).unwrap()
+    }
+
+    fn set_status(store: &mut MobileJobStore, job_id: &str, status: JobStatus) {
+        store
+            .update_job(
+                job_id,
+                UpdateJobRequest {
+                    status,
+                    error: None,
+                    sessions_created: None,
+                    lease_id: None,
+                },
+            )
+            .unwrap();
+    }
+
+    fn fail_leased(store: &mut MobileJobStore, job: &MobileJob) -> MobileJob {
+        store
+            .update_job(
+                &job.job_id,
+                UpdateJobRequest {
+                    status: JobStatus::Failed,
+                    error: Some("stt down".into()),
+                    sessions_created: None,
+                    lease_id: job.lease.as_ref().map(|l| l.lease_id.clone()),
+                },
+            )
+            .unwrap()
+    }
+
+    fn new_job(store: &mut MobileJobStore, recording_id: &str) -> String {
+        store
+            .create_job("p1".into(), recording_id.into(), "2026-01-01T00:00:00Z".into(), 1000, None)
+            .unwrap()
+            .job_id
+    }
+
+    #[test]
+    fn only_status_changes_emit_events() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = store(dir.path());
+        let job_id = new_job(&mut store, "r1");
+        let mut rx = store.subscribe();
+
+        set_status(&mut store, &job_id, JobStatus::Transcoding);
+        set_status(&mut store, &job_id, JobStatus::Transcoding);
+        set_status(&mut store, &job_id, JobStatus::Transcribing);
+
+        let events = store.events_since(0).unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].event_id, 1);
+        assert_eq!(events[1].status, JobStatus::Transcribing);
+        assert_eq!(rx.try_recv().unwrap().event_id, 1);
+        assert_eq!(rx.try_recv().unwrap().event_id, 2);
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn cursor_outside_window_needs_resync() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = store(dir.path());
+        let job_id = new_job(&mut store, "r1");
+        assert!(store.events_since(0).unwrap().is_empty());
+        assert!(store.events_since(1).is_none());
+
+        for _ in 0..EVENT_RETENTION + 5 {
+            let job = store.jobs[&job_id].clone();
+            store.events.push_back(JobEvent {
+                event_id: store.latest_event_id() + 1,
+                job_id: job.job_id,
+                physician_id: job.physician_id,
+                status: job.status,
+                error: None,
+                sessions_created: Vec::new(),
+                updated_at: job.updated_at,
+            });
+        }
+        set_status(&mut store, &job_id, JobStatus::Transcoding);
+
+        let latest = store.latest_event_id();
+        assert_eq!(latest, EVENT_RETENTION as u64 + 6);
+        assert_eq!(store.events.len(), EVENT_RETENTION);
+        assert!(store.events_since(5).is_none());
+        assert_eq!(store.events_since(6).unwrap().len(), EVENT_RETENTION);
+        assert!(store.events_since(latest).unwrap().is_empty());
+    }
+
+    #[test]
+    fn event_ids_survive_reload() {
+        let dir = tempfile::tempdir().unwrap();
+        let job_id = {
+            let mut store = store(dir.path());
+            let job_id = new_job(&mut store, "r1");
+            set_status(&mut store, &job_id, JobStatus::Transcoding);
+            job_id
+        };
+        let mut store = store(dir.path());
+        assert_eq!(store.latest_event_id(), 1);
+        set_status(&mut store, &job_id, JobStatus::Complete);
+        assert_eq!(store.events_since(1).unwrap()[0].event_id, 2);
+    }
+
+    #[test]
+    fn claims_oldest_queued_job_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = store(dir.path());
+        let first = new_job(&mut store, "r1");
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        let second = new_job(&mut store, "r2");
+        let now = chrono::Utc::now();
+
+        let a = store.claim_job("w1", 60, now).unwrap().unwrap();
+        let b = store.claim_job("w2", 60, now).unwrap().unwrap();
+        assert_eq!(a.job_id, first);
+        assert_eq!(b.job_id, second);
+        assert_eq!(a.attempts, 1);
+        assert_eq!(a.lease.as_ref().unwrap().worker_id, "w1");
+        assert!(store.claim_job("w3", 60, now).unwrap().is_none());
+    }
+
+    #[test]
+    fn lapsed_lease_is_reclaimed_then_dead_lettered() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = store(dir.path());
+        let job_id = new_job(&mut store, "r1");
+        let mut now = chrono::Utc::now();
+
+        for attempt in 1..=MAX_ATTEMPTS {
+            let job = store.claim_job("w1", 60, now).unwrap().unwrap();
+            assert_eq!(job.attempts, attempt);
+            // Heartbeats keep it; nobody else can take it meanwhile
+            now += chrono::Duration::seconds(50);
+            let lease_id = job.lease.unwrap().lease_id;
+            store.heartbeat(&job_id, &lease_id, 60, now).unwrap();
+            now += chrono::Duration::seconds(50);
+            assert!(store.claim_job("w2", 60, now).unwrap().is_none());
+            now += chrono::Duration::seconds(11);
+            assert!(matches!(
+                store.heartbeat(&job_id, &lease_id, 60, now),
+                Err(ApiError::Conflict(_))
+            ));
+        }
+
+        assert!(store.claim_job("w2", 60, now).unwrap().is_none());
+        let job = store.get_job(&job_id).unwrap();
+        assert_eq!(job.status, JobStatus::DeadLetter);
+        assert!(job.lease.is_none());
+        assert!(job.error.unwrap().contains("w1"));
+    }
+
+    #[test]
+    fn reported_failure_requeues_until_out_of_attempts() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = store(dir.path());
+        let job_id = new_job(&mut store, "r1");
+        let now = chrono::Utc::now();
+
+        for _ in 1..MAX_ATTEMPTS {
+            let job = store.claim_job("w1", 60, now).unwrap().unwrap();
+            let job = fail_leased(&mut store, &job);
+            assert_eq!(job.status, JobStatus::Queued);
+            assert_eq!(job.error.as_deref(), Some("stt down"));
+        }
+        let job = store.claim_job("w1", 60, now).unwrap().unwrap();
+        assert_eq!(fail_leased(&mut store, &job).status, JobStatus::DeadLetter);
+
+        // Manual requeue starts the count over
+        set_status(&mut store, &job_id, JobStatus::Queued);
+        assert_eq!(store.claim_job("w1", 60, now).unwrap().unwrap().attempts, 1);
+    }
+
+    #[test]
+    fn leased_job_rejects_stale_or_missing_lease() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = store(dir.path());
+        let job_id = new_job(&mut store, "r1");
+        store.claim_job("w1", 60, chrono::Utc::now()).unwrap();
+
+        for lease_id in [None, Some("not-the-lease".to_string())] {
+            let err = store
+                .update_job(
+                    &job_id,
+                    UpdateJobRequest {
+                        status: JobStatus::Transcoding,
+                        error: None,
+                        sessions_created: None,
+                        lease_id,
+                    },
+                )
+                .unwrap_err();
+            assert!(matches!(err, ApiError::Conflict(_)));
+        }
+    }
+}
@@ -1,4 +1,7 @@
+pub mod api_keys;
+pub mod audit_log;
 pub mod config_data;
+pub mod encryption;
 pub mod infrastructure;
 pub mod medplum_auth;
 pub mod mobile_jobs;
@@ -6,8 +9,10 @@ pub mod openai_image;
 pub mod patients;
 pub mod physicians;
 pub mod rooms;
+pub mod search_index;
 pub mod sessions;
 pub mod speakers;
+pub mod uploads;
 
 use std::path::PathBuf;
 use tokio::sync::RwLock;
@@ -19,7 +24,10 @@ pub struct AppState {
     pub infrastructure: RwLock<infrastructure::InfrastructureStore>,
     pub sessions: sessions::SessionStore,
     pub mobile_jobs: RwLock<mobile_jobs::MobileJobStore>,
+    pub uploads: RwLock<uploads::UploadStore>,
     pub config_data: RwLock<config_data::ConfigDataStore>,
+    pub api_keys: RwLock<api_keys::ApiKeyManager>,
+    pub audit_log: RwLock<audit_log::AuditLog>,
     pub patients: RwLock<patients::PatientManager>,
     pub medplum_auth: medplum_auth::MedplumAuthProxy,
     pub openai_image: openai_image::OpenAIImageProxy,
@@ -97,6 +97,8 @@ impl PatientManager {
 
     /// Idempotent confirm. Lookup-or-create. On hit, appends `session_id`
     /// (deduped) and refreshes `medplum_patient_id` when supplied.
+    /// `session_date` (YYYY-MM-DD) is recorded in `session_dates` so clients
+    /// can window the patient's history without resolving every session.
     pub fn confirm(
         &mut self,
         physician_id: &str,
@@ -104,6 +106,7 @@ impl PatientManager {
         dob: &str,
         session_id: &str,
         medplum_patient_id: Option<String>,
+        session_date: Option<&str>,
     ) -> Result<(PatientRecord, bool), ApiError> {
         if physician_id.is_empty() {
             return Err(ApiError::BadRequest("physician_id is empty".into()));
@@ -113,6 +116,11 @@ impl PatientManager {
                 "dob must be YYYY-MM-DD, got {dob}"
             )));
         }
+        if let Some(d) = session_date.filter(|d| !is_iso_date(d)) {
+            return Err(ApiError::BadRequest(format!(
+                "session_date must be YYYY-MM-DD, got {d}"
+            )));
+        }
         let normalized = normalize_patient_name(name);
         if normalized.is_empty() {
             return Err(ApiError::BadRequest("name is empty after normalization".into()));
@@ -125,6 +133,9 @@ impl PatientManager {
             if !rec.session_ids.iter().any(|s| s == session_id) {
                 rec.session_ids.push(session_id.to_string());
             }
+            if let Some(d) = session_date {
+                rec.session_dates.insert(session_id.to_string(), d.to_string());
+            }
             if let Some(mpid) = medplum_patient_id {
                 // Later writes win — reconciles a UUID fallback to a real Medplum
                 // FHIR ID on a subsequent confirm.
@@ -143,6 +154,9 @@ impl PatientManager {
                 dob: dob.to_string(),
                 medplum_patient_id,
                 session_ids: vec![session_id.to_string()],
+                session_dates: session_date
+                    .map(|d| BTreeMap::from([(session_id.to_string(), d.to_string())]))
+                    .unwrap_or_default(),
                 created_at: now.clone(),
                 updated_at: now,
             };
@@ -294,7 +308,7 @@ mod tests {
     fn confirm_creates_record_on_first_call() {
         let (mut m, _tmp) = manager();
         let (rec, created) = m
-            .confirm("phys-1", "Judie Joan Guest", "1945-04-08", "sess-a", None)
+            .confirm("phys-1", "Judie Joan Guest", "1945-04-08", "sess-a", None, None)
             .unwrap();
         assert!(created);
         assert_eq!(rec.name, "Judie Joan Guest");
@@ -308,10 +322,10 @@ mod tests {
     fn confirm_is_idempotent_on_same_key() {
         let (mut m, _tmp) = manager();
         let (r1, c1) = m
-            .confirm("phys-1", "Judie Guest", "1945-04-08", "sess-a", None)
+            .confirm("phys-1", "Judie Guest", "1945-04-08", "sess-a", None, None)
             .unwrap();
         let (r2, c2) = m
-            .confirm("phys-1", "Judie Guest", "1945-04-08", "sess-b", None)
+            .confirm("phys-1", "Judie Guest", "1945-04-08", "sess-b", None, None)
             .unwrap();
         assert!(c1);
         assert!(!c2);
@@ -322,9 +336,9 @@ mod tests {
     #[test]
     fn confirm_dedupes_repeat_session_ids() {
         let (mut m, _tmp) = manager();
-        m.confirm("phys-1", "A", "1950-01-01", "sess-a", None).unwrap();
-        m.confirm("phys-1", "A", "1950-01-01", "sess-a", None).unwrap();
-        let rec = m.confirm("phys-1", "A", "1950-01-01", "sess-b", None).unwrap().0;
+        m.confirm("phys-1", "A", "1950-01-01", "sess-a", None, None).unwrap();
+        m.confirm("phys-1", "A", "1950-01-01", "sess-a", None, None).unwrap();
+        let rec = m.confirm("phys-1", "A", "1950-01-01", "sess-b", None, None).unwrap().0;
         assert_eq!(rec.session_ids, vec!["sess-a", "sess-b"]);
     }
 
@@ -332,7 +346,7 @@ mod tests {
     fn confirm_reconciles_medplum_id_later() {
         let (mut m, _tmp) = manager();
         let (r1, _) = m
-            .confirm("phys-1", "A", "1950-01-01", "sess-a", None)
+            .confirm("phys-1", "A", "1950-01-01", "sess-a", None, None)
             .unwrap();
         let uuid_patient_id = r1.patient_id.clone();
         let (r2, created) = m
@@ -342,6 +356,7 @@ mod tests {
                 "1950-01-01",
                 "sess-b",
                 Some("mp-7".into()),
+                None,
             )
             .unwrap();
         assert!(!created);
@@ -352,24 +367,24 @@ mod tests {
     #[test]
     fn confirm_separates_different_dobs_with_same_name() {
         let (mut m, _tmp) = manager();
-        let (r1, _) = m.confirm("phys-1", "John Smith", "1970-01-01", "a", None).unwrap();
-        let (r2, _) = m.confirm("phys-1", "John Smith", "1980-02-02", "b", None).unwrap();
+        let (r1, _) = m.confirm("phys-1", "John Smith", "1970-01-01", "a", None, None).unwrap();
+        let (r2, _) = m.confirm("phys-1", "John Smith", "1980-02-02", "b", None, None).unwrap();
         assert_ne!(r1.patient_id, r2.patient_id);
     }
 
     #[test]
     fn confirm_separates_physicians() {
         let (mut m, _tmp) = manager();
-        let (r1, _) = m.confirm("phys-1", "A", "1990-01-01", "a", None).unwrap();
-        let (r2, _) = m.confirm("phys-2", "A", "1990-01-01", "a", None).unwrap();
+        let (r1, _) = m.confirm("phys-1", "A", "1990-01-01", "a", None, None).unwrap();
+        let (r2, _) = m.confirm("phys-2", "A", "1990-01-01", "a", None, None).unwrap();
         assert_ne!(r1.patient_id, r2.patient_id);
     }
 
     #[test]
     fn confirm_normalizes_name_variants() {
         let (mut m, _tmp) = manager();
-        let (r1, _) = m.confirm("phys-1", "  john SMITH ", "1970-01-01", "a", None).unwrap();
-        let (r2, _) = m.confirm("phys-1", "John Smith", "1970-01-01", "b", None).unwrap();
+        let (r1, _) = m.confirm("phys-1", "  john SMITH ", "1970-01-01", "a", None, None).unwrap();
+        let (r2, _) = m.confirm("phys-1", "John Smith", "1970-01-01", "b", None, None).unwrap();
         assert_eq!(r1.patient_id, r2.patient_id);
         assert_eq!(r2.session_ids, vec!["a", "b"]);
     }
@@ -377,8 +392,8 @@ mod tests {
     #[test]
     fn confirm_normalizes_surname_comma_given() {
         let (mut m, _tmp) = manager();
-        let (r1, _) = m.confirm("phys-1", "Guest, Judie Joan", "1945-04-08", "a", None).unwrap();
-        let (r2, _) = m.confirm("phys-1", "Judie Joan Guest", "1945-04-08", "b", None).unwrap();
+        let (r1, _) = m.confirm("phys-1", "Guest, Judie Joan", "1945-04-08", "a", None, None).unwrap();
+        let (r2, _) = m.confirm("phys-1", "Judie Joan Guest", "1945-04-08", "b", None, None).unwrap();
         assert_eq!(r1.patient_id, r2.patient_id);
         assert_eq!(r1.name, "Judie Joan Guest");
     }
@@ -386,26 +401,26 @@ mod tests {
     #[test]
     fn confirm_rejects_empty_physician_id() {
         let (mut m, _tmp) = manager();
-        assert!(m.confirm("", "A", "1990-01-01", "a", None).is_err());
+        assert!(m.confirm("", "A", "1990-01-01", "a", None, None).is_err());
     }
 
     #[test]
     fn confirm_rejects_bad_dob_format() {
         let (mut m, _tmp) = manager();
-        assert!(m.confirm("phys-1", "A", "not-a-date", "a", None).is_err());
-        assert!(m.confirm("phys-1", "A", "04/08/1945", "a", None).is_err());
+        assert!(m.confirm("phys-1", "A", "not-a-date", "a", None, None).is_err());
+        assert!(m.confirm("phys-1", "A", "04/08/1945", "a", None, None).is_err());
     }
 
     #[test]
     fn confirm_rejects_empty_name() {
         let (mut m, _tmp) = manager();
-        assert!(m.confirm("phys-1", "   ", "1990-01-01", "a", None).is_err());
+        assert!(m.confirm("phys-1", "   ", "1990-01-01", "a", None, None).is_err());
     }
 
     #[test]
     fn lookups_return_inserted_records() {
         let (mut m, _tmp) = manager();
-        let (r, _) = m.confirm("phys-1", "Judie Guest", "1945-04-08", "a", Some("mp-9".into())).unwrap();
+        let (r, _) = m.confirm("phys-1", "Judie Guest", "1945-04-08", "a", Some("mp-9".into()), None).unwrap();
         assert_eq!(
             m.get_by_name_dob("phys-1", "Judie Guest", "1945-04-08")
                 .map(|p| p.patient_id.clone()),
@@ -422,8 +437,8 @@ mod tests {
     #[test]
     fn delete_removes_record_and_reindexes() {
         let (mut m, _tmp) = manager();
-        let (r1, _) = m.confirm("phys-1", "Alice", "1990-01-01", "s1", None).unwrap();
-        let (r2, _) = m.confirm("phys-1", "Bob", "1980-01-01", "s2", None).unwrap();
+        let (r1, _) = m.confirm("phys-1", "Alice", "1990-01-01", "s1", None, None).unwrap();
+        let (r2, _) = m.confirm("phys-1", "Bob", "1980-01-01", "s2", None, None).unwrap();
         assert!(m.delete("phys-1", &r1.patient_id).unwrap());
         // r1 gone, r2 still retrievable
         assert!(m.get_by_patient_id("phys-1", &r1.patient_id).is_none());
@@ -444,9 +459,9 @@ mod tests {
     #[test]
     fn list_for_physician_filters_by_owner() {
         let (mut m, _tmp) = manager();
-        m.confirm("phys-1", "A", "1990-01-01", "a", None).unwrap();
-        m.confirm("phys-1", "B", "1980-01-01", "b", None).unwrap();
-        m.confirm("phys-2", "C", "1970-01-01", "c", None).unwrap();
+        m.confirm("phys-1", "A", "1990-01-01", "a", None, None).unwrap();
+        m.confirm("phys-1", "B", "1980-01-01", "b", None, None).unwrap();
+        m.confirm("phys-2", "C", "1970-01-01", "c", None, None).unwrap();
         let p1 = m.list_for_physician("phys-1");
         assert_eq!(p1.len(), 2);
         assert!(p1.iter().all(|r| r.physician_id == "phys-1"));
@@ -458,9 +473,9 @@ mod tests {
         let path = tmp.path().join("patients.json");
         {
             let mut m = PatientManager::load(path.clone()).unwrap();
-            m.confirm("phys-1", "Judie Guest", "1945-04-08", "a", Some("mp-1".into()))
+            m.confirm("phys-1", "Judie Guest", "1945-04-08", "a", Some("mp-1".into()), None)
                 .unwrap();
-            m.confirm("phys-1", "Judie Guest", "1945-04-08", "b", None)
+            m.confirm("phys-1", "Judie Guest", "1945-04-08", "b", None, None)
                 .unwrap();
         }
         let m = PatientManager::load(path).unwrap();
@@ -469,6 +484,22 @@ mod tests {
         assert_eq!(rec.medplum_patient_id.as_deref(), Some("mp-1"));
     }
 
+    #[test]
+    fn confirm_records_session_dates() {
+        let (mut m, _tmp) = manager();
+        m.confirm("phys-1", "A", "1950-01-01", "a", None, Some("2026-03-02"))
+            .unwrap();
+        let (rec, _) = m
+            .confirm("phys-1", "A", "1950-01-01", "b", None, None)
+            .unwrap();
+        assert_eq!(rec.session_ids, vec!["a", "b"]);
+        assert_eq!(rec.session_dates.len(), 1);
+        assert_eq!(rec.session_dates["a"], "2026-03-02");
+        assert!(m
+            .confirm("phys-1", "A", "1950-01-01", "c", None, Some("03/02/2026"))
+            .is_err());
+    }
+
     /// Verifies normalize_patient_name is byte-equivalent to the tauri-side
     /// implementation in patient_name_tracker.rs. Known inputs + expected
     /// outputs — if these diverge, idempotency of `confirm` breaks because
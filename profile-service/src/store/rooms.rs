@@ -102,6 +102,7 @@ impl RoomManager {
             whisper_model: None,
             debug_storage_enabled: None,
             input_device_id: None,
+            config_channel: None,
             created_at: now.clone(),
             updated_at: now,
         };
@@ -148,6 +149,7 @@ impl RoomManager {
         if req.whisper_model.is_some() { room.whisper_model = req.whisper_model; }
         if req.debug_storage_enabled.is_some() { room.debug_storage_enabled = req.debug_storage_enabled; }
         if req.input_device_id.is_some() { room.input_device_id = req.input_device_id; }
+        if req.config_channel.is_some() { room.config_channel = req.config_channel; }
 
         room.updated_at = Utc::now().to_rfc3339();
         let updated = room.clone();
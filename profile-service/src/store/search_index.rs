@@ -0,0 +1,422 @@
+//! Inverted index over archived transcripts and SOAP notes, one per
+//! physician.
+//!
+//! Held in memory only: with session encryption at rest a term index on
+//! disk would leak the very text it indexes. `SessionStore` builds a
+//! physician's index from their tree on first search and keeps it current
+//! as sessions are uploaded, edited, split, merged and deleted.
+//!
+//! Matching is AND over the query's non-stopword terms, with prefix
+//! expansion for terms of [`MIN_PREFIX_LEN`]+ characters ("statin" finds
+//! "statins"). Ranking is BM25F with SOAP hits weighted above transcript
+//! hits.
+
+use crate::types::{SearchField, SearchHit, SearchSnippet};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+pub const DEFAULT_LIMIT: usize = 20;
+pub const MAX_LIMIT: usize = 100;
+
+/// Shorter query terms only match whole words
+const MIN_PREFIX_LEN: usize = 3;
+
+/// Characters of context either side of the first hit in a snippet
+const SNIPPET_CONTEXT: usize = 80;
+
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+const SOAP_WEIGHT: f64 = 1.5;
+const TRANSCRIPT_WEIGHT: f64 = 1.0;
+
+/// Dropped from queries so natural phrasing ("when did we discuss the
+/// statin side effects") still matches. Documents are indexed in full.
+const STOPWORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "by", "did", "do", "for", "from", "had", "has",
+    "have", "i", "in", "is", "it", "of", "on", "or", "that", "the", "this", "to", "was", "we",
+    "were", "what", "when", "where", "which", "who", "with", "you",
+];
+
+/// A session as the index sees it
+#[derive(Debug, Clone)]
+pub struct SearchDoc {
+    pub session_id: String,
+    /// YYYY-MM-DD
+    pub date: String,
+    pub started_at: Option<String>,
+    pub patient_name: Option<String>,
+    pub encounter_number: Option<u32>,
+    pub transcript: String,
+    pub soap_note: String,
+}
+
+impl SearchDoc {
+    fn field(&self, field: SearchField) -> &str {
+        match field {
+            SearchField::Transcript => &self.transcript,
+            SearchField::SoapNote => &self.soap_note,
+        }
+    }
+}
+
+/// Term frequencies in each field
+#[derive(Debug, Default, Clone, Copy)]
+struct Postings {
+    transcript: u32,
+    soap_note: u32,
+}
+
+struct IndexedDoc {
+    doc: SearchDoc,
+    transcript_len: usize,
+    soap_len: usize,
+}
+
+#[derive(Default)]
+pub struct SearchIndex {
+    docs: HashMap<String, IndexedDoc>,
+    /// term → session_id → frequencies. Ordered for prefix scans.
+    terms: BTreeMap<String, HashMap<String, Postings>>,
+}
+
+/// A word in a text, with `[start, end)` character offsets
+struct Token {
+    term: String,
+    start: usize,
+    end: usize,
+}
+
+/// Lowercased alphanumeric runs
+fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut start = 0;
+    for (i, c) in text.chars().enumerate() {
+        if c.is_alphanumeric() {
+            if current.is_empty() {
+                start = i;
+            }
+            current.extend(c.to_lowercase());
+        } else if !current.is_empty() {
+            tokens.push(Token {
+                term: std::mem::take(&mut current),
+                start,
+                end: i,
+            });
+        }
+    }
+    if !current.is_empty() {
+        let end = text.chars().count();
+        tokens.push(Token {
+            term: current,
+            start,
+            end,
+        });
+    }
+    tokens
+}
+
+/// Distinct query terms, minus stopwords unless that would leave nothing
+fn query_terms(query: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    let all: Vec<String> = tokenize(query)
+        .into_iter()
+        .map(|t| t.term)
+        .filter(|t| seen.insert(t.clone()))
+        .collect();
+    let content: Vec<String> = all
+        .iter()
+        .filter(|t| !STOPWORDS.contains(&t.as_str()))
+        .cloned()
+        .collect();
+    if content.is_empty() {
+        all
+    } else {
+        content
+    }
+}
+
+impl SearchIndex {
+    pub fn len(&self) -> usize {
+        self.docs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.docs.is_empty()
+    }
+
+    /// Add or replace a session.
+    pub fn upsert(&mut self, doc: SearchDoc) {
+        self.remove(&doc.session_id);
+
+        let transcript = tokenize(&doc.transcript);
+        let soap = tokenize(&doc.soap_note);
+        for t in &transcript {
+            self.postings_mut(&t.term, &doc.session_id).transcript += 1;
+        }
+        for t in &soap {
+            self.postings_mut(&t.term, &doc.session_id).soap_note += 1;
+        }
+        self.docs.insert(
+            doc.session_id.clone(),
+            IndexedDoc {
+                transcript_len: transcript.len(),
+                soap_len: soap.len(),
+                doc,
+            },
+        );
+    }
+
+    pub fn remove(&mut self, session_id: &str) {
+        let Some(old) = self.docs.remove(session_id) else {
+            return;
+        };
+        let terms: HashSet<String> = tokenize(&old.doc.transcript)
+            .into_iter()
+            .chain(tokenize(&old.doc.soap_note))
+            .map(|t| t.term)
+            .collect();
+        for term in terms {
+            if let Some(postings) = self.terms.get_mut(&term) {
+                postings.remove(session_id);
+                if postings.is_empty() {
+                    self.terms.remove(&term);
+                }
+            }
+        }
+    }
+
+    fn postings_mut(&mut self, term: &str, session_id: &str) -> &mut Postings {
+        self.terms
+            .entry(term.to_string())
+            .or_default()
+            .entry(session_id.to_string())
+            .or_default()
+    }
+
+    /// Indexed terms a query term matches: itself, plus longer words it
+    /// prefixes when long enough.
+    fn expand(&self, query_term: &str) -> Vec<&str> {
+        if query_term.chars().count() < MIN_PREFIX_LEN {
+            return self
+                .terms
+                .get_key_value(query_term)
+                .map(|(k, _)| vec![k.as_str()])
+                .unwrap_or_default();
+        }
+        self.terms
+            .range(query_term.to_string()..)
+            .take_while(|(k, _)| k.starts_with(query_term))
+            .map(|(k, _)| k.as_str())
+            .collect()
+    }
+
+    /// Ranked sessions containing every query term, optionally limited to
+    /// dates in `[from, to]` (inclusive, YYYY-MM-DD).
+    pub fn search(
+        &self,
+        query: &str,
+        from: Option<&str>,
+        to: Option<&str>,
+        limit: usize,
+    ) -> Vec<SearchHit> {
+        let terms = query_terms(query);
+        if terms.is_empty() || self.docs.is_empty() {
+            return Vec::new();
+        }
+
+        let n = self.docs.len() as f64;
+        let avg_transcript =
+            (self.docs.values().map(|d| d.transcript_len).sum::<usize>() as f64 / n).max(1.0);
+        let avg_soap = (self.docs.values().map(|d| d.soap_len).sum::<usize>() as f64 / n).max(1.0);
+
+        // Per query term: the expanded index terms, and per-session scores
+        let mut matched_terms: HashSet<&str> = HashSet::new();
+        let mut scores: Option<HashMap<&str, f64>> = None;
+        for term in &terms {
+            let expansions = self.expand(term);
+            matched_terms.extend(expansions.iter().copied());
+
+            let mut per_doc: HashMap<&str, Postings> = HashMap::new();
+            for exp in &expansions {
+                for (sid, p) in &self.terms[*exp] {
+                    let acc = per_doc.entry(sid.as_str()).or_default();
+                    acc.transcript += p.transcript;
+                    acc.soap_note += p.soap_note;
+                }
+            }
+
+            let df = per_doc.len() as f64;
+            let idf = (1.0 + (n - df + 0.5) / (df + 0.5)).ln();
+            let term_scores: HashMap<&str, f64> = per_doc
+                .into_iter()
+                .map(|(sid, p)| {
+                    let d = &self.docs[sid];
+                    let tf = TRANSCRIPT_WEIGHT * p.transcript as f64
+                        / (1.0 - B + B * d.transcript_len as f64 / avg_transcript)
+                        + SOAP_WEIGHT * p.soap_note as f64
+                            / (1.0 - B + B * d.soap_len as f64 / avg_soap);
+                    (sid, idf * tf * (K1 + 1.0) / (tf + K1))
+                })
+                .collect();
+
+            // AND: keep only sessions that matched every term so far
+            scores = Some(match scores {
+                None => term_scores,
+                Some(prev) => prev
+                    .into_iter()
+                    .filter_map(|(sid, s)| term_scores.get(sid).map(|t| (sid, s + t)))
+                    .collect(),
+            });
+        }
+
+        let mut hits: Vec<SearchHit> = scores
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(sid, score)| (&self.docs[sid].doc, score))
+            .filter(|(doc, _)| from.is_none_or(|f| doc.date.as_str() >= f))
+            .filter(|(doc, _)| to.is_none_or(|t| doc.date.as_str() <= t))
+            .map(|(doc, score)| SearchHit {
+                session_id: doc.session_id.clone(),
+                date: doc.date.clone(),
+                started_at: doc.started_at.clone(),
+                patient_name: doc.patient_name.clone(),
+                encounter_number: doc.encounter_number,
+                score,
+                snippets: [SearchField::SoapNote, SearchField::Transcript]
+                    .into_iter()
+                    .filter_map(|f| snippet(f, doc.field(f), &matched_terms))
+                    .collect(),
+            })
+            .collect();
+
+        hits.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| b.date.cmp(&a.date))
+                .then_with(|| a.session_id.cmp(&b.session_id))
+        });
+        hits.truncate(limit);
+        hits
+    }
+}
+
+/// Window around the first matched term in `text`, with every match inside
+/// it highlighted. `None` if the field has no match.
+fn snippet(field: SearchField, text: &str, matched: &HashSet<&str>) -> Option<SearchSnippet> {
+    let tokens = tokenize(text);
+    let first = tokens.iter().find(|t| matched.contains(t.term.as_str()))?;
+
+    let chars: Vec<char> = text.chars().collect();
+    let mut start = first.start.saturating_sub(SNIPPET_CONTEXT);
+    let mut end = (first.end + SNIPPET_CONTEXT).min(chars.len());
+    // Don't cut words in half
+    while start > 0 && chars[start - 1].is_alphanumeric() {
+        start -= 1;
+    }
+    while end < chars.len() && chars[end].is_alphanumeric() {
+        end += 1;
+    }
+
+    let prefix = if start > 0 { "…" } else { "" };
+    let suffix = if end < chars.len() { "…" } else { "" };
+    let offset = prefix.chars().count();
+    let body: String = chars[start..end].iter().collect::<String>().replace('\n', " ");
+    let highlights = tokens
+        .iter()
+        .filter(|t| t.start >= start && t.end <= end && matched.contains(t.term.as_str()))
+        .map(|t| [t.start - start + offset, t.end - start + offset])
+        .collect();
+
+    Some(SearchSnippet {
+        field,
+        text: format!("{prefix}{body}{suffix}"),
+        highlights,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doc(id: &str, date: &str, transcript: &str, soap: &str) -> SearchDoc {
+        SearchDoc {
+            session_id: id.into(),
+            date: date.into(),
+            started_at: None,
+            patient_name: None,
+            encounter_number: None,
+            transcript: transcript.into(),
+            soap_note: soap.into(),
+        }
+    }
+
+    fn index() -> SearchIndex {
+        let mut idx = SearchIndex::default();
+        idx.upsert(doc(
+            "a",
+            "2026-01-10",
+            "We talked about the statin. Muscle aches are a known side effect.",
+            "A/P: Statins — discussed side effects, continue atorvastatin.",
+        ));
+        idx.upsert(doc("b", "2026-02-03", "Knee pain after running.", "A/P: Knee strain."));
+        idx.upsert(doc("c", "2026-03-01", "Side effects of the new inhaler.", ""));
+        idx
+    }
+
+    #[test]
+    fn natural_question_matches_with_prefix_expansion() {
+        let hits = index().search("did we discuss the statin side effects", None, None, 10);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].session_id, "a");
+    }
+
+    #[test]
+    fn all_terms_required() {
+        let hits = index().search("side effects", None, None, 10);
+        let ids: Vec<_> = hits.iter().map(|h| h.session_id.as_str()).collect();
+        assert_eq!(ids.len(), 2);
+        assert!(ids.contains(&"a") && ids.contains(&"c"));
+        assert!(index().search("knee statin", None, None, 10).is_empty());
+    }
+
+    #[test]
+    fn soap_hits_outrank_transcript_hits() {
+        let mut idx = SearchIndex::default();
+        idx.upsert(doc("t", "2026-01-01", "Discussed gout flare.", "Follow up in May."));
+        idx.upsert(doc("s", "2026-01-01", "Follow up in May.", "Discussed gout flare."));
+        let hits = idx.search("gout", None, None, 10);
+        assert_eq!(hits[0].session_id, "s");
+        assert!(hits[0].score > hits[1].score);
+    }
+
+    #[test]
+    fn date_filters_are_inclusive() {
+        let idx = index();
+        let hits = idx.search("side effects", Some("2026-03-01"), None, 10);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].session_id, "c");
+        let hits = idx.search("side effects", None, Some("2026-01-10"), 10);
+        assert_eq!(hits[0].session_id, "a");
+    }
+
+    #[test]
+    fn snippets_highlight_matches() {
+        let hits = index().search("knee", None, None, 10);
+        let s = &hits[0].snippets[0];
+        assert_eq!(s.field, SearchField::SoapNote);
+        let chars: Vec<char> = s.text.chars().collect();
+        let [start, end] = s.highlights[0];
+        assert_eq!(chars[start..end].iter().collect::<String>(), "Knee");
+    }
+
+    #[test]
+    fn upsert_replaces_and_remove_forgets() {
+        let mut idx = index();
+        idx.upsert(doc("b", "2026-02-03", "Shoulder pain.", ""));
+        assert!(idx.search("knee", None, None, 10).is_empty());
+        assert_eq!(idx.search("shoulder", None, None, 10).len(), 1);
+        idx.remove("b");
+        assert!(idx.search("shoulder", None, None, 10).is_empty());
+        assert_eq!(idx.len(), 2);
+    }
+}
@@ -1,10 +1,14 @@
 use crate::error::ApiError;
+use crate::store::encryption::{self, SessionCipher};
+use crate::store::search_index::{SearchDoc, SearchIndex};
 use crate::types::{
-    ArchiveDetails, ArchiveMetadata, ArchiveSummary, ArchivedPatientNote, SessionFeedback,
+    ArchiveDetails, ArchiveMetadata, ArchiveSummary, ArchivedPatientNote, SearchHit,
+    SessionFeedback,
 };
 use chrono::{Datelike, NaiveDate};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use tracing::info;
 use uuid::Uuid;
 
@@ -74,19 +78,50 @@ fn is_allowed_session_file(filename: &str) -> bool {
     false
 }
 
+/// Counts from [`SessionStore::encrypt_existing`]
+#[derive(Debug, Default, serde::Serialize)]
+pub struct EncryptReport {
+    pub encrypted: usize,
+    pub already_encrypted: usize,
+}
+
+/// A physician's full-text index, or the build in progress.
+enum SearchSlot {
+    /// The builder holds `done`'s lock until the slot turns `Ready`; other
+    /// searches wait on it. Changes made during the walk are queued in
+    /// `pending` (session ID, and its directory unless it was deleted) and
+    /// replayed onto the result, so none are lost.
+    Building {
+        done: Arc<tokio::sync::Mutex<()>>,
+        pending: Vec<(String, Option<PathBuf>)>,
+    },
+    Ready(SearchIndex),
+}
+
 pub struct SessionStore {
     base_dir: PathBuf,
     session_cache: tokio::sync::RwLock<HashMap<(String, String), PathBuf>>,
+    /// Encryption at rest; `None` stores plaintext (no master key configured)
+    cipher: Option<SessionCipher>,
+    /// Full-text indexes by physician, built on first search. The map lock
+    /// is only held briefly; builds walk the tree without it.
+    search: tokio::sync::RwLock<HashMap<String, SearchSlot>>,
 }
 
 impl SessionStore {
-    pub fn new(base_dir: PathBuf) -> Self {
+    pub fn new(base_dir: PathBuf, cipher: Option<SessionCipher>) -> Self {
         Self {
             base_dir,
             session_cache: tokio::sync::RwLock::new(HashMap::new()),
+            cipher,
+            search: tokio::sync::RwLock::new(HashMap::new()),
         }
     }
 
+    pub fn is_encrypted(&self) -> bool {
+        self.cipher.is_some()
+    }
+
     /// sessions/{physician_id}/{YYYY}/{MM}/{DD}/{session_id}/
     fn session_dir(
         &self,
@@ -138,14 +173,14 @@ impl SessionStore {
         // Write metadata atomically
         let meta_json = serde_json::to_string_pretty(metadata)
             .map_err(|e| ApiError::Internal(format!("Failed to serialize metadata: {e}")))?;
-        atomic_write(&dir.join("metadata.json"), &meta_json).await?;
+        self.write_file(&dir.join("metadata.json"), meta_json.as_bytes()).await?;
 
         // Write transcript atomically
-        atomic_write(&dir.join("transcript.txt"), transcript.as_bytes()).await?;
+        self.write_file(&dir.join("transcript.txt"), transcript.as_bytes()).await?;
 
         // Write SOAP if provided (atomically)
         if let Some(soap) = soap_note {
-            atomic_write(&dir.join("soap_note.txt"), soap.as_bytes()).await?;
+            self.write_file(&dir.join("soap_note.txt"), soap.as_bytes()).await?;
         }
 
         // Cache this session path
@@ -157,6 +192,7 @@ impl SessionStore {
             );
         }
 
+        self.reindex_session(physician_id, session_id, &dir).await;
         info!(physician_id, session_id, "Session uploaded");
         Ok(())
     }
@@ -172,7 +208,7 @@ impl SessionStore {
     ) -> Result<(), ApiError> {
         let dir = self.find_session_dir(physician_id, session_id).await?;
 
-        atomic_write(&dir.join("soap_note.txt"), content.as_bytes()).await?;
+        self.write_file(&dir.join("soap_note.txt"), content.as_bytes()).await?;
 
         // Update metadata
         if let Ok(mut meta) = self.read_metadata(&dir).await {
@@ -182,6 +218,7 @@ impl SessionStore {
             self.write_metadata(&dir, &meta).await?;
         }
 
+        self.reindex_session(physician_id, session_id, &dir).await;
         info!(physician_id, session_id, "SOAP updated");
         Ok(())
     }
@@ -195,6 +232,7 @@ impl SessionStore {
     ) -> Result<(), ApiError> {
         let dir = self.find_session_dir(physician_id, session_id).await?;
         self.write_metadata(&dir, metadata).await?;
+        self.reindex_session(physician_id, session_id, &dir).await;
         info!(physician_id, session_id, "Metadata updated");
         Ok(())
     }
@@ -209,9 +247,7 @@ impl SessionStore {
     ) -> Result<(), ApiError> {
         let dir = self.find_session_dir(physician_id, session_id).await?;
         let meta_path = dir.join("metadata.json");
-        let existing_json = tokio::fs::read_to_string(&meta_path)
-            .await
-            .map_err(|e| ApiError::Internal(format!("Failed to read metadata: {e}")))?;
+        let existing_json = self.read_text(&meta_path).await?;
         let mut existing: serde_json::Value = serde_json::from_str(&existing_json)
             .map_err(|e| ApiError::Internal(format!("Failed to parse metadata: {e}")))?;
 
@@ -226,7 +262,8 @@ impl SessionStore {
 
         let merged_json = serde_json::to_string_pretty(&existing)
             .map_err(|e| ApiError::Internal(format!("Failed to serialize metadata: {e}")))?;
-        atomic_write(&meta_path, &merged_json).await?;
+        self.write_file(&meta_path, merged_json.as_bytes()).await?;
+        self.reindex_session(physician_id, session_id, &dir).await;
         info!(physician_id, session_id, "Metadata patched");
         Ok(())
     }
@@ -241,7 +278,7 @@ impl SessionStore {
         let dir = self.find_session_dir(physician_id, session_id).await?;
         let json = serde_json::to_string_pretty(feedback)
             .map_err(|e| ApiError::Internal(format!("Failed to serialize feedback: {e}")))?;
-        atomic_write(&dir.join("feedback.json"), &json).await?;
+        self.write_file(&dir.join("feedback.json"), json.as_bytes()).await?;
         info!(physician_id, session_id, "Feedback updated");
         Ok(())
     }
@@ -257,6 +294,7 @@ impl SessionStore {
         let mut meta = self.read_metadata(&dir).await?;
         meta.patient_name = Some(patient_name.to_string());
         self.write_metadata(&dir, &meta).await?;
+        self.reindex_session(physician_id, session_id, &dir).await;
         info!(physician_id, session_id, patient_name, "Patient name updated");
         Ok(())
     }
@@ -270,8 +308,8 @@ impl SessionStore {
         let dir = self.find_session_dir(physician_id, session_id).await?;
         let metadata = self.read_metadata(&dir).await?;
 
-        let transcript = read_optional_file(&dir.join("transcript.txt")).await;
-        let soap_note = read_optional_file(&dir.join("soap_note.txt")).await;
+        let transcript = self.read_optional_text(&dir.join("transcript.txt")).await;
+        let soap_note = self.read_optional_text(&dir.join("soap_note.txt")).await;
 
         let audio_path = if dir.join("audio.wav").exists() {
             Some(dir.join("audio.wav").to_string_lossy().to_string())
@@ -303,9 +341,7 @@ impl SessionStore {
         if !path.exists() {
             return Ok(None);
         }
-        let content = tokio::fs::read_to_string(&path)
-            .await
-            .map_err(|e| ApiError::Internal(format!("Failed to read feedback: {e}")))?;
+        let content = self.read_text(&path).await?;
         let feedback: SessionFeedback = serde_json::from_str(&content)
             .map_err(|e| ApiError::Internal(format!("Failed to parse feedback: {e}")))?;
         Ok(Some(feedback))
@@ -327,6 +363,7 @@ impl SessionStore {
             let mut cache = self.session_cache.write().await;
             cache.remove(&(physician_id.to_string(), session_id.to_string()));
         }
+        self.unindex_session(physician_id, session_id).await;
 
         info!(physician_id, session_id, "Session deleted");
         Ok(())
@@ -343,9 +380,7 @@ impl SessionStore {
         if !path.exists() {
             return Ok(Vec::new());
         }
-        let content = tokio::fs::read_to_string(&path)
-            .await
-            .map_err(|e| ApiError::Internal(format!("Failed to read transcript: {e}")))?;
+        let content = self.read_text(&path).await?;
         Ok(content.lines().map(|l| l.to_string()).collect())
     }
 
@@ -361,9 +396,7 @@ impl SessionStore {
         let date = parse_date_from_started_at(&meta.started_at)?;
 
         let transcript_path = dir.join("transcript.txt");
-        let content = tokio::fs::read_to_string(&transcript_path)
-            .await
-            .map_err(|e| ApiError::Internal(format!("Failed to read transcript: {e}")))?;
+        let content = self.read_text(&transcript_path).await?;
         let lines: Vec<&str> = content.lines().collect();
 
         if split_line == 0 || split_line >= lines.len() {
@@ -383,7 +416,7 @@ impl SessionStore {
         let second_half = lines[split_line..].join("\n");
 
         // Update original session atomically
-        atomic_write(&transcript_path, first_half.as_bytes()).await?;
+        self.write_file(&transcript_path, first_half.as_bytes()).await?;
         let mut first_meta = meta.clone();
         first_meta.word_count = first_half.split_whitespace().count();
         first_meta.has_soap_note = false;
@@ -398,7 +431,7 @@ impl SessionStore {
             .await
             .map_err(|e| ApiError::Internal(format!("Failed to create new session dir: {e}")))?;
 
-        atomic_write(&new_dir.join("transcript.txt"), second_half.as_bytes()).await?;
+        self.write_file(&new_dir.join("transcript.txt"), second_half.as_bytes()).await?;
 
         let mut second_meta = meta;
         second_meta.session_id = new_id.clone();
@@ -416,9 +449,11 @@ impl SessionStore {
             let mut cache = self.session_cache.write().await;
             cache.insert(
                 (physician_id.to_string(), new_id.clone()),
-                new_dir,
+                new_dir.clone(),
             );
         }
+        self.reindex_session(physician_id, session_id, &dir).await;
+        self.reindex_session(physician_id, &new_id, &new_dir).await;
 
         info!(
             physician_id,
@@ -462,10 +497,12 @@ impl SessionStore {
         }
 
         let meta_a = self.read_metadata(&dir_a).await?;
-        let transcript_a = read_optional_file(&dir_a.join("transcript.txt"))
+        let transcript_a = self
+            .read_optional_text(&dir_a.join("transcript.txt"))
             .await
             .unwrap_or_default();
-        let transcript_b = read_optional_file(&dir_b.join("transcript.txt"))
+        let transcript_b = self
+            .read_optional_text(&dir_b.join("transcript.txt"))
             .await
             .unwrap_or_default();
 
@@ -485,7 +522,7 @@ impl SessionStore {
 
         // Merge into first session atomically
         let merged_transcript = format!("{}\n{}", transcript_a.trim(), transcript_b.trim());
-        atomic_write(&dir_a.join("transcript.txt"), merged_transcript.as_bytes()).await?;
+        self.write_file(&dir_a.join("transcript.txt"), merged_transcript.as_bytes()).await?;
 
         let mut merged_meta = meta_a;
         merged_meta.word_count = merged_transcript.split_whitespace().count();
@@ -502,6 +539,8 @@ impl SessionStore {
             let mut cache = self.session_cache.write().await;
             cache.remove(&(physician_id.to_string(), session_ids[1].clone()));
         }
+        self.reindex_session(physician_id, &session_ids[0], &dir_a).await;
+        self.unindex_session(physician_id, &session_ids[1]).await;
 
         info!(
             physician_id,
@@ -666,7 +705,7 @@ impl SessionStore {
                 let feedback_path = path.join("feedback.json");
                 let has_feedback = feedback_path.exists();
                 let quality_rating = if has_feedback {
-                    read_quality_rating(&feedback_path).await
+                    self.read_quality_rating(&feedback_path).await
                 } else {
                     None
                 };
@@ -705,18 +744,18 @@ impl SessionStore {
         Ok(sessions)
     }
 
-    /// Get the audio file path for a session
-    pub async fn get_audio_path(
+    /// Read a session's audio (decrypted)
+    pub async fn get_audio(
         &self,
         physician_id: &str,
         session_id: &str,
-    ) -> Result<PathBuf, ApiError> {
+    ) -> Result<Vec<u8>, ApiError> {
         let dir = self.find_session_dir(physician_id, session_id).await?;
         let path = dir.join("audio.wav");
         if !path.exists() {
             return Err(ApiError::NotFound("Audio file not found".to_string()));
         }
-        Ok(path)
+        self.read_file(&path).await
     }
 
     /// Save audio file for a session
@@ -727,10 +766,7 @@ impl SessionStore {
         data: &[u8],
     ) -> Result<(), ApiError> {
         let dir = self.find_session_dir(physician_id, session_id).await?;
-        let path = dir.join("audio.wav");
-        tokio::fs::write(&path, data)
-            .await
-            .map_err(|e| ApiError::Internal(format!("Failed to write audio: {e}")))?;
+        self.write_file(&dir.join("audio.wav"), data).await?;
 
         // Update metadata
         if let Ok(mut meta) = self.read_metadata(&dir).await {
@@ -742,6 +778,57 @@ impl SessionStore {
         Ok(())
     }
 
+    /// Save a session's audio from a staged file, which is consumed.
+    ///
+    /// Without encryption the file is moved into place and never read into
+    /// memory. Sealed files are a single AES-GCM message, so with encryption
+    /// it is read and sealed whole, as [`Self::save_audio`] does.
+    pub async fn save_audio_file(
+        &self,
+        physician_id: &str,
+        session_id: &str,
+        src: &Path,
+    ) -> Result<(), ApiError> {
+        let dir = self.find_session_dir(physician_id, session_id).await?;
+        let dest = dir.join("audio.wav");
+        if self.cipher.is_some() {
+            let data = tokio::fs::read(src)
+                .await
+                .map_err(|e| ApiError::Internal(format!("Failed to read audio: {e}")))?;
+            self.write_file(&dest, &data).await?;
+            let _ = tokio::fs::remove_file(src).await;
+        } else if tokio::fs::rename(src, &dest).await.is_err() {
+            // Different filesystem: copy beside the target, then swap in
+            let tmp_path = dest.with_extension(format!("{}.tmp", Uuid::new_v4()));
+            tokio::fs::copy(src, &tmp_path)
+                .await
+                .map_err(|e| ApiError::Internal(format!("Failed to copy audio: {e}")))?;
+            tokio::fs::rename(&tmp_path, &dest).await.map_err(|e| {
+                let _ = std::fs::remove_file(&tmp_path);
+                ApiError::Internal(format!("Failed to rename: {e}"))
+            })?;
+            let _ = tokio::fs::remove_file(src).await;
+        }
+
+        // Update metadata
+        if let Ok(mut meta) = self.read_metadata(&dir).await {
+            meta.has_audio = true;
+            let _ = self.write_metadata(&dir, &meta).await;
+        }
+
+        info!(physician_id, session_id, "Audio saved from upload");
+        Ok(())
+    }
+
+    /// Check that a session exists, without reading it.
+    pub async fn require_session(
+        &self,
+        physician_id: &str,
+        session_id: &str,
+    ) -> Result<(), ApiError> {
+        self.find_session_dir(physician_id, session_id).await.map(|_| ())
+    }
+
     /// Save an auxiliary file to a session directory
     pub async fn save_session_file(
         &self,
@@ -759,9 +846,10 @@ impl SessionStore {
         if let Some(parent) = file_path.parent() {
             let _ = tokio::fs::create_dir_all(parent).await;
         }
-        tokio::fs::write(&file_path, data)
-            .await
-            .map_err(|e| ApiError::Internal(format!("Failed to write {filename}: {e}")))?;
+        self.write_file(&file_path, data).await?;
+        if filename.starts_with("soap_patient_") {
+            self.reindex_session(physician_id, session_id, &dir).await;
+        }
         info!(physician_id, session_id, filename, bytes = data.len(), "Session file saved");
         Ok(())
     }
@@ -781,9 +869,7 @@ impl SessionStore {
         if !path.exists() {
             return Err(ApiError::NotFound(format!("File not found: {filename}")));
         }
-        tokio::fs::read(&path)
-            .await
-            .map_err(|e| ApiError::Internal(format!("Failed to read {filename}: {e}")))
+        self.read_file(&path).await
     }
 
     /// Save day log to a date directory
@@ -799,9 +885,7 @@ impl SessionStore {
         tokio::fs::create_dir_all(&dir)
             .await
             .map_err(|e| ApiError::Internal(format!("Failed to create date dir: {e}")))?;
-        tokio::fs::write(dir.join("day_log.jsonl"), data)
-            .await
-            .map_err(|e| ApiError::Internal(format!("Failed to write day_log: {e}")))?;
+        self.write_file(&dir.join("day_log.jsonl"), data).await?;
         info!(physician_id, date = date_str, bytes = data.len(), "Day log saved");
         Ok(())
     }
@@ -819,13 +903,223 @@ impl SessionStore {
         if !path.exists() {
             return Err(ApiError::NotFound("Day log not found".to_string()));
         }
-        tokio::fs::read(&path)
-            .await
-            .map_err(|e| ApiError::Internal(format!("Failed to read day_log: {e}")))
+        self.read_file(&path).await
+    }
+
+    /// Full-text search over a physician's transcripts and SOAP notes,
+    /// including per-patient notes. The index is built from disk on the first
+    /// search after startup.
+    pub async fn search(
+        &self,
+        physician_id: &str,
+        query: &str,
+        from: Option<&str>,
+        to: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<SearchHit>, ApiError> {
+        validate_id(physician_id, "Physician ID")?;
+        for (label, date) in [("from", from), ("to", to)] {
+            if let Some(d) = date {
+                NaiveDate::parse_from_str(d, "%Y-%m-%d")
+                    .map_err(|e| ApiError::BadRequest(format!("Invalid {label} date: {e}")))?;
+            }
+        }
+
+        loop {
+            let building = match self.search.read().await.get(physician_id) {
+                Some(SearchSlot::Ready(index)) => return Ok(index.search(query, from, to, limit)),
+                Some(SearchSlot::Building { done, .. }) => Some(done.clone()),
+                None => None,
+            };
+            if let Some(done) = building {
+                // Wait for the build in progress, then look again
+                let _finished = done.lock().await;
+                let mut indexes = self.search.write().await;
+                // Still building under the same lock: its request was dropped
+                if matches!(
+                    indexes.get(physician_id),
+                    Some(SearchSlot::Building { done: d, .. }) if Arc::ptr_eq(d, &done)
+                ) {
+                    indexes.remove(physician_id);
+                }
+                continue;
+            }
+
+            let done = Arc::new(tokio::sync::Mutex::new(()));
+            let guard = done.clone().lock_owned().await;
+            {
+                let mut indexes = self.search.write().await;
+                if indexes.contains_key(physician_id) {
+                    continue;
+                }
+                indexes.insert(
+                    physician_id.to_string(),
+                    SearchSlot::Building {
+                        done,
+                        pending: Vec::new(),
+                    },
+                );
+            }
+            let index = match self.build_search_index(physician_id).await {
+                Ok(index) => index,
+                Err(e) => {
+                    self.search.write().await.remove(physician_id);
+                    return Err(e);
+                }
+            };
+            let hits = self
+                .finish_search_build(physician_id, index, query, from, to, limit)
+                .await;
+            drop(guard);
+            return Ok(hits);
+        }
     }
 
     // ── Helpers ────────────────────────────────────────────────────
 
+    /// Index every session in a physician's tree.
+    async fn build_search_index(&self, physician_id: &str) -> Result<SearchIndex, ApiError> {
+        let mut index = SearchIndex::default();
+        for date_str in self.list_dates(physician_id, None, None).await? {
+            let Ok(date) = NaiveDate::parse_from_str(&date_str, "%Y-%m-%d") else {
+                continue;
+            };
+            let mut entries = match tokio::fs::read_dir(self.date_dir(physician_id, &date)?).await {
+                Ok(e) => e,
+                Err(_) => continue,
+            };
+            while let Ok(Some(entry)) = entries.next_entry().await {
+                let path = entry.path();
+                if !path.is_dir() {
+                    continue;
+                }
+                if let Some(doc) = self.search_doc(&path).await {
+                    index.upsert(doc);
+                }
+            }
+        }
+        info!(physician_id, sessions = index.len(), "Search index built");
+        Ok(index)
+    }
+
+    /// Replay the changes queued while `index` was built, until none are
+    /// left, then publish it and run the first query.
+    async fn finish_search_build(
+        &self,
+        physician_id: &str,
+        mut index: SearchIndex,
+        query: &str,
+        from: Option<&str>,
+        to: Option<&str>,
+        limit: usize,
+    ) -> Vec<SearchHit> {
+        loop {
+            let pending = {
+                let mut indexes = self.search.write().await;
+                let pending = match indexes.get_mut(physician_id) {
+                    Some(SearchSlot::Building { pending, .. }) => std::mem::take(pending),
+                    _ => Vec::new(),
+                };
+                if pending.is_empty() {
+                    let hits = index.search(query, from, to, limit);
+                    indexes.insert(physician_id.to_string(), SearchSlot::Ready(index));
+                    return hits;
+                }
+                pending
+            };
+            for (session_id, dir) in pending {
+                let doc = match dir {
+                    Some(dir) => self.search_doc(&dir).await,
+                    None => None,
+                };
+                match doc {
+                    Some(doc) => index.upsert(doc),
+                    None => index.remove(&session_id),
+                }
+            }
+        }
+    }
+
+    async fn search_doc(&self, session_dir: &Path) -> Option<SearchDoc> {
+        let session_id = session_dir.file_name()?.to_str()?.to_string();
+        let meta = self.read_metadata(session_dir).await.ok()?;
+        let date = parse_date_from_started_at(&meta.started_at).ok()?;
+        Some(SearchDoc {
+            session_id,
+            date: date.format("%Y-%m-%d").to_string(),
+            started_at: Some(meta.started_at),
+            patient_name: meta.patient_name,
+            encounter_number: meta.encounter_number,
+            transcript: self
+                .read_optional_text(&session_dir.join("transcript.txt"))
+                .await
+                .unwrap_or_default(),
+            soap_note: self.read_soap_text(session_dir).await,
+        })
+    }
+
+    /// `soap_note.txt` followed by any per-patient `soap_patient_N.txt` notes
+    /// from multi-patient encounters.
+    async fn read_soap_text(&self, session_dir: &Path) -> String {
+        let mut text = self
+            .read_optional_text(&session_dir.join("soap_note.txt"))
+            .await
+            .unwrap_or_default();
+        let mut patient_files = Vec::new();
+        if let Ok(mut entries) = tokio::fs::read_dir(session_dir).await {
+            while let Ok(Some(entry)) = entries.next_entry().await {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                if name.starts_with("soap_patient_") && name.ends_with(".txt") {
+                    patient_files.push(name);
+                }
+            }
+        }
+        patient_files.sort();
+        for name in patient_files {
+            if let Some(note) = self.read_optional_text(&session_dir.join(&name)).await {
+                text.push('\n');
+                text.push_str(&note);
+            }
+        }
+        text
+    }
+
+    /// Refresh one session in the physician's index, if it has been built,
+    /// or queue it for the build in progress.
+    async fn reindex_session(&self, physician_id: &str, session_id: &str, dir: &Path) {
+        loop {
+            let ready = match self.search.read().await.get(physician_id) {
+                None => return,
+                Some(slot) => matches!(slot, SearchSlot::Ready(_)),
+            };
+            // The builder re-reads queued sessions itself
+            let doc = if ready { self.search_doc(dir).await } else { None };
+            match self.search.write().await.get_mut(physician_id) {
+                Some(SearchSlot::Ready(index)) if ready => match doc {
+                    Some(doc) => index.upsert(doc),
+                    None => index.remove(session_id),
+                },
+                // The build finished in between: read the session after all
+                Some(SearchSlot::Ready(_)) => continue,
+                Some(SearchSlot::Building { pending, .. }) => {
+                    pending.push((session_id.to_string(), Some(dir.to_path_buf())))
+                }
+                None => {}
+            }
+            return;
+        }
+    }
+
+    async fn unindex_session(&self, physician_id: &str, session_id: &str) {
+        match self.search.write().await.get_mut(physician_id) {
+            Some(SearchSlot::Ready(index)) => index.remove(session_id),
+            Some(SearchSlot::Building { pending, .. }) => {
+                pending.push((session_id.to_string(), None))
+            }
+            None => {}
+        }
+    }
+
     /// Find session directory by scanning date directories (session_id is unique).
     /// Results are cached in memory to avoid repeated directory walks.
     async fn find_session_dir(
@@ -906,11 +1200,8 @@ impl SessionStore {
         )))
     }
 
-    async fn read_metadata(&self, session_dir: &PathBuf) -> Result<ArchiveMetadata, ApiError> {
-        let path = session_dir.join("metadata.json");
-        let content = tokio::fs::read_to_string(&path)
-            .await
-            .map_err(|e| ApiError::Internal(format!("Failed to read metadata: {e}")))?;
+    async fn read_metadata(&self, session_dir: &Path) -> Result<ArchiveMetadata, ApiError> {
+        let content = self.read_text(&session_dir.join("metadata.json")).await?;
         serde_json::from_str(&content)
             .map_err(|e| ApiError::Internal(format!("Failed to parse metadata: {e}")))
     }
@@ -922,7 +1213,7 @@ impl SessionStore {
     ) -> Result<(), ApiError> {
         let json = serde_json::to_string_pretty(metadata)
             .map_err(|e| ApiError::Internal(format!("Failed to serialize metadata: {e}")))?;
-        atomic_write(&session_dir.join("metadata.json"), &json).await
+        self.write_file(&session_dir.join("metadata.json"), json.as_bytes()).await
     }
 
     async fn read_patient_notes(&self, session_dir: &PathBuf) -> Option<Vec<ArchivedPatientNote>> {
@@ -930,7 +1221,7 @@ impl SessionStore {
         if !labels_path.exists() {
             return None;
         }
-        let labels_content = tokio::fs::read_to_string(&labels_path).await.ok()?;
+        let labels_content = self.read_optional_text(&labels_path).await?;
         let labels: Vec<serde_json::Value> = serde_json::from_str(&labels_content).ok()?;
 
         let mut notes = Vec::new();
@@ -938,7 +1229,8 @@ impl SessionStore {
             let index = label_val.get("index")?.as_u64()? as u32;
             let label = label_val.get("label")?.as_str()?.to_string();
             let file = format!("soap_patient_{}.txt", index);
-            let content = tokio::fs::read_to_string(session_dir.join(&file))
+            let content = self
+                .read_optional_text(&session_dir.join(&file))
                 .await
                 .unwrap_or_default();
             notes.push(ArchivedPatientNote {
@@ -953,19 +1245,147 @@ impl SessionStore {
             Some(notes)
         }
     }
-}
 
-/// Minimal shim that only deserializes `qualityRating` from feedback.json,
-/// avoiding the full SessionFeedback allocation (patient_feedback Vec, comments)
-/// when all we need is the rating for the session list row.
-async fn read_quality_rating(path: &std::path::Path) -> Option<String> {
-    #[derive(serde::Deserialize)]
-    struct QualityRatingOnly {
-        #[serde(rename = "qualityRating")]
-        quality_rating: Option<String>,
-    }
-    let content = tokio::fs::read_to_string(path).await.ok()?;
-    serde_json::from_str::<QualityRatingOnly>(&content).ok()?.quality_rating
+    /// Minimal shim that only deserializes `qualityRating` from feedback.json,
+    /// avoiding the full SessionFeedback allocation (patient_feedback Vec, comments)
+    /// when all we need is the rating for the session list row.
+    async fn read_quality_rating(&self, path: &Path) -> Option<String> {
+        #[derive(serde::Deserialize)]
+        struct QualityRatingOnly {
+            #[serde(rename = "qualityRating")]
+            quality_rating: Option<String>,
+        }
+        let content = self.read_optional_text(path).await?;
+        serde_json::from_str::<QualityRatingOnly>(&content).ok()?.quality_rating
+    }
+
+    // ── Encryption at rest ─────────────────────────────────────────
+
+    /// `sessions/{physician_id}/` for any path inside the tree — the
+    /// directory whose data key seals the file.
+    fn owning_physician_dir(&self, path: &Path) -> Result<PathBuf, ApiError> {
+        path.strip_prefix(&self.base_dir)
+            .ok()
+            .and_then(|rel| rel.components().next())
+            .map(|first| self.base_dir.join(first))
+            .ok_or_else(|| {
+                ApiError::Internal(format!("{} is outside the session store", path.display()))
+            })
+    }
+
+    /// Atomically write a file in the tree, sealed when encryption is enabled.
+    async fn write_file(&self, path: &Path, content: &[u8]) -> Result<(), ApiError> {
+        match &self.cipher {
+            Some(cipher) => {
+                let sealed = cipher.seal(&self.owning_physician_dir(path)?, content)?;
+                atomic_write(path, sealed).await
+            }
+            None => atomic_write(path, content).await,
+        }
+    }
+
+    /// Read a file from the tree, decrypting it if sealed. Plaintext files
+    /// (written before encryption was enabled) are returned as-is.
+    async fn read_file(&self, path: &Path) -> Result<Vec<u8>, ApiError> {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let data = tokio::fs::read(path)
+            .await
+            .map_err(|e| ApiError::Internal(format!("Failed to read {name}: {e}")))?;
+        if !encryption::is_sealed(&data) {
+            return Ok(data);
+        }
+        let cipher = self.cipher.as_ref().ok_or_else(|| {
+            ApiError::Internal(format!("{name} is encrypted but no master key is configured"))
+        })?;
+        cipher.open(&self.owning_physician_dir(path)?, &data)
+    }
+
+    async fn read_text(&self, path: &Path) -> Result<String, ApiError> {
+        let data = self.read_file(path).await?;
+        String::from_utf8(data)
+            .map_err(|e| ApiError::Internal(format!("{} is not UTF-8: {e}", path.display())))
+    }
+
+    /// Read file contents, returning None if the file doesn't exist or can't
+    /// be read
+    async fn read_optional_text(&self, path: &Path) -> Option<String> {
+        self.read_text(path).await.ok()
+    }
+
+    /// Encrypt every plaintext file in the tree in place (the
+    /// `--encrypt-sessions` migration). Already-sealed files are left alone,
+    /// so an interrupted run can simply be repeated. Run it with the server
+    /// stopped.
+    pub fn encrypt_existing(&self) -> Result<EncryptReport, ApiError> {
+        let cipher = self.cipher.as_ref().ok_or_else(|| {
+            ApiError::BadRequest("No master key configured (PROFILE_MASTER_KEY)".into())
+        })?;
+        let mut report = EncryptReport::default();
+        if !self.base_dir.exists() {
+            return Ok(report);
+        }
+        let mut pending = vec![self.base_dir.clone()];
+        while let Some(dir) = pending.pop() {
+            let entries = std::fs::read_dir(&dir)
+                .map_err(|e| ApiError::Internal(format!("Failed to read {}: {e}", dir.display())))?;
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.is_dir() {
+                    pending.push(path);
+                    continue;
+                }
+                let name = entry.file_name();
+                let name = name.to_string_lossy();
+                if name == encryption::DATA_KEY_FILE || name.ends_with(".tmp") {
+                    continue;
+                }
+                let data = std::fs::read(&path).map_err(|e| {
+                    ApiError::Internal(format!("Failed to read {}: {e}", path.display()))
+                })?;
+                if encryption::is_sealed(&data) {
+                    report.already_encrypted += 1;
+                    continue;
+                }
+                let sealed = cipher.seal(&self.owning_physician_dir(&path)?, &data)?;
+                let tmp_path = path.with_extension(format!("{}.tmp", Uuid::new_v4()));
+                std::fs::write(&tmp_path, sealed)
+                    .and_then(|_| std::fs::rename(&tmp_path, &path))
+                    .map_err(|e| {
+                        let _ = std::fs::remove_file(&tmp_path);
+                        ApiError::Internal(format!("Failed to encrypt {}: {e}", path.display()))
+                    })?;
+                report.encrypted += 1;
+            }
+        }
+        info!(
+            encrypted = report.encrypted,
+            already_encrypted = report.already_encrypted,
+            "Session tree encrypted"
+        );
+        Ok(report)
+    }
+
+    /// Rewrap every physician's data key under the current master key (the
+    /// `--rotate-master-key` command). Session files are not touched.
+    /// Returns the number of keys rewrapped.
+    pub fn rotate_master_key(&self) -> Result<usize, ApiError> {
+        let cipher = self.cipher.as_ref().ok_or_else(|| {
+            ApiError::BadRequest("No master key configured (PROFILE_MASTER_KEY)".into())
+        })?;
+        if !self.base_dir.exists() {
+            return Ok(0);
+        }
+        let entries = std::fs::read_dir(&self.base_dir)
+            .map_err(|e| ApiError::Internal(format!("Failed to read session store: {e}")))?;
+        let mut rewrapped = 0;
+        for entry in entries.flatten() {
+            if entry.path().is_dir() && cipher.rewrap(&entry.path())? {
+                rewrapped += 1;
+            }
+        }
+        info!(rewrapped, master_key_id = %cipher.master_key_id(), "Master key rotated");
+        Ok(rewrapped)
+    }
 }
 
 /// Parse a date from an RFC 3339 started_at string
@@ -978,11 +1398,6 @@ fn parse_date_from_started_at(started_at: &str) -> Result<NaiveDate, ApiError> {
         .map_err(|e| ApiError::BadRequest(format!("Cannot parse date from started_at: {e}")))
 }
 
-/// Read file contents, returning None if file doesn't exist
-async fn read_optional_file(path: &PathBuf) -> Option<String> {
-    tokio::fs::read_to_string(path).await.ok()
-}
-
 /// Atomic write: write to a unique temp file, then rename into place.
 /// Accepts any content that implements `AsRef<[u8]>` (covers both `&str` and `&[u8]`).
 async fn atomic_write(path: impl AsRef<Path>, content: impl AsRef<[u8]>) -> Result<(), ApiError> {
@@ -1063,4 +1478,24 @@ mod tests {
         assert!(!is_allowed_session_file("soap_patient_sub/nested.txt"));
         assert!(!is_allowed_session_file("soap_patient_1.json"));
     }
+
+    #[tokio::test]
+    async fn search_recovers_from_an_abandoned_build() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SessionStore::new(dir.path().to_path_buf(), None);
+        // A builder whose request was dropped mid-walk leaves its slot behind
+        store.search.write().await.insert(
+            "doc-1".into(),
+            SearchSlot::Building {
+                done: Arc::new(tokio::sync::Mutex::new(())),
+                pending: vec![("s1".into(), None)],
+            },
+        );
+        let hits = store.search("doc-1", "knee", None, None, 10).await.unwrap();
+        assert!(hits.is_empty());
+        assert!(matches!(
+            store.search.read().await.get("doc-1"),
+            Some(SearchSlot::Ready(_))
+        ));
+    }
 }
@@ -0,0 +1,593 @@
+use crate::error::ApiError;
+use crate::types::Caller;
+use axum::body::Bytes;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::{Mutex, OwnedMutexGuard, RwLock};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Largest file accepted, matching the single-request body limit.
+pub const MAX_UPLOAD_BYTES: u64 = 500 * 1024 * 1024;
+/// Largest chunk a single PATCH may carry.
+pub const MAX_CHUNK_BYTES: usize = 16 * 1024 * 1024;
+/// Uploads untouched this long are dropped with their data on load.
+const UPLOAD_TTL_DAYS: i64 = 7;
+const INDEX_FILE: &str = "uploads.json";
+
+/// Where a finished upload goes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum UploadTarget {
+    /// `audio.wav` of an existing session, as `POST .../audio` would write it
+    SessionAudio {
+        physician_id: String,
+        session_id: String,
+    },
+    /// A mobile recording; completing the upload creates its processing job
+    /// the way `POST /mobile/upload` does
+    MobileRecording {
+        physician_id: String,
+        recording_id: String,
+        started_at: String,
+        duration_ms: u64,
+        #[serde(default)]
+        device_info: Option<String>,
+    },
+}
+
+/// A resumable upload. Chunks are appended at `offset` until it reaches
+/// `length`, when the file is checked against `sha256` and handed to its
+/// target.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Upload {
+    pub upload_id: String,
+    pub target: UploadTarget,
+    pub length: u64,
+    /// Lowercase hex SHA-256 of the whole file
+    pub sha256: String,
+    /// Bytes received and synced to disk; the next chunk must start here
+    pub offset: u64,
+    pub created_at: String,
+    pub updated_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<String>,
+    /// Job created for a `MobileRecording` upload
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub job_id: Option<String>,
+    /// Registry key that started the upload. Upload IDs only go to the
+    /// client that started it, but a scope check alone would let any
+    /// `mobile_upload` key read or append to any upload.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_by_key: Option<String>,
+}
+
+impl Upload {
+    /// Registry keys may only touch uploads they started; the legacy key
+    /// (and any caller while auth is open) may touch any. Others get a 404,
+    /// as if the upload didn't exist.
+    pub fn check_caller(&self, caller: &Caller) -> Result<(), ApiError> {
+        match caller.key_id() {
+            Some(id) if self.created_by_key.as_deref() != Some(id) => Err(ApiError::NotFound(
+                format!("Upload not found: {}", self.upload_id),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// All bytes are in and verified, but the target hasn't taken them yet.
+    pub fn needs_finalize(&self) -> bool {
+        self.offset == self.length && self.completed_at.is_none()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct UploadIndex {
+    schema_version: u32,
+    uploads: Vec<Upload>,
+}
+
+/// Staging area for resumable uploads (`uploads/`).
+///
+/// Each upload's bytes live in `{upload_id}.part` next to the index. The
+/// recorded `offset` only moves after the chunk is synced; anything past it
+/// from an interrupted chunk is cut off by the next write, so the client
+/// resumes from the last offset it was told.
+///
+/// Chunk I/O happens under the upload's own [`PartFile`] lock, not the store
+/// lock, so one slow upload doesn't stall the others (see [`append`]).
+///
+/// Part files are owner-only but not encrypted; they're removed as soon as
+/// the upload completes.
+pub struct UploadStore {
+    dir: PathBuf,
+    uploads: HashMap<String, Upload>,
+    parts: HashMap<String, Arc<Mutex<PartFile>>>,
+}
+
+/// An upload's staged bytes and the SHA-256 of the prefix written so far,
+/// so completing the file doesn't re-read it.
+pub struct PartFile {
+    path: PathBuf,
+    hasher: Sha256,
+    /// Bytes covered by `hasher`; rebuilt from disk when it falls out of
+    /// step with the upload's offset (after a restart or a failed save)
+    hashed: u64,
+}
+
+/// What writing a chunk left behind.
+enum Written {
+    /// More bytes to come
+    Partial,
+    /// The file is complete and matches the declared checksum
+    Verified,
+    /// The file is complete but its checksum is this instead; the part file
+    /// has been discarded
+    Mismatch(String),
+}
+
+impl PartFile {
+    fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            hasher: Sha256::new(),
+            hashed: 0,
+        }
+    }
+
+    /// Write `data` at `offset` and, once the file reaches `length`, check
+    /// it against `expected`. Blocking.
+    fn write_chunk(
+        &mut self,
+        offset: u64,
+        data: &[u8],
+        length: u64,
+        expected: &str,
+    ) -> std::io::Result<Written> {
+        if self.hashed != offset {
+            self.rehash(offset)?;
+        }
+        write_chunk(&self.path, offset, data)?;
+        self.hasher.update(data);
+        self.hashed = offset + data.len() as u64;
+        if self.hashed < length {
+            return Ok(Written::Partial);
+        }
+        let actual = hex(&self.hasher.clone().finalize());
+        if actual == expected {
+            Ok(Written::Verified)
+        } else {
+            self.discard();
+            Ok(Written::Mismatch(actual))
+        }
+    }
+
+    /// Hash the first `len` bytes already on disk.
+    fn rehash(&mut self, len: u64) -> std::io::Result<()> {
+        self.hasher = Sha256::new();
+        self.hashed = 0;
+        if len == 0 {
+            return Ok(());
+        }
+        let mut file = std::fs::File::open(&self.path)?.take(len);
+        let mut buf = vec![0u8; 1024 * 1024];
+        loop {
+            let n = file.read(&mut buf)?;
+            if n == 0 {
+                break;
+            }
+            self.hasher.update(&buf[..n]);
+            self.hashed += n as u64;
+        }
+        if self.hashed != len {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                format!("part file holds {} of {len} recorded bytes", self.hashed),
+            ));
+        }
+        Ok(())
+    }
+
+    fn discard(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+        self.hasher = Sha256::new();
+        self.hashed = 0;
+    }
+}
+
+impl UploadStore {
+    /// Load the index, dropping expired uploads and orphaned part files.
+    pub fn load(dir: PathBuf) -> Result<Self, ApiError> {
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| ApiError::Internal(format!("Failed to create uploads dir: {e}")))?;
+        let index_path = dir.join(INDEX_FILE);
+        let uploads: Vec<Upload> = if index_path.exists() {
+            let content = std::fs::read_to_string(&index_path)
+                .map_err(|e| ApiError::Internal(format!("Failed to read uploads index: {e}")))?;
+            serde_json::from_str::<UploadIndex>(&content)
+                .map_err(|e| ApiError::Internal(format!("Failed to parse uploads index: {e}")))?
+                .uploads
+        } else {
+            Vec::new()
+        };
+
+        let cutoff = Utc::now() - chrono::Duration::days(UPLOAD_TTL_DAYS);
+        let (live, expired): (Vec<_>, Vec<_>) = uploads.into_iter().partition(|u| {
+            DateTime::parse_from_rfc3339(&u.updated_at).is_ok_and(|t| t >= cutoff)
+        });
+        let store = Self {
+            dir,
+            uploads: live.into_iter().map(|u| (u.upload_id.clone(), u)).collect(),
+            parts: HashMap::new(),
+        };
+        store.remove_orphaned_parts();
+        if !expired.is_empty() {
+            info!(count = expired.len(), "Dropped expired uploads");
+            store.save()?;
+        }
+        Ok(store)
+    }
+
+    fn save(&self) -> Result<(), ApiError> {
+        let mut uploads: Vec<Upload> = self.uploads.values().cloned().collect();
+        uploads.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+        let content = serde_json::to_string_pretty(&UploadIndex {
+            schema_version: 1,
+            uploads,
+        })
+        .map_err(|e| ApiError::Internal(format!("Failed to serialize uploads index: {e}")))?;
+        let path = self.dir.join(INDEX_FILE);
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, &content)
+            .map_err(|e| ApiError::Internal(format!("Failed to write temp file: {e}")))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let _ = std::fs::set_permissions(&tmp, std::fs::Permissions::from_mode(0o600));
+        }
+        std::fs::rename(&tmp, &path)
+            .map_err(|e| ApiError::Internal(format!("Failed to rename: {e}")))?;
+        Ok(())
+    }
+
+    /// Remove part files with no incomplete upload behind them.
+    fn remove_orphaned_parts(&self) {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("part") {
+                continue;
+            }
+            let live = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .and_then(|id| self.uploads.get(id))
+                .is_some_and(|u| u.completed_at.is_none());
+            if !live {
+                if let Err(e) = std::fs::remove_file(&path) {
+                    warn!("Failed to remove orphaned upload {}: {e}", path.display());
+                }
+            }
+        }
+    }
+
+    /// Start an upload, or return the unfinished one already under way for
+    /// the same target, file and caller so a client that lost its upload ID
+    /// still resumes.
+    pub fn create(
+        &mut self,
+        target: UploadTarget,
+        length: u64,
+        sha256: &str,
+        caller: &Caller,
+    ) -> Result<Upload, ApiError> {
+        if length == 0 || length > MAX_UPLOAD_BYTES {
+            return Err(ApiError::BadRequest(format!(
+                "length must be between 1 and {MAX_UPLOAD_BYTES} bytes"
+            )));
+        }
+        let sha256 = sha256.to_ascii_lowercase();
+        if sha256.len() != 64 || !sha256.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(ApiError::BadRequest(
+                "sha256 must be 64 hex characters".into(),
+            ));
+        }
+
+        let created_by_key = caller.key_id().map(str::to_string);
+        if let Some(existing) = self.uploads.values().find(|u| {
+            u.completed_at.is_none()
+                && u.target == target
+                && u.length == length
+                && u.sha256 == sha256
+                && u.created_by_key == created_by_key
+        }) {
+            return Ok(existing.clone());
+        }
+
+        let now = Utc::now().to_rfc3339();
+        let upload = Upload {
+            upload_id: Uuid::new_v4().to_string(),
+            target,
+            length,
+            sha256,
+            offset: 0,
+            created_at: now.clone(),
+            updated_at: now,
+            completed_at: None,
+            job_id: None,
+            created_by_key,
+        };
+        self.uploads.insert(upload.upload_id.clone(), upload.clone());
+        self.save()?;
+        Ok(upload)
+    }
+
+    pub fn get(&self, upload_id: &str) -> Result<Upload, ApiError> {
+        self.uploads
+            .get(upload_id)
+            .cloned()
+            .ok_or_else(|| ApiError::NotFound(format!("Upload not found: {upload_id}")))
+    }
+
+    /// Path of an upload's staged bytes.
+    pub fn part_path(&self, upload_id: &str) -> PathBuf {
+        self.dir.join(format!("{upload_id}.part"))
+    }
+
+    /// Lock guarding an upload's part file.
+    fn part(&mut self, upload_id: &str) -> Result<Arc<Mutex<PartFile>>, ApiError> {
+        self.get(upload_id)?;
+        let path = self.part_path(upload_id);
+        Ok(self
+            .parts
+            .entry(upload_id.to_string())
+            .or_insert_with(|| Arc::new(Mutex::new(PartFile::new(path))))
+            .clone())
+    }
+
+    /// Move an upload's offset after a chunk was written (or back to zero
+    /// after a checksum mismatch).
+    fn record_offset(&mut self, upload_id: &str, offset: u64) -> Result<Upload, ApiError> {
+        let entry = self
+            .uploads
+            .get_mut(upload_id)
+            .ok_or_else(|| ApiError::NotFound(format!("Upload not found: {upload_id}")))?;
+        entry.offset = offset;
+        entry.updated_at = Utc::now().to_rfc3339();
+        let result = entry.clone();
+        self.save()?;
+        Ok(result)
+    }
+
+    /// Record that the target has taken the file, and drop the staged bytes
+    /// if the target didn't move them away.
+    pub fn complete(&mut self, upload_id: &str, job_id: Option<String>) -> Result<Upload, ApiError> {
+        let part = self.part_path(upload_id);
+        let upload = self
+            .uploads
+            .get_mut(upload_id)
+            .ok_or_else(|| ApiError::NotFound(format!("Upload not found: {upload_id}")))?;
+        let now = Utc::now().to_rfc3339();
+        upload.completed_at = Some(now.clone());
+        upload.updated_at = now;
+        upload.job_id = job_id;
+        let result = upload.clone();
+        self.save()?;
+        self.parts.remove(upload_id);
+        let _ = std::fs::remove_file(part);
+        Ok(result)
+    }
+}
+
+/// Append `data` at `offset`, which must equal the upload's current offset.
+/// The chunk that completes the file is checked against the declared
+/// checksum; on a mismatch the upload starts over from zero. An empty chunk
+/// writes nothing.
+///
+/// The store lock is only held to look up and record offsets; the write,
+/// sync and hashing run on the blocking pool under the upload's own lock.
+/// That lock is returned so the caller can hold it through the hand-off to
+/// the target, keeping a retried final chunk from racing it.
+pub async fn append(
+    uploads: &RwLock<UploadStore>,
+    upload_id: &str,
+    offset: u64,
+    data: Bytes,
+    caller: &Caller,
+) -> Result<(Upload, OwnedMutexGuard<PartFile>), ApiError> {
+    validate_upload_id(upload_id)?;
+    uploads.read().await.get(upload_id)?.check_caller(caller)?;
+    let lock = uploads.write().await.part(upload_id)?;
+    let mut part = lock.lock_owned().await;
+
+    // Read after taking the part lock so a chunk that just landed is seen
+    let upload = uploads.read().await.get(upload_id)?;
+    if upload.completed_at.is_some() {
+        return Err(ApiError::Conflict(format!("Upload already complete: {upload_id}")));
+    }
+    if offset != upload.offset {
+        return Err(ApiError::Conflict(format!(
+            "Upload-Offset {offset} does not match current offset {}",
+            upload.offset
+        )));
+    }
+    let end = offset + data.len() as u64;
+    if end > upload.length {
+        return Err(ApiError::BadRequest(format!(
+            "Chunk ends at {end}, past upload length {}",
+            upload.length
+        )));
+    }
+    if data.is_empty() {
+        return Ok((upload, part));
+    }
+
+    let (length, expected) = (upload.length, upload.sha256.clone());
+    let (part, written) = tokio::task::spawn_blocking(move || {
+        let written = part.write_chunk(offset, &data, length, &expected);
+        (part, written)
+    })
+    .await
+    .map_err(|e| ApiError::Internal(format!("Upload write task failed: {e}")))?;
+    let written =
+        written.map_err(|e| ApiError::Internal(format!("Failed to write upload chunk: {e}")))?;
+
+    match written {
+        Written::Partial | Written::Verified => {
+            let upload = uploads.write().await.record_offset(upload_id, end)?;
+            Ok((upload, part))
+        }
+        Written::Mismatch(actual) => {
+            uploads.write().await.record_offset(upload_id, 0)?;
+            Err(ApiError::BadRequest(format!(
+                "Checksum mismatch (expected {}, got {actual}); upload restarted from offset 0",
+                upload.sha256
+            )))
+        }
+    }
+}
+
+/// Upload IDs become file names; only accept what `create` hands out.
+pub fn validate_upload_id(upload_id: &str) -> Result<(), ApiError> {
+    if Uuid::parse_str(upload_id).is_err() {
+        return Err(ApiError::BadRequest(format!("Invalid upload ID: {upload_id}")));
+    }
+    Ok(())
+}
+
+/// Write `data` at `offset`, discarding anything past it left by an
+/// interrupted chunk, and sync before returning.
+fn write_chunk(path: &Path, offset: u64, data: &[u8]) -> std::io::Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.create(true).write(true).truncate(false);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    file.set_len(offset)?;
+    file.seek(SeekFrom::Start(offset))?;
+    file.write_all(data)?;
+    file.sync_data()
+}
+
+fn hex(digest: &[u8]) -> String {
+    digest.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target() -> UploadTarget {
+        UploadTarget::SessionAudio {
+            physician_id: "p1".into(),
+            session_id: "s1".into(),
+        }
+    }
+
+    fn sha256_hex(data: &[u8]) -> String {
+        hex(&Sha256::digest(data))
+    }
+
+    fn load(dir: &Path) -> RwLock<UploadStore> {
+        RwLock::new(UploadStore::load(dir.to_path_buf()).unwrap())
+    }
+
+    async fn put(
+        store: &RwLock<UploadStore>,
+        upload_id: &str,
+        offset: u64,
+        data: &[u8],
+    ) -> Result<Upload, ApiError> {
+        let data = Bytes::copy_from_slice(data);
+        append(store, upload_id, offset, data, &Caller::LegacyKey)
+            .await
+            .map(|(upload, _)| upload)
+    }
+
+    #[tokio::test]
+    async fn resumes_after_reload_from_synced_offset() {
+        let dir = tempfile::tempdir().unwrap();
+        let data = b"0123456789abcdef";
+        let id = {
+            let store = load(dir.path());
+            let upload = store
+                .write()
+                .await
+                .create(target(), data.len() as u64, &sha256_hex(data), &Caller::LegacyKey)
+                .unwrap();
+            put(&store, &upload.upload_id, 0, &data[..6]).await.unwrap();
+            upload.upload_id
+        };
+        // Bytes past the recorded offset, as if the process died mid-chunk
+        let part = dir.path().join(format!("{id}.part"));
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&part)
+            .unwrap()
+            .write_all(b"zz")
+            .unwrap();
+
+        // The running checksum is rebuilt from the synced prefix
+        let store = load(dir.path());
+        assert_eq!(store.read().await.get(&id).unwrap().offset, 6);
+        assert!(matches!(put(&store, &id, 8, &data[8..]).await, Err(ApiError::Conflict(_))));
+        let upload = put(&store, &id, 6, &data[6..]).await.unwrap();
+        assert!(upload.needs_finalize());
+        assert_eq!(std::fs::read(&part).unwrap(), data);
+
+        store.write().await.complete(&id, None).unwrap();
+        assert!(!part.exists());
+        assert!(matches!(put(&store, &id, 16, b"").await, Err(ApiError::Conflict(_))));
+    }
+
+    #[tokio::test]
+    async fn checksum_mismatch_restarts_upload() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = load(dir.path());
+        let upload = store
+            .write()
+            .await
+            .create(target(), 4, &sha256_hex(b"good"), &Caller::LegacyKey)
+            .unwrap();
+        let err = put(&store, &upload.upload_id, 0, b"bad!").await.unwrap_err();
+        assert!(matches!(err, ApiError::BadRequest(_)));
+        assert_eq!(store.read().await.get(&upload.upload_id).unwrap().offset, 0);
+        assert!(put(&store, &upload.upload_id, 0, b"good")
+            .await
+            .unwrap()
+            .needs_finalize());
+    }
+
+    #[tokio::test]
+    async fn create_returns_unfinished_upload_for_same_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = load(dir.path());
+        let sha = sha256_hex(b"abcd");
+        let first = store.write().await.create(target(), 4, &sha, &Caller::LegacyKey).unwrap();
+        put(&store, &first.upload_id, 0, b"ab").await.unwrap();
+        let mut store = store.write().await;
+
+        let again = store.create(target(), 4, &sha.to_uppercase(), &Caller::LegacyKey).unwrap();
+        assert_eq!(again.upload_id, first.upload_id);
+        assert_eq!(again.offset, 2);
+
+        let other = UploadTarget::SessionAudio {
+            physician_id: "p1".into(),
+            session_id: "s2".into(),
+        };
+        let other = store.create(other, 4, &sha, &Caller::LegacyKey).unwrap();
+        assert_ne!(other.upload_id, first.upload_id);
+        assert!(store.create(target(), 0, &sha, &Caller::LegacyKey).is_err());
+        assert!(store.create(target(), 4, "abc", &Caller::LegacyKey).is_err());
+    }
+}
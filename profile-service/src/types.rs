@@ -1,6 +1,6 @@
 use crate::error::ApiError;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 // ── Physician ──────────────────────────────────────────────────────
 
@@ -292,6 +292,9 @@ pub struct Room {
     pub debug_storage_enabled: Option<bool>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub input_device_id: Option<String>,
+    /// Server config channel this room follows (None = stable)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config_channel: Option<ConfigChannel>,
 
     pub created_at: String,
     pub updated_at: String,
@@ -371,6 +374,8 @@ pub struct UpdateRoomRequest {
     pub debug_storage_enabled: Option<bool>,
     #[serde(default)]
     pub input_device_id: Option<String>,
+    #[serde(default)]
+    pub config_channel: Option<ConfigChannel>,
 }
 
 impl UpdateRoomRequest {
@@ -392,6 +397,210 @@ impl UpdateRoomRequest {
     }
 }
 
+// ── API Keys ──────────────────────────────────────────────────────
+
+/// What an API key may do. `Admin` implies `Room`, which implies
+/// `MobileUpload`; see `auth::required_scope` for the route mapping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiKeyScope {
+    /// iOS app: upload recordings, track its jobs, list physicians
+    MobileUpload,
+    /// Desktop rooms and the mobile worker: all non-admin reads and writes
+    Room,
+    /// Config pushes, infrastructure settings and key management
+    Admin,
+}
+
+impl ApiKeyScope {
+    /// Whether a key holding this scope may call a route requiring `required`
+    pub fn grants(&self, required: ApiKeyScope) -> bool {
+        match self {
+            ApiKeyScope::Admin => true,
+            ApiKeyScope::Room => required != ApiKeyScope::Admin,
+            ApiKeyScope::MobileUpload => required == ApiKeyScope::MobileUpload,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiKeyScope::MobileUpload => "mobile_upload",
+            ApiKeyScope::Room => "room",
+            ApiKeyScope::Admin => "admin",
+        }
+    }
+}
+
+/// A registered API key. The secret itself is never stored or returned
+/// after creation — only its SHA-256 hash (kept server-side) and a short
+/// prefix so admins can tell keys apart.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKey {
+    pub id: String,
+    pub name: String,
+    pub scopes: Vec<ApiKeyScope>,
+    pub key_prefix: String,
+    pub created_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_used_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revoked_at: Option<String>,
+}
+
+impl ApiKey {
+    pub fn allows(&self, required: ApiKeyScope) -> bool {
+        self.revoked_at.is_none() && self.scopes.iter().any(|s| s.grants(required))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateApiKeyRequest {
+    pub name: String,
+    pub scopes: Vec<ApiKeyScope>,
+}
+
+impl CreateApiKeyRequest {
+    pub fn validate(&self) -> Result<(), ApiError> {
+        if self.name.is_empty() {
+            return Err(ApiError::BadRequest("Name must not be empty".into()));
+        }
+        if self.name.len() > 500 {
+            return Err(ApiError::BadRequest("Name exceeds 500 characters".into()));
+        }
+        if self.scopes.is_empty() {
+            return Err(ApiError::BadRequest("At least one scope is required".into()));
+        }
+        Ok(())
+    }
+}
+
+/// Response to key creation — the only time the secret is returned
+#[derive(Debug, Serialize)]
+pub struct CreatedApiKey {
+    #[serde(flatten)]
+    pub key: ApiKey,
+    pub secret: String,
+}
+
+// ── Audit Log ─────────────────────────────────────────────────────
+
+/// Who made a request, as established by the auth middleware
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Caller {
+    /// Auth disabled, or no valid key presented
+    Anonymous,
+    /// The shared `--api-key` / `PROFILE_API_KEY` secret
+    LegacyKey,
+    /// A registry key (`/admin/keys`)
+    Key { id: String, name: String },
+}
+
+impl Caller {
+    /// ID of the registry key behind the request, if any
+    pub fn key_id(&self) -> Option<&str> {
+        match self {
+            Caller::Key { id, .. } => Some(id),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOutcome {
+    /// 2xx/3xx
+    Success,
+    /// 401/403 — rejected by auth
+    Denied,
+    /// Any other 4xx/5xx
+    Failed,
+}
+
+impl AuditOutcome {
+    pub fn from_status(status: u16) -> Self {
+        match status {
+            401 | 403 => AuditOutcome::Denied,
+            s if s < 400 => AuditOutcome::Success,
+            _ => AuditOutcome::Failed,
+        }
+    }
+}
+
+/// What was accessed, by whom, and how it went. Identifiers only — never
+/// request/response bodies or query strings (patient search terms are PHI).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub seq: u64,
+    pub timestamp: String,
+    pub caller: Caller,
+    pub method: String,
+    /// Route template, e.g. `/physicians/:physician_id/sessions/:session_id/audio`
+    pub route: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub physician_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub patient_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub job_id: Option<String>,
+    /// Resumable upload (`/uploads/:upload_id`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upload_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+    pub status: u16,
+    pub outcome: AuditOutcome,
+}
+
+/// One line of the audit log. `hash` is SHA-256 over `prev_hash` and the
+/// record, so editing or removing any entry breaks every later link.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    #[serde(flatten)]
+    pub record: AuditRecord,
+    pub prev_hash: String,
+    pub hash: String,
+}
+
+/// Filters for `GET /admin/audit`
+#[derive(Debug, Default, Deserialize)]
+pub struct AuditQuery {
+    pub physician_id: Option<String>,
+    pub session_id: Option<String>,
+    pub patient_id: Option<String>,
+    /// Registry key id, or `legacy` / `anonymous`
+    pub caller: Option<String>,
+    pub outcome: Option<AuditOutcome>,
+    /// RFC 3339, inclusive
+    pub since: Option<String>,
+    /// RFC 3339, exclusive
+    pub until: Option<String>,
+    /// Most recent N matches (default 500)
+    pub limit: Option<usize>,
+}
+
+/// Result of walking the hash chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditVerifyReport {
+    pub valid: bool,
+    pub entries: u64,
+    /// Hash of the last entry. Record it elsewhere to detect truncation,
+    /// which a chain alone can't reveal.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub head_hash: Option<String>,
+    /// 1-based line of the first broken entry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub broken_at_line: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Why the last append failed, while the log is failing. The chain can
+    /// be intact while entries are going unwritten.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_write_error: Option<String>,
+}
+
 // ── Speaker ────────────────────────────────────────────────────────
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -580,6 +789,37 @@ pub struct ArchiveDetails {
     pub patient_notes: Option<Vec<ArchivedPatientNote>>,
 }
 
+/// Which part of an archived session a search snippet came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchField {
+    Transcript,
+    SoapNote,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchSnippet {
+    pub field: SearchField,
+    pub text: String,
+    /// `[start, end)` character offsets of matched terms within `text`
+    pub highlights: Vec<[usize; 2]>,
+}
+
+/// One ranked result of `GET /physicians/:id/search`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub session_id: String,
+    pub date: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub patient_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encounter_number: Option<u32>,
+    pub score: f64,
+    pub snippets: Vec<SearchSnippet>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SessionFeedback {
@@ -678,6 +918,10 @@ pub struct PatientRecord {
     /// Session back-links. Deduped on confirm.
     #[serde(default)]
     pub session_ids: Vec<String>,
+    /// session_id → YYYY-MM-DD, for sessions confirmed with a date. Records
+    /// written before this field existed have no entries.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub session_dates: BTreeMap<String, String>,
     pub created_at: String,
     pub updated_at: String,
 }
@@ -691,6 +935,9 @@ pub struct ConfirmPatientRequest {
     pub session_id: String,
     #[serde(default)]
     pub medplum_patient_id: Option<String>,
+    /// Session date (YYYY-MM-DD), stored in `PatientRecord::session_dates`.
+    #[serde(default)]
+    pub session_date: Option<String>,
 }
 
 /// Response from POST /physicians/:id/patients/confirm
@@ -737,6 +984,123 @@ pub struct RenumberRequest {
 pub struct ConfigVersion {
     pub version: u64,
     pub updated_at: String,
+    /// Channel the version was resolved for. Clients refetch when their
+    /// room moves channel, even if the version hasn't changed.
+    #[serde(default)]
+    pub channel: ConfigChannel,
+}
+
+/// Config rollout channel. Rooms follow `stable` unless assigned to
+/// `candidate`, which serves staged config where one has been pushed and
+/// falls back to stable for every kind without one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigChannel {
+    #[default]
+    Stable,
+    Candidate,
+}
+
+/// Config category with its own revision history.
+/// Serialized as the URL segment used by `/config/:kind/...`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigKind {
+    Prompts,
+    Billing,
+    Thresholds,
+    Defaults,
+}
+
+impl ConfigKind {
+    pub const ALL: [ConfigKind; 4] = [
+        ConfigKind::Prompts,
+        ConfigKind::Billing,
+        ConfigKind::Thresholds,
+        ConfigKind::Defaults,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConfigKind::Prompts => "prompts",
+            ConfigKind::Billing => "billing",
+            ConfigKind::Thresholds => "thresholds",
+            ConfigKind::Defaults => "defaults",
+        }
+    }
+
+    pub fn parse(s: &str) -> Result<Self, ApiError> {
+        Self::ALL
+            .into_iter()
+            .find(|k| k.as_str() == s)
+            .ok_or_else(|| ApiError::NotFound(format!("Unknown config kind: {s}")))
+    }
+}
+
+/// How a config revision came about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigRevisionSource {
+    /// Seeded from the config on disk when history was first enabled
+    Initial,
+    /// PUT /config/:kind
+    Update,
+    /// POST /config/:kind/rollback/:n
+    Rollback,
+    /// POST /config/:kind/promote (candidate channel moved to stable)
+    Promote,
+}
+
+/// History entry for one immutable config revision.
+/// `revision` counts per kind from 1; `version` is the global config
+/// version the revision was published under.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigRevisionMeta {
+    pub kind: ConfigKind,
+    pub revision: u64,
+    pub version: u64,
+    pub created_at: String,
+    pub source: ConfigRevisionSource,
+    /// Revision whose content a rollback restored
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rolled_back_from: Option<u64>,
+}
+
+/// A config revision with its full content
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigRevision {
+    #[serde(flatten)]
+    pub meta: ConfigRevisionMeta,
+    pub data: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigChangeOp {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// One leaf-level difference between two revisions.
+/// `path` is a JSON pointer (RFC 6901) into the config document.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfigChange {
+    pub path: String,
+    pub op: ConfigChangeOp,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub new: Option<serde_json::Value>,
+}
+
+/// Structural diff between two revisions of one config kind
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigDiff {
+    pub kind: ConfigKind,
+    pub from: u64,
+    pub to: u64,
+    pub changes: Vec<ConfigChange>,
 }
 
 // ── Prompt Templates ─────────────────────────────────────────────
@@ -789,6 +1153,14 @@ pub struct PromptTemplates {
     /// Context text when sensor confirms presence (conservative "NOT transitions" framing)
     #[serde(default)]
     pub encounter_detection_sensor_present: String,
+    /// Context text when the room head count rises mid-encounter.
+    /// Placeholders: {baseline}, {current}
+    #[serde(default)]
+    pub encounter_detection_occupancy_arrival: String,
+    /// Context text when the room drops to one person after several.
+    /// Placeholder: {peak}
+    #[serde(default)]
+    pub encounter_detection_occupancy_one_remains: String,
 
     // Clinical content check
     /// Binary clinical-vs-non-clinical classification system prompt
@@ -850,6 +1222,8 @@ impl Default for PromptTemplates {
             encounter_detection_system: String::new(),
             encounter_detection_sensor_departed: String::new(),
             encounter_detection_sensor_present: String::new(),
+            encounter_detection_occupancy_arrival: String::new(),
+            encounter_detection_occupancy_one_remains: String::new(),
             clinical_content_check: String::new(),
             multi_patient_check: String::new(),
             multi_patient_detect: String::new(),
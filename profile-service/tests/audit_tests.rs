@@ -0,0 +1,186 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::TestApp;
+use serde_json::json;
+
+const LEGACY: &str = "secret-key-123";
+
+fn audit_entries(resp: &common::TestResponse) -> Vec<serde_json::Value> {
+    resp.assert_ok();
+    resp.json().as_array().unwrap().clone()
+}
+
+#[tokio::test]
+async fn session_access_is_logged_with_route_template() {
+    let app = TestApp::with_auth(LEGACY);
+    app.get_authed("/physicians/doc-1/sessions/sess-1", LEGACY).await;
+
+    let entries = audit_entries(&app.get_authed("/admin/audit", LEGACY).await);
+    assert_eq!(entries.len(), 1);
+    let e = &entries[0];
+    assert_eq!(e["seq"], 1);
+    assert_eq!(e["method"], "GET");
+    assert_eq!(e["route"], "/physicians/:physician_id/sessions/:session_id");
+    assert_eq!(e["physician_id"], "doc-1");
+    assert_eq!(e["session_id"], "sess-1");
+    assert_eq!(e["caller"]["type"], "legacy_key");
+    assert_eq!(e["status"], 404);
+    assert_eq!(e["outcome"], "failed");
+}
+
+#[tokio::test]
+async fn rejected_requests_are_logged_as_denied() {
+    let app = TestApp::with_auth(LEGACY);
+    app.get("/physicians/doc-1/day-log/2026-01-05").await
+        .assert_status(StatusCode::UNAUTHORIZED);
+
+    let entries = audit_entries(&app.get_authed("/admin/audit?outcome=denied", LEGACY).await);
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0]["caller"]["type"], "anonymous");
+    assert_eq!(entries[0]["date"], "2026-01-05");
+    assert_eq!(entries[0]["status"], 401);
+}
+
+#[tokio::test]
+async fn registry_key_caller_is_recorded() {
+    let app = TestApp::with_auth(LEGACY);
+    let resp = app
+        .post_json_authed(
+            "/admin/keys",
+            &json!({"name": "iPhone", "scopes": ["mobile_upload"]}),
+            LEGACY,
+        )
+        .await;
+    resp.assert_ok();
+    let created = resp.json();
+    let id = created["id"].as_str().unwrap().to_string();
+    let secret = created["secret"].as_str().unwrap().to_string();
+
+    // mobile_upload can't read patients
+    app.get_authed("/physicians/doc-1/patients/pat-1", &secret).await
+        .assert_status(StatusCode::FORBIDDEN);
+
+    let entries = audit_entries(&app.get_authed(&format!("/admin/audit?caller={id}"), LEGACY).await);
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0]["caller"]["name"], "iPhone");
+    assert_eq!(entries[0]["patient_id"], "pat-1");
+    assert_eq!(entries[0]["outcome"], "denied");
+}
+
+#[tokio::test]
+async fn query_strings_are_not_logged() {
+    let app = TestApp::new();
+    app.get("/physicians/doc-1/patients?name=Jane%20Doe&dob=1970-01-01").await.assert_ok();
+
+    let resp = app.get("/admin/audit").await;
+    assert!(!resp.text().contains("Jane"));
+    assert!(!resp.text().contains("1970"));
+    let entries = audit_entries(&resp);
+    assert_eq!(entries[0]["route"], "/physicians/:physician_id/patients");
+}
+
+#[tokio::test]
+async fn non_phi_routes_are_not_logged() {
+    let app = TestApp::new();
+    app.get("/physicians").await.assert_ok();
+    app.get("/rooms").await.assert_ok();
+    app.get("/config/prompts").await.assert_ok();
+
+    assert!(audit_entries(&app.get("/admin/audit").await).is_empty());
+}
+
+#[tokio::test]
+async fn query_filters_by_physician_and_limit() {
+    let app = TestApp::new();
+    for i in 0..3 {
+        app.get(&format!("/physicians/doc-1/sessions/s{i}")).await;
+    }
+    app.get("/physicians/doc-2/sessions?date=2026-01-05").await.assert_ok();
+
+    let entries = audit_entries(&app.get("/admin/audit?physician_id=doc-1").await);
+    assert_eq!(entries.len(), 3);
+
+    let entries = audit_entries(&app.get("/admin/audit?physician_id=doc-1&limit=1").await);
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0]["session_id"], "s2");
+
+    app.get("/admin/audit?since=yesterday").await
+        .assert_status(StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn verify_detects_tampered_entry() {
+    let app = TestApp::new();
+    for i in 0..3 {
+        app.get(&format!("/physicians/doc-1/sessions/s{i}")).await;
+    }
+
+    let report = app.get("/admin/audit/verify").await.json();
+    assert_eq!(report["valid"], true);
+    assert_eq!(report["entries"], 3);
+
+    // Rewrite the second entry's session id
+    let path = profile_service::store::audit_log::log_path(app.data_dir());
+    let content = std::fs::read_to_string(&path).unwrap();
+    std::fs::write(&path, content.replacen("\"s1\"", "\"s9\"", 1)).unwrap();
+
+    let report = app.get("/admin/audit/verify").await.json();
+    assert_eq!(report["valid"], false);
+    assert_eq!(report["broken_at_line"], 2);
+    assert_eq!(report["entries"], 1);
+}
+
+#[tokio::test]
+async fn verify_detects_deleted_entry() {
+    let app = TestApp::new();
+    for i in 0..3 {
+        app.get(&format!("/physicians/doc-1/sessions/s{i}")).await;
+    }
+
+    let path = profile_service::store::audit_log::log_path(app.data_dir());
+    let content = std::fs::read_to_string(&path).unwrap();
+    let kept: Vec<&str> = content.lines().enumerate().filter(|(i, _)| *i != 1).map(|(_, l)| l).collect();
+    std::fs::write(&path, kept.join("\n")).unwrap();
+
+    let report = profile_service::store::audit_log::verify(&path).unwrap();
+    assert!(!report.valid);
+    assert_eq!(report.broken_at_line, Some(2));
+}
+
+#[tokio::test]
+async fn phi_is_withheld_while_the_audit_log_is_unwritable() {
+    let app = TestApp::new();
+    let uri = "/physicians/doc-1/day-log/2026-03-26";
+    app.put_bytes(uri, b"{\"event\":\"room_entered\"}\n").await.assert_ok();
+
+    // A file where the audit directory should be makes every append fail
+    let audit_dir = app.data_dir().join("audit");
+    let moved = app.data_dir().join("audit.moved");
+    std::fs::rename(&audit_dir, &moved).unwrap();
+    std::fs::write(&audit_dir, b"").unwrap();
+
+    let resp = app.get(uri).await;
+    resp.assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(!resp.text().contains("room_entered"));
+
+    let health = app.get("/health").await.json();
+    assert_eq!(health["healthy"], false);
+    assert!(health["audit_error"].as_str().is_some());
+    let report = app.get("/admin/audit/verify").await.json();
+    assert!(report["last_write_error"].as_str().is_some());
+
+    // Refused before the handler runs, so the write never lands
+    app.put_bytes(uri, b"{\"event\":\"room_left\"}\n").await
+        .assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+
+    std::fs::remove_file(&audit_dir).unwrap();
+    std::fs::rename(&moved, &audit_dir).unwrap();
+    assert_eq!(app.get(uri).await.text(), "{\"event\":\"room_entered\"}\n");
+    assert_eq!(app.get("/health").await.json()["healthy"], true);
+    let report = app.get("/admin/audit/verify").await.json();
+    assert_eq!(report["valid"], true);
+    // The PUT before the failure and the GET after it
+    assert_eq!(report["entries"], 2);
+    assert!(report.get("last_write_error").is_none());
+}
@@ -50,3 +50,156 @@ async fn no_auth_configured_allows_all() {
     let resp = app.get("/rooms").await;
     resp.assert_ok();
 }
+
+#[tokio::test]
+async fn no_auth_configured_ignores_a_presented_key() {
+    let app = TestApp::new();
+    // Clients configured with a key keep working against an open server
+    app.get_authed("/physicians", "stale-key").await.assert_ok();
+}
+
+// ── Per-device key registry ─────────────────────────────────────
+
+const LEGACY: &str = "secret-key-123";
+
+async fn create_key(app: &TestApp, name: &str, scopes: &[&str]) -> (String, String) {
+    let resp = app
+        .post_json_authed(
+            "/admin/keys",
+            &serde_json::json!({"name": name, "scopes": scopes}),
+            LEGACY,
+        )
+        .await;
+    resp.assert_ok();
+    let json = resp.json();
+    (
+        json["id"].as_str().unwrap().to_string(),
+        json["secret"].as_str().unwrap().to_string(),
+    )
+}
+
+#[tokio::test]
+async fn mobile_upload_key_is_limited_to_mobile_routes() {
+    let app = TestApp::with_auth(LEGACY);
+    let (_, secret) = create_key(&app, "iPhone", &["mobile_upload"]).await;
+
+    app.get_authed("/physicians", &secret).await.assert_ok();
+    app.get_authed("/mobile/jobs", &secret).await.assert_ok();
+
+    let resp = app.get_authed("/rooms", &secret).await;
+    resp.assert_status(StatusCode::FORBIDDEN);
+    assert!(resp.json()["error"].as_str().unwrap().contains("room"));
+    app.put_json_authed("/mobile/jobs/some-job", &serde_json::json!({}), &secret)
+        .await
+        .assert_status(StatusCode::FORBIDDEN);
+
+    // Resumable upload chunks are reachable; starting a session audio upload isn't
+    app.get_authed("/uploads/9b2f4c0e-52f7-4d3c-a3c1-0d6f3f1e2a77", &secret)
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    app.post_json_authed(
+        "/physicians/p1/sessions/s1/audio/resumable",
+        &serde_json::json!({}),
+        &secret,
+    )
+    .await
+    .assert_status(StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn room_key_reads_config_but_cannot_push_it() {
+    let app = TestApp::with_auth(LEGACY);
+    let (_, secret) = create_key(&app, "Exam 1", &["room"]).await;
+
+    app.get_authed("/rooms", &secret).await.assert_ok();
+    app.get_authed("/config/prompts", &secret).await.assert_ok();
+    app.get_authed("/mobile/jobs", &secret).await.assert_ok();
+
+    app.put_json_authed("/config/prompts", &serde_json::json!({}), &secret)
+        .await
+        .assert_status(StatusCode::FORBIDDEN);
+    app.get_authed("/admin/keys", &secret)
+        .await
+        .assert_status(StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn admin_key_can_push_config_and_manage_keys() {
+    let app = TestApp::with_auth(LEGACY);
+    let (_, secret) = create_key(&app, "Ops laptop", &["admin"]).await;
+
+    app.put_json_authed("/config/prompts", &serde_json::json!({}), &secret)
+        .await
+        .assert_ok();
+    let resp = app.get_authed("/admin/keys", &secret).await;
+    resp.assert_ok();
+    let json = resp.json();
+    let keys = json.as_array().unwrap();
+    assert_eq!(keys.len(), 1);
+    // The secret is only returned at creation
+    assert!(keys[0].get("secret").is_none());
+    assert!(keys[0].get("key_hash").is_none());
+    assert_eq!(keys[0]["key_prefix"], &secret[..12]);
+    assert!(keys[0]["last_used_at"].is_string());
+}
+
+#[tokio::test]
+async fn revoked_key_is_rejected() {
+    let app = TestApp::with_auth(LEGACY);
+    let (id, secret) = create_key(&app, "Old room", &["room"]).await;
+    app.get_authed("/rooms", &secret).await.assert_ok();
+
+    let resp = app
+        .post_json_authed(&format!("/admin/keys/{id}/revoke"), &serde_json::json!({}), LEGACY)
+        .await;
+    resp.assert_ok();
+    assert!(resp.json()["revoked_at"].is_string());
+
+    let resp = app.get_authed("/rooms", &secret).await;
+    resp.assert_status(StatusCode::UNAUTHORIZED);
+    assert!(resp.json()["error"].as_str().unwrap().contains("revoked"));
+}
+
+#[tokio::test]
+async fn create_key_requires_a_scope() {
+    let app = TestApp::with_auth(LEGACY);
+    app.post_json_authed(
+        "/admin/keys",
+        &serde_json::json!({"name": "nothing", "scopes": []}),
+        LEGACY,
+    )
+    .await
+    .assert_status(StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn first_registry_key_must_be_admin() {
+    let app = TestApp::new();
+    app.post_json(
+        "/admin/keys",
+        &serde_json::json!({"name": "Exam 1", "scopes": ["room"]}),
+    )
+    .await
+    .assert_status(StatusCode::FORBIDDEN);
+    // Auth stays open until an admin key exists
+    app.get("/rooms").await.assert_ok();
+}
+
+#[tokio::test]
+async fn first_registry_key_enables_auth_without_legacy_key() {
+    let app = TestApp::new();
+    app.get("/rooms").await.assert_ok();
+
+    let resp = app
+        .post_json(
+            "/admin/keys",
+            &serde_json::json!({"name": "Admin", "scopes": ["admin"]}),
+        )
+        .await;
+    resp.assert_ok();
+    let secret = resp.json()["secret"].as_str().unwrap().to_string();
+
+    app.get("/rooms").await.assert_status(StatusCode::UNAUTHORIZED);
+    app.get_authed("/rooms", &secret).await.assert_ok();
+    app.get("/health").await.assert_ok();
+}
@@ -34,6 +34,39 @@ impl TestApp {
         }
     }
 
+    /// Create a test app that encrypts the session store under `master_key`
+    /// (64 hex chars).
+    pub fn with_master_key(master_key: &str) -> Self {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let router = Self::encrypted_router(temp_dir.path(), master_key, None);
+        Self {
+            router,
+            _temp_dir: temp_dir,
+        }
+    }
+
+    /// Rebuild the app over the same data directory with a different master
+    /// key setup, as after a restart.
+    pub fn restart_with_master_key(&mut self, master_key: &str, previous: Option<&str>) {
+        self.router = Self::encrypted_router(self._temp_dir.path(), master_key, previous);
+    }
+
+    fn encrypted_router(dir: &std::path::Path, master_key: &str, previous: Option<&str>) -> axum::Router {
+        let master = profile_service::store::encryption::MasterKeyConfig::from_values(
+            Some(master_key.to_string()),
+            previous.map(str::to_string),
+        )
+        .expect("Invalid master key")
+        .expect("Master key missing");
+        let state = profile_service::create_app_state_with_master_key(dir, Some(master));
+        profile_service::build_app(state, None)
+    }
+
+    /// The temp data directory backing this app.
+    pub fn data_dir(&self) -> &std::path::Path {
+        self._temp_dir.path()
+    }
+
     // ── Unauthenticated helpers ─────────────────────────────────────
 
     pub async fn get(&self, uri: &str) -> TestResponse {
@@ -64,10 +97,71 @@ impl TestApp {
         .await
     }
 
+    pub async fn put_bytes(&self, uri: &str, body: &[u8]) -> TestResponse {
+        self.request(
+            Request::builder()
+                .method("PUT")
+                .uri(uri)
+                .body(Body::from(body.to_vec()))
+                .unwrap(),
+        )
+        .await
+    }
+
+    pub async fn patch_bytes(&self, uri: &str, headers: &[(&str, &str)], body: &[u8]) -> TestResponse {
+        let mut req = Request::builder().method("PATCH").uri(uri);
+        for (name, value) in headers {
+            req = req.header(*name, *value);
+        }
+        self.request(req.body(Body::from(body.to_vec())).unwrap()).await
+    }
+
     pub async fn delete(&self, uri: &str) -> TestResponse {
         self.request(Request::builder().method("DELETE").uri(uri).body(Body::empty()).unwrap()).await
     }
 
+    pub async fn post_multipart(&self, uri: &str, fields: &[(&str, &[u8])]) -> TestResponse {
+        const BOUNDARY: &str = "test-boundary-7f3a";
+        let mut body = Vec::new();
+        for (name, value) in fields {
+            body.extend_from_slice(format!("--{BOUNDARY}\r\n").as_bytes());
+            body.extend_from_slice(
+                format!("Content-Disposition: form-data; name=\"{name}\"\r\n\r\n").as_bytes(),
+            );
+            body.extend_from_slice(value);
+            body.extend_from_slice(b"\r\n");
+        }
+        body.extend_from_slice(format!("--{BOUNDARY}--\r\n").as_bytes());
+        self.request(
+            Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header("content-type", format!("multipart/form-data; boundary={BOUNDARY}"))
+                .body(Body::from(body))
+                .unwrap(),
+        )
+        .await
+    }
+
+    /// Open a server-sent event stream without waiting for the body to end.
+    pub async fn open_event_stream(&self, uri: &str, last_event_id: Option<&str>) -> TestEventStream {
+        let mut req = Request::builder().method("GET").uri(uri);
+        if let Some(id) = last_event_id {
+            req = req.header("Last-Event-ID", id);
+        }
+        let response = self
+            .router
+            .clone()
+            .oneshot(req.body(Body::empty()).unwrap())
+            .await
+            .expect("Request failed");
+        TestEventStream {
+            status: response.status(),
+            body: response.into_body(),
+            buf: String::new(),
+        }
+    }
+
     // ── Authenticated helpers ───────────────────────────────────────
 
     pub async fn get_authed(&self, uri: &str, key: &str) -> TestResponse {
@@ -146,6 +240,62 @@ impl TestApp {
     }
 }
 
+/// One parsed server-sent event.
+#[derive(Debug)]
+pub struct TestEvent {
+    pub event: String,
+    pub id: String,
+    pub data: serde_json::Value,
+}
+
+/// An open server-sent event stream.
+pub struct TestEventStream {
+    pub status: StatusCode,
+    body: Body,
+    buf: String,
+}
+
+impl TestEventStream {
+    /// Next event, skipping keep-alive comments. None if nothing arrives
+    /// within a second or the stream ends.
+    pub async fn next_event(&mut self) -> Option<TestEvent> {
+        loop {
+            if let Some(end) = self.buf.find("\n\n") {
+                let block: String = self.buf.drain(..end + 2).collect();
+                let mut event = TestEvent {
+                    event: "message".into(),
+                    id: String::new(),
+                    data: serde_json::Value::Null,
+                };
+                let mut data = None;
+                for line in block.lines() {
+                    if let Some(v) = line.strip_prefix("event:") {
+                        event.event = v.trim().to_string();
+                    } else if let Some(v) = line.strip_prefix("id:") {
+                        event.id = v.trim().to_string();
+                    } else if let Some(v) = line.strip_prefix("data:") {
+                        data = Some(v.trim().to_string());
+                    }
+                }
+                match data {
+                    Some(d) => {
+                        event.data = serde_json::from_str(&d).expect("Event data is not JSON");
+                        return Some(event);
+                    }
+                    None => continue,
+                }
+            }
+            let frame = tokio::time::timeout(std::time::Duration::from_secs(1), self.body.frame())
+                .await
+                .ok()??
+                .expect("Failed to read stream");
+            if let Ok(bytes) = frame.into_data() {
+                self.buf.push_str(&String::from_utf8_lossy(&bytes));
+            }
+        }
+    }
+}
+
 /// Response wrapper with convenience methods.
 pub struct TestResponse {
     pub status: StatusCode,
@@ -158,6 +308,10 @@ impl TestResponse {
             .unwrap_or_else(|e| panic!("Failed to parse JSON: {e}\nBody: {}", self.text()))
     }
 
+    pub fn bytes(&self) -> &[u8] {
+        &self.body
+    }
+
     pub fn text(&self) -> String {
         String::from_utf8_lossy(&self.body).to_string()
     }
@@ -411,7 +411,7 @@ async fn update_operational_defaults_bumps_version() {
 #[tokio::test]
 async fn operational_defaults_store_persists_across_reload() {
     use profile_service::store::config_data::ConfigDataStore;
-    use profile_service::types::OperationalDefaults;
+    use profile_service::types::{ConfigChannel, OperationalDefaults};
 
     let tmp = tempfile::tempdir().expect("tempdir");
     let mut store = ConfigDataStore::load(tmp.path()).expect("load");
@@ -424,7 +424,7 @@ async fn operational_defaults_store_persists_across_reload() {
 
     // Reload from the same dir — values must round-trip via operational_defaults.json
     let store2 = ConfigDataStore::load(tmp.path()).expect("reload");
-    let loaded = store2.get_defaults();
+    let loaded = store2.get_defaults(ConfigChannel::Stable);
     assert_eq!(loaded.sleep_start_hour, 21);
     assert_eq!(loaded.encounter_check_interval_secs, 300);
     assert_eq!(loaded.soap_model, "my-soap");
@@ -738,3 +738,296 @@ async fn update_operational_defaults_partial_body_uses_defaults() {
     assert_eq!(updated["soap_model"], "soap-model-fast");
     assert_eq!(updated["encounter_check_interval_secs"], 120);
 }
+
+// ── Revision history ────────────────────────────────────────────
+
+#[tokio::test]
+async fn history_seeds_initial_revision_per_kind() {
+    let app = TestApp::new();
+
+    let resp = app.get("/config/history").await;
+    resp.assert_ok();
+    let json = resp.json();
+    let entries = json.as_array().unwrap();
+    assert_eq!(entries.len(), 4);
+    for entry in entries {
+        assert_eq!(entry["revision"], 1);
+        assert_eq!(entry["source"], "initial");
+    }
+
+    let resp = app.get("/config/thresholds/revisions/1").await;
+    resp.assert_ok();
+    assert_eq!(resp.json()["data"]["force_check_word_threshold"], 3000);
+}
+
+#[tokio::test]
+async fn updates_are_kept_as_revisions() {
+    let app = TestApp::new();
+
+    for threshold in [4000, 4500] {
+        app.put_json(
+            "/config/thresholds",
+            &serde_json::json!({"force_check_word_threshold": threshold}),
+        )
+        .await
+        .assert_ok();
+    }
+
+    let resp = app.get("/config/history?kind=thresholds").await;
+    resp.assert_ok();
+    let json = resp.json();
+    let revisions: Vec<u64> = json
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["revision"].as_u64().unwrap())
+        .collect();
+    assert_eq!(revisions, vec![3, 2, 1], "newest first");
+
+    // The first update is still readable after the second replaced it
+    let resp = app.get("/config/thresholds/revisions/2").await;
+    resp.assert_ok();
+    let json = resp.json();
+    assert_eq!(json["data"]["force_check_word_threshold"], 4000);
+    assert_eq!(json["source"], "update");
+
+    app.get("/config/thresholds/revisions/9")
+        .await
+        .assert_status(axum::http::StatusCode::NOT_FOUND);
+    app.get("/config/history?kind=nope")
+        .await
+        .assert_status(axum::http::StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn diff_reports_changed_added_and_removed_paths() {
+    let app = TestApp::new();
+
+    app.put_json(
+        "/config/billing",
+        &serde_json::json!({
+            "visit_type_mappings": {"general": {"code": "A007A"}},
+            "procedure_type_mappings": {"pap": "G365A"}
+        }),
+    )
+    .await
+    .assert_ok();
+    app.put_json(
+        "/config/billing",
+        &serde_json::json!({
+            "visit_type_mappings": {"general": {"code": "A001A"}},
+            "procedure_type_mappings": {"injection": "G373A"}
+        }),
+    )
+    .await
+    .assert_ok();
+
+    // Defaults to latest vs previous
+    let resp = app.get("/config/billing/diff").await;
+    resp.assert_ok();
+    let json = resp.json();
+    assert_eq!(json["from"], 2);
+    assert_eq!(json["to"], 3);
+    let changes = json["changes"].as_array().unwrap();
+    let find = |path: &str| changes.iter().find(|c| c["path"] == path).cloned();
+
+    let code = find("/visit_type_mappings/general/code").expect("code change");
+    assert_eq!(code["op"], "changed");
+    assert_eq!(code["old"], "A007A");
+    assert_eq!(code["new"], "A001A");
+    assert_eq!(find("/procedure_type_mappings/pap").unwrap()["op"], "removed");
+    assert_eq!(find("/procedure_type_mappings/injection").unwrap()["op"], "added");
+    // The version stamp differs between every revision and is not a change
+    assert!(find("/version").is_none());
+    assert_eq!(changes.len(), 3);
+}
+
+#[tokio::test]
+async fn rollback_restores_content_and_bumps_version() {
+    let app = TestApp::new();
+
+    app.put_json(
+        "/config/prompts",
+        &serde_json::json!({"encounter_detection_system": "good prompt"}),
+    )
+    .await
+    .assert_ok();
+    app.put_json(
+        "/config/prompts",
+        &serde_json::json!({"encounter_detection_system": "bad prompt"}),
+    )
+    .await
+    .assert_ok();
+    let before = app.get("/config/version").await.json()["version"]
+        .as_u64()
+        .unwrap();
+
+    let resp = app.post_json("/config/prompts/rollback/2", &serde_json::json!({})).await;
+    resp.assert_ok();
+    let json = resp.json();
+    assert_eq!(json["revision"], 4);
+    assert_eq!(json["source"], "rollback");
+    assert_eq!(json["rolled_back_from"], 2);
+
+    // Clients polling the version see it move forward, not back
+    let after = app.get("/config/version").await.json()["version"]
+        .as_u64()
+        .unwrap();
+    assert!(after > before);
+
+    let resp = app.get("/config/prompts").await;
+    resp.assert_ok();
+    let json = resp.json();
+    assert_eq!(json["encounter_detection_system"], "good prompt");
+    assert_eq!(json["version"], after);
+
+    app.post_json("/config/prompts/rollback/42", &serde_json::json!({}))
+        .await
+        .assert_status(axum::http::StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn revision_history_persists_across_reload() {
+    use profile_service::store::config_data::ConfigDataStore;
+    use profile_service::types::{ConfigChannel, ConfigKind, OperationalDefaults};
+
+    let tmp = tempfile::tempdir().expect("tempdir");
+    let mut store = ConfigDataStore::load(tmp.path()).expect("load");
+    let mut new_defaults = OperationalDefaults::default();
+    new_defaults.sleep_start_hour = 21;
+    store.update_defaults(new_defaults).expect("update");
+
+    // Reload must not re-seed an initial revision on top of existing history
+    let mut store2 = ConfigDataStore::load(tmp.path()).expect("reload");
+    assert_eq!(store2.list_history(Some(ConfigKind::Defaults)).len(), 2);
+    assert_eq!(store2.list_history(None).len(), 5);
+
+    let restored = store2.rollback(ConfigKind::Defaults, 1).expect("rollback");
+    assert_eq!(restored.meta.revision, 3);
+    assert_eq!(store2.get_defaults(ConfigChannel::Stable).sleep_start_hour, 22);
+}
+
+// ── Staged rollout (candidate channel) ──────────────────────────
+
+async fn create_room(app: &TestApp, name: &str) -> String {
+    let resp = app.post_json("/rooms", &serde_json::json!({"name": name})).await;
+    resp.assert_ok();
+    resp.json()["id"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn candidate_config_served_only_to_assigned_rooms() {
+    let app = TestApp::new();
+    let exam = create_room(&app, "Exam 1").await;
+    let other = create_room(&app, "Exam 2").await;
+    app.put_json(&format!("/rooms/{exam}"), &serde_json::json!({"config_channel": "candidate"}))
+        .await
+        .assert_ok();
+
+    app.put_json(
+        "/config/prompts?channel=candidate",
+        &serde_json::json!({"encounter_detection_system": "candidate prompt"}),
+    )
+    .await
+    .assert_ok();
+
+    let resp = app.get(&format!("/config/prompts?room_id={exam}")).await;
+    resp.assert_ok();
+    assert_eq!(resp.json()["encounter_detection_system"], "candidate prompt");
+
+    for uri in [format!("/config/prompts?room_id={other}"), "/config/prompts".to_string()] {
+        let resp = app.get(&uri).await;
+        resp.assert_ok();
+        assert_eq!(resp.json()["encounter_detection_system"], "", "{uri}");
+    }
+
+    // Kinds without a candidate fall back to stable on the candidate channel
+    let resp = app.get(&format!("/config/thresholds?room_id={exam}")).await;
+    resp.assert_ok();
+    assert_eq!(resp.json()["force_check_word_threshold"], 3000);
+
+    let resp = app.get(&format!("/config/version?room_id={exam}")).await;
+    resp.assert_ok();
+    assert_eq!(resp.json()["channel"], "candidate");
+    let resp = app.get("/config/version?room_id=deleted-room").await;
+    resp.assert_ok();
+    assert_eq!(resp.json()["channel"], "stable");
+
+    // Staging is not a stable revision
+    let history = app.get("/config/history?kind=prompts").await.json();
+    assert_eq!(history.as_array().unwrap().len(), 1);
+    assert_eq!(app.get("/config/candidate").await.json(), serde_json::json!(["prompts"]));
+}
+
+#[tokio::test]
+async fn promote_moves_candidate_to_stable() {
+    let app = TestApp::new();
+
+    app.put_json(
+        "/config/thresholds?channel=candidate",
+        &serde_json::json!({"force_check_word_threshold": 3500}),
+    )
+    .await
+    .assert_ok();
+    let staged_version = app.get("/config/version").await.json()["version"]
+        .as_u64()
+        .unwrap();
+
+    let resp = app.post_json("/config/thresholds/promote", &serde_json::json!({})).await;
+    resp.assert_ok();
+    let json = resp.json();
+    assert_eq!(json["source"], "promote");
+    assert_eq!(json["revision"], 2);
+    assert!(json["version"].as_u64().unwrap() > staged_version);
+
+    let resp = app.get("/config/thresholds").await;
+    assert_eq!(resp.json()["force_check_word_threshold"], 3500);
+    assert_eq!(app.get("/config/candidate").await.json(), serde_json::json!([]));
+
+    // Nothing left to promote
+    app.post_json("/config/thresholds/promote", &serde_json::json!({}))
+        .await
+        .assert_status(axum::http::StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn discard_candidate_returns_rooms_to_stable() {
+    let app = TestApp::new();
+
+    app.put_json(
+        "/config/prompts?channel=candidate",
+        &serde_json::json!({"clinical_content_check": "experimental"}),
+    )
+    .await
+    .assert_ok();
+    let before = app.get("/config/version").await.json()["version"]
+        .as_u64()
+        .unwrap();
+
+    app.delete("/config/prompts/candidate").await.assert_ok();
+
+    // Version moves so candidate rooms drop their cached candidate config
+    let after = app.get("/config/version").await.json()["version"]
+        .as_u64()
+        .unwrap();
+    assert!(after > before);
+    let resp = app.get("/config/prompts?channel=candidate").await;
+    assert_eq!(resp.json()["clinical_content_check"], "");
+
+    app.delete("/config/prompts/candidate")
+        .await
+        .assert_status(axum::http::StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn staged_defaults_are_validated() {
+    let app = TestApp::new();
+
+    app.put_json(
+        "/config/defaults?channel=candidate",
+        &serde_json::json!({"sleep_start_hour": 24}),
+    )
+    .await
+    .assert_status(axum::http::StatusCode::BAD_REQUEST);
+    assert_eq!(app.get("/config/candidate").await.json(), serde_json::json!([]));
+}
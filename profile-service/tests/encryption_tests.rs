@@ -0,0 +1,130 @@
+mod common;
+
+use common::TestApp;
+use profile_service::store::encryption::MasterKeyConfig;
+use std::path::{Path, PathBuf};
+
+const KEY_A: &str = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+const KEY_B: &str = "1f1e1d1c1b1a191817161514131211100f0e0d0c0b0a09080706050403020100";
+
+const SESSION_URI: &str = "/physicians/doc-1/sessions/enc-session";
+
+fn session_body() -> serde_json::Value {
+    serde_json::json!({
+        "metadata": {
+            "session_id": "enc-session",
+            "started_at": "2026-03-26T10:00:00Z",
+            "duration_ms": 1800000_u64,
+            "segment_count": 50,
+            "word_count": 500,
+            "has_soap_note": false,
+            "has_audio": false,
+            "auto_ended": false,
+            "patient_name": "John Doe"
+        },
+        "transcript": "Patient presents with chest pain.",
+        "soap_note": "S: Chest pain for 2 days."
+    })
+}
+
+fn session_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join("sessions/doc-1/2026/03/26/enc-session")
+}
+
+fn master(current: &str, previous: Option<&str>) -> Option<MasterKeyConfig> {
+    MasterKeyConfig::from_values(Some(current.into()), previous.map(str::to_string)).unwrap()
+}
+
+fn on_disk(path: &Path) -> String {
+    String::from_utf8_lossy(&std::fs::read(path).unwrap()).to_string()
+}
+
+#[tokio::test]
+async fn session_files_are_encrypted_on_disk_and_transparent_on_get() {
+    let app = TestApp::with_master_key(KEY_A);
+    app.post_json(SESSION_URI, &session_body()).await.assert_ok();
+    app.put_bytes(&format!("{SESSION_URI}/files/billing.json"), b"{\"codes\":[\"99213\"]}")
+        .await
+        .assert_ok();
+
+    let dir = session_dir(app.data_dir());
+    assert!(!on_disk(&dir.join("transcript.txt")).contains("chest pain"));
+    assert!(!on_disk(&dir.join("metadata.json")).contains("John Doe"));
+    assert!(!on_disk(&dir.join("billing.json")).contains("99213"));
+    assert!(app.data_dir().join("sessions/doc-1/data_key.json").exists());
+
+    let details = app.get(SESSION_URI).await.json();
+    assert_eq!(details["metadata"]["patient_name"], "John Doe");
+    assert_eq!(details["transcript"], "Patient presents with chest pain.");
+    let billing = app.get(&format!("{SESSION_URI}/files/billing.json")).await;
+    assert_eq!(billing.text(), "{\"codes\":[\"99213\"]}");
+
+    // Listing reads encrypted metadata too
+    let list = app.get("/physicians/doc-1/sessions?date=2026-03-26").await.json();
+    assert_eq!(list[0]["patient_name"], "John Doe");
+}
+
+#[tokio::test]
+async fn day_log_is_encrypted() {
+    let app = TestApp::with_master_key(KEY_A);
+    let uri = "/physicians/doc-1/day-log/2026-03-26";
+    app.put_bytes(uri, b"{\"event\":\"room_entered\"}\n").await.assert_ok();
+
+    let path = app.data_dir().join("sessions/doc-1/2026/03/26/day_log.jsonl");
+    assert!(!on_disk(&path).contains("room_entered"));
+    assert_eq!(app.get(uri).await.text(), "{\"event\":\"room_entered\"}\n");
+}
+
+#[tokio::test]
+async fn plaintext_tree_stays_readable_and_migrates_in_place() {
+    let mut app = TestApp::new();
+    app.post_json(SESSION_URI, &session_body()).await.assert_ok();
+    let transcript = session_dir(app.data_dir()).join("transcript.txt");
+    assert!(on_disk(&transcript).contains("chest pain"));
+
+    // Enabling encryption doesn't break existing plaintext files
+    app.restart_with_master_key(KEY_A, None);
+    app.get(SESSION_URI).await.assert_ok();
+
+    let state = profile_service::create_app_state_with_master_key(app.data_dir(), master(KEY_A, None));
+    let report = state.sessions.encrypt_existing().unwrap();
+    assert_eq!(report.encrypted, 3);
+    assert!(!on_disk(&transcript).contains("chest pain"));
+
+    // Idempotent
+    let report = state.sessions.encrypt_existing().unwrap();
+    assert_eq!(report.encrypted, 0);
+    assert_eq!(report.already_encrypted, 3);
+
+    let details = app.get(SESSION_URI).await.json();
+    assert_eq!(details["transcript"], "Patient presents with chest pain.");
+}
+
+#[tokio::test]
+async fn master_key_rotation_rewraps_without_rewriting_files() {
+    let mut app = TestApp::with_master_key(KEY_A);
+    app.post_json(SESSION_URI, &session_body()).await.assert_ok();
+    let transcript = session_dir(app.data_dir()).join("transcript.txt");
+    let before = std::fs::read(&transcript).unwrap();
+
+    let state =
+        profile_service::create_app_state_with_master_key(app.data_dir(), master(KEY_B, Some(KEY_A)));
+    assert_eq!(state.sessions.rotate_master_key().unwrap(), 1);
+    assert_eq!(std::fs::read(&transcript).unwrap(), before);
+
+    // Old key can be dropped once rewrapped
+    app.restart_with_master_key(KEY_B, None);
+    let details = app.get(SESSION_URI).await.json();
+    assert_eq!(details["transcript"], "Patient presents with chest pain.");
+}
+
+#[tokio::test]
+async fn unknown_master_key_cannot_read() {
+    let mut app = TestApp::with_master_key(KEY_A);
+    app.post_json(SESSION_URI, &session_body()).await.assert_ok();
+
+    app.restart_with_master_key(KEY_B, None);
+    app.get(SESSION_URI)
+        .await
+        .assert_status(axum::http::StatusCode::INTERNAL_SERVER_ERROR);
+}
@@ -0,0 +1,185 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::TestApp;
+use serde_json::json;
+
+async fn create_physician(app: &TestApp) -> String {
+    let resp = app.post_json("/physicians", &json!({ "name": "Dr. Mobile" })).await;
+    resp.assert_ok();
+    resp.json()["id"].as_str().unwrap().to_string()
+}
+
+async fn upload(app: &TestApp, physician_id: &str, recording_id: &str) -> String {
+    let resp = app
+        .post_multipart(
+            "/mobile/upload",
+            &[
+                ("audio", b"fake-m4a".as_slice()),
+                ("physician_id", physician_id.as_bytes()),
+                ("started_at", b"2026-03-26T10:00:00Z".as_slice()),
+                ("duration_ms", b"60000".as_slice()),
+                ("recording_id", recording_id.as_bytes()),
+            ],
+        )
+        .await;
+    resp.assert_ok();
+    resp.json()["job_id"].as_str().unwrap().to_string()
+}
+
+async fn set_status(app: &TestApp, job_id: &str, body: serde_json::Value) {
+    app.put_json(&format!("/mobile/jobs/{job_id}"), &body)
+        .await
+        .assert_ok();
+}
+
+#[tokio::test]
+async fn stream_pushes_status_changes() {
+    let app = TestApp::new();
+    let physician_id = create_physician(&app).await;
+    let job_id = upload(&app, &physician_id, "rec-1").await;
+
+    let mut stream = app.open_event_stream("/mobile/jobs/stream", None).await;
+    assert_eq!(stream.status, StatusCode::OK);
+
+    set_status(&app, &job_id, json!({ "status": "transcoding" })).await;
+    // Unchanged status is not an event
+    set_status(&app, &job_id, json!({ "status": "transcoding" })).await;
+    set_status(
+        &app,
+        &job_id,
+        json!({
+            "status": "complete",
+            "sessions_created": [
+                { "session_id": "s-1", "encounter_number": 1, "word_count": 120, "has_soap": true }
+            ]
+        }),
+    )
+    .await;
+
+    let first = stream.next_event().await.expect("no transcoding event");
+    assert_eq!(first.event, "job");
+    assert_eq!(first.id, "1");
+    assert_eq!(first.data["job_id"], job_id);
+    assert_eq!(first.data["status"], "transcoding");
+    assert!(first.data.get("sessions_created").is_none());
+
+    let done = stream.next_event().await.expect("no complete event");
+    assert_eq!(done.id, "2");
+    assert_eq!(done.data["status"], "complete");
+    assert_eq!(done.data["sessions_created"][0]["session_id"], "s-1");
+
+    assert!(stream.next_event().await.is_none());
+}
+
+#[tokio::test]
+async fn stream_replays_after_last_event_id() {
+    let app = TestApp::new();
+    let physician_id = create_physician(&app).await;
+    let job_id = upload(&app, &physician_id, "rec-1").await;
+    for status in ["transcoding", "transcribing", "detecting"] {
+        set_status(&app, &job_id, json!({ "status": status })).await;
+    }
+
+    let mut stream = app.open_event_stream("/mobile/jobs/stream", Some("1")).await;
+    let replayed = stream.next_event().await.unwrap();
+    assert_eq!(replayed.id, "2");
+    assert_eq!(replayed.data["status"], "transcribing");
+    assert_eq!(stream.next_event().await.unwrap().id, "3");
+
+    // Live events follow the replay
+    set_status(&app, &job_id, json!({ "status": "generating_soap" })).await;
+    assert_eq!(stream.next_event().await.unwrap().id, "4");
+}
+
+#[tokio::test]
+async fn stream_filters_by_physician() {
+    let app = TestApp::new();
+    let mine = create_physician(&app).await;
+    let theirs = create_physician(&app).await;
+    let my_job = upload(&app, &mine, "rec-1").await;
+    let their_job = upload(&app, &theirs, "rec-2").await;
+
+    let mut stream = app
+        .open_event_stream(&format!("/mobile/jobs/stream?physician_id={mine}"), Some("0"))
+        .await;
+    set_status(&app, &their_job, json!({ "status": "transcoding" })).await;
+    set_status(&app, &my_job, json!({ "status": "transcoding" })).await;
+
+    let event = stream.next_event().await.unwrap();
+    assert_eq!(event.id, "2");
+    assert_eq!(event.data["job_id"], my_job);
+    assert!(stream.next_event().await.is_none());
+}
+
+#[tokio::test]
+async fn stale_cursor_gets_resync() {
+    let app = TestApp::new();
+    let physician_id = create_physician(&app).await;
+    let job_id = upload(&app, &physician_id, "rec-1").await;
+    set_status(&app, &job_id, json!({ "status": "transcoding" })).await;
+
+    let mut stream = app.open_event_stream("/mobile/jobs/stream", Some("99")).await;
+    let event = stream.next_event().await.unwrap();
+    assert_eq!(event.event, "resync");
+    assert_eq!(event.id, "1");
+    assert_eq!(event.data["latest_event_id"], 1);
+
+    let resp = app.open_event_stream("/mobile/jobs/stream", Some("abc")).await;
+    assert_eq!(resp.status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn claim_leases_job_to_one_worker() {
+    let app = TestApp::new();
+    let physician_id = create_physician(&app).await;
+    let job_id = upload(&app, &physician_id, "rec-1").await;
+
+    let resp = app
+        .post_json("/mobile/jobs/claim", &json!({ "worker_id": "w1", "lease_secs": 60 }))
+        .await;
+    resp.assert_ok();
+    let job = resp.json();
+    assert_eq!(job["job_id"], job_id);
+    assert_eq!(job["attempts"], 1);
+    let lease_id = job["lease"]["lease_id"].as_str().unwrap().to_string();
+
+    let resp = app.post_json("/mobile/jobs/claim", &json!({ "worker_id": "w2" })).await;
+    resp.assert_status(StatusCode::NO_CONTENT);
+
+    // Only the lease holder may move the job along
+    app.put_json(&format!("/mobile/jobs/{job_id}"), &json!({ "status": "transcoding" }))
+        .await
+        .assert_status(StatusCode::CONFLICT);
+    set_status(&app, &job_id, json!({ "status": "transcoding", "lease_id": lease_id })).await;
+
+    app.post_json(
+        &format!("/mobile/jobs/{job_id}/heartbeat"),
+        &json!({ "lease_id": lease_id }),
+    )
+    .await
+    .assert_ok();
+
+    set_status(&app, &job_id, json!({ "status": "complete", "lease_id": lease_id })).await;
+    let job = app.get(&format!("/mobile/jobs/{job_id}")).await.json();
+    assert_eq!(job["status"], "complete");
+    assert!(job.get("lease").is_none());
+
+    app.post_json(
+        &format!("/mobile/jobs/{job_id}/heartbeat"),
+        &json!({ "lease_id": lease_id }),
+    )
+    .await
+    .assert_status(StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn claim_validates_request() {
+    let app = TestApp::new();
+    app.post_json("/mobile/jobs/claim", &json!({ "worker_id": " " }))
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+    app.post_json("/mobile/jobs/claim", &json!({ "worker_id": "w1", "lease_secs": 0 }))
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,171 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::TestApp;
+use serde_json::json;
+
+fn session(id: &str, started_at: &str, transcript: &str, soap: Option<&str>) -> serde_json::Value {
+    json!({
+        "metadata": {
+            "session_id": id,
+            "started_at": started_at,
+            "duration_ms": 600000_u64,
+            "segment_count": 10,
+            "word_count": 100,
+            "has_soap_note": soap.is_some(),
+            "has_audio": false,
+            "auto_ended": false,
+            "patient_name": "Jane Roe"
+        },
+        "transcript": transcript,
+        "soap_note": soap
+    })
+}
+
+async fn seed(app: &TestApp) {
+    app.post_json(
+        "/physicians/doc-1/sessions/s-statin",
+        &session(
+            "s-statin",
+            "2026-01-12T09:00:00Z",
+            "Speaker 1: Any muscle aches since starting the statin?\nSpeaker 2: A little.",
+            Some("A/P: Statin side effects discussed. Continue atorvastatin."),
+        ),
+    )
+    .await
+    .assert_ok();
+    app.post_json(
+        "/physicians/doc-1/sessions/s-knee",
+        &session("s-knee", "2026-02-20T14:00:00Z", "Knee pain after a fall.", None),
+    )
+    .await
+    .assert_ok();
+}
+
+#[tokio::test]
+async fn search_ranks_and_highlights() {
+    let app = TestApp::new();
+    seed(&app).await;
+
+    let resp = app
+        .get("/physicians/doc-1/search?q=did%20we%20discuss%20statin%20side%20effects")
+        .await;
+    resp.assert_ok();
+    let hits = resp.json();
+    let hits = hits.as_array().unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0]["session_id"], "s-statin");
+    assert_eq!(hits[0]["date"], "2026-01-12");
+    assert_eq!(hits[0]["patient_name"], "Jane Roe");
+
+    let snippet = &hits[0]["snippets"][0];
+    assert_eq!(snippet["field"], "soap_note");
+    let text: Vec<char> = snippet["text"].as_str().unwrap().chars().collect();
+    let first = snippet["highlights"][0].as_array().unwrap();
+    let (start, end) = (first[0].as_u64().unwrap() as usize, first[1].as_u64().unwrap() as usize);
+    assert_eq!(text[start..end].iter().collect::<String>(), "Statin");
+}
+
+#[tokio::test]
+async fn search_applies_date_filters() {
+    let app = TestApp::new();
+    seed(&app).await;
+
+    let hits = app.get("/physicians/doc-1/search?q=pain&from=2026-02-01").await.json();
+    assert_eq!(hits.as_array().unwrap().len(), 1);
+    let hits = app.get("/physicians/doc-1/search?q=pain&to=2026-01-31").await.json();
+    assert!(hits.as_array().unwrap().is_empty());
+
+    app.get("/physicians/doc-1/search?q=pain&from=January")
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+    app.get("/physicians/doc-1/search?q=%20")
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn index_follows_soap_updates_split_and_delete() {
+    let app = TestApp::new();
+    seed(&app).await;
+    // Build the index before editing
+    app.get("/physicians/doc-1/search?q=knee").await.assert_ok();
+
+    app.put_json(
+        "/physicians/doc-1/sessions/s-knee/soap",
+        &json!({ "content": "A/P: Meniscus tear suspected, order MRI." }),
+    )
+    .await
+    .assert_ok();
+    let hits = app.get("/physicians/doc-1/search?q=meniscus").await.json();
+    assert_eq!(hits[0]["session_id"], "s-knee");
+
+    let resp = app
+        .post_json("/physicians/doc-1/sessions/s-statin/split", &json!({ "split_line": 1 }))
+        .await;
+    resp.assert_ok();
+    // Split drops the SOAP note; the second transcript line moves to the new session
+    let hits = app.get("/physicians/doc-1/search?q=atorvastatin").await.json();
+    assert!(hits.as_array().unwrap().is_empty());
+    let hits = app.get("/physicians/doc-1/search?q=little").await.json();
+    assert_eq!(hits.as_array().unwrap().len(), 1);
+    assert_ne!(hits[0]["session_id"], "s-statin");
+
+    app.delete("/physicians/doc-1/sessions/s-knee").await.assert_ok();
+    let hits = app.get("/physicians/doc-1/search?q=meniscus").await.json();
+    assert!(hits.as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn edits_during_the_first_build_are_not_lost() {
+    let app = TestApp::new();
+    seed(&app).await;
+    // Searches racing the first build wait for it; the edit is either seen
+    // by the walk or replayed onto the index afterwards
+    let soap = json!({ "content": "A/P: Meniscus tear suspected, order MRI." });
+    let (first, second, edit) = tokio::join!(
+        app.get("/physicians/doc-1/search?q=knee"),
+        app.get("/physicians/doc-1/search?q=statin"),
+        app.put_json("/physicians/doc-1/sessions/s-knee/soap", &soap),
+    );
+    first.assert_ok();
+    second.assert_ok();
+    edit.assert_ok();
+    let hits = app.get("/physicians/doc-1/search?q=meniscus").await.json();
+    assert_eq!(hits[0]["session_id"], "s-knee");
+}
+
+#[tokio::test]
+async fn search_reads_encrypted_sessions() {
+    let app = TestApp::with_master_key(
+        "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+    );
+    seed(&app).await;
+    let hits = app.get("/physicians/doc-1/search?q=atorvastatin").await.json();
+    assert_eq!(hits[0]["session_id"], "s-statin");
+}
+
+#[tokio::test]
+async fn search_indexes_per_patient_notes() {
+    let app = TestApp::new();
+    seed(&app).await;
+    app.put_bytes(
+        "/physicians/doc-1/sessions/s-knee/files/soap_patient_1.txt",
+        b"A/P: Gout flare, start colchicine.",
+    )
+    .await
+    .assert_ok();
+    // Picked up when the index is built...
+    let hits = app.get("/physicians/doc-1/search?q=colchicine").await.json();
+    assert_eq!(hits[0]["session_id"], "s-knee");
+
+    // ...and when a note is uploaded after it
+    app.put_bytes(
+        "/physicians/doc-1/sessions/s-knee/files/soap_patient_2.txt",
+        b"A/P: Plantar fasciitis, stretching program.",
+    )
+    .await
+    .assert_ok();
+    let hits = app.get("/physicians/doc-1/search?q=fasciitis").await.json();
+    assert_eq!(hits[0]["session_id"], "s-knee");
+}
@@ -0,0 +1,273 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::TestApp;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data).iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn audio() -> Vec<u8> {
+    (0..10_000u32).map(|i| (i % 251) as u8).collect()
+}
+
+async fn create_physician(app: &TestApp) -> String {
+    let resp = app.post_json("/physicians", &json!({ "name": "Dr. Upload" })).await;
+    resp.assert_ok();
+    resp.json()["id"].as_str().unwrap().to_string()
+}
+
+async fn patch_chunk(app: &TestApp, upload_id: &str, offset: usize, chunk: &[u8]) -> common::TestResponse {
+    app.patch_bytes(
+        &format!("/uploads/{upload_id}"),
+        &[("Upload-Offset", &offset.to_string())],
+        chunk,
+    )
+    .await
+}
+
+#[tokio::test]
+async fn session_audio_upload_resumes_and_completes() {
+    let app = TestApp::new();
+    let physician_id = create_physician(&app).await;
+    app.post_json(
+        &format!("/physicians/{physician_id}/sessions/s1"),
+        &json!({
+            "metadata": {
+                "session_id": "s1",
+                "started_at": "2026-03-26T10:00:00Z",
+                "duration_ms": 60000_u64,
+                "segment_count": 1,
+                "word_count": 10,
+                "has_soap_note": false,
+                "has_audio": false,
+                "auto_ended": false
+            },
+            "transcript": "Hello."
+        }),
+    )
+    .await
+    .assert_ok();
+
+    let data = audio();
+    let create_uri = format!("/physicians/{physician_id}/sessions/s1/audio/resumable");
+    let create_body = json!({ "length": data.len(), "sha256": sha256_hex(&data) });
+    let resp = app.post_json(&create_uri, &create_body).await;
+    resp.assert_ok();
+    let upload_id = resp.json()["upload_id"].as_str().unwrap().to_string();
+
+    patch_chunk(&app, &upload_id, 0, &data[..4000]).await.assert_ok();
+
+    // After a restart the client may only have the file: starting again
+    // finds the same upload and its offset
+    let resumed = app.post_json(&create_uri, &create_body).await.json();
+    assert_eq!(resumed["upload_id"], upload_id);
+    assert_eq!(resumed["offset"], 4000);
+
+    patch_chunk(&app, &upload_id, 0, &data[..4000])
+        .await
+        .assert_status(StatusCode::CONFLICT);
+    let upload = app.get(&format!("/uploads/{upload_id}")).await.json();
+    assert_eq!(upload["offset"], 4000);
+    assert!(upload.get("completed_at").is_none());
+
+    let resp = patch_chunk(&app, &upload_id, 4000, &data[4000..]).await;
+    resp.assert_ok();
+    assert!(resp.json()["completed_at"].is_string());
+
+    let resp = app
+        .get(&format!("/physicians/{physician_id}/sessions/s1/audio"))
+        .await;
+    resp.assert_ok();
+    assert_eq!(resp.bytes(), data.as_slice());
+}
+
+#[tokio::test]
+async fn mobile_upload_creates_job_on_completion() {
+    let app = TestApp::new();
+    let physician_id = create_physician(&app).await;
+    let data = audio();
+
+    let resp = app
+        .post_json(
+            "/mobile/upload/resumable",
+            &json!({
+                "physician_id": physician_id,
+                "recording_id": "rec-1",
+                "started_at": "2026-03-26T10:00:00Z",
+                "duration_ms": 60000,
+                "length": data.len(),
+                "sha256": sha256_hex(&data)
+            }),
+        )
+        .await;
+    resp.assert_ok();
+    let upload_id = resp.json()["upload_id"].as_str().unwrap().to_string();
+
+    for (i, chunk) in data.chunks(3000).enumerate() {
+        patch_chunk(&app, &upload_id, i * 3000, chunk).await.assert_ok();
+    }
+    let upload = app.get(&format!("/uploads/{upload_id}")).await.json();
+    let job_id = upload["job_id"].as_str().unwrap();
+
+    let job = app.get(&format!("/mobile/jobs/{job_id}")).await.json();
+    assert_eq!(job["recording_id"], "rec-1");
+    assert_eq!(job["status"], "queued");
+    let resp = app.get(&format!("/mobile/uploads/{job_id}")).await;
+    resp.assert_ok();
+    assert_eq!(resp.bytes(), data.as_slice());
+}
+
+#[tokio::test]
+async fn checksum_mismatch_restarts_from_zero() {
+    let app = TestApp::new();
+    let physician_id = create_physician(&app).await;
+    let data = audio();
+
+    let resp = app
+        .post_json(
+            "/mobile/upload/resumable",
+            &json!({
+                "physician_id": physician_id,
+                "recording_id": "rec-1",
+                "started_at": "2026-03-26T10:00:00Z",
+                "duration_ms": 60000,
+                "length": data.len(),
+                "sha256": sha256_hex(b"something else")
+            }),
+        )
+        .await;
+    let upload_id = resp.json()["upload_id"].as_str().unwrap().to_string();
+
+    let resp = patch_chunk(&app, &upload_id, 0, &data).await;
+    resp.assert_status(StatusCode::BAD_REQUEST);
+    assert!(resp.json()["error"].as_str().unwrap().contains("Checksum mismatch"));
+    let upload = app.get(&format!("/uploads/{upload_id}")).await.json();
+    assert_eq!(upload["offset"], 0);
+    assert!(upload.get("job_id").is_none());
+}
+
+#[tokio::test]
+async fn rejects_bad_requests() {
+    let app = TestApp::new();
+    let physician_id = create_physician(&app).await;
+
+    app.post_json(
+        &format!("/physicians/{physician_id}/sessions/missing/audio/resumable"),
+        &json!({ "length": 10, "sha256": sha256_hex(b"x") }),
+    )
+    .await
+    .assert_status(StatusCode::NOT_FOUND);
+
+    let resp = app
+        .post_json(
+            "/mobile/upload/resumable",
+            &json!({
+                "physician_id": physician_id,
+                "recording_id": "rec-1",
+                "started_at": "2026-03-26T10:00:00Z",
+                "duration_ms": 60000,
+                "length": 4,
+                "sha256": sha256_hex(b"abcd")
+            }),
+        )
+        .await;
+    let upload_id = resp.json()["upload_id"].as_str().unwrap().to_string();
+
+    app.patch_bytes(&format!("/uploads/{upload_id}"), &[], b"abcd")
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+    patch_chunk(&app, &upload_id, 0, b"abcdef")
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+    app.get("/uploads/not-a-uuid")
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn accepts_chunks_above_default_body_limit() {
+    let app = TestApp::new();
+    let physician_id = create_physician(&app).await;
+    let data = vec![7u8; 3 * 1024 * 1024];
+
+    let resp = app
+        .post_json(
+            "/mobile/upload/resumable",
+            &json!({
+                "physician_id": physician_id,
+                "recording_id": "rec-1",
+                "started_at": "2026-03-26T10:00:00Z",
+                "duration_ms": 60000,
+                "length": data.len(),
+                "sha256": sha256_hex(&data)
+            }),
+        )
+        .await;
+    let upload_id = resp.json()["upload_id"].as_str().unwrap().to_string();
+
+    let resp = patch_chunk(&app, &upload_id, 0, &data).await;
+    resp.assert_ok();
+    assert!(resp.json()["completed_at"].is_string());
+}
+
+#[tokio::test]
+async fn registry_keys_only_reach_their_own_uploads() {
+    const LEGACY: &str = "secret-key-123";
+    let app = TestApp::with_auth(LEGACY);
+    let physician_id = app
+        .post_json_authed("/physicians", &json!({ "name": "Dr. Upload" }), LEGACY)
+        .await
+        .json()["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let mut secrets = Vec::new();
+    for name in ["iPhone A", "iPhone B"] {
+        let resp = app
+            .post_json_authed(
+                "/admin/keys",
+                &json!({ "name": name, "scopes": ["mobile_upload"] }),
+                LEGACY,
+            )
+            .await;
+        resp.assert_ok();
+        secrets.push(resp.json()["secret"].as_str().unwrap().to_string());
+    }
+    let (owner, other) = (&secrets[0], &secrets[1]);
+
+    let create_body = json!({
+        "physician_id": physician_id,
+        "recording_id": "rec-1",
+        "started_at": "2026-03-26T10:00:00Z",
+        "duration_ms": 60000,
+        "length": 4,
+        "sha256": sha256_hex(b"abcd")
+    });
+    let resp = app
+        .post_json_authed("/mobile/upload/resumable", &create_body, owner)
+        .await;
+    resp.assert_ok();
+    let upload_id = resp.json()["upload_id"].as_str().unwrap().to_string();
+    let uri = format!("/uploads/{upload_id}");
+
+    app.get_authed(&uri, other)
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    app.patch_bytes(&uri, &[("Upload-Offset", "0"), ("X-API-Key", other)], b"ab")
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    // Starting the same file with another key doesn't hand out this upload
+    let resp = app
+        .post_json_authed("/mobile/upload/resumable", &create_body, other)
+        .await;
+    assert_ne!(resp.json()["upload_id"], upload_id.as_str());
+
+    app.patch_bytes(&uri, &[("Upload-Offset", "0"), ("X-API-Key", owner)], b"ab")
+        .await
+        .assert_ok();
+    assert_eq!(app.get_authed(&uri, owner).await.json()["offset"], 2);
+    assert_eq!(app.get_authed(&uri, LEGACY).await.json()["offset"], 2);
+}
@@ -0,0 +1,678 @@
+//! Full-text search over the local archive's transcripts and SOAP notes.
+//!
+//! Mirrors the profile-service index (`store::search_index`) so local and
+//! server hits rank and highlight the same way and can be merged by
+//! `commands::archive::search_archive`. The index lives in memory and is
+//! refreshed on each search by comparing file modification times, so only
+//! sessions written since the last search are re-read.
+//!
+//! Matching is AND over the query's non-stopword terms, with prefix
+//! expansion for terms of [`MIN_PREFIX_LEN`]+ characters ("statin" finds
+//! "statins"). Ranking is BM25F with SOAP hits weighted above transcript
+//! hits.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+
+/// Which part of an archived session a snippet came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchField {
+    Transcript,
+    SoapNote,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchSnippet {
+    pub field: SearchField,
+    pub text: String,
+    /// `[start, end)` character offsets of matched terms within `text`
+    pub highlights: Vec<[usize; 2]>,
+}
+
+/// One ranked result, same shape as profile-service `GET /physicians/:id/search`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub session_id: String,
+    pub date: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub patient_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encounter_number: Option<u32>,
+    pub score: f64,
+    pub snippets: Vec<SearchSnippet>,
+}
+
+pub const DEFAULT_LIMIT: usize = 20;
+pub const MAX_LIMIT: usize = 100;
+
+/// Shorter query terms only match whole words
+const MIN_PREFIX_LEN: usize = 3;
+
+/// Characters of context either side of the first hit in a snippet
+const SNIPPET_CONTEXT: usize = 80;
+
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+const SOAP_WEIGHT: f64 = 1.5;
+const TRANSCRIPT_WEIGHT: f64 = 1.0;
+
+/// Dropped from queries so natural phrasing ("when did we discuss the
+/// statin side effects") still matches. Documents are indexed in full.
+const STOPWORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "by", "did", "do", "for", "from", "had", "has",
+    "have", "i", "in", "is", "it", "of", "on", "or", "that", "the", "this", "to", "was", "we",
+    "were", "what", "when", "where", "which", "who", "with", "you",
+];
+
+/// A session as the index sees it
+#[derive(Debug, Clone)]
+pub struct SearchDoc {
+    pub session_id: String,
+    /// YYYY-MM-DD
+    pub date: String,
+    pub started_at: Option<String>,
+    pub patient_name: Option<String>,
+    pub encounter_number: Option<u32>,
+    pub transcript: String,
+    pub soap_note: String,
+}
+
+impl SearchDoc {
+    fn field(&self, field: SearchField) -> &str {
+        match field {
+            SearchField::Transcript => &self.transcript,
+            SearchField::SoapNote => &self.soap_note,
+        }
+    }
+}
+
+/// Term frequencies in each field
+#[derive(Debug, Default, Clone, Copy)]
+struct Postings {
+    transcript: u32,
+    soap_note: u32,
+}
+
+struct IndexedDoc {
+    doc: SearchDoc,
+    transcript_len: usize,
+    soap_len: usize,
+}
+
+#[derive(Default)]
+pub struct SearchIndex {
+    docs: HashMap<String, IndexedDoc>,
+    /// term → session_id → frequencies. Ordered for prefix scans.
+    terms: BTreeMap<String, HashMap<String, Postings>>,
+}
+
+/// A word in a text, with `[start, end)` character offsets
+struct Token {
+    term: String,
+    start: usize,
+    end: usize,
+}
+
+/// Lowercased alphanumeric runs
+fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut start = 0;
+    for (i, c) in text.chars().enumerate() {
+        if c.is_alphanumeric() {
+            if current.is_empty() {
+                start = i;
+            }
+            current.extend(c.to_lowercase());
+        } else if !current.is_empty() {
+            tokens.push(Token {
+                term: std::mem::take(&mut current),
+                start,
+                end: i,
+            });
+        }
+    }
+    if !current.is_empty() {
+        let end = text.chars().count();
+        tokens.push(Token {
+            term: current,
+            start,
+            end,
+        });
+    }
+    tokens
+}
+
+/// Distinct query terms, minus stopwords unless that would leave nothing
+fn query_terms(query: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    let all: Vec<String> = tokenize(query)
+        .into_iter()
+        .map(|t| t.term)
+        .filter(|t| seen.insert(t.clone()))
+        .collect();
+    let content: Vec<String> = all
+        .iter()
+        .filter(|t| !STOPWORDS.contains(&t.as_str()))
+        .cloned()
+        .collect();
+    if content.is_empty() {
+        all
+    } else {
+        content
+    }
+}
+
+impl SearchIndex {
+    pub fn len(&self) -> usize {
+        self.docs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.docs.is_empty()
+    }
+
+    pub fn session_ids(&self) -> impl Iterator<Item = &str> {
+        self.docs.keys().map(String::as_str)
+    }
+
+    /// Add or replace a session.
+    pub fn upsert(&mut self, doc: SearchDoc) {
+        self.remove(&doc.session_id);
+
+        let transcript = tokenize(&doc.transcript);
+        let soap = tokenize(&doc.soap_note);
+        for t in &transcript {
+            self.postings_mut(&t.term, &doc.session_id).transcript += 1;
+        }
+        for t in &soap {
+            self.postings_mut(&t.term, &doc.session_id).soap_note += 1;
+        }
+        self.docs.insert(
+            doc.session_id.clone(),
+            IndexedDoc {
+                transcript_len: transcript.len(),
+                soap_len: soap.len(),
+                doc,
+            },
+        );
+    }
+
+    pub fn remove(&mut self, session_id: &str) {
+        let Some(old) = self.docs.remove(session_id) else {
+            return;
+        };
+        let terms: HashSet<String> = tokenize(&old.doc.transcript)
+            .into_iter()
+            .chain(tokenize(&old.doc.soap_note))
+            .map(|t| t.term)
+            .collect();
+        for term in terms {
+            if let Some(postings) = self.terms.get_mut(&term) {
+                postings.remove(session_id);
+                if postings.is_empty() {
+                    self.terms.remove(&term);
+                }
+            }
+        }
+    }
+
+    fn postings_mut(&mut self, term: &str, session_id: &str) -> &mut Postings {
+        self.terms
+            .entry(term.to_string())
+            .or_default()
+            .entry(session_id.to_string())
+            .or_default()
+    }
+
+    /// Indexed terms a query term matches: itself, plus longer words it
+    /// prefixes when long enough.
+    fn expand(&self, query_term: &str) -> Vec<&str> {
+        if query_term.chars().count() < MIN_PREFIX_LEN {
+            return self
+                .terms
+                .get_key_value(query_term)
+                .map(|(k, _)| vec![k.as_str()])
+                .unwrap_or_default();
+        }
+        self.terms
+            .range(query_term.to_string()..)
+            .take_while(|(k, _)| k.starts_with(query_term))
+            .map(|(k, _)| k.as_str())
+            .collect()
+    }
+
+    /// Ranked sessions containing every query term, optionally limited to
+    /// dates in `[from, to]` (inclusive, YYYY-MM-DD).
+    pub fn search(
+        &self,
+        query: &str,
+        from: Option<&str>,
+        to: Option<&str>,
+        limit: usize,
+    ) -> Vec<SearchHit> {
+        let terms = query_terms(query);
+        if terms.is_empty() || self.docs.is_empty() {
+            return Vec::new();
+        }
+
+        let n = self.docs.len() as f64;
+        let avg_transcript =
+            (self.docs.values().map(|d| d.transcript_len).sum::<usize>() as f64 / n).max(1.0);
+        let avg_soap = (self.docs.values().map(|d| d.soap_len).sum::<usize>() as f64 / n).max(1.0);
+
+        // Per query term: the expanded index terms, and per-session scores
+        let mut matched_terms: HashSet<&str> = HashSet::new();
+        let mut scores: Option<HashMap<&str, f64>> = None;
+        for term in &terms {
+            let expansions = self.expand(term);
+            matched_terms.extend(expansions.iter().copied());
+
+            let mut per_doc: HashMap<&str, Postings> = HashMap::new();
+            for exp in &expansions {
+                for (sid, p) in &self.terms[*exp] {
+                    let acc = per_doc.entry(sid.as_str()).or_default();
+                    acc.transcript += p.transcript;
+                    acc.soap_note += p.soap_note;
+                }
+            }
+
+            let df = per_doc.len() as f64;
+            let idf = (1.0 + (n - df + 0.5) / (df + 0.5)).ln();
+            let term_scores: HashMap<&str, f64> = per_doc
+                .into_iter()
+                .map(|(sid, p)| {
+                    let d = &self.docs[sid];
+                    let tf = TRANSCRIPT_WEIGHT * p.transcript as f64
+                        / (1.0 - B + B * d.transcript_len as f64 / avg_transcript)
+                        + SOAP_WEIGHT * p.soap_note as f64
+                            / (1.0 - B + B * d.soap_len as f64 / avg_soap);
+                    (sid, idf * tf * (K1 + 1.0) / (tf + K1))
+                })
+                .collect();
+
+            // AND: keep only sessions that matched every term so far
+            scores = Some(match scores {
+                None => term_scores,
+                Some(prev) => prev
+                    .into_iter()
+                    .filter_map(|(sid, s)| term_scores.get(sid).map(|t| (sid, s + t)))
+                    .collect(),
+            });
+        }
+
+        let mut hits: Vec<SearchHit> = scores
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(sid, score)| (&self.docs[sid].doc, score))
+            .filter(|(doc, _)| from.is_none_or(|f| doc.date.as_str() >= f))
+            .filter(|(doc, _)| to.is_none_or(|t| doc.date.as_str() <= t))
+            .map(|(doc, score)| SearchHit {
+                session_id: doc.session_id.clone(),
+                date: doc.date.clone(),
+                started_at: doc.started_at.clone(),
+                patient_name: doc.patient_name.clone(),
+                encounter_number: doc.encounter_number,
+                score,
+                snippets: [SearchField::SoapNote, SearchField::Transcript]
+                    .into_iter()
+                    .filter_map(|f| snippet(f, doc.field(f), &matched_terms))
+                    .collect(),
+            })
+            .collect();
+
+        hits.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| b.date.cmp(&a.date))
+                .then_with(|| a.session_id.cmp(&b.session_id))
+        });
+        hits.truncate(limit);
+        hits
+    }
+}
+
+/// Window around the first matched term in `text`, with every match inside
+/// it highlighted. `None` if the field has no match.
+fn snippet(field: SearchField, text: &str, matched: &HashSet<&str>) -> Option<SearchSnippet> {
+    let tokens = tokenize(text);
+    let first = tokens.iter().find(|t| matched.contains(t.term.as_str()))?;
+
+    let chars: Vec<char> = text.chars().collect();
+    let mut start = first.start.saturating_sub(SNIPPET_CONTEXT);
+    let mut end = (first.end + SNIPPET_CONTEXT).min(chars.len());
+    // Don't cut words in half
+    while start > 0 && chars[start - 1].is_alphanumeric() {
+        start -= 1;
+    }
+    while end < chars.len() && chars[end].is_alphanumeric() {
+        end += 1;
+    }
+
+    let prefix = if start > 0 { "…" } else { "" };
+    let suffix = if end < chars.len() { "…" } else { "" };
+    let offset = prefix.chars().count();
+    let body: String = chars[start..end].iter().collect::<String>().replace('\n', " ");
+    let highlights = tokens
+        .iter()
+        .filter(|t| t.start >= start && t.end <= end && matched.contains(t.term.as_str()))
+        .map(|t| [t.start - start + offset, t.end - start + offset])
+        .collect();
+
+    Some(SearchSnippet {
+        field,
+        text: format!("{prefix}{body}{suffix}"),
+        highlights,
+    })
+}
+
+/// Combine server and local hits for the same query. Server hits win for
+/// sessions present in both (they include other workstations' edits);
+/// local-only hits (not yet synced) are added. Scores come from different
+/// corpora, so the interleaving of local-only hits is approximate.
+pub fn merge_hits(server: Vec<SearchHit>, local: Vec<SearchHit>, limit: usize) -> Vec<SearchHit> {
+    let server_ids: HashSet<String> = server.iter().map(|h| h.session_id.clone()).collect();
+    let mut merged = server;
+    merged.extend(local.into_iter().filter(|h| !server_ids.contains(&h.session_id)));
+    merged.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| b.date.cmp(&a.date))
+    });
+    merged.truncate(limit);
+    merged
+}
+
+// ============================================================================
+// Local archive
+// ============================================================================
+
+/// Just the metadata fields search results need
+#[derive(Deserialize)]
+struct SearchMetadata {
+    #[serde(default)]
+    started_at: Option<String>,
+    #[serde(default)]
+    patient_name: Option<String>,
+    #[serde(default)]
+    encounter_number: Option<u32>,
+}
+
+/// Index for one archive root plus the newest mtime seen per session
+#[derive(Default)]
+struct LocalIndex {
+    index: SearchIndex,
+    stamps: HashMap<String, SystemTime>,
+}
+
+fn local_indexes() -> &'static Mutex<HashMap<PathBuf, LocalIndex>> {
+    static INDEXES: OnceLock<Mutex<HashMap<PathBuf, LocalIndex>>> = OnceLock::new();
+    INDEXES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Search the local archive (`local_archive::get_archive_dir`).
+pub fn search_local(
+    query: &str,
+    from: Option<&str>,
+    to: Option<&str>,
+    limit: usize,
+) -> Result<Vec<SearchHit>, String> {
+    let base = crate::local_archive::get_archive_dir()?;
+    search_archive_dir(&base, query, from, to, limit)
+}
+
+/// Search an archive rooted at `base` (`YYYY/MM/DD/{session_id}/`),
+/// refreshing the cached index first.
+pub fn search_archive_dir(
+    base: &Path,
+    query: &str,
+    from: Option<&str>,
+    to: Option<&str>,
+    limit: usize,
+) -> Result<Vec<SearchHit>, String> {
+    let mut indexes = local_indexes()
+        .lock()
+        .map_err(|_| "Search index lock poisoned".to_string())?;
+    let local = indexes.entry(base.to_path_buf()).or_default();
+    refresh(local, base)?;
+    Ok(local.index.search(query, from, to, limit))
+}
+
+/// Re-read sessions whose files changed since the last refresh and drop
+/// sessions that are gone.
+fn refresh(local: &mut LocalIndex, base: &Path) -> Result<(), String> {
+    let mut seen = HashSet::new();
+    if base.exists() {
+        for (date, dir) in session_dirs(base)? {
+            let Some(session_id) = dir.file_name().and_then(|n| n.to_str()).map(str::to_string)
+            else {
+                continue;
+            };
+            let stamp = ["metadata.json", "transcript.txt", "soap_note.txt"]
+                .iter()
+                .filter_map(|f| fs::metadata(dir.join(f)).and_then(|m| m.modified()).ok())
+                .max();
+            let Some(stamp) = stamp else {
+                continue;
+            };
+            seen.insert(session_id.clone());
+            if local.stamps.get(&session_id) == Some(&stamp) {
+                continue;
+            }
+            match read_doc(&dir, &session_id, date) {
+                Some(doc) => local.index.upsert(doc),
+                None => local.index.remove(&session_id),
+            }
+            local.stamps.insert(session_id, stamp);
+        }
+    }
+    local.stamps.retain(|id, _| seen.contains(id));
+    let stale: Vec<String> = local
+        .index
+        .session_ids()
+        .filter(|id| !seen.contains(*id))
+        .map(str::to_string)
+        .collect();
+    for id in stale {
+        local.index.remove(&id);
+    }
+    Ok(())
+}
+
+/// Every `(YYYY-MM-DD, session_dir)` under the archive root
+fn session_dirs(base: &Path) -> Result<Vec<(String, PathBuf)>, String> {
+    fn subdirs(dir: &Path) -> Vec<PathBuf> {
+        fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|e| e.path())
+                    .filter(|p| p.is_dir())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+    fn name(p: &Path) -> &str {
+        p.file_name().and_then(|n| n.to_str()).unwrap_or_default()
+    }
+
+    fs::read_dir(base).map_err(|e| format!("Failed to read archive: {}", e))?;
+    let mut out = Vec::new();
+    for year in subdirs(base) {
+        for month in subdirs(&year) {
+            for day in subdirs(&month) {
+                let date = format!("{}-{}-{}", name(&year), name(&month), name(&day));
+                for session in subdirs(&day) {
+                    out.push((date.clone(), session));
+                }
+            }
+        }
+    }
+    Ok(out)
+}
+
+fn read_doc(dir: &Path, session_id: &str, date: String) -> Option<SearchDoc> {
+    let meta: SearchMetadata =
+        serde_json::from_str(&fs::read_to_string(dir.join("metadata.json")).ok()?).ok()?;
+    Some(SearchDoc {
+        session_id: session_id.to_string(),
+        date,
+        started_at: meta.started_at,
+        patient_name: meta.patient_name,
+        encounter_number: meta.encounter_number,
+        transcript: fs::read_to_string(dir.join("transcript.txt")).unwrap_or_default(),
+        soap_note: fs::read_to_string(dir.join("soap_note.txt")).unwrap_or_default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doc(id: &str, date: &str, transcript: &str, soap: &str) -> SearchDoc {
+        SearchDoc {
+            session_id: id.into(),
+            date: date.into(),
+            started_at: None,
+            patient_name: None,
+            encounter_number: None,
+            transcript: transcript.into(),
+            soap_note: soap.into(),
+        }
+    }
+
+    fn index() -> SearchIndex {
+        let mut idx = SearchIndex::default();
+        idx.upsert(doc(
+            "a",
+            "2026-01-10",
+            "We talked about the statin. Muscle aches are a known side effect.",
+            "A/P: Statins — discussed side effects, continue atorvastatin.",
+        ));
+        idx.upsert(doc("b", "2026-02-03", "Knee pain after running.", "A/P: Knee strain."));
+        idx.upsert(doc("c", "2026-03-01", "Side effects of the new inhaler.", ""));
+        idx
+    }
+
+    #[test]
+    fn natural_question_matches_with_prefix_expansion() {
+        let hits = index().search("did we discuss the statin side effects", None, None, 10);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].session_id, "a");
+    }
+
+    #[test]
+    fn all_terms_required() {
+        let hits = index().search("side effects", None, None, 10);
+        let ids: Vec<_> = hits.iter().map(|h| h.session_id.as_str()).collect();
+        assert_eq!(ids.len(), 2);
+        assert!(ids.contains(&"a") && ids.contains(&"c"));
+        assert!(index().search("knee statin", None, None, 10).is_empty());
+    }
+
+    #[test]
+    fn soap_hits_outrank_transcript_hits() {
+        let mut idx = SearchIndex::default();
+        idx.upsert(doc("t", "2026-01-01", "Discussed gout flare.", "Follow up in May."));
+        idx.upsert(doc("s", "2026-01-01", "Follow up in May.", "Discussed gout flare."));
+        let hits = idx.search("gout", None, None, 10);
+        assert_eq!(hits[0].session_id, "s");
+        assert!(hits[0].score > hits[1].score);
+    }
+
+    #[test]
+    fn date_filters_are_inclusive() {
+        let idx = index();
+        let hits = idx.search("side effects", Some("2026-03-01"), None, 10);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].session_id, "c");
+        let hits = idx.search("side effects", None, Some("2026-01-10"), 10);
+        assert_eq!(hits[0].session_id, "a");
+    }
+
+    #[test]
+    fn snippets_highlight_matches() {
+        let hits = index().search("knee", None, None, 10);
+        let s = &hits[0].snippets[0];
+        assert_eq!(s.field, SearchField::SoapNote);
+        let chars: Vec<char> = s.text.chars().collect();
+        let [start, end] = s.highlights[0];
+        assert_eq!(chars[start..end].iter().collect::<String>(), "Knee");
+    }
+
+    fn write_session(base: &Path, date: &str, id: &str, transcript: &str, soap: Option<&str>) {
+        let dir = base.join(date.replace('-', "/")).join(id);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("metadata.json"),
+            format!(r#"{{"session_id":"{id}","started_at":"{date}T09:00:00Z","patient_name":"Jane Roe"}}"#),
+        )
+        .unwrap();
+        fs::write(dir.join("transcript.txt"), transcript).unwrap();
+        if let Some(soap) = soap {
+            fs::write(dir.join("soap_note.txt"), soap).unwrap();
+        }
+    }
+
+    #[test]
+    fn searches_local_archive_and_picks_up_changes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let base = tmp.path();
+        write_session(base, "2026-01-12", "s1", "Muscle aches on the statin.", Some("A/P: statin myalgia."));
+        write_session(base, "2026-02-20", "s2", "Knee pain.", None);
+
+        let hits = search_archive_dir(base, "statin", None, None, 10).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].session_id, "s1");
+        assert_eq!(hits[0].date, "2026-01-12");
+        assert_eq!(hits[0].patient_name.as_deref(), Some("Jane Roe"));
+
+        // New session, then a deleted one
+        write_session(base, "2026-03-01", "s3", "Switched statin brands.", None);
+        assert_eq!(search_archive_dir(base, "statin", None, None, 10).unwrap().len(), 2);
+        fs::remove_dir_all(base.join("2026/01/12/s1")).unwrap();
+        let hits = search_archive_dir(base, "statin", None, None, 10).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].session_id, "s3");
+    }
+
+    #[test]
+    fn merge_prefers_server_and_keeps_local_only() {
+        let hit = |id: &str, score: f64| SearchHit {
+            session_id: id.into(),
+            date: "2026-01-01".into(),
+            started_at: None,
+            patient_name: None,
+            encounter_number: None,
+            score,
+            snippets: Vec::new(),
+        };
+        let merged = merge_hits(
+            vec![hit("a", 2.0), hit("b", 1.0)],
+            vec![hit("a", 9.0), hit("c", 1.5)],
+            10,
+        );
+        let ids: Vec<_> = merged.iter().map(|h| h.session_id.as_str()).collect();
+        assert_eq!(ids, ["a", "c", "b"]);
+        assert_eq!(merged[0].score, 2.0);
+        assert_eq!(merge_hits(vec![hit("a", 2.0)], vec![hit("c", 1.0)], 1).len(), 1);
+    }
+
+    #[test]
+    fn upsert_replaces_and_remove_forgets() {
+        let mut idx = index();
+        idx.upsert(doc("b", "2026-02-03", "Shoulder pain.", ""));
+        assert!(idx.search("knee", None, None, 10).is_empty());
+        assert_eq!(idx.search("shoulder", None, None, 10).len(), 1);
+        idx.remove("b");
+        assert!(idx.search("shoulder", None, None, 10).is_empty());
+        assert_eq!(idx.len(), 2);
+    }
+}
@@ -2,7 +2,9 @@
 //!
 //! Queues WAV audio files for asynchronous upload to the profile server
 //! when the app is idle (not actively recording). The queue persists to
-//! disk so pending uploads survive app restarts.
+//! disk so pending uploads survive app restarts, along with how far each
+//! resumable upload got, so an interrupted file continues from the last
+//! offset the server acknowledged instead of starting over.
 
 use crate::commands::{SharedContinuousModeState, SharedPipelineState};
 use crate::profile_client::ProfileClient;
@@ -20,6 +22,12 @@ pub struct QueueEntry {
     pub session_id: String,
     pub audio_path: PathBuf,
     pub added_at: String,
+    /// Server upload ID once the resumable upload has started
+    #[serde(default)]
+    pub upload_id: Option<String>,
+    /// Last offset the server acknowledged
+    #[serde(default)]
+    pub uploaded_bytes: u64,
 }
 
 /// In-memory queue backed by a JSON file on disk.
@@ -67,10 +75,22 @@ impl AudioUploadQueue {
             session_id,
             audio_path,
             added_at: chrono::Utc::now().to_rfc3339(),
+            upload_id: None,
+            uploaded_bytes: 0,
         });
         self.save();
     }
 
+    /// Remember how far a session's upload got.
+    pub fn record_progress(&mut self, session_id: &str, upload_id: &str, uploaded_bytes: u64) {
+        let Some(entry) = self.entries.iter_mut().find(|e| e.session_id == session_id) else {
+            return;
+        };
+        entry.upload_id = Some(upload_id.to_string());
+        entry.uploaded_bytes = uploaded_bytes;
+        self.save();
+    }
+
     /// Peek at the next entry without removing it.
     pub fn next(&self) -> Option<&QueueEntry> {
         self.entries.front()
@@ -157,10 +177,32 @@ pub async fn audio_upload_task(
 
             let client = profile_client.read().await.clone();
             if let Some(client) = client {
-                match client
-                    .upload_audio(&entry.physician_id, &entry.session_id, &entry.audio_path)
-                    .await
-                {
+                if entry.uploaded_bytes > 0 {
+                    info!(
+                        "Resuming audio upload for session {} at {} bytes",
+                        entry.session_id, entry.uploaded_bytes
+                    );
+                }
+                let result = client
+                    .upload_audio(
+                        &entry.physician_id,
+                        &entry.session_id,
+                        &entry.audio_path,
+                        entry.upload_id.as_deref(),
+                        |upload| {
+                            let queue = queue.clone();
+                            let session_id = entry.session_id.clone();
+                            async move {
+                                queue.lock().await.record_progress(
+                                    &session_id,
+                                    &upload.upload_id,
+                                    upload.offset,
+                                );
+                            }
+                        },
+                    )
+                    .await;
+                match result {
                     Ok(()) => {
                         info!("Uploaded audio for session {}", entry.session_id);
                         queue.lock().await.remove_first();
@@ -280,11 +322,39 @@ mod tests {
             session_id: "sess1".to_string(),
             audio_path: PathBuf::from("/tmp/audio.wav"),
             added_at: "2026-03-18T12:00:00Z".to_string(),
+            upload_id: Some("u1".to_string()),
+            uploaded_bytes: 4096,
         };
 
         let json = serde_json::to_string(&entry).unwrap();
         let deserialized: QueueEntry = serde_json::from_str(&json).unwrap();
         assert_eq!(deserialized.session_id, "sess1");
         assert_eq!(deserialized.physician_id, "dr1");
+        assert_eq!(deserialized.uploaded_bytes, 4096);
+    }
+
+    #[test]
+    fn test_entries_queued_before_resumable_uploads_still_load() {
+        let json = r#"{"physician_id":"dr1","session_id":"sess1","audio_path":"/tmp/a.wav","added_at":"2026-03-18T12:00:00Z"}"#;
+        let entry: QueueEntry = serde_json::from_str(json).unwrap();
+        assert!(entry.upload_id.is_none());
+        assert_eq!(entry.uploaded_bytes, 0);
+    }
+
+    #[test]
+    fn test_record_progress_updates_matching_entry() {
+        let mut queue = AudioUploadQueue {
+            entries: VecDeque::new(),
+            queue_path: PathBuf::from("/tmp/test_audio_queue6.json"),
+        };
+        queue.add("dr1".to_string(), "sess1".to_string(), PathBuf::from("/tmp/a.wav"));
+        queue.add("dr1".to_string(), "sess2".to_string(), PathBuf::from("/tmp/b.wav"));
+
+        queue.record_progress("sess2", "up-2", 8192);
+        queue.record_progress("missing", "up-x", 1);
+
+        assert!(queue.entries[0].upload_id.is_none());
+        assert_eq!(queue.entries[1].upload_id.as_deref(), Some("up-2"));
+        assert_eq!(queue.entries[1].uploaded_bytes, 8192);
     }
 }
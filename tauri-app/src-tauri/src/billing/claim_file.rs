@@ -0,0 +1,778 @@
+//! OHIP claims batch file export (MOH EDT fixed-width format).
+//!
+//! Turns confirmed `BillingRecord`s into a single claims batch that can be
+//! uploaded through the Ministry's Electronic Data Transfer (EDT) service
+//! instead of re-keying every code into third-party billing software.
+//!
+//! Layout (Interface to Health Care Systems technical specification, V03):
+//!
+//! ```text
+//! HEB  batch header          (one per file)
+//! HEH  claim header-1        (one per encounter)
+//! HET  item record           (one per billed code / time entry)
+//! HEE  batch trailer         (one per file, carries H/R/T record counts)
+//! ```
+//!
+//! Every record is exactly 79 bytes followed by a carriage return. Numeric
+//! fields are right-justified and zero-filled; alphanumeric fields are
+//! left-justified and space-filled.
+//!
+//! Fees are submitted at the full FFS rate the UI shows (`BillingCode::fee_cents`
+//! × quantity, and `TimeEntry` rate × billable units). FHO shadow percentages
+//! and the after-hours uplift are applied by the Ministry at payment time, so
+//! they never appear on the claim line itself.
+//!
+//! Encounters that fail validation (bad health number, unknown code, missing
+//! diagnostic code, …) are left out of the batch and reported back per
+//! encounter so the clinician can fix them and re-export.
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use super::diagnostic_codes::is_valid_diagnostic_code;
+use super::ohip_codes::get_code;
+use super::types::{BillingRecord, BillingStatus};
+
+// ── Format constants ───────────────────────────────────────────────────────
+
+/// Fixed record length, excluding the trailing carriage return.
+pub const RECORD_LEN: usize = 79;
+/// Technical specification release identifier written into the batch header.
+pub const TECH_SPEC_RELEASE: &str = "V03";
+/// Payment program for Ontario residents (Health Care Payment).
+const PAYMENT_PROGRAM_HCP: &str = "HCP";
+/// Payee: pay the provider.
+const PAYEE_PROVIDER: &str = "P";
+/// Largest fee the 6-digit "fee submitted" field can carry ($9,999.99).
+const MAX_FEE_SUBMITTED_CENTS: u64 = 999_999;
+/// Largest value of the 2-digit "number of services" field.
+const MAX_NUMBER_OF_SERVICES: u32 = 99;
+/// Record terminator mandated by the spec.
+const RECORD_TERMINATOR: char = '\r';
+
+// ── Inputs ─────────────────────────────────────────────────────────────────
+
+/// Provider identity written into the batch header. Supplied by the
+/// physician at export time (not stored in the archive).
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ClaimBatchHeader {
+    /// 6-digit OHIP billing number.
+    pub provider_number: String,
+    /// 4-character group number ("0000" for solo billing).
+    #[serde(default = "default_group_number")]
+    pub group_number: String,
+    /// 2-digit specialty code ("00" = family practice).
+    #[serde(default = "default_specialty_code")]
+    pub specialty_code: String,
+    /// Single-letter MOH office code that processes the claims.
+    pub moh_office_code: String,
+    /// Batch sequence number for the batch date (0–9999).
+    #[serde(default)]
+    pub batch_sequence: u16,
+}
+
+fn default_group_number() -> String {
+    "0000".to_string()
+}
+fn default_specialty_code() -> String {
+    "00".to_string()
+}
+
+/// Health-card data for one patient. Never persisted by the app — the
+/// clinician supplies it at export time.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PatientHealthCard {
+    /// 10-digit Ontario health number.
+    pub health_number: String,
+    /// 0–2 letter version code printed on the card.
+    #[serde(default)]
+    pub version_code: String,
+    /// Patient birth date (YYYY-MM-DD). Required; `export_ohip_claim_file`
+    /// fills it from the archived DOB before building the batch.
+    #[serde(default)]
+    pub birth_date: Option<String>,
+}
+
+/// One encounter to place on the batch: the billing record plus whatever
+/// health-card data the clinician provided for that patient.
+#[derive(Clone, Debug)]
+pub struct ClaimEncounter {
+    pub record: BillingRecord,
+    pub health_card: Option<PatientHealthCard>,
+}
+
+// ── Outputs ────────────────────────────────────────────────────────────────
+
+/// Validation problems for a single encounter. The encounter is omitted
+/// from the batch whenever `errors` is non-empty.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EncounterClaimErrors {
+    pub session_id: String,
+    pub date: String,
+    pub patient_name: Option<String>,
+    pub errors: Vec<String>,
+}
+
+/// Result of building a claims batch.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ClaimBatchExport {
+    /// Full file contents, CR-terminated records.
+    pub contents: String,
+    /// Batch identification written to the header (YYYYMMDD + 4-digit sequence).
+    pub batch_id: String,
+    /// Number of HEH claim header records.
+    pub claim_count: u32,
+    /// Number of HET item records.
+    pub item_count: u32,
+    /// Sum of all submitted fees, in cents.
+    pub total_fee_submitted_cents: u64,
+    /// Draft records in range that were skipped (only confirmed records are claimed).
+    pub skipped_draft_count: u32,
+    /// Encounters left out of the batch because they failed validation.
+    pub errors: Vec<EncounterClaimErrors>,
+}
+
+/// One line item on a claim, after validation.
+#[derive(Clone, Debug, PartialEq)]
+struct ClaimItem {
+    service_code: String,
+    fee_submitted_cents: u64,
+    number_of_services: u32,
+    service_date: NaiveDate,
+    diagnostic_code: String,
+}
+
+/// A fully validated claim, ready to be rendered.
+#[derive(Clone, Debug)]
+struct ValidatedClaim {
+    health_number: String,
+    version_code: String,
+    birth_date: NaiveDate,
+    accounting_number: String,
+    items: Vec<ClaimItem>,
+}
+
+// ── Public entry point ─────────────────────────────────────────────────────
+
+/// Build a claims batch for `encounters`.
+///
+/// Returns `Err` only when the batch header itself is invalid (nothing could
+/// be submitted). Per-encounter problems are collected in
+/// [`ClaimBatchExport::errors`] and those encounters are left out.
+pub fn build_claim_batch(
+    header: &ClaimBatchHeader,
+    batch_date: NaiveDate,
+    encounters: &[ClaimEncounter],
+) -> Result<ClaimBatchExport, String> {
+    validate_header(header)?;
+
+    let mut ordered: Vec<&ClaimEncounter> = encounters.iter().collect();
+    ordered.sort_by(|a, b| {
+        a.record
+            .date
+            .cmp(&b.record.date)
+            .then_with(|| a.record.session_id.cmp(&b.record.session_id))
+    });
+
+    let mut claims = Vec::new();
+    let mut errors = Vec::new();
+    let mut skipped_draft_count = 0u32;
+
+    for enc in ordered {
+        if enc.record.status != BillingStatus::Confirmed {
+            skipped_draft_count += 1;
+            continue;
+        }
+        match validate_encounter(enc) {
+            Ok(claim) => claims.push(claim),
+            Err(errs) => errors.push(EncounterClaimErrors {
+                session_id: enc.record.session_id.clone(),
+                date: enc.record.date.clone(),
+                patient_name: enc.record.patient_name.clone(),
+                errors: errs,
+            }),
+        }
+    }
+
+    let batch_id = format!(
+        "{}{:04}",
+        batch_date.format("%Y%m%d"),
+        header.batch_sequence.min(9999)
+    );
+
+    let mut contents = String::new();
+    push_record(&mut contents, batch_header_record(header, &batch_id));
+
+    let mut item_count = 0u32;
+    let mut total_fee_submitted_cents = 0u64;
+    for claim in &claims {
+        push_record(&mut contents, claim_header_record(claim));
+        for item in &claim.items {
+            push_record(&mut contents, item_record(item));
+            item_count += 1;
+            total_fee_submitted_cents += item.fee_submitted_cents;
+        }
+    }
+    let claim_count = claims.len() as u32;
+    push_record(&mut contents, batch_trailer_record(claim_count, 0, item_count));
+
+    Ok(ClaimBatchExport {
+        contents,
+        batch_id,
+        claim_count,
+        item_count,
+        total_fee_submitted_cents,
+        skipped_draft_count,
+        errors,
+    })
+}
+
+// ── Validation ─────────────────────────────────────────────────────────────
+
+fn validate_header(header: &ClaimBatchHeader) -> Result<(), String> {
+    let mut problems = Vec::new();
+    if !is_digits(&header.provider_number, 6) {
+        problems.push("provider number must be 6 digits");
+    }
+    if header.group_number.len() != 4 || !header.group_number.chars().all(|c| c.is_ascii_alphanumeric()) {
+        problems.push("group number must be 4 alphanumeric characters");
+    }
+    if !is_digits(&header.specialty_code, 2) {
+        problems.push("specialty code must be 2 digits");
+    }
+    if header.moh_office_code.len() != 1 || !header.moh_office_code.chars().all(|c| c.is_ascii_uppercase()) {
+        problems.push("MOH office code must be a single uppercase letter");
+    }
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Invalid claim batch header: {}", problems.join("; ")))
+    }
+}
+
+/// Validate one confirmed encounter. Collects every problem rather than
+/// stopping at the first so the clinician can fix them in one pass.
+fn validate_encounter(enc: &ClaimEncounter) -> Result<ValidatedClaim, Vec<String>> {
+    let record = &enc.record;
+    let mut errors = Vec::new();
+
+    let service_date = match NaiveDate::parse_from_str(&record.date, "%Y-%m-%d") {
+        Ok(d) => Some(d),
+        Err(_) => {
+            errors.push(format!("Invalid service date '{}'", record.date));
+            None
+        }
+    };
+
+    let card = enc.health_card.clone().unwrap_or_default();
+    let health_number: String = card.health_number.chars().filter(|c| !c.is_whitespace() && *c != '-').collect();
+    if health_number.is_empty() {
+        errors.push("Missing health number".to_string());
+    } else if !is_valid_health_number(&health_number) {
+        errors.push(format!("Invalid health number '{}'", health_number));
+    }
+
+    let version_code = card.version_code.trim().to_uppercase();
+    if version_code.len() > 2 || !version_code.chars().all(|c| c.is_ascii_uppercase()) {
+        errors.push(format!("Invalid version code '{}'", card.version_code));
+    }
+
+    let birth_date = match card.birth_date.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+        None => {
+            errors.push("Missing patient birth date".to_string());
+            None
+        }
+        Some(s) => match NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+            Ok(d) if service_date.is_some_and(|sd| d > sd) => {
+                errors.push(format!("Birth date {} is after the service date", s));
+                None
+            }
+            Ok(d) => Some(d),
+            Err(_) => {
+                errors.push(format!("Invalid birth date '{}'", s));
+                None
+            }
+        },
+    };
+
+    let diagnostic_code = match record.diagnostic_code.as_deref().map(str::trim) {
+        None | Some("") => {
+            errors.push("Missing diagnostic code".to_string());
+            None
+        }
+        Some(dx) if !is_valid_diagnostic_code(dx) => {
+            errors.push(format!("Unknown diagnostic code '{}'", dx));
+            None
+        }
+        Some(dx) => Some(dx.to_string()),
+    };
+
+    // (service code, fee per unit, number of services)
+    let mut lines: Vec<(&str, u32, u32)> = Vec::new();
+    for c in &record.codes {
+        lines.push((&c.code, c.fee_cents, c.quantity.max(1) as u32));
+    }
+    for te in &record.time_entries {
+        if te.billable_units > 0 {
+            lines.push((&te.code, te.rate_per_15min_cents, te.billable_units as u32));
+        }
+    }
+    if lines.is_empty() {
+        errors.push("No billable codes on record".to_string());
+    }
+
+    let mut items = Vec::new();
+    for (code, unit_fee_cents, services) in lines {
+        if get_code(code).is_none() {
+            errors.push(format!("Unknown OHIP service code '{}'", code));
+            continue;
+        }
+        if services > MAX_NUMBER_OF_SERVICES {
+            errors.push(format!("{}: {} services exceeds the maximum of {}", code, services, MAX_NUMBER_OF_SERVICES));
+            continue;
+        }
+        let fee = unit_fee_cents as u64 * services as u64;
+        if fee > MAX_FEE_SUBMITTED_CENTS {
+            errors.push(format!("{}: fee ${:.2} exceeds the claim field maximum", code, fee as f64 / 100.0));
+            continue;
+        }
+        if let (Some(sd), Some(dx)) = (service_date, diagnostic_code.as_ref()) {
+            items.push(ClaimItem {
+                service_code: code.to_string(),
+                fee_submitted_cents: fee,
+                number_of_services: services,
+                service_date: sd,
+                diagnostic_code: dx.clone(),
+            });
+        }
+    }
+
+    match (errors.is_empty(), birth_date) {
+        (true, Some(birth_date)) => Ok(ValidatedClaim {
+            health_number,
+            version_code,
+            birth_date,
+            accounting_number: accounting_number(&record.session_id),
+            items,
+        }),
+        _ => Err(errors),
+    }
+}
+
+/// Ontario health numbers are 10 digits whose last digit is a Luhn
+/// (mod-10) check digit over the first nine.
+pub fn is_valid_health_number(hn: &str) -> bool {
+    if !is_digits(hn, 10) {
+        return false;
+    }
+    let digits: Vec<u32> = hn.chars().filter_map(|c| c.to_digit(10)).collect();
+    let sum: u32 = digits[..9]
+        .iter()
+        .enumerate()
+        .map(|(i, &d)| {
+            if i % 2 == 0 {
+                let doubled = d * 2;
+                doubled / 10 + doubled % 10
+            } else {
+                d
+            }
+        })
+        .sum();
+    (10 - sum % 10) % 10 == digits[9]
+}
+
+/// Claim accounting number: the first 8 alphanumeric characters of the
+/// session ID, upper-cased. MOH echoes it back on the remittance advice,
+/// which is how paid claims are matched to archived records.
+pub fn accounting_number(session_id: &str) -> String {
+    session_id
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .take(8)
+        .collect::<String>()
+        .to_uppercase()
+}
+
+fn is_digits(s: &str, len: usize) -> bool {
+    s.len() == len && s.chars().all(|c| c.is_ascii_digit())
+}
+
+// ── Record rendering ───────────────────────────────────────────────────────
+
+fn batch_header_record(header: &ClaimBatchHeader, batch_id: &str) -> String {
+    let mut r = String::with_capacity(RECORD_LEN);
+    r.push_str("HEB");
+    r.push_str(TECH_SPEC_RELEASE);
+    r.push_str(&alpha(&header.moh_office_code, 1));
+    r.push_str(&alpha(batch_id, 12));
+    r.push_str(&numeric(0, 6)); // operator number
+    r.push_str(&alpha(&header.group_number, 4));
+    r.push_str(&alpha(&header.provider_number, 6));
+    r.push_str(&alpha(&header.specialty_code, 2));
+    r
+}
+
+fn claim_header_record(claim: &ValidatedClaim) -> String {
+    let mut r = String::with_capacity(RECORD_LEN);
+    r.push_str("HEH");
+    r.push_str(&alpha(&claim.health_number, 10));
+    r.push_str(&alpha(&claim.version_code, 2));
+    r.push_str(&claim.birth_date.format("%Y%m%d").to_string());
+    r.push_str(&alpha(&claim.accounting_number, 8));
+    r.push_str(PAYMENT_PROGRAM_HCP);
+    r.push_str(PAYEE_PROVIDER);
+    r.push_str(&alpha("", 6)); // referring provider number
+    r.push_str(&alpha("", 4)); // master number (facility)
+    r.push_str(&alpha("", 8)); // in-patient admission date
+    r.push_str(&alpha("", 4)); // referring lab licence
+    r.push_str(&alpha("", 1)); // manual review indicator
+    r.push_str(&alpha("", 4)); // service location indicator
+    r
+}
+
+fn item_record(item: &ClaimItem) -> String {
+    let mut r = String::with_capacity(RECORD_LEN);
+    r.push_str("HET");
+    r.push_str(&alpha(&item.service_code, 5));
+    r.push_str(&alpha("", 2)); // reserved
+    r.push_str(&numeric(item.fee_submitted_cents, 6));
+    r.push_str(&numeric(item.number_of_services as u64, 2));
+    r.push_str(&item.service_date.format("%Y%m%d").to_string());
+    r.push_str(&alpha(&item.diagnostic_code, 4));
+    r
+}
+
+fn batch_trailer_record(h_count: u32, r_count: u32, t_count: u32) -> String {
+    let mut r = String::with_capacity(RECORD_LEN);
+    r.push_str("HEE");
+    r.push_str(&numeric(h_count as u64, 4));
+    r.push_str(&numeric(r_count as u64, 4));
+    r.push_str(&numeric(t_count as u64, 5));
+    r
+}
+
+/// Pad the record's reserved tail with spaces and append it with its terminator.
+fn push_record(out: &mut String, mut record: String) {
+    debug_assert!(record.len() <= RECORD_LEN, "record overflow: {record:?}");
+    while record.len() < RECORD_LEN {
+        record.push(' ');
+    }
+    out.push_str(&record);
+    out.push(RECORD_TERMINATOR);
+}
+
+/// Left-justified, space-filled, truncated to `width`.
+fn alpha(value: &str, width: usize) -> String {
+    let truncated: String = value.chars().filter(|c| c.is_ascii()).take(width).collect();
+    format!("{:<width$}", truncated, width = width)
+}
+
+/// Right-justified, zero-filled. Callers validate the range beforehand.
+fn numeric(value: u64, width: usize) -> String {
+    format!("{:0>width$}", value, width = width)
+}
+
+// ── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::billing::types::{BillingCode, BillingConfidence, TimeEntry};
+
+    // Luhn-valid 10-digit health number used throughout.
+    const HN: &str = "9876543217";
+
+    fn header() -> ClaimBatchHeader {
+        ClaimBatchHeader {
+            provider_number: "123456".into(),
+            group_number: "0000".into(),
+            specialty_code: "00".into(),
+            moh_office_code: "N".into(),
+            batch_sequence: 1,
+        }
+    }
+
+    fn code(code: &str, fee_cents: u32, quantity: u8) -> BillingCode {
+        BillingCode {
+            code: code.into(),
+            description: "Test".into(),
+            fee_cents,
+            category: "in_basket".into(),
+            shadow_pct: 30,
+            billable_amount_cents: fee_cents * 30 / 100,
+            confidence: BillingConfidence::High,
+            auto_extracted: true,
+            after_hours: false,
+            after_hours_premium_cents: 0,
+            quantity,
+            limit_reason: None,
+        }
+    }
+
+    fn record(session_id: &str, date: &str, status: BillingStatus) -> BillingRecord {
+        BillingRecord {
+            session_id: session_id.into(),
+            date: date.into(),
+            patient_name: Some("Jane Doe".into()),
+            status,
+            codes: vec![code("A007A", 4195, 1)],
+            time_entries: vec![TimeEntry {
+                code: "Q310A".into(),
+                description: "Direct Patient Care".into(),
+                rate_per_15min_cents: 2000,
+                minutes: 30,
+                billable_units: 2,
+                billable_amount_cents: 4000,
+                auto_calculated: true,
+            }],
+            total_shadow_cents: 0,
+            total_out_of_basket_cents: 0,
+            total_time_based_cents: 0,
+            total_amount_cents: 0,
+            confirmed_at: None,
+            notes: None,
+            extraction_model: None,
+            extracted_at: None,
+            diagnostic_code: Some("250".into()),
+            diagnostic_description: None,
+            diagnostic_evidence: None,
+            diagnostic_reasoning: None,
+            suggestions: vec![],
+            applied_upgrades: vec![],
+            suppressed_codes: vec![],
+            remittance: None,
+        }
+    }
+
+    fn card() -> PatientHealthCard {
+        PatientHealthCard {
+            health_number: HN.into(),
+            version_code: "ab".into(),
+            birth_date: Some("1970-01-15".into()),
+        }
+    }
+
+    fn encounter(rec: BillingRecord) -> ClaimEncounter {
+        ClaimEncounter { record: rec, health_card: Some(card()) }
+    }
+
+    fn batch_date() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 5, 1).unwrap()
+    }
+
+    fn records_of(contents: &str) -> Vec<&str> {
+        contents.split(RECORD_TERMINATOR).filter(|r| !r.is_empty()).collect()
+    }
+
+    #[test]
+    fn test_health_number_luhn() {
+        assert!(is_valid_health_number(HN));
+        assert!(!is_valid_health_number("9876543210"));
+        assert!(!is_valid_health_number("987654321"));
+        assert!(!is_valid_health_number("98765432AB"));
+    }
+
+    #[test]
+    fn test_accounting_number_from_session_id() {
+        assert_eq!(accounting_number("3f2a9c1e-77b0-4d4e-9a1b-000000000000"), "3F2A9C1E");
+        assert_eq!(accounting_number("ab-c"), "ABC");
+    }
+
+    #[test]
+    fn test_every_record_is_fixed_width() {
+        let export = build_claim_batch(
+            &header(),
+            batch_date(),
+            &[encounter(record("s1", "2026-04-30", BillingStatus::Confirmed))],
+        )
+        .unwrap();
+        let recs = records_of(&export.contents);
+        assert_eq!(recs.len(), 5); // HEB + HEH + 2×HET + HEE
+        for r in &recs {
+            assert_eq!(r.len(), RECORD_LEN, "bad record width: {r:?}");
+        }
+        assert!(export.contents.ends_with(RECORD_TERMINATOR));
+    }
+
+    #[test]
+    fn test_batch_header_layout() {
+        let export = build_claim_batch(&header(), batch_date(), &[]).unwrap();
+        let heb = records_of(&export.contents)[0];
+        assert_eq!(&heb[0..3], "HEB");
+        assert_eq!(&heb[3..6], "V03");
+        assert_eq!(&heb[6..7], "N");
+        assert_eq!(&heb[7..19], "202605010001");
+        assert_eq!(&heb[19..25], "000000");
+        assert_eq!(&heb[25..29], "0000");
+        assert_eq!(&heb[29..35], "123456");
+        assert_eq!(&heb[35..37], "00");
+        assert_eq!(export.batch_id, "202605010001");
+    }
+
+    #[test]
+    fn test_claim_and_item_layout() {
+        let export = build_claim_batch(
+            &header(),
+            batch_date(),
+            &[encounter(record("abcd1234-xyz", "2026-04-30", BillingStatus::Confirmed))],
+        )
+        .unwrap();
+        let recs = records_of(&export.contents);
+
+        let heh = recs[1];
+        assert_eq!(&heh[0..3], "HEH");
+        assert_eq!(&heh[3..13], HN);
+        assert_eq!(&heh[13..15], "AB");
+        assert_eq!(&heh[15..23], "19700115");
+        assert_eq!(&heh[23..31], "ABCD1234");
+        assert_eq!(&heh[31..34], "HCP");
+        assert_eq!(&heh[34..35], "P");
+
+        let het = recs[2];
+        assert_eq!(&het[0..3], "HET");
+        assert_eq!(&het[3..8], "A007A");
+        assert_eq!(&het[10..16], "004195");
+        assert_eq!(&het[16..18], "01");
+        assert_eq!(&het[18..26], "20260430");
+        assert_eq!(&het[26..30], "250 ");
+
+        // Time entry: 2 units × $20.00
+        let het_time = recs[3];
+        assert_eq!(&het_time[3..8], "Q310A");
+        assert_eq!(&het_time[10..16], "004000");
+        assert_eq!(&het_time[16..18], "02");
+    }
+
+    #[test]
+    fn test_trailer_counts_and_totals() {
+        let export = build_claim_batch(
+            &header(),
+            batch_date(),
+            &[
+                encounter(record("s1", "2026-04-30", BillingStatus::Confirmed)),
+                encounter(record("s2", "2026-04-30", BillingStatus::Confirmed)),
+            ],
+        )
+        .unwrap();
+        let recs = records_of(&export.contents);
+        let hee = recs.last().unwrap();
+        assert_eq!(&hee[0..3], "HEE");
+        assert_eq!(&hee[3..7], "0002");
+        assert_eq!(&hee[7..11], "0000");
+        assert_eq!(&hee[11..16], "00004");
+        assert_eq!(export.claim_count, 2);
+        assert_eq!(export.item_count, 4);
+        assert_eq!(export.total_fee_submitted_cents, 2 * (4195 + 4000));
+    }
+
+    #[test]
+    fn test_quantity_multiplies_fee() {
+        let mut rec = record("s1", "2026-04-30", BillingStatus::Confirmed);
+        rec.codes = vec![code("G385A", 1000, 2)];
+        rec.time_entries.clear();
+        let export = build_claim_batch(&header(), batch_date(), &[encounter(rec)]).unwrap();
+        let het = records_of(&export.contents)[2];
+        assert_eq!(&het[10..16], "002000");
+        assert_eq!(&het[16..18], "02");
+    }
+
+    #[test]
+    fn test_drafts_are_skipped_not_errors() {
+        let export = build_claim_batch(
+            &header(),
+            batch_date(),
+            &[encounter(record("s1", "2026-04-30", BillingStatus::Draft))],
+        )
+        .unwrap();
+        assert_eq!(export.claim_count, 0);
+        assert_eq!(export.skipped_draft_count, 1);
+        assert!(export.errors.is_empty());
+    }
+
+    #[test]
+    fn test_validation_errors_reported_per_encounter() {
+        let mut bad = record("bad", "2026-04-30", BillingStatus::Confirmed);
+        bad.diagnostic_code = None;
+        bad.codes.push(code("ZZZZZ", 100, 1));
+        let mut bad_enc = encounter(bad);
+        bad_enc.health_card = Some(PatientHealthCard {
+            health_number: "1234567890".into(),
+            version_code: "A".into(),
+            birth_date: None,
+        });
+
+        let export = build_claim_batch(
+            &header(),
+            batch_date(),
+            &[bad_enc, encounter(record("good", "2026-04-30", BillingStatus::Confirmed))],
+        )
+        .unwrap();
+
+        assert_eq!(export.claim_count, 1);
+        assert_eq!(export.errors.len(), 1);
+        let errs = &export.errors[0];
+        assert_eq!(errs.session_id, "bad");
+        assert_eq!(errs.patient_name.as_deref(), Some("Jane Doe"));
+        assert!(errs.errors.iter().any(|e| e.contains("health number")));
+        assert!(errs.errors.iter().any(|e| e.contains("birth date")));
+        assert!(errs.errors.iter().any(|e| e.contains("diagnostic code")));
+        assert!(errs.errors.iter().any(|e| e.contains("ZZZZZ")));
+    }
+
+    #[test]
+    fn test_missing_health_card_is_an_error() {
+        let enc = ClaimEncounter {
+            record: record("s1", "2026-04-30", BillingStatus::Confirmed),
+            health_card: None,
+        };
+        let export = build_claim_batch(&header(), batch_date(), &[enc]).unwrap();
+        assert_eq!(export.claim_count, 0);
+        assert!(export.errors[0].errors.iter().any(|e| e == "Missing health number"));
+    }
+
+    #[test]
+    fn test_invalid_header_rejected() {
+        let mut h = header();
+        h.provider_number = "12".into();
+        h.moh_office_code = "n".into();
+        let err = build_claim_batch(&h, batch_date(), &[]).unwrap_err();
+        assert!(err.contains("provider number"));
+        assert!(err.contains("office code"));
+    }
+
+    #[test]
+    fn test_claims_ordered_by_date_then_session() {
+        let export = build_claim_batch(
+            &header(),
+            batch_date(),
+            &[
+                encounter(record("bbbbbbbb", "2026-04-30", BillingStatus::Confirmed)),
+                encounter(record("cccccccc", "2026-04-29", BillingStatus::Confirmed)),
+                encounter(record("aaaaaaaa", "2026-04-30", BillingStatus::Confirmed)),
+            ],
+        )
+        .unwrap();
+        let accounts: Vec<&str> = records_of(&export.contents)
+            .into_iter()
+            .filter(|r| r.starts_with("HEH"))
+            .map(|r| &r[23..31])
+            .collect();
+        assert_eq!(accounts, vec!["CCCCCCCC", "AAAAAAAA", "BBBBBBBB"]);
+    }
+
+    #[test]
+    fn test_serde_camel_case_inputs() {
+        let h: ClaimBatchHeader =
+            serde_json::from_str(r#"{"providerNumber":"123456","mohOfficeCode":"N"}"#).unwrap();
+        assert_eq!(h.group_number, "0000");
+        assert_eq!(h.specialty_code, "00");
+        assert_eq!(h.batch_sequence, 0);
+        let c: PatientHealthCard = serde_json::from_str(r#"{"healthNumber":"9876543217"}"#).unwrap();
+        assert_eq!(c.version_code, "");
+        assert!(c.birth_date.is_none());
+    }
+}
@@ -801,6 +801,53 @@ pub const COMMON_DIAGNOSTIC_CODES: &[(&str, &str)] = &[
     ("799", "Other ill-defined conditions (use as last resort)"),
 ];
 
+// ── ICD-10 crosswalk ────────────────────────────────────────────────────────
+
+/// OHIP diagnostic code → WHO ICD-10 category, for codes where the OHIP
+/// grouping lands on a single ICD-10 category. Broad OHIP groupings that span
+/// several categories (e.g. 300, 427, 599) are deliberately absent; FHIR
+/// resources built from them carry the OHIP coding only.
+pub const ICD10_CROSSWALK: &[(&str, &str)] = &[
+    ("244", "E03"),
+    ("250", "E14"),
+    ("272", "E78"),
+    ("274", "M10"),
+    ("280", "D50"),
+    ("296", "F31"),
+    ("309", "F43"),
+    ("311", "F32"),
+    ("346", "G43"),
+    ("382", "H66"),
+    ("401", "I10"),
+    ("410", "I21"),
+    ("428", "I50"),
+    ("436", "I64"),
+    ("451", "I80"),
+    ("460", "J00"),
+    ("461", "J01"),
+    ("466", "J20"),
+    ("477", "J30"),
+    ("486", "J18"),
+    ("491", "J42"),
+    ("493", "J45"),
+    ("496", "J44"),
+    ("574", "K80"),
+    ("585", "N18"),
+    ("600", "N40"),
+    ("692", "L25"),
+    ("715", "M19"),
+    ("724", "M54"),
+    ("917", "Z00"),
+];
+
+/// ICD-10 category for an OHIP diagnostic code, when the mapping is unambiguous.
+pub fn icd10_for_diagnostic_code(code: &str) -> Option<&'static str> {
+    ICD10_CROSSWALK
+        .iter()
+        .find(|(ohip, _)| *ohip == code)
+        .map(|(_, icd10)| *icd10)
+}
+
 // ── Tests ────────────────────────────────────────────────────────────────────
 
 #[cfg(test)]
@@ -812,6 +859,16 @@ mod tests {
         assert_eq!(DIAGNOSTIC_CODES.len(), DIAGNOSTIC_CODE_COUNT);
     }
 
+    #[test]
+    fn test_icd10_crosswalk_only_maps_known_codes() {
+        for (ohip, icd10) in ICD10_CROSSWALK {
+            assert!(is_valid_diagnostic_code(ohip), "{ohip} is not an OHIP code");
+            assert_eq!(icd10.len(), 3, "{ohip} maps to {icd10}, not a category");
+        }
+        assert_eq!(icd10_for_diagnostic_code("401"), Some("I10"));
+        assert_eq!(icd10_for_diagnostic_code("427"), None);
+    }
+
     #[test]
     fn test_get_known_code() {
         let code = get_diagnostic_code("250").expect("250 should exist");
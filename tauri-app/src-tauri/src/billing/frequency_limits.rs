@@ -0,0 +1,223 @@
+//! Per-patient annual frequency limits (`OhipCode::max_per_year`).
+//!
+//! The rule engine only sees the current encounter, so on its own it would
+//! re-suggest once-a-year codes (periodic health visits, K039A, K013A
+//! counselling units, …) at every visit. [`PriorCodeUsage`] tallies what was
+//! already billed for the same patient in the 12 months before the service
+//! date, and [`apply_frequency_limits`] trims or suppresses codes that would
+//! push the patient past the limit, stamping the reason on the code.
+//!
+//! History comes from prior `billing.json` files for the patient's sessions
+//! (resolved via the profile-service patient index). Only confirmed records
+//! count — drafts are unreviewed suggestions, not claims.
+
+use chrono::{Duration, NaiveDate};
+use std::collections::HashMap;
+
+use super::ohip_codes;
+use super::types::{BillingCode, BillingRecord, BillingStatus};
+
+/// Look-back window for "per year" limits. OHIP applies them per 12-month
+/// period ending on the service date, not per calendar year.
+pub const LOOKBACK_DAYS: i64 = 365;
+
+/// Units of each OHIP code already billed for one patient inside the
+/// look-back window.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PriorCodeUsage {
+    units_by_code: HashMap<String, u32>,
+    /// Number of prior encounters that contributed to the tally.
+    pub encounters_counted: usize,
+}
+
+impl PriorCodeUsage {
+    /// Tally confirmed codes from `records`, skipping the current session
+    /// and anything outside `(service_date - LOOKBACK_DAYS, service_date]`.
+    pub fn from_records(
+        records: &[BillingRecord],
+        current_session_id: &str,
+        service_date: NaiveDate,
+    ) -> Self {
+        let window_start = service_date - Duration::days(LOOKBACK_DAYS);
+        let mut usage = Self::default();
+        for rec in records {
+            if rec.session_id == current_session_id || rec.status != BillingStatus::Confirmed {
+                continue;
+            }
+            let Ok(date) = NaiveDate::parse_from_str(&rec.date, "%Y-%m-%d") else {
+                continue;
+            };
+            if date <= window_start || date > service_date {
+                continue;
+            }
+            for c in &rec.codes {
+                *usage.units_by_code.entry(c.code.clone()).or_insert(0) += c.quantity.max(1) as u32;
+            }
+            usage.encounters_counted += 1;
+        }
+        usage
+    }
+
+    /// Units of `code` already billed in the window.
+    pub fn units(&self, code: &str) -> u32 {
+        self.units_by_code.get(code).copied().unwrap_or(0)
+    }
+
+    /// Units of `code` still available this period, or `None` when the code
+    /// has no annual limit.
+    pub fn remaining(&self, code: &str) -> Option<u32> {
+        let max = ohip_codes::get_code(code)?.max_per_year? as u32;
+        Some(max.saturating_sub(self.units(code)))
+    }
+}
+
+/// Enforce annual limits on `codes` in place.
+///
+/// - Codes whose limit is already used up are removed and returned (with
+///   `limit_reason` set) so the caller can keep them on the record for review.
+/// - Codes that would only partly exceed the limit keep the remaining units
+///   and get a `limit_reason` explaining the reduced quantity.
+pub fn apply_frequency_limits(
+    codes: &mut Vec<BillingCode>,
+    usage: &PriorCodeUsage,
+) -> Vec<BillingCode> {
+    let mut suppressed = Vec::new();
+    let mut kept = Vec::with_capacity(codes.len());
+    for mut c in codes.drain(..) {
+        let Some(max) = ohip_codes::get_code(&c.code).and_then(|o| o.max_per_year) else {
+            kept.push(c);
+            continue;
+        };
+        let used = usage.units(&c.code);
+        let remaining = (max as u32).saturating_sub(used);
+        let requested = c.quantity.max(1) as u32;
+        if remaining == 0 {
+            c.limit_reason = Some(format!(
+                "{} is limited to {} per year; {} already billed in the past 12 months",
+                c.code, max, used
+            ));
+            suppressed.push(c);
+        } else if requested > remaining {
+            c.limit_reason = Some(format!(
+                "{} is limited to {} per year; {} already billed in the past 12 months, quantity reduced from {} to {}",
+                c.code, max, used, requested, remaining
+            ));
+            c.quantity = remaining as u8;
+            kept.push(c);
+        } else {
+            kept.push(c);
+        }
+    }
+    *codes = kept;
+    suppressed
+}
+
+// ── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::billing::types::BillingConfidence;
+
+    fn code(code: &str, quantity: u8) -> BillingCode {
+        BillingCode {
+            code: code.into(),
+            description: "Test".into(),
+            fee_cents: 1000,
+            category: "in_basket".into(),
+            shadow_pct: 30,
+            billable_amount_cents: 300,
+            confidence: BillingConfidence::High,
+            auto_extracted: true,
+            after_hours: false,
+            after_hours_premium_cents: 0,
+            quantity,
+            limit_reason: None,
+        }
+    }
+
+    fn record(session_id: &str, date: &str, status: BillingStatus, codes: Vec<BillingCode>) -> BillingRecord {
+        BillingRecord {
+            session_id: session_id.into(),
+            date: date.into(),
+            patient_name: None,
+            status,
+            codes,
+            time_entries: vec![],
+            total_shadow_cents: 0,
+            total_out_of_basket_cents: 0,
+            total_time_based_cents: 0,
+            total_amount_cents: 0,
+            confirmed_at: None,
+            notes: None,
+            extraction_model: None,
+            extracted_at: None,
+            diagnostic_code: None,
+            diagnostic_description: None,
+            diagnostic_evidence: None,
+            diagnostic_reasoning: None,
+            suggestions: vec![],
+            applied_upgrades: vec![],
+            suppressed_codes: vec![],
+            remittance: None,
+        }
+    }
+
+    fn service_date() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 5, 1).unwrap()
+    }
+
+    #[test]
+    fn test_usage_counts_confirmed_within_window_only() {
+        let records = vec![
+            record("a", "2026-01-10", BillingStatus::Confirmed, vec![code("K030A", 1)]),
+            record("b", "2026-03-10", BillingStatus::Confirmed, vec![code("K030A", 2)]),
+            // Draft — not a claim
+            record("c", "2026-04-01", BillingStatus::Draft, vec![code("K030A", 1)]),
+            // Outside the 12-month window
+            record("d", "2025-05-01", BillingStatus::Confirmed, vec![code("K030A", 1)]),
+            // After the service date
+            record("e", "2026-06-01", BillingStatus::Confirmed, vec![code("K030A", 1)]),
+            // The encounter being billed
+            record("cur", "2026-05-01", BillingStatus::Confirmed, vec![code("K030A", 1)]),
+        ];
+        let usage = PriorCodeUsage::from_records(&records, "cur", service_date());
+        assert_eq!(usage.units("K030A"), 3);
+        assert_eq!(usage.encounters_counted, 2);
+        assert_eq!(usage.remaining("K030A"), Some(1));
+        assert_eq!(usage.remaining("A007A"), None);
+    }
+
+    #[test]
+    fn test_exhausted_code_is_suppressed_with_reason() {
+        let records = vec![record("a", "2025-09-01", BillingStatus::Confirmed, vec![code("K131A", 1)])];
+        let usage = PriorCodeUsage::from_records(&records, "cur", service_date());
+        let mut codes = vec![code("K131A", 1), code("A007A", 1)];
+        let suppressed = apply_frequency_limits(&mut codes, &usage);
+        assert_eq!(codes.len(), 1);
+        assert_eq!(codes[0].code, "A007A");
+        assert_eq!(suppressed.len(), 1);
+        assert_eq!(suppressed[0].code, "K131A");
+        assert!(suppressed[0].limit_reason.as_deref().unwrap().contains("1 per year"));
+    }
+
+    #[test]
+    fn test_partial_overflow_reduces_quantity() {
+        let records = vec![record("a", "2026-02-01", BillingStatus::Confirmed, vec![code("K030A", 3)])];
+        let usage = PriorCodeUsage::from_records(&records, "cur", service_date());
+        let mut codes = vec![code("K030A", 2)];
+        let suppressed = apply_frequency_limits(&mut codes, &usage);
+        assert!(suppressed.is_empty());
+        assert_eq!(codes[0].quantity, 1);
+        assert!(codes[0].limit_reason.as_deref().unwrap().contains("reduced from 2 to 1"));
+    }
+
+    #[test]
+    fn test_no_history_leaves_codes_untouched() {
+        let mut codes = vec![code("K131A", 1), code("K030A", 1)];
+        let suppressed = apply_frequency_limits(&mut codes, &PriorCodeUsage::default());
+        assert!(suppressed.is_empty());
+        assert_eq!(codes.len(), 2);
+        assert!(codes.iter().all(|c| c.limit_reason.is_none()));
+    }
+}
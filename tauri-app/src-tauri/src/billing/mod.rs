@@ -1,7 +1,10 @@
+pub mod claim_file;
 pub mod clinical_features;
 pub mod diagnostic_codes;
 pub mod diagnostic_tools_model;
+pub mod frequency_limits;
 pub mod ohip_codes;
+pub mod remittance;
 pub mod rule_engine;
 pub mod time_tracking;
 pub mod types;
@@ -10,7 +13,15 @@ pub mod upgrade_suggestions;
 /// Type alias for optional server-configurable billing data.
 pub(crate) type BillingDataRef<'a> = Option<&'a crate::server_config::BillingData>;
 
+pub use claim_file::{
+    build_claim_batch, ClaimBatchExport, ClaimBatchHeader, ClaimEncounter, EncounterClaimErrors,
+    PatientHealthCard,
+};
 pub use clinical_features::ClinicalFeatures;
+pub use frequency_limits::{apply_frequency_limits, PriorCodeUsage};
+pub use remittance::{
+    parse_remittance_advice, reconcile_remittance, ReconciliationReport, RemittanceAdvice,
+};
 pub use rule_engine::{
     map_features_to_billing, map_features_to_billing_with_context,
     map_features_to_billing_with_tools_model, RuleEngineContext,
@@ -18,7 +29,7 @@ pub use rule_engine::{
 pub use time_tracking::{calculate_daily_caps, calculate_direct_care_time, calculate_monthly_caps};
 pub use types::{
     AppliedUpgrade, BillingCode, BillingConfidence, BillingDaySummary, BillingMonthSummary,
-    BillingRecord, BillingStatus, CapWarning, DailyCapStatus, MonthlyCapStatus, ResolvedDiagnostic,
-    TimeEntry, UpgradeSuggestion,
+    BillingRecord, BillingStatus, CapWarning, DailyCapStatus, MonthlyCapStatus, RemittanceOutcome,
+    RemittanceStatus, ResolvedDiagnostic, TimeEntry, UpgradeSuggestion,
 };
 pub use upgrade_suggestions::compute_upgrade_suggestions;
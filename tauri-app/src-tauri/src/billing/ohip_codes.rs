@@ -1154,6 +1154,10 @@ pub static OHIP_CODES: &[OhipCode] = &[
     },
 
     // ── Periodic Health Visits (in-basket, 30% shadow, Counselling) ─────
+    // Schedule of Benefits, Consultations and Visits, Family Practice &
+    // Practice in General (00), periodic health visit payment rules: a
+    // periodic health visit is payable once per patient per 12-month period,
+    // hence `max_per_year: Some(1)`.
     OhipCode {
         code: "K130A",
         description: "Periodic Health Visit \u{2014} Adolescent",
@@ -1162,7 +1166,7 @@ pub static OHIP_CODES: &[OhipCode] = &[
         shadow_pct: 30,
         category: CodeCategory::Counselling,
         after_hours_eligible: false,
-        max_per_year: None,
+        max_per_year: Some(1), // once per 12-month period
     },
     OhipCode {
         code: "K131A",
@@ -1172,7 +1176,7 @@ pub static OHIP_CODES: &[OhipCode] = &[
         shadow_pct: 30,
         category: CodeCategory::Counselling,
         after_hours_eligible: false,
-        max_per_year: None,
+        max_per_year: Some(1), // once per 12-month period
     },
     OhipCode {
         code: "K132A",
@@ -1182,7 +1186,7 @@ pub static OHIP_CODES: &[OhipCode] = &[
         shadow_pct: 30,
         category: CodeCategory::Counselling,
         after_hours_eligible: false,
-        max_per_year: None,
+        max_per_year: Some(1), // once per 12-month period
     },
     OhipCode {
         code: "K133A",
@@ -1192,7 +1196,7 @@ pub static OHIP_CODES: &[OhipCode] = &[
         shadow_pct: 30,
         category: CodeCategory::Counselling,
         after_hours_eligible: false,
-        max_per_year: None,
+        max_per_year: Some(1), // once per 12-month period
     },
     OhipCode {
         code: "K125A",
@@ -2600,6 +2604,10 @@ mod tests {
         assert_eq!(k039.max_per_year, Some(2));
         let k030 = get_code("K030A").unwrap();
         assert_eq!(k030.max_per_year, Some(4));
+        // Periodic health visits are payable once per 12-month period
+        for code in ["K130A", "K131A", "K132A", "K133A"] {
+            assert_eq!(get_code(code).unwrap().max_per_year, Some(1), "{code}");
+        }
     }
 
     #[test]
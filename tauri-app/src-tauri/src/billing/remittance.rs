@@ -0,0 +1,523 @@
+//! MOH remittance advice (RA) parsing and reconciliation.
+//!
+//! After each payment cycle MOH publishes an RA file listing what was paid
+//! for every processed claim. This module parses the fixed-width RA and
+//! matches each claim back to the archived `BillingRecord` it came from,
+//! stamping a [`RemittanceOutcome`] (paid / adjusted / rejected, amounts,
+//! explanatory codes) onto the record so summaries can show actual revenue.
+//!
+//! Record types used (Remittance Advice technical specification, V03):
+//!
+//! ```text
+//! HR1  file header     payment date, provider, total amount payable
+//! HR4  claim header    claim number, accounting number, patient, health number
+//! HR5  claim item      service date/code, amount submitted/paid, explanatory code
+//! ```
+//!
+//! HR2/HR3 (payee address), HR6/HR7 (balance forward, accounting
+//! transactions) and HR8 (message facility) carry nothing reconcilable and
+//! are skipped, as are any record types this parser doesn't know.
+//!
+//! Matching order for each RA claim:
+//! 1. Accounting number — the claim export writes
+//!    [`accounting_number`](super::claim_file::accounting_number) of the
+//!    session ID, and MOH echoes it back.
+//! 2. Service date + service codes (+ patient surname when the RA carries
+//!    one), for claims keyed in by other billing software. Used only when it
+//!    singles out exactly one record.
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use super::claim_file::accounting_number;
+use super::types::{BillingRecord, RemittanceOutcome, RemittanceStatus};
+
+// ── Parsed RA ──────────────────────────────────────────────────────────────
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RemittanceAdvice {
+    /// Payment date from HR1 (YYYY-MM-DD).
+    pub payment_date: Option<String>,
+    pub provider_number: Option<String>,
+    /// Total amount payable from HR1, in cents.
+    pub total_payable_cents: i64,
+    pub claims: Vec<RemittanceClaim>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RemittanceClaim {
+    pub claim_number: String,
+    pub accounting_number: String,
+    /// Patient surname as printed on the RA (blank for most HCP claims).
+    pub patient_last_name: String,
+    pub health_number: String,
+    pub version_code: String,
+    pub items: Vec<RemittanceItem>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RemittanceItem {
+    /// YYYY-MM-DD.
+    pub service_date: String,
+    pub service_code: String,
+    pub number_of_services: u8,
+    pub amount_submitted_cents: u32,
+    pub amount_paid_cents: i64,
+    pub explanatory_code: Option<String>,
+}
+
+impl RemittanceClaim {
+    pub fn amount_submitted_cents(&self) -> u32 {
+        self.items.iter().map(|i| i.amount_submitted_cents).sum()
+    }
+
+    pub fn amount_paid_cents(&self) -> i64 {
+        self.items.iter().map(|i| i.amount_paid_cents).sum()
+    }
+
+    /// Service date of the first item; all items on a claim share one date
+    /// in practice.
+    pub fn service_date(&self) -> Option<&str> {
+        self.items.first().map(|i| i.service_date.as_str())
+    }
+
+    fn status(&self) -> RemittanceStatus {
+        if self.amount_paid_cents() <= 0 {
+            RemittanceStatus::Rejected
+        } else if self
+            .items
+            .iter()
+            .all(|i| i.amount_paid_cents >= i.amount_submitted_cents as i64)
+        {
+            RemittanceStatus::Paid
+        } else {
+            RemittanceStatus::Adjusted
+        }
+    }
+
+    fn explanatory_codes(&self) -> Vec<String> {
+        let mut codes: Vec<String> = Vec::new();
+        for code in self.items.iter().filter_map(|i| i.explanatory_code.as_ref()) {
+            if !codes.contains(code) {
+                codes.push(code.clone());
+            }
+        }
+        codes
+    }
+}
+
+// ── Reconciliation report ──────────────────────────────────────────────────
+
+/// An RA claim matched to an archived encounter.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ReconciledClaim {
+    pub session_id: String,
+    pub date: String,
+    pub patient_name: Option<String>,
+    pub outcome: RemittanceOutcome,
+}
+
+/// An RA claim with no matching archived encounter.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UnmatchedRemittanceClaim {
+    pub claim_number: String,
+    pub accounting_number: String,
+    pub service_date: Option<String>,
+    pub service_codes: Vec<String>,
+    pub amount_paid_cents: i64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ReconciliationReport {
+    pub payment_date: Option<String>,
+    pub matched: Vec<ReconciledClaim>,
+    pub unmatched: Vec<UnmatchedRemittanceClaim>,
+    pub paid_count: u32,
+    pub adjusted_count: u32,
+    pub rejected_count: u32,
+    /// Sum paid across matched claims, in cents.
+    pub total_paid_cents: i64,
+    /// Sum submitted across matched claims, in cents.
+    pub total_submitted_cents: u64,
+}
+
+// ── Parsing ────────────────────────────────────────────────────────────────
+
+/// Parse an RA file. Records may be terminated by CR, LF or CRLF; errors
+/// cite the 1-based record number.
+pub fn parse_remittance_advice(contents: &str) -> Result<RemittanceAdvice, String> {
+    let mut ra = RemittanceAdvice::default();
+    let records = contents.split(['\r', '\n']).filter(|l| !l.trim().is_empty());
+    for (idx, line) in records.enumerate() {
+        let record_no = idx + 1;
+        match line.get(0..3) {
+            Some("HR1") => {
+                ra.provider_number = non_empty(field(line, 11, 17));
+                ra.payment_date = Some(date_field(line, 20, 28, record_no)?);
+                let amount = numeric_field(line, 58, 67, record_no)? as i64;
+                ra.total_payable_cents = if field(line, 67, 68) == "-" { -amount } else { amount };
+            }
+            Some("HR4") => {
+                let claim_number = field(line, 3, 14).to_string();
+                if claim_number.is_empty() {
+                    return Err(format!("record {record_no}: HR4 record has no claim number"));
+                }
+                ra.claims.push(RemittanceClaim {
+                    claim_number,
+                    accounting_number: field(line, 23, 31).to_uppercase(),
+                    patient_last_name: field(line, 31, 45).to_string(),
+                    health_number: field(line, 52, 62).to_string(),
+                    version_code: field(line, 62, 64).to_string(),
+                    items: vec![],
+                });
+            }
+            Some("HR5") => {
+                let claim = ra
+                    .claims
+                    .last_mut()
+                    .ok_or_else(|| format!("record {record_no}: HR5 item before any HR4 claim header"))?;
+                let claim_number = field(line, 3, 14);
+                if claim_number != claim.claim_number {
+                    return Err(format!(
+                        "record {record_no}: HR5 claim number {claim_number} does not follow its HR4 ({})",
+                        claim.claim_number
+                    ));
+                }
+                let paid = numeric_field(line, 37, 43, record_no)? as i64;
+                claim.items.push(RemittanceItem {
+                    service_date: date_field(line, 15, 23, record_no)?,
+                    number_of_services: numeric_field(line, 23, 25, record_no)? as u8,
+                    service_code: field(line, 25, 30).to_string(),
+                    amount_submitted_cents: numeric_field(line, 31, 37, record_no)? as u32,
+                    amount_paid_cents: if field(line, 43, 44) == "-" { -paid } else { paid },
+                    explanatory_code: non_empty(field(line, 44, 46)),
+                });
+            }
+            _ => {}
+        }
+    }
+    if ra.claims.is_empty() {
+        return Err("No claims (HR4 records) found in remittance advice".to_string());
+    }
+    Ok(ra)
+}
+
+/// Trimmed slice of a fixed-width record; short records read as blank.
+fn field(line: &str, start: usize, end: usize) -> &str {
+    let end = end.min(line.len());
+    line.get(start..end).unwrap_or("").trim()
+}
+
+fn non_empty(s: &str) -> Option<String> {
+    (!s.is_empty()).then(|| s.to_string())
+}
+
+fn numeric_field(line: &str, start: usize, end: usize, record_no: usize) -> Result<u64, String> {
+    let raw = field(line, start, end);
+    raw.parse::<u64>()
+        .map_err(|_| format!("record {record_no}: expected a number at columns {}-{}, got {raw:?}", start + 1, end))
+}
+
+fn date_field(line: &str, start: usize, end: usize, record_no: usize) -> Result<String, String> {
+    let raw = field(line, start, end);
+    NaiveDate::parse_from_str(raw, "%Y%m%d")
+        .map(|d| d.format("%Y-%m-%d").to_string())
+        .map_err(|_| format!("record {record_no}: invalid date {raw:?} at columns {}-{}", start + 1, end))
+}
+
+// ── Reconciliation ─────────────────────────────────────────────────────────
+
+/// Match RA claims to `records`, stamping `remittance` on every matched
+/// record. Records not covered by the RA are left untouched. The caller
+/// persists the records listed in `report.matched`.
+pub fn reconcile_remittance(
+    ra: &RemittanceAdvice,
+    records: &mut [BillingRecord],
+    reconciled_at: &str,
+) -> ReconciliationReport {
+    let mut report = ReconciliationReport {
+        payment_date: ra.payment_date.clone(),
+        ..Default::default()
+    };
+    let mut taken: HashSet<usize> = HashSet::new();
+
+    for claim in &ra.claims {
+        let Some(idx) = find_record(claim, records, &taken) else {
+            report.unmatched.push(UnmatchedRemittanceClaim {
+                claim_number: claim.claim_number.clone(),
+                accounting_number: claim.accounting_number.clone(),
+                service_date: claim.service_date().map(str::to_string),
+                service_codes: claim.items.iter().map(|i| i.service_code.clone()).collect(),
+                amount_paid_cents: claim.amount_paid_cents(),
+            });
+            continue;
+        };
+        taken.insert(idx);
+
+        let outcome = RemittanceOutcome {
+            status: claim.status(),
+            claim_number: claim.claim_number.clone(),
+            payment_date: ra.payment_date.clone(),
+            amount_submitted_cents: claim.amount_submitted_cents(),
+            amount_paid_cents: claim.amount_paid_cents(),
+            explanatory_codes: claim.explanatory_codes(),
+            reconciled_at: reconciled_at.to_string(),
+        };
+        match outcome.status {
+            RemittanceStatus::Paid => report.paid_count += 1,
+            RemittanceStatus::Adjusted => report.adjusted_count += 1,
+            RemittanceStatus::Rejected => report.rejected_count += 1,
+        }
+        report.total_paid_cents += outcome.amount_paid_cents;
+        report.total_submitted_cents += outcome.amount_submitted_cents as u64;
+
+        let record = &mut records[idx];
+        record.remittance = Some(outcome.clone());
+        report.matched.push(ReconciledClaim {
+            session_id: record.session_id.clone(),
+            date: record.date.clone(),
+            patient_name: record.patient_name.clone(),
+            outcome,
+        });
+    }
+    report
+}
+
+fn find_record(
+    claim: &RemittanceClaim,
+    records: &[BillingRecord],
+    taken: &HashSet<usize>,
+) -> Option<usize> {
+    let available = || records.iter().enumerate().filter(|(i, _)| !taken.contains(i));
+
+    if !claim.accounting_number.is_empty() {
+        if let Some((i, _)) = available()
+            .find(|(_, r)| accounting_number(&r.session_id) == claim.accounting_number)
+        {
+            return Some(i);
+        }
+    }
+
+    let service_date = claim.service_date()?;
+    let surname = claim.patient_last_name.to_lowercase();
+    let candidates: Vec<usize> = available()
+        .filter(|(_, r)| r.date == service_date && record_covers_items(r, claim))
+        .filter(|(_, r)| {
+            surname.is_empty()
+                || r.patient_name
+                    .as_deref()
+                    .is_some_and(|n| n.to_lowercase().contains(&surname))
+        })
+        .map(|(i, _)| i)
+        .collect();
+    match candidates.as_slice() {
+        [only] => Some(*only),
+        _ => None,
+    }
+}
+
+/// Every service code on the RA claim appears on the record (as a billing
+/// code or a time entry).
+fn record_covers_items(record: &BillingRecord, claim: &RemittanceClaim) -> bool {
+    claim.items.iter().all(|item| {
+        record.codes.iter().any(|c| c.code == item.service_code)
+            || record.time_entries.iter().any(|t| t.code == item.service_code)
+    })
+}
+
+// ── Tests ──────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::billing::types::{BillingCode, BillingConfidence, BillingStatus};
+
+    fn hr1(payment_date: &str, total_cents: u64) -> String {
+        format!(
+            "HR1V03 0000123456000{payment_date}{:<30}{total_cents:0>9} {:<8}",
+            "DR TEST", "00012345"
+        )
+    }
+
+    fn hr4(claim_number: &str, accounting: &str, surname: &str) -> String {
+        format!(
+            "HR4{claim_number:<11}112345600{accounting:<8}{surname:<14}{:<5}ON{:<10}{:<2}HCP",
+            "", "1234567890", "AB"
+        )
+    }
+
+    fn hr5(claim_number: &str, date: &str, code: &str, submitted: u32, paid: i64, expl: &str) -> String {
+        let sign = if paid < 0 { "-" } else { " " };
+        format!(
+            "HR5{claim_number:<11}1{date}01{code:<5} {submitted:0>6}{:0>6}{sign}{expl:<2}",
+            paid.unsigned_abs()
+        )
+    }
+
+    fn ra_file(lines: &[String]) -> String {
+        lines.iter().map(|l| format!("{l}\r")).collect()
+    }
+
+    fn code(code: &str, fee_cents: u32) -> BillingCode {
+        BillingCode {
+            code: code.into(),
+            description: "Test".into(),
+            fee_cents,
+            category: "in_basket".into(),
+            shadow_pct: 30,
+            billable_amount_cents: fee_cents * 30 / 100,
+            confidence: BillingConfidence::High,
+            auto_extracted: true,
+            after_hours: false,
+            after_hours_premium_cents: 0,
+            quantity: 1,
+            limit_reason: None,
+        }
+    }
+
+    fn record(session_id: &str, date: &str, patient: Option<&str>, codes: Vec<BillingCode>) -> BillingRecord {
+        BillingRecord {
+            session_id: session_id.into(),
+            date: date.into(),
+            patient_name: patient.map(str::to_string),
+            status: BillingStatus::Confirmed,
+            codes,
+            time_entries: vec![],
+            total_shadow_cents: 0,
+            total_out_of_basket_cents: 0,
+            total_time_based_cents: 0,
+            total_amount_cents: 0,
+            confirmed_at: None,
+            notes: None,
+            extraction_model: None,
+            extracted_at: None,
+            diagnostic_code: None,
+            diagnostic_description: None,
+            diagnostic_evidence: None,
+            diagnostic_reasoning: None,
+            suggestions: vec![],
+            applied_upgrades: vec![],
+            suppressed_codes: vec![],
+            remittance: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_header_claims_and_items() {
+        let contents = ra_file(&[
+            hr1("20260515", 4_560),
+            "HR2SOME PAYEE ADDRESS".to_string(),
+            hr4("AB000000001", "ABCD1234", ""),
+            hr5("AB000000001", "20260402", "A007A", 3_765, 3_765, ""),
+            hr5("AB000000001", "20260402", "G538A", 1_000, 795, "30"),
+            "HR8SOME MESSAGE TEXT".to_string(),
+        ]);
+        let ra = parse_remittance_advice(&contents).unwrap();
+        assert_eq!(ra.payment_date.as_deref(), Some("2026-05-15"));
+        assert_eq!(ra.provider_number.as_deref(), Some("123456"));
+        assert_eq!(ra.total_payable_cents, 4_560);
+        assert_eq!(ra.claims.len(), 1);
+        let claim = &ra.claims[0];
+        assert_eq!(claim.accounting_number, "ABCD1234");
+        assert_eq!(claim.health_number, "1234567890");
+        assert_eq!(claim.version_code, "AB");
+        assert_eq!(claim.items.len(), 2);
+        assert_eq!(claim.items[1].service_date, "2026-04-02");
+        assert_eq!(claim.items[1].service_code, "G538A");
+        assert_eq!(claim.items[1].amount_paid_cents, 795);
+        assert_eq!(claim.items[1].explanatory_code.as_deref(), Some("30"));
+        assert_eq!(claim.amount_submitted_cents(), 4_765);
+    }
+
+    #[test]
+    fn test_parse_accepts_lf_and_negative_amounts() {
+        let contents = [
+            hr1("20260515", 0),
+            hr4("AB000000002", "ABCD1234", ""),
+            hr5("AB000000002", "20260402", "A007A", 3_765, -3_765, "I2"),
+        ]
+        .join("\n");
+        let ra = parse_remittance_advice(&contents).unwrap();
+        assert_eq!(ra.claims[0].items[0].amount_paid_cents, -3_765);
+    }
+
+    #[test]
+    fn test_parse_rejects_orphan_item_and_bad_numbers() {
+        let orphan = ra_file(&[hr5("AB000000001", "20260402", "A007A", 100, 100, "")]);
+        assert!(parse_remittance_advice(&orphan).unwrap_err().contains("before any HR4"));
+
+        let mut bad = hr5("AB000000001", "20260402", "A007A", 100, 100, "");
+        bad.replace_range(31..37, "12X456");
+        let contents = ra_file(&[hr4("AB000000001", "X", ""), bad]);
+        assert!(parse_remittance_advice(&contents).unwrap_err().contains("record 2"));
+
+        assert!(parse_remittance_advice(&ra_file(&[hr1("20260515", 0)])).is_err());
+    }
+
+    #[test]
+    fn test_reconcile_by_accounting_number_sets_status() {
+        let sid_paid = "abcd1234-0000-0000-0000-000000000001";
+        let sid_adj = "efgh5678-0000-0000-0000-000000000002";
+        let sid_rej = "ijkl9012-0000-0000-0000-000000000003";
+        let contents = ra_file(&[
+            hr1("20260515", 0),
+            hr4("C1", &accounting_number(sid_paid), ""),
+            hr5("C1", "20260402", "A007A", 3_765, 3_765, ""),
+            hr4("C2", &accounting_number(sid_adj), ""),
+            hr5("C2", "20260402", "A007A", 3_765, 3_765, ""),
+            hr5("C2", "20260402", "G538A", 1_000, 500, "30"),
+            hr4("C3", &accounting_number(sid_rej), ""),
+            hr5("C3", "20260403", "K131A", 6_000, 0, "EH"),
+        ]);
+        let ra = parse_remittance_advice(&contents).unwrap();
+        let mut records = vec![
+            record(sid_paid, "2026-04-02", None, vec![code("A007A", 3_765)]),
+            record(sid_adj, "2026-04-02", None, vec![code("A007A", 3_765), code("G538A", 1_000)]),
+            record(sid_rej, "2026-04-03", None, vec![code("K131A", 6_000)]),
+        ];
+        let report = reconcile_remittance(&ra, &mut records, "2026-05-16T00:00:00Z");
+
+        assert_eq!(report.matched.len(), 3);
+        assert!(report.unmatched.is_empty());
+        assert_eq!((report.paid_count, report.adjusted_count, report.rejected_count), (1, 1, 1));
+        assert_eq!(report.total_paid_cents, 3_765 + 4_265);
+
+        let adj = records[1].remittance.as_ref().unwrap();
+        assert_eq!(adj.status, RemittanceStatus::Adjusted);
+        assert_eq!(adj.explanatory_codes, vec!["30".to_string()]);
+        assert_eq!(adj.payment_date.as_deref(), Some("2026-05-15"));
+        assert_eq!(records[2].remittance.as_ref().unwrap().status, RemittanceStatus::Rejected);
+    }
+
+    #[test]
+    fn test_reconcile_falls_back_to_date_codes_and_surname() {
+        let contents = ra_file(&[
+            hr1("20260515", 0),
+            hr4("C1", "", "SMITH"),
+            hr5("C1", "20260402", "A007A", 3_765, 3_765, ""),
+            // Ambiguous: two unmatched records on that date bill A001A, no surname
+            hr4("C2", "", ""),
+            hr5("C2", "20260402", "A001A", 2_375, 2_375, ""),
+        ]);
+        let ra = parse_remittance_advice(&contents).unwrap();
+        let mut records = vec![
+            record("s1", "2026-04-02", Some("Jane Doe"), vec![code("A007A", 3_765), code("A001A", 2_375)]),
+            record("s2", "2026-04-02", Some("John Smith"), vec![code("A007A", 3_765), code("A001A", 2_375)]),
+            record("s3", "2026-04-02", Some("Ann Lee"), vec![code("A001A", 2_375)]),
+        ];
+        let report = reconcile_remittance(&ra, &mut records, "now");
+
+        assert_eq!(report.matched.len(), 1);
+        assert_eq!(report.matched[0].session_id, "s2");
+        assert!(records[0].remittance.is_none());
+        assert_eq!(report.unmatched.len(), 1);
+        assert_eq!(report.unmatched[0].claim_number, "C2");
+    }
+}
@@ -1,5 +1,6 @@
 use super::clinical_features::*;
 use super::diagnostic_codes;
+use super::frequency_limits::{self, PriorCodeUsage};
 use super::ohip_codes::{self, Basket, OhipCode};
 use super::types::*;
 
@@ -19,6 +20,12 @@ pub struct RuleEngineContext {
     /// `validate_condition_evidence` pattern. When `None`, validation is
     /// skipped (backward compat).
     pub transcript: Option<String>,
+    /// Codes already billed for this patient in the past 12 months. When
+    /// provided, codes with an annual limit (`OhipCode::max_per_year`) are
+    /// trimmed or moved to `BillingRecord::suppressed_codes`, and K013A
+    /// units roll over to K033A once the patient's 3 units are used. `None`
+    /// (patient unknown / no history) leaves codes untouched.
+    pub prior_code_usage: Option<PriorCodeUsage>,
 }
 
 /// Map extracted clinical features to a draft billing record with OHIP codes.
@@ -69,8 +76,16 @@ pub fn map_features_to_billing_with_tools_model(
     // 1. Visit type -> assessment code
     let mut assessment_code = visit_type_to_code(&features.visit_type, billing_data);
 
-    // Counselling: switch K013A → K033A when yearly cap is exhausted
-    if assessment_code == "K013A" && ctx.counselling_exhausted {
+    // Counselling: switch K013A → K033A when yearly cap is exhausted, either
+    // per the physician's flag or per the patient's billing history.
+    let k013_remaining = ctx
+        .prior_code_usage
+        .as_ref()
+        .and_then(|u| u.remaining("K013A"))
+        .unwrap_or(K013A_UNITS_PER_YEAR as u32)
+        .min(K013A_UNITS_PER_YEAR as u32) as u8;
+    let k013_exhausted_by_history = k013_remaining == 0;
+    if assessment_code == "K013A" && (ctx.counselling_exhausted || k013_exhausted_by_history) {
         assessment_code = "K033A".to_string();
     }
 
@@ -93,18 +108,25 @@ pub fn map_features_to_billing_with_tools_model(
         if matches!(assessment_code.as_str(), "K013A" | "K033A" | "K005A" | "K007A") {
             let units = counselling_units_from_duration(duration_ms, billing_data);
 
-            // K013A is capped at 3 units/year — overflow goes to K033A (out-of-basket)
-            if assessment_code == "K013A" && units > 3 {
-                code.quantity = 3;
+            // K013A is capped at 3 units/year — overflow goes to K033A (out-of-basket).
+            // With patient history, the cap is whatever is left of this year's 3.
+            if assessment_code == "K013A" && units > k013_remaining {
+                code.quantity = k013_remaining;
                 codes.push(code);
                 // Add K033A for the overflow units
                 if let Some(k033) = ohip_codes::get_code("K033A") {
                     let mut overflow = make_billing_code(k033, confidence, false);
-                    overflow.quantity = units - 3;
+                    overflow.quantity = units - k013_remaining;
                     codes.push(overflow);
                 }
             } else {
                 code.quantity = units;
+                if assessment_code == "K033A" && k013_exhausted_by_history && !ctx.counselling_exhausted {
+                    code.limit_reason = Some(format!(
+                        "K013A is limited to {} units per year; already used in the past 12 months, billed as K033A",
+                        K013A_UNITS_PER_YEAR
+                    ));
+                }
                 codes.push(code);
             }
         } else {
@@ -251,6 +273,7 @@ pub fn map_features_to_billing_with_tools_model(
                 after_hours: false,
                 after_hours_premium_cents: 0,
                 quantity: 1,
+                limit_reason: None,
             });
         }
     }
@@ -270,6 +293,12 @@ pub fn map_features_to_billing_with_tools_model(
     //     instead of emitting parallel quantity:1 rows.
     dedupe_codes(&mut codes);
 
+    // 6.6 Annual frequency limits against the patient's prior billing.
+    let suppressed_codes = match ctx.prior_code_usage.as_ref() {
+        Some(usage) => frequency_limits::apply_frequency_limits(&mut codes, usage),
+        None => Vec::new(),
+    };
+
     // 7. Collect billing code strings before moving `codes` into record
     let billing_code_strs: Vec<String> = codes.iter().map(|c| c.code.clone()).collect();
 
@@ -296,6 +325,8 @@ pub fn map_features_to_billing_with_tools_model(
         diagnostic_reasoning: None,
         suggestions: vec![],
         applied_upgrades: vec![],
+        suppressed_codes,
+        remittance: None,
     };
 
     // Resolve diagnostic code via 5-stage pipeline:
@@ -688,6 +719,10 @@ fn validate_condition_evidence(cond: &ConditionType, evidence: &str) -> bool {
 
 // ── Helpers ────────────────────────────────────────────────────────────────
 
+/// K013A counselling units payable per patient per 12 months before
+/// further units must be billed as K033A.
+const K013A_UNITS_PER_YEAR: u8 = 3;
+
 /// Per-unit time-based codes — duplicate entries indicate the LLM extracted
 /// multiple conditions that all warrant counselling/management for the same
 /// session. These should aggregate into a single line with summed quantity,
@@ -752,6 +787,7 @@ pub(crate) fn make_billing_code(
         after_hours,
         after_hours_premium_cents,
         quantity: 1,
+        limit_reason: None,
     }
 }
 
@@ -1410,6 +1446,8 @@ mod tests {
             diagnostic_reasoning: None,
             suggestions: vec![],
             applied_upgrades: vec![],
+            suppressed_codes: vec![],
+            remittance: None,
         };
         // Provide a tools-model resolution with the BAD code (491).
         let bad_resolution = crate::billing::types::ResolvedDiagnostic {
@@ -2336,6 +2374,100 @@ mod tests {
         assert_eq!(record.codes[1].quantity, 2);
     }
 
+    // ====== Annual frequency limits (patient history) ======
+
+    /// Confirmed prior-visit record produced by the same rule engine.
+    fn confirmed_prior(session_id: &str, date: &str, features: &ClinicalFeatures, duration_ms: u64) -> BillingRecord {
+        let mut r = map_features_to_billing(features, session_id, date, duration_ms, None, None);
+        r.status = BillingStatus::Confirmed;
+        r
+    }
+
+    #[test]
+    fn test_periodic_health_visit_suppressed_when_billed_this_year() {
+        let mut features = default_features();
+        features.visit_type = VisitType::PeriodicHealthAdult;
+        let prior = confirmed_prior("prev", "2025-11-20", &features, 20 * 60_000);
+        let ctx = RuleEngineContext {
+            prior_code_usage: Some(PriorCodeUsage::from_records(
+                &[prior],
+                "s1",
+                chrono::NaiveDate::from_ymd_opt(2026, 4, 5).unwrap(),
+            )),
+            ..Default::default()
+        };
+        let record = map_features_to_billing_with_context(&features, "s1", "2026-04-05", 20 * 60_000, None, &ctx, None);
+        assert!(!record.has_code("K131A"));
+        assert_eq!(record.suppressed_codes.len(), 1);
+        assert_eq!(record.suppressed_codes[0].code, "K131A");
+        assert!(record.suppressed_codes[0].limit_reason.is_some());
+        // Suppressed codes never count toward the totals
+        let mut recomputed = record.clone();
+        recomputed.recalculate_totals();
+        assert_eq!(recomputed.total_amount_cents, record.total_amount_cents);
+    }
+
+    #[test]
+    fn test_periodic_health_visit_allowed_after_twelve_months() {
+        let mut features = default_features();
+        features.visit_type = VisitType::PeriodicHealthAdult;
+        let prior = confirmed_prior("prev", "2025-04-01", &features, 20 * 60_000);
+        let ctx = RuleEngineContext {
+            prior_code_usage: Some(PriorCodeUsage::from_records(
+                &[prior],
+                "s1",
+                chrono::NaiveDate::from_ymd_opt(2026, 4, 5).unwrap(),
+            )),
+            ..Default::default()
+        };
+        let record = map_features_to_billing_with_context(&features, "s1", "2026-04-05", 20 * 60_000, None, &ctx, None);
+        assert!(record.has_code("K131A"));
+        assert!(record.suppressed_codes.is_empty());
+    }
+
+    #[test]
+    fn test_counselling_history_rolls_remaining_units_to_k033() {
+        let mut features = default_features();
+        features.visit_type = VisitType::Counselling;
+        // Prior visit used 2 of the 3 K013A units (74 min → 2 units)
+        let prior = confirmed_prior("prev", "2026-02-01", &features, 74 * 60_000);
+        let ctx = RuleEngineContext {
+            prior_code_usage: Some(PriorCodeUsage::from_records(
+                &[prior],
+                "s1",
+                chrono::NaiveDate::from_ymd_opt(2026, 4, 5).unwrap(),
+            )),
+            ..Default::default()
+        };
+        // 74 min again → 2 units: 1 left on K013A, 1 overflows to K033A
+        let record = map_features_to_billing_with_context(&features, "s1", "2026-04-05", 74 * 60_000, None, &ctx, None);
+        assert_eq!(record.codes[0].code, "K013A");
+        assert_eq!(record.codes[0].quantity, 1);
+        assert_eq!(record.codes[1].code, "K033A");
+        assert_eq!(record.codes[1].quantity, 1);
+    }
+
+    #[test]
+    fn test_counselling_history_exhausted_uses_k033_with_reason() {
+        let mut features = default_features();
+        features.visit_type = VisitType::Counselling;
+        // 76 min → 3 units: the full annual K013A allowance
+        let prior = confirmed_prior("prev", "2026-01-15", &features, 76 * 60_000);
+        let ctx = RuleEngineContext {
+            prior_code_usage: Some(PriorCodeUsage::from_records(
+                &[prior],
+                "s1",
+                chrono::NaiveDate::from_ymd_opt(2026, 4, 5).unwrap(),
+            )),
+            ..Default::default()
+        };
+        let record = map_features_to_billing_with_context(&features, "s1", "2026-04-05", 74 * 60_000, None, &ctx, None);
+        assert_eq!(record.codes[0].code, "K033A");
+        assert_eq!(record.codes[0].quantity, 2);
+        assert!(record.codes[0].limit_reason.as_deref().unwrap().contains("K013A"));
+        assert!(!record.has_code("K013A"));
+    }
+
     #[test]
     fn test_counselling_no_split_at_3_units() {
         let mut features = default_features();
@@ -345,6 +345,8 @@ mod tests {
             diagnostic_reasoning: None,
             suggestions: vec![],
             applied_upgrades: vec![],
+            suppressed_codes: vec![],
+            remittance: None,
         }
     }
 
@@ -434,6 +436,8 @@ mod tests {
                 percentage: total_hours / DAILY_HOUR_LIMIT,
                 warning_level: CapWarning::Normal,
             },
+            total_paid_cents: 0,
+            reconciled_count: 0,
         }
     }
 
@@ -55,6 +55,11 @@ pub struct BillingCode {
     /// Quantity (default 1). For add-on codes like G385A (max 2), can be >1.
     #[serde(default = "default_quantity")]
     pub quantity: u8,
+    /// Why an annual frequency limit (`OhipCode::max_per_year`) reduced or
+    /// suppressed this code, given the patient's prior billing history.
+    /// Set by `billing::frequency_limits::apply_frequency_limits`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit_reason: Option<String>,
 }
 
 fn default_quantity() -> u8 {
@@ -107,6 +112,39 @@ pub struct AppliedUpgrade {
     pub applied_at: String,
 }
 
+// ── Remittance outcome ─────────────────────────────────────────────────────
+
+/// How MOH settled a submitted claim, per the remittance advice.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum RemittanceStatus {
+    /// Every item paid at the submitted amount.
+    Paid,
+    /// Paid, but at least one item was reduced (or only partly paid).
+    Adjusted,
+    /// Nothing paid.
+    Rejected,
+}
+
+/// Remittance result stamped onto a BillingRecord by RA reconciliation.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RemittanceOutcome {
+    pub status: RemittanceStatus,
+    /// MOH claim number from the RA.
+    pub claim_number: String,
+    /// RA payment date (YYYY-MM-DD).
+    pub payment_date: Option<String>,
+    pub amount_submitted_cents: u32,
+    /// May be negative when the RA carries a recovery against the claim.
+    pub amount_paid_cents: i64,
+    /// Explanatory codes attached to any item of the claim (deduplicated).
+    #[serde(default)]
+    pub explanatory_codes: Vec<String>,
+    /// ISO-8601 timestamp the RA was reconciled.
+    pub reconciled_at: String,
+}
+
 // ── Full billing record for one encounter ──────────────────────────────────
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -148,6 +186,16 @@ pub struct BillingRecord {
     /// billing record is invalidated (SOAP regen path).
     #[serde(default)]
     pub applied_upgrades: Vec<AppliedUpgrade>,
+    /// Codes the rule engine would have suggested but dropped because the
+    /// patient already used up the code's annual limit. Each carries a
+    /// `limit_reason`; excluded from the totals. Kept on the record so the
+    /// clinician can see (and override) the suppression.
+    #[serde(default)]
+    pub suppressed_codes: Vec<BillingCode>,
+    /// What MOH actually paid for this encounter, once an RA covering it has
+    /// been reconciled. None until then.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remittance: Option<RemittanceOutcome>,
 }
 
 /// Result of resolving a diagnostic code via tools-model (file_lookup + LLM pick).
@@ -250,6 +298,12 @@ pub struct BillingDaySummary {
     pub confirmed_count: u32,
     pub draft_count: u32,
     pub cap_status: DailyCapStatus,
+    /// Amount MOH actually paid for encounters with a reconciled RA.
+    #[serde(default)]
+    pub total_paid_cents: i64,
+    /// Encounters that have a remittance outcome.
+    #[serde(default)]
+    pub reconciled_count: u32,
 }
 
 // ── Month summary ──────────────────────────────────────────────────────────
@@ -269,6 +323,11 @@ pub struct BillingMonthSummary {
     pub indirect_admin_ratio: f32,
     pub admin_ratio: f32,
     pub cap_status: MonthlyCapStatus,
+    /// Actual revenue from reconciled RAs (vs. the projected totals above).
+    #[serde(default)]
+    pub total_paid_cents: i64,
+    #[serde(default)]
+    pub reconciled_count: u32,
 }
 
 // ── Tests ──────────────────────────────────────────────────────────────────
@@ -290,6 +349,7 @@ mod tests {
             after_hours,
             after_hours_premium_cents: ah_premium,
             quantity: 1,
+            limit_reason: None,
         }
     }
 
@@ -327,6 +387,8 @@ mod tests {
             diagnostic_reasoning: None,
             suggestions: vec![],
             applied_upgrades: vec![],
+            suppressed_codes: vec![],
+            remittance: None,
         }
     }
 
@@ -212,6 +212,8 @@ mod tests {
             diagnostic_reasoning: None,
             suggestions: vec![],
             applied_upgrades: vec![],
+            suppressed_codes: vec![],
+            remittance: None,
         }
     }
 
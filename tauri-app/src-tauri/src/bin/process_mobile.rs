@@ -1,9 +1,13 @@
 //! Mobile Recording Processing CLI
 //!
-//! Polls the profile service for queued mobile recordings, processes them through
+//! Claims queued mobile recordings from the profile service, processes them through
 //! the same pipeline as the desktop app (STT → encounter detection → SOAP), and
 //! uploads results back to the profile service.
 //!
+//! Each job is leased to this worker via `POST /mobile/jobs/claim` and the lease
+//! is renewed by heartbeat while processing, so several workers can share the
+//! queue without processing a recording twice.
+//!
 //! Shares Rust modules with the desktop app — zero algorithm divergence.
 //!
 //! Usage:
@@ -72,6 +76,16 @@ struct MobileJob {
     created_at: String,
     updated_at: String,
     device_info: Option<String>,
+    #[serde(default)]
+    attempts: u32,
+    lease: Option<JobLease>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct JobLease {
+    lease_id: String,
+    worker_id: String,
+    expires_at: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -89,17 +103,31 @@ struct UpdateJobRequest {
     error: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     sessions_created: Option<Vec<CreatedSession>>,
+    lease_id: String,
 }
 
 /// Build an UpdateJobRequest with just a status change (no error or sessions).
-fn status_update(status: &str) -> UpdateJobRequest {
+fn status_update(status: &str, lease_id: &str) -> UpdateJobRequest {
     UpdateJobRequest {
         status: status.to_string(),
         error: None,
         sessions_created: None,
+        lease_id: lease_id.to_string(),
     }
 }
 
+#[derive(Debug, Serialize)]
+struct ClaimJobRequest<'a> {
+    worker_id: &'a str,
+    lease_secs: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct HeartbeatRequest<'a> {
+    lease_id: &'a str,
+    lease_secs: u64,
+}
+
 #[derive(Debug, Serialize)]
 struct UploadSessionRequest {
     metadata: SessionMetadata,
@@ -135,6 +163,8 @@ struct CliConfig {
     once: bool,
     stt_alias: String,
     soap_model: String,
+    worker_id: String,
+    lease_secs: u64,
 }
 
 impl CliConfig {
@@ -153,6 +183,12 @@ impl CliConfig {
             once: false,
             stt_alias: "medical-streaming".to_string(),
             soap_model: "soap-model-fast".to_string(),
+            worker_id: format!(
+                "{}-{}",
+                env::var("HOSTNAME").unwrap_or_else(|_| "process_mobile".to_string()),
+                std::process::id()
+            ),
+            lease_secs: 300,
         };
 
         let mut i = 1;
@@ -178,6 +214,14 @@ impl CliConfig {
                     i += 1;
                     config.poll_interval_secs = args[i].parse().unwrap_or(10);
                 }
+                "--worker-id" => {
+                    i += 1;
+                    config.worker_id = args[i].clone();
+                }
+                "--lease-secs" => {
+                    i += 1;
+                    config.lease_secs = args[i].parse().unwrap_or(300);
+                }
                 "--once" => {
                     config.once = true;
                 }
@@ -201,7 +245,7 @@ impl CliConfig {
 fn print_usage(program: &str) {
     eprintln!("Mobile Recording Processing CLI");
     eprintln!();
-    eprintln!("Polls the profile service for uploaded mobile recordings and processes them");
+    eprintln!("Claims uploaded mobile recordings from the profile service and processes them");
     eprintln!("through STT → encounter detection → SOAP generation.");
     eprintln!();
     eprintln!("Usage: {} [options]", program);
@@ -212,12 +256,15 @@ fn print_usage(program: &str) {
     eprintln!("  --llm-url URL             LLM Router URL (default: $LLM_ROUTER_URL or http://localhost:8080)");
     eprintln!("  --llm-api-key KEY         LLM Router API key (default: $LLM_API_KEY)");
     eprintln!("  --poll-interval SECS      Poll interval in seconds (default: 10)");
+    eprintln!("  --worker-id ID            Worker name on job leases (default: $HOSTNAME-<pid>)");
+    eprintln!("  --lease-secs SECS         Job lease length, renewed by heartbeat (default: 300)");
     eprintln!("  --once                    Process one job and exit");
     eprintln!("  --help                    Show this help");
 }
 
 // ── Profile Service Client ──────────────────────────────────────────────────
 
+#[derive(Clone)]
 struct ProfileServiceClient {
     client: reqwest::Client,
     base_url: String,
@@ -235,20 +282,41 @@ impl ProfileServiceClient {
         }
     }
 
-    async fn get_queued_jobs(&self) -> Result<Vec<MobileJob>, String> {
-        let url = format!("{}/mobile/jobs?status=queued", self.base_url);
+    /// Lease the oldest queued job. `None` when the queue is empty.
+    async fn claim_job(&self, worker_id: &str, lease_secs: u64) -> Result<Option<MobileJob>, String> {
+        let url = format!("{}/mobile/jobs/claim", self.base_url);
         let resp = self
             .client
-            .get(&url)
+            .post(&url)
+            .json(&ClaimJobRequest { worker_id, lease_secs })
             .send()
             .await
-            .map_err(|e| format!("Failed to fetch jobs: {e}"))?;
+            .map_err(|e| format!("Failed to claim job: {e}"))?;
+        if resp.status() == reqwest::StatusCode::NO_CONTENT {
+            return Ok(None);
+        }
         if !resp.status().is_success() {
-            return Err(format!("Failed to fetch jobs: HTTP {}", resp.status()));
+            return Err(format!("Failed to claim job: HTTP {}", resp.status()));
         }
         resp.json()
             .await
-            .map_err(|e| format!("Failed to parse jobs: {e}"))
+            .map(Some)
+            .map_err(|e| format!("Failed to parse claimed job: {e}"))
+    }
+
+    async fn heartbeat(&self, job_id: &str, lease_id: &str, lease_secs: u64) -> Result<(), String> {
+        let url = format!("{}/mobile/jobs/{}/heartbeat", self.base_url, job_id);
+        let resp = self
+            .client
+            .post(&url)
+            .json(&HeartbeatRequest { lease_id, lease_secs })
+            .send()
+            .await
+            .map_err(|e| format!("Failed to renew lease: {e}"))?;
+        if !resp.status().is_success() {
+            return Err(format!("Failed to renew lease: HTTP {}", resp.status()));
+        }
+        Ok(())
     }
 
     async fn update_job_status(
@@ -438,8 +506,10 @@ async fn generate_soap(
 }
 
 /// Process a single job through the full pipeline.
+#[allow(clippy::too_many_arguments)]
 async fn process_job(
     job: &MobileJob,
+    lease_id: &str,
     profile_client: &ProfileServiceClient,
     stt_client: &WhisperServerClient,
     llm_client: &LLMClient,
@@ -467,13 +537,13 @@ async fn process_job(
         .map_err(|e| format!("Failed to write audio: {e}"))?;
 
     profile_client
-        .update_job_status(&job.job_id, &status_update(status::TRANSCODING))
+        .update_job_status(&job.job_id, &status_update(status::TRANSCODING, lease_id))
         .await?;
     info!("Transcoding AAC → WAV...");
     let wav_path = transcode_to_wav(&m4a_path)?;
 
     profile_client
-        .update_job_status(&job.job_id, &status_update(status::TRANSCRIBING))
+        .update_job_status(&job.job_id, &status_update(status::TRANSCRIBING, lease_id))
         .await?;
     let samples = read_wav_samples(&wav_path)?;
     let transcript = transcribe_audio(stt_client, &samples, &config.stt_alias).await?;
@@ -483,13 +553,13 @@ async fn process_job(
     );
 
     profile_client
-        .update_job_status(&job.job_id, &status_update(status::DETECTING))
+        .update_job_status(&job.job_id, &status_update(status::DETECTING, lease_id))
         .await?;
     let encounters = split_transcript_into_encounters(&transcript);
     info!("Detected {} encounter(s)", encounters.len());
 
     profile_client
-        .update_job_status(&job.job_id, &status_update(status::GENERATING_SOAP))
+        .update_job_status(&job.job_id, &status_update(status::GENERATING_SOAP, lease_id))
         .await?;
 
     let physician = profile_client.get_physician(&job.physician_id).await?;
@@ -565,6 +635,89 @@ async fn process_job(
     Ok(created_sessions)
 }
 
+/// Process a claimed job while renewing its lease, then report the outcome.
+/// A failure report lets the server requeue or dead-letter the job.
+#[allow(clippy::too_many_arguments)]
+async fn run_claimed_job(
+    job: &MobileJob,
+    profile_client: &ProfileServiceClient,
+    stt_client: &WhisperServerClient,
+    llm_client: &LLMClient,
+    config: &CliConfig,
+    models: &ResolvedModels,
+    templates: &transcription_app_lib::server_config::PromptTemplates,
+    work_dir: &Path,
+) {
+    let short_id = &job.job_id[..8.min(job.job_id.len())];
+    let Some(lease_id) = job.lease.as_ref().map(|l| l.lease_id.clone()) else {
+        error!("Claimed job {short_id} has no lease, skipping");
+        return;
+    };
+    info!("Processing job {} (physician={}, {:.0}s audio, attempt {}, soap_model={})",
+        short_id,
+        &job.physician_id[..8.min(job.physician_id.len())],
+        job.duration_ms as f64 / 1000.0,
+        job.attempts,
+        &models.soap_model,
+    );
+
+    // Renew at a third of the lease so one missed heartbeat doesn't lose it
+    let heartbeat = {
+        let client = profile_client.clone();
+        let job_id = job.job_id.clone();
+        let lease_id = lease_id.clone();
+        let lease_secs = config.lease_secs;
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(Duration::from_secs((lease_secs / 3).max(1)));
+            ticker.tick().await; // first tick completes immediately
+            loop {
+                ticker.tick().await;
+                if let Err(e) = client.heartbeat(&job_id, &lease_id, lease_secs).await {
+                    warn!("Lease heartbeat for job {} failed: {e}", &job_id[..8.min(job_id.len())]);
+                }
+            }
+        })
+    };
+
+    let result = process_job(
+        job,
+        &lease_id,
+        profile_client,
+        stt_client,
+        llm_client,
+        config,
+        models,
+        templates,
+        work_dir,
+    )
+    .await;
+    heartbeat.abort();
+
+    let update = match result {
+        Ok(sessions) => {
+            info!("Job {} complete: {} session(s) created", short_id, sessions.len());
+            UpdateJobRequest {
+                status: status::COMPLETE.to_string(),
+                error: None,
+                sessions_created: Some(sessions),
+                lease_id,
+            }
+        }
+        Err(e) => {
+            error!("Job {} failed: {}", short_id, e);
+            UpdateJobRequest {
+                status: status::FAILED.to_string(),
+                error: Some(e),
+                sessions_created: None,
+                lease_id,
+            }
+        }
+    };
+    if let Err(e) = profile_client.update_job_status(&job.job_id, &update).await {
+        warn!("Failed to report outcome of job {short_id}: {e}");
+    }
+}
+
 // ── Main ────────────────────────────────────────────────────────────────────
 
 #[tokio::main]
@@ -616,78 +769,38 @@ async fn main() {
         // Refresh server-config each iteration. The version-check short-circuit
         // makes this ~one HTTP HEAD when nothing changed; full body only on
         // server version bumps. Cheap on the 10s poll cadence.
-        let server_cfg = server_config::load_server_config(&shared_client).await;
+        // No room: mobile jobs always process on the stable config channel.
+        let server_cfg = server_config::load_server_config(&shared_client, None).await;
         let models = resolve_models(&server_cfg, &config.soap_model);
 
-        match profile_client.get_queued_jobs().await {
-            Ok(jobs) => {
-                if jobs.is_empty() {
-                    if config.once {
-                        info!("No queued jobs found. Exiting (--once mode).");
-                        break;
-                    }
-                } else {
-                    for job in &jobs {
-                        info!("Processing job {} (physician={}, {:.0}s audio, soap_model={})",
-                            &job.job_id[..8.min(job.job_id.len())],
-                            &job.physician_id[..8.min(job.physician_id.len())],
-                            job.duration_ms as f64 / 1000.0,
-                            &models.soap_model,
-                        );
-
-                        match process_job(
-                            job,
-                            &profile_client,
-                            &stt_client,
-                            &llm_client,
-                            &config,
-                            &models,
-                            &server_cfg.prompts,
-                            &work_dir,
-                        )
-                        .await
-                        {
-                            Ok(sessions) => {
-                                info!(
-                                    "Job {} complete: {} session(s) created",
-                                    &job.job_id[..8.min(job.job_id.len())],
-                                    sessions.len()
-                                );
-                                let _ = profile_client
-                                    .update_job_status(
-                                        &job.job_id,
-                                        &UpdateJobRequest {
-                                            status: status::COMPLETE.to_string(),
-                                            error: None,
-                                            sessions_created: Some(sessions),
-                                        },
-                                    )
-                                    .await;
-                            }
-                            Err(e) => {
-                                error!("Job {} failed: {}", &job.job_id[..8.min(job.job_id.len())], e);
-                                let _ = profile_client
-                                    .update_job_status(
-                                        &job.job_id,
-                                        &UpdateJobRequest {
-                                            status: status::FAILED.to_string(),
-                                            error: Some(e),
-                                            sessions_created: None,
-                                        },
-                                    )
-                                    .await;
-                            }
-                        }
-
-                        if config.once {
-                            info!("Processed one job. Exiting (--once mode).");
-                            return;
-                        }
-                    }
+        match profile_client.claim_job(&config.worker_id, config.lease_secs).await {
+            Ok(Some(job)) => {
+                run_claimed_job(
+                    &job,
+                    &profile_client,
+                    &stt_client,
+                    &llm_client,
+                    &config,
+                    &models,
+                    &server_cfg.prompts,
+                    &work_dir,
+                )
+                .await;
+                if config.once {
+                    info!("Processed one job. Exiting (--once mode).");
+                    return;
+                }
+                // More may be queued; claim again without waiting
+                continue;
+            }
+            Ok(None) => {
+                if config.once {
+                    info!("No queued jobs found. Exiting (--once mode).");
+                    break;
                 }
             }
             Err(e) => {
-                warn!("Failed to poll for jobs: {e}");
+                warn!("Failed to claim a job: {e}");
             }
         }
 
@@ -48,6 +48,8 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use uuid::Uuid;
 
+use crate::diarization::overlap::OverlapRegion;
+
 pub use config::BiomarkerConfig;
 pub use thread::{BiomarkerHandle, start_biomarker_thread};
 
@@ -72,11 +74,12 @@ pub enum BiomarkerInput {
         start_ms: u64,
         end_ms: u64,
     },
-    /// Segment info for session metrics (speaker, duration)
+    /// Segment info for session metrics (speaker, duration, detected overlap)
     SegmentInfo {
         speaker_id: Option<String>,
         start_ms: u64,
         end_ms: u64,
+        overlaps: Vec<OverlapRegion>,
     },
     /// Record a dropout event (buffer overflow)
     Dropout,
@@ -195,8 +198,11 @@ pub struct ConversationDynamics {
     pub speaker_turns: Vec<SpeakerTurnStats>,
     /// Silence/pause statistics
     pub silence: SilenceStats,
-    /// Total overlap count (speaker B starts before speaker A ends)
+    /// Total overlap count (overlapped-speech regions detected by diarization)
     pub total_overlap_count: u32,
+    /// Total overlapped speech in ms
+    #[serde(default)]
+    pub total_overlap_ms: u64,
     /// Total interruption count (overlap > 500ms)
     pub total_interruption_count: u32,
     /// Mean response latency in ms (time from speaker A ending to speaker B starting)
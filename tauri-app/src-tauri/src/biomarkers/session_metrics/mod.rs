@@ -14,7 +14,7 @@ use super::{SessionMetrics, ConversationDynamics, SpeakerTurnStats, SilenceStats
 const MAX_SEGMENT_HISTORY: usize = 100;
 
 /// Threshold in ms for considering an overlap as an interruption
-const INTERRUPTION_THRESHOLD_MS: i64 = 500;
+const INTERRUPTION_THRESHOLD_MS: u64 = 500;
 
 /// Threshold in ms for considering a pause as "long"
 const LONG_PAUSE_THRESHOLD_MS: u64 = 2000;
@@ -51,8 +51,10 @@ pub struct SessionAggregator {
     speaker_turn_durations: HashMap<String, Vec<u64>>,
     /// Silence gaps between different speakers (positive gaps only)
     silence_gaps: Vec<u64>,
-    /// Total overlap count (when speaker B starts before speaker A ends)
+    /// Total overlap count (detected overlapped-speech regions)
     total_overlap_count: u32,
+    /// Total overlapped speech in milliseconds
+    total_overlap_ms: u64,
     /// Total interruption count (overlap > 500ms)
     total_interruption_count: u32,
     /// Response latencies (positive gaps between different speakers)
@@ -76,6 +78,7 @@ impl SessionAggregator {
             speaker_turn_durations: HashMap::new(),
             silence_gaps: Vec::new(),
             total_overlap_count: 0,
+            total_overlap_ms: 0,
             total_interruption_count: 0,
             response_latencies: Vec::new(),
             speaker_turn_counts: HashMap::new(),
@@ -87,6 +90,18 @@ impl SessionAggregator {
         self.cough_count += 1;
     }
 
+    /// Add an overlapped-speech region detected by diarization
+    ///
+    /// Utterances come from VAD and never overlap in time, so overlap can't be
+    /// inferred from turn timing; it's detected within an utterance instead.
+    pub fn add_overlap(&mut self, duration_ms: u64) {
+        self.total_overlap_count += 1;
+        self.total_overlap_ms += duration_ms;
+        if duration_ms > INTERRUPTION_THRESHOLD_MS {
+            self.total_interruption_count += 1;
+        }
+    }
+
     /// Add a speaker turn (segment)
     pub fn add_turn(&mut self, speaker_id: Option<&str>, start_ms: u64, end_ms: u64) {
         // Update session timing
@@ -125,29 +140,17 @@ impl SessionAggregator {
 
         // --- Conversation dynamics analysis ---
 
-        // Compare with previous segment for overlap/silence detection
+        // Compare with previous segment for silence detection
+        // (overlap comes from add_overlap, not from timing)
         if let Some(prev_segment) = self.segment_history.back() {
             // Only analyze when speakers differ (conversation dynamics)
-            if prev_segment.speaker_id != speaker {
-                // Calculate gap: positive = silence, negative = overlap
-                let gap_ms = start_ms as i64 - prev_segment.end_ms as i64;
-
-                if gap_ms < 0 {
-                    // Overlap detected
-                    self.total_overlap_count += 1;
-
-                    // Check if it's a significant interruption (> 500ms overlap)
-                    if gap_ms.abs() > INTERRUPTION_THRESHOLD_MS {
-                        self.total_interruption_count += 1;
-                    }
-                } else {
-                    // Positive gap = response latency / silence
-                    let gap_u64 = gap_ms as u64;
-                    self.response_latencies.push(gap_u64);
+            if prev_segment.speaker_id != speaker && start_ms >= prev_segment.end_ms {
+                // Positive gap = response latency / silence
+                let gap_ms = start_ms - prev_segment.end_ms;
+                self.response_latencies.push(gap_ms);
 
-                    // Track as silence gap
-                    self.silence_gaps.push(gap_u64);
-                }
+                // Track as silence gap
+                self.silence_gaps.push(gap_ms);
             }
         }
 
@@ -289,6 +292,7 @@ impl SessionAggregator {
             speaker_turns,
             silence,
             total_overlap_count: self.total_overlap_count,
+            total_overlap_ms: self.total_overlap_ms,
             total_interruption_count: self.total_interruption_count,
             mean_response_latency_ms,
             engagement_score,
@@ -392,6 +396,7 @@ impl SessionAggregator {
         self.speaker_turn_durations.clear();
         self.silence_gaps.clear();
         self.total_overlap_count = 0;
+        self.total_overlap_ms = 0;
         self.total_interruption_count = 0;
         self.response_latencies.clear();
         self.speaker_turn_counts.clear();
@@ -505,15 +510,15 @@ mod tests {
     fn test_overlap_detection() {
         let mut agg = SessionAggregator::new();
 
-        // Speaker A speaks 0-5000ms
         agg.add_turn(Some("Speaker_A"), 0, 5000);
-        // Speaker B starts at 4500ms (overlaps by 500ms)
-        agg.add_turn(Some("Speaker_B"), 4500, 7000);
+        // Detected 500ms of overlapped speech inside the utterance
+        agg.add_overlap(500);
 
         let metrics = agg.get_metrics();
         let dynamics = metrics.conversation_dynamics.unwrap();
 
         assert_eq!(dynamics.total_overlap_count, 1);
+        assert_eq!(dynamics.total_overlap_ms, 500);
         // 500ms overlap is exactly at threshold, should NOT be interruption
         assert_eq!(dynamics.total_interruption_count, 0);
     }
@@ -522,18 +527,47 @@ mod tests {
     fn test_interruption_detection() {
         let mut agg = SessionAggregator::new();
 
-        // Speaker A speaks 0-5000ms
         agg.add_turn(Some("Speaker_A"), 0, 5000);
-        // Speaker B interrupts at 4000ms (overlaps by 1000ms > 500ms threshold)
-        agg.add_turn(Some("Speaker_B"), 4000, 7000);
+        // 1000ms overlap > 500ms threshold
+        agg.add_overlap(1000);
+        agg.add_overlap(300);
 
         let metrics = agg.get_metrics();
         let dynamics = metrics.conversation_dynamics.unwrap();
 
-        assert_eq!(dynamics.total_overlap_count, 1);
+        assert_eq!(dynamics.total_overlap_count, 2);
+        assert_eq!(dynamics.total_overlap_ms, 1300);
         assert_eq!(dynamics.total_interruption_count, 1);
     }
 
+    #[test]
+    fn test_turn_timing_alone_is_not_overlap() {
+        let mut agg = SessionAggregator::new();
+
+        // Speaker B's segment starts before A's ends, but nothing was detected
+        agg.add_turn(Some("Speaker_A"), 0, 5000);
+        agg.add_turn(Some("Speaker_B"), 4000, 7000);
+
+        let metrics = agg.get_metrics();
+        let dynamics = metrics.conversation_dynamics.unwrap();
+
+        assert_eq!(dynamics.total_overlap_count, 0);
+        assert_eq!(dynamics.total_interruption_count, 0);
+        // Negative gap isn't a response latency either
+        assert_eq!(dynamics.mean_response_latency_ms, 0.0);
+    }
+
+    #[test]
+    fn test_reset_clears_overlap() {
+        let mut agg = SessionAggregator::new();
+        agg.add_overlap(800);
+        agg.reset();
+
+        let dynamics = agg.get_metrics().conversation_dynamics.unwrap();
+        assert_eq!(dynamics.total_overlap_count, 0);
+        assert_eq!(dynamics.total_overlap_ms, 0);
+    }
+
     #[test]
     fn test_response_latency() {
         let mut agg = SessionAggregator::new();
@@ -17,6 +17,7 @@ use super::config::BiomarkerConfig;
 use super::session_metrics::SessionAggregator;
 use super::voice_metrics::{calculate_stability, calculate_vitality};
 use super::{AudioQualitySnapshot, BiomarkerInput, BiomarkerOutput, SpeakerBiomarkers, VocalBiomarkers};
+use crate::diarization::overlap::OverlapRegion;
 
 #[cfg(feature = "biomarkers")]
 use super::yamnet::YamnetProvider;
@@ -75,12 +76,20 @@ impl BiomarkerHandle {
         });
     }
 
-    /// Send segment info for session metrics
-    pub fn send_segment_info(&self, speaker_id: Option<String>, start_ms: u64, end_ms: u64) {
+    /// Send segment info for session metrics, with any overlap regions
+    /// diarization detected inside the segment
+    pub fn send_segment_info(
+        &self,
+        speaker_id: Option<String>,
+        start_ms: u64,
+        end_ms: u64,
+        overlaps: Vec<OverlapRegion>,
+    ) {
         let _ = self.input_tx.send(BiomarkerInput::SegmentInfo {
             speaker_id,
             start_ms,
             end_ms,
+            overlaps,
         });
     }
 
@@ -308,9 +317,13 @@ fn run_biomarker_thread(
                 speaker_id,
                 start_ms,
                 end_ms,
+                overlaps,
             } => {
                 if config.session_metrics_enabled {
                     session.add_turn(speaker_id.as_deref(), start_ms, end_ms);
+                    for region in &overlaps {
+                        session.add_overlap(region.duration_ms());
+                    }
 
                     // Try to correlate with pending biomarkers and assign to speaker
                     if let Some(ref speaker) = speaker_id {
@@ -3,6 +3,10 @@
 //! Guides the user through a multi-phase calibration to determine per-room
 //! CO2 baseline, per-person contribution, and response characteristics.
 //! Runs as a tokio task, communicates with the frontend via Tauri events.
+//!
+//! Every reading is also recorded to a labelled trace (the phase gives the
+//! head count), and on completion a per-room fusion profile is built from it
+//! and saved — see `presence_sensor::room_profile`.
 
 use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
@@ -13,6 +17,11 @@ use tauri::Emitter;
 use tokio::sync::mpsc;
 use tracing::{info, warn};
 
+use crate::presence_sensor::room_profile::RoomProfile;
+use crate::presence_sensor::thermal;
+use crate::presence_sensor::trace::TraceRow;
+use crate::presence_sensor::ThermalConfig;
+
 // --- Types ---
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -101,6 +110,27 @@ struct Esp32Response {
     humidity_pct: Option<f32>,
 }
 
+// Full thermal frame from `GET /thermal` (same as esp32_http.rs)
+#[derive(Debug, Deserialize)]
+struct ThermalFrameResponse {
+    #[serde(default)]
+    pixels: Vec<f32>,
+    #[serde(default = "default_thermal_width")]
+    width: u16,
+    #[serde(default = "default_thermal_height")]
+    height: u16,
+}
+
+fn default_thermal_width() -> u16 {
+    32
+}
+fn default_thermal_height() -> u16 {
+    24
+}
+
+/// Fetch thermal frames every N polls (~5s at 1Hz), matching the live source
+const THERMAL_POLL_INTERVAL: u64 = 5;
+
 // --- Handle ---
 
 pub struct CalibrationHandle {
@@ -342,6 +372,15 @@ fn build_status(
     }
 }
 
+async fn fetch_thermal_frame(client: &reqwest::Client, url: &str) -> Option<ThermalFrameResponse> {
+    let resp = client.get(url).send().await.ok()?;
+    if !resp.status().is_success() {
+        return None;
+    }
+    let frame: ThermalFrameResponse = resp.json().await.ok()?;
+    (!frame.pixels.is_empty()).then_some(frame)
+}
+
 pub async fn run_calibration(
     sensor_url: String,
     room_id: String,
@@ -364,6 +403,9 @@ pub async fn run_calibration(
     let mut consecutive_failures: u32 = 0;
     let mut last_response: Option<Esp32Response> = None;
     let mut result: Option<CalibrationResult> = None;
+    let mut trace: Vec<TraceRow> = Vec::new();
+    let mut poll_count: u64 = 0;
+    let thermal_config = ThermalConfig::default();
 
     info!("CO2 calibration started for room {} (sensor: {})", room_id, sensor_url);
 
@@ -407,7 +449,12 @@ pub async fn run_calibration(
 
         // Computing phase
         if phase == CalibrationPhase::Computing {
-            result = Some(compute_results(&phase_results, &room_id, &sensor_url, &all_readings));
+            let computed = compute_results(&phase_results, &room_id, &sensor_url, &all_readings);
+            let profile = RoomProfile::from_calibration(&computed, &trace);
+            if let Err(e) = profile.save(&trace) {
+                warn!("Failed to save presence profile for room {}: {}", room_id, e);
+            }
+            result = Some(computed);
             phase = CalibrationPhase::Complete;
             info!("Calibration complete: {:?}", result);
         }
@@ -426,6 +473,7 @@ pub async fn run_calibration(
 
         // Poll sensor
         let url = format!("{}/", sensor_url.trim_end_matches('/'));
+        poll_count += 1;
         match client.get(&url).send().await {
             Ok(resp) if resp.status().is_success() => {
                 if let Ok(data) = resp.json::<Esp32Response>().await {
@@ -454,6 +502,26 @@ pub async fn run_calibration(
 
                         if matches!(phase, CalibrationPhase::EmptyRoom | CalibrationPhase::OnePerson | CalibrationPhase::TwoPeople | CalibrationPhase::ThreePlus) {
                             phase_readings.push(reading.clone());
+
+                            // Thermal is best-effort: rooms without a camera just
+                            // get no thermal weight in the profile
+                            let frame = if poll_count % THERMAL_POLL_INTERVAL == 0 {
+                                fetch_thermal_frame(&client, &format!("{}thermal", url)).await
+                            } else {
+                                None
+                            };
+                            trace.push(TraceRow {
+                                elapsed_secs: reading.timestamp_secs,
+                                occupancy: Some(phase_occupancy(phase)),
+                                mmwave: Some(data.present),
+                                hot_pixels: frame.as_ref().map(|f| {
+                                    thermal::count_hot_pixels(&f.pixels, thermal_config.hot_pixel_threshold_c)
+                                }),
+                                thermal_blobs: frame.as_ref().map(|f| {
+                                    thermal::estimate_occupancy(&f.pixels, f.width, f.height, &thermal_config)
+                                }),
+                                co2_ppm: Some(co2),
+                            });
                         }
                         all_readings.push(reading);
                     }
@@ -43,6 +43,19 @@ mod tests {
             medplum_server_url: "http://localhost:8103".to_string(),
             medplum_client_id: "test-client-id".to_string(),
             medplum_auto_sync: true,
+            emr_backend: "medplum".to_string(),
+            fhir_server_url: String::new(),
+            smart_client_id: String::new(),
+            smart_private_key_path: String::new(),
+            smart_key_id: String::new(),
+            smart_scope: "system/*.read system/*.write".to_string(),
+            hl7_enabled: false,
+            hl7_host: String::new(),
+            hl7_port: 2575,
+            hl7_message_type: "MDM_T02".to_string(),
+            hl7_sending_facility: String::new(),
+            hl7_receiving_application: String::new(),
+            hl7_receiving_facility: String::new(),
             whisper_mode: "remote".to_string(),
             whisper_server_url: "http://100.119.83.76:8001".to_string(),
             whisper_server_model: "large-v3-turbo".to_string(),
@@ -58,6 +71,7 @@ mod tests {
             auto_end_silence_ms: 180_000,
             debug_storage_enabled: true,
             pharm_service_url: "http://100.119.83.76:8091".to_string(),
+            mcp_auth_token: String::new(),
             image_source: "off".to_string(),
             gemini_api_key: String::new(),
             openai_api_key: String::new(),
@@ -1,14 +1,23 @@
 //! Archive command handlers for local session history
 
 use super::CommandError;
+use crate::archive_search::{self, SearchHit};
 use crate::commands::physicians::SharedServerConfig;
-use crate::commands::{SharedActivePhysician, SharedMedplumClient, SharedProfileClient};
+use crate::commands::{
+    active_emr_backend, SharedActivePhysician, SharedMedplumClient, SharedProfileClient,
+    SharedSmartBackend,
+};
 use crate::config::Config;
+use crate::emr_backend::EMR_BACKEND_MEDPLUM;
+use crate::hl7_outbound::{Hl7Destination, Hl7Document};
+use crate::hl7_outbox::{self, SharedHl7Outbox};
 use crate::local_archive::{
     self, ArchiveDetails, ArchiveSummary, SessionFeedback,
 };
+use crate::medplum_outbox::{OutboxEntry, SharedMedplumOutbox};
 use crate::ollama::LLMClient;
 use crate::profile_client::{ConfirmPatientRequestBody, ProfileClient};
+use crate::rediarization::{self, RediarizationSummary};
 use crate::server_config_resolve::resolve;
 use chrono::Utc;
 use serde::Serialize;
@@ -171,6 +180,54 @@ pub async fn get_local_session_details(
     )))
 }
 
+/// Full-text search over archived transcripts and SOAP notes.
+///
+/// Searches the local archive and, when a physician is active, the
+/// profile-service index, merging hits by session ID. Works offline against
+/// the local archive alone. `from` / `to` are inclusive YYYY-MM-DD bounds.
+#[tauri::command]
+pub async fn search_archive(
+    query: String,
+    from: Option<String>,
+    to: Option<String>,
+    limit: Option<usize>,
+    active_physician: State<'_, SharedActivePhysician>,
+    profile_client: State<'_, SharedProfileClient>,
+) -> Result<Vec<SearchHit>, CommandError> {
+    if query.trim().is_empty() {
+        return Err(CommandError::Validation("Search query must not be empty".into()));
+    }
+    let limit = limit
+        .unwrap_or(archive_search::DEFAULT_LIMIT)
+        .clamp(1, archive_search::MAX_LIMIT);
+    info!("Searching archive (limit {})", limit);
+
+    let (q, f, t) = (query.clone(), from.clone(), to.clone());
+    let local = tokio::task::spawn_blocking(move || {
+        archive_search::search_local(&q, f.as_deref(), t.as_deref(), limit)
+    })
+    .await
+    .map_err(|e| CommandError::Other(format!("Search task failed: {e}")))?
+    .unwrap_or_else(|e| {
+        warn!("Local archive search failed: {e}");
+        Vec::new()
+    });
+
+    let physician_id = active_physician.read().await.as_ref().map(|p| p.id.clone());
+    let client = profile_client.read().await.clone();
+    if let (Some(phys_id), Some(client)) = (physician_id, client) {
+        match client
+            .search_sessions(&phys_id, &query, from.as_deref(), to.as_deref(), limit)
+            .await
+        {
+            Ok(server) => return Ok(archive_search::merge_hits(server, local, limit)),
+            Err(e) => warn!("Server search failed, using local only: {e}"),
+        }
+    }
+
+    Ok(local)
+}
+
 fn is_blank(s: &Option<String>) -> bool {
     s.as_deref().map_or(true, str::is_empty)
 }
@@ -682,6 +739,13 @@ pub fn update_session_patient_name(
 #[serde(rename_all = "camelCase")]
 pub struct ConfirmPatientResult {
     pub medplum_synced: bool,
+    /// Medplum write failed transiently and was queued in the outbox for
+    /// background retry.
+    pub medplum_queued: bool,
+    /// The HL7 v2 receiver acknowledged the note (ADR-0036)
+    pub hl7_sent: bool,
+    /// HL7 message not acknowledged yet; the HL7 outbox retries it
+    pub hl7_queued: bool,
     pub profile_service_synced: bool,
     /// Canonical patient_id from the profile-service (Medplum FHIR ID when
     /// available, else a UUID fallback). None if both writes failed.
@@ -693,8 +757,13 @@ pub struct ConfirmPatientResult {
 }
 
 /// Confirm the patient for an archived session — writes to local archive,
-/// Medplum FHIR (if authenticated), and the profile-service patient index.
+/// the EMR selected by `emr_backend` (if authenticated), and the
+/// profile-service patient index. `medplum_*` result fields carry the EMR's
+/// IDs whichever backend wrote them.
 /// Runs the three writes in-order so the UI can show per-store status.
+/// A transient Medplum failure is queued in the Medplum outbox and retried
+/// in the background. When the HL7 v2 interface is enabled the SOAP note is
+/// also sent as an HL7 message, persisted in the HL7 outbox until ACKed.
 ///
 /// Requires `patient_dob` in YYYY-MM-DD format. Use
 /// `update_session_patient_name` for name-only renames without DOB.
@@ -711,6 +780,9 @@ pub async fn confirm_session_patient(
     active_physician: State<'_, SharedActivePhysician>,
     profile_client: State<'_, SharedProfileClient>,
     medplum_client: State<'_, SharedMedplumClient>,
+    smart_backend: State<'_, SharedSmartBackend>,
+    outbox: State<'_, SharedMedplumOutbox>,
+    hl7_outbox: State<'_, SharedHl7Outbox>,
 ) -> Result<ConfirmPatientResult, CommandError> {
     // Validate DOB format up-front so downstream stores don't have to.
     if chrono::NaiveDate::parse_from_str(&patient_dob, "%Y-%m-%d").is_err() {
@@ -725,8 +797,12 @@ pub async fn confirm_session_patient(
     let now = Utc::now().to_rfc3339();
     let mut errors: Vec<String> = Vec::new();
     let mut medplum_patient_id: Option<String> = None;
+    let mut medplum_encounter_id: Option<String> = None;
     let mut profile_patient_id: Option<String> = None;
     let mut medplum_synced = false;
+    let mut medplum_queued = false;
+    let mut hl7_sent = false;
+    let mut hl7_queued = false;
     let mut profile_service_synced = false;
 
     info!(
@@ -757,24 +833,35 @@ pub async fn confirm_session_patient(
     // — threaded into sync_continuous_session so FHIR Encounter participants
     // point at the physician rather than the ClientApplication even when
     // we're running on a proxy-minted token.
-    let practitioner_fhir_id: Option<String> = {
+    let (physician_id, physician_name, practitioner_fhir_id) = {
         let guard = active_physician.read().await;
-        guard.as_ref().and_then(|p| p.medplum_practitioner_id.clone())
+        (
+            guard.as_ref().map(|p| p.id.clone()),
+            guard.as_ref().map(|p| p.name.clone()),
+            guard.as_ref().and_then(|p| p.medplum_practitioner_id.clone()),
+        )
     };
 
-    // Step B — Medplum. If local OAuth auth isn't valid, try minting a
-    // token via the profile-service proxy (v0.10.49+). Skip the step
-    // entirely only when neither path is available.
+    // Step B — EMR (the backend `emr_backend` selects). For Medplum, if
+    // local OAuth auth isn't valid, try minting a token via the
+    // profile-service proxy (v0.10.49+); the SMART backend authenticates
+    // itself. Skip the step entirely only when no path is available.
     {
-        let guard = medplum_client.read().await;
-        if let Some(client) = guard.as_ref() {
-            // If local auth isn't usable, try the proxy path.
-            if !client.is_authenticated().await {
+        if let Ok(client) = active_emr_backend(&medplum_client, &smart_backend).await {
+            // If local Medplum auth isn't usable, try the proxy path.
+            let proxy_target = if client.backend_name() == EMR_BACKEND_MEDPLUM
+                && !client.is_authenticated().await
+            {
+                medplum_client.read().await.clone()
+            } else {
+                None
+            };
+            if let Some(medplum) = proxy_target {
                 let pf = profile_client.read().await.clone();
                 if let Some(pf) = pf {
                     match pf.fetch_medplum_token().await {
                         Ok((token, expires_in)) => {
-                            if let Err(e) = client
+                            if let Err(e) = medplum
                                 .inject_proxy_token(
                                     token,
                                     expires_in,
@@ -822,26 +909,79 @@ pub async fn confirm_session_patient(
                     Ok(sync) => {
                         medplum_synced = true;
                         medplum_patient_id = Some(sync.patient_id.clone());
+                        medplum_encounter_id = Some(sync.encounter_id.clone());
+                        // A re-confirm supersedes any queued retry of this session.
+                        outbox.lock().await.complete(&session_id);
                         for e in &sync.errors {
                             errors.push(format!("medplum: {e}"));
                         }
                     }
                     Err(e) => {
-                        warn!(event = "confirm_patient_medplum_failed", error = %e);
-                        errors.push(format!("medplum: {e}"));
+                        // Persist the failure either way; transient ones are
+                        // retried in the background, the rest stay parked
+                        // until the patient is re-confirmed.
+                        let retryable = e.is_retryable();
+                        warn!(event = "confirm_patient_medplum_failed", retryable, error = %e);
+                        outbox.lock().await.enqueue(OutboxEntry {
+                            session_id: session_id.clone(),
+                            date: date.clone(),
+                            physician_id: physician_id.clone(),
+                            patient_name: patient_name.clone(),
+                            patient_dob: patient_dob.clone(),
+                            soap_note: soap_note.clone(),
+                            transcript: transcript.clone(),
+                            session_started_at: session_started_at.clone(),
+                            session_duration_ms,
+                            practitioner_fhir_id: practitioner_fhir_id.clone(),
+                            queued_at: now.clone(),
+                            attempts: 0,
+                            next_attempt_at: None,
+                            last_error: Some(e.to_string()),
+                            parked: !retryable,
+                        });
+                        if retryable {
+                            medplum_queued = true;
+                            errors.push(format!("medplum: {e} (queued for retry)"));
+                        } else {
+                            errors.push(format!("medplum: {e}"));
+                        }
                     }
                 }
             } else {
-                info!("Medplum not authenticated (local or proxy) — skipping EMR write");
+                info!(
+                    backend = client.backend_name(),
+                    "EMR not authenticated (local or proxy) — skipping EMR write"
+                );
             }
         }
     }
 
+    // Step B2 — HL7 v2 outbound, for EMRs that only ingest MDM/ORU over MLLP.
+    // Needs a note to send. The message is persisted before sending, so one
+    // the receiver doesn't ACK is retried by the HL7 outbox.
+    if let (Some(dest), Some(note)) = (
+        Hl7Destination::from_settings(&Config::load_or_default()),
+        soap_note.as_deref(),
+    ) {
+        let doc = Hl7Document {
+            session_id: &session_id,
+            patient_name: &patient_name,
+            patient_dob: &patient_dob,
+            soap_note: note,
+            session_started_at: &session_started_at,
+            physician_name: physician_name.as_deref(),
+        };
+        match hl7_outbox::send_session_note(&hl7_outbox, &dest, &date, &doc).await {
+            Ok(()) => hl7_sent = true,
+            Err(e) if e.is_retryable() => {
+                hl7_queued = true;
+                errors.push(format!("hl7: {e} (queued for retry)"));
+            }
+            Err(e) => errors.push(format!("hl7: {e}")),
+        }
+    }
+
     // Step C — profile-service. Always attempt; idempotent on (name, dob).
-    let physician_id = {
-        let guard = active_physician.read().await;
-        guard.as_ref().map(|p| p.id.clone())
-    };
     let pf_client = profile_client.read().await.clone();
     match (physician_id.clone(), pf_client) {
         (Some(phys_id), Some(pf)) => {
@@ -850,6 +990,9 @@ pub async fn confirm_session_patient(
                 dob: patient_dob.clone(),
                 session_id: session_id.clone(),
                 medplum_patient_id: medplum_patient_id.clone(),
+                session_date: chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+                    .is_ok()
+                    .then(|| date.clone()),
             };
             match pf.confirm_patient(&phys_id, &body).await {
                 Ok(resp) => {
@@ -878,6 +1021,7 @@ pub async fn confirm_session_patient(
             &date,
             &now,
             medplum_patient_id.as_deref(),
+            medplum_encounter_id.as_deref(),
             &patient_dob,
         ) {
             warn!(event = "confirm_patient_metadata_write_failed", error = %e);
@@ -915,6 +1059,9 @@ pub async fn confirm_session_patient(
         event = "confirm_patient_complete",
         session_id = %session_id,
         medplum_synced,
+        medplum_queued,
+        hl7_sent,
+        hl7_queued,
         profile_service_synced,
         patient_id = ?canonical_patient_id,
         errors = errors.len(),
@@ -923,6 +1070,9 @@ pub async fn confirm_session_patient(
 
     Ok(ConfirmPatientResult {
         medplum_synced,
+        medplum_queued,
+        hl7_sent,
+        hl7_queued,
         profile_service_synced,
         patient_id: canonical_patient_id,
         medplum_patient_id,
@@ -980,6 +1130,58 @@ pub fn get_session_transcript_lines(
     Ok(local_archive::get_transcript_lines(&session_id, &date)?)
 }
 
+/// Re-run speaker diarization over an archived continuous-mode encounter.
+///
+/// Rewrites speaker labels in `segments.jsonl` and `transcript.txt`; the live
+/// labelling is kept alongside as `*.original.*`. `audio_path` overrides the
+/// lookup of the run's recording in `recordings/`.
+#[tauri::command]
+pub async fn rediarize_local_session(
+    session_id: String,
+    date: String,
+    audio_path: Option<String>,
+    active_physician: State<'_, SharedActivePhysician>,
+    profile_client: State<'_, SharedProfileClient>,
+) -> Result<RediarizationSummary, CommandError> {
+    info!("Re-diarizing local session: {}", session_id);
+
+    let session_dir = local_archive::get_session_dir_from_str(&session_id, &date)?;
+    if !session_dir.exists() {
+        return Err(CommandError::NotFound(format!("Session: {}", session_id)));
+    }
+
+    // Embedding extraction over a whole encounter is CPU-bound
+    let summary = tokio::task::spawn_blocking(move || {
+        let config = Config::load_or_default();
+        rediarization::rediarize_archived_session(
+            &session_dir,
+            &config,
+            audio_path.map(PathBuf::from),
+        )
+    })
+    .await
+    .map_err(|e| CommandError::Other(format!("Re-diarization task failed: {e}")))?
+    .map_err(CommandError::Other)?;
+
+    let sid = session_id.clone();
+    spawn_sync(
+        active_physician.inner().clone(),
+        profile_client.inner().clone(),
+        "rediarize_session",
+        move |phys_id, client| async move {
+            if let Ok(details) = local_archive::get_session(&sid, &date) {
+                if let Ok(body) = serde_json::to_value(&details) {
+                    if let Err(e) = client.upload_session(&phys_id, &sid, &body).await {
+                        warn!("Server sync failed (upload re-diarized session): {e}");
+                    }
+                }
+            }
+        },
+    );
+
+    Ok(summary)
+}
+
 /// Read a local audio file and return its bytes
 /// Used by the history window's audio player for locally-archived sessions
 #[tauri::command]
@@ -1,9 +1,11 @@
 //! Tauri commands for FHO+ billing management.
 
 use crate::billing::{
-    BillingDaySummary, BillingMonthSummary, BillingRecord,
-    calculate_daily_caps, calculate_monthly_caps,
+    BillingDaySummary, BillingMonthSummary, BillingRecord, ClaimBatchExport, ClaimBatchHeader,
+    ClaimEncounter, PatientHealthCard, PriorCodeUsage, ReconciliationReport, build_claim_batch,
+    calculate_daily_caps, calculate_monthly_caps, parse_remittance_advice, reconcile_remittance,
 };
+use crate::billing::frequency_limits::LOOKBACK_DAYS;
 use crate::commands::CommandError;
 use crate::commands::physicians::{SharedActivePhysician, SharedProfileClient, SharedServerConfig};
 use crate::config::Config;
@@ -11,6 +13,7 @@ use crate::llm_client::LLMClient;
 use crate::local_archive;
 use chrono::{Datelike, Duration};
 use serde::Deserialize;
+use std::collections::HashMap;
 use tauri::State;
 use tracing::{debug, info, warn};
 
@@ -325,10 +328,25 @@ pub async fn extract_billing_codes(
         crate::pipeline_log::PipelineLogger::new(),
     ));
 
+    let prior_code_usage = {
+        let phys_id = active_physician.read().await.as_ref().map(|p| p.id.clone());
+        let pc = profile_client.read().await.clone();
+        load_prior_code_usage(
+            &session_id,
+            &parsed_date,
+            details.metadata.patient_name.as_deref(),
+            details.metadata.patient_dob.as_deref(),
+            phys_id.as_deref(),
+            pc.as_ref(),
+        )
+        .await
+    };
+
     let rule_ctx = crate::billing::RuleEngineContext {
         is_hospital: context.as_ref().map_or(false, |c| c.is_hospital),
         counselling_exhausted: context.as_ref().map_or(config.billing_counselling_exhausted, |c| c.counselling_exhausted),
         transcript: Some(transcript.to_string()),
+        prior_code_usage,
     };
 
     let sc = server_config.read().await;
@@ -355,6 +373,74 @@ pub async fn extract_billing_codes(
     Ok(record)
 }
 
+/// Tally the patient's billed codes over the past 12 months so annual limits
+/// can be enforced. Needs a name + DOB to resolve the patient on the server;
+/// returns None (no limits applied) when the patient can't be identified.
+async fn load_prior_code_usage(
+    session_id: &str,
+    service_date: &chrono::DateTime<chrono::Utc>,
+    patient_name: Option<&str>,
+    patient_dob: Option<&str>,
+    phys_id: Option<&str>,
+    client: Option<&crate::profile_client::ProfileClient>,
+) -> Option<PriorCodeUsage> {
+    let (name, dob, pid, c) = (patient_name?, patient_dob?, phys_id?, client?);
+    let patient = match c.search_patient_by_name_dob(pid, name, dob).await {
+        Ok(Some(p)) => p,
+        Ok(None) => return None,
+        Err(e) => {
+            warn!("Patient lookup for frequency limits failed: {e}");
+            return None;
+        }
+    };
+
+    // Drop sessions dated outside the window before any lookup. Sessions
+    // confirmed before the index recorded dates are resolved against the
+    // window's local date directories in one pass.
+    let window_start = *service_date - chrono::Duration::days(LOOKBACK_DAYS);
+    let prior: Vec<&String> = patient
+        .session_ids
+        .iter()
+        .filter(|s| s.as_str() != session_id)
+        .collect();
+    let local_index = if prior.iter().any(|s| !patient.session_dates.contains_key(*s)) {
+        local_archive::list_session_ids_between(&window_start, service_date)
+    } else {
+        std::collections::HashMap::new()
+    };
+
+    let mut records = Vec::new();
+    let mut server_needed: Vec<String> = Vec::new();
+    for sid in prior {
+        let date = match patient.session_dates.get(sid) {
+            Some(d) => match super::parse_date(d) {
+                Ok(date) if date > window_start && date <= *service_date => Some(date),
+                _ => continue,
+            },
+            None => local_index.get(sid).copied(),
+        };
+        let local = date.filter(|d| {
+            local_archive::get_session_archive_dir(sid, d).is_ok_and(|dir| dir.is_dir())
+        });
+        match local {
+            Some(d) => match local_archive::get_billing_record(sid, &d) {
+                Ok(Some(record)) => records.push(record),
+                Ok(None) => {}
+                Err(e) => warn!("Local billing read failed for {}: {e}", sid),
+            },
+            None => server_needed.push(sid.clone()),
+        }
+    }
+    records.extend(fetch_server_billing_records(pid, c, server_needed).await);
+
+    let usage = PriorCodeUsage::from_records(&records, session_id, service_date.date_naive());
+    debug!(
+        "Frequency limits: {} prior encounter(s) counted for session {}",
+        usage.encounters_counted, session_id
+    );
+    Some(usage)
+}
+
 /// Collect billing records for a date across local + server, parallelizing
 /// remote fetches. Sessions from other rooms are included when the physician
 /// + profile client are known.
@@ -391,28 +477,39 @@ async fn collect_billing_records(
         }
     }
 
-    if !server_needed.is_empty() {
-        if let (Some(pid), Some(c)) = (phys_id, client) {
-            let fetches = server_needed.into_iter().map(|sid| {
-                let c = c.clone();
-                let pid = pid.to_string();
-                async move {
-                    match c.download_billing_record(&pid, &sid).await {
-                        Ok(opt) => opt,
-                        Err(e) => {
-                            warn!("Server billing fetch failed for {}: {e}", sid);
-                            None
-                        }
-                    }
-                }
-            });
-            let results = futures_util::future::join_all(fetches).await;
-            records.extend(results.into_iter().flatten());
-        }
+    if let (Some(pid), Some(c)) = (phys_id, client) {
+        records.extend(fetch_server_billing_records(pid, c, server_needed).await);
     }
     records
 }
 
+/// Download server billing records for `session_ids` in parallel. Failed and
+/// missing records are skipped.
+async fn fetch_server_billing_records(
+    phys_id: &str,
+    client: &crate::profile_client::ProfileClient,
+    session_ids: Vec<String>,
+) -> Vec<BillingRecord> {
+    let fetches = session_ids.into_iter().map(|sid| {
+        let c = client.clone();
+        let pid = phys_id.to_string();
+        async move {
+            match c.download_billing_record(&pid, &sid).await {
+                Ok(opt) => opt,
+                Err(e) => {
+                    warn!("Server billing fetch failed for {}: {e}", sid);
+                    None
+                }
+            }
+        }
+    });
+    futures_util::future::join_all(fetches)
+        .await
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
 /// Shared daily-summary builder used by single-day and monthly roll-up paths.
 async fn build_daily_summary(
     date: String,
@@ -431,11 +528,17 @@ async fn build_daily_summary(
     let mut draft = 0u32;
     let mut time_hours: std::collections::HashMap<String, f32> = std::collections::HashMap::new();
     let mut total_time_hours = 0.0f32;
+    let mut total_paid = 0i64;
+    let mut reconciled = 0u32;
 
     for r in &records {
         total_shadow += r.total_shadow_cents;
         total_oob += r.total_out_of_basket_cents;
         total_time += r.total_time_based_cents;
+        if let Some(ra) = &r.remittance {
+            total_paid += ra.amount_paid_cents;
+            reconciled += 1;
+        }
         match r.status {
             crate::billing::BillingStatus::Confirmed => confirmed += 1,
             crate::billing::BillingStatus::Draft => draft += 1,
@@ -459,6 +562,8 @@ async fn build_daily_summary(
         confirmed_count: confirmed,
         draft_count: draft,
         cap_status,
+        total_paid_cents: total_paid,
+        reconciled_count: reconciled,
         encounters: records,
     })
 }
@@ -508,12 +613,16 @@ pub async fn get_monthly_billing_summary(
     let mut total_time = 0u32;
     let mut total_hours = 0.0f32;
     let mut hours_by_code: std::collections::HashMap<String, f32> = std::collections::HashMap::new();
+    let mut total_paid = 0i64;
+    let mut reconciled = 0u32;
 
     for ds in &daily_summaries {
         total_shadow += ds.total_shadow_cents;
         total_oob += ds.total_out_of_basket_cents;
         total_time += ds.total_time_based_cents;
         total_hours += ds.total_time_hours;
+        total_paid += ds.total_paid_cents;
+        reconciled += ds.reconciled_count;
         for (code, hours) in &ds.time_hours_by_code {
             *hours_by_code.entry(code.clone()).or_insert(0.0) += hours;
         }
@@ -548,6 +657,8 @@ pub async fn get_monthly_billing_summary(
         indirect_admin_ratio,
         admin_ratio,
         cap_status,
+        total_paid_cents: total_paid,
+        reconciled_count: reconciled,
     })
 }
 
@@ -626,6 +737,167 @@ pub fn export_billing_csv(
     Ok(csv)
 }
 
+/// Build an OHIP claims batch file (MOH EDT format) from the confirmed
+/// billing records (local + server) in `[start_date, end_date]`.
+///
+/// `health_cards` is keyed by session ID; the app never stores health
+/// numbers, so the frontend collects them at export time. When a card has
+/// no birth date, the vision-extracted `patient_dob` from the archive is used.
+/// Encounters that fail validation are returned in `errors` and left out of
+/// the file.
+#[tauri::command]
+pub async fn export_ohip_claim_file(
+    start_date: String,
+    end_date: String,
+    header: ClaimBatchHeader,
+    health_cards: HashMap<String, PatientHealthCard>,
+    active_physician: State<'_, SharedActivePhysician>,
+    profile_client: State<'_, SharedProfileClient>,
+) -> Result<ClaimBatchExport, CommandError> {
+    info!("Exporting OHIP claim file from {} to {}", start_date, end_date);
+    let start = super::parse_date(&start_date)?;
+    let end = super::parse_date(&end_date)?;
+
+    let phys_id = active_physician.read().await.as_ref().map(|p| p.id.clone());
+    let client = profile_client.read().await.clone();
+    let mut encounters = Vec::new();
+    let mut current = start;
+    while current <= end {
+        let date_str = format!("{:04}-{:02}-{:02}", current.year(), current.month(), current.day());
+        let records = collect_billing_records(&date_str, &current, phys_id.as_deref(), client.as_ref()).await;
+        for record in records {
+            let health_card = match health_cards.get(&record.session_id).cloned() {
+                Some(mut card) => {
+                    if card.birth_date.is_none() {
+                        card.birth_date = archived_patient_dob(
+                            &record.session_id,
+                            &date_str,
+                            phys_id.as_deref(),
+                            client.as_ref(),
+                        )
+                        .await;
+                    }
+                    Some(card)
+                }
+                None => None,
+            };
+            encounters.push(ClaimEncounter { record, health_card });
+        }
+
+        current = current + Duration::days(1);
+    }
+
+    let export = build_claim_batch(&header, chrono::Local::now().date_naive(), &encounters)
+        .map_err(CommandError::Validation)?;
+    info!(
+        "OHIP claim file {}: {} claims, {} items, {} encounters with errors, {} drafts skipped",
+        export.batch_id,
+        export.claim_count,
+        export.item_count,
+        export.errors.len(),
+        export.skipped_draft_count,
+    );
+    Ok(export)
+}
+
+/// Confirmed patient DOB from a session's archive metadata. Reads the local
+/// archive first, then the profile service for sessions recorded elsewhere.
+async fn archived_patient_dob(
+    session_id: &str,
+    date: &str,
+    phys_id: Option<&str>,
+    client: Option<&crate::profile_client::ProfileClient>,
+) -> Option<String> {
+    if local_archive::has_local_metadata(session_id, date) {
+        return local_archive::get_session_dir_from_str(session_id, date)
+            .and_then(|dir| local_archive::read_metadata(&dir))
+            .ok()
+            .and_then(|m| m.patient_dob);
+    }
+    let (pid, c) = (phys_id?, client?);
+    match c.get_session(pid, session_id).await {
+        Ok(details) => details.metadata.patient_dob,
+        Err(e) => {
+            warn!("Server metadata fetch failed for {}: {e}", session_id);
+            None
+        }
+    }
+}
+
+/// Reconcile an MOH remittance advice file against archived billing records.
+///
+/// Loads every record (local + server) on the RA's service dates, matches
+/// claims back to them, and saves the matched records with their
+/// `remittance` outcome stamped on, locally when the session is archived
+/// here and on the server. Claims that match nothing are returned in
+/// `unmatched` for manual follow-up.
+#[tauri::command]
+pub async fn reconcile_remittance_advice(
+    contents: String,
+    active_physician: State<'_, SharedActivePhysician>,
+    profile_client: State<'_, SharedProfileClient>,
+) -> Result<ReconciliationReport, CommandError> {
+    let ra = parse_remittance_advice(&contents).map_err(CommandError::Validation)?;
+    info!(
+        "Reconciling remittance advice: {} claims, payment date {:?}",
+        ra.claims.len(),
+        ra.payment_date
+    );
+
+    let mut service_dates: Vec<String> = ra
+        .claims
+        .iter()
+        .filter_map(|c| c.service_date().map(str::to_string))
+        .collect();
+    service_dates.sort();
+    service_dates.dedup();
+
+    let phys_id = active_physician.read().await.as_ref().map(|p| p.id.clone());
+    let client = profile_client.read().await.clone();
+    let mut records = Vec::new();
+    for date in &service_dates {
+        let parsed_date = super::parse_date(date)?;
+        records.extend(collect_billing_records(date, &parsed_date, phys_id.as_deref(), client.as_ref()).await);
+    }
+
+    let report = reconcile_remittance(&ra, &mut records, &chrono::Utc::now().to_rfc3339());
+
+    // Write outcomes back where each record lives: the local archive for
+    // sessions recorded here, and the server for every session so other
+    // rooms see the status.
+    let matched: Vec<&BillingRecord> = report
+        .matched
+        .iter()
+        .filter_map(|m| records.iter().find(|r| r.session_id == m.session_id))
+        .collect();
+    for record in &matched {
+        if !local_archive::has_local_metadata(&record.session_id, &record.date) {
+            continue;
+        }
+        let parsed_date = super::parse_date(&record.date)?;
+        if let Err(e) = local_archive::save_billing_record(&record.session_id, &parsed_date, record) {
+            warn!("Failed to save remittance outcome for {}: {e}", record.session_id);
+        }
+    }
+    if let (Some(pid), Some(c)) = (phys_id.as_deref(), client.as_ref()) {
+        let uploads = matched.iter().map(|record| async move {
+            if let Err(e) = c.upload_billing_record(pid, &record.session_id, record).await {
+                warn!("Server sync failed (remittance outcome for {}): {e}", record.session_id);
+            }
+        });
+        futures_util::future::join_all(uploads).await;
+    }
+
+    info!(
+        "Remittance reconciled: {} paid, {} adjusted, {} rejected, {} unmatched",
+        report.paid_count,
+        report.adjusted_count,
+        report.rejected_count,
+        report.unmatched.len(),
+    );
+    Ok(report)
+}
+
 /// Search result for OHIP code lookup
 #[derive(serde::Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -783,6 +1055,7 @@ mod tests {
             after_hours: false,
             after_hours_premium_cents: 0,
             quantity: 1,
+            limit_reason: None,
         }
     }
 
@@ -821,6 +1094,8 @@ mod tests {
                 },
             ],
             applied_upgrades: vec![],
+            suppressed_codes: vec![],
+            remittance: None,
         };
         r.recalculate_totals();
         r
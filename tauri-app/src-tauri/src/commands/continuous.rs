@@ -1,6 +1,6 @@
 //! Commands for continuous charting mode (start/stop/status)
 
-use super::CommandError;
+use super::{CommandError, SharedMedplumClient, SharedSmartBackend};
 use crate::commands::physicians::{
     SharedActivePhysician, SharedProfileClient, SharedRoomConfig, SharedServerConfig,
 };
@@ -12,7 +12,7 @@ use crate::server_sync::ServerSyncContext;
 use chrono::Timelike;
 use std::sync::atomic::Ordering;
 use std::sync::{Arc, Mutex};
-use tauri::{AppHandle, State};
+use tauri::{AppHandle, Manager, State};
 use tracing::{info, warn};
 
 /// Resolve the effective (start, end) sleep hours using the precedence rule:
@@ -98,16 +98,24 @@ pub type SharedContinuousModeState = Arc<Mutex<Option<Arc<ContinuousModeHandle>>
 /// When sleep mode is enabled, automatically stops at sleep_start_hour (EST)
 /// and restarts at sleep_end_hour (EST).
 #[tauri::command]
-pub async fn start_continuous_mode(
-    app: AppHandle,
-    continuous_state: State<'_, SharedContinuousModeState>,
-    active_physician: State<'_, SharedActivePhysician>,
-    room_config_state: State<'_, SharedRoomConfig>,
-    profile_client_state: State<'_, SharedProfileClient>,
-    server_config: State<'_, SharedServerConfig>,
-) -> Result<(), CommandError> {
+pub async fn start_continuous_mode(app: AppHandle) -> Result<(), CommandError> {
+    start_continuous_mode_for_app(&app).await
+}
+
+/// Body of `start_continuous_mode`, reading managed state from the app
+/// handle. The MCP `start_continuous_mode` tool calls this directly, so it
+/// doesn't depend on the command's parameter list.
+pub async fn start_continuous_mode_for_app(app: &AppHandle) -> Result<(), CommandError> {
     info!("Starting continuous charting mode");
 
+    let continuous_state = app.state::<SharedContinuousModeState>();
+    let active_physician = app.state::<SharedActivePhysician>();
+    let room_config_state = app.state::<SharedRoomConfig>();
+    let profile_client_state = app.state::<SharedProfileClient>();
+    let server_config = app.state::<SharedServerConfig>();
+    let medplum_client = app.state::<SharedMedplumClient>();
+    let smart_backend = app.state::<SharedSmartBackend>();
+
     // Check if already running
     {
         let state = continuous_state
@@ -118,10 +126,13 @@ pub async fn start_continuous_mode(
         }
     }
 
-    // Build server sync context from current physician/room state
+    // Build server sync context from current physician/room state. The
+    // EMR clients ride along for prior-visit SOAP context.
     let sync_ctx = ServerSyncContext::from_state(
         &active_physician, &room_config_state, &profile_client_state,
-    ).await;
+    )
+    .await
+    .with_emr(medplum_client.inner().clone(), smart_backend.inner().clone());
 
     // Create handle — persists across sleep/wake cycles
     let handle = Arc::new(ContinuousModeHandle::new());
@@ -140,6 +151,7 @@ pub async fn start_continuous_mode(
     // Clone the Arc<RwLock<ServerConfig>> so every tick can re-read the
     // latest server defaults without snapshotting.
     let server_config_for_task = server_config.inner().clone();
+    let app = app.clone();
 
     tokio::spawn(async move {
         // Last-logged sleep hours to avoid spamming the log every tick;
@@ -0,0 +1,84 @@
+//! HL7 v2 outbound interface commands (ADR-0036).
+//!
+//! `confirm_session_patient` sends the note automatically when the interface
+//! is enabled. These commands cover the rest:
+//!  - `hl7_send_session` — (re)send an archived session's note, using the
+//!    patient name and DOB recorded in its `ArchiveMetadata`.
+//!  - `hl7_outbox_status` — messages still waiting for an ACK.
+
+use super::CommandError;
+use crate::config::Config;
+use crate::hl7_outbound::{Hl7Destination, Hl7Document, Hl7Error};
+use crate::hl7_outbox::{self, Hl7OutboxStatus, SharedHl7Outbox};
+use crate::local_archive;
+use serde::Serialize;
+use tauri::State;
+
+/// Outcome of `hl7_send_session`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Hl7SendResult {
+    /// The receiver ACKed the message
+    pub acknowledged: bool,
+    /// Not acknowledged yet; the outbox will retry it
+    pub queued: bool,
+    pub error: Option<String>,
+}
+
+/// Send an archived session's SOAP note to the configured HL7 receiver.
+///
+/// Requires a confirmed patient (name and DOB in the archive metadata) and a
+/// SOAP note. Failures that a retry can fix are queued, not returned as
+/// errors.
+#[tauri::command]
+pub async fn hl7_send_session(
+    session_id: String,
+    date: String,
+    outbox: State<'_, SharedHl7Outbox>,
+) -> Result<Hl7SendResult, CommandError> {
+    let dest = Hl7Destination::from_settings(&Config::load_or_default())
+        .ok_or_else(|| CommandError::Config("HL7 interface is not enabled".into()))?;
+    let details = local_archive::get_session(&session_id, &date).map_err(CommandError::NotFound)?;
+
+    let metadata = &details.metadata;
+    let (Some(patient_name), Some(patient_dob)) = (&metadata.patient_name, &metadata.patient_dob)
+    else {
+        return Err(CommandError::Validation(
+            "session has no confirmed patient name and DOB".into(),
+        ));
+    };
+    let soap_note = details
+        .soap_note
+        .as_deref()
+        .ok_or_else(|| CommandError::Validation("session has no SOAP note".into()))?;
+
+    let doc = Hl7Document {
+        session_id: &session_id,
+        patient_name,
+        patient_dob,
+        soap_note,
+        session_started_at: &metadata.started_at,
+        physician_name: metadata.physician_name.as_deref(),
+    };
+    match hl7_outbox::send_session_note(&outbox, &dest, &date, &doc).await {
+        Ok(()) => Ok(Hl7SendResult {
+            acknowledged: true,
+            queued: false,
+            error: None,
+        }),
+        Err(Hl7Error::InvalidMessage(msg)) => Err(CommandError::Validation(msg)),
+        Err(e) => Ok(Hl7SendResult {
+            acknowledged: false,
+            queued: e.is_retryable(),
+            error: Some(e.to_string()),
+        }),
+    }
+}
+
+/// Messages waiting in the HL7 outbox. PHI-free: no message bodies.
+#[tauri::command]
+pub async fn hl7_outbox_status(
+    outbox: State<'_, SharedHl7Outbox>,
+) -> Result<Vec<Hl7OutboxStatus>, CommandError> {
+    Ok(outbox.lock().await.status())
+}
@@ -1,8 +1,14 @@
 //! Medplum EMR integration commands
 
-use super::{CommandError, SharedMedplumClient};
+use super::{active_emr_backend, CommandError, SharedMedplumClient, SharedSmartBackend};
 use crate::activity_log;
 use crate::config::Config;
+use crate::fhir_extraction::{
+    self, EncounterLink, StructuredResourcesPreview, StructuredResourcesUploadResult,
+};
+use crate::local_archive;
+use crate::medication_extraction::MedEntry;
+use crate::medplum_outbox::{OutboxStatus, SharedMedplumOutbox};
 use crate::medplum::{
     AuthState, AuthUrl, Encounter, EncounterDetails, EncounterSummary, MedplumClient, Patient,
     SyncResult, SyncStatus,
@@ -186,14 +192,12 @@ pub async fn medplum_refresh_token(
 #[tauri::command]
 pub async fn medplum_search_patients(
     medplum_state: State<'_, SharedMedplumClient>,
+    smart_state: State<'_, SharedSmartBackend>,
     query: String,
 ) -> Result<Vec<Patient>, CommandError> {
     info!("Searching for patients (query_len={})", query.len());
 
-    let client_guard = medplum_state.read().await;
-    let client = client_guard
-        .as_ref()
-        .ok_or_else(|| CommandError::Config("Medplum client not initialized".into()))?;
+    let client = active_emr_backend(medplum_state.inner(), smart_state.inner()).await?;
 
     client
         .search_patients(&query)
@@ -205,14 +209,12 @@ pub async fn medplum_search_patients(
 #[tauri::command]
 pub async fn medplum_create_encounter(
     medplum_state: State<'_, SharedMedplumClient>,
+    smart_state: State<'_, SharedSmartBackend>,
     patient_id: String,
 ) -> Result<Encounter, CommandError> {
     info!("Creating encounter for patient: {}", patient_id);
 
-    let client_guard = medplum_state.read().await;
-    let client = client_guard
-        .as_ref()
-        .ok_or_else(|| CommandError::Config("Medplum client not initialized".into()))?;
+    let client = active_emr_backend(medplum_state.inner(), smart_state.inner()).await?;
 
     client
         .create_encounter(&patient_id)
@@ -224,6 +226,7 @@ pub async fn medplum_create_encounter(
 #[tauri::command]
 pub async fn medplum_complete_encounter(
     medplum_state: State<'_, SharedMedplumClient>,
+    smart_state: State<'_, SharedSmartBackend>,
     encounter_id: String,
     encounter_fhir_id: String,
     patient_id: String,
@@ -233,10 +236,7 @@ pub async fn medplum_complete_encounter(
 ) -> Result<SyncResult, CommandError> {
     info!("Completing encounter: {}", encounter_id);
 
-    let client_guard = medplum_state.read().await;
-    let client = client_guard
-        .as_ref()
-        .ok_or_else(|| CommandError::Config("Medplum client not initialized".into()))?;
+    let client = active_emr_backend(medplum_state.inner(), smart_state.inner()).await?;
 
     let mut sync_status = SyncStatus::default();
     let mut errors = Vec::new();
@@ -402,15 +402,13 @@ pub async fn medplum_complete_encounter(
 #[tauri::command]
 pub async fn medplum_get_encounter_history(
     medplum_state: State<'_, SharedMedplumClient>,
+    smart_state: State<'_, SharedSmartBackend>,
     start_date: Option<String>,
     end_date: Option<String>,
 ) -> Result<Vec<EncounterSummary>, CommandError> {
     info!("Getting encounter history");
 
-    let client_guard = medplum_state.read().await;
-    let client = client_guard
-        .as_ref()
-        .ok_or_else(|| CommandError::Config("Medplum client not initialized".into()))?;
+    let client = active_emr_backend(medplum_state.inner(), smart_state.inner()).await?;
 
     client
         .get_encounter_history(start_date.as_deref(), end_date.as_deref())
@@ -422,14 +420,12 @@ pub async fn medplum_get_encounter_history(
 #[tauri::command]
 pub async fn medplum_get_encounter_details(
     medplum_state: State<'_, SharedMedplumClient>,
+    smart_state: State<'_, SharedSmartBackend>,
     encounter_id: String,
 ) -> Result<EncounterDetails, CommandError> {
     info!("Getting encounter details: {}", encounter_id);
 
-    let client_guard = medplum_state.read().await;
-    let client = client_guard
-        .as_ref()
-        .ok_or_else(|| CommandError::Config("Medplum client not initialized".into()))?;
+    let client = active_emr_backend(medplum_state.inner(), smart_state.inner()).await?;
 
     client
         .get_encounter_details(&encounter_id)
@@ -441,14 +437,12 @@ pub async fn medplum_get_encounter_details(
 #[tauri::command]
 pub async fn medplum_get_audio_data(
     medplum_state: State<'_, SharedMedplumClient>,
+    smart_state: State<'_, SharedSmartBackend>,
     binary_id: String,
 ) -> Result<Vec<u8>, CommandError> {
     info!("Fetching audio data: {}", binary_id);
 
-    let client_guard = medplum_state.read().await;
-    let client = client_guard
-        .as_ref()
-        .ok_or_else(|| CommandError::Config("Medplum client not initialized".into()))?;
+    let client = active_emr_backend(medplum_state.inner(), smart_state.inner()).await?;
 
     client
         .get_audio_data(&binary_id)
@@ -460,6 +454,7 @@ pub async fn medplum_get_audio_data(
 #[tauri::command]
 pub async fn medplum_sync_encounter(
     medplum_state: State<'_, SharedMedplumClient>,
+    smart_state: State<'_, SharedSmartBackend>,
     encounter_id: String,
     encounter_fhir_id: String,
     patient_id: String,
@@ -472,6 +467,7 @@ pub async fn medplum_sync_encounter(
     // Reuse the complete_encounter logic
     medplum_complete_encounter(
         medplum_state,
+        smart_state,
         encounter_id,
         encounter_fhir_id,
         patient_id,
@@ -486,6 +482,7 @@ pub async fn medplum_sync_encounter(
 #[tauri::command]
 pub async fn medplum_quick_sync(
     medplum_state: State<'_, SharedMedplumClient>,
+    smart_state: State<'_, SharedSmartBackend>,
     transcript: String,
     soap_note: Option<String>,
     audio_file_path: Option<String>,
@@ -493,10 +490,7 @@ pub async fn medplum_quick_sync(
 ) -> Result<SyncResult, CommandError> {
     info!("Quick sync: creating placeholder patient and encounter");
 
-    let client_guard = medplum_state.read().await;
-    let client = client_guard
-        .as_ref()
-        .ok_or_else(|| CommandError::Config("Medplum client not initialized".into()))?;
+    let client = active_emr_backend(medplum_state.inner(), smart_state.inner()).await?;
 
     // Step 1: Create placeholder patient
     let patient = client
@@ -689,15 +683,13 @@ pub async fn medplum_quick_sync(
 #[tauri::command]
 pub async fn medplum_add_soap_to_encounter(
     medplum_state: State<'_, SharedMedplumClient>,
+    smart_state: State<'_, SharedSmartBackend>,
     encounter_fhir_id: String,
     soap_note: String,
 ) -> Result<bool, CommandError> {
     info!("Adding SOAP note to existing encounter: {}", encounter_fhir_id);
 
-    let client_guard = medplum_state.read().await;
-    let client = client_guard
-        .as_ref()
-        .ok_or_else(|| CommandError::Config("Medplum client not initialized".into()))?;
+    let client = active_emr_backend(medplum_state.inner(), smart_state.inner()).await?;
 
     // Fetch patient ID from the encounter's subject reference
     let encounter_details = client
@@ -777,6 +769,7 @@ pub struct MultiPatientSyncResult {
 #[tauri::command]
 pub async fn medplum_multi_patient_quick_sync(
     medplum_state: State<'_, SharedMedplumClient>,
+    smart_state: State<'_, SharedSmartBackend>,
     transcript: String,
     soap_result: MultiPatientSoapResult,
     audio_file_path: Option<String>,
@@ -792,10 +785,7 @@ pub async fn medplum_multi_patient_quick_sync(
         return Err(CommandError::Validation("No patients to sync".into()));
     }
 
-    let client_guard = medplum_state.read().await;
-    let client = client_guard
-        .as_ref()
-        .ok_or_else(|| CommandError::Config("Medplum client not initialized".into()))?;
+    let client = active_emr_backend(medplum_state.inner(), smart_state.inner()).await?;
 
     let mut synced_patients: Vec<PatientSyncInfo> = Vec::new();
     let mut errors: Vec<String> = Vec::new();
@@ -1006,6 +996,85 @@ pub async fn medplum_multi_patient_quick_sync(
     })
 }
 
+/// Build the structured FHIR resources (Condition, MedicationStatement,
+/// vital-sign Observations) for an archived session, for the clinician to
+/// review. Nothing is uploaded; the reviewed preview goes back through
+/// `medplum_upload_structured_resources`.
+#[tauri::command]
+pub fn preview_structured_resources(
+    session_id: String,
+    date: String,
+    medications: Vec<MedEntry>,
+) -> Result<StructuredResourcesPreview, CommandError> {
+    let details = local_archive::get_session(&session_id, &date)?;
+    if details.metadata.patient_count.is_some_and(|n| n > 1) {
+        return Err(CommandError::Validation(
+            "Structured resources need a single-patient session".into(),
+        ));
+    }
+    let soap = details
+        .soap_note
+        .ok_or_else(|| CommandError::NotFound("Session has no SOAP note".into()))?;
+    let billing = local_archive::get_billing_record(&session_id, &super::parse_date(&date)?)?;
+
+    Ok(fhir_extraction::extract_structured_resources(
+        &soap,
+        billing.as_ref(),
+        &medications,
+    ))
+}
+
+/// Upload a clinician-reviewed preview to the session's Medplum Encounter.
+/// The session must have been through patient confirmation (which creates
+/// the Encounter), and is only uploaded once.
+#[tauri::command]
+pub async fn medplum_upload_structured_resources(
+    medplum_state: State<'_, SharedMedplumClient>,
+    smart_state: State<'_, SharedSmartBackend>,
+    session_id: String,
+    date: String,
+    preview: StructuredResourcesPreview,
+) -> Result<StructuredResourcesUploadResult, CommandError> {
+    let metadata = local_archive::get_session(&session_id, &date)?.metadata;
+    let (Some(_), Some(patient_fhir_id), Some(encounter_fhir_id)) = (
+        metadata.patient_confirmed_at.as_ref(),
+        metadata.medplum_patient_id.as_deref(),
+        metadata.medplum_encounter_id.as_deref(),
+    ) else {
+        return Err(CommandError::Validation(
+            "Confirm the patient (with Medplum connected) before uploading structured resources"
+                .into(),
+        ));
+    };
+    if metadata.medplum_structured_resource_ids.is_some() {
+        return Err(CommandError::Validation(
+            "Structured resources were already uploaded for this session".into(),
+        ));
+    }
+    let preview = preview.validated().map_err(CommandError::Validation)?;
+    if preview.is_empty() {
+        return Ok(StructuredResourcesUploadResult::default());
+    }
+
+    let resources = fhir_extraction::to_fhir_resources(
+        &preview,
+        &EncounterLink {
+            session_id: &session_id,
+            patient_fhir_id,
+            encounter_fhir_id,
+            effective: &metadata.started_at,
+        },
+    );
+
+    let client = active_emr_backend(medplum_state.inner(), smart_state.inner()).await?;
+    let result = fhir_extraction::upload_structured_resources(client.as_ref(), &resources).await;
+
+    if !result.created.is_empty() {
+        local_archive::record_structured_resource_ids(&session_id, &date, &result.created)?;
+    }
+    Ok(result)
+}
+
 /// Check if Medplum server is reachable (doesn't require authentication)
 ///
 /// Optional URL override allows testing pending settings without persisting them.
@@ -1050,3 +1119,12 @@ pub async fn medplum_check_connection(
         Ok(false)
     }
 }
+
+/// Session syncs waiting in the Medplum outbox. PHI-free: no names, DOBs or
+/// note text.
+#[tauri::command]
+pub async fn medplum_outbox_status(
+    outbox: State<'_, SharedMedplumOutbox>,
+) -> Result<Vec<OutboxStatus>, CommandError> {
+    Ok(outbox.lock().await.status())
+}
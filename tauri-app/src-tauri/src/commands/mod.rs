@@ -9,6 +9,7 @@ mod billing;
 pub(crate) mod calibration;
 mod clinical_chat;
 mod continuous;
+mod hl7;
 mod listening;
 mod medication;
 mod medplum;
@@ -32,6 +33,7 @@ pub use billing::*;
 pub use calibration::*;
 pub use clinical_chat::*;
 pub use continuous::*;
+pub use hl7::*;
 pub use listening::*;
 pub use medication::*;
 pub use medplum::*;
@@ -47,9 +49,12 @@ pub use settings::*;
 pub use speaker_profiles::*;
 pub use whisper_server::*;
 
+use crate::config::Config;
+use crate::emr_backend::{EmrBackend, EMR_BACKEND_SMART};
 use crate::medplum::MedplumClient;
 use crate::pipeline::PipelineHandle;
 use crate::session::SessionManager;
+use crate::smart_backend::SmartBackendClient;
 use serde::{Deserialize, Serialize};
 use std::sync::{Arc, Mutex};
 use tauri::AppHandle;
@@ -167,6 +172,45 @@ pub fn create_medplum_client() -> SharedMedplumClient {
     Arc::new(RwLock::new(None))
 }
 
+/// Shared SMART Backend Services client, built from settings on first use
+pub type SharedSmartBackend = Arc<RwLock<Option<Arc<SmartBackendClient>>>>;
+
+/// Create an empty shared SMART backend slot
+pub fn create_smart_backend() -> SharedSmartBackend {
+    Arc::new(RwLock::new(None))
+}
+
+/// The EMR backend selected by `Settings::emr_backend` (ADR-0035).
+///
+/// Medplum is returned once it has been initialized (session restore or
+/// login); the SMART client is built from settings here, and rebuilt when
+/// its server or client ID changes.
+pub async fn active_emr_backend(
+    medplum_state: &SharedMedplumClient,
+    smart_state: &SharedSmartBackend,
+) -> Result<Arc<dyn EmrBackend>, CommandError> {
+    let config = Config::load_or_default();
+    if config.emr_backend != EMR_BACKEND_SMART {
+        let client_guard = medplum_state.read().await;
+        let client = client_guard
+            .as_ref()
+            .ok_or_else(|| CommandError::Config("Medplum client not initialized".into()))?;
+        return Ok(Arc::new(client.clone()));
+    }
+
+    let mut smart_guard = smart_state.write().await;
+    if let Some(ref client) = *smart_guard {
+        if client.matches_settings(&config) {
+            return Ok(client.clone());
+        }
+    }
+    let client = SmartBackendClient::from_settings(&config)
+        .map(Arc::new)
+        .map_err(|e| CommandError::Config(format!("SMART backend: {}", e)))?;
+    *smart_guard = Some(client.clone());
+    Ok(client)
+}
+
 /// Parse a "YYYY-MM-DD" date string into a DateTime<Utc> (noon UTC).
 pub(crate) fn parse_date(date: &str) -> Result<chrono::DateTime<chrono::Utc>, CommandError> {
     let naive_date = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
@@ -197,6 +197,17 @@ pub async fn start_session(
                         Ok(s) => s,
                         Err(_) => continue,
                     };
+                    // Push to MCP live-transcript subscribers (no-op when none)
+                    crate::mcp::notifications::publish_transcript_segment(
+                        crate::mcp::notifications::TranscriptSegmentUpdate {
+                            source: "session",
+                            session_id: session.session_id(),
+                            text: &segment.text,
+                            start_ms: segment.start_ms,
+                            end_ms: segment.end_ms,
+                            speaker_id: segment.speaker_id.as_deref(),
+                        },
+                    );
                     session.add_segment(segment);
                     drop(session);
 
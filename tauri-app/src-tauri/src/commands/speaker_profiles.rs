@@ -286,6 +286,7 @@ fn extract_embedding_from_audio(audio: &[f32]) -> Result<Vec<f32>, CommandError>
         min_audio_samples: 8000,
         min_energy_threshold: -10.0,
         n_threads: 2,
+        overlap_detection: false,
     };
 
     let mut provider = DiarizationProvider::new(diarization_config)
@@ -95,6 +95,36 @@ pub struct Settings {
     pub medplum_client_id: String,
     #[serde(default = "default_medplum_auto_sync")]
     pub medplum_auto_sync: bool,
+    // EMR backend selection: "medplum" or "smart_backend" (ADR-0035)
+    #[serde(default = "default_emr_backend")]
+    pub emr_backend: String,
+    // SMART Backend Services settings (used when emr_backend = "smart_backend")
+    #[serde(default)]
+    pub fhir_server_url: String,
+    #[serde(default)]
+    pub smart_client_id: String,
+    #[serde(default)]
+    pub smart_private_key_path: String,
+    #[serde(default)]
+    pub smart_key_id: String,
+    #[serde(default = "default_smart_scope")]
+    pub smart_scope: String,
+    // HL7 v2 outbound interface for legacy EMRs (ADR-0036)
+    #[serde(default)]
+    pub hl7_enabled: bool,
+    #[serde(default)]
+    pub hl7_host: String,
+    #[serde(default = "default_hl7_port")]
+    pub hl7_port: u16,
+    // "MDM_T02" or "ORU_R01"
+    #[serde(default = "default_hl7_message_type")]
+    pub hl7_message_type: String,
+    #[serde(default)]
+    pub hl7_sending_facility: String,
+    #[serde(default)]
+    pub hl7_receiving_application: String,
+    #[serde(default)]
+    pub hl7_receiving_facility: String,
     // Whisper server settings (for remote transcription)
     #[serde(default = "default_whisper_mode")]
     pub whisper_mode: String,
@@ -144,6 +174,10 @@ pub struct Settings {
     // Pharmacotherapy refactor service (MacBook-hosted; see clinical-assistant window)
     #[serde(default = "default_pharm_service_url")]
     pub pharm_service_url: String,
+    // Bearer token the IT Admin Coordinator presents for the MCP control and
+    // PHI tools; empty leaves those tools disabled
+    #[serde(default)]
+    pub mcp_auth_token: String,
     // AI image generation settings
     #[serde(default = "default_image_source")]
     pub image_source: String,
@@ -515,6 +549,22 @@ fn default_medplum_auto_sync() -> bool {
     true
 }
 
+fn default_emr_backend() -> String {
+    crate::emr_backend::EMR_BACKEND_MEDPLUM.to_string()
+}
+
+fn default_smart_scope() -> String {
+    "system/*.read system/*.write".to_string()
+}
+
+fn default_hl7_port() -> u16 {
+    2575
+}
+
+fn default_hl7_message_type() -> String {
+    crate::hl7_outbound::HL7_MDM_T02.to_string()
+}
+
 impl Default for Settings {
     fn default() -> Self {
         Self {
@@ -535,6 +585,19 @@ impl Default for Settings {
             medplum_server_url: default_medplum_url(),
             medplum_client_id: default_medplum_client_id(),
             medplum_auto_sync: default_medplum_auto_sync(),
+            emr_backend: default_emr_backend(),
+            fhir_server_url: String::new(),
+            smart_client_id: String::new(),
+            smart_private_key_path: String::new(),
+            smart_key_id: String::new(),
+            smart_scope: default_smart_scope(),
+            hl7_enabled: false,
+            hl7_host: String::new(),
+            hl7_port: default_hl7_port(),
+            hl7_message_type: default_hl7_message_type(),
+            hl7_sending_facility: String::new(),
+            hl7_receiving_application: String::new(),
+            hl7_receiving_facility: String::new(),
             whisper_mode: default_whisper_mode(),
             whisper_server_url: default_whisper_server_url(),
             whisper_server_model: default_whisper_server_model(),
@@ -555,6 +618,7 @@ impl Default for Settings {
             auto_end_silence_ms: default_auto_end_silence_ms(),
             debug_storage_enabled: default_debug_storage_enabled(),
             pharm_service_url: default_pharm_service_url(),
+            mcp_auth_token: String::new(),
             image_source: default_image_source(),
             gemini_api_key: String::new(),
             openai_api_key: String::new(),
@@ -758,6 +822,57 @@ impl Settings {
             });
         }
 
+        // EMR backend must be a known value, and SMART needs its server and credentials
+        if !crate::emr_backend::EMR_BACKENDS.contains(&self.emr_backend.as_str()) {
+            errors.push(SettingsValidationError {
+                field: "emr_backend".to_string(),
+                message: format!(
+                    "Unknown EMR backend '{}'. Must be one of: {}",
+                    self.emr_backend,
+                    crate::emr_backend::EMR_BACKENDS.join(", ")
+                ),
+            });
+        } else if self.emr_backend == crate::emr_backend::EMR_BACKEND_SMART {
+            for (field, value) in [
+                ("fhir_server_url", &self.fhir_server_url),
+                ("smart_client_id", &self.smart_client_id),
+                ("smart_private_key_path", &self.smart_private_key_path),
+            ] {
+                if value.is_empty() {
+                    errors.push(SettingsValidationError {
+                        field: field.to_string(),
+                        message: format!("SMART backend requires {} to be configured", field),
+                    });
+                }
+            }
+        }
+
+        // HL7 message type must be known, and an enabled interface needs a destination
+        if !crate::hl7_outbound::HL7_MESSAGE_TYPES.contains(&self.hl7_message_type.as_str()) {
+            errors.push(SettingsValidationError {
+                field: "hl7_message_type".to_string(),
+                message: format!(
+                    "Unknown HL7 message type '{}'. Must be one of: {}",
+                    self.hl7_message_type,
+                    crate::hl7_outbound::HL7_MESSAGE_TYPES.join(", ")
+                ),
+            });
+        }
+        if self.hl7_enabled {
+            if self.hl7_host.is_empty() {
+                errors.push(SettingsValidationError {
+                    field: "hl7_host".to_string(),
+                    message: "HL7 interface requires hl7_host to be configured".to_string(),
+                });
+            }
+            if self.hl7_port == 0 {
+                errors.push(SettingsValidationError {
+                    field: "hl7_port".to_string(),
+                    message: "HL7 port must be between 1 and 65535".to_string(),
+                });
+            }
+        }
+
         // SOAP format must be a known value
         if self.soap_format != "problem_based" && self.soap_format != "comprehensive" {
             errors.push(SettingsValidationError {
@@ -907,8 +1022,36 @@ pub struct InfrastructureOverlay {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub medplum_client_id: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub emr_backend: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fhir_server_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub smart_client_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub smart_private_key_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub smart_key_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub smart_scope: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hl7_enabled: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hl7_host: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hl7_port: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hl7_message_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hl7_sending_facility: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hl7_receiving_application: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hl7_receiving_facility: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub pharm_service_url: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mcp_auth_token: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub whisper_mode: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub encounter_detection_model: Option<String>,
@@ -1044,7 +1187,21 @@ impl Settings {
             stt_postprocess: Some(self.stt_postprocess),
             medplum_server_url: if self.medplum_server_url.is_empty() { None } else { Some(self.medplum_server_url.clone()) },
             medplum_client_id: Some(self.medplum_client_id.clone()),
+            emr_backend: Some(self.emr_backend.clone()),
+            fhir_server_url: if self.fhir_server_url.is_empty() { None } else { Some(self.fhir_server_url.clone()) },
+            smart_client_id: if self.smart_client_id.is_empty() { None } else { Some(self.smart_client_id.clone()) },
+            smart_private_key_path: if self.smart_private_key_path.is_empty() { None } else { Some(self.smart_private_key_path.clone()) },
+            smart_key_id: if self.smart_key_id.is_empty() { None } else { Some(self.smart_key_id.clone()) },
+            smart_scope: Some(self.smart_scope.clone()),
+            hl7_enabled: Some(self.hl7_enabled),
+            hl7_host: if self.hl7_host.is_empty() { None } else { Some(self.hl7_host.clone()) },
+            hl7_port: Some(self.hl7_port),
+            hl7_message_type: Some(self.hl7_message_type.clone()),
+            hl7_sending_facility: if self.hl7_sending_facility.is_empty() { None } else { Some(self.hl7_sending_facility.clone()) },
+            hl7_receiving_application: if self.hl7_receiving_application.is_empty() { None } else { Some(self.hl7_receiving_application.clone()) },
+            hl7_receiving_facility: if self.hl7_receiving_facility.is_empty() { None } else { Some(self.hl7_receiving_facility.clone()) },
             pharm_service_url: if self.pharm_service_url.is_empty() { None } else { Some(self.pharm_service_url.clone()) },
+            mcp_auth_token: if self.mcp_auth_token.is_empty() { None } else { Some(self.mcp_auth_token.clone()) },
             whisper_mode: Some(self.whisper_mode.clone()),
             encounter_detection_model: Some(self.encounter_detection_model.clone()),
             encounter_detection_nothink: Some(self.encounter_detection_nothink),
@@ -1065,7 +1222,21 @@ impl Settings {
         if let Some(v) = infra.stt_postprocess { self.stt_postprocess = v; }
         if let Some(ref v) = infra.medplum_server_url { self.medplum_server_url = v.clone(); }
         if let Some(ref v) = infra.medplum_client_id { self.medplum_client_id = v.clone(); }
+        if let Some(ref v) = infra.emr_backend { self.emr_backend = v.clone(); }
+        if let Some(ref v) = infra.fhir_server_url { self.fhir_server_url = v.clone(); }
+        if let Some(ref v) = infra.smart_client_id { self.smart_client_id = v.clone(); }
+        if let Some(ref v) = infra.smart_private_key_path { self.smart_private_key_path = v.clone(); }
+        if let Some(ref v) = infra.smart_key_id { self.smart_key_id = v.clone(); }
+        if let Some(ref v) = infra.smart_scope { self.smart_scope = v.clone(); }
+        if let Some(v) = infra.hl7_enabled { self.hl7_enabled = v; }
+        if let Some(ref v) = infra.hl7_host { self.hl7_host = v.clone(); }
+        if let Some(v) = infra.hl7_port { self.hl7_port = v; }
+        if let Some(ref v) = infra.hl7_message_type { self.hl7_message_type = v.clone(); }
+        if let Some(ref v) = infra.hl7_sending_facility { self.hl7_sending_facility = v.clone(); }
+        if let Some(ref v) = infra.hl7_receiving_application { self.hl7_receiving_application = v.clone(); }
+        if let Some(ref v) = infra.hl7_receiving_facility { self.hl7_receiving_facility = v.clone(); }
         if let Some(ref v) = infra.pharm_service_url { self.pharm_service_url = v.clone(); }
+        if let Some(ref v) = infra.mcp_auth_token { self.mcp_auth_token = v.clone(); }
         if let Some(ref v) = infra.whisper_mode { self.whisper_mode = v.clone(); }
         if let Some(ref v) = infra.encounter_detection_model { self.encounter_detection_model = v.clone(); }
         if let Some(v) = infra.encounter_detection_nothink { self.encounter_detection_nothink = v; }
@@ -1145,7 +1316,11 @@ impl Settings {
         // Infrastructure
         for field in &["llm_router_url", "llm_api_key", "llm_client_id", "soap_model", "soap_model_fast",
                        "fast_model", "whisper_server_url", "whisper_server_model", "stt_alias", "stt_postprocess",
-                       "medplum_server_url", "medplum_client_id", "pharm_service_url",
+                       "medplum_server_url", "medplum_client_id", "emr_backend", "fhir_server_url",
+                       "smart_client_id", "smart_private_key_path", "smart_key_id", "smart_scope",
+                       "hl7_enabled", "hl7_host", "hl7_port", "hl7_message_type", "hl7_sending_facility",
+                       "hl7_receiving_application", "hl7_receiving_facility",
+                       "pharm_service_url", "mcp_auth_token",
                        "whisper_mode", "encounter_detection_model", "encounter_detection_nothink"] {
             m.insert(*field, SettingsTier::Infrastructure);
         }
@@ -1626,6 +1801,19 @@ mod tests {
             medplum_server_url: "http://192.168.1.100:8103".to_string(),
             medplum_client_id: "test-client".to_string(),
             medplum_auto_sync: false,
+            emr_backend: "smart_backend".to_string(),
+            fhir_server_url: "http://192.168.1.100:8080/fhir".to_string(),
+            smart_client_id: "scribe-backend".to_string(),
+            smart_private_key_path: "/etc/fabricscribe/smart.pem".to_string(),
+            smart_key_id: "key-1".to_string(),
+            smart_scope: "system/Patient.rs".to_string(),
+            hl7_enabled: true,
+            hl7_host: "192.168.1.50".to_string(),
+            hl7_port: 6661,
+            hl7_message_type: "ORU_R01".to_string(),
+            hl7_sending_facility: "CLINIC".to_string(),
+            hl7_receiving_application: "ACCURO".to_string(),
+            hl7_receiving_facility: "CLINIC".to_string(),
             whisper_mode: "remote".to_string(),
             whisper_server_url: "http://192.168.1.100:8000".to_string(),
             whisper_server_model: "large-v3".to_string(),
@@ -1641,6 +1829,7 @@ mod tests {
             auto_end_silence_ms: 180_000, // 3 minutes
             debug_storage_enabled: true,
             pharm_service_url: "http://100.119.83.76:8091".to_string(),
+            mcp_auth_token: "mcp-secret".to_string(),
             image_source: "off".to_string(),
             gemini_api_key: String::new(),
             openai_api_key: String::new(),
@@ -1699,11 +1888,16 @@ mod tests {
         assert_eq!(config.medplum_server_url, "http://192.168.1.100:8103");
         assert_eq!(config.medplum_client_id, "test-client");
         assert!(!config.medplum_auto_sync);
+        assert_eq!(config.emr_backend, "smart_backend");
+        assert_eq!(config.smart_key_id, "key-1");
+        assert_eq!(config.hl7_port, 6661);
+        assert_eq!(config.hl7_message_type, "ORU_R01");
         assert_eq!(config.whisper_mode, "remote");
         assert_eq!(config.whisper_server_url, "http://192.168.1.100:8000");
         assert_eq!(config.whisper_server_model, "large-v3");
         assert_eq!(config.soap_detail_level, 7);
         assert_eq!(config.soap_format, "comprehensive");
+        assert_eq!(config.mcp_auth_token, "mcp-secret");
         assert_eq!(config.soap_custom_instructions, "Add more detail");
     }
 
@@ -1763,6 +1957,19 @@ mod tests {
             medplum_server_url: default_medplum_url(),
             medplum_client_id: String::new(),
             medplum_auto_sync: true,
+            emr_backend: default_emr_backend(),
+            fhir_server_url: String::new(),
+            smart_client_id: String::new(),
+            smart_private_key_path: String::new(),
+            smart_key_id: String::new(),
+            smart_scope: default_smart_scope(),
+            hl7_enabled: false,
+            hl7_host: String::new(),
+            hl7_port: default_hl7_port(),
+            hl7_message_type: default_hl7_message_type(),
+            hl7_sending_facility: String::new(),
+            hl7_receiving_application: String::new(),
+            hl7_receiving_facility: String::new(),
             whisper_mode: "remote".to_string(),  // Always remote
             whisper_server_url: default_whisper_server_url(),
             whisper_server_model: default_whisper_server_model(),
@@ -1778,6 +1985,7 @@ mod tests {
             auto_end_silence_ms: 180_000,
             debug_storage_enabled: true,
             pharm_service_url: default_pharm_service_url(),
+            mcp_auth_token: String::new(),
             image_source: default_image_source(),
             gemini_api_key: String::new(),
             openai_api_key: String::new(),
@@ -694,6 +694,19 @@ pub async fn run_continuous_mode<C: crate::run_context::RunContext>(
                         continue;
                     };
 
+                    // Push to MCP live-transcript subscribers (no-op when none).
+                    // No session_id yet: the encounter is still being buffered.
+                    crate::mcp::notifications::publish_transcript_segment(
+                        crate::mcp::notifications::TranscriptSegmentUpdate {
+                            source: "continuous",
+                            session_id: None,
+                            text: &segment.text,
+                            start_ms: segment.start_ms,
+                            end_ms: segment.end_ms,
+                            speaker_id: segment.speaker_id.as_deref(),
+                        },
+                    );
+
                     // Log segment to segment timeline and replay bundle
                     if let Ok(mut sl) = segment_logger_for_consumer.lock() {
                         sl.log_segment(
@@ -834,6 +847,8 @@ pub async fn run_continuous_mode<C: crate::run_context::RunContext>(
     let mut shadow_sensor_state_rx: Option<tokio::sync::watch::Receiver<crate::presence_sensor::PresenceState>> = None;
     // Hybrid mode: dedicated watch receiver for sensor state in the detection loop
     let mut hybrid_sensor_state_rx: Option<tokio::sync::watch::Receiver<crate::presence_sensor::PresenceState>> = None;
+    // Hybrid mode: head-count estimates for occupancy hints in the detection loop
+    let mut hybrid_occupancy_rx: Option<tokio::sync::watch::Receiver<crate::presence_sensor::OccupancyEstimate>> = None;
 
     if use_sensor_mode {
         // Auto-detect serial port only when not using HTTP mode
@@ -844,7 +859,7 @@ pub async fn run_continuous_mode<C: crate::run_context::RunContext>(
             String::new()
         };
 
-        let sensor_config = crate::presence_sensor::SuiteConfig {
+        let mut sensor_config = crate::presence_sensor::SuiteConfig {
             port: sensor_port,
             url: config.presence_sensor_url.clone(),
             debounce_secs: config.presence_debounce_secs,
@@ -863,6 +878,25 @@ pub async fn run_continuous_mode<C: crate::run_context::RunContext>(
             },
             fusion: crate::presence_sensor::FusionConfig::default(),
         };
+        // Calibrated rooms fuse thermal + CO2 with per-sensor weights;
+        // without a profile the suite stays mmWave-only.
+        let room_id = crate::room_config::RoomConfig::load()
+            .ok()
+            .flatten()
+            .and_then(|room| room.room_id);
+        if let Some(room_id) = room_id {
+            match crate::presence_sensor::RoomProfile::load(&room_id) {
+                Ok(Some(profile)) => {
+                    info!(
+                        "Using presence calibration for room {} (weights mmwave={:.2} thermal={:.2} co2={:.2})",
+                        room_id, profile.weights.mmwave, profile.weights.thermal, profile.weights.co2
+                    );
+                    profile.apply(&mut sensor_config);
+                }
+                Ok(None) => {}
+                Err(e) => warn!("Failed to load presence calibration for room {}: {}", room_id, e),
+            }
+        }
 
         match crate::presence_sensor::PresenceSensorSuite::start_suite(&sensor_config) {
             Ok(sensor) => {
@@ -885,6 +919,7 @@ pub async fn run_continuous_mode<C: crate::run_context::RunContext>(
                 // Get a dedicated state receiver for hybrid detection loop
                 if is_hybrid_mode {
                     hybrid_sensor_state_rx = Some(sensor.subscribe_state());
+                    hybrid_occupancy_rx = Some(sensor.subscribe_occupancy());
                 }
                 sensor_handle = Some(sensor);
             }
@@ -1078,7 +1113,7 @@ pub async fn run_continuous_mode<C: crate::run_context::RunContext>(
     let hybrid_confirm_window_secs = config.hybrid_confirm_window_secs;
     let hybrid_min_words_for_sensor_split = config.hybrid_min_words_for_sensor_split;
     let billing_counselling_exhausted = config.billing_counselling_exhausted;
-    // Move the hybrid sensor receiver into the detector task
+    // Move the hybrid sensor receivers into the detector task
     let mut hybrid_sensor_rx = hybrid_sensor_state_rx;
 
     // Dedicated LLM client for the merge-back coordinator (LLMClient is !Clone,
@@ -1182,6 +1217,9 @@ pub async fn run_continuous_mode<C: crate::run_context::RunContext>(
         let mut sensor_state = crate::continuous_mode_trigger_wait::SensorLoopState::new(
             hybrid_sensor_rx.is_some(),
         );
+        // Hybrid mode: head count across the current encounter, sampled at
+        // each detection check. Reset on split.
+        let mut encounter_occupancy = crate::presence_sensor::EncounterOccupancy::new();
 
         // Long-lived deps for the trigger waiter.
         let trigger_wait_deps = crate::continuous_mode_trigger_wait::TriggerWaitDeps {
@@ -1409,6 +1447,10 @@ pub async fn run_continuous_mode<C: crate::run_context::RunContext>(
                     if !ctx.sensor_departed && sensor_state.is_currently_present() {
                         ctx.sensor_present = true;
                     }
+                    // Head-count change (someone joined, or all but one left) — hint only
+                    if let Some(ref rx) = hybrid_occupancy_rx {
+                        ctx.occupancy_hint = encounter_occupancy.observe(&rx.borrow());
+                    }
                     ctx
                 };
                 let (system_prompt, user_prompt) = build_encounter_detection_prompt(
@@ -1432,6 +1474,7 @@ pub async fn run_continuous_mode<C: crate::run_context::RunContext>(
                     "cleaned_word_count": cleaned_word_count,
                     "sensor_present": detection_context.sensor_present,
                     "sensor_departed": detection_context.sensor_departed,
+                    "occupancy_hint": detection_context.occupancy_hint,
                     "nothink": detection_nothink,
                     "consecutive_llm_failures": consecutive_llm_failures,
                 });
@@ -1722,6 +1765,8 @@ pub async fn run_continuous_mode<C: crate::run_context::RunContext>(
                             // This blocks LLM-only splits while the same person remains in the room.
                             sensor_state.sensor_continuous_present = sensor_state.is_currently_present();
                         }
+                        let sensor_saw_arrival = encounter_occupancy.saw_arrival();
+                        encounter_occupancy.reset();
                         info!(
                             "Encounter #{} detected (end_segment_index={})",
                             loop_state.encounter_number, end_index
@@ -1741,7 +1786,10 @@ pub async fn run_continuous_mode<C: crate::run_context::RunContext>(
                         )
                         .await
                         {
-                            Ok(s) => s,
+                            Ok(s) => crate::continuous_mode_splitter::SplitContext {
+                                sensor_saw_arrival,
+                                ..s
+                            },
                             Err(e) => {
                                 warn!("Splitter failed: {}", e);
                                 continue;
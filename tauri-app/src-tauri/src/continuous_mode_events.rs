@@ -44,6 +44,12 @@ pub enum ContinuousModeEvent {
         session_id: String,
         error: String,
     },
+    /// Billing codes were extracted and archived for an encounter.
+    BillingReady {
+        session_id: String,
+        codes_count: usize,
+        total_amount_cents: u32,
+    },
     EncounterMerged {
         #[serde(skip_serializing_if = "Option::is_none")]
         kept_session_id: Option<String>,
@@ -111,6 +117,7 @@ impl ContinuousModeEvent {
     pub fn emit(&self, app: &tauri::AppHandle) {
         use tauri::Emitter;
         let _ = app.emit("continuous_mode_event", self);
+        crate::mcp::notifications::publish_continuous_event(self);
     }
 
     /// Emit this event via a RunContext. Used by run_continuous_mode so the
@@ -275,6 +282,21 @@ mod tests {
         assert_eq!(json.as_object().unwrap().len(), 3);
     }
 
+    #[test]
+    fn serialize_billing_ready() {
+        let event = ContinuousModeEvent::BillingReady {
+            session_id: "sess-bill".into(),
+            codes_count: 3,
+            total_amount_cents: 4520,
+        };
+        let json: serde_json::Value = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["type"], "billing_ready");
+        assert_eq!(json["session_id"], "sess-bill");
+        assert_eq!(json["codes_count"], 3);
+        assert_eq!(json["total_amount_cents"], 4520);
+        assert_eq!(json.as_object().unwrap().len(), 4);
+    }
+
     #[test]
     fn serialize_encounter_merged_with_kept() {
         let event = ContinuousModeEvent::EncounterMerged {
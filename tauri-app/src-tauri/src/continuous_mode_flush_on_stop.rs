@@ -651,6 +651,7 @@ pub async fn run<C: RunContext>(
                         Some(&bundle_for_flush),
                         flush_screenshot_arg,
                         &flush_vision_model,
+                        Some(&sync_ctx),
                     )
                     .await;
                     if let crate::encounter_pipeline::SoapGenerationOutcome::Success {
@@ -725,8 +726,13 @@ pub async fn run<C: RunContext>(
                                             success: true,
                                         });
                                     }
+                                    ContinuousModeEvent::BillingReady {
+                                        session_id: session_id.clone(),
+                                        codes_count: record.codes.len(),
+                                        total_amount_cents: record.total_amount_cents,
+                                    }
+                                    .emit_via(ctx);
                                     // Re-upload so server's has_billing_record catches up.
-                                    let _ = record;
                                     let today = ctx.now_utc().format("%Y-%m-%d").to_string();
                                     sync_ctx.resync_session(&session_id, &today);
                                 }
@@ -793,6 +793,7 @@ pub async fn run<C: RunContext>(
                                             None, // retrospective regen targets prev session; current bundle is for the new encounter
                                             prev_screenshot_arg,
                                             &deps.vision_model,
+                                            Some(&deps.sync_ctx),
                                         )
                                         .await;
                                     if let crate::encounter_pipeline::SoapGenerationOutcome::Success {
@@ -956,6 +957,7 @@ pub async fn run<C: RunContext>(
                     Some(&deps.bundle),
                     cur_screenshot_arg,
                     &deps.vision_model,
+                    Some(&deps.sync_ctx),
                 )
                 .await;
                 if let crate::encounter_pipeline::SoapGenerationOutcome::Success {
@@ -92,9 +92,11 @@ pub async fn run<C: RunContext>(
         encounter_duration_ms,
         notes_text,
         encounter_patient_name,
+        sensor_saw_arrival,
         ..
     } = split;
     let encounter_word_count = *encounter_word_count;
+    let sensor_saw_arrival = *sensor_saw_arrival;
     let encounter_duration_ms = *encounter_duration_ms;
 
     // Clinical content check: flag non-clinical encounters
@@ -171,13 +173,17 @@ pub async fn run<C: RunContext>(
         let screenshot_arg = Some(deduped_screenshots.as_slice());
 
         // Detect couples/family visits before SOAP so the per-patient SOAP
-        // path can fire when patient_count > 1.
-        let multi_patient_detection = if encounter_word_count >= deps.multi_patient_detect_word_threshold {
+        // path can fire when patient_count > 1. A sensor-counted arrival
+        // runs the check even on short encounters.
+        let multi_patient_detection = if encounter_word_count >= deps.multi_patient_detect_word_threshold
+            || sensor_saw_arrival
+        {
             info!(
                 event = "post_split_multi_patient_detect",
                 component = "continuous_mode_post_split",
                 encounter_number,
                 word_count = encounter_word_count,
+                sensor_saw_arrival,
                 screenshots_attached = deduped_screenshots.len(),
                 "Running multi-patient detection"
             );
@@ -251,6 +257,7 @@ pub async fn run<C: RunContext>(
             Some(&deps.bundle),
             screenshot_arg,
             &deps.vision_model,
+            Some(&deps.sync_ctx),
         )
         .await;
 
@@ -372,6 +379,12 @@ pub async fn run<C: RunContext>(
                                     success: true,
                                 });
                             }
+                            ContinuousModeEvent::BillingReady {
+                                session_id: target_sid.clone(),
+                                codes_count: record.codes.len(),
+                                total_amount_cents: record.total_amount_cents,
+                            }
+                            .emit_via(ctx);
                             // Single resync per target: uploads metadata, transcript,
                             // soap_note, billing.json, has_billing_record=true. For
                             // siblings this also creates the new sibling sessions
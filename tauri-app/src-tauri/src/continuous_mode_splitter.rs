@@ -89,6 +89,10 @@ pub struct SplitContext {
     /// Resolved session archive directory for the new encounter. `None` if
     /// the archive path could not be resolved (rare — path derivation failure).
     pub session_dir: Option<PathBuf>,
+    /// Room sensors counted someone joining mid-encounter. The splitter
+    /// leaves this false; the detector loop sets it from its
+    /// `EncounterOccupancy` tracker before post_split runs.
+    pub sensor_saw_arrival: bool,
 }
 
 /// Run the split pipeline and return a `SplitContext` for the downstream
@@ -382,6 +386,7 @@ pub async fn split_encounter<C: RunContext>(
         encounter_patient_name,
         detection_method: detection_method_str,
         session_dir,
+        sensor_saw_arrival: false,
     })
 }
 
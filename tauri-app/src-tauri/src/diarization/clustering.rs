@@ -124,7 +124,7 @@ pub struct SpeakerClusterer {
 
 /// Default similarity threshold for enrolled speaker matching
 /// Higher than auto-clustering for more confident recognition
-const DEFAULT_ENROLLED_THRESHOLD: f32 = 0.6;
+pub(crate) const DEFAULT_ENROLLED_THRESHOLD: f32 = 0.6;
 
 impl SpeakerClusterer {
     /// Create a new speaker clusterer with the given configuration
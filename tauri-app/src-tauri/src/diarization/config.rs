@@ -28,6 +28,10 @@ pub struct DiarizationConfig {
     /// Minimum energy threshold to process audio (log scale)
     /// Audio below this is considered silence
     pub min_energy_threshold: f32,
+
+    /// Run sub-window overlap detection on each utterance
+    /// (one extra embedding per 750ms of speech)
+    pub overlap_detection: bool,
 }
 
 impl Default for DiarizationConfig {
@@ -40,6 +44,7 @@ impl Default for DiarizationConfig {
             n_threads: 2,
             min_audio_samples: 8000, // 500ms at 16kHz
             min_energy_threshold: -10.0,
+            overlap_detection: true,
         }
     }
 }
@@ -152,6 +157,7 @@ mod tests {
         assert_eq!(config.similarity_threshold, 0.3);
         assert_eq!(config.max_speakers, 10);
         assert_eq!(config.min_audio_samples, 8000);
+        assert!(config.overlap_detection);
     }
 
     #[test]
@@ -4,11 +4,15 @@
 //! 1. Converting audio to mel spectrograms
 //! 2. Extracting speaker embeddings via ONNX model
 //! 3. Clustering embeddings to assign speaker IDs
+//!
+//! [`offline`] re-clusters an archived encounter after the fact.
 
 pub mod clustering;
 pub mod config;
 pub mod embedding;
 pub mod mel;
+pub mod offline;
+pub mod overlap;
 pub mod provider;
 
 pub use config::{ClusterConfig, DiarizationConfig};
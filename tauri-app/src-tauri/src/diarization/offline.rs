@@ -0,0 +1,380 @@
+//! Offline second-pass speaker clustering.
+//!
+//! The live clusterer ([`SpeakerClusterer`](super::clustering::SpeakerClusterer))
+//! is single-pass: early segments are assigned against centroids built from
+//! one or two embeddings, so speaker IDs drift at the start of an encounter
+//! and are never revisited. Once an encounter is archived every segment is
+//! available, so this pass can do better:
+//!
+//! 1. Re-extract one embedding per segment (via a [`SegmentEmbedder`])
+//! 2. Average-linkage agglomerative clustering, merging the closest pair of
+//!    clusters until their similarity drops below `stop_threshold`
+//! 3. Re-anchor clusters to enrolled speaker profiles (at most one cluster
+//!    per profile, best match first)
+//! 4. Name the remaining clusters "Speaker N" in order of first appearance
+//!
+//! Reading/writing the archive lives in `crate::rediarization`.
+
+use super::clustering::{EnrolledCentroid, DEFAULT_ENROLLED_THRESHOLD};
+use super::{cosine_similarity, l2_normalize, DiarizationError};
+
+/// Configuration for the offline clustering pass
+#[derive(Debug, Clone)]
+pub struct OfflineDiarizationConfig {
+    /// Average-linkage similarity below which two clusters are kept apart.
+    /// Averaging over every pair smooths out per-segment noise, so this sits
+    /// above the online `similarity_threshold` (0.3).
+    pub stop_threshold: f32,
+
+    /// Upper bound on speakers; clusters keep merging past `stop_threshold`
+    /// until at most this many remain
+    pub max_speakers: usize,
+
+    /// Minimum centroid similarity to label a cluster with an enrolled profile
+    pub enrolled_threshold: f32,
+}
+
+impl Default for OfflineDiarizationConfig {
+    fn default() -> Self {
+        Self {
+            stop_threshold: 0.45,
+            max_speakers: 10,
+            enrolled_threshold: DEFAULT_ENROLLED_THRESHOLD,
+        }
+    }
+}
+
+/// Audio clock span of one archived transcript segment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SegmentSpan {
+    /// Segment index from `segments.jsonl`
+    pub index: u64,
+    pub start_ms: u64,
+    pub end_ms: u64,
+}
+
+/// Source of per-segment embeddings for the offline pass.
+///
+/// Production reads the encounter's recording (`rediarization::WavSegmentEmbedder`);
+/// tests supply synthetic embeddings.
+pub trait SegmentEmbedder {
+    /// Embedding for the audio between `start_ms` and `end_ms`, or `None` if
+    /// the audio is too short or quiet for a reliable embedding.
+    fn embed(&mut self, start_ms: u64, end_ms: u64) -> Result<Option<Vec<f32>>, DiarizationError>;
+}
+
+/// New speaker label for one segment
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpeakerRelabel {
+    pub index: u64,
+    /// `None` when no embedding could be extracted for the segment
+    pub speaker_id: Option<String>,
+    /// Cosine similarity to the assigned cluster centroid (or enrolled profile)
+    pub speaker_confidence: Option<f32>,
+}
+
+/// Cluster L2-normalized embeddings with average linkage.
+///
+/// Returns one cluster number per embedding, numbered 0.. in order of first
+/// appearance. O(n³) in the number of segments, which is fine for encounter
+/// sizes (a few hundred segments).
+pub fn cluster_embeddings(embeddings: &[Vec<f32>], config: &OfflineDiarizationConfig) -> Vec<usize> {
+    let n = embeddings.len();
+    if n == 0 {
+        return Vec::new();
+    }
+
+    // Pairwise cluster similarity; updated in place as clusters merge
+    let mut sim = vec![vec![0.0f32; n]; n];
+    for i in 0..n {
+        for j in (i + 1)..n {
+            let s = cosine_similarity(&embeddings[i], &embeddings[j]);
+            sim[i][j] = s;
+            sim[j][i] = s;
+        }
+    }
+
+    let mut active = vec![true; n];
+    let mut sizes = vec![1usize; n];
+    // Representative cluster for each embedding (union by lowest index)
+    let mut parent: Vec<usize> = (0..n).collect();
+    let mut cluster_count = n;
+    let max_speakers = config.max_speakers.max(1);
+
+    while cluster_count > 1 {
+        let mut best: Option<(usize, usize, f32)> = None;
+        for i in (0..n).filter(|&i| active[i]) {
+            for j in ((i + 1)..n).filter(|&j| active[j]) {
+                if best.is_none_or(|(_, _, s)| sim[i][j] > s) {
+                    best = Some((i, j, sim[i][j]));
+                }
+            }
+        }
+        let Some((a, b, s)) = best else { break };
+        if s < config.stop_threshold && cluster_count <= max_speakers {
+            break;
+        }
+
+        // Lance-Williams update for average linkage: merge b into a
+        let (na, nb) = (sizes[a] as f32, sizes[b] as f32);
+        for k in (0..n).filter(|&k| active[k] && k != a && k != b) {
+            let merged = (na * sim[a][k] + nb * sim[b][k]) / (na + nb);
+            sim[a][k] = merged;
+            sim[k][a] = merged;
+        }
+        sizes[a] += sizes[b];
+        active[b] = false;
+        for p in parent.iter_mut() {
+            if *p == b {
+                *p = a;
+            }
+        }
+        cluster_count -= 1;
+    }
+
+    // Renumber by first appearance so labels follow the conversation order
+    let mut numbering: Vec<Option<usize>> = vec![None; n];
+    let mut next = 0;
+    parent
+        .iter()
+        .map(|&root| {
+            *numbering[root].get_or_insert_with(|| {
+                next += 1;
+                next - 1
+            })
+        })
+        .collect()
+}
+
+/// Greedily pair clusters with enrolled profiles, best similarity first.
+///
+/// Returns the matched enrolled index for each centroid. A profile labels at
+/// most one cluster, so two clusters can't both become "Dr. Smith".
+fn anchor_to_enrolled(
+    centroids: &[Vec<f32>],
+    enrolled: &[EnrolledCentroid],
+    threshold: f32,
+) -> Vec<Option<usize>> {
+    let mut candidates: Vec<(usize, usize, f32)> = Vec::new();
+    for (c, centroid) in centroids.iter().enumerate() {
+        for (e, profile) in enrolled.iter().enumerate() {
+            let s = profile.similarity(centroid);
+            if s >= threshold {
+                candidates.push((c, e, s));
+            }
+        }
+    }
+    candidates.sort_by(|a, b| b.2.partial_cmp(&a.2).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut cluster_match = vec![None; centroids.len()];
+    let mut profile_taken = vec![false; enrolled.len()];
+    for (c, e, _) in candidates {
+        if cluster_match[c].is_none() && !profile_taken[e] {
+            cluster_match[c] = Some(e);
+            profile_taken[e] = true;
+        }
+    }
+    cluster_match
+}
+
+/// Run the full offline pass over an encounter's segments.
+///
+/// Returns one [`SpeakerRelabel`] per span, in input order.
+pub fn rediarize(
+    spans: &[SegmentSpan],
+    embedder: &mut dyn SegmentEmbedder,
+    enrolled: &[EnrolledCentroid],
+    config: &OfflineDiarizationConfig,
+) -> Result<Vec<SpeakerRelabel>, DiarizationError> {
+    // Step 1: embeddings (None for segments too short/quiet to embed)
+    let mut embeddings: Vec<Option<Vec<f32>>> = Vec::with_capacity(spans.len());
+    for span in spans {
+        let embedding = embedder.embed(span.start_ms, span.end_ms)?.map(|mut e| {
+            l2_normalize(&mut e);
+            e
+        });
+        embeddings.push(embedding);
+    }
+
+    let embedded: Vec<Vec<f32>> = embeddings.iter().flatten().cloned().collect();
+
+    // Step 2: cluster
+    let assignments = cluster_embeddings(&embedded, config);
+    let cluster_count = assignments.iter().max().map_or(0, |m| m + 1);
+
+    let mut centroids = vec![vec![0.0f32; embedded.first().map_or(0, |e| e.len())]; cluster_count];
+    for (embedding, &cluster) in embedded.iter().zip(&assignments) {
+        for (c, e) in centroids[cluster].iter_mut().zip(embedding) {
+            *c += *e;
+        }
+    }
+    for centroid in centroids.iter_mut() {
+        l2_normalize(centroid);
+    }
+
+    // Step 3: enrolled profiles first, then "Speaker N" in order of appearance
+    let anchors = anchor_to_enrolled(&centroids, enrolled, config.enrolled_threshold);
+    let mut next_speaker_num = 0;
+    let names: Vec<String> = anchors
+        .iter()
+        .map(|anchor| match anchor {
+            Some(e) => enrolled[*e].name.clone(),
+            None => {
+                next_speaker_num += 1;
+                format!("Speaker {}", next_speaker_num)
+            }
+        })
+        .collect();
+
+    tracing::info!(
+        "Offline re-diarization: {} segments ({} embedded) -> {} speakers ({} enrolled)",
+        spans.len(),
+        embedded.len(),
+        cluster_count,
+        anchors.iter().filter(|a| a.is_some()).count()
+    );
+
+    // Step 4: map back onto every span
+    let mut assigned = assignments.into_iter();
+    Ok(spans
+        .iter()
+        .zip(&embeddings)
+        .map(|(span, embedding)| match embedding {
+            Some(embedding) => {
+                let cluster = assigned.next().unwrap_or(0);
+                let confidence = match anchors[cluster] {
+                    Some(e) => enrolled[e].similarity(embedding),
+                    None => cosine_similarity(&centroids[cluster], embedding),
+                };
+                SpeakerRelabel {
+                    index: span.index,
+                    speaker_id: Some(names[cluster].clone()),
+                    speaker_confidence: Some(confidence.clamp(0.0, 1.0)),
+                }
+            }
+            None => SpeakerRelabel {
+                index: span.index,
+                speaker_id: None,
+                speaker_confidence: None,
+            },
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIM: usize = 64;
+
+    /// Unit vector along `axis` with a small deterministic wobble on the other
+    /// dimensions, so same-speaker embeddings are similar but not identical.
+    fn voice(axis: usize, wobble_seed: usize) -> Vec<f32> {
+        let mut v: Vec<f32> = (0..DIM)
+            .map(|i| 0.15 * (((i * 7 + wobble_seed * 13) % 17) as f32 / 17.0 - 0.5))
+            .collect();
+        v[axis] = 1.0;
+        l2_normalize(&mut v);
+        v
+    }
+
+    struct FixedEmbedder(Vec<Option<Vec<f32>>>);
+
+    impl SegmentEmbedder for FixedEmbedder {
+        fn embed(&mut self, start_ms: u64, _end_ms: u64) -> Result<Option<Vec<f32>>, DiarizationError> {
+            Ok(self.0[(start_ms / 1000) as usize].clone())
+        }
+    }
+
+    fn spans(n: usize) -> Vec<SegmentSpan> {
+        (0..n)
+            .map(|i| SegmentSpan {
+                index: i as u64,
+                start_ms: i as u64 * 1000,
+                end_ms: i as u64 * 1000 + 900,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_cluster_embeddings_separates_speakers() {
+        let embeddings: Vec<Vec<f32>> = (0..12).map(|i| voice(if i % 3 == 0 { 5 } else { 40 }, i)).collect();
+        let labels = cluster_embeddings(&embeddings, &OfflineDiarizationConfig::default());
+
+        // First segment's speaker is cluster 0, numbered by first appearance
+        assert_eq!(labels[0], 0);
+        assert_eq!(labels[1], 1);
+        for (i, label) in labels.iter().enumerate() {
+            assert_eq!(*label, if i % 3 == 0 { 0 } else { 1 });
+        }
+    }
+
+    #[test]
+    fn test_cluster_embeddings_respects_max_speakers() {
+        let embeddings: Vec<Vec<f32>> = (0..6).map(|i| voice(i * 10, i)).collect();
+        let config = OfflineDiarizationConfig {
+            max_speakers: 2,
+            ..Default::default()
+        };
+        let labels = cluster_embeddings(&embeddings, &config);
+        assert_eq!(labels.iter().max(), Some(&1));
+    }
+
+    #[test]
+    fn test_cluster_embeddings_empty() {
+        assert!(cluster_embeddings(&[], &OfflineDiarizationConfig::default()).is_empty());
+    }
+
+    #[test]
+    fn test_rediarize_anchors_enrolled_and_skips_unembedded() {
+        let mut per_segment: Vec<Option<Vec<f32>>> = (0..8)
+            .map(|i| Some(voice(if i % 2 == 0 { 3 } else { 30 }, i)))
+            .collect();
+        per_segment[5] = None; // too short to embed
+        let mut embedder = FixedEmbedder(per_segment);
+
+        let enrolled = vec![EnrolledCentroid::new(
+            "p1".to_string(),
+            "Dr. Smith".to_string(),
+            voice(30, 99),
+            true,
+        )];
+
+        let relabels = rediarize(&spans(8), &mut embedder, &enrolled, &OfflineDiarizationConfig::default()).unwrap();
+
+        assert_eq!(relabels.len(), 8);
+        assert_eq!(relabels[0].speaker_id.as_deref(), Some("Speaker 1"));
+        assert_eq!(relabels[1].speaker_id.as_deref(), Some("Dr. Smith"));
+        assert_eq!(relabels[4].speaker_id.as_deref(), Some("Speaker 1"));
+        assert_eq!(relabels[5].speaker_id, None);
+        assert_eq!(relabels[5].speaker_confidence, None);
+        assert!(relabels[1].speaker_confidence.unwrap() > 0.9);
+    }
+
+    #[test]
+    fn test_enrolled_profile_labels_at_most_one_cluster() {
+        // Two distinct voices that are both somewhat close to the enrolled profile
+        let mut a = voice(3, 1);
+        a[4] = 0.8;
+        l2_normalize(&mut a);
+        let mut b = voice(4, 2);
+        b[3] = 0.8;
+        l2_normalize(&mut b);
+        let mut profile = vec![0.0; DIM];
+        profile[3] = 1.0;
+        profile[4] = 0.9;
+
+        let enrolled = vec![EnrolledCentroid::new("p1".into(), "Dr. Smith".into(), profile, true)];
+        let config = OfflineDiarizationConfig {
+            stop_threshold: 0.99,
+            enrolled_threshold: 0.5,
+            ..Default::default()
+        };
+        let mut embedder = FixedEmbedder(vec![Some(a), Some(b)]);
+        let relabels = rediarize(&spans(2), &mut embedder, &enrolled, &config).unwrap();
+
+        let names: Vec<_> = relabels.iter().filter_map(|r| r.speaker_id.as_deref()).collect();
+        assert_eq!(names.iter().filter(|n| **n == "Dr. Smith").count(), 1);
+        assert!(names.contains(&"Speaker 1"));
+    }
+}
@@ -0,0 +1,276 @@
+//! Overlapping-speech detection from sub-window embeddings.
+//!
+//! `identify_speaker` embeds a whole utterance, so when the patient and
+//! clinician talk over each other the utterance goes to whichever voice
+//! dominates. To find the overlapped part, the utterance is cut into short
+//! sliding windows, each window is embedded, and each window is compared with
+//! the dominant speaker (the medoid window — the one most similar to all
+//! others):
+//!
+//! - similar → dominant speaker alone
+//! - partially similar (inside `mixed_similarity`) → two voices mixed, overlap
+//! - dissimilar → a different speaker alone, not overlap
+//!
+//! A window straddling a clean hand-over from the dominant speaker to another
+//! also looks mixed, so a mixed run sitting between a dominant window and a
+//! different-speaker window is dropped. Remaining mixed windows that touch are
+//! merged into [`OverlapRegion`]s.
+
+use serde::{Deserialize, Serialize};
+
+use super::{cosine_similarity, l2_normalize};
+
+/// A span of an utterance where more than one speaker was talking
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OverlapRegion {
+    /// Audio clock start (milliseconds)
+    pub start_ms: u64,
+    /// Audio clock end (milliseconds)
+    pub end_ms: u64,
+    /// Lowest window similarity to the dominant speaker within the region
+    pub dominant_similarity: f32,
+}
+
+impl OverlapRegion {
+    pub fn duration_ms(&self) -> u64 {
+        self.end_ms.saturating_sub(self.start_ms)
+    }
+}
+
+/// Configuration for sub-window overlap detection
+#[derive(Debug, Clone)]
+pub struct OverlapConfig {
+    /// Sub-window length; long enough for a usable embedding
+    pub window_ms: u64,
+
+    /// Step between sub-windows
+    pub hop_ms: u64,
+
+    /// Utterances shorter than this are not analysed (too few windows to
+    /// establish a dominant speaker)
+    pub min_utterance_ms: u64,
+
+    /// Similarity to the dominant speaker treated as mixed speech (low, high)
+    pub mixed_similarity: (f32, f32),
+}
+
+impl Default for OverlapConfig {
+    fn default() -> Self {
+        Self {
+            window_ms: 1500,
+            hop_ms: 750,
+            min_utterance_ms: 3000,
+            mixed_similarity: (0.2, 0.6),
+        }
+    }
+}
+
+/// Embedding for one sub-window of an utterance
+#[derive(Debug, Clone)]
+pub struct WindowEmbedding {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub embedding: Vec<f32>,
+}
+
+/// Sub-window spans for an utterance. The last window is aligned to the
+/// utterance end so the tail is always covered.
+pub fn sub_windows(start_ms: u64, end_ms: u64, config: &OverlapConfig) -> Vec<(u64, u64)> {
+    if end_ms.saturating_sub(start_ms) < config.min_utterance_ms || config.hop_ms == 0 {
+        return Vec::new();
+    }
+
+    let mut windows = Vec::new();
+    let mut window_start = start_ms;
+    while window_start + config.window_ms <= end_ms {
+        windows.push((window_start, window_start + config.window_ms));
+        window_start += config.hop_ms;
+    }
+    if let Some(&(_, last_end)) = windows.last() {
+        if last_end < end_ms {
+            windows.push((end_ms - config.window_ms, end_ms));
+        }
+    }
+    windows
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum WindowKind {
+    Dominant,
+    Mixed(f32),
+    Other,
+}
+
+/// Find overlapped regions from sub-window embeddings.
+pub fn find_overlap_regions(windows: &[WindowEmbedding], config: &OverlapConfig) -> Vec<OverlapRegion> {
+    if windows.len() < 2 {
+        return Vec::new();
+    }
+
+    let normalized: Vec<Vec<f32>> = windows
+        .iter()
+        .map(|w| {
+            let mut e = w.embedding.clone();
+            l2_normalize(&mut e);
+            e
+        })
+        .collect();
+
+    // Medoid approximates the dominant speaker without being pulled toward
+    // the overlapped windows the way a mean would
+    let dominant = normalized
+        .iter()
+        .max_by(|a, b| {
+            let score = |x: &Vec<f32>| normalized.iter().map(|o| cosine_similarity(x, o)).sum::<f32>();
+            score(a).partial_cmp(&score(b)).unwrap_or(std::cmp::Ordering::Equal)
+        })
+        .cloned()
+        .unwrap_or_default();
+
+    let (low, high) = config.mixed_similarity;
+    let kinds: Vec<WindowKind> = normalized
+        .iter()
+        .map(|e| {
+            let sim = cosine_similarity(&dominant, e);
+            if sim >= high {
+                WindowKind::Dominant
+            } else if sim >= low {
+                WindowKind::Mixed(sim)
+            } else {
+                WindowKind::Other
+            }
+        })
+        .collect();
+
+    let mut regions: Vec<OverlapRegion> = Vec::new();
+    let mut i = 0;
+    while i < kinds.len() {
+        if !matches!(kinds[i], WindowKind::Mixed(_)) {
+            i += 1;
+            continue;
+        }
+        let run_start = i;
+        while i < kinds.len() && matches!(kinds[i], WindowKind::Mixed(_)) {
+            i += 1;
+        }
+        let before = run_start.checked_sub(1).map(|j| kinds[j]);
+        let after = kinds.get(i).copied();
+        let is_hand_over = matches!(
+            (before, after),
+            (Some(WindowKind::Dominant), Some(WindowKind::Other))
+                | (Some(WindowKind::Other), Some(WindowKind::Dominant))
+        );
+        if is_hand_over {
+            continue;
+        }
+
+        for (w, kind) in windows[run_start..i].iter().zip(&kinds[run_start..i]) {
+            let WindowKind::Mixed(sim) = *kind else { continue };
+            match regions.last_mut() {
+                Some(last) if w.start_ms <= last.end_ms => {
+                    last.end_ms = last.end_ms.max(w.end_ms);
+                    last.dominant_similarity = last.dominant_similarity.min(sim);
+                }
+                _ => regions.push(OverlapRegion {
+                    start_ms: w.start_ms,
+                    end_ms: w.end_ms,
+                    dominant_similarity: sim,
+                }),
+            }
+        }
+    }
+    regions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIM: usize = 16;
+
+    fn axis(i: usize) -> Vec<f32> {
+        let mut v = vec![0.0; DIM];
+        v[i] = 1.0;
+        v
+    }
+
+    /// Two voices mixed with weight `b` on the second
+    fn mix(a: usize, b_axis: usize, b: f32) -> Vec<f32> {
+        let mut v = axis(a);
+        v[b_axis] = b;
+        l2_normalize(&mut v);
+        v
+    }
+
+    fn windows(embeddings: Vec<Vec<f32>>) -> Vec<WindowEmbedding> {
+        embeddings
+            .into_iter()
+            .enumerate()
+            .map(|(i, embedding)| WindowEmbedding {
+                start_ms: i as u64 * 750,
+                end_ms: i as u64 * 750 + 1500,
+                embedding,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_sub_windows_cover_utterance() {
+        let config = OverlapConfig::default();
+        let spans = sub_windows(10_000, 14_000, &config);
+        assert_eq!(
+            spans,
+            vec![(10_000, 11_500), (10_750, 12_250), (11_500, 13_000), (12_250, 13_750), (12_500, 14_000)]
+        );
+    }
+
+    #[test]
+    fn test_sub_windows_skip_short_utterances() {
+        assert!(sub_windows(0, 2_999, &OverlapConfig::default()).is_empty());
+    }
+
+    #[test]
+    fn test_single_speaker_has_no_overlap() {
+        let w = windows(vec![axis(0), mix(0, 5, 0.1), axis(0), mix(0, 6, 0.1)]);
+        assert!(find_overlap_regions(&w, &OverlapConfig::default()).is_empty());
+    }
+
+    #[test]
+    fn test_mixed_windows_merge_into_one_region() {
+        // Dominant speaker on axis 0; windows 3 and 4 carry a second voice
+        let w = windows(vec![
+            axis(0),
+            axis(0),
+            axis(0),
+            mix(0, 1, 2.5),
+            mix(0, 1, 2.0),
+            axis(0),
+        ]);
+        let regions = find_overlap_regions(&w, &OverlapConfig::default());
+        assert_eq!(regions.len(), 1);
+        assert_eq!(regions[0].start_ms, 2250);
+        assert_eq!(regions[0].end_ms, 4500);
+        assert_eq!(regions[0].duration_ms(), 2250);
+        assert!(regions[0].dominant_similarity < 0.6);
+    }
+
+    #[test]
+    fn test_clean_speaker_change_is_not_overlap() {
+        // Second half is a different voice entirely, no mixing
+        let w = windows(vec![axis(0), axis(0), axis(0), axis(0), axis(0), axis(3)]);
+        assert!(find_overlap_regions(&w, &OverlapConfig::default()).is_empty());
+    }
+
+    #[test]
+    fn test_hand_over_window_is_not_overlap() {
+        // Dominant speaker hands over to another; the straddling window is mixed
+        let w = windows(vec![axis(0), axis(0), axis(0), mix(0, 3, 1.2), axis(3)]);
+        assert!(find_overlap_regions(&w, &OverlapConfig::default()).is_empty());
+    }
+
+    #[test]
+    fn test_too_few_windows() {
+        let w = windows(vec![mix(0, 1, 1.0)]);
+        assert!(find_overlap_regions(&w, &OverlapConfig::default()).is_empty());
+    }
+}
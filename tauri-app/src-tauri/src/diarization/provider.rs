@@ -9,6 +9,7 @@ use super::clustering::SpeakerClusterer;
 use super::config::{ClusterConfig, DiarizationConfig, MelConfig};
 use super::embedding::EmbeddingExtractor;
 use super::mel::MelSpectrogramGenerator;
+use super::overlap::{self, OverlapConfig, OverlapRegion, WindowEmbedding};
 use super::DiarizationError;
 use crate::speaker_profiles::SpeakerProfile;
 
@@ -46,6 +47,7 @@ pub struct DiarizationProvider {
     extractor: EmbeddingExtractor,
     clusterer: SpeakerClusterer,
     config: DiarizationConfig,
+    overlap_config: OverlapConfig,
 }
 
 #[cfg(feature = "diarization")]
@@ -79,6 +81,7 @@ impl DiarizationProvider {
             extractor,
             clusterer,
             config,
+            overlap_config: OverlapConfig::default(),
         })
     }
 
@@ -130,6 +133,24 @@ impl DiarizationProvider {
         Ok((speaker_id, confidence))
     }
 
+    /// Extract an embedding for an utterance without assigning it to a speaker
+    ///
+    /// Used by the offline re-diarization pass. Returns `Ok(None)` for audio
+    /// that `identify_speaker` would label "Unknown" (too short or too quiet).
+    pub fn embed_utterance(&mut self, utterance: &Utterance) -> Result<Option<Vec<f32>>, DiarizationError> {
+        if utterance.audio.len() < self.config.min_audio_samples {
+            return Ok(None);
+        }
+
+        let mel_spec = self.mel_gen.compute(utterance.audio)?;
+        let energy = MelSpectrogramGenerator::compute_energy(&mel_spec);
+        if energy < self.config.min_energy_threshold.exp() {
+            return Ok(None);
+        }
+
+        self.extractor.extract(&mel_spec).map(Some)
+    }
+
     /// Identify speaker from raw audio samples
     ///
     /// Convenience method that creates an Utterance internally.
@@ -143,6 +164,62 @@ impl DiarizationProvider {
         self.identify_speaker(&utterance)
     }
 
+    /// Detect regions of an utterance where two speakers talk over each other
+    ///
+    /// Embeds short sub-windows and compares them with the utterance's dominant
+    /// speaker (see [`overlap`]). Returns an empty list when overlap detection is
+    /// disabled or the utterance is too short to analyse.
+    pub fn detect_overlap(&mut self, utterance: &Utterance) -> Result<Vec<OverlapRegion>, DiarizationError> {
+        if !self.config.overlap_detection {
+            return Ok(Vec::new());
+        }
+
+        let mut windows = Vec::new();
+        for (start_ms, end_ms) in overlap::sub_windows(utterance.start_ms, utterance.end_ms, &self.overlap_config) {
+            // 16 samples per ms at 16kHz
+            let from = ((start_ms - utterance.start_ms) * 16) as usize;
+            let to = (((end_ms - utterance.start_ms) * 16) as usize).min(utterance.audio.len());
+            if from >= to {
+                continue;
+            }
+
+            let mel_spec = self.mel_gen.compute(&utterance.audio[from..to])?;
+            // Silent windows carry no speaker information
+            if MelSpectrogramGenerator::compute_energy(&mel_spec) < self.config.min_energy_threshold.exp() {
+                continue;
+            }
+            windows.push(WindowEmbedding {
+                start_ms,
+                end_ms,
+                embedding: self.extractor.extract(&mel_spec)?,
+            });
+        }
+
+        let regions = overlap::find_overlap_regions(&windows, &self.overlap_config);
+        if !regions.is_empty() {
+            tracing::debug!(
+                "Utterance {}ms-{}ms: {} overlap region(s)",
+                utterance.start_ms,
+                utterance.end_ms,
+                regions.len()
+            );
+        }
+        Ok(regions)
+    }
+
+    /// Detect overlap from raw audio samples
+    ///
+    /// Convenience method that creates an Utterance internally.
+    pub fn detect_overlap_from_audio(
+        &mut self,
+        audio: &[f32],
+        start_ms: u64,
+        end_ms: u64,
+    ) -> Result<Vec<OverlapRegion>, DiarizationError> {
+        let utterance = Utterance::new(audio, start_ms, end_ms);
+        self.detect_overlap(&utterance)
+    }
+
     /// Reset the speaker clusterer for a new session
     ///
     /// Call this when starting a new recording session to clear
@@ -265,6 +342,10 @@ impl DiarizationProvider {
         Err(DiarizationError::FeatureNotEnabled)
     }
 
+    pub fn embed_utterance(&mut self, _utterance: &Utterance) -> Result<Option<Vec<f32>>, DiarizationError> {
+        Err(DiarizationError::FeatureNotEnabled)
+    }
+
     pub fn identify_speaker_from_audio(
         &mut self,
         _audio: &[f32],
@@ -274,6 +355,15 @@ impl DiarizationProvider {
         Err(DiarizationError::FeatureNotEnabled)
     }
 
+    pub fn detect_overlap_from_audio(
+        &mut self,
+        _audio: &[f32],
+        _start_ms: u64,
+        _end_ms: u64,
+    ) -> Result<Vec<OverlapRegion>, DiarizationError> {
+        Err(DiarizationError::FeatureNotEnabled)
+    }
+
     pub fn reset(&mut self) {}
 
     pub fn speaker_count(&self) -> usize {
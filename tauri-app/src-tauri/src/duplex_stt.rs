@@ -0,0 +1,618 @@
+//! Duplex STT streaming session
+//!
+//! `WhisperServerClient::transcribe_streaming_blocking` only starts once the VAD
+//! has closed an utterance, so long utterances add seconds of latency before any
+//! text appears. A duplex session is opened when the VAD detects speech and is fed
+//! 16 kHz PCM frames as they arrive. The STT Router answers with partial
+//! hypotheses while audio is still flowing and with final hypotheses as it
+//! commits text. When the VAD flushes the utterance the session is told the
+//! audio has ended and the remaining finals are reconciled into `Segment`s.
+//!
+//! Protocol (`/v1/audio/stream`, `"mode": "duplex"`):
+//! - client → `{"alias", "postprocess", "mode": "duplex", "sample_rate": 16000, "encoding": "pcm_s16le"}`
+//! - server → `{"type": "ready"}`
+//! - client → binary frames of little-endian i16 samples, then `{"type": "end"}`
+//! - server → `{"type": "partial", "text"}` replacing the previous partial
+//! - server → `{"type": "final", "text", "start_ms"?, "end_ms"?}` with times relative to the first frame
+//! - server → `{"type": "done"}` once every final has been sent, or `{"type": "error", "detail"}`
+//!
+//! The socket is owned by a worker thread so the pipeline thread never blocks on
+//! reads while it is pushing audio.
+
+use serde::Deserialize;
+use std::net::TcpStream;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+use tungstenite::protocol::Message as WsMessage;
+use tungstenite::stream::MaybeTlsStream;
+use tungstenite::WebSocket;
+
+use crate::transcription::Segment;
+
+/// How long the server has to acknowledge the duplex config. Routers that predate
+/// duplex mode silently wait for a WAV upload, so this also bounds the fallback
+/// delay against them.
+const READY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Socket read timeout used by the worker between checks for outgoing audio
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// How long to wait for the remaining finals after the end of the audio
+pub const FINISH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Sample rate of the PCM frames sent to the server
+const SAMPLE_RATE: u32 = 16000;
+
+type Socket = WebSocket<MaybeTlsStream<TcpStream>>;
+
+/// Hypothesis received from a duplex session
+#[derive(Debug, Clone, PartialEq)]
+pub enum SttHypothesis {
+    /// Unstable text for the audio after the last final; replaces the previous partial
+    Partial(String),
+    /// Committed text, with its span in ms relative to the start of the stream when known
+    Final {
+        text: String,
+        start_ms: Option<u64>,
+        end_ms: Option<u64>,
+    },
+}
+
+/// Message received from the server in duplex mode
+#[derive(Debug, Clone, Deserialize)]
+struct DuplexServerMessage {
+    #[serde(rename = "type")]
+    msg_type: String,
+    #[serde(default)]
+    text: Option<String>,
+    #[serde(default)]
+    start_ms: Option<u64>,
+    #[serde(default)]
+    end_ms: Option<u64>,
+    #[serde(default)]
+    detail: Option<String>,
+}
+
+/// Pipeline thread → worker
+enum Command {
+    Audio(Vec<u8>),
+    End,
+}
+
+/// Worker → pipeline thread
+enum Event {
+    Hypothesis(SttHypothesis),
+    Done,
+    Error(String),
+}
+
+/// An open duplex session for one utterance
+///
+/// Dropping the session without calling [`finish`](Self::finish) aborts it; the
+/// worker closes the socket on its next poll.
+pub struct DuplexSttSession {
+    commands: mpsc::Sender<Command>,
+    events: mpsc::Receiver<Event>,
+    samples_sent: usize,
+}
+
+impl DuplexSttSession {
+    /// Connect, send the duplex config and wait for the server to accept it.
+    pub fn connect(ws_url: &str, alias: &str, postprocess: bool) -> Result<Self, String> {
+        let (mut ws, _response) = tungstenite::connect(ws_url)
+            .map_err(|e| format!("Failed to connect to STT WebSocket: {}", e))?;
+
+        // Language is omitted for the same reason as the whole-utterance path:
+        // auto-detect avoids silence hallucinations.
+        let config = serde_json::json!({
+            "alias": alias,
+            "postprocess": postprocess,
+            "mode": "duplex",
+            "sample_rate": SAMPLE_RATE,
+            "encoding": "pcm_s16le",
+        });
+        ws.send(WsMessage::Text(config.to_string()))
+            .map_err(|e| format!("Failed to send STT config: {}", e))?;
+
+        set_read_timeout(&ws, Some(READY_TIMEOUT))?;
+        loop {
+            match ws.read() {
+                Ok(WsMessage::Text(text)) => {
+                    let msg = parse_message(&text)?;
+                    match msg.msg_type.as_str() {
+                        "ready" => break,
+                        "error" => {
+                            let _ = ws.close(None);
+                            return Err(format!(
+                                "STT error: {}",
+                                msg.detail.unwrap_or_else(|| "Unknown STT error".to_string())
+                            ));
+                        }
+                        other => debug!("Ignoring {} before duplex ready", other),
+                    }
+                }
+                Ok(WsMessage::Close(_)) => {
+                    return Err("WebSocket closed before duplex session was ready".to_string())
+                }
+                Ok(_) => {}
+                Err(e) if is_timeout(&e) => {
+                    let _ = ws.close(None);
+                    return Err("STT server did not accept duplex mode".to_string());
+                }
+                Err(e) => return Err(format!("WebSocket read error: {}", e)),
+            }
+        }
+        set_read_timeout(&ws, Some(POLL_INTERVAL))?;
+        debug!("Duplex STT session ready: alias={}, postprocess={}", alias, postprocess);
+
+        let (command_tx, command_rx) = mpsc::channel();
+        let (event_tx, event_rx) = mpsc::channel();
+        std::thread::Builder::new()
+            .name("duplex-stt".to_string())
+            .spawn(move || run_worker(ws, command_rx, event_tx))
+            .map_err(|e| format!("Failed to spawn duplex STT worker: {}", e))?;
+
+        Ok(Self {
+            commands: command_tx,
+            events: event_rx,
+            samples_sent: 0,
+        })
+    }
+
+    /// Number of samples pushed so far
+    pub fn samples_sent(&self) -> usize {
+        self.samples_sent
+    }
+
+    /// Push 16 kHz samples normalized to [-1.0, 1.0].
+    pub fn send_audio(&mut self, samples: &[f32]) -> Result<(), String> {
+        if samples.is_empty() {
+            return Ok(());
+        }
+        self.commands
+            .send(Command::Audio(encode_pcm16(samples)))
+            .map_err(|_| "Duplex STT session is closed".to_string())?;
+        self.samples_sent += samples.len();
+        Ok(())
+    }
+
+    /// Hypotheses received since the last poll, without blocking.
+    pub fn poll(&mut self) -> Result<Vec<SttHypothesis>, String> {
+        let mut hypotheses = Vec::new();
+        loop {
+            match self.events.try_recv() {
+                Ok(Event::Hypothesis(h)) => hypotheses.push(h),
+                Ok(Event::Error(e)) => return Err(e),
+                // Only sent after End, which `finish` consumes the session for
+                Ok(Event::Done) => return Ok(hypotheses),
+                Err(mpsc::TryRecvError::Empty) => return Ok(hypotheses),
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    return Err("Duplex STT session ended unexpectedly".to_string())
+                }
+            }
+        }
+    }
+
+    /// Mark the end of the audio and collect the remaining hypotheses.
+    pub fn finish(self, timeout: Duration) -> Result<Vec<SttHypothesis>, String> {
+        self.commands
+            .send(Command::End)
+            .map_err(|_| "Duplex STT session is closed".to_string())?;
+
+        let deadline = Instant::now() + timeout;
+        let mut hypotheses = Vec::new();
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match self.events.recv_timeout(remaining) {
+                Ok(Event::Hypothesis(h)) => hypotheses.push(h),
+                Ok(Event::Done) => return Ok(hypotheses),
+                Ok(Event::Error(e)) => return Err(e),
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    return Err("Duplex STT session timed out waiting for finals".to_string())
+                }
+                Err(mpsc::RecvTimeoutError::Disconnected) => {
+                    return Err("Duplex STT session ended unexpectedly".to_string())
+                }
+            }
+        }
+    }
+}
+
+/// Owns the socket: forwards audio and relays hypotheses until the server is done.
+fn run_worker(mut ws: Socket, commands: mpsc::Receiver<Command>, events: mpsc::Sender<Event>) {
+    let mut ended = false;
+    loop {
+        loop {
+            let sent = match commands.try_recv() {
+                Ok(Command::Audio(bytes)) => ws.send(WsMessage::Binary(bytes)),
+                Ok(Command::End) => {
+                    ended = true;
+                    ws.send(WsMessage::Text(r#"{"type":"end"}"#.to_string()))
+                }
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    debug!("Duplex STT session dropped, closing socket");
+                    let _ = ws.close(None);
+                    let _ = ws.flush();
+                    return;
+                }
+            };
+            if let Err(e) = sent {
+                let _ = events.send(Event::Error(format!("Failed to send to STT server: {}", e)));
+                return;
+            }
+        }
+
+        let event = match ws.read() {
+            Ok(WsMessage::Text(text)) => match parse_message(&text) {
+                Ok(msg) => match msg.msg_type.as_str() {
+                    "partial" => Event::Hypothesis(SttHypothesis::Partial(msg.text.unwrap_or_default())),
+                    "final" => Event::Hypothesis(SttHypothesis::Final {
+                        text: msg.text.unwrap_or_default(),
+                        start_ms: msg.start_ms,
+                        end_ms: msg.end_ms,
+                    }),
+                    "done" => Event::Done,
+                    "error" => Event::Error(format!(
+                        "STT error: {}",
+                        msg.detail.unwrap_or_else(|| "Unknown STT error".to_string())
+                    )),
+                    other => {
+                        debug!("Unknown duplex STT message type: {}", other);
+                        continue;
+                    }
+                },
+                Err(e) => Event::Error(e),
+            },
+            Ok(WsMessage::Close(_)) => Event::Error(if ended {
+                "WebSocket closed before the final transcript".to_string()
+            } else {
+                "WebSocket closed by STT server".to_string()
+            }),
+            // Pings are answered by tungstenite on the next read or write
+            Ok(_) => continue,
+            Err(e) if is_timeout(&e) => continue,
+            Err(e) => Event::Error(format!("WebSocket read error: {}", e)),
+        };
+
+        let finished = !matches!(event, Event::Hypothesis(_));
+        if events.send(event).is_err() || finished {
+            let _ = ws.close(None);
+            let _ = ws.flush();
+            return;
+        }
+    }
+}
+
+fn parse_message(text: &str) -> Result<DuplexServerMessage, String> {
+    serde_json::from_str(text).map_err(|e| format!("Failed to parse STT message: {} (raw: {})", e, text))
+}
+
+fn is_timeout(err: &tungstenite::Error) -> bool {
+    matches!(
+        err,
+        tungstenite::Error::Io(e)
+            if matches!(e.kind(), std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut)
+    )
+}
+
+fn set_read_timeout(ws: &Socket, timeout: Option<Duration>) -> Result<(), String> {
+    let result = match ws.get_ref() {
+        MaybeTlsStream::Plain(stream) => stream.set_read_timeout(timeout),
+        MaybeTlsStream::NativeTls(stream) => stream.get_ref().set_read_timeout(timeout),
+        _ => Ok(()),
+    };
+    result.map_err(|e| format!("Failed to configure STT socket: {}", e))
+}
+
+/// Convert f32 samples to little-endian i16, clamped the same way as `encode_wav`
+fn encode_pcm16(samples: &[f32]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(samples.len() * 2);
+    for &sample in samples {
+        let i16_sample = (sample.clamp(-1.0, 1.0) * 32767.0) as i16;
+        bytes.extend_from_slice(&i16_sample.to_le_bytes());
+    }
+    bytes
+}
+
+/// Committed text for one utterance with its span relative to the stream start
+#[derive(Debug, Clone)]
+struct FinalSpan {
+    text: String,
+    start_ms: Option<u64>,
+    end_ms: Option<u64>,
+}
+
+/// Folds the partial and final hypotheses of one utterance into segments
+#[derive(Debug, Clone)]
+pub struct HypothesisReconciler {
+    start_ms: u64,
+    finals: Vec<FinalSpan>,
+    partial: String,
+}
+
+impl HypothesisReconciler {
+    /// `start_ms` is the absolute time of the first sample sent to the session
+    pub fn new(start_ms: u64) -> Self {
+        Self {
+            start_ms,
+            finals: Vec::new(),
+            partial: String::new(),
+        }
+    }
+
+    /// Apply a hypothesis. A final commits its text and clears the partial.
+    pub fn apply(&mut self, hypothesis: SttHypothesis) {
+        match hypothesis {
+            SttHypothesis::Partial(text) => self.partial = text.trim().to_string(),
+            SttHypothesis::Final { text, start_ms, end_ms } => {
+                self.partial.clear();
+                let text = text.trim();
+                if !text.is_empty() {
+                    self.finals.push(FinalSpan {
+                        text: text.to_string(),
+                        start_ms,
+                        end_ms,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Committed text followed by the current partial, for live display
+    pub fn display_text(&self) -> String {
+        self.finals
+            .iter()
+            .map(|f| f.text.as_str())
+            .chain((!self.partial.is_empty()).then_some(self.partial.as_str()))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// One segment per final, timed on the audio clock and clamped to the utterance.
+    ///
+    /// Finals without timestamps fill the gap between their neighbours. A partial
+    /// left over when the session ended is kept as the last segment rather than
+    /// dropping speech the server did not get round to committing.
+    pub fn into_segments(mut self, end_ms: u64) -> Vec<Segment> {
+        if !self.partial.is_empty() {
+            warn!("Duplex STT session ended with an uncommitted partial, keeping it");
+            self.finals.push(FinalSpan {
+                text: std::mem::take(&mut self.partial),
+                start_ms: None,
+                end_ms: None,
+            });
+        }
+
+        let end_ms = end_ms.max(self.start_ms);
+        let mut segments: Vec<Segment> = Vec::with_capacity(self.finals.len());
+        let mut cursor = self.start_ms;
+        for (i, span) in self.finals.iter().enumerate() {
+            let start = span
+                .start_ms
+                .map(|ms| self.start_ms + ms)
+                .unwrap_or(cursor)
+                .clamp(cursor, end_ms);
+            let next_start = self.finals[i + 1..]
+                .iter()
+                .find_map(|f| f.start_ms)
+                .map(|ms| self.start_ms + ms);
+            let end = span
+                .end_ms
+                .map(|ms| self.start_ms + ms)
+                .or(next_start)
+                .unwrap_or(end_ms)
+                .clamp(start, end_ms);
+            segments.push(Segment::new(start, end, span.text.clone()));
+            cursor = end;
+        }
+        segments
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    /// Scripted STT Router: acknowledges duplex mode, sends a partial for every
+    /// audio frame and two timed finals once the client ends the audio.
+    fn spawn_mock_server(accept_duplex: bool) -> (String, std::thread::JoinHandle<usize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("ws://{}/v1/audio/stream", listener.local_addr().unwrap());
+        let handle = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut ws = tungstenite::accept(stream).unwrap();
+            let config: serde_json::Value = match ws.read().unwrap() {
+                WsMessage::Text(text) => serde_json::from_str(&text).unwrap(),
+                other => panic!("expected config, got {:?}", other),
+            };
+            assert_eq!(config["mode"], "duplex");
+            assert_eq!(config["encoding"], "pcm_s16le");
+            if !accept_duplex {
+                ws.send(WsMessage::Text(r#"{"type":"error","detail":"Unknown mode"}"#.into()))
+                    .unwrap();
+                return 0;
+            }
+            ws.send(WsMessage::Text(r#"{"type":"ready"}"#.into())).unwrap();
+
+            let mut received = 0usize;
+            let mut frames = 0usize;
+            loop {
+                match ws.read().unwrap() {
+                    WsMessage::Binary(bytes) => {
+                        received += bytes.len() / 2;
+                        frames += 1;
+                        let partial = serde_json::json!({
+                            "type": "partial",
+                            "text": format!("partial {}", frames),
+                        });
+                        ws.send(WsMessage::Text(partial.to_string())).unwrap();
+                    }
+                    WsMessage::Text(text) => {
+                        assert_eq!(text, r#"{"type":"end"}"#);
+                        break;
+                    }
+                    other => panic!("unexpected message {:?}", other),
+                }
+            }
+            for msg in [
+                r#"{"type":"final","text":" The patient reports chest pain. ","start_ms":0,"end_ms":900}"#,
+                r#"{"type":"final","text":"Started yesterday.","start_ms":1100}"#,
+                r#"{"type":"done"}"#,
+            ] {
+                ws.send(WsMessage::Text(msg.into())).unwrap();
+            }
+            let _ = ws.read();
+            received
+        });
+        (url, handle)
+    }
+
+    fn wait_for_partial(session: &mut DuplexSttSession) -> Vec<SttHypothesis> {
+        let deadline = Instant::now() + Duration::from_secs(2);
+        loop {
+            let hypotheses = session.poll().unwrap();
+            if !hypotheses.is_empty() || Instant::now() > deadline {
+                return hypotheses;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn test_duplex_session_streams_frames_and_reconciles_finals() {
+        let (url, server) = spawn_mock_server(true);
+        let mut session = DuplexSttSession::connect(&url, "medical-streaming", true).unwrap();
+        let mut reconciler = HypothesisReconciler::new(5_000);
+
+        // Partials arrive while the utterance is still open
+        session.send_audio(&[0.1; 512]).unwrap();
+        let hypotheses = wait_for_partial(&mut session);
+        assert_eq!(hypotheses, vec![SttHypothesis::Partial("partial 1".into())]);
+        for h in hypotheses {
+            reconciler.apply(h);
+        }
+        assert_eq!(reconciler.display_text(), "partial 1");
+
+        session.send_audio(&[0.1; 512]).unwrap();
+        session.send_audio(&[0.1; 100]).unwrap();
+        assert_eq!(session.samples_sent(), 1124);
+
+        for h in session.finish(Duration::from_secs(5)).unwrap() {
+            reconciler.apply(h);
+        }
+        assert_eq!(
+            reconciler.display_text(),
+            "The patient reports chest pain. Started yesterday."
+        );
+
+        let segments = reconciler.into_segments(7_000);
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].text, "The patient reports chest pain.");
+        assert_eq!((segments[0].start_ms, segments[0].end_ms), (5_000, 5_900));
+        assert_eq!(segments[1].text, "Started yesterday.");
+        assert_eq!((segments[1].start_ms, segments[1].end_ms), (6_100, 7_000));
+
+        assert_eq!(server.join().unwrap(), 1124);
+    }
+
+    #[test]
+    fn test_duplex_session_rejected_by_server() {
+        let (url, server) = spawn_mock_server(false);
+        let err = DuplexSttSession::connect(&url, "medical-streaming", true)
+            .err()
+            .unwrap();
+        assert!(err.contains("Unknown mode"), "{}", err);
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_duplex_session_times_out_on_legacy_server() {
+        // A router without duplex support reads the config and waits for a WAV
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("ws://{}/v1/audio/stream", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut ws = tungstenite::accept(stream).unwrap();
+            let _ = ws.read();
+            let _ = ws.read();
+        });
+        let err = DuplexSttSession::connect(&url, "medical-streaming", true)
+            .err()
+            .unwrap();
+        assert!(err.contains("did not accept duplex mode"), "{}", err);
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_reconciler_partial_replaces_previous() {
+        let mut r = HypothesisReconciler::new(0);
+        r.apply(SttHypothesis::Partial("the".into()));
+        r.apply(SttHypothesis::Partial("the patient".into()));
+        assert_eq!(r.display_text(), "the patient");
+
+        r.apply(SttHypothesis::Final {
+            text: "The patient.".into(),
+            start_ms: None,
+            end_ms: None,
+        });
+        r.apply(SttHypothesis::Partial("has a".into()));
+        assert_eq!(r.display_text(), "The patient. has a");
+    }
+
+    #[test]
+    fn test_reconciler_untimed_finals_share_the_utterance() {
+        let mut r = HypothesisReconciler::new(1_000);
+        r.apply(SttHypothesis::Final {
+            text: "First.".into(),
+            start_ms: None,
+            end_ms: None,
+        });
+        r.apply(SttHypothesis::Final {
+            text: "Second.".into(),
+            start_ms: Some(800),
+            end_ms: None,
+        });
+        let segments = r.into_segments(2_500);
+        assert_eq!((segments[0].start_ms, segments[0].end_ms), (1_000, 1_800));
+        assert_eq!((segments[1].start_ms, segments[1].end_ms), (1_800, 2_500));
+    }
+
+    #[test]
+    fn test_reconciler_clamps_and_keeps_trailing_partial() {
+        let mut r = HypothesisReconciler::new(1_000);
+        r.apply(SttHypothesis::Final {
+            text: "Out of range.".into(),
+            start_ms: Some(0),
+            end_ms: Some(10_000),
+        });
+        r.apply(SttHypothesis::Final {
+            text: "   ".into(),
+            start_ms: None,
+            end_ms: None,
+        });
+        r.apply(SttHypothesis::Partial("trailing words".into()));
+        let segments = r.into_segments(2_000);
+        assert_eq!(segments.len(), 2);
+        assert_eq!((segments[0].start_ms, segments[0].end_ms), (1_000, 2_000));
+        assert_eq!(segments[1].text, "trailing words");
+        assert_eq!((segments[1].start_ms, segments[1].end_ms), (2_000, 2_000));
+    }
+
+    #[test]
+    fn test_reconciler_without_hypotheses_has_no_segments() {
+        assert!(HypothesisReconciler::new(0).into_segments(1_000).is_empty());
+    }
+
+    #[test]
+    fn test_encode_pcm16() {
+        let bytes = encode_pcm16(&[0.0, 1.0, -2.0]);
+        let samples: Vec<i16> = bytes
+            .chunks(2)
+            .map(|c| i16::from_le_bytes([c[0], c[1]]))
+            .collect();
+        assert_eq!(samples, vec![0, 32767, -32767]);
+    }
+}
@@ -0,0 +1,2592 @@
+//! EMR backend abstraction.
+//!
+//! Every FHIR R4 operation the app performs against an EMR — patient search
+//! and upsert, encounter lifecycle, document upload, history fetch — is
+//! written once, as a provided method on [`EmrBackend`]. Implementors only
+//! supply auth and transport:
+//!
+//! - `MedplumClient` (`medplum.rs`): OAuth PKCE or profile-service proxy
+//!   tokens, FHIR at `{server}/fhir/R4`.
+//! - `SmartBackendClient` (`smart_backend.rs`): SMART Backend Services
+//!   (client_credentials grant with a signed JWT client assertion) against
+//!   any FHIR R4 server, e.g. HAPI.
+//!
+//! `Settings::emr_backend` selects the active one; see
+//! `commands::active_emr_backend`. Errors use `MedplumError` for both, which
+//! is FHIR-level apart from its name.
+
+use crate::medplum::{
+    Encounter, EncounterDetails, EncounterSummary, MedplumError, MedplumSessionSync, Patient,
+};
+use async_trait::async_trait;
+use base64::Engine;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use sha2::{Digest, Sha256};
+use tracing::info;
+
+/// `Settings::emr_backend` value for Medplum (the default).
+pub const EMR_BACKEND_MEDPLUM: &str = "medplum";
+/// `Settings::emr_backend` value for a SMART Backend Services FHIR server.
+pub const EMR_BACKEND_SMART: &str = "smart_backend";
+/// Every accepted `Settings::emr_backend` value.
+pub const EMR_BACKENDS: &[&str] = &[EMR_BACKEND_MEDPLUM, EMR_BACKEND_SMART];
+
+#[async_trait]
+pub trait EmrBackend: Send + Sync {
+    /// Short backend name for logs (`"medplum"`, `"smart_backend"`).
+    fn backend_name(&self) -> &'static str;
+
+    /// HTTP client carrying the backend's timeouts.
+    fn http(&self) -> &reqwest::Client;
+
+    /// FHIR R4 base URL without a trailing slash, e.g. `https://emr/fhir/R4`.
+    fn fhir_base(&self) -> &str;
+
+    /// A bearer token valid for the next request, refreshed or re-minted as
+    /// needed.
+    async fn access_token(&self) -> Result<String, MedplumError>;
+
+    /// Whether a request made now would be authorized.
+    async fn is_authenticated(&self) -> bool;
+
+    /// Practitioner that new Encounters are attributed to.
+    async fn practitioner_id(&self) -> Option<String>;
+
+    /// Attribute subsequent Encounters to this Practitioner.
+    async fn set_practitioner_id(&self, practitioner_id: &str);
+
+    /// Search for patients by name or MRN
+    async fn search_patients(&self, query: &str) -> Result<Vec<Patient>, MedplumError> {
+        let token = self.access_token().await?;
+
+        let response = self
+            .http()
+            .get(format!(
+                "{}/Patient?name:contains={}&_count=20",
+                self.fhir_base(),
+                urlencoding::encode(query)
+            ))
+            .bearer_auth(&token)
+            .send()
+            .await?;
+
+        let bundle: serde_json::Value = handle_response(response).await?;
+
+        let mut patients = Vec::new();
+        if let Some(entries) = bundle["entry"].as_array() {
+            for entry in entries {
+                let resource = &entry["resource"];
+                if let Some(id) = resource["id"].as_str() {
+                    let name = extract_patient_name(resource);
+                    let mrn = resource["identifier"]
+                        .as_array()
+                        .and_then(|ids| {
+                            ids.iter().find(|id| {
+                                id["system"].as_str() == Some("http://hospital.example.org/mrn")
+                            })
+                        })
+                        .and_then(|id| id["value"].as_str())
+                        .map(|s| s.to_string());
+
+                    patients.push(Patient {
+                        id: id.to_string(),
+                        name,
+                        mrn,
+                        birth_date: resource["birthDate"].as_str().map(|s| s.to_string()),
+                    });
+                }
+            }
+        }
+
+        Ok(patients)
+    }
+
+    /// Create a placeholder patient for storing scribe sessions
+    /// This creates a patient record that can be easily identified as app-created
+    async fn create_placeholder_patient(&self) -> Result<Patient, MedplumError> {
+        let token = self.access_token().await?;
+        let practitioner_id = self
+            .practitioner_id()
+            .await
+            .ok_or(MedplumError::NotAuthenticated)?;
+
+        // Generate unique identifier for this session
+        let session_uuid = uuid::Uuid::new_v4().to_string();
+        let timestamp = Utc::now().format("%Y-%m-%d %H:%M").to_string();
+
+        // Create a placeholder patient with identifiable metadata
+        let patient = serde_json::json!({
+            "resourceType": "Patient",
+            "identifier": [{
+                "system": "urn:fabricscribe:session",
+                "value": session_uuid
+            }],
+            "name": [{
+                "use": "official",
+                "family": "Session",
+                "given": ["Scribe"],
+                "text": format!("Scribe Session - {}", timestamp)
+            }],
+            "meta": {
+                "tag": [{
+                    "system": "urn:fabricscribe",
+                    "code": "placeholder-patient"
+                }, {
+                    "system": "urn:fabricscribe:practitioner",
+                    "code": practitioner_id
+                }]
+            },
+            "generalPractitioner": [{
+                "reference": format!("Practitioner/{}", practitioner_id)
+            }],
+            "active": true
+        });
+
+        let response = self
+            .http()
+            .post(format!("{}/Patient", self.fhir_base()))
+            .bearer_auth(&token)
+            .json(&patient)
+            .send()
+            .await?;
+
+        let created: serde_json::Value = handle_response(response).await?;
+
+        let patient_id = created["id"]
+            .as_str()
+            .ok_or_else(|| MedplumError::ValidationError("No patient ID in response".to_string()))?
+            .to_string();
+
+        info!("Created placeholder patient: {} for practitioner: {}", patient_id, practitioner_id);
+
+        Ok(Patient {
+            id: patient_id,
+            name: format!("Scribe Session - {}", timestamp),
+            mrn: Some(session_uuid),
+            birth_date: None,
+        })
+    }
+
+    /// Create a new encounter for a patient
+    async fn create_encounter(&self, patient_id: &str) -> Result<Encounter, MedplumError> {
+        validate_fhir_id(patient_id)?;
+        let token = self.access_token().await?;
+        let practitioner_id = self
+            .practitioner_id()
+            .await
+            .ok_or(MedplumError::NotAuthenticated)?;
+
+        // Generate unique encounter identifier
+        let encounter_uuid = uuid::Uuid::new_v4().to_string();
+        let start_time = Utc::now().to_rfc3339();
+
+        // First, get patient name
+        let patient_response = self
+            .http()
+            .get(format!("{}/Patient/{}", self.fhir_base(), patient_id))
+            .bearer_auth(&token)
+            .send()
+            .await?;
+
+        let patient_resource: serde_json::Value = handle_response(patient_response).await?;
+        let patient_name = extract_patient_name(&patient_resource);
+
+        // Create encounter resource
+        let encounter = serde_json::json!({
+            "resourceType": "Encounter",
+            "identifier": [{
+                "system": "urn:fabricscribe:encounter",
+                "value": encounter_uuid
+            }],
+            "status": "in-progress",
+            "class": {
+                "system": "http://terminology.hl7.org/CodeSystem/v3-ActCode",
+                "code": "AMB",
+                "display": "ambulatory"
+            },
+            "subject": {
+                "reference": format!("Patient/{}", patient_id)
+            },
+            "participant": [{
+                "type": [{
+                    "coding": [{
+                        "system": "http://terminology.hl7.org/CodeSystem/v3-ParticipationType",
+                        "code": "PPRF",
+                        "display": "primary performer"
+                    }]
+                }],
+                "individual": {
+                    "reference": format!("Practitioner/{}", practitioner_id)
+                }
+            }],
+            "period": {
+                "start": start_time
+            },
+            "meta": {
+                "tag": [{
+                    "system": "urn:fabricscribe",
+                    "code": "scribe-session"
+                }]
+            }
+        });
+
+        let response = self
+            .http()
+            .post(format!("{}/Encounter", self.fhir_base()))
+            .bearer_auth(&token)
+            .json(&encounter)
+            .send()
+            .await?;
+
+        let created: serde_json::Value = handle_response(response).await?;
+
+        // Use the server-returned ID, not the pre-generated UUID
+        let fhir_id = created["id"].as_str().unwrap_or(&encounter_uuid).to_string();
+        tracing::info!("Created encounter with FHIR ID: {} (local UUID was: {})", fhir_id, encounter_uuid);
+
+        Ok(Encounter {
+            id: fhir_id,
+            patient_id: patient_id.to_string(),
+            patient_name,
+            status: "in-progress".to_string(),
+            start_time,
+            end_time: None,
+        })
+    }
+
+    /// Upload transcript to an encounter
+    async fn upload_transcript(
+        &self,
+        encounter_id: &str,
+        encounter_fhir_id: &str,
+        patient_id: &str,
+        transcript: &str,
+    ) -> Result<String, MedplumError> {
+        validate_fhir_id(encounter_fhir_id)?;
+        validate_fhir_id(patient_id)?;
+        let token = self.access_token().await?;
+
+        let doc_ref = serde_json::json!({
+            "resourceType": "DocumentReference",
+            "identifier": [{
+                "system": "urn:fabricscribe:encounter",
+                "value": encounter_id
+            }],
+            "status": "current",
+            "type": {
+                "coding": [{
+                    "system": "http://loinc.org",
+                    "code": "75476-2",
+                    "display": "Transcript"
+                }]
+            },
+            "category": [{
+                "coding": [{
+                    "system": "urn:fabricscribe",
+                    "code": "transcription"
+                }]
+            }],
+            "subject": {
+                "reference": format!("Patient/{}", patient_id)
+            },
+            "context": {
+                "encounter": [{
+                    "reference": format!("Encounter/{}", encounter_fhir_id)
+                }]
+            },
+            "content": [{
+                "attachment": {
+                    "contentType": "text/plain",
+                    "data": base64::engine::general_purpose::STANDARD.encode(transcript)
+                }
+            }],
+            "date": Utc::now().to_rfc3339()
+        });
+
+        let response = self
+            .http()
+            .post(format!("{}/DocumentReference", self.fhir_base()))
+            .bearer_auth(&token)
+            .json(&doc_ref)
+            .send()
+            .await?;
+
+        let created: serde_json::Value = handle_response(response).await?;
+        Ok(created["id"].as_str().unwrap_or("").to_string())
+    }
+
+    /// Upload SOAP note to an encounter
+    async fn upload_soap_note(
+        &self,
+        encounter_id: &str,
+        encounter_fhir_id: &str,
+        patient_id: &str,
+        soap_note: &str,
+    ) -> Result<String, MedplumError> {
+        validate_fhir_id(encounter_fhir_id)?;
+        validate_fhir_id(patient_id)?;
+        let token = self.access_token().await?;
+
+        let doc_ref = serde_json::json!({
+            "resourceType": "DocumentReference",
+            "identifier": [{
+                "system": "urn:fabricscribe:encounter",
+                "value": encounter_id
+            }],
+            "status": "current",
+            "type": {
+                "coding": [{
+                    "system": "http://loinc.org",
+                    "code": "11506-3",
+                    "display": "Progress note"
+                }]
+            },
+            "category": [{
+                "coding": [{
+                    "system": "urn:fabricscribe",
+                    "code": "soap-note"
+                }]
+            }],
+            "subject": {
+                "reference": format!("Patient/{}", patient_id)
+            },
+            "context": {
+                "encounter": [{
+                    "reference": format!("Encounter/{}", encounter_fhir_id)
+                }]
+            },
+            "content": [{
+                "attachment": {
+                    "contentType": "text/plain",
+                    "data": base64::engine::general_purpose::STANDARD.encode(soap_note)
+                }
+            }],
+            "date": Utc::now().to_rfc3339()
+        });
+
+        let response = self
+            .http()
+            .post(format!("{}/DocumentReference", self.fhir_base()))
+            .bearer_auth(&token)
+            .json(&doc_ref)
+            .send()
+            .await?;
+
+        let created: serde_json::Value = handle_response(response).await?;
+        Ok(created["id"].as_str().unwrap_or("").to_string())
+    }
+
+    /// Upload audio recording as Binary + Media resources
+    async fn upload_audio(
+        &self,
+        encounter_id: &str,
+        encounter_fhir_id: &str,
+        patient_id: &str,
+        audio_data: &[u8],
+        content_type: &str,
+        duration_seconds: Option<u64>,
+    ) -> Result<String, MedplumError> {
+        validate_fhir_id(encounter_fhir_id)?;
+        validate_fhir_id(patient_id)?;
+        let token = self.access_token().await?;
+
+        // Step 1: Upload binary audio data
+        let binary_response = self
+            .http()
+            .post(format!("{}/Binary", self.fhir_base()))
+            .bearer_auth(&token)
+            .header("Content-Type", content_type)
+            .body(audio_data.to_vec())
+            .send()
+            .await?;
+
+        let binary: serde_json::Value = handle_response(binary_response).await?;
+        let binary_id = binary["id"].as_str().unwrap_or("");
+
+        // Step 2: Create Media resource linking to Binary
+        let mut media = serde_json::json!({
+            "resourceType": "Media",
+            "identifier": [{
+                "system": "urn:fabricscribe:encounter",
+                "value": encounter_id
+            }],
+            "status": "completed",
+            "type": {
+                "coding": [{
+                    "system": "http://terminology.hl7.org/CodeSystem/media-type",
+                    "code": "audio",
+                    "display": "Audio"
+                }]
+            },
+            "subject": {
+                "reference": format!("Patient/{}", patient_id)
+            },
+            "encounter": {
+                "reference": format!("Encounter/{}", encounter_fhir_id)
+            },
+            "content": {
+                "contentType": content_type,
+                "url": format!("Binary/{}", binary_id)
+            },
+            "meta": {
+                "tag": [{
+                    "system": "urn:fabricscribe",
+                    "code": "scribe-session"
+                }]
+            }
+        });
+
+        if let Some(duration) = duration_seconds {
+            media["duration"] = serde_json::json!(duration);
+        }
+
+        let media_response = self
+            .http()
+            .post(format!("{}/Media", self.fhir_base()))
+            .bearer_auth(&token)
+            .json(&media)
+            .send()
+            .await?;
+
+        let created: serde_json::Value = handle_response(media_response).await?;
+        Ok(created["id"].as_str().unwrap_or("").to_string())
+    }
+
+    /// Complete an encounter (set status to finished)
+    async fn complete_encounter(&self, encounter_fhir_id: &str) -> Result<(), MedplumError> {
+        validate_fhir_id(encounter_fhir_id)?;
+        let token = self.access_token().await?;
+
+        // Get current encounter
+        let get_response = self
+            .http()
+            .get(format!("{}/Encounter/{}", self.fhir_base(), encounter_fhir_id))
+            .bearer_auth(&token)
+            .send()
+            .await?;
+
+        let mut encounter: serde_json::Value = handle_response(get_response).await?;
+
+        // Update status and end time
+        encounter["status"] = serde_json::json!("finished");
+        encounter["period"]["end"] = serde_json::json!(Utc::now().to_rfc3339());
+
+        // PUT the updated encounter
+        let put_response = self
+            .http()
+            .put(format!("{}/Encounter/{}", self.fhir_base(), encounter_fhir_id))
+            .bearer_auth(&token)
+            .json(&encounter)
+            .send()
+            .await?;
+
+        handle_response::<serde_json::Value>(put_response).await?;
+        Ok(())
+    }
+
+    /// Get encounter history for the current practitioner
+    async fn get_encounter_history(
+        &self,
+        start_date: Option<&str>,
+        end_date: Option<&str>,
+    ) -> Result<Vec<EncounterSummary>, MedplumError> {
+        let token = self.access_token().await?;
+        let practitioner_id = self
+            .practitioner_id()
+            .await
+            .ok_or(MedplumError::NotAuthenticated)?;
+
+        // Build query URL. `_include=Encounter:subject` pulls the referenced
+        // Patient resources into the same bundle so we can avoid N+1 HTTP GETs
+        // for patient names. `_count` is per-page; full traversal happens via
+        // Bundle.link[rel=next] in `fetch_all_pages`.
+        let mut url = format!(
+            "{}/Encounter?_sort=-date&_count=100&_include=Encounter:subject",
+            self.fhir_base()
+        );
+
+        if let Some(start) = start_date {
+            // Start date is inclusive - query for >= start day midnight UTC
+            url.push_str(&format!("&date=ge{}T00:00:00Z", start));
+        }
+        if let Some(end) = end_date {
+            // End date is inclusive - query for < next day midnight UTC
+            if let Ok(date) = NaiveDate::parse_from_str(end, "%Y-%m-%d") {
+                if let Some(next_day) = date.checked_add_signed(Duration::days(1)) {
+                    url.push_str(&format!("&date=lt{}T00:00:00Z", next_day.format("%Y-%m-%d")));
+                } else {
+                    url.push_str(&format!("&date=le{}T23:59:59Z", end));
+                }
+            } else {
+                // Fallback if parsing fails - use end of day
+                url.push_str(&format!("&date=le{}T23:59:59Z", end));
+            }
+        }
+
+        let bundle = fetch_all_pages(self.http(), &token, &url).await?;
+        let included_patients = extract_patients_from_bundle(&bundle);
+
+        let mut encounters = Vec::new();
+        let practitioner_ref = format!("Practitioner/{}", practitioner_id);
+
+        if let Some(entries) = bundle["entry"].as_array() {
+            for entry in entries {
+                let resource = &entry["resource"];
+
+                // Skip non-Encounter resources (the bundle also includes
+                // Patient resources from `_include=Encounter:subject`).
+                if resource["resourceType"].as_str() != Some("Encounter") {
+                    continue;
+                }
+
+                // Filter by practitioner (since we removed participant from URL query)
+                let is_our_encounter = resource["participant"]
+                    .as_array()
+                    .map(|participants| {
+                        participants.iter().any(|p| {
+                            p["individual"]["reference"].as_str() == Some(&practitioner_ref)
+                        })
+                    })
+                    .unwrap_or(false);
+
+                if !is_our_encounter {
+                    continue;
+                }
+
+                let fhir_id = resource["id"].as_str().unwrap_or("").to_string();
+                let encounter_id = resource["identifier"]
+                    .as_array()
+                    .and_then(|ids| ids.first())
+                    .and_then(|id| id["value"].as_str())
+                    .unwrap_or("")
+                    .to_string();
+
+                let start_time = resource["period"]["start"].as_str().unwrap_or("");
+                let end_time = resource["period"]["end"].as_str();
+
+                // Calculate duration if both times exist
+                let duration_minutes = if let (Ok(start), Some(end_str)) = (
+                    DateTime::parse_from_rfc3339(start_time),
+                    end_time,
+                ) {
+                    if let Ok(end) = DateTime::parse_from_rfc3339(end_str) {
+                        Some((end - start).num_minutes())
+                    } else {
+                        None
+                    }
+                } else {
+                    None
+                };
+
+                // Resolve via the _included Patient resource; falls back to
+                // `subject.display` then "Unknown".
+                let patient_name = resource["subject"]["reference"]
+                    .as_str()
+                    .and_then(|r| r.strip_prefix("Patient/"))
+                    .and_then(|id| included_patients.get(id))
+                    .map(extract_patient_name)
+                    .or_else(|| {
+                        resource["subject"]["display"]
+                            .as_str()
+                            .map(|s| s.to_string())
+                    })
+                    .unwrap_or_else(|| "Unknown".to_string());
+
+                encounters.push(EncounterSummary {
+                    id: encounter_id,
+                    fhir_id,
+                    patient_name,
+                    date: start_time.to_string(),
+                    duration_minutes,
+                    has_soap_note: false, // Updated below
+                    has_audio: false,     // Updated below
+                });
+            }
+        }
+
+        // Batch query for SOAP notes and audio for all encounters
+        if !encounters.is_empty() {
+            let encounter_fhir_ids: Vec<&str> =
+                encounters.iter().map(|e| e.fhir_id.as_str()).collect();
+
+            // Query SOAP notes (DocumentReference with category=soap-note)
+            let soap_encounter_ids = encounters_with_soap_notes(self.http(), self.fhir_base(), &token, &encounter_fhir_ids)
+                .await
+                .unwrap_or_default();
+
+            // Query audio (Media resources)
+            let audio_encounter_ids = encounters_with_audio(self.http(), self.fhir_base(), &token, &encounter_fhir_ids)
+                .await
+                .unwrap_or_default();
+
+            // Update encounter summaries with document indicators
+            for encounter in &mut encounters {
+                encounter.has_soap_note = soap_encounter_ids.contains(&encounter.fhir_id);
+                encounter.has_audio = audio_encounter_ids.contains(&encounter.fhir_id);
+            }
+        }
+
+        Ok(encounters)
+    }
+
+    /// Get detailed encounter data including documents
+    async fn get_encounter_details(&self, encounter_id: &str) -> Result<EncounterDetails, MedplumError> {
+        validate_fhir_id(encounter_id)?;
+        let token = self.access_token().await?;
+
+        // Fetch encounter directly by FHIR ID
+        let encounter_response = self
+            .http()
+            .get(format!(
+                "{}/Encounter/{}",
+                self.fhir_base(), encounter_id
+            ))
+            .bearer_auth(&token)
+            .send()
+            .await?;
+
+        let encounter: serde_json::Value = handle_response(encounter_response).await?;
+
+        let fhir_id = encounter["id"].as_str().unwrap_or("").to_string();
+        let start_time = encounter["period"]["start"].as_str().unwrap_or("");
+        let end_time = encounter["period"]["end"].as_str();
+
+        let duration_minutes = if let (Ok(start), Some(end_str)) = (
+            DateTime::parse_from_rfc3339(start_time),
+            end_time,
+        ) {
+            if let Ok(end) = DateTime::parse_from_rfc3339(end_str) {
+                Some((end - start).num_minutes())
+            } else {
+                None
+            }
+        } else {
+            None
+        };
+
+        // Extract patient ID from subject reference (e.g., "Patient/123" -> "123")
+        let patient_id = encounter["subject"]["reference"]
+            .as_str()
+            .and_then(|r| r.strip_prefix("Patient/"))
+            .map(|id| id.to_string());
+
+        let patient_name = patient_name_from_encounter(self.http(), self.fhir_base(), &token, &encounter)
+            .await
+            .unwrap_or_else(|_| "Unknown".to_string());
+
+        // Fetch documents by encounter reference
+        let docs_response = self
+            .http()
+            .get(format!(
+                "{}/DocumentReference?encounter=Encounter/{}",
+                self.fhir_base(), encounter_id
+            ))
+            .bearer_auth(&token)
+            .send()
+            .await?;
+
+        let docs_bundle: serde_json::Value = handle_response(docs_response).await?;
+
+        let mut transcript = None;
+        let mut soap_note = None;
+        let mut session_info = None;
+
+        if let Some(entries) = docs_bundle["entry"].as_array() {
+            for entry in entries {
+                let resource = &entry["resource"];
+                let category = resource["category"]
+                    .as_array()
+                    .and_then(|c| c.first())
+                    .and_then(|c| c["coding"].as_array())
+                    .and_then(|c| c.first())
+                    .and_then(|c| c["code"].as_str())
+                    .unwrap_or("");
+
+                let content_data = resource["content"]
+                    .as_array()
+                    .and_then(|c| c.first())
+                    .and_then(|c| c["attachment"]["data"].as_str())
+                    .and_then(|d| {
+                        base64::engine::general_purpose::STANDARD
+                            .decode(d)
+                            .ok()
+                            .and_then(|bytes| String::from_utf8(bytes).ok())
+                    });
+
+                match category {
+                    "transcription" => transcript = content_data,
+                    "soap-note" => soap_note = content_data,
+                    "session-info" => session_info = content_data,
+                    _ => {}
+                }
+            }
+        }
+
+        // Fetch media for audio URL by encounter reference
+        let media_response = self
+            .http()
+            .get(format!(
+                "{}/Media?encounter=Encounter/{}",
+                self.fhir_base(), encounter_id
+            ))
+            .bearer_auth(&token)
+            .send()
+            .await?;
+
+        let media_bundle: serde_json::Value = handle_response(media_response).await?;
+        let audio_url = media_bundle["entry"]
+            .as_array()
+            .and_then(|e| e.first())
+            .and_then(|e| e["resource"]["content"]["url"].as_str())
+            .map(|url| format!("{}/{}", self.fhir_base(), url));
+
+        Ok(EncounterDetails {
+            summary: EncounterSummary {
+                id: encounter_id.to_string(),
+                fhir_id,
+                patient_name,
+                date: start_time.to_string(),
+                duration_minutes,
+                has_soap_note: soap_note.is_some(),
+                has_audio: audio_url.is_some(),
+            },
+            transcript,
+            soap_note,
+            audio_url,
+            session_info,
+            patient_id,
+        })
+    }
+
+    /// Fetch raw binary data (e.g., audio files) from Medplum
+    async fn get_audio_data(&self, binary_id: &str) -> Result<Vec<u8>, MedplumError> {
+        validate_fhir_id(binary_id)?;
+        let token = self.access_token().await?;
+
+        let response = self
+            .http()
+            .get(format!("{}/Binary/{}", self.fhir_base(), binary_id))
+            .bearer_auth(&token)
+            .header("Accept", "application/octet-stream")
+            .send()
+            .await?;
+
+        match response.status() {
+            status if status.is_success() => {
+                Ok(response.bytes().await?.to_vec())
+            }
+            reqwest::StatusCode::UNAUTHORIZED => {
+                Err(MedplumError::TokenExpired)
+            }
+            reqwest::StatusCode::FORBIDDEN => {
+                let body = response.text().await.unwrap_or_default();
+                Err(MedplumError::AccessDenied(truncate_error_body(&body)))
+            }
+            reqwest::StatusCode::NOT_FOUND => {
+                Err(MedplumError::NotFound(format!("Binary/{}", binary_id)))
+            }
+            _ => {
+                let body = response.text().await.unwrap_or_default();
+                Err(MedplumError::AuthError(format!(
+                    "Failed to fetch audio: {}",
+                    truncate_error_body(&body)
+                )))
+            }
+        }
+    }
+
+    /// Find an existing Patient by name + DOB. Matches strictly on
+    /// normalized name AND birthDate (either both match → hit, else miss).
+    /// Returns the Medplum FHIR Patient ID.
+    ///
+    /// Called by `sync_continuous_session` before building its transaction:
+    /// a hit is referenced directly, a miss becomes a conditional create.
+    async fn find_patient_by_name_dob(
+        &self,
+        name: &str,
+        dob: &str,
+    ) -> Result<Option<String>, MedplumError> {
+        // Extract given + family from "First Middle Last" — Medplum search
+        // matches on `name:contains` which looks at given + family + text.
+        let parts: Vec<&str> = name.split_whitespace().collect();
+        let search_query = if parts.len() >= 2 {
+            format!("{} {}", parts[0], parts[parts.len() - 1])
+        } else {
+            name.to_string()
+        };
+
+        // Search raw FHIR resources (not via search_patients — that helper
+        // strips middle names by returning only given[0]+family, so
+        // "Cheryl Lynn Bond" arrives here as "Cheryl Bond" and name_matches
+        // would reject the real match on every re-confirm, creating
+        // duplicate Patients). We query with an explicit birthdate filter so
+        // Medplum does the first cut and we only name-match the
+        // birthday-matching candidates.
+        let token = self.access_token().await?;
+        let search_url = format!(
+            "{}/Patient?name:contains={}&birthdate={}&_count=20",
+            self.fhir_base(),
+            urlencoding::encode(&search_query),
+            urlencoding::encode(dob),
+        );
+        let response = self
+            .http()
+            .get(&search_url)
+            .bearer_auth(&token)
+            .send()
+            .await?;
+        let bundle: serde_json::Value = handle_response(response).await?;
+        if let Some(entries) = bundle["entry"].as_array() {
+            for entry in entries {
+                let resource = &entry["resource"];
+                if resource["birthDate"].as_str() != Some(dob) {
+                    continue;
+                }
+                if !name_resource_matches(resource, name) {
+                    continue;
+                }
+                let id = resource["id"].as_str().unwrap_or_default().to_string();
+                if id.is_empty() {
+                    continue;
+                }
+                tracing::info!(
+                    event = "medplum_patient_match",
+                    patient_id = %id,
+                    "matched existing Medplum Patient"
+                );
+                return Ok(Some(id));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Sync a continuous-mode archived session as ONE FHIR `transaction`
+    /// Bundle: Patient (conditional create, only when the name + DOB search
+    /// missed) → finished Encounter with the recorded period → SOAP and
+    /// transcript DocumentReferences. The server applies all of it or none
+    /// of it, so a failed sync never leaves a half-written Encounter.
+    ///
+    /// Encounter and documents are conditional updates keyed on the session
+    /// identifier (`SESSION_IDENTIFIER_SYSTEM|<session_id>`), so retrying a
+    /// sync — from the outbox or a re-confirm — rewrites the same resources
+    /// instead of duplicating them.
+    ///
+    /// `practitioner_fhir_id` (v0.10.49+) overrides the backend's
+    /// Practitioner reference when set — the confirm flow passes the active
+    /// physician's `medplum_practitioner_id` so FHIR Encounter participants
+    /// point at the real Practitioner even when we're using a
+    /// ClientApplication-owned access token via the profile-service proxy.
+    ///
+    /// Returns the full sync record for the tauri-side to persist in
+    /// `ArchiveMetadata.medplum_patient_id` / `medplum_encounter_id`.
+    #[allow(clippy::too_many_arguments)]
+    async fn sync_continuous_session(
+        &self,
+        local_session_id: &str,
+        name: &str,
+        dob: &str,
+        soap_note: Option<&str>,
+        transcript: Option<&str>,
+        session_started_at_rfc3339: &str,
+        session_duration_ms: u64,
+        practitioner_fhir_id: Option<&str>,
+    ) -> Result<MedplumSessionSync, MedplumError> {
+        if let Some(pid) = practitioner_fhir_id {
+            self.set_practitioner_id(pid).await;
+        }
+        let practitioner_id = self.practitioner_id().await;
+        let existing_patient_id = self.find_patient_by_name_dob(name, dob).await?;
+
+        let transaction = SessionTransaction {
+            session_id: local_session_id,
+            name,
+            dob,
+            existing_patient_id: existing_patient_id.as_deref(),
+            practitioner_id: practitioner_id.as_deref(),
+            started_at: session_started_at_rfc3339,
+            duration_ms: session_duration_ms,
+            soap_note,
+            transcript,
+        };
+        let bundle = transaction.to_bundle();
+
+        let token = self.access_token().await?;
+        let response = self
+            .http()
+            .post(self.fhir_base())
+            .bearer_auth(&token)
+            .json(&bundle)
+            .send()
+            .await?;
+        let reply: serde_json::Value = handle_response(response).await?;
+        let sync = transaction.read_response(&reply)?;
+
+        tracing::info!(
+            event = "medplum_session_transaction",
+            backend = self.backend_name(),
+            patient_id = %sync.patient_id,
+            encounter_id = %sync.encounter_id,
+            new_patient = existing_patient_id.is_none(),
+            "continuous session synced as one transaction"
+        );
+        Ok(sync)
+    }
+
+    /// Fetch the last N encounters for a patient (by Medplum FHIR ID), most
+    /// recent first. Used by `prior_visits` to find earlier visits for
+    /// follow-up SOAP context.
+    async fn fetch_encounters_for_patient(
+        &self,
+        patient_fhir_id: &str,
+        limit: usize,
+    ) -> Result<Vec<EncounterSummary>, MedplumError> {
+        validate_fhir_id(patient_fhir_id)?;
+        let token = self.access_token().await?;
+
+        let url = format!(
+            "{}/Encounter?subject=Patient/{}&_sort=-date&_count={}",
+            self.fhir_base(), patient_fhir_id, limit
+        );
+        let response = self.http().get(&url).bearer_auth(&token).send().await?;
+        let bundle: serde_json::Value = handle_response(response).await?;
+
+        let mut out = Vec::new();
+        if let Some(entries) = bundle["entry"].as_array() {
+            for entry in entries {
+                let r = &entry["resource"];
+                let Some(id) = r["id"].as_str() else { continue };
+                let start = r["period"]["start"].as_str().unwrap_or("").to_string();
+                let duration_minutes = {
+                    let end = r["period"]["end"].as_str();
+                    match (
+                        chrono::DateTime::parse_from_rfc3339(&start),
+                        end.and_then(|e| chrono::DateTime::parse_from_rfc3339(e).ok()),
+                    ) {
+                        (Ok(s), Some(e)) => Some((e - s).num_minutes()),
+                        _ => None,
+                    }
+                };
+                let patient_name = r["subject"]["display"]
+                    .as_str()
+                    .unwrap_or("Unknown")
+                    .to_string();
+                out.push(EncounterSummary {
+                    id: id.to_string(),
+                    fhir_id: id.to_string(),
+                    patient_name,
+                    date: start,
+                    duration_minutes,
+                    // Doc-presence flags not computed here — callers that
+                    // need the SOAP fetch it per encounter via
+                    // `fetch_soap_note_for_encounter`; leaving as false keeps
+                    // the network footprint minimal for the common path.
+                    has_soap_note: false,
+                    has_audio: false,
+                });
+            }
+        }
+        Ok(out)
+    }
+
+    /// Fetch the SOAP note attached to an encounter, if any. Lighter than
+    /// `get_encounter_details`: one DocumentReference query, no patient or
+    /// Media lookups.
+    async fn fetch_soap_note_for_encounter(
+        &self,
+        encounter_fhir_id: &str,
+    ) -> Result<Option<String>, MedplumError> {
+        validate_fhir_id(encounter_fhir_id)?;
+        let token = self.access_token().await?;
+
+        let url = format!(
+            "{}/DocumentReference?encounter=Encounter/{}&category=soap-note&_sort=-date&_count=1",
+            self.fhir_base(), encounter_fhir_id
+        );
+        let response = self.http().get(&url).bearer_auth(&token).send().await?;
+        let bundle: serde_json::Value = handle_response(response).await?;
+
+        Ok(bundle["entry"]
+            .as_array()
+            .and_then(|e| e.first())
+            .and_then(|e| e["resource"]["content"].as_array())
+            .and_then(|c| c.first())
+            .and_then(|c| c["attachment"]["data"].as_str())
+            .and_then(|d| base64::engine::general_purpose::STANDARD.decode(d).ok())
+            .and_then(|bytes| String::from_utf8(bytes).ok()))
+    }
+
+    /// Create one structured clinical resource built by `fhir_extraction`
+    /// (Condition, MedicationStatement or Observation). Returns the
+    /// server-assigned reference, e.g. `Condition/123`.
+    async fn create_clinical_resource(
+        &self,
+        resource: &serde_json::Value,
+    ) -> Result<String, MedplumError> {
+        let resource_type = resource["resourceType"].as_str().unwrap_or_default();
+        if !matches!(resource_type, "Condition" | "MedicationStatement" | "Observation") {
+            return Err(MedplumError::ValidationError(format!(
+                "Unsupported clinical resource type: {resource_type:?}"
+            )));
+        }
+        let token = self.access_token().await?;
+
+        let response = self
+            .http()
+            .post(format!("{}/{}", self.fhir_base(), resource_type))
+            .bearer_auth(&token)
+            .json(resource)
+            .send()
+            .await?;
+        let created: serde_json::Value = handle_response(response).await?;
+        let id = created["id"]
+            .as_str()
+            .filter(|id| !id.is_empty())
+            .ok_or_else(|| {
+                MedplumError::ValidationError(format!("{resource_type} created without an id"))
+            })?;
+        Ok(format!("{resource_type}/{id}"))
+    }
+}
+
+/// Validate a FHIR resource ID per the FHIR specification.
+///
+/// FHIR IDs must match `[A-Za-z0-9\-\.]{1,64}`. This prevents path traversal
+/// and injection when IDs are interpolated into URL paths.
+fn validate_fhir_id(id: &str) -> Result<(), MedplumError> {
+    if id.is_empty() {
+        return Err(MedplumError::ValidationError(
+            "FHIR ID must not be empty".to_string(),
+        ));
+    }
+    if id.len() > 64 {
+        return Err(MedplumError::ValidationError(
+            "FHIR ID must not exceed 64 characters".to_string(),
+        ));
+    }
+    if !id
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.')
+    {
+        return Err(MedplumError::ValidationError(
+            "FHIR ID contains invalid characters (only alphanumeric, hyphens, and dots allowed)"
+                .to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Extract patient name from FHIR Patient resource
+fn extract_patient_name(resource: &serde_json::Value) -> String {
+    if let Some(names) = resource["name"].as_array() {
+        if let Some(name) = names.first() {
+            let family = name["family"].as_str().unwrap_or("");
+            let given = name["given"]
+                .as_array()
+                .and_then(|g| g.first())
+                .and_then(|g| g.as_str())
+                .unwrap_or("");
+            return format!("{} {}", given, family).trim().to_string();
+        }
+    }
+    "Unknown Patient".to_string()
+}
+
+/// Walk a FHIR Bundle's `entry[]` and pull included Patient resources into
+/// a `HashMap<patient_id, patient_resource>` for O(1) lookup. Use with
+/// `_include=Encounter:subject` on the original search to avoid N+1.
+fn extract_patients_from_bundle(
+    bundle: &serde_json::Value,
+) -> std::collections::HashMap<String, serde_json::Value> {
+    let mut patients = std::collections::HashMap::new();
+    if let Some(entries) = bundle["entry"].as_array() {
+        for entry in entries {
+            let resource = &entry["resource"];
+            if resource["resourceType"].as_str() == Some("Patient") {
+                if let Some(id) = resource["id"].as_str() {
+                    patients.insert(id.to_string(), resource.clone());
+                }
+            }
+        }
+    }
+    patients
+}
+
+/// Get patient name from encounter subject reference.
+///
+/// Slow path — issues one HTTP GET per encounter. Prefer the in-bundle
+/// `_include=Encounter:subject` resolution (see `extract_patients_from_bundle`),
+/// which carries the Patient resources alongside the Encounters in a single
+/// response. This helper is retained for one-off callers (e.g. encounter
+/// details lookup) that don't have a bundle to read from.
+async fn patient_name_from_encounter(
+    http: &reqwest::Client,
+    fhir_base: &str,
+    token: &str,
+    encounter: &serde_json::Value,
+) -> Result<String, MedplumError> {
+    if let Some(reference) = encounter["subject"]["reference"].as_str() {
+        let response = http
+            .get(format!("{}/{}", fhir_base, reference))
+            .bearer_auth(token)
+            .send()
+            .await?;
+
+        let patient: serde_json::Value = handle_response(response).await?;
+        return Ok(extract_patient_name(&patient));
+    }
+    Ok("Unknown".to_string())
+}
+
+/// Hard cap on pages traversed when following `Bundle.link[rel=next]`. Keeps
+/// pathological misuse bounded — a typical clinic year produces under 5,000
+/// encounters, well under this ceiling. Adjust if real workloads exceed it.
+const MAX_BUNDLE_PAGES: usize = 50;
+
+/// Fetch a FHIR search URL and concatenate all `entry[]` arrays across
+/// `Bundle.link[rel=next]` pages into one synthetic bundle. Returns the
+/// first response's bundle (with `entry` replaced) so callers can read it
+/// the same way they read a single-page bundle.
+async fn fetch_all_pages(
+    http: &reqwest::Client,
+    token: &str,
+    initial_url: &str,
+) -> Result<serde_json::Value, MedplumError> {
+    let mut accumulated_entries: Vec<serde_json::Value> = Vec::new();
+    let mut current_url = initial_url.to_string();
+    let mut pages = 0usize;
+    let mut first_bundle: Option<serde_json::Value> = None;
+
+    loop {
+        pages += 1;
+        if pages > MAX_BUNDLE_PAGES {
+            tracing::warn!(
+                event = "medplum_pagination_capped",
+                pages_traversed = pages - 1,
+                "Hit MAX_BUNDLE_PAGES; remaining pages dropped",
+            );
+            break;
+        }
+        let response = http
+            .get(&current_url)
+            .bearer_auth(token)
+            .send()
+            .await?;
+        let bundle: serde_json::Value = handle_response(response).await?;
+        if let Some(entries) = bundle["entry"].as_array() {
+            accumulated_entries.extend(entries.iter().cloned());
+        }
+        let next_url = bundle["link"]
+            .as_array()
+            .and_then(|links| {
+                links.iter().find_map(|l| {
+                    if l["relation"].as_str() == Some("next") {
+                        l["url"].as_str().map(|s| s.to_string())
+                    } else {
+                        None
+                    }
+                })
+            });
+        if first_bundle.is_none() {
+            first_bundle = Some(bundle);
+        }
+        match next_url {
+            Some(url) => current_url = url,
+            None => break,
+        }
+    }
+
+    let mut out = first_bundle.unwrap_or_else(|| serde_json::json!({}));
+    out["entry"] = serde_json::Value::Array(accumulated_entries);
+    Ok(out)
+}
+
+/// Get encounter FHIR IDs that have SOAP notes
+async fn encounters_with_soap_notes(
+    http: &reqwest::Client,
+    fhir_base: &str,
+    token: &str,
+    encounter_ids: &[&str],
+) -> Result<std::collections::HashSet<String>, MedplumError> {
+    use std::collections::HashSet;
+
+    let mut result = HashSet::new();
+    if encounter_ids.is_empty() {
+        return Ok(result);
+    }
+
+    // Query DocumentReference resources with soap-note category
+    // FHIR search: context:encounter references and category code
+    let url = format!(
+        "{}/DocumentReference?category=soap-note&_count=200&_elements=context",
+        fhir_base
+    );
+
+    let bundle = fetch_all_pages(http, token, &url).await?;
+
+    if let Some(entries) = bundle["entry"].as_array() {
+        for entry in entries {
+            // Extract encounter reference from context.encounter
+            if let Some(encounters) = entry["resource"]["context"]["encounter"].as_array() {
+                for enc in encounters {
+                    if let Some(reference) = enc["reference"].as_str() {
+                        // Reference format: "Encounter/{id}"
+                        if let Some(id) = reference.strip_prefix("Encounter/") {
+                            if encounter_ids.contains(&id) {
+                                result.insert(id.to_string());
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// Get encounter FHIR IDs that have audio recordings
+async fn encounters_with_audio(
+    http: &reqwest::Client,
+    fhir_base: &str,
+    token: &str,
+    encounter_ids: &[&str],
+) -> Result<std::collections::HashSet<String>, MedplumError> {
+    use std::collections::HashSet;
+
+    let mut result = HashSet::new();
+    if encounter_ids.is_empty() {
+        return Ok(result);
+    }
+
+    // Query Media resources
+    let url = format!("{}/Media?_count=200&_elements=encounter", fhir_base);
+
+    let bundle = fetch_all_pages(http, token, &url).await?;
+
+    if let Some(entries) = bundle["entry"].as_array() {
+        for entry in entries {
+            // Extract encounter reference
+            if let Some(reference) = entry["resource"]["encounter"]["reference"].as_str() {
+                // Reference format: "Encounter/{id}"
+                if let Some(id) = reference.strip_prefix("Encounter/") {
+                    if encounter_ids.contains(&id) {
+                        result.insert(id.to_string());
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// Truncate an error body to avoid leaking PHI from server responses.
+/// Uses `ceil_char_boundary()` for safe UTF-8 truncation (project convention).
+fn truncate_error_body(body: &str) -> String {
+    const MAX_ERROR_BODY_LEN: usize = 200;
+    if body.len() <= MAX_ERROR_BODY_LEN {
+        body.to_string()
+    } else {
+        let boundary = body.ceil_char_boundary(MAX_ERROR_BODY_LEN);
+        format!("{}...", &body[..boundary])
+    }
+}
+
+/// Handle HTTP response and convert to appropriate error
+pub(crate) async fn handle_response<T: serde::de::DeserializeOwned>(
+    response: reqwest::Response,
+) -> Result<T, MedplumError> {
+    match response.status() {
+        status if status.is_success() => {
+            Ok(response.json().await?)
+        }
+        reqwest::StatusCode::UNAUTHORIZED => {
+            Err(MedplumError::TokenExpired)
+        }
+        reqwest::StatusCode::FORBIDDEN => {
+            let body = response.text().await.unwrap_or_default();
+            Err(MedplumError::AccessDenied(truncate_error_body(&body)))
+        }
+        reqwest::StatusCode::NOT_FOUND => {
+            Err(MedplumError::NotFound("Resource not found".into()))
+        }
+        reqwest::StatusCode::UNPROCESSABLE_ENTITY => {
+            let body = response.text().await.unwrap_or_default();
+            Err(MedplumError::ValidationError(truncate_error_body(&body)))
+        }
+        status if status.is_server_error() => {
+            let body = response.text().await.unwrap_or_default();
+            Err(MedplumError::ServerError(
+                status.as_u16(),
+                truncate_error_body(&body),
+            ))
+        }
+        _ => {
+            let body = response.text().await.unwrap_or_default();
+            Err(MedplumError::AuthError(format!(
+                "Unexpected response: {}",
+                truncate_error_body(&body)
+            )))
+        }
+    }
+}
+
+/// Loose name match for dedup: normalize both sides, then compare as-is.
+/// "Judie Joan Guest" vs "judie joan guest" → match. "Judie Guest" vs
+/// "Judie Joan Guest" → NO match (different specificity is treated as a
+/// different Patient — FabricScribe's confirm dialog carries the full
+/// display name from vision, so this is the correct conservative rule).
+fn name_matches(a: &str, b: &str) -> bool {
+    let norm = |s: &str| -> String {
+        s.split_whitespace()
+            .map(|w| w.to_lowercase())
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    norm(a) == norm(b)
+}
+
+/// Compare a FHIR Patient resource's name against a caller-supplied full
+/// name (e.g., "Cheryl Lynn Bond"). Uses `name[0].text` as authoritative
+/// when present — that field carries the exact clinical display string.
+/// Falls back to reconstructing `given.join(' ') + family` ONLY when
+/// `.text` is absent (preserves middle names across the given array,
+/// unlike `extract_patient_name` which lossily takes only
+/// `given[0]+family` and caused the Apr 20 dedup bug).
+fn name_resource_matches(resource: &serde_json::Value, caller: &str) -> bool {
+    let Some(name) = resource
+        .get("name")
+        .and_then(|n| n.as_array())
+        .and_then(|a| a.first())
+    else {
+        return false;
+    };
+    if let Some(text) = name.get("text").and_then(|v| v.as_str()) {
+        return name_matches(text, caller);
+    }
+    let given_joined: String = name
+        .get("given")
+        .and_then(|g| g.as_array())
+        .map(|a| {
+            a.iter()
+                .filter_map(|v| v.as_str())
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .unwrap_or_default();
+    let family = name.get("family").and_then(|v| v.as_str()).unwrap_or("");
+    let reconstructed = format!("{given_joined} {family}")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    name_matches(&reconstructed, caller)
+}
+
+/// Identifier system for resources written per archived session. Conditional
+/// updates key on it, which is what makes `sync_continuous_session` safe to retry.
+pub const SESSION_IDENTIFIER_SYSTEM: &str = "urn:fabricscribe:session";
+
+/// Identifier system for Patients created by the confirm flow. The value is a
+/// hash of normalized name + DOB, so a retried conditional create finds the
+/// Patient the first attempt wrote without putting PHI in a search URL.
+pub const PATIENT_KEY_SYSTEM: &str = "urn:fabricscribe:patient-key";
+
+/// Stable, PHI-free patient identifier: sha256 of the normalized name and
+/// DOB, first 16 bytes as hex. Case and spacing in the name don't matter.
+pub(crate) fn patient_key(name: &str, dob: &str) -> String {
+    let normalized = name
+        .split_whitespace()
+        .map(|w| w.to_lowercase())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let digest = Sha256::digest(format!("{normalized}|{dob}").as_bytes());
+    digest[..16].iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Everything `sync_continuous_session` writes, as one transaction Bundle.
+#[derive(Debug, Clone)]
+struct SessionTransaction<'a> {
+    session_id: &'a str,
+    name: &'a str,
+    dob: &'a str,
+    /// Patient found by name + DOB search; `None` adds a conditional create.
+    existing_patient_id: Option<&'a str>,
+    practitioner_id: Option<&'a str>,
+    started_at: &'a str,
+    duration_ms: u64,
+    soap_note: Option<&'a str>,
+    transcript: Option<&'a str>,
+}
+
+impl SessionTransaction<'_> {
+    const PATIENT_URN: &'static str = "urn:uuid:00000000-0000-4000-8000-000000000001";
+    const ENCOUNTER_URN: &'static str = "urn:uuid:00000000-0000-4000-8000-000000000002";
+
+    fn patient_key(&self) -> String {
+        patient_key(self.name, self.dob)
+    }
+
+    fn patient_reference(&self) -> String {
+        match self.existing_patient_id {
+            Some(id) => format!("Patient/{id}"),
+            None => Self::PATIENT_URN.to_string(),
+        }
+    }
+
+    /// Conditional-update request for a resource keyed on this session.
+    fn session_keyed_entry(
+        &self,
+        resource_type: &str,
+        key: &str,
+        full_url: Option<&str>,
+        mut resource: serde_json::Value,
+    ) -> serde_json::Value {
+        resource["identifier"] = serde_json::json!([{
+            "system": SESSION_IDENTIFIER_SYSTEM,
+            "value": key
+        }]);
+        let mut entry = serde_json::json!({
+            "resource": resource,
+            "request": {
+                "method": "PUT",
+                "url": format!(
+                    "{resource_type}?identifier={}",
+                    urlencoding::encode(&format!("{SESSION_IDENTIFIER_SYSTEM}|{key}"))
+                )
+            }
+        });
+        if let Some(url) = full_url {
+            entry["fullUrl"] = serde_json::json!(url);
+        }
+        entry
+    }
+
+    fn document_entry(&self, kind: &str, loinc: (&str, &str), category: &str, text: &str) -> serde_json::Value {
+        self.session_keyed_entry(
+            "DocumentReference",
+            &format!("{}/{kind}", self.session_id),
+            None,
+            serde_json::json!({
+                "resourceType": "DocumentReference",
+                "status": "current",
+                "type": {
+                    "coding": [{
+                        "system": "http://loinc.org",
+                        "code": loinc.0,
+                        "display": loinc.1
+                    }]
+                },
+                "category": [{
+                    "coding": [{
+                        "system": "urn:fabricscribe",
+                        "code": category
+                    }]
+                }],
+                "subject": { "reference": self.patient_reference() },
+                "context": {
+                    "encounter": [{ "reference": Self::ENCOUNTER_URN }]
+                },
+                "content": [{
+                    "attachment": {
+                        "contentType": "text/plain",
+                        "data": base64::engine::general_purpose::STANDARD.encode(text)
+                    }
+                }],
+                "date": self.started_at
+            }),
+        )
+    }
+
+    fn to_bundle(&self) -> serde_json::Value {
+        let mut entries = Vec::new();
+
+        if self.existing_patient_id.is_none() {
+            let key = self.patient_key();
+            let parts: Vec<&str> = self.name.split_whitespace().collect();
+            let mut name_obj = serde_json::json!({ "use": "official", "text": self.name });
+            if parts.len() >= 2 {
+                // Middle names → extra given entries.
+                name_obj["given"] = serde_json::json!(parts[..parts.len() - 1]);
+                name_obj["family"] = serde_json::json!(parts[parts.len() - 1]);
+            }
+            entries.push(serde_json::json!({
+                "fullUrl": Self::PATIENT_URN,
+                "resource": {
+                    "resourceType": "Patient",
+                    "identifier": [{ "system": PATIENT_KEY_SYSTEM, "value": key }],
+                    "name": [name_obj],
+                    "birthDate": self.dob,
+                    "meta": {
+                        "tag": [{
+                            "system": "urn:fabricscribe",
+                            "code": "confirmed-patient",
+                        }],
+                    },
+                },
+                "request": {
+                    "method": "POST",
+                    "url": "Patient",
+                    "ifNoneExist": format!(
+                        "identifier={}",
+                        urlencoding::encode(&format!("{PATIENT_KEY_SYSTEM}|{key}"))
+                    )
+                }
+            }));
+        }
+
+        let end_time = DateTime::parse_from_rfc3339(self.started_at)
+            .ok()
+            .map(|t| t + Duration::milliseconds(self.duration_ms as i64))
+            .map(|t| t.to_rfc3339())
+            .unwrap_or_else(|| Utc::now().to_rfc3339());
+        let mut encounter = serde_json::json!({
+            "resourceType": "Encounter",
+            "status": "finished",
+            "class": {
+                "system": "http://terminology.hl7.org/CodeSystem/v3-ActCode",
+                "code": "AMB",
+                "display": "ambulatory"
+            },
+            "subject": { "reference": self.patient_reference() },
+            "period": { "start": self.started_at, "end": end_time },
+            "meta": {
+                "tag": [{
+                    "system": "urn:fabricscribe",
+                    "code": "scribe-session"
+                }]
+            }
+        });
+        if let Some(practitioner_id) = self.practitioner_id {
+            encounter["participant"] = serde_json::json!([{
+                "type": [{
+                    "coding": [{
+                        "system": "http://terminology.hl7.org/CodeSystem/v3-ParticipationType",
+                        "code": "PPRF",
+                        "display": "primary performer"
+                    }]
+                }],
+                "individual": { "reference": format!("Practitioner/{practitioner_id}") }
+            }]);
+        }
+        entries.push(self.session_keyed_entry(
+            "Encounter",
+            self.session_id,
+            Some(Self::ENCOUNTER_URN),
+            encounter,
+        ));
+
+        if let Some(soap) = self.soap_note {
+            entries.push(self.document_entry("soap-note", ("11506-3", "Progress note"), "soap-note", soap));
+        }
+        if let Some(transcript) = self.transcript {
+            entries.push(self.document_entry("transcript", ("75476-2", "Transcript"), "transcription", transcript));
+        }
+
+        serde_json::json!({
+            "resourceType": "Bundle",
+            "type": "transaction",
+            "entry": entries
+        })
+    }
+
+    /// Map a `transaction-response` back onto the request entries (the
+    /// server answers in request order).
+    fn read_response(&self, reply: &serde_json::Value) -> Result<MedplumSessionSync, MedplumError> {
+        let entries = reply["entry"].as_array().cloned().unwrap_or_default();
+        let id_at = |index: usize, resource_type: &str| -> Result<String, MedplumError> {
+            let entry = entries.get(index).ok_or_else(|| {
+                MedplumError::ValidationError(format!(
+                    "transaction response is missing the {resource_type} entry"
+                ))
+            })?;
+            entry["response"]["location"]
+                .as_str()
+                .and_then(|location| id_from_location(location, resource_type))
+                .or_else(|| entry["resource"]["id"].as_str().map(String::from))
+                .ok_or_else(|| {
+                    MedplumError::ValidationError(format!(
+                        "transaction response has no {resource_type} id"
+                    ))
+                })
+        };
+
+        let mut index = 0;
+        let mut next_id = |resource_type: &str| {
+            let id = id_at(index, resource_type);
+            index += 1;
+            id
+        };
+        let patient_id = match self.existing_patient_id {
+            Some(id) => id.to_string(),
+            None => next_id("Patient")?,
+        };
+        let encounter_id = next_id("Encounter")?;
+        let soap_doc_id = self.soap_note.map(|_| next_id("DocumentReference")).transpose()?;
+        let transcript_doc_id = self.transcript.map(|_| next_id("DocumentReference")).transpose()?;
+
+        Ok(MedplumSessionSync {
+            patient_id,
+            encounter_id,
+            transcript_doc_id,
+            soap_doc_id,
+            errors: Vec::new(),
+        })
+    }
+}
+
+/// Resource ID from a transaction-response `location`, which may be relative
+/// (`Encounter/123/_history/1`) or absolute.
+fn id_from_location(location: &str, resource_type: &str) -> Option<String> {
+    let mut segments = location.split('/');
+    segments.find(|s| *s == resource_type)?;
+    segments.next().filter(|id| !id.is_empty()).map(String::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::medplum::MedplumClient;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    #[test]
+    fn extract_patients_from_bundle_collects_included_patients() {
+        let bundle = serde_json::json!({
+            "entry": [
+                {"resource": {"resourceType": "Encounter", "id": "enc-1"}},
+                {"resource": {"resourceType": "Patient", "id": "pat-1", "name": [{"text": "Alice Smith"}]}},
+                {"resource": {"resourceType": "Encounter", "id": "enc-2"}},
+                {"resource": {"resourceType": "Patient", "id": "pat-2", "name": [{"text": "Bob Jones"}]}},
+                {"resource": {"resourceType": "Observation", "id": "obs-1"}},
+            ]
+        });
+        let patients = extract_patients_from_bundle(&bundle);
+        assert_eq!(patients.len(), 2);
+        assert!(patients.contains_key("pat-1"));
+        assert!(patients.contains_key("pat-2"));
+        assert_eq!(
+            patients["pat-1"]["name"][0]["text"].as_str(),
+            Some("Alice Smith")
+        );
+    }
+
+    #[test]
+    fn extract_patients_from_bundle_handles_missing_entry() {
+        let bundle = serde_json::json!({});
+        let patients = extract_patients_from_bundle(&bundle);
+        assert!(patients.is_empty());
+    }
+
+    #[test]
+    fn extract_patients_from_bundle_skips_unidentified_patients() {
+        let bundle = serde_json::json!({
+            "entry": [
+                {"resource": {"resourceType": "Patient"}},
+                {"resource": {"resourceType": "Patient", "id": "pat-ok"}},
+            ]
+        });
+        let patients = extract_patients_from_bundle(&bundle);
+        assert_eq!(patients.len(), 1);
+        assert!(patients.contains_key("pat-ok"));
+    }
+
+    #[test]
+    fn test_validate_fhir_id_valid() {
+        assert!(validate_fhir_id("abc123").is_ok());
+        assert!(validate_fhir_id("550e8400-e29b-41d4-a716-446655440000").is_ok());
+        assert!(validate_fhir_id("Patient.123").is_ok());
+        assert!(validate_fhir_id("a").is_ok());
+        assert!(validate_fhir_id("ABC-def-123.456").is_ok());
+    }
+
+    #[test]
+    fn test_validate_fhir_id_empty() {
+        let err = validate_fhir_id("").unwrap_err();
+        assert!(matches!(err, MedplumError::ValidationError(_)));
+    }
+
+    #[test]
+    fn test_validate_fhir_id_too_long() {
+        let long_id = "a".repeat(65);
+        let err = validate_fhir_id(&long_id).unwrap_err();
+        assert!(matches!(err, MedplumError::ValidationError(_)));
+
+        // Exactly 64 should be fine
+        let max_id = "a".repeat(64);
+        assert!(validate_fhir_id(&max_id).is_ok());
+    }
+
+    #[test]
+    fn test_validate_fhir_id_invalid_chars() {
+        assert!(validate_fhir_id("foo/bar").is_err());
+        assert!(validate_fhir_id("foo\\bar").is_err());
+        assert!(validate_fhir_id("../etc").is_err());
+        assert!(validate_fhir_id("id with spaces").is_err());
+        assert!(validate_fhir_id("id\0null").is_err());
+        assert!(validate_fhir_id("id?query=1").is_err());
+        assert!(validate_fhir_id("id#fragment").is_err());
+        assert!(validate_fhir_id("id&param=val").is_err());
+    }
+
+    #[test]
+    fn test_truncate_error_body_short() {
+        let short = "Short error message";
+        assert_eq!(truncate_error_body(short), short);
+    }
+
+    #[test]
+    fn test_truncate_error_body_exact_limit() {
+        let exact = "a".repeat(200);
+        assert_eq!(truncate_error_body(&exact), exact);
+    }
+
+    #[test]
+    fn test_truncate_error_body_over_limit() {
+        let long = "a".repeat(300);
+        let truncated = truncate_error_body(&long);
+        assert!(truncated.len() <= 204); // 200 + "..."
+        assert!(truncated.ends_with("..."));
+    }
+
+    #[test]
+    fn test_truncate_error_body_utf8_safety() {
+        // Multi-byte characters: each is 4 bytes
+        let emoji_string = "x".repeat(198) + "\u{1F600}\u{1F600}\u{1F600}"; // 198 + 12 = 210 bytes
+        let truncated = truncate_error_body(&emoji_string);
+        assert!(truncated.ends_with("..."));
+        // Verify it's valid UTF-8 (would panic if not)
+        let _ = truncated.as_bytes();
+    }
+
+    #[test]
+    fn test_truncate_error_body_empty() {
+        assert_eq!(truncate_error_body(""), "");
+    }
+
+    // ── find_patient_by_name_dob helpers (v0.10.46+) ─────────────
+
+    #[test]
+    fn name_matches_is_case_and_whitespace_insensitive() {
+        assert!(name_matches(
+            "Judie Joan Guest",
+            "judie  joan  guest"
+        ));
+        assert!(name_matches("John Smith", "  JOHN SMITH "));
+    }
+
+    #[test]
+    fn name_matches_rejects_different_specificity() {
+        // "Judie Guest" and "Judie Joan Guest" differ — Medplum should treat
+        // them as distinct Patients to avoid merging records with partial data.
+        assert!(!name_matches("Judie Guest", "Judie Joan Guest"));
+    }
+
+    #[test]
+    fn name_matches_rejects_completely_different() {
+        assert!(!name_matches("John Smith", "Jane Doe"));
+    }
+
+    #[test]
+    fn name_resource_matches_prefers_text_field() {
+        // The Apr 20 bug repro: FHIR resource has name[0].text with the full
+        // display string, but extract_patient_name() lossily drops middles.
+        // name_resource_matches uses name[0].text first and picks up the
+        // match correctly.
+        let r = serde_json::json!({
+            "name": [{
+                "use": "official",
+                "text": "Cheryl Lynn Bond",
+                "given": ["Cheryl"],
+                "family": "Bond",
+            }]
+        });
+        assert!(name_resource_matches(&r, "Cheryl Lynn Bond"));
+        assert!(!name_resource_matches(&r, "Cheryl Bond"));
+    }
+
+    #[test]
+    fn name_resource_matches_falls_back_to_given_plus_family() {
+        // No .text → reconstruct from given list + family, preserving middles.
+        let r = serde_json::json!({
+            "name": [{
+                "use": "official",
+                "given": ["Cheryl", "Lynn"],
+                "family": "Bond",
+            }]
+        });
+        assert!(name_resource_matches(&r, "Cheryl Lynn Bond"));
+        // Partial match rejected.
+        assert!(!name_resource_matches(&r, "Cheryl Bond"));
+    }
+
+    #[test]
+    fn name_resource_matches_no_name_array_returns_false() {
+        let r = serde_json::json!({});
+        assert!(!name_resource_matches(&r, "Anyone"));
+    }
+
+    #[test]
+    fn name_resource_matches_empty_name_array_returns_false() {
+        let r = serde_json::json!({ "name": [] });
+        assert!(!name_resource_matches(&r, "Anyone"));
+    }
+
+    #[test]
+    fn name_resource_matches_case_and_whitespace_insensitive() {
+        let r = serde_json::json!({
+            "name": [{ "text": "  cheryl   LYNN   bond " }]
+        });
+        assert!(name_resource_matches(&r, "Cheryl Lynn Bond"));
+    }
+
+    /// End-to-end regression for the v0.10.47 dedup fix: confirm that the
+    /// FHIR search + `name_resource_matches` flow used by
+    /// `find_patient_by_name_dob` correctly reuses an existing Patient
+    /// (whose stored form has `given=["Cheryl"], family="Bond",
+    /// text="Cheryl Lynn Bond"`) when the caller supplies the full name
+    /// "Cheryl Lynn Bond". In v0.10.46 this test would have failed — the
+    /// client went through `search_patients`, which built the Patient.name
+    /// as "Cheryl Bond" (lossy `given[0]+family`), and `name_matches`
+    /// rejected the real match → would have POSTed a duplicate Patient.
+    ///
+    /// Requires `MEDPLUM_TEST_CLIENT_ID` + `MEDPLUM_TEST_CLIENT_SECRET`
+    /// env vars (a ClientApplication with `client_credentials` grant).
+    /// Run with: MEDPLUM_TEST_CLIENT_ID=... MEDPLUM_TEST_CLIENT_SECRET=...
+    ///           cargo test upsert_dedup_live -- --ignored
+    #[tokio::test]
+    #[ignore = "Requires live Medplum + MEDPLUM_TEST_CLIENT_{ID,SECRET} env vars"]
+    async fn upsert_dedup_live_reuses_existing_patient_with_middle_name() {
+        let client_id = std::env::var("MEDPLUM_TEST_CLIENT_ID")
+            .expect("MEDPLUM_TEST_CLIENT_ID required for live test");
+        let client_secret = std::env::var("MEDPLUM_TEST_CLIENT_SECRET")
+            .expect("MEDPLUM_TEST_CLIENT_SECRET required for live test");
+        let base = std::env::var("MEDPLUM_TEST_BASE_URL")
+            .unwrap_or_else(|_| "http://100.119.83.76:8103".to_string());
+
+        let http = reqwest::Client::new();
+
+        // Fetch a client_credentials access token.
+        let token: String = http
+            .post(format!("{base}/oauth2/token"))
+            .form(&[
+                ("grant_type", "client_credentials"),
+                ("client_id", client_id.as_str()),
+                ("client_secret", client_secret.as_str()),
+            ])
+            .send()
+            .await
+            .expect("oauth2 token request")
+            .json::<serde_json::Value>()
+            .await
+            .expect("token json")["access_token"]
+            .as_str()
+            .expect("access_token string")
+            .to_string();
+
+        // Per-run unique fixture — avoids collisions with prior test runs
+        // and real clinical records. We always POST fresh, assert, then
+        // DELETE in cleanup.
+        let run_id = uuid::Uuid::new_v4().simple().to_string();
+        let dob = "1901-02-03";
+        let full_name = format!("Fixture MiddleName {run_id}");
+        let given = "Fixture";
+        let family = run_id.clone();
+
+        // Create the fixture: lossy given list (only first name), full name
+        // only in `text`. This is the exact shape v0.10.46's
+        // `extract_patient_name` would mishandle (returning "Fixture {run_id}"
+        // and failing the caller-side name match).
+        let created: serde_json::Value = http
+            .post(format!("{base}/fhir/R4/Patient"))
+            .bearer_auth(&token)
+            .json(&serde_json::json!({
+                "resourceType": "Patient",
+                "name": [{
+                    "use": "official",
+                    "text": full_name,
+                    "given": [given],
+                    "family": family,
+                }],
+                "birthDate": dob,
+                "meta": {"tag": [{
+                    "system": "urn:fabricscribe",
+                    "code": "upsert-dedup-fixture",
+                }]},
+            }))
+            .send()
+            .await
+            .expect("create request")
+            .json()
+            .await
+            .expect("create json");
+        let patient_id = created["id"].as_str().expect("patient id").to_string();
+
+        // Replay the upsert search logic using the same URL shape +
+        // matcher as `find_patient_by_name_dob`.
+        let search_query = format!("{given} {family}");
+        let bundle: serde_json::Value = http
+            .get(format!(
+                "{base}/fhir/R4/Patient?name:contains={}&birthdate={}&_count=20",
+                urlencoding::encode(&search_query),
+                urlencoding::encode(dob),
+            ))
+            .bearer_auth(&token)
+            .send()
+            .await
+            .expect("dedup search request")
+            .json()
+            .await
+            .expect("dedup search json");
+
+        let mut matched_id: Option<String> = None;
+        if let Some(entries) = bundle["entry"].as_array() {
+            for e in entries {
+                let r = &e["resource"];
+                if r["birthDate"].as_str() != Some(dob) {
+                    continue;
+                }
+                if !name_resource_matches(r, &full_name) {
+                    continue;
+                }
+                matched_id = r["id"].as_str().map(|s| s.to_string());
+                break;
+            }
+        }
+
+        let assertion = matched_id.as_deref() == Some(patient_id.as_str());
+
+        // Cleanup: delete the fixture regardless of assertion outcome.
+        let _ = http
+            .delete(format!("{base}/fhir/R4/Patient/{patient_id}"))
+            .bearer_auth(&token)
+            .send()
+            .await;
+
+        assert!(
+            assertion,
+            "find_patient_by_name_dob should reuse existing Patient with matching \
+             name[0].text even when given[0]+family alone would lose the middle name; \
+             expected {}, matched {:?}",
+            patient_id, matched_id
+        );
+        println!(
+            "✓ dedup correctly reuses Patient/{} for caller name \"{}\" with dob {}",
+            patient_id, full_name, dob
+        );
+    }
+
+    fn session_transaction(existing_patient_id: Option<&str>) -> SessionTransaction<'_> {
+        SessionTransaction {
+            session_id: "sess-1",
+            name: "Cheryl Lynn Bond",
+            dob: "1960-05-04",
+            existing_patient_id,
+            practitioner_id: Some("prac-1"),
+            started_at: "2026-10-01T14:00:00+00:00",
+            duration_ms: 900_000,
+            soap_note: Some("S: cough"),
+            transcript: Some("Doctor: hello"),
+        }
+    }
+
+    #[test]
+    fn session_transaction_creates_patient_conditionally_and_links_by_urn() {
+        let bundle = session_transaction(None).to_bundle();
+        assert_eq!(bundle["type"], "transaction");
+        let entries = bundle["entry"].as_array().unwrap();
+        assert_eq!(entries.len(), 4);
+
+        let patient = &entries[0];
+        assert_eq!(patient["request"]["method"], "POST");
+        let if_none_exist = patient["request"]["ifNoneExist"].as_str().unwrap();
+        assert!(if_none_exist.starts_with("identifier=urn%3Afabricscribe%3Apatient-key%7C"));
+        assert!(!if_none_exist.contains("Cheryl"), "no PHI in the conditional URL");
+        assert_eq!(patient["resource"]["name"][0]["given"], serde_json::json!(["Cheryl", "Lynn"]));
+
+        let encounter = &entries[1];
+        assert_eq!(encounter["request"]["method"], "PUT");
+        assert_eq!(
+            encounter["request"]["url"],
+            "Encounter?identifier=urn%3Afabricscribe%3Asession%7Csess-1"
+        );
+        assert_eq!(encounter["resource"]["subject"]["reference"], patient["fullUrl"]);
+        assert_eq!(encounter["resource"]["status"], "finished");
+        assert_eq!(encounter["resource"]["period"]["end"], "2026-10-01T14:15:00+00:00");
+        assert_eq!(
+            encounter["resource"]["participant"][0]["individual"]["reference"],
+            "Practitioner/prac-1"
+        );
+
+        for (entry, key) in [(&entries[2], "sess-1/soap-note"), (&entries[3], "sess-1/transcript")] {
+            assert_eq!(entry["request"]["method"], "PUT");
+            assert_eq!(entry["resource"]["identifier"][0]["value"], key);
+            assert_eq!(
+                entry["resource"]["context"]["encounter"][0]["reference"],
+                encounter["fullUrl"]
+            );
+        }
+    }
+
+    #[test]
+    fn session_transaction_reuses_found_patient() {
+        let bundle = session_transaction(Some("pat-9")).to_bundle();
+        let entries = bundle["entry"].as_array().unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0]["resource"]["resourceType"], "Encounter");
+        assert_eq!(entries[0]["resource"]["subject"]["reference"], "Patient/pat-9");
+    }
+
+    #[test]
+    fn patient_key_ignores_case_and_spacing() {
+        let a = session_transaction(None);
+        let b = SessionTransaction { name: " cheryl  lynn BOND ", ..a.clone() };
+        let c = SessionTransaction { dob: "1960-05-05", ..a.clone() };
+        assert_eq!(a.patient_key(), b.patient_key());
+        assert_ne!(a.patient_key(), c.patient_key());
+        assert_eq!(a.patient_key().len(), 32);
+    }
+
+    #[test]
+    fn read_response_maps_entries_in_request_order() {
+        let reply = serde_json::json!({
+            "resourceType": "Bundle",
+            "type": "transaction-response",
+            "entry": [
+                {"response": {"status": "201 Created", "location": "Patient/p1/_history/1"}},
+                {"response": {"status": "200 OK", "location": "https://emr.example/fhir/R4/Encounter/e1/_history/2"}},
+                {"response": {"status": "201 Created"}, "resource": {"resourceType": "DocumentReference", "id": "d1"}},
+                {"response": {"status": "201 Created", "location": "DocumentReference/d2"}},
+            ]
+        });
+        let sync = session_transaction(None).read_response(&reply).unwrap();
+        assert_eq!(sync.patient_id, "p1");
+        assert_eq!(sync.encounter_id, "e1");
+        assert_eq!(sync.soap_doc_id.as_deref(), Some("d1"));
+        assert_eq!(sync.transcript_doc_id.as_deref(), Some("d2"));
+
+        let truncated = serde_json::json!({ "entry": [reply["entry"][0].clone()] });
+        assert!(session_transaction(None).read_response(&truncated).is_err());
+    }
+
+    #[test]
+    fn id_from_location_handles_relative_and_absolute() {
+        assert_eq!(id_from_location("Encounter/e1/_history/1", "Encounter").as_deref(), Some("e1"));
+        assert_eq!(id_from_location("http://x/fhir/R4/Encounter/e1", "Encounter").as_deref(), Some("e1"));
+        assert_eq!(id_from_location("Patient/p1", "Encounter"), None);
+        assert_eq!(id_from_location("Encounter/", "Encounter"), None);
+    }
+
+    /// Minimal FHIR server: patient search misses, the transaction endpoint
+    /// answers with one location per entry (or 503 when `fail` is set).
+    async fn spawn_mock_transaction_server(
+        fail: bool,
+    ) -> (String, Arc<std::sync::Mutex<Vec<serde_json::Value>>>) {
+        use axum::extract::State;
+        use axum::http::StatusCode;
+        use axum::Json;
+
+        type Received = Arc<std::sync::Mutex<Vec<serde_json::Value>>>;
+        async fn transaction(
+            State((received, fail)): State<(Received, bool)>,
+            Json(bundle): Json<serde_json::Value>,
+        ) -> (StatusCode, Json<serde_json::Value>) {
+            received.lock().unwrap().push(bundle.clone());
+            if fail {
+                return (StatusCode::SERVICE_UNAVAILABLE, Json(serde_json::json!({})));
+            }
+            let entries: Vec<_> = bundle["entry"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .enumerate()
+                .map(|(i, e)| {
+                    let resource_type = e["resource"]["resourceType"].as_str().unwrap();
+                    serde_json::json!({
+                        "response": { "status": "201 Created", "location": format!("{resource_type}/id-{i}/_history/1") }
+                    })
+                })
+                .collect();
+            (
+                StatusCode::OK,
+                Json(serde_json::json!({ "resourceType": "Bundle", "type": "transaction-response", "entry": entries })),
+            )
+        }
+        async fn search_patients() -> Json<serde_json::Value> {
+            Json(serde_json::json!({ "resourceType": "Bundle", "type": "searchset" }))
+        }
+
+        let received: Received = Arc::default();
+        let app = axum::Router::new()
+            .route("/fhir/R4", axum::routing::post(transaction))
+            .route("/fhir/R4/Patient", axum::routing::get(search_patients))
+            .with_state((received.clone(), fail));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (url, received)
+    }
+
+    #[tokio::test]
+    async fn sync_continuous_session_posts_one_transaction() {
+        let (url, received) = spawn_mock_transaction_server(false).await;
+        let client = MedplumClient::new(&url, "test-client").unwrap();
+        client.inject_proxy_token("token".into(), 3600, None).await.unwrap();
+
+        let sync = client
+            .sync_continuous_session(
+                "sess-1",
+                "Jane Doe",
+                "1970-01-01",
+                Some("S: cough"),
+                None,
+                "2026-10-01T14:00:00Z",
+                60_000,
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(sync.patient_id, "id-0");
+        assert_eq!(sync.encounter_id, "id-1");
+        assert_eq!(sync.soap_doc_id.as_deref(), Some("id-2"));
+        assert_eq!(sync.transcript_doc_id, None);
+        assert_eq!(received.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn sync_continuous_session_surfaces_server_errors_as_retryable() {
+        let (url, received) = spawn_mock_transaction_server(true).await;
+        let client = MedplumClient::new(&url, "test-client").unwrap();
+        client.inject_proxy_token("token".into(), 3600, None).await.unwrap();
+
+        let err = client
+            .sync_continuous_session("sess-1", "Jane Doe", "1970-01-01", None, None, "2026-10-01T14:00:00Z", 0, None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, MedplumError::ServerError(503, _)));
+        assert!(err.is_retryable());
+        assert_eq!(received.lock().unwrap().len(), 1);
+    }
+
+    // ── Backend contract ─────────────────────────────────────────────
+    //
+    // One in-memory FHIR R4 server, one scenario, run against every
+    // backend. The stub honours the parts of FHIR the provided methods rely
+    // on: reads, creates, updates, the searches they issue, conditional
+    // transaction entries and `urn:uuid` reference resolution. Its token
+    // endpoint verifies SMART client assertions.
+
+    const CONTRACT_TOKEN: &str = "contract-token";
+    const SMART_CLIENT_ID: &str = "scribe-backend";
+
+    #[derive(Default)]
+    struct FhirStore {
+        base_url: String,
+        resources: Vec<serde_json::Value>,
+        next_id: usize,
+        token_requests: usize,
+        unauthorized_requests: usize,
+    }
+
+    type SharedStore = Arc<std::sync::Mutex<FhirStore>>;
+
+    impl FhirStore {
+        fn insert(&mut self, mut resource: serde_json::Value) -> serde_json::Value {
+            self.next_id += 1;
+            let resource_type = resource["resourceType"].as_str().unwrap_or_default().to_lowercase();
+            resource["id"] = serde_json::json!(format!("{resource_type}-{}", self.next_id));
+            self.resources.push(resource.clone());
+            resource
+        }
+
+        fn find(&self, resource_type: &str, id: &str) -> Option<usize> {
+            self.resources
+                .iter()
+                .position(|r| r["resourceType"] == resource_type && r["id"] == id)
+        }
+
+        /// Resolve `identifier=system|value` against stored resources.
+        fn find_by_identifier(&self, resource_type: &str, query: &str) -> Option<usize> {
+            let query = urlencoding::decode(query.strip_prefix("identifier=")?).ok()?;
+            let (system, value) = query.split_once('|')?;
+            self.resources.iter().position(|r| {
+                r["resourceType"] == resource_type
+                    && r["identifier"].as_array().is_some_and(|ids| {
+                        ids.iter().any(|i| i["system"] == system && i["value"] == value)
+                    })
+            })
+        }
+    }
+
+    fn authorized(store: &SharedStore, headers: &axum::http::HeaderMap) -> bool {
+        let ok = headers
+            .get(axum::http::header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            == Some(&format!("Bearer {CONTRACT_TOKEN}"));
+        if !ok {
+            store.lock().unwrap().unauthorized_requests += 1;
+        }
+        ok
+    }
+
+    fn references(resource: &serde_json::Value, path: &[&str]) -> Vec<String> {
+        let mut values = vec![resource];
+        for key in path {
+            values = values
+                .into_iter()
+                .flat_map(|v| match &v[*key] {
+                    serde_json::Value::Array(items) => items.iter().collect::<Vec<_>>(),
+                    other => vec![other],
+                })
+                .collect();
+        }
+        values.into_iter().filter_map(|v| v.as_str().map(String::from)).collect()
+    }
+
+    /// The search parameters the provided methods send; others are ignored.
+    fn matches_search(resource: &serde_json::Value, params: &HashMap<String, String>) -> bool {
+        params.iter().all(|(key, value)| match key.as_str() {
+            "birthdate" => resource["birthDate"] == value.as_str(),
+            "subject" => references(resource, &["subject", "reference"]).contains(value),
+            "encounter" => references(resource, &["context", "encounter", "reference"])
+                .iter()
+                .chain(&references(resource, &["encounter", "reference"]))
+                .any(|r| r == value),
+            "category" => references(resource, &["category", "coding", "code"]).contains(value),
+            _ => true,
+        })
+    }
+
+    async fn spawn_contract_server() -> (String, SharedStore) {
+        use axum::extract::{Path, Query, State};
+        use axum::http::{HeaderMap, StatusCode};
+        use axum::routing::{get, post};
+        use axum::{Form, Json};
+
+        type Reply = (StatusCode, Json<serde_json::Value>);
+        fn unauthorized() -> Reply {
+            (StatusCode::UNAUTHORIZED, Json(serde_json::json!({})))
+        }
+
+        async fn smart_configuration(State(store): State<SharedStore>) -> Json<serde_json::Value> {
+            let base_url = store.lock().unwrap().base_url.clone();
+            Json(serde_json::json!({
+                "token_endpoint": format!("{base_url}/token"),
+                "grant_types_supported": ["client_credentials"],
+                "token_endpoint_auth_methods_supported": ["private_key_jwt"],
+                "token_endpoint_auth_signing_alg_values_supported": ["ES384", "RS384"],
+            }))
+        }
+
+        async fn token(
+            State(store): State<SharedStore>,
+            Form(form): Form<HashMap<String, String>>,
+        ) -> Reply {
+            let mut store = store.lock().unwrap();
+            store.token_requests += 1;
+            let mut validation = jsonwebtoken::Validation::new(jsonwebtoken::Algorithm::ES384);
+            validation.set_audience(&[format!("{}/token", store.base_url)]);
+            let claims = jsonwebtoken::decode::<crate::smart_backend::ClientAssertionClaims>(
+                form.get("client_assertion").map(String::as_str).unwrap_or_default(),
+                &jsonwebtoken::DecodingKey::from_ec_pem(
+                    crate::smart_backend::tests::TEST_EC_PUBLIC_KEY.as_bytes(),
+                )
+                .unwrap(),
+                &validation,
+            );
+            let valid = form.get("grant_type").map(String::as_str) == Some("client_credentials")
+                && form.get("client_assertion_type").map(String::as_str)
+                    == Some("urn:ietf:params:oauth:client-assertion-type:jwt-bearer")
+                && claims.is_ok_and(|c| c.claims.iss == SMART_CLIENT_ID && c.claims.sub == SMART_CLIENT_ID);
+            if !valid {
+                return (StatusCode::BAD_REQUEST, Json(serde_json::json!({ "error": "invalid_client" })));
+            }
+            (
+                StatusCode::OK,
+                Json(serde_json::json!({
+                    "access_token": CONTRACT_TOKEN,
+                    "token_type": "bearer",
+                    "expires_in": 300,
+                    "scope": form.get("scope"),
+                })),
+            )
+        }
+
+        async fn search(
+            State(store): State<SharedStore>,
+            headers: HeaderMap,
+            Path(resource_type): Path<String>,
+            Query(params): Query<HashMap<String, String>>,
+        ) -> Reply {
+            if !authorized(&store, &headers) {
+                return unauthorized();
+            }
+            let store = store.lock().unwrap();
+            let entries: Vec<_> = store
+                .resources
+                .iter()
+                .filter(|r| r["resourceType"] == resource_type.as_str() && matches_search(r, &params))
+                .map(|r| serde_json::json!({ "resource": r }))
+                .collect();
+            (
+                StatusCode::OK,
+                Json(serde_json::json!({ "resourceType": "Bundle", "type": "searchset", "entry": entries })),
+            )
+        }
+
+        async fn create(
+            State(store): State<SharedStore>,
+            headers: HeaderMap,
+            Json(resource): Json<serde_json::Value>,
+        ) -> Reply {
+            if !authorized(&store, &headers) {
+                return unauthorized();
+            }
+            (StatusCode::CREATED, Json(store.lock().unwrap().insert(resource)))
+        }
+
+        async fn read(
+            State(store): State<SharedStore>,
+            headers: HeaderMap,
+            Path((resource_type, id)): Path<(String, String)>,
+        ) -> Reply {
+            if !authorized(&store, &headers) {
+                return unauthorized();
+            }
+            let store = store.lock().unwrap();
+            match store.find(&resource_type, &id) {
+                Some(i) => (StatusCode::OK, Json(store.resources[i].clone())),
+                None => (StatusCode::NOT_FOUND, Json(serde_json::json!({}))),
+            }
+        }
+
+        async fn update(
+            State(store): State<SharedStore>,
+            headers: HeaderMap,
+            Path((resource_type, id)): Path<(String, String)>,
+            Json(resource): Json<serde_json::Value>,
+        ) -> Reply {
+            if !authorized(&store, &headers) {
+                return unauthorized();
+            }
+            let mut store = store.lock().unwrap();
+            match store.find(&resource_type, &id) {
+                Some(i) => {
+                    store.resources[i] = resource.clone();
+                    (StatusCode::OK, Json(resource))
+                }
+                None => (StatusCode::NOT_FOUND, Json(serde_json::json!({}))),
+            }
+        }
+
+        async fn transaction(
+            State(store): State<SharedStore>,
+            headers: HeaderMap,
+            Json(bundle): Json<serde_json::Value>,
+        ) -> Reply {
+            if !authorized(&store, &headers) {
+                return unauthorized();
+            }
+            let mut store = store.lock().unwrap();
+            let entries = bundle["entry"].as_array().cloned().unwrap_or_default();
+
+            // Pass 1: decide each entry's target ID.
+            let mut targets = Vec::new();
+            let mut resolved = HashMap::new();
+            for entry in &entries {
+                let resource_type = entry["resource"]["resourceType"].as_str().unwrap().to_string();
+                let request = &entry["request"];
+                let existing = match request["method"].as_str() {
+                    Some("POST") => request["ifNoneExist"]
+                        .as_str()
+                        .and_then(|q| store.find_by_identifier(&resource_type, q)),
+                    _ => request["url"]
+                        .as_str()
+                        .and_then(|url| url.split_once('?'))
+                        .and_then(|(_, q)| store.find_by_identifier(&resource_type, q)),
+                };
+                let id = match existing {
+                    Some(i) => store.resources[i]["id"].as_str().unwrap().to_string(),
+                    None => store.insert(serde_json::json!({ "resourceType": resource_type }))["id"]
+                        .as_str()
+                        .unwrap()
+                        .to_string(),
+                };
+                if let Some(full_url) = entry["fullUrl"].as_str() {
+                    resolved.insert(full_url.to_string(), format!("{resource_type}/{id}"));
+                }
+                targets.push((resource_type, id, existing.is_some()));
+            }
+
+            // Pass 2: write resources with urn:uuid references resolved.
+            let mut response_entries = Vec::new();
+            for (entry, (resource_type, id, existed)) in entries.iter().zip(&targets) {
+                let conditional_create_hit = *existed && entry["request"]["method"] == "POST";
+                if !conditional_create_hit {
+                    let mut text = entry["resource"].to_string();
+                    for (urn, reference) in &resolved {
+                        text = text.replace(urn.as_str(), reference);
+                    }
+                    let mut resource: serde_json::Value = serde_json::from_str(&text).unwrap();
+                    resource["id"] = serde_json::json!(id);
+                    let i = store.find(resource_type, id).unwrap();
+                    store.resources[i] = resource;
+                }
+                response_entries.push(serde_json::json!({
+                    "response": {
+                        "status": if *existed { "200 OK" } else { "201 Created" },
+                        "location": format!("{resource_type}/{id}/_history/1"),
+                    }
+                }));
+            }
+            (
+                StatusCode::OK,
+                Json(serde_json::json!({
+                    "resourceType": "Bundle",
+                    "type": "transaction-response",
+                    "entry": response_entries,
+                })),
+            )
+        }
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let store: SharedStore = Arc::default();
+        store.lock().unwrap().base_url = url.clone();
+        let app = axum::Router::new()
+            .route("/token", post(token))
+            .route("/fhir/R4", post(transaction))
+            .route("/fhir/R4/.well-known/smart-configuration", get(smart_configuration))
+            .route("/fhir/R4/:resource_type", get(search).post(create))
+            .route("/fhir/R4/:resource_type/:id", get(read).put(update))
+            .with_state(store.clone());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (url, store)
+    }
+
+    /// Everything the app does against an EMR, in the order a clinic day
+    /// does it. Both backends must pass unchanged.
+    async fn run_contract(backend: &dyn EmrBackend) {
+        assert!(backend.is_authenticated().await, "{}", backend.backend_name());
+        backend.set_practitioner_id("prac-1").await;
+        assert_eq!(backend.practitioner_id().await.as_deref(), Some("prac-1"));
+
+        // Session mode: placeholder patient, encounter, documents, finish.
+        let placeholder = backend.create_placeholder_patient().await.unwrap();
+        let encounter = backend.create_encounter(&placeholder.id).await.unwrap();
+        assert_eq!(encounter.patient_name, "Scribe Session");
+        backend
+            .upload_transcript(&encounter.id, &encounter.id, &placeholder.id, "Doctor: hello")
+            .await
+            .unwrap();
+        backend
+            .upload_soap_note(&encounter.id, &encounter.id, &placeholder.id, "S: cough")
+            .await
+            .unwrap();
+        backend.complete_encounter(&encounter.id).await.unwrap();
+        let details = backend.get_encounter_details(&encounter.id).await.unwrap();
+        assert_eq!(details.transcript.as_deref(), Some("Doctor: hello"));
+        assert_eq!(details.soap_note.as_deref(), Some("S: cough"));
+        assert_eq!(details.patient_id.as_deref(), Some(placeholder.id.as_str()));
+        assert!(details.summary.duration_minutes.is_some(), "encounter was finished");
+
+        // Continuous mode: one transaction, then a retry of the same session.
+        let sync = |soap: &'static str| {
+            backend.sync_continuous_session(
+                "sess-1",
+                "Cheryl Lynn Bond",
+                "1960-05-04",
+                Some(soap),
+                Some("Doctor: how are you?"),
+                "2026-10-01T14:00:00+00:00",
+                900_000,
+                None,
+            )
+        };
+        let first = sync("S: follow-up").await.unwrap();
+        let retry = sync("S: follow-up, amended").await.unwrap();
+        assert_eq!(first.patient_id, retry.patient_id, "retry reuses the Patient");
+        assert_eq!(first.encounter_id, retry.encounter_id, "retry reuses the Encounter");
+        assert_eq!(first.soap_doc_id, retry.soap_doc_id);
+
+        assert_eq!(
+            backend.find_patient_by_name_dob("Cheryl Lynn Bond", "1960-05-04").await.unwrap(),
+            Some(first.patient_id.clone())
+        );
+        assert_eq!(
+            backend.find_patient_by_name_dob("Cheryl Lynn Bond", "1960-05-05").await.unwrap(),
+            None
+        );
+        let patients = backend.search_patients("Cheryl").await.unwrap();
+        assert!(patients.iter().any(|p| p.id == first.patient_id));
+
+        // Prior visits read back what the sync wrote.
+        let encounters = backend.fetch_encounters_for_patient(&first.patient_id, 5).await.unwrap();
+        assert_eq!(encounters.len(), 1);
+        assert_eq!(encounters[0].fhir_id, first.encounter_id);
+        assert_eq!(encounters[0].duration_minutes, Some(15));
+        assert_eq!(
+            backend.fetch_soap_note_for_encounter(&first.encounter_id).await.unwrap().as_deref(),
+            Some("S: follow-up, amended")
+        );
+
+        let condition = serde_json::json!({
+            "resourceType": "Condition",
+            "subject": { "reference": format!("Patient/{}", first.patient_id) },
+            "encounter": { "reference": format!("Encounter/{}", first.encounter_id) },
+        });
+        let reference = backend.create_clinical_resource(&condition).await.unwrap();
+        assert!(reference.starts_with("Condition/"));
+    }
+
+    #[tokio::test]
+    async fn medplum_client_satisfies_the_backend_contract() {
+        let (url, store) = spawn_contract_server().await;
+        let client = MedplumClient::new(&url, "test-client").unwrap();
+        client.inject_proxy_token(CONTRACT_TOKEN.into(), 3600, None).await.unwrap();
+        assert_eq!(client.fhir_base(), format!("{url}/fhir/R4"));
+
+        run_contract(&client).await;
+
+        let store = store.lock().unwrap();
+        assert_eq!(store.token_requests, 0);
+        assert_eq!(store.unauthorized_requests, 0);
+    }
+
+    #[tokio::test]
+    async fn smart_backend_client_satisfies_the_backend_contract() {
+        let (url, store) = spawn_contract_server().await;
+        let client = crate::smart_backend::SmartBackendClient::new(
+            &format!("{url}/fhir/R4"),
+            SMART_CLIENT_ID,
+            crate::smart_backend::tests::TEST_EC_PRIVATE_KEY.as_bytes(),
+            Some("key-1"),
+            "system/*.read system/*.write",
+        )
+        .unwrap();
+
+        run_contract(&client).await;
+
+        let store = store.lock().unwrap();
+        assert_eq!(store.token_requests, 1, "the token is cached across requests");
+        assert_eq!(store.unauthorized_requests, 0);
+    }
+
+    #[tokio::test]
+    async fn smart_backend_client_reports_rejected_credentials() {
+        let (url, _store) = spawn_contract_server().await;
+        let client = crate::smart_backend::SmartBackendClient::new(
+            &format!("{url}/fhir/R4"),
+            "someone-else",
+            crate::smart_backend::tests::TEST_EC_PRIVATE_KEY.as_bytes(),
+            None,
+            "system/*.read",
+        )
+        .unwrap();
+
+        assert!(!client.is_authenticated().await);
+        let err = client.search_patients("Cheryl").await.unwrap_err();
+        assert!(matches!(err, MedplumError::ValidationError(_) | MedplumError::AuthError(_)), "{err:?}");
+        assert!(!err.is_retryable());
+    }
+}
@@ -5,6 +5,8 @@
 
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
+use crate::presence_sensor::OccupancyHint;
+
 /// Calculate the dynamic confidence threshold for encounter detection.
 ///
 /// Short encounters (<20 min) use a higher base threshold (0.85) to reduce
@@ -63,8 +65,8 @@ pub const MULTI_PATIENT_DETECT_TIMEOUT_SECS: u64 = 180;
 pub const ENCOUNTER_DETECTION_TIMEOUT_SECS: u64 = 180;
 
 /// Optional context signals for encounter detection.
-/// Provides real-time signals from sensor (departure/presence) to augment
-/// the LLM prompt. Vision-extracted patient names are used only for metadata
+/// Provides real-time signals from sensor (departure/presence/head count) to
+/// augment the LLM prompt. Vision-extracted patient names are used only for metadata
 /// labeling, NOT for split decisions (EMR chart name is unreliable — doctor
 /// may open family members, not open chart, or vision may parse same name
 /// differently).
@@ -74,6 +76,8 @@ pub struct EncounterDetectionContext {
     pub sensor_departed: bool,
     /// Whether the presence sensor confirms someone is still in the room
     pub sensor_present: bool,
+    /// Head-count change seen by the room sensors during this encounter
+    pub occupancy_hint: Option<OccupancyHint>,
 }
 
 /// Result of encounter detection
@@ -283,6 +287,23 @@ Respond with ONLY the JSON object."#.to_string());
             multiple people's medical issues in a single encounter. \
             Only split if there is a clear farewell, departure, AND arrival of a completely unrelated new patient.".to_string());
 
+    let occupancy_text = context.and_then(|ctx| ctx.occupancy_hint).map(|hint| match hint {
+        OccupancyHint::Arrival { baseline, current } => templates
+            .and_then(|t| (!t.encounter_detection_occupancy_arrival.is_empty()).then(|| t.encounter_detection_occupancy_arrival.clone()))
+            .unwrap_or_else(|| "CONTEXT: The room sensors now count {current} people, up from {baseline} earlier in this encounter. \
+                Someone has joined — often a family member or caregiver for the same visit, but it may be \
+                the next patient arriving. Check the transcript for a second patient being seen; \
+                a new person alone is NOT a transition.".to_string())
+            .replace("{baseline}", &baseline.to_string())
+            .replace("{current}", &current.to_string()),
+        OccupancyHint::OnlyOneRemains { peak } => templates
+            .and_then(|t| (!t.encounter_detection_occupancy_one_remains.is_empty()).then(|| t.encounter_detection_occupancy_one_remains.clone()))
+            .unwrap_or_else(|| "CONTEXT: The room sensors count one person now, down from {peak} earlier in this encounter — \
+                everyone except (likely) the physician has left. If the transcript also shows the visit \
+                concluding (plan, farewell), the encounter has likely ended.".to_string())
+            .replace("{peak}", &peak.to_string()),
+    });
+
     // Build context section if signals are available
     let context_section = if let Some(ctx) = context {
         let mut parts = Vec::new();
@@ -294,6 +315,10 @@ Respond with ONLY the JSON object."#.to_string());
         if ctx.sensor_present && !ctx.sensor_departed {
             parts.push(sensor_present_text);
         }
+        // Head-count change — a hint for the LLM, never a split on its own
+        if let Some(text) = occupancy_text {
+            parts.push(text);
+        }
         if parts.is_empty() {
             String::new()
         } else {
@@ -1007,6 +1032,7 @@ mod tests {
         let ctx = EncounterDetectionContext {
             sensor_departed: true,
             sensor_present: false,
+            occupancy_hint: None,
         };
         let (_, user) = build_encounter_detection_prompt("test transcript", Some(&ctx), None);
         assert!(user.contains("presence sensor"), "User prompt should mention sensor departure");
@@ -1017,6 +1043,7 @@ mod tests {
         let ctx = EncounterDetectionContext {
             sensor_departed: false,
             sensor_present: true,
+            occupancy_hint: None,
         };
         let (_, user) = build_encounter_detection_prompt("test transcript", Some(&ctx), None);
         assert!(user.contains("still in the room"), "User prompt should mention sensor presence");
@@ -1028,12 +1055,52 @@ mod tests {
         let ctx = EncounterDetectionContext {
             sensor_departed: false,
             sensor_present: false,
+            occupancy_hint: None,
         };
         let (_, user) = build_encounter_detection_prompt("test transcript", Some(&ctx), None);
         // No sensor signals — no context section
         assert!(!user.contains("presence sensor"), "No sensor signal should be present");
     }
 
+    #[test]
+    fn test_detection_prompt_with_occupancy_arrival() {
+        let ctx = EncounterDetectionContext {
+            sensor_present: true,
+            occupancy_hint: Some(OccupancyHint::Arrival { baseline: 2, current: 3 }),
+            ..Default::default()
+        };
+        let (_, user) = build_encounter_detection_prompt("test transcript", Some(&ctx), None);
+        assert!(user.contains("count 3 people, up from 2"), "{}", user);
+        assert!(user.contains("second patient"));
+        assert!(user.contains("still in the room"), "Presence text is kept alongside the hint");
+    }
+
+    #[test]
+    fn test_detection_prompt_with_occupancy_one_remains() {
+        let ctx = EncounterDetectionContext {
+            sensor_present: true,
+            occupancy_hint: Some(OccupancyHint::OnlyOneRemains { peak: 2 }),
+            ..Default::default()
+        };
+        let (_, user) = build_encounter_detection_prompt("test transcript", Some(&ctx), None);
+        assert!(user.contains("down from 2"), "{}", user);
+        assert!(user.contains("likely ended"));
+    }
+
+    #[test]
+    fn test_detection_prompt_occupancy_template_override() {
+        let templates = crate::server_config::PromptTemplates {
+            encounter_detection_occupancy_arrival: "HEADCOUNT {baseline}->{current}".into(),
+            ..Default::default()
+        };
+        let ctx = EncounterDetectionContext {
+            occupancy_hint: Some(OccupancyHint::Arrival { baseline: 1, current: 2 }),
+            ..Default::default()
+        };
+        let (_, user) = build_encounter_detection_prompt("t", Some(&ctx), Some(&templates));
+        assert!(user.contains("HEADCOUNT 1->2"), "{}", user);
+    }
+
     // ── Clinical content check tests ─────────────────────────────
 
     #[test]
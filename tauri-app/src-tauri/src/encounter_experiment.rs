@@ -489,6 +489,7 @@ pub fn build_detection_prompt(
                 Some(crate::encounter_detection::EncounterDetectionContext {
                     sensor_departed: config.sensor_departed,
                     sensor_present: config.sensor_present,
+                    occupancy_hint: None,
                 })
             } else {
                 None
@@ -1838,6 +1839,7 @@ mod tests {
         let ctx = crate::encounter_detection::EncounterDetectionContext {
             sensor_departed: true,
             sensor_present: false,
+            occupancy_hint: None,
         };
         let (sys_prod, user_prod) =
             build_encounter_detection_prompt("[0] (Speaker 1): hello", Some(&ctx), None);
@@ -1861,6 +1863,7 @@ mod tests {
         let ctx = crate::encounter_detection::EncounterDetectionContext {
             sensor_departed: false,
             sensor_present: true,
+            occupancy_hint: None,
         };
         let (sys_prod, user_prod) =
             build_encounter_detection_prompt("[0] (Speaker 1): hello", Some(&ctx), None);
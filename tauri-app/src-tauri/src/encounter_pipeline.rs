@@ -132,14 +132,34 @@ pub async fn generate_and_archive_soap(
     // Pass the resolved `soap_model` alias (per ADR; `soap-model` has vision
     // capabilities). Ignored when `screenshot_paths` is None or empty.
     vision_model: &str,
+    // Sources for a confirmed patient's prior visits (local archive, then
+    // profile-service, then Medplum). `None` skips the lookup — tests and
+    // replay tools pass `None`; continuous mode passes its sync context.
+    prior_visit_sources: Option<&crate::server_sync::ServerSyncContext>,
 ) -> SoapGenerationOutcome {
     let soap_timeout = soap_timeout_override.unwrap_or(SOAP_GENERATION_TIMEOUT_SECS);
     let effective_detail = effective_soap_detail_level(soap_detail_level, word_count);
+    // Single-patient notes only: the per-patient fan-out would attach one
+    // patient's history to every sibling's note.
+    let prior_visits = match prior_visit_sources {
+        Some(sources) if multi_patient_detection.is_none() => {
+            crate::prior_visits::load_prior_visits(session_id, session_date, sources).await
+        }
+        _ => None,
+    };
+    let mut log_extra = log_extra;
+    if let Some(obj) = log_extra.as_object_mut() {
+        obj.insert(
+            "prior_visit_count".into(),
+            serde_json::json!(prior_visits.as_ref().map_or(0, |c| c.visit_ids.len())),
+        );
+    }
     let soap_opts = SoapOptions {
         detail_level: effective_detail,
         format: SoapFormat::from_config_str(soap_format),
         custom_instructions: soap_custom_instructions.to_string(),
         session_notes,
+        prior_visits: prior_visits.as_ref().map(|c| c.block.clone()).unwrap_or_default(),
         ..Default::default()
     };
     let soap_system_prompt = build_simple_soap_prompt(&soap_opts, templates);
@@ -147,12 +167,14 @@ pub async fn generate_and_archive_soap(
     // Timeout) have user_prompt — the tokio::time::timeout Elapsed case can't
     // recover it from the cancelled future. Match `audio_events=None` and
     // `speaker_context=None` against the call below to keep the two in sync;
-    // build_soap_user_content drops empty/whitespace session_notes itself.
+    // build_soap_user_content drops empty/whitespace session_notes and
+    // prior_visits itself.
     let soap_user_prompt = build_soap_user_content(
         filtered_text,
         None,
         Some(soap_opts.session_notes.as_str()),
         None,
+        Some(soap_opts.prior_visits.as_str()),
     );
 
     let soap_start = Instant::now();
@@ -230,6 +252,14 @@ pub async fn generate_and_archive_soap(
                     Some(soap_format),
                 ) {
                     warn!("Failed to save SOAP for session {}: {}", session_id, e);
+                } else if let Some(ref ctx) = prior_visits {
+                    if let Err(e) = local_archive::record_prior_visits_context(
+                        session_id,
+                        session_date,
+                        &ctx.visit_ids,
+                    ) {
+                        warn!(session_id = %session_id, error = %e, "Failed to record prior-visit context");
+                    }
                 }
                 Vec::new()
             };
@@ -701,7 +731,7 @@ pub async fn extract_and_archive_billing(
 /// `start_marker` is absent. Markers are sourced from `llm_client::SOAP_SECTION_*`
 /// so renderer changes propagate. Tolerates marker variants with/without
 /// trailing newline.
-fn extract_soap_section(soap: &str, start_marker: &str, end_marker: Option<&str>) -> String {
+pub(crate) fn extract_soap_section(soap: &str, start_marker: &str, end_marker: Option<&str>) -> String {
     let start_trim = start_marker.trim_end_matches('\n');
     let Some(idx) = soap.find(start_marker).or_else(|| soap.find(start_trim)) else {
         return String::new();
@@ -1385,6 +1415,7 @@ pub async fn recover_orphaned_soap(
             None, // orphan recovery has no replay bundle
             orphan_screenshot_arg,
             vision_model,
+            Some(sync_ctx),
         )
         .await;
 
@@ -1534,6 +1565,7 @@ pub async fn regen_soap_after_merge(
         None, // merge-regen path: bundle of merging-into session is finalized later via build_merged_and_reset
         merge_screenshot_arg,
         vision_model,
+        Some(sync_ctx),
     )
     .await;
 
@@ -1642,9 +1674,13 @@ mod tests {
             has_billing_record: None,
             patient_confirmed_at: None,
             medplum_patient_id: None,
+            medplum_encounter_id: None,
+            medplum_structured_resource_ids: None,
             has_clinician_notes: false,
             soap_prompt_version: None,
             billing_prompt_version: None,
+            prior_visits_context_version: None,
+            prior_visit_ids: None,
             sibling_group_id: None,
             sibling_index: None,
             sibling_group_size: None,
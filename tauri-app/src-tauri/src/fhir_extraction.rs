@@ -0,0 +1,734 @@
+//! Structured FHIR resources from a finalized encounter.
+//!
+//! Medplum sync only carries the SOAP note as a text DocumentReference, so
+//! nothing clinically meaningful is queryable in the EMR. This module turns
+//! what the clinician has already signed off on into coded resources:
+//!
+//! - **Condition**: the billing record's resolved OHIP diagnostic code, with
+//!   an ICD-10 coding when `diagnostic_codes::ICD10_CROSSWALK` has one.
+//! - **MedicationStatement**: one per entry of the clinician-confirmed
+//!   medication list (`medication_extraction::MedEntry`).
+//! - **Observation**: vital signs written in the SOAP Objective section
+//!   (BP, HR, RR, temperature, SpO2, weight, height, BMI).
+//!
+//! Drafts are returned to the History Window for preview and come back
+//! (possibly edited) through `medplum_upload_structured_resources`. They are
+//! re-validated before anything is built from them. Only
+//! [`upload_structured_resources`] talks to the network.
+
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::billing::diagnostic_codes;
+use crate::billing::BillingRecord;
+use crate::llm_client::{SOAP_SECTION_ASSESSMENT, SOAP_SECTION_OBJECTIVE};
+use crate::emr_backend::EmrBackend;
+use crate::medication_extraction::MedEntry;
+
+/// Coding system for OHIP 3-digit diagnostic codes
+pub const OHIP_DIAGNOSTIC_SYSTEM: &str = "urn:fabricscribe:ohip-diagnostic-code";
+/// Coding system for WHO ICD-10 categories
+pub const ICD10_SYSTEM: &str = "http://hl7.org/fhir/sid/icd-10";
+const LOINC_SYSTEM: &str = "http://loinc.org";
+const UCUM_SYSTEM: &str = "http://unitsofmeasure.org";
+
+/// A Condition for the encounter's resolved diagnostic code
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConditionDraft {
+    /// 3-digit OHIP diagnostic code
+    pub ohip_code: String,
+    pub description: String,
+    /// ICD-10 category from the crosswalk, when the mapping is unambiguous
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icd10_code: Option<String>,
+    /// SOAP quote supporting the code, carried over from the billing record
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub evidence: Option<String>,
+}
+
+impl ConditionDraft {
+    /// Draft from a billing record's diagnostic code. `None` when the record
+    /// has no code or the code is not in the OHIP database.
+    pub fn from_billing(record: &BillingRecord) -> Option<Self> {
+        let code = record.diagnostic_code.as_deref()?.trim();
+        let dc = diagnostic_codes::get_diagnostic_code(code)?;
+        Some(Self {
+            ohip_code: dc.code.to_string(),
+            description: dc.description.to_string(),
+            icd10_code: diagnostic_codes::icd10_for_diagnostic_code(dc.code).map(String::from),
+            evidence: record
+                .diagnostic_evidence
+                .as_deref()
+                .map(str::trim)
+                .filter(|e| !e.is_empty())
+                .map(String::from),
+        })
+    }
+}
+
+/// A MedicationStatement for one confirmed medication
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MedicationStatementDraft {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dose: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frequency: Option<String>,
+}
+
+impl From<&MedEntry> for MedicationStatementDraft {
+    fn from(entry: &MedEntry) -> Self {
+        Self {
+            name: entry.name.trim().to_string(),
+            dose: entry.dose.clone(),
+            frequency: entry.frequency.clone(),
+        }
+    }
+}
+
+/// Vital signs recognized in the Objective section
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VitalSign {
+    BloodPressure,
+    HeartRate,
+    RespiratoryRate,
+    BodyTemperature,
+    OxygenSaturation,
+    BodyWeight,
+    BodyHeight,
+    Bmi,
+}
+
+impl VitalSign {
+    const ALL: [VitalSign; 8] = [
+        VitalSign::BloodPressure,
+        VitalSign::HeartRate,
+        VitalSign::RespiratoryRate,
+        VitalSign::BodyTemperature,
+        VitalSign::OxygenSaturation,
+        VitalSign::BodyWeight,
+        VitalSign::BodyHeight,
+        VitalSign::Bmi,
+    ];
+
+    /// Lowercase labels clinicians write before the value
+    fn labels(self) -> &'static [&'static str] {
+        match self {
+            VitalSign::BloodPressure => &["blood pressure", "bp"],
+            VitalSign::HeartRate => &["heart rate", "pulse", "hr"],
+            VitalSign::RespiratoryRate => &["respiratory rate", "resp rate", "rr"],
+            VitalSign::BodyTemperature => &["temperature", "temp"],
+            VitalSign::OxygenSaturation => &["oxygen saturation", "o2 sat", "spo2", "sats"],
+            VitalSign::BodyWeight => &["weight", "wt"],
+            VitalSign::BodyHeight => &["height", "ht"],
+            VitalSign::Bmi => &["bmi"],
+        }
+    }
+
+    /// LOINC code and display from the FHIR vital-signs profile
+    fn loinc(self) -> (&'static str, &'static str) {
+        match self {
+            VitalSign::BloodPressure => ("85354-9", "Blood pressure panel with all children optional"),
+            VitalSign::HeartRate => ("8867-4", "Heart rate"),
+            VitalSign::RespiratoryRate => ("9279-1", "Respiratory rate"),
+            VitalSign::BodyTemperature => ("8310-5", "Body temperature"),
+            VitalSign::OxygenSaturation => ("2708-6", "Oxygen saturation in Arterial blood"),
+            VitalSign::BodyWeight => ("29463-7", "Body weight"),
+            VitalSign::BodyHeight => ("8302-2", "Body height"),
+            VitalSign::Bmi => ("39156-5", "Body mass index (BMI) [Ratio]"),
+        }
+    }
+}
+
+/// One vital sign read from the Objective section
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VitalSignDraft {
+    pub kind: VitalSign,
+    /// The value, or the systolic pressure for blood pressure
+    pub value: f64,
+    /// Diastolic pressure; blood pressure only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diastolic: Option<f64>,
+    /// UCUM unit code
+    pub unit: String,
+    /// The text the value was read from, shown in the preview
+    pub source_text: String,
+}
+
+impl VitalSignDraft {
+    /// True when the value and unit are physiologically possible. Guards
+    /// against misreads ("HR 24 hours") and edits in the preview.
+    pub fn is_plausible(&self) -> bool {
+        let in_range = |lo: f64, hi: f64| (lo..=hi).contains(&self.value);
+        match (self.kind, self.unit.as_str()) {
+            (VitalSign::BloodPressure, "mm[Hg]") => {
+                in_range(50.0, 300.0)
+                    && self
+                        .diastolic
+                        .is_some_and(|d| (20.0..=200.0).contains(&d) && d < self.value)
+            }
+            (VitalSign::HeartRate, "/min") => in_range(20.0, 300.0),
+            (VitalSign::RespiratoryRate, "/min") => in_range(4.0, 80.0),
+            (VitalSign::BodyTemperature, "Cel") => in_range(30.0, 45.0),
+            (VitalSign::BodyTemperature, "[degF]") => in_range(86.0, 113.0),
+            (VitalSign::OxygenSaturation, "%") => in_range(50.0, 100.0),
+            (VitalSign::BodyWeight, "kg") => in_range(0.5, 500.0),
+            (VitalSign::BodyWeight, "[lb_av]") => in_range(1.0, 1100.0),
+            (VitalSign::BodyHeight, "cm") => in_range(30.0, 250.0),
+            (VitalSign::BodyHeight, "[in_i]") => in_range(12.0, 100.0),
+            (VitalSign::Bmi, "kg/m2") => in_range(8.0, 100.0),
+            _ => false,
+        }
+    }
+}
+
+/// Everything proposed for upload, shown to the clinician before upload
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructuredResourcesPreview {
+    pub conditions: Vec<ConditionDraft>,
+    pub medication_statements: Vec<MedicationStatementDraft>,
+    pub observations: Vec<VitalSignDraft>,
+}
+
+impl StructuredResourcesPreview {
+    pub fn is_empty(&self) -> bool {
+        self.conditions.is_empty()
+            && self.medication_statements.is_empty()
+            && self.observations.is_empty()
+    }
+
+    /// Check a preview returned by the UI. Descriptions and ICD-10 codes are
+    /// re-derived from the code database, so only the OHIP code itself is
+    /// trusted from the client.
+    pub fn validated(mut self) -> Result<Self, String> {
+        for condition in &mut self.conditions {
+            let dc = diagnostic_codes::get_diagnostic_code(condition.ohip_code.trim())
+                .ok_or_else(|| format!("Unknown OHIP diagnostic code: {}", condition.ohip_code))?;
+            condition.ohip_code = dc.code.to_string();
+            condition.description = dc.description.to_string();
+            condition.icd10_code = diagnostic_codes::icd10_for_diagnostic_code(dc.code).map(String::from);
+        }
+        for med in &mut self.medication_statements {
+            med.name = med.name.trim().to_string();
+            if med.name.is_empty() {
+                return Err("Medication name must not be empty".into());
+            }
+        }
+        if let Some(vital) = self.observations.iter().find(|v| !v.is_plausible()) {
+            return Err(format!(
+                "Implausible {:?} value: {} {}",
+                vital.kind, vital.value, vital.unit
+            ));
+        }
+        Ok(self)
+    }
+}
+
+/// Build the preview for one encounter from its archived SOAP note, billing
+/// record and the clinician-confirmed medication list.
+pub fn extract_structured_resources(
+    soap: &str,
+    billing: Option<&BillingRecord>,
+    medications: &[MedEntry],
+) -> StructuredResourcesPreview {
+    let objective = crate::encounter_pipeline::extract_soap_section(
+        soap,
+        SOAP_SECTION_OBJECTIVE,
+        Some(SOAP_SECTION_ASSESSMENT),
+    );
+    StructuredResourcesPreview {
+        conditions: billing.and_then(ConditionDraft::from_billing).into_iter().collect(),
+        medication_statements: medications
+            .iter()
+            .filter(|m| !m.name.trim().is_empty())
+            .map(MedicationStatementDraft::from)
+            .collect(),
+        observations: parse_vital_signs(&objective),
+    }
+}
+
+/// Read vital signs out of free text. Takes the first plausible reading of
+/// each kind; weights and heights without a unit are skipped rather than
+/// guessed.
+pub fn parse_vital_signs(text: &str) -> Vec<VitalSignDraft> {
+    let lower = text.to_lowercase();
+    VitalSign::ALL
+        .iter()
+        .filter_map(|&kind| {
+            kind.labels().iter().find_map(|label| {
+                label_positions(&lower, label)
+                    .filter_map(|end| read_vital(kind, &lower[end..]))
+                    .find(VitalSignDraft::is_plausible)
+            })
+        })
+        .collect()
+}
+
+/// Byte offsets just past each whole-word occurrence of `label`
+fn label_positions<'a>(text: &'a str, label: &'a str) -> impl Iterator<Item = usize> + 'a {
+    text.match_indices(label).filter_map(move |(start, _)| {
+        let end = start + label.len();
+        let before_ok = !text[..start].chars().next_back().is_some_and(|c| c.is_alphanumeric());
+        let after_ok = !text[end..].chars().next().is_some_and(|c| c.is_alphanumeric());
+        (before_ok && after_ok).then_some(end)
+    })
+}
+
+/// Parse the value following a label, e.g. `": 142/88 mmhg"` or
+/// `" of 36.8°c"`.
+fn read_vital(kind: VitalSign, after_label: &str) -> Option<VitalSignDraft> {
+    let mut rest = after_label.trim_start_matches(|c: char| c.is_whitespace() || c == ':' || c == '=');
+    for filler in ["was ", "is ", "of ", "at "] {
+        rest = rest.strip_prefix(filler).unwrap_or(rest).trim_start();
+    }
+    let (value, consumed) = leading_number(rest)?;
+    let mut tail = &rest[consumed..];
+
+    let mut diastolic = None;
+    if kind == VitalSign::BloodPressure {
+        let (d, used) = leading_number(tail.strip_prefix('/')?)?;
+        diastolic = Some(d);
+        tail = &tail[1 + used..];
+    }
+
+    let unit_text = tail.trim_start().trim_start_matches('°');
+    let starts = |unit: &str| {
+        unit_text.starts_with(unit)
+            && !unit_text[unit.len()..].chars().next().is_some_and(|c| c.is_alphabetic())
+    };
+    let unit = match kind {
+        VitalSign::BloodPressure => "mm[Hg]",
+        VitalSign::HeartRate | VitalSign::RespiratoryRate => "/min",
+        VitalSign::OxygenSaturation => "%",
+        VitalSign::Bmi => "kg/m2",
+        VitalSign::BodyTemperature if starts("f") => "[degF]",
+        VitalSign::BodyTemperature if starts("c") => "Cel",
+        VitalSign::BodyTemperature if value > 45.0 => "[degF]",
+        VitalSign::BodyTemperature => "Cel",
+        VitalSign::BodyWeight if starts("kg") => "kg",
+        VitalSign::BodyWeight if starts("lb") || starts("lbs") || starts("pounds") => "[lb_av]",
+        VitalSign::BodyHeight if starts("cm") => "cm",
+        VitalSign::BodyHeight if starts("in") || starts("inches") => "[in_i]",
+        VitalSign::BodyWeight | VitalSign::BodyHeight => return None,
+    };
+
+    let source_len = after_label.len() - tail.len();
+    Some(VitalSignDraft {
+        kind,
+        value,
+        diastolic,
+        unit: unit.to_string(),
+        source_text: after_label[..source_len].trim().to_string(),
+    })
+}
+
+/// A leading decimal number and the bytes it used
+fn leading_number(s: &str) -> Option<(f64, usize)> {
+    let len = s
+        .char_indices()
+        .take_while(|(i, c)| c.is_ascii_digit() || (*c == '.' && *i > 0))
+        .count();
+    let digits = s[..len].trim_end_matches('.');
+    if digits.is_empty() {
+        return None;
+    }
+    digits.parse().ok().map(|v| (v, digits.len()))
+}
+
+/// References shared by every resource built for one encounter
+#[derive(Debug, Clone)]
+pub struct EncounterLink<'a> {
+    /// Local archive session ID, recorded as the resource identifier
+    pub session_id: &'a str,
+    pub patient_fhir_id: &'a str,
+    pub encounter_fhir_id: &'a str,
+    /// RFC3339 start of the visit; used as the clinical effective time
+    pub effective: &'a str,
+}
+
+impl EncounterLink<'_> {
+    fn base(&self, resource_type: &str) -> serde_json::Value {
+        serde_json::json!({
+            "resourceType": resource_type,
+            "identifier": [{
+                "system": "urn:fabricscribe:encounter",
+                "value": self.session_id
+            }],
+            "subject": {
+                "reference": format!("Patient/{}", self.patient_fhir_id)
+            },
+            "meta": {
+                "tag": [{
+                    "system": "urn:fabricscribe",
+                    "code": "scribe-session"
+                }]
+            }
+        })
+    }
+
+    fn encounter_ref(&self) -> serde_json::Value {
+        serde_json::json!({ "reference": format!("Encounter/{}", self.encounter_fhir_id) })
+    }
+}
+
+/// FHIR R4 resources for a validated preview, all linked to the encounter
+pub fn to_fhir_resources(
+    preview: &StructuredResourcesPreview,
+    link: &EncounterLink<'_>,
+) -> Vec<serde_json::Value> {
+    let mut out = Vec::new();
+
+    for condition in &preview.conditions {
+        let mut coding = vec![serde_json::json!({
+            "system": OHIP_DIAGNOSTIC_SYSTEM,
+            "code": condition.ohip_code,
+            "display": condition.description
+        })];
+        if let Some(icd10) = &condition.icd10_code {
+            coding.push(serde_json::json!({ "system": ICD10_SYSTEM, "code": icd10 }));
+        }
+        let mut resource = link.base("Condition");
+        resource["clinicalStatus"] = serde_json::json!({
+            "coding": [{
+                "system": "http://terminology.hl7.org/CodeSystem/condition-clinical",
+                "code": "active"
+            }]
+        });
+        resource["verificationStatus"] = serde_json::json!({
+            "coding": [{
+                "system": "http://terminology.hl7.org/CodeSystem/condition-ver-status",
+                "code": "confirmed"
+            }]
+        });
+        resource["category"] = serde_json::json!([{
+            "coding": [{
+                "system": "http://terminology.hl7.org/CodeSystem/condition-category",
+                "code": "encounter-diagnosis",
+                "display": "Encounter Diagnosis"
+            }]
+        }]);
+        resource["code"] = serde_json::json!({ "coding": coding, "text": condition.description });
+        resource["encounter"] = link.encounter_ref();
+        resource["recordedDate"] = serde_json::json!(link.effective);
+        if let Some(evidence) = &condition.evidence {
+            resource["note"] = serde_json::json!([{ "text": evidence }]);
+        }
+        out.push(resource);
+    }
+
+    for med in &preview.medication_statements {
+        let mut resource = link.base("MedicationStatement");
+        resource["status"] = serde_json::json!("active");
+        resource["medicationCodeableConcept"] = serde_json::json!({ "text": med.name });
+        resource["context"] = link.encounter_ref();
+        resource["dateAsserted"] = serde_json::json!(link.effective);
+        let dosage = [med.dose.as_deref(), med.frequency.as_deref()]
+            .into_iter()
+            .flatten()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        if !dosage.is_empty() {
+            resource["dosage"] = serde_json::json!([{ "text": dosage }]);
+        }
+        out.push(resource);
+    }
+
+    for vital in &preview.observations {
+        let (code, display) = vital.kind.loinc();
+        let mut coding = vec![serde_json::json!({
+            "system": LOINC_SYSTEM,
+            "code": code,
+            "display": display
+        })];
+        if vital.kind == VitalSign::OxygenSaturation {
+            coding.push(serde_json::json!({
+                "system": LOINC_SYSTEM,
+                "code": "59408-5",
+                "display": "Oxygen saturation in Arterial blood by Pulse oximetry"
+            }));
+        }
+        let mut resource = link.base("Observation");
+        resource["status"] = serde_json::json!("final");
+        resource["category"] = serde_json::json!([{
+            "coding": [{
+                "system": "http://terminology.hl7.org/CodeSystem/observation-category",
+                "code": "vital-signs",
+                "display": "Vital Signs"
+            }]
+        }]);
+        resource["code"] = serde_json::json!({ "coding": coding, "text": display });
+        resource["encounter"] = link.encounter_ref();
+        resource["effectiveDateTime"] = serde_json::json!(link.effective);
+        match vital.diastolic {
+            Some(diastolic) if vital.kind == VitalSign::BloodPressure => {
+                let component = |code: &str, display: &str, value: f64| {
+                    serde_json::json!({
+                        "code": { "coding": [{ "system": LOINC_SYSTEM, "code": code, "display": display }] },
+                        "valueQuantity": quantity(value, &vital.unit)
+                    })
+                };
+                resource["component"] = serde_json::json!([
+                    component("8480-6", "Systolic blood pressure", vital.value),
+                    component("8462-4", "Diastolic blood pressure", diastolic),
+                ]);
+            }
+            _ => resource["valueQuantity"] = quantity(vital.value, &vital.unit),
+        }
+        out.push(resource);
+    }
+
+    out
+}
+
+fn quantity(value: f64, ucum: &str) -> serde_json::Value {
+    serde_json::json!({ "value": value, "unit": ucum, "system": UCUM_SYSTEM, "code": ucum })
+}
+
+/// Outcome of uploading a confirmed preview
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructuredResourcesUploadResult {
+    /// `<ResourceType>/<id>` for each resource the server created
+    pub created: Vec<String>,
+    /// Non-fatal per-resource errors. Empty on full success.
+    pub errors: Vec<String>,
+}
+
+/// Create each resource on the server. Fail-open per resource, like
+/// `sync_continuous_session`: one rejected Observation does not stop the
+/// Conditions from landing.
+pub async fn upload_structured_resources(
+    client: &dyn EmrBackend,
+    resources: &[serde_json::Value],
+) -> StructuredResourcesUploadResult {
+    let mut result = StructuredResourcesUploadResult::default();
+    for resource in resources {
+        let resource_type = resource["resourceType"].as_str().unwrap_or("resource");
+        match client.create_clinical_resource(resource).await {
+            Ok(reference) => result.created.push(reference),
+            Err(e) => {
+                warn!(event = "structured_fhir_resource_failed", resource_type, error = %e);
+                result.errors.push(format!("{resource_type}: {e}"));
+            }
+        }
+    }
+    info!(
+        event = "structured_fhir_upload",
+        created = result.created.len(),
+        failed = result.errors.len(),
+        "structured FHIR resources uploaded"
+    );
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::medplum::MedplumClient;
+    use std::sync::{Arc, Mutex};
+
+    const SOAP: &str = "S:\nFollow-up for blood pressure.\n\nO:\n• BP 142/88, HR 76 bpm, Temp 36.8°C\n• SpO2 98% on room air, RR: 16\n• Weight 82.5 kg, BMI 27.1\n\nA:\n• Essential hypertension, suboptimally controlled\n\nP:\n• Increase ramipril to 10 mg daily";
+
+    fn billing(code: Option<&str>) -> BillingRecord {
+        serde_json::from_value(serde_json::json!({
+            "sessionId": "s1",
+            "date": "2026-10-01",
+            "patientName": null,
+            "status": "confirmed",
+            "codes": [],
+            "timeEntries": [],
+            "totalShadowCents": 0,
+            "totalOutOfBasketCents": 0,
+            "totalTimeBasedCents": 0,
+            "totalAmountCents": 0,
+            "confirmedAt": null,
+            "notes": null,
+            "extractionModel": null,
+            "extractedAt": null,
+            "diagnosticCode": code,
+            "diagnosticEvidence": "BP 142/88 on ramipril 5 mg"
+        }))
+        .unwrap()
+    }
+
+    fn vital(vitals: &[VitalSignDraft], kind: VitalSign) -> &VitalSignDraft {
+        vitals.iter().find(|v| v.kind == kind).expect("vital present")
+    }
+
+    #[test]
+    fn parses_vitals_from_objective_text() {
+        let vitals = parse_vital_signs(
+            "BP 142/88, HR 76 bpm, Temp 36.8°C\nSpO2 98% on room air, RR: 16\nWeight 82.5 kg, BMI 27.1",
+        );
+        assert_eq!(vitals.len(), 7);
+        let bp = vital(&vitals, VitalSign::BloodPressure);
+        assert_eq!((bp.value, bp.diastolic), (142.0, Some(88.0)));
+        assert_eq!(bp.source_text, "142/88");
+        assert_eq!(vital(&vitals, VitalSign::HeartRate).value, 76.0);
+        assert_eq!(vital(&vitals, VitalSign::BodyTemperature).unit, "Cel");
+        assert_eq!(vital(&vitals, VitalSign::OxygenSaturation).value, 98.0);
+        assert_eq!(vital(&vitals, VitalSign::RespiratoryRate).value, 16.0);
+        assert_eq!(vital(&vitals, VitalSign::BodyWeight).value, 82.5);
+        assert_eq!(vital(&vitals, VitalSign::Bmi).value, 27.1);
+    }
+
+    #[test]
+    fn skips_implausible_and_unitless_readings() {
+        let vitals = parse_vital_signs(
+            "24 hr urine pending. Pulse 72. Temperature of 98.6 F. Weight 180. Height 5'10\". BP not taken",
+        );
+        assert_eq!(vital(&vitals, VitalSign::HeartRate).value, 72.0);
+        assert_eq!(vital(&vitals, VitalSign::BodyTemperature).unit, "[degF]");
+        assert!(vitals.iter().all(|v| !matches!(
+            v.kind,
+            VitalSign::BodyWeight | VitalSign::BodyHeight | VitalSign::BloodPressure
+        )));
+        // "hr" inside "24 hr urine" is followed by no number and "HRT" is not a label
+        assert!(parse_vital_signs("On HRT 1 mg daily").is_empty());
+    }
+
+    #[test]
+    fn preview_combines_billing_meds_and_vitals() {
+        let meds = vec![
+            MedEntry { name: "Ramipril".into(), dose: Some("10 mg".into()), frequency: Some("daily".into()) },
+            MedEntry { name: "  ".into(), dose: None, frequency: None },
+        ];
+        let preview = extract_structured_resources(SOAP, Some(&billing(Some("401"))), &meds);
+        assert_eq!(preview.conditions.len(), 1);
+        assert_eq!(preview.conditions[0].icd10_code.as_deref(), Some("I10"));
+        assert_eq!(preview.medication_statements.len(), 1);
+        assert_eq!(preview.observations.len(), 7);
+
+        let without_code = extract_structured_resources(SOAP, Some(&billing(None)), &[]);
+        assert!(without_code.conditions.is_empty());
+        assert!(extract_structured_resources("", None, &[]).is_empty());
+    }
+
+    #[test]
+    fn validation_rederives_codes_and_rejects_edits_out_of_range() {
+        let mut preview = extract_structured_resources(SOAP, Some(&billing(Some("401"))), &[]);
+        preview.conditions[0].description = "edited".into();
+        preview.conditions[0].icd10_code = Some("Z99".into());
+        let checked = preview.clone().validated().unwrap();
+        assert_eq!(checked.conditions[0].icd10_code.as_deref(), Some("I10"));
+        assert_ne!(checked.conditions[0].description, "edited");
+
+        let mut bad_code = preview.clone();
+        bad_code.conditions[0].ohip_code = "000".into();
+        assert!(bad_code.validated().is_err());
+
+        let mut bad_vital = preview;
+        bad_vital.observations[0].value = 900.0;
+        assert!(bad_vital.validated().is_err());
+    }
+
+    #[test]
+    fn fhir_resources_are_coded_and_linked_to_the_encounter() {
+        let meds = vec![MedEntry { name: "Ramipril".into(), dose: Some("10 mg".into()), frequency: None }];
+        let preview = extract_structured_resources(SOAP, Some(&billing(Some("401"))), &meds);
+        let link = EncounterLink {
+            session_id: "s1",
+            patient_fhir_id: "pat-1",
+            encounter_fhir_id: "enc-1",
+            effective: "2026-10-01T14:00:00+00:00",
+        };
+        let resources = to_fhir_resources(&preview, &link);
+        assert_eq!(resources.len(), 9);
+
+        let condition = &resources[0];
+        assert_eq!(condition["resourceType"], "Condition");
+        assert_eq!(condition["code"]["coding"][0]["system"], OHIP_DIAGNOSTIC_SYSTEM);
+        assert_eq!(condition["code"]["coding"][1]["code"], "I10");
+        assert_eq!(condition["encounter"]["reference"], "Encounter/enc-1");
+
+        let med = &resources[1];
+        assert_eq!(med["context"]["reference"], "Encounter/enc-1");
+        assert_eq!(med["dosage"][0]["text"], "10 mg");
+
+        let bp = resources
+            .iter()
+            .find(|r| r["code"]["coding"][0]["code"] == "85354-9")
+            .unwrap();
+        assert_eq!(bp["component"][1]["valueQuantity"]["value"], 88.0);
+        assert!(bp.get("valueQuantity").is_none());
+        for r in &resources {
+            assert_eq!(r["subject"]["reference"], "Patient/pat-1");
+        }
+    }
+
+    /// Minimal FHIR server: accepts POST /fhir/R4/<type>, records the body and
+    /// answers with a server-assigned id. Observations are rejected so the
+    /// fail-open path is exercised.
+    async fn spawn_mock_fhir() -> (String, Arc<Mutex<Vec<serde_json::Value>>>) {
+        use axum::extract::{Path, State};
+        use axum::http::StatusCode;
+        use axum::Json;
+
+        type Received = Arc<Mutex<Vec<serde_json::Value>>>;
+        async fn create(
+            State(received): State<Received>,
+            Path(resource_type): Path<String>,
+            Json(body): Json<serde_json::Value>,
+        ) -> (StatusCode, Json<serde_json::Value>) {
+            if resource_type == "Observation" {
+                return (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({})));
+            }
+            let mut received = received.lock().unwrap();
+            received.push(body);
+            let id = format!("{}-{}", resource_type.to_lowercase(), received.len());
+            (StatusCode::CREATED, Json(serde_json::json!({ "resourceType": resource_type, "id": id })))
+        }
+
+        let received: Received = Arc::default();
+        let app = axum::Router::new()
+            .route("/fhir/R4/:resource_type", axum::routing::post(create))
+            .with_state(received.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (url, received)
+    }
+
+    #[tokio::test]
+    async fn uploads_against_mock_fhir_server_fail_open() {
+        let (url, received) = spawn_mock_fhir().await;
+        let client = MedplumClient::new(&url, "test-client").unwrap();
+        client.inject_proxy_token("token".into(), 3600, None).await.unwrap();
+
+        let meds = vec![MedEntry { name: "Ramipril".into(), dose: None, frequency: None }];
+        let preview = extract_structured_resources(SOAP, Some(&billing(Some("401"))), &meds);
+        let link = EncounterLink {
+            session_id: "s1",
+            patient_fhir_id: "pat-1",
+            encounter_fhir_id: "enc-1",
+            effective: "2026-10-01T14:00:00+00:00",
+        };
+        let result = upload_structured_resources(&client, &to_fhir_resources(&preview, &link)).await;
+
+        assert_eq!(result.created, vec!["Condition/condition-1", "MedicationStatement/medicationstatement-2"]);
+        assert_eq!(result.errors.len(), 7);
+        assert!(result.errors.iter().all(|e| e.starts_with("Observation:")));
+        let received = received.lock().unwrap();
+        assert_eq!(received[0]["encounter"]["reference"], "Encounter/enc-1");
+    }
+
+    #[tokio::test]
+    async fn rejects_non_clinical_resource_types() {
+        let client = MedplumClient::new("http://127.0.0.1:1", "test-client").unwrap();
+        client.inject_proxy_token("token".into(), 3600, None).await.unwrap();
+        let result = upload_structured_resources(
+            &client,
+            &[serde_json::json!({ "resourceType": "Patient" })],
+        )
+        .await;
+        assert!(result.created.is_empty());
+        assert_eq!(result.errors.len(), 1);
+    }
+}
@@ -11,6 +11,7 @@ pub mod policies;
 pub mod mismatch_report;
 pub mod replay_llm_backend;
 pub mod scripted_sensor_source;
+pub mod sensor_timeline;
 pub mod recording_run_context;
 pub mod archive_comparator;
 pub mod event_comparator;
@@ -207,7 +207,8 @@ fn fake_pipeline_handle() -> PipelineHandle {
 /// `compiled_defaults` helper, construct manually using Default where possible.
 fn compiled_default_server_config() -> ServerConfig {
     use crate::server_config::{
-        BillingData, ConfigSource, DetectionThresholds, OperationalDefaults, PromptTemplates,
+        BillingData, ConfigChannel, ConfigSource, DetectionThresholds, OperationalDefaults,
+        PromptTemplates,
     };
     ServerConfig {
         prompts: std::sync::Arc::new(PromptTemplates::default()),
@@ -216,6 +217,7 @@ fn compiled_default_server_config() -> ServerConfig {
         defaults: OperationalDefaults::default(),
         version: 0,
         source: ConfigSource::CompiledDefaults,
+        channel: ConfigChannel::Stable,
     }
 }
 
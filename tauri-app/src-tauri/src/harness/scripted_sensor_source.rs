@@ -11,8 +11,9 @@ use std::time::Duration;
 
 /// Build a MockSource that replays the recorded sensor transitions.
 ///
-/// Transitions with `to == "Present"` become `true`; anything else becomes
-/// `false`. The `delay` for each event is the wall-clock delta from the first
+/// Transitions to Present (any case — production bundles record
+/// `PresenceState::as_str`, i.e. `"present"`) become `true`; anything else
+/// becomes `false`. The `delay` for each event is the wall-clock delta from the first
 /// transition (or 1 second apart if timestamps can't be parsed).
 pub fn mock_source_from_transitions(transitions: &[SensorTransition]) -> MockSource {
     let base_ts_ms = transitions
@@ -31,7 +32,7 @@ pub fn mock_source_from_transitions(transitions: &[SensorTransition]) -> MockSou
             fallback_offset += Duration::from_secs(1);
             fallback_offset
         };
-        let present = t.to == "Present";
+        let present = t.to.eq_ignore_ascii_case("present");
         sequence.push((offset, present));
     }
 
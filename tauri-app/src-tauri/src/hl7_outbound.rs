@@ -0,0 +1,619 @@
+//! HL7 v2 outbound interface for EMRs that don't accept FHIR.
+//!
+//! Accuro, PS Suite and OSCAR ingest clinical documents as HL7 v2 messages
+//! over MLLP. This module renders a finished SOAP note as an MDM^T02
+//! (document with content) or ORU^R01 (observation result) message and
+//! sends it to the configured host:port, waiting for the receiver's ACK.
+//! Messages that are not acknowledged are persisted and retried by
+//! `hl7_outbox`.
+//!
+//! Patient identity matches the FHIR sync: the confirmed name and DOB, with
+//! the same hashed patient key (`emr_backend::patient_key`) in PID-3.
+
+use crate::config::Settings;
+use crate::emr_backend::patient_key;
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// Document notification with the note as content
+pub const HL7_MDM_T02: &str = "MDM_T02";
+/// Observation result with the note as text observations
+pub const HL7_ORU_R01: &str = "ORU_R01";
+pub const HL7_MESSAGE_TYPES: &[&str] = &[HL7_MDM_T02, HL7_ORU_R01];
+
+/// MSH-3, and the assigning authority for PID-3 and document numbers
+const SENDING_APPLICATION: &str = "FABRICSCRIBE";
+const HL7_VERSION: &str = "2.5";
+/// OBX-3 / OBR-4 for the note: LOINC progress note
+const NOTE_CODE: &str = "11506-3^Progress note^LN";
+
+/// MLLP block delimiters
+const START_BLOCK: u8 = 0x0B;
+const END_BLOCK: u8 = 0x1C;
+const CARRIAGE_RETURN: u8 = 0x0D;
+
+pub(crate) const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// How long the receiver has to ACK after the whole message is written
+pub(crate) const ACK_TIMEOUT: Duration = Duration::from_secs(30);
+/// Larger responses are not ACKs
+const MAX_ACK_BYTES: usize = 64 * 1024;
+
+/// HL7 interface errors
+#[derive(Debug, thiserror::Error)]
+pub enum Hl7Error {
+    #[error("Invalid HL7 message: {0}")]
+    InvalidMessage(String),
+
+    #[error("MLLP connection error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Timed out waiting for {0}")]
+    Timeout(&'static str),
+
+    #[error("Malformed ACK: {0}")]
+    MalformedAck(String),
+
+    #[error("Receiver reported an application error ({code}): {text}")]
+    ApplicationError { code: String, text: String },
+
+    #[error("Receiver rejected the message ({code}): {text}")]
+    Rejected { code: String, text: String },
+}
+
+impl Hl7Error {
+    /// True when resending the same message may succeed: the receiver was
+    /// unreachable, didn't answer, or failed while processing (AE). A reject
+    /// (AR) means the message itself is unacceptable.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            Hl7Error::Io(_)
+                | Hl7Error::Timeout(_)
+                | Hl7Error::MalformedAck(_)
+                | Hl7Error::ApplicationError { .. }
+        )
+    }
+}
+
+/// Where and how to send, from `Settings`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hl7Destination {
+    pub host: String,
+    pub port: u16,
+    pub message_type: String,
+    pub sending_facility: String,
+    pub receiving_application: String,
+    pub receiving_facility: String,
+}
+
+impl Hl7Destination {
+    /// `None` when the interface is disabled or has no host.
+    pub fn from_settings(settings: &Settings) -> Option<Self> {
+        if !settings.hl7_enabled || settings.hl7_host.is_empty() {
+            return None;
+        }
+        Some(Self {
+            host: settings.hl7_host.clone(),
+            port: settings.hl7_port,
+            message_type: settings.hl7_message_type.clone(),
+            sending_facility: settings.hl7_sending_facility.clone(),
+            receiving_application: settings.hl7_receiving_application.clone(),
+            receiving_facility: settings.hl7_receiving_facility.clone(),
+        })
+    }
+}
+
+/// A finished note and the patient it belongs to
+#[derive(Debug, Clone)]
+pub struct Hl7Document<'a> {
+    pub session_id: &'a str,
+    pub patient_name: &'a str,
+    /// YYYY-MM-DD
+    pub patient_dob: &'a str,
+    pub soap_note: &'a str,
+    /// RFC3339
+    pub session_started_at: &'a str,
+    pub physician_name: Option<&'a str>,
+}
+
+/// The parts of an ACK this interface acts on (MSA-1..3)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hl7Ack {
+    pub code: String,
+    pub control_id: String,
+    pub text: String,
+}
+
+/// New MSH-10 value. 20 characters, the limit in older HL7 versions.
+pub fn new_control_id() -> String {
+    uuid::Uuid::new_v4().simple().to_string()[..20].to_string()
+}
+
+/// Render `doc` as the destination's message type, one segment per line
+/// separated by carriage returns.
+pub fn build_message(
+    dest: &Hl7Destination,
+    doc: &Hl7Document<'_>,
+    control_id: &str,
+    now: DateTime<Utc>,
+) -> Result<String, Hl7Error> {
+    let dob = NaiveDate::parse_from_str(doc.patient_dob, "%Y-%m-%d").map_err(|_| {
+        Hl7Error::InvalidMessage(format!(
+            "patient DOB must be YYYY-MM-DD, got {}",
+            doc.patient_dob
+        ))
+    })?;
+    if doc.patient_name.trim().is_empty() {
+        return Err(Hl7Error::InvalidMessage("patient name is empty".into()));
+    }
+    if doc.soap_note.trim().is_empty() {
+        return Err(Hl7Error::InvalidMessage("note is empty".into()));
+    }
+    let message_type = match dest.message_type.as_str() {
+        HL7_MDM_T02 => "MDM^T02^MDM_T02",
+        HL7_ORU_R01 => "ORU^R01^ORU_R01",
+        other => {
+            return Err(Hl7Error::InvalidMessage(format!(
+                "unknown message type {other}"
+            )))
+        }
+    };
+
+    let timestamp = hl7_timestamp(now);
+    let started = DateTime::parse_from_rfc3339(doc.session_started_at)
+        .map(|t| hl7_timestamp(t.with_timezone(&Utc)))
+        .unwrap_or_else(|_| timestamp.clone());
+    let provider = doc
+        .physician_name
+        .map(|n| format!("^{}", person_name(n)))
+        .unwrap_or_default();
+    let document_number = format!("{}^{SENDING_APPLICATION}", escape(doc.session_id));
+
+    // MSH-1 is the field separator itself, so MSH is written by hand.
+    let mut segments = vec![format!(
+        "MSH|^~\\&|{SENDING_APPLICATION}|{}|{}|{}|{timestamp}||{message_type}|{}|P|{HL7_VERSION}",
+        escape(&dest.sending_facility),
+        escape(&dest.receiving_application),
+        escape(&dest.receiving_facility),
+        escape(control_id),
+    )];
+    if dest.message_type == HL7_MDM_T02 {
+        segments.push(segment("EVN", &["T02", &timestamp]));
+    }
+    segments.push(segment(
+        "PID",
+        &[
+            "1",
+            "",
+            &format!(
+                "{}^^^{SENDING_APPLICATION}^PI",
+                patient_key(doc.patient_name, doc.patient_dob)
+            ),
+            "",
+            &person_name(doc.patient_name),
+            "",
+            &dob.format("%Y%m%d").to_string(),
+        ],
+    ));
+    segments.push(segment("PV1", &["1", "O", "", "", "", "", &provider]));
+    if dest.message_type == HL7_MDM_T02 {
+        // TXA-2 CN = consultation, TXA-3 TX = text, TXA-17 DO = documented,
+        // TXA-19 AV = available for patient care.
+        let mut txa = vec![""; 19];
+        txa[0] = "1";
+        txa[1] = "CN";
+        txa[2] = "TX";
+        txa[3] = &started;
+        txa[4] = &provider;
+        txa[5] = &started;
+        txa[11] = &document_number;
+        txa[16] = "DO";
+        txa[18] = "AV";
+        segments.push(segment("TXA", &txa));
+    } else {
+        // OBR-3 filler order number, OBR-7 observation time, OBR-16
+        // ordering provider, OBR-25 F = final.
+        let mut obr = vec![""; 25];
+        obr[0] = "1";
+        obr[2] = &document_number;
+        obr[3] = NOTE_CODE;
+        obr[6] = &started;
+        obr[15] = &provider;
+        obr[21] = &timestamp;
+        obr[24] = "F";
+        segments.push(segment("OBR", &obr));
+    }
+    // One TX observation per line so line breaks survive every receiver.
+    for (i, line) in doc.soap_note.lines().enumerate() {
+        segments.push(segment(
+            "OBX",
+            &[
+                &(i + 1).to_string(),
+                "TX",
+                NOTE_CODE,
+                "",
+                &escape(line),
+                "",
+                "",
+                "",
+                "",
+                "",
+                "F",
+            ],
+        ));
+    }
+
+    Ok(segments.join("\r") + "\r")
+}
+
+/// Send one message over MLLP and return the receiver's ACK, whatever its code.
+pub async fn send_mllp(host: &str, port: u16, message: &str) -> Result<Hl7Ack, Hl7Error> {
+    let mut stream = tokio::time::timeout(CONNECT_TIMEOUT, TcpStream::connect((host, port)))
+        .await
+        .map_err(|_| Hl7Error::Timeout("MLLP connection"))??;
+    stream.write_all(&frame(message)).await?;
+    stream.flush().await?;
+
+    let response = tokio::time::timeout(ACK_TIMEOUT, read_frame(&mut stream))
+        .await
+        .map_err(|_| Hl7Error::Timeout("HL7 ACK"))??;
+    parse_ack(&response)
+}
+
+/// Send a message and require a positive ACK for it.
+pub async fn deliver(
+    dest: &Hl7Destination,
+    message: &str,
+    control_id: &str,
+) -> Result<(), Hl7Error> {
+    let ack = send_mllp(&dest.host, dest.port, message).await?;
+    if ack.control_id != control_id {
+        return Err(Hl7Error::MalformedAck(format!(
+            "ACK is for message {}, expected {control_id}",
+            ack.control_id
+        )));
+    }
+    match ack.code.as_str() {
+        "AA" | "CA" => Ok(()),
+        "AR" | "CR" => Err(Hl7Error::Rejected {
+            code: ack.code,
+            text: ack.text,
+        }),
+        _ => Err(Hl7Error::ApplicationError {
+            code: ack.code,
+            text: ack.text,
+        }),
+    }
+}
+
+/// Wrap a message in an MLLP block: VT, message, FS, CR.
+fn frame(message: &str) -> Vec<u8> {
+    let mut framed = Vec::with_capacity(message.len() + 3);
+    framed.push(START_BLOCK);
+    framed.extend_from_slice(message.as_bytes());
+    framed.extend_from_slice(&[END_BLOCK, CARRIAGE_RETURN]);
+    framed
+}
+
+/// Read one MLLP block and return its payload.
+async fn read_frame(stream: &mut TcpStream) -> Result<Vec<u8>, Hl7Error> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    loop {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            return Err(Hl7Error::MalformedAck(
+                "connection closed before end of block".into(),
+            ));
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        if let Some(end) = buf
+            .windows(2)
+            .position(|w| w == [END_BLOCK, CARRIAGE_RETURN])
+        {
+            let start = match buf.iter().position(|&b| b == START_BLOCK) {
+                Some(i) if i < end => i + 1,
+                _ => return Err(Hl7Error::MalformedAck("missing MLLP start block".into())),
+            };
+            return Ok(buf[start..end].to_vec());
+        }
+        if buf.len() > MAX_ACK_BYTES {
+            return Err(Hl7Error::MalformedAck("response exceeds size limit".into()));
+        }
+    }
+}
+
+/// Pull MSA-1..3 out of an ACK payload.
+fn parse_ack(payload: &[u8]) -> Result<Hl7Ack, Hl7Error> {
+    let text = String::from_utf8_lossy(payload);
+    let msa = text
+        .split(['\r', '\n'])
+        .find(|s| s.starts_with("MSA|"))
+        .ok_or_else(|| Hl7Error::MalformedAck("no MSA segment".into()))?;
+    let fields: Vec<&str> = msa.split('|').collect();
+    let code = fields.get(1).copied().unwrap_or_default();
+    if code.is_empty() {
+        return Err(Hl7Error::MalformedAck("empty acknowledgment code".into()));
+    }
+    Ok(Hl7Ack {
+        code: code.to_string(),
+        control_id: fields.get(2).copied().unwrap_or_default().to_string(),
+        text: fields.get(3).copied().unwrap_or_default().to_string(),
+    })
+}
+
+/// Join fields into a segment, dropping trailing empty fields.
+fn segment(name: &str, fields: &[&str]) -> String {
+    let used = fields
+        .iter()
+        .rposition(|f| !f.is_empty())
+        .map_or(0, |i| i + 1);
+    std::iter::once(name)
+        .chain(fields[..used].iter().copied())
+        .collect::<Vec<_>>()
+        .join("|")
+}
+
+/// Escape HL7 delimiters in free text. Carriage returns would end the segment.
+fn escape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\E\\"),
+            '|' => out.push_str("\\F\\"),
+            '^' => out.push_str("\\S\\"),
+            '&' => out.push_str("\\T\\"),
+            '~' => out.push_str("\\R\\"),
+            '\r' | '\n' => out.push(' '),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// "First Middle Last" as an XPN: family^given^middle.
+fn person_name(name: &str) -> String {
+    let parts: Vec<String> = name.split_whitespace().map(escape).collect();
+    match parts.as_slice() {
+        [] => String::new(),
+        [only] => only.clone(),
+        [given, middle @ .., family] => {
+            let name = format!("{family}^{given}^{}", middle.join(" "));
+            name.trim_end_matches('^').to_string()
+        }
+    }
+}
+
+/// HL7 DTM in the clinic's local time, with offset.
+fn hl7_timestamp(t: DateTime<Utc>) -> String {
+    t.with_timezone(&Local).format("%Y%m%d%H%M%S%z").to_string()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    pub(crate) fn destination(message_type: &str, port: u16) -> Hl7Destination {
+        Hl7Destination {
+            host: "127.0.0.1".into(),
+            port,
+            message_type: message_type.into(),
+            sending_facility: "CLINIC".into(),
+            receiving_application: "ACCURO".into(),
+            receiving_facility: "CLINIC".into(),
+        }
+    }
+
+    pub(crate) fn document() -> Hl7Document<'static> {
+        Hl7Document {
+            session_id: "session-1",
+            patient_name: "Cheryl Lynn Bond",
+            patient_dob: "1958-03-14",
+            soap_note:
+                "S: Cough x 2 weeks | worse at night\n\nA: Bronchitis^viral\nP: Fluids & rest",
+            session_started_at: "2026-10-01T14:00:00Z",
+            physician_name: Some("Alex Chen"),
+        }
+    }
+
+    fn segments(message: &str) -> Vec<Vec<String>> {
+        message
+            .split('\r')
+            .filter(|s| !s.is_empty())
+            .map(|s| s.split('|').map(String::from).collect())
+            .collect()
+    }
+
+    /// Accept one connection, capture the raw bytes up to the end block and
+    /// answer with an ACK built from the message's control ID.
+    pub(crate) async fn spawn_listener(
+        code: &'static str,
+    ) -> (u16, tokio::task::JoinHandle<Vec<u8>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let handle = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut raw = Vec::new();
+            let mut chunk = [0u8; 1024];
+            while !raw.ends_with(&[END_BLOCK, CARRIAGE_RETURN]) {
+                let n = socket.read(&mut chunk).await.unwrap();
+                assert!(n > 0, "client closed before end of block");
+                raw.extend_from_slice(&chunk[..n]);
+            }
+            let text = String::from_utf8_lossy(&raw[1..raw.len() - 2]).to_string();
+            let control_id = text
+                .split('\r')
+                .next()
+                .unwrap()
+                .split('|')
+                .nth(9)
+                .unwrap()
+                .to_string();
+            let ack = format!("MSH|^~\\&|ACCURO|CLINIC|FABRICSCRIBE|CLINIC|20261001140100||ACK^T02^ACK|a1|P|2.5\rMSA|{code}|{control_id}|result text\r");
+            socket.write_all(&frame(&ack)).await.unwrap();
+            raw
+        });
+        (port, handle)
+    }
+
+    #[test]
+    fn mdm_message_carries_patient_and_note() {
+        let message = build_message(
+            &destination(HL7_MDM_T02, 2575),
+            &document(),
+            "ctl-1",
+            Utc::now(),
+        )
+        .unwrap();
+        let segs = segments(&message);
+        let names: Vec<&str> = segs.iter().map(|s| s[0].as_str()).collect();
+        assert_eq!(
+            names,
+            ["MSH", "EVN", "PID", "PV1", "TXA", "OBX", "OBX", "OBX", "OBX"]
+        );
+
+        assert_eq!(segs[0][1], "^~\\&");
+        assert_eq!(segs[0][8], "MDM^T02^MDM_T02");
+        assert_eq!(segs[0][9], "ctl-1");
+        assert_eq!(segs[0][11], "2.5");
+        assert_eq!(
+            segs[2][3],
+            format!(
+                "{}^^^FABRICSCRIBE^PI",
+                patient_key("cheryl lynn bond", "1958-03-14")
+            )
+        );
+        assert_eq!(segs[2][5], "Bond^Cheryl^Lynn");
+        assert_eq!(segs[2][7], "19580314");
+        assert_eq!(segs[4][12], "session-1^FABRICSCRIBE");
+        assert_eq!(segs[4][5], "^Chen^Alex");
+        assert_eq!(segs[5][5], "S: Cough x 2 weeks \\F\\ worse at night");
+        assert_eq!(segs[6][5], "");
+        assert_eq!(segs[7][5], "A: Bronchitis\\S\\viral");
+        assert_eq!(segs[8][1], "4");
+        assert_eq!(segs[8][5], "P: Fluids \\T\\ rest");
+        assert_eq!(segs[8][11], "F");
+    }
+
+    #[test]
+    fn oru_message_uses_an_observation_request() {
+        let message = build_message(
+            &destination(HL7_ORU_R01, 2575),
+            &document(),
+            "ctl-2",
+            Utc::now(),
+        )
+        .unwrap();
+        let segs = segments(&message);
+        let names: Vec<&str> = segs.iter().map(|s| s[0].as_str()).collect();
+        assert_eq!(&names[..4], ["MSH", "PID", "PV1", "OBR"]);
+        assert_eq!(segs[0][8], "ORU^R01^ORU_R01");
+        assert_eq!(segs[3][3], "session-1^FABRICSCRIBE");
+        assert_eq!(segs[3][4], NOTE_CODE);
+        assert_eq!(segs[3][25], "F");
+    }
+
+    #[test]
+    fn build_rejects_bad_input() {
+        let dest = destination(HL7_MDM_T02, 2575);
+        let bad_dob = Hl7Document {
+            patient_dob: "14/03/1958",
+            ..document()
+        };
+        let no_note = Hl7Document {
+            soap_note: "  ",
+            ..document()
+        };
+        for doc in [bad_dob, no_note] {
+            let err = build_message(&dest, &doc, "c", Utc::now()).unwrap_err();
+            assert!(matches!(err, Hl7Error::InvalidMessage(_)));
+            assert!(!err.is_retryable());
+        }
+        let err =
+            build_message(&destination("ADT_A08", 2575), &document(), "c", Utc::now()).unwrap_err();
+        assert!(matches!(err, Hl7Error::InvalidMessage(_)));
+    }
+
+    #[test]
+    fn names_and_escapes() {
+        assert_eq!(person_name("Cher"), "Cher");
+        assert_eq!(person_name("Jane  Doe"), "Doe^Jane");
+        assert_eq!(escape("a\\b~c\rd"), "a\\E\\b\\R\\c d");
+        assert_eq!(segment("PV1", &["1", "O", "", ""]), "PV1|1|O");
+        assert_eq!(new_control_id().len(), 20);
+    }
+
+    #[test]
+    fn parse_ack_reads_msa() {
+        let ack = parse_ack(b"MSH|^~\\&|X\rMSA|AE|ctl-9|Unknown patient\r").unwrap();
+        assert_eq!(
+            ack,
+            Hl7Ack {
+                code: "AE".into(),
+                control_id: "ctl-9".into(),
+                text: "Unknown patient".into()
+            }
+        );
+        assert!(matches!(
+            parse_ack(b"MSH|^~\\&|X\r"),
+            Err(Hl7Error::MalformedAck(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn delivers_over_mllp_and_accepts_aa() {
+        let (port, listener) = spawn_listener("AA").await;
+        let dest = destination(HL7_MDM_T02, port);
+        let message = build_message(&dest, &document(), "ctl-aa", Utc::now()).unwrap();
+
+        deliver(&dest, &message, "ctl-aa").await.unwrap();
+
+        let raw = listener.await.unwrap();
+        assert_eq!(raw[0], START_BLOCK);
+        assert_eq!(&raw[raw.len() - 2..], [END_BLOCK, CARRIAGE_RETURN]);
+        assert_eq!(&raw[1..raw.len() - 2], message.as_bytes());
+        assert_eq!(raw.iter().filter(|&&b| b == START_BLOCK).count(), 1);
+    }
+
+    #[tokio::test]
+    async fn nacks_are_classified() {
+        for (code, retryable) in [("AE", true), ("AR", false)] {
+            let (port, listener) = spawn_listener(code).await;
+            let dest = destination(HL7_ORU_R01, port);
+            let message = build_message(&dest, &document(), "ctl-nack", Utc::now()).unwrap();
+
+            let err = deliver(&dest, &message, "ctl-nack").await.unwrap_err();
+            assert_eq!(err.is_retryable(), retryable, "{code}: {err}");
+            listener.await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn ack_for_another_message_is_not_success() {
+        let (port, listener) = spawn_listener("AA").await;
+        let dest = destination(HL7_MDM_T02, port);
+        let message = build_message(&dest, &document(), "ctl-sent", Utc::now()).unwrap();
+
+        let err = deliver(&dest, &message, "ctl-expected").await.unwrap_err();
+        assert!(matches!(err, Hl7Error::MalformedAck(_)));
+        assert!(err.is_retryable());
+        listener.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn closed_connection_is_retryable() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            drop(socket);
+        });
+        let err = send_mllp("127.0.0.1", port, "MSH|^~\\&|X\r")
+            .await
+            .unwrap_err();
+        assert!(err.is_retryable(), "{err}");
+    }
+}
@@ -0,0 +1,441 @@
+//! Durable outbox for HL7 v2 messages.
+//!
+//! Every message is written here before it is sent and removed only when the
+//! receiver ACKs it, so a message is never lost to a crash, an unreachable
+//! interface engine or a missing ACK. `hl7_outbox_task` resends due entries
+//! with the same back-off as the Medplum outbox.
+//!
+//! Retries resend the stored message unchanged, including its MSH-10 control
+//! ID, so a receiver that did process an earlier copy can recognise the
+//! duplicate. Rejected messages (AR) are kept but parked; sending the session
+//! again replaces them.
+
+use crate::config::Config;
+use crate::hl7_outbound::{self, Hl7Destination, Hl7Document, Hl7Error};
+use crate::medplum_outbox::backoff;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// How often the background task looks for due entries
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// How long a message being sent inline stays out of `hl7_outbox_task`'s
+/// reach: the connect and ACK timeouts, doubled to leave room for the write.
+/// If the app dies mid-send the entry falls due once this passes.
+fn in_flight_hold() -> chrono::Duration {
+    chrono::Duration::from_std((hl7_outbound::CONNECT_TIMEOUT + hl7_outbound::ACK_TIMEOUT) * 2)
+        .unwrap_or_else(|_| chrono::Duration::minutes(2))
+}
+
+/// A rendered message waiting for its ACK
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Hl7OutboxEntry {
+    pub session_id: String,
+    /// Archive date (YYYY-MM-DD)
+    pub date: String,
+    /// MSH-10 of `message`; the ACK must echo it
+    pub control_id: String,
+    pub message_type: String,
+    pub message: String,
+    pub queued_at: String,
+    #[serde(default)]
+    pub attempts: u32,
+    /// RFC3339; `None` means due now
+    #[serde(default)]
+    pub next_attempt_at: Option<String>,
+    #[serde(default)]
+    pub last_error: Option<String>,
+    /// Rejected or unsendable; skipped until replaced
+    #[serde(default)]
+    pub parked: bool,
+}
+
+impl Hl7OutboxEntry {
+    fn is_due(&self, now: DateTime<Utc>) -> bool {
+        !self.parked
+            && self
+                .next_attempt_at
+                .as_deref()
+                .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+                .is_none_or(|t| t <= now)
+    }
+}
+
+/// PHI-free view of an outbox entry for the UI
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Hl7OutboxStatus {
+    pub session_id: String,
+    pub date: String,
+    pub control_id: String,
+    pub message_type: String,
+    pub queued_at: String,
+    pub attempts: u32,
+    pub next_attempt_at: Option<String>,
+    pub last_error: Option<String>,
+    pub parked: bool,
+}
+
+/// Unacknowledged HL7 messages backed by a JSON file on disk.
+pub struct Hl7Outbox {
+    entries: Vec<Hl7OutboxEntry>,
+    path: PathBuf,
+}
+
+/// Shared handle used by Tauri state management and the background task.
+pub type SharedHl7Outbox = Arc<tokio::sync::Mutex<Hl7Outbox>>;
+
+impl Hl7Outbox {
+    /// Load the outbox from its persistent file, or start empty.
+    pub fn load() -> Self {
+        let path = dirs::home_dir()
+            .unwrap_or_default()
+            .join(".transcriptionapp")
+            .join("cache")
+            .join("hl7_outbox.json");
+        let entries = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default();
+        Self { entries, path }
+    }
+
+    /// Persist a message before sending it. A pending message for the same
+    /// session is replaced: the latest note wins.
+    pub fn enqueue(&mut self, entry: Hl7OutboxEntry) {
+        self.entries.retain(|e| e.session_id != entry.session_id);
+        self.entries.push(entry);
+        self.save();
+    }
+
+    /// First entry whose back-off has elapsed.
+    pub fn next_due(&self, now: DateTime<Utc>) -> Option<&Hl7OutboxEntry> {
+        self.entries.iter().find(|e| e.is_due(now))
+    }
+
+    /// Drop a message once it has been acknowledged. Keyed on the control ID
+    /// so an ACK for a superseded message leaves its replacement queued.
+    pub fn complete(&mut self, control_id: &str) {
+        let before = self.entries.len();
+        self.entries.retain(|e| e.control_id != control_id);
+        if self.entries.len() != before {
+            self.save();
+        }
+    }
+
+    /// Record a failed attempt. Retryable failures are rescheduled with
+    /// back-off; anything else parks the entry.
+    pub fn record_failure(
+        &mut self,
+        control_id: &str,
+        error: &str,
+        retryable: bool,
+        now: DateTime<Utc>,
+    ) {
+        let Some(entry) = self.entries.iter_mut().find(|e| e.control_id == control_id) else {
+            return;
+        };
+        entry.attempts += 1;
+        entry.last_error = Some(error.to_string());
+        entry.parked = !retryable;
+        entry.next_attempt_at = Some((now + backoff(entry.attempts)).to_rfc3339());
+        self.save();
+    }
+
+    pub fn status(&self) -> Vec<Hl7OutboxStatus> {
+        self.entries
+            .iter()
+            .map(|e| Hl7OutboxStatus {
+                session_id: e.session_id.clone(),
+                date: e.date.clone(),
+                control_id: e.control_id.clone(),
+                message_type: e.message_type.clone(),
+                queued_at: e.queued_at.clone(),
+                attempts: e.attempts,
+                next_attempt_at: e.next_attempt_at.clone(),
+                last_error: e.last_error.clone(),
+                parked: e.parked,
+            })
+            .collect()
+    }
+
+    /// Persist the outbox to disk (creates parent dirs lazily).
+    fn save(&self) {
+        if let Some(parent) = self.path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        if let Ok(json) = serde_json::to_string_pretty(&self.entries) {
+            let _ = std::fs::write(&self.path, json);
+        }
+    }
+}
+
+/// Render a session's note, persist it, and send it once.
+///
+/// The entry is queued as in flight (not due until [`in_flight_hold`] has
+/// passed) so the background task can't send it a second time while this
+/// attempt waits for its ACK. `Ok` means the receiver acknowledged it. On `Err` the message stays in the
+/// outbox: retried in the background when the error is retryable, parked
+/// otherwise. Invalid input fails before anything is queued.
+pub async fn send_session_note(
+    outbox: &SharedHl7Outbox,
+    dest: &Hl7Destination,
+    date: &str,
+    doc: &Hl7Document<'_>,
+) -> Result<(), Hl7Error> {
+    let now = Utc::now();
+    let control_id = hl7_outbound::new_control_id();
+    let message = hl7_outbound::build_message(dest, doc, &control_id, now)?;
+    outbox.lock().await.enqueue(Hl7OutboxEntry {
+        session_id: doc.session_id.to_string(),
+        date: date.to_string(),
+        control_id: control_id.clone(),
+        message_type: dest.message_type.clone(),
+        message: message.clone(),
+        queued_at: now.to_rfc3339(),
+        attempts: 0,
+        next_attempt_at: Some((now + in_flight_hold()).to_rfc3339()),
+        last_error: None,
+        parked: false,
+    });
+
+    match hl7_outbound::deliver(dest, &message, &control_id).await {
+        Ok(()) => {
+            outbox.lock().await.complete(&control_id);
+            info!(
+                event = "hl7_message_acknowledged",
+                session_id = %doc.session_id,
+                control_id = %control_id,
+                message_type = %dest.message_type,
+                "HL7 message acknowledged"
+            );
+            Ok(())
+        }
+        Err(e) => {
+            let retryable = e.is_retryable();
+            warn!(
+                event = "hl7_message_failed",
+                session_id = %doc.session_id,
+                control_id = %control_id,
+                retryable,
+                error = %e
+            );
+            outbox
+                .lock()
+                .await
+                .record_failure(&control_id, &e.to_string(), retryable, Utc::now());
+            Err(e)
+        }
+    }
+}
+
+/// Background task that resends unacknowledged messages one at a time.
+///
+/// Idles while the HL7 interface is disabled. Destination settings are read
+/// on every pass, so a corrected host or port applies to queued messages.
+pub async fn hl7_outbox_task(outbox: SharedHl7Outbox) {
+    loop {
+        tokio::time::sleep(POLL_INTERVAL).await;
+
+        let Some(dest) = Hl7Destination::from_settings(&Config::load_or_default()) else {
+            continue;
+        };
+        let Some(entry) = outbox.lock().await.next_due(Utc::now()).cloned() else {
+            continue;
+        };
+
+        match hl7_outbound::deliver(&dest, &entry.message, &entry.control_id).await {
+            Ok(()) => {
+                outbox.lock().await.complete(&entry.control_id);
+                info!(
+                    event = "hl7_outbox_delivered",
+                    session_id = %entry.session_id,
+                    control_id = %entry.control_id,
+                    attempts = entry.attempts + 1,
+                    "queued HL7 message acknowledged"
+                );
+            }
+            Err(e) => {
+                let retryable = e.is_retryable();
+                warn!(
+                    event = "hl7_outbox_attempt_failed",
+                    session_id = %entry.session_id,
+                    control_id = %entry.control_id,
+                    attempts = entry.attempts + 1,
+                    retryable,
+                    error = %e
+                );
+                outbox.lock().await.record_failure(
+                    &entry.control_id,
+                    &e.to_string(),
+                    retryable,
+                    Utc::now(),
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hl7_outbound::tests::{destination, document, spawn_listener};
+    use crate::hl7_outbound::HL7_MDM_T02;
+
+    fn outbox(name: &str) -> SharedHl7Outbox {
+        Arc::new(tokio::sync::Mutex::new(Hl7Outbox {
+            entries: Vec::new(),
+            path: std::env::temp_dir()
+                .join(format!("hl7_outbox_{name}_{}.json", std::process::id())),
+        }))
+    }
+
+    async fn cleanup(outbox: &SharedHl7Outbox) {
+        let _ = std::fs::remove_file(&outbox.lock().await.path);
+    }
+
+    #[tokio::test]
+    async fn acknowledged_message_leaves_the_outbox() {
+        let outbox = outbox("ack");
+        let (port, listener) = spawn_listener("AA").await;
+
+        send_session_note(
+            &outbox,
+            &destination(HL7_MDM_T02, port),
+            "2026-10-01",
+            &document(),
+        )
+        .await
+        .unwrap();
+
+        listener.await.unwrap();
+        assert!(outbox.lock().await.status().is_empty());
+        cleanup(&outbox).await;
+    }
+
+    #[tokio::test]
+    async fn unacknowledged_message_is_persisted_for_retry() {
+        let outbox = outbox("unreachable");
+        // Bind then drop to get a port nothing listens on.
+        let port = {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            listener.local_addr().unwrap().port()
+        };
+        let dest = destination(HL7_MDM_T02, port);
+
+        let err = send_session_note(&outbox, &dest, "2026-10-01", &document())
+            .await
+            .unwrap_err();
+        assert!(err.is_retryable(), "{err}");
+
+        let path = outbox.lock().await.path.clone();
+        let stored: Vec<Hl7OutboxEntry> =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].attempts, 1);
+        assert!(!stored[0].parked);
+        assert!(stored[0].message.starts_with("MSH|"));
+        assert!(stored[0].message.contains(&stored[0].control_id));
+
+        // The retry resends the stored message and clears it on ACK.
+        let (port, listener) = spawn_listener("AA").await;
+        let entry = stored[0].clone();
+        hl7_outbound::deliver(
+            &destination(HL7_MDM_T02, port),
+            &entry.message,
+            &entry.control_id,
+        )
+        .await
+        .unwrap();
+        outbox.lock().await.complete(&entry.control_id);
+        assert_eq!(
+            listener.await.unwrap()[1..entry.message.len() + 1],
+            *entry.message.as_bytes()
+        );
+        assert!(outbox.lock().await.status().is_empty());
+        cleanup(&outbox).await;
+    }
+
+    #[tokio::test]
+    async fn rejected_message_is_parked() {
+        let outbox = outbox("reject");
+        let (port, listener) = spawn_listener("AR").await;
+
+        let err = send_session_note(
+            &outbox,
+            &destination(HL7_MDM_T02, port),
+            "2026-10-01",
+            &document(),
+        )
+        .await
+        .unwrap_err();
+        assert!(!err.is_retryable());
+        listener.await.unwrap();
+
+        let guard = outbox.lock().await;
+        assert!(guard.status()[0].parked);
+        assert!(guard
+            .next_due(Utc::now() + chrono::Duration::days(1))
+            .is_none());
+        drop(guard);
+        cleanup(&outbox).await;
+    }
+
+    #[tokio::test]
+    async fn message_sent_inline_is_not_due_for_the_background_task() {
+        let outbox = outbox("inflight");
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let dest = destination(HL7_MDM_T02, listener.local_addr().unwrap().port());
+        let send = tokio::spawn({
+            let outbox = outbox.clone();
+            async move { send_session_note(&outbox, &dest, "2026-10-01", &document()).await }
+        });
+
+        // Connected but not yet acknowledged: queued, yet not due
+        let (socket, _) = listener.accept().await.unwrap();
+        {
+            let guard = outbox.lock().await;
+            assert_eq!(guard.status().len(), 1);
+            assert!(guard.next_due(Utc::now()).is_none());
+            assert!(guard
+                .next_due(Utc::now() + in_flight_hold() + chrono::Duration::seconds(1))
+                .is_some());
+        }
+
+        // The inline attempt's failure hands the entry to the background task
+        drop(socket);
+        assert!(send.await.unwrap().is_err());
+        assert_eq!(outbox.lock().await.status()[0].attempts, 1);
+        cleanup(&outbox).await;
+    }
+
+    #[tokio::test]
+    async fn resending_a_session_replaces_its_entry() {
+        let outbox = outbox("replace");
+        let mut guard = outbox.lock().await;
+        for control_id in ["c1", "c2"] {
+            guard.enqueue(Hl7OutboxEntry {
+                session_id: "s1".into(),
+                date: "2026-10-01".into(),
+                control_id: control_id.into(),
+                message_type: HL7_MDM_T02.into(),
+                message: "MSH|".into(),
+                queued_at: Utc::now().to_rfc3339(),
+                attempts: 0,
+                next_attempt_at: None,
+                last_error: None,
+                parked: false,
+            });
+        }
+        // A late ACK for the superseded message must not drop its replacement.
+        guard.complete("c1");
+        assert_eq!(guard.status().len(), 1);
+        assert_eq!(guard.next_due(Utc::now()).unwrap().control_id, "c2");
+        drop(guard);
+        cleanup(&outbox).await;
+    }
+}
@@ -30,6 +30,7 @@
 //! ```
 
 pub mod activity_log;
+pub mod archive_search;
 pub mod audio_processing;
 pub mod audio;
 pub mod audio_upload_queue;
@@ -54,18 +55,23 @@ pub mod patient_name_tracker;
 pub mod debug_storage;
 pub mod diarization;
 pub mod feedback_to_label;
+pub mod emr_backend;
+pub mod fhir_extraction;
 pub mod local_archive;
 pub mod enhancement;
 pub mod listening;
 pub mod gemini_client;
 pub mod openai_image_client;
 pub mod harness;
+pub mod hl7_outbound;
+pub mod hl7_outbox;
 pub mod llm_backend;
 pub mod llm_client;
 pub mod run_context;
 pub mod mcp;
 pub mod medication_extraction;
 pub mod medplum;
+pub mod medplum_outbox;
 pub mod models;
 pub mod ollama;
 pub mod permissions;
@@ -76,10 +82,12 @@ pub mod preprocessing;
 pub mod experiment;
 pub mod replay_bundle;
 pub mod recordings_retention;
+pub mod rediarization;
 pub mod replay_fetch;
 pub mod segment_log;
 pub mod server_sync;
 pub mod shadow_observer;
+pub mod smart_backend;
 pub mod day_log;
 pub mod performance_summary;
 pub mod screenshot;
@@ -105,9 +113,11 @@ pub mod vad;
 pub mod encounter_experiment;
 pub mod vision_experiment;
 pub mod whisper_server;
+pub mod duplex_stt;
 pub mod room_config;
 pub mod profile_client;
 pub mod physician_cache;
+pub mod prior_visits;
 pub mod server_config;
 pub mod server_config_resolve;
 
@@ -322,7 +332,11 @@ pub fn run() {
 
             // Initialize Medplum client state (lazy initialization)
             let medplum_client = commands::create_medplum_client();
-            app.manage(medplum_client);
+            app.manage(medplum_client.clone());
+
+            // SMART Backend Services client (built on first use when emr_backend = "smart_backend")
+            let smart_backend = commands::create_smart_backend();
+            app.manage(smart_backend.clone());
 
             // Initialize listening state for auto-session detection (Arc-wrapped for callback sharing)
             let listening_state: commands::SharedListeningState = Arc::new(Mutex::new(Default::default()));
@@ -340,6 +354,7 @@ pub fn run() {
             let room_config = room_config::RoomConfig::load().unwrap_or(None);
             let server_urls = room_config.as_ref().map(|rc| rc.all_server_urls());
             let room_id_for_merge = room_config.as_ref().and_then(|rc| rc.room_id.clone());
+            let room_id_for_server_config = room_id_for_merge.clone();
             let profile_api_key = room_config.as_ref().and_then(|rc| rc.profile_api_key.clone());
             let shared_room_config: commands::SharedRoomConfig = Arc::new(tokio::sync::RwLock::new(room_config));
             app.manage(shared_room_config);
@@ -387,10 +402,11 @@ pub fn run() {
             {
                 let client_for_config = shared_profile_client.clone();
                 let config_state = shared_server_config.clone();
+                let room_id = room_id_for_server_config;
                 tauri::async_runtime::spawn(async move {
                     let client_guard = client_for_config.read().await;
                     if let Some(ref client) = *client_guard {
-                        let config = server_config::load_server_config(client).await;
+                        let config = server_config::load_server_config(client, room_id.as_deref()).await;
                         let source = format!("{:?}", config.source);
                         let version = config.version;
                         *config_state.write().await = config;
@@ -425,11 +441,29 @@ pub fn run() {
                 upload_continuous,
             ));
 
+            // Medplum outbox — retries session syncs that failed transiently
+            let medplum_outbox = Arc::new(tokio::sync::Mutex::new(
+                medplum_outbox::MedplumOutbox::load(),
+            ));
+            app.manage(medplum_outbox.clone());
+            tauri::async_runtime::spawn(medplum_outbox::medplum_outbox_task(
+                medplum_outbox,
+                medplum_client,
+                smart_backend,
+                shared_profile_client.clone(),
+            ));
+
+            // HL7 outbox — resends HL7 v2 messages the receiver hasn't ACKed
+            let hl7_outbox = Arc::new(tokio::sync::Mutex::new(hl7_outbox::Hl7Outbox::load()));
+            app.manage(hl7_outbox.clone());
+            tauri::async_runtime::spawn(hl7_outbox::hl7_outbox_task(hl7_outbox));
+
             // Start MCP server on port 7101 for IT Admin Coordinator
             let mcp_session = session_manager.clone();
+            let mcp_app = app.handle().clone();
             tauri::async_runtime::spawn(async move {
                 info!("Starting MCP server on port 7101");
-                mcp::start_mcp_server(mcp_session).await;
+                mcp::start_mcp_server(mcp_app, mcp_session).await;
             });
 
             // Resize window to match screen height (sidebar mode)
@@ -552,7 +586,13 @@ pub fn run() {
             commands::medplum_quick_sync,
             commands::medplum_add_soap_to_encounter,
             commands::medplum_multi_patient_quick_sync,
+            commands::preview_structured_resources,
+            commands::medplum_upload_structured_resources,
+            commands::medplum_outbox_status,
             commands::medplum_check_connection,
+            // HL7 v2 outbound interface (legacy EMRs)
+            commands::hl7_send_session,
+            commands::hl7_outbox_status,
             // Whisper server commands (remote transcription)
             commands::check_whisper_server_status,
             commands::list_whisper_server_models,
@@ -577,6 +617,7 @@ pub fn run() {
             commands::get_local_session_dates,
             commands::get_local_sessions_by_date,
             commands::get_local_session_details,
+            commands::search_archive,
             commands::save_local_soap_note,
             commands::save_local_multi_patient_soap_note,
             commands::save_patient_handout,
@@ -591,6 +632,8 @@ pub fn run() {
             commands::get_daily_billing_summary,
             commands::get_monthly_billing_summary,
             commands::export_billing_csv,
+            commands::export_ohip_claim_file,
+            commands::reconcile_remittance_advice,
             commands::search_ohip_codes,
             commands::search_diagnostic_codes,
             commands::read_local_audio_file,
@@ -603,6 +646,7 @@ pub fn run() {
             commands::confirm_session_patient,
             commands::renumber_local_encounters,
             commands::get_session_transcript_lines,
+            commands::rediarize_local_session,
             commands::suggest_split_points,
             commands::get_session_feedback,
             commands::save_session_feedback,
@@ -427,7 +427,14 @@ pub(crate) const MALFORMED_SOAP_SENTINEL: &str = "SOAP generation produced malfo
 /// string whenever `build_simple_soap_prompt` (or the per-patient / single-
 /// patient variants) is materially edited so audits can correlate clinical
 /// drift to specific prompt revisions.
-pub const SOAP_PROMPT_VERSION: &str = "v0.10.96-identity";
+pub const SOAP_PROMPT_VERSION: &str = "v0.10.105-prior-visits";
+
+/// Version tag for the prior-visits block appended to the SOAP user prompt
+/// (`prior_visits::PriorVisitsContext`). Stamped on
+/// `ArchiveMetadata.prior_visits_context_version` next to
+/// `soap_prompt_version` when a follow-up note was generated with prior-visit
+/// context. Bump when the block's layout, bounds, or sources change.
+pub const PRIOR_VISITS_CONTEXT_VERSION: &str = "v1";
 
 /// True iff `s` is a usable SOAP note — non-empty after trimming and not the
 /// malformed-output placeholder from [`MALFORMED_SOAP_SENTINEL`].
@@ -472,6 +479,10 @@ pub struct SoapOptions {
     /// Session-specific notes from the clinician (entered during recording)
     #[serde(default)]
     pub session_notes: String,
+    /// Assessment + plan of the confirmed patient's previous visits, built by
+    /// `prior_visits::load_prior_visits`. Empty for unconfirmed patients.
+    #[serde(default)]
+    pub prior_visits: String,
 }
 
 fn default_detail_level() -> u8 {
@@ -486,6 +497,7 @@ impl Default for SoapOptions {
             custom_instructions: String::new(),
             session_custom_instructions: String::new(),
             session_notes: String::new(),
+            prior_visits: String::new(),
         }
     }
 }
@@ -1225,7 +1237,7 @@ impl LLMClient {
 
         let system_prompt = build_simple_soap_prompt(&opts, templates);
         let session_notes = if opts.session_notes.trim().is_empty() { None } else { Some(opts.session_notes.as_str()) };
-        let user_content = build_soap_user_content(&prepared_transcript, audio_events, session_notes, speaker_context);
+        let user_content = build_soap_user_content(&prepared_transcript, audio_events, session_notes, speaker_context, Some(opts.prior_visits.as_str()));
 
         let multimodal = build_multimodal_user_content_if_available(&user_content, screenshot_paths);
         let (parsed, model_used) = match multimodal {
@@ -1316,7 +1328,7 @@ impl LLMClient {
 
         let system_prompt = build_single_patient_soap_prompt(&opts, patient_label, patient_summary, templates);
         let session_notes = if opts.session_notes.trim().is_empty() { None } else { Some(opts.session_notes.as_str()) };
-        let user_content = build_soap_user_content(&prepared_transcript, audio_events, session_notes, speaker_context);
+        let user_content = build_soap_user_content(&prepared_transcript, audio_events, session_notes, speaker_context, Some(opts.prior_visits.as_str()));
 
         let multimodal = build_multimodal_user_content_if_available(&user_content, screenshot_paths);
         let (parsed, model_used) = match multimodal {
@@ -1465,7 +1477,7 @@ impl LLMClient {
 
         let system_prompt = build_simple_soap_prompt(&opts, templates);
         let session_notes = if opts.session_notes.trim().is_empty() { None } else { Some(opts.session_notes.as_str()) };
-        let user_content = build_soap_user_content(&prepared_transcript, audio_events, session_notes, speaker_context);
+        let user_content = build_soap_user_content(&prepared_transcript, audio_events, session_notes, speaker_context, Some(opts.prior_visits.as_str()));
 
         let multimodal = build_multimodal_user_content_if_available(&user_content, screenshot_paths);
         let (parsed, raw_response, model_used, metrics) = match multimodal {
@@ -2209,7 +2221,7 @@ Respond ONLY with JSON: {{"is_greeting": true/false, "confidence": 0.0-1.0, "det
 
         // Build user content: text BEFORE image (slightly faster per integration guide)
         let session_notes = if opts.session_notes.trim().is_empty() { None } else { Some(opts.session_notes.as_str()) };
-        let text_content = build_soap_user_content(&prepared_transcript, audio_events, session_notes, None);
+        let text_content = build_soap_user_content(&prepared_transcript, audio_events, session_notes, None, Some(opts.prior_visits.as_str()));
 
         let user_parts = vec![
             ContentPart::Text { text: text_content },
@@ -2309,12 +2321,14 @@ pub fn build_simple_soap_prompt(
         let detail_instruction = build_soap_detail_instruction(options, templates);
         let format_instruction = build_soap_format_instruction(options, templates);
         let custom_section = build_soap_custom_section(options, templates);
-        return format!("{base}{custom_section}\n\n{format_instruction}\n\n- {detail_instruction}");
+        let prior_visits_instruction = build_soap_prior_visits_instruction(options);
+        return format!("{base}{custom_section}\n\n{format_instruction}\n\n- {detail_instruction}{prior_visits_instruction}");
     }
 
     let detail_instruction = build_soap_detail_instruction(options, templates);
     let format_instruction = build_soap_format_instruction(options, templates);
     let custom_section = build_soap_custom_section(options, templates);
+    let prior_visits_instruction = build_soap_prior_visits_instruction(options);
     let not_found = SOAP_IDENTITY_NOT_FOUND;
 
     format!(
@@ -2357,11 +2371,21 @@ PATIENT IDENTIFICATION (top-level `patient_name` and `patient_dob` fields — se
 - If the visible chart appears to belong to a DIFFERENT patient than the one being scribed (e.g. the chart name doesn't match anyone mentioned in the transcript, or the demographics on the chart contradict transcript content), return {not_found} rather than guessing. It is far safer to leave these blank than to attach the wrong identity to a SOAP note.
 - If multiple distinct chart pages are shown across the screenshots (e.g. clinician reviewed prior records), pick the one most consistent with the transcript content for this patient.
 
-- CLINICIAN NOTES: If provided, incorporate clinician observations into the appropriate SOAP sections (usually Objective for physical observations, Subjective for reported symptoms).
+- CLINICIAN NOTES: If provided, incorporate clinician observations into the appropriate SOAP sections (usually Objective for physical observations, Subjective for reported symptoms).{prior_visits_instruction}
 - {detail_instruction}"#
     )
 }
 
+/// Build the prior-visits instruction fragment for SOAP prompts. Empty unless
+/// `options.prior_visits` carries context, so prompts for first visits and
+/// unconfirmed patients are unchanged.
+fn build_soap_prior_visits_instruction(options: &SoapOptions) -> String {
+    if options.prior_visits.trim().is_empty() {
+        return String::new();
+    }
+    "\n- PRIOR VISITS: If provided, these are this patient's assessments and plans from earlier visits. Use them ONLY to describe change over time for problems discussed TODAY (e.g. \"BP improved since last visit\", \"tolerating metformin started at last visit\"). Do NOT carry prior diagnoses, findings, or plans into this note unless they were discussed in today's transcript. If today's transcript contradicts a prior visit, today's transcript wins.".to_string()
+}
+
 /// Build the detail-level instruction fragment for SOAP prompts.
 fn build_soap_detail_instruction(
     options: &SoapOptions,
@@ -3356,6 +3380,7 @@ pub fn build_soap_user_content(
     audio_events: Option<&[AudioEvent]>,
     session_notes: Option<&str>,
     speaker_context: Option<&SpeakerContext>,
+    prior_visits: Option<&str>,
 ) -> String {
     let mut content = String::new();
 
@@ -3393,6 +3418,12 @@ pub fn build_soap_user_content(
         content.push_str(&notes_section);
     }
 
+    // Add prior visits last so the model reads today's encounter first
+    if let Some(visits) = prior_visits.map(str::trim).filter(|v| !v.is_empty()) {
+        content.push_str("\n\nPRIOR VISITS (most recent first):\n");
+        content.push_str(visits);
+    }
+
     content
 }
 
@@ -4144,6 +4175,34 @@ mod tests {
             "prompt must request top-level patient identity fields");
     }
 
+    #[test]
+    fn test_prior_visits_only_change_prompt_when_present() {
+        let opts = SoapOptions::default();
+        let prompt = build_simple_soap_prompt(&opts, None);
+        assert!(!prompt.contains("PRIOR VISITS"));
+        let content = build_soap_user_content("Doctor: BP is 128/82.", None, None, None, Some("  "));
+        assert!(!content.contains("PRIOR VISITS"));
+
+        let block = "Visit 1 — 2026-09-01\nAssessment:\n• Hypertension, BP 152/94";
+        let opts = SoapOptions { prior_visits: block.to_string(), ..Default::default() };
+        let prompt = build_simple_soap_prompt(&opts, None);
+        assert!(prompt.contains("PRIOR VISITS"));
+        assert!(prompt.contains("change over time"));
+
+        let content = build_soap_user_content(
+            "Doctor: BP is 128/82.",
+            None,
+            Some("Recheck in 3 months"),
+            None,
+            Some(block),
+        );
+        // Today's transcript and notes come before the prior-visit context
+        let transcript_at = content.find("TRANSCRIPT:").unwrap();
+        let notes_at = content.find("CLINICIAN NOTES:").unwrap();
+        let visits_at = content.find("PRIOR VISITS (most recent first):\nVisit 1").unwrap();
+        assert!(transcript_at < notes_at && notes_at < visits_at);
+    }
+
     #[test]
     fn test_parse_and_format_soap_json_drops_legacy_procedure_fields() {
         // SOAP responses from old prompts carried `procedure_candidates` /
@@ -328,6 +328,16 @@ pub struct ArchiveMetadata {
     /// (v0.10.46+)
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub medplum_patient_id: Option<String>,
+    /// Medplum FHIR Encounter ID created for this session during patient
+    /// confirmation. Structured resources (Condition, MedicationStatement,
+    /// Observation) are linked to it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub medplum_encounter_id: Option<String>,
+    /// References (`Condition/<id>`, ...) of the structured FHIR resources
+    /// uploaded for this session after clinician review. Set once; a second
+    /// upload is refused so the EMR never gets duplicate problem-list entries.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub medplum_structured_resource_ids: Option<Vec<String>>,
     /// True iff the session archive directory contains a `clinician_notes.json`
     /// file with at least one submitted note. Kept in sync by the splitter /
     /// flush / merge paths that write the file.
@@ -343,6 +353,16 @@ pub struct ArchiveMetadata {
     /// no billing record.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub billing_prompt_version: Option<String>,
+    /// Version tag for the prior-visits block injected into the SOAP prompt
+    /// (`llm_client::PRIOR_VISITS_CONTEXT_VERSION`). Set only when the note was
+    /// generated for a confirmed patient with at least one prior visit;
+    /// cleared whenever SOAP is rewritten without that context.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prior_visits_context_version: Option<String>,
+    /// Prior visits the SOAP prompt drew on, most recent first: archive
+    /// session IDs, or Medplum Encounter IDs for visits only found in the EMR.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prior_visit_ids: Option<Vec<String>>,
     /// Sibling-group UUID shared across all child sessions produced by an
     /// auto-split multi-patient encounter. Same value on every sibling; absent
     /// on single-patient sessions and on legacy multi-patient sessions still
@@ -390,9 +410,13 @@ impl ArchiveMetadata {
             has_billing_record: None,
             patient_confirmed_at: None,
             medplum_patient_id: None,
+            medplum_encounter_id: None,
+            medplum_structured_resource_ids: None,
             has_clinician_notes: false,
             soap_prompt_version: None,
             billing_prompt_version: None,
+            prior_visits_context_version: None,
+            prior_visit_ids: None,
             sibling_group_id: None,
             sibling_index: None,
             sibling_group_size: None,
@@ -684,6 +708,9 @@ pub fn add_soap_note(
         metadata.soap_format = format.map(|s| s.to_string());
         metadata.soap_prompt_version =
             Some(crate::llm_client::SOAP_PROMPT_VERSION.to_string());
+        // Re-stamped by `record_prior_visits_context` when this SOAP used it
+        metadata.prior_visits_context_version = None;
+        metadata.prior_visit_ids = None;
 
         // Invalidate stale billing when SOAP changes (needs re-extraction)
         if metadata.has_billing_record == Some(true) {
@@ -869,6 +896,30 @@ pub fn get_billing_record(
     Ok(Some(record))
 }
 
+/// Map every session archived between `from` and `to` (inclusive) to its
+/// archive date, reading each day directory once. Used by the patient-history
+/// lookup for annual frequency limits, for sessions the profile-service
+/// patient index lists without a date.
+pub fn list_session_ids_between(
+    from: &DateTime<Utc>,
+    to: &DateTime<Utc>,
+) -> std::collections::HashMap<String, DateTime<Utc>> {
+    let mut sessions = std::collections::HashMap::new();
+    let mut date = *from;
+    while date.date_naive() <= to.date_naive() {
+        let entries = get_date_dir(&date).ok().and_then(|d| fs::read_dir(d).ok());
+        for entry in entries.into_iter().flatten().flatten() {
+            if entry.path().is_dir() {
+                if let Some(id) = entry.file_name().to_str() {
+                    sessions.insert(id.to_string(), date);
+                }
+            }
+        }
+        date += chrono::Duration::days(1);
+    }
+    sessions
+}
+
 /// Three-state result for the patient-summary lookup. The IPC caller uses
 /// this to decide whether to fall back to the profile service: only
 /// `FileMissing` triggers a cross-machine fetch. `LabelNotFound` and
@@ -1017,6 +1068,8 @@ pub fn save_multi_patient_soap(
     metadata.patient_count = Some(notes.len() as u32);
     metadata.has_soap_note = true;
     metadata.soap_prompt_version = Some(crate::llm_client::SOAP_PROMPT_VERSION.to_string());
+    metadata.prior_visits_context_version = None;
+    metadata.prior_visit_ids = None;
     let metadata_json = serde_json::to_string_pretty(&metadata)
         .map_err(|e| format!("Failed to serialize metadata: {}", e))?;
     fs::write(&metadata_path, metadata_json)
@@ -1580,9 +1633,13 @@ pub fn split_session(session_id: &str, date_str: &str, split_line: usize) -> Res
         has_billing_record: None,
         patient_confirmed_at: None,
         medplum_patient_id: None,
+        medplum_encounter_id: None,
+        medplum_structured_resource_ids: None,
         has_clinician_notes: false,
         soap_prompt_version: None,
         billing_prompt_version: None,
+        prior_visits_context_version: None,
+        prior_visit_ids: None,
         // Manual split breaks the new half out of any sibling group it inherited.
         // Original session keeps its sibling linkage (handled by the in-place
         // mutation below; we don't touch its sibling_* fields).
@@ -1748,6 +1805,8 @@ pub fn split_into_siblings(
         anchor_meta.has_billing_record = None;
         anchor_meta.patient_confirmed_at = None;
         anchor_meta.medplum_patient_id = None;
+        anchor_meta.medplum_encounter_id = None;
+        anchor_meta.medplum_structured_resource_ids = None;
         anchor_meta.has_patient_handout = None;
         anchor_meta.billing_prompt_version = None;
 
@@ -1820,9 +1879,13 @@ pub fn split_into_siblings(
             has_billing_record: None,
             patient_confirmed_at: None,
             medplum_patient_id: None,
+            medplum_encounter_id: None,
+            medplum_structured_resource_ids: None,
             has_clinician_notes: false,
             soap_prompt_version: anchor_meta.soap_prompt_version.clone(),
             billing_prompt_version: None,
+            prior_visits_context_version: None,
+            prior_visit_ids: None,
             sibling_group_id: Some(group_id.clone()),
             sibling_index: Some(i as u32),
             sibling_group_size: Some(n as u32),
@@ -2080,15 +2143,16 @@ pub fn update_patient_name(session_id: &str, date_str: &str, name: &str) -> Resu
 }
 
 /// Mark a session as patient-confirmed. Writes `patient_confirmed_at`,
-/// optionally `medplum_patient_id`, and updates `patient_dob` (since the
-/// clinician may correct the vision-extracted DOB at confirmation time).
-/// Used by the `confirm_session_patient` Tauri command after the Medplum +
-/// profile-service dual-write completes. (v0.10.46+)
+/// optionally `medplum_patient_id` and `medplum_encounter_id`, and updates
+/// `patient_dob` (since the clinician may correct the vision-extracted DOB at
+/// confirmation time). Used by the `confirm_session_patient` Tauri command
+/// after the Medplum + profile-service dual-write completes. (v0.10.46+)
 pub fn mark_patient_confirmed(
     session_id: &str,
     date_str: &str,
     confirmed_at_rfc3339: &str,
     medplum_patient_id: Option<&str>,
+    medplum_encounter_id: Option<&str>,
     patient_dob: &str,
 ) -> Result<(), String> {
     let session_dir = get_session_dir_from_str(session_id, date_str)?;
@@ -2107,6 +2171,9 @@ pub fn mark_patient_confirmed(
     if let Some(id) = medplum_patient_id {
         metadata.medplum_patient_id = Some(id.to_string());
     }
+    if let Some(id) = medplum_encounter_id {
+        metadata.medplum_encounter_id = Some(id.to_string());
+    }
 
     let json = serde_json::to_string_pretty(&metadata)
         .map_err(|e| format!("Failed to serialize metadata: {}", e))?;
@@ -2121,6 +2188,61 @@ pub fn mark_patient_confirmed(
     Ok(())
 }
 
+/// Record the structured FHIR resources uploaded for a session. Called by
+/// `medplum_upload_structured_resources` once the upload has finished.
+pub fn record_structured_resource_ids(
+    session_id: &str,
+    date_str: &str,
+    resource_ids: &[String],
+) -> Result<(), String> {
+    let session_dir = get_session_dir_from_str(session_id, date_str)?;
+    let mut metadata = read_metadata(&session_dir)?;
+    metadata.medplum_structured_resource_ids = Some(resource_ids.to_vec());
+
+    let json = serde_json::to_string_pretty(&metadata)
+        .map_err(|e| format!("Failed to serialize metadata: {}", e))?;
+    fs::write(session_dir.join("metadata.json"), json)
+        .map_err(|e| format!("Failed to write metadata: {}", e))
+}
+
+/// Back-fill the Medplum IDs of a session whose sync was retried from the
+/// outbox after `mark_patient_confirmed` had already run without them.
+pub fn record_medplum_ids(
+    session_id: &str,
+    date_str: &str,
+    patient_id: &str,
+    encounter_id: &str,
+) -> Result<(), String> {
+    let session_dir = get_session_dir_from_str(session_id, date_str)?;
+    let mut metadata = read_metadata(&session_dir)?;
+    metadata.medplum_patient_id = Some(patient_id.to_string());
+    metadata.medplum_encounter_id = Some(encounter_id.to_string());
+
+    let json = serde_json::to_string_pretty(&metadata)
+        .map_err(|e| format!("Failed to serialize metadata: {}", e))?;
+    fs::write(session_dir.join("metadata.json"), json)
+        .map_err(|e| format!("Failed to write metadata: {}", e))
+}
+
+/// Stamp the prior-visits context a freshly archived SOAP was generated with.
+/// Call after `add_soap_note`, which clears both fields.
+pub fn record_prior_visits_context(
+    session_id: &str,
+    date: &DateTime<Utc>,
+    visit_ids: &[String],
+) -> Result<(), String> {
+    let session_dir = get_session_archive_dir(session_id, date)?;
+    let mut metadata = read_metadata(&session_dir)?;
+    metadata.prior_visits_context_version =
+        Some(crate::llm_client::PRIOR_VISITS_CONTEXT_VERSION.to_string());
+    metadata.prior_visit_ids = Some(visit_ids.to_vec());
+
+    let json = serde_json::to_string_pretty(&metadata)
+        .map_err(|e| format!("Failed to serialize metadata: {}", e))?;
+    fs::write(session_dir.join("metadata.json"), json)
+        .map_err(|e| format!("Failed to write metadata: {}", e))
+}
+
 /// Renumber encounter numbers for continuous mode sessions on a given date.
 ///
 /// Loads all sessions with `charting_mode == "continuous"`, sorts by `started_at`,
@@ -2834,6 +2956,40 @@ mod tests {
         let _ = fs::remove_dir_all(&session_dir);
     }
 
+    #[test]
+    fn test_list_session_ids_between_bounds_the_window() {
+        let session_id = format!("test-billing-lookup-{}", Uuid::new_v4());
+        let billed_on = DateTime::parse_from_rfc3339("2031-03-10T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let record: crate::billing::BillingRecord = serde_json::from_value(serde_json::json!({
+            "sessionId": session_id,
+            "date": "2031-03-10",
+            "patientName": null,
+            "status": "confirmed",
+            "codes": [],
+            "timeEntries": [],
+            "totalShadowCents": 0,
+            "totalOutOfBasketCents": 0,
+            "totalTimeBasedCents": 0,
+            "totalAmountCents": 0,
+            "confirmedAt": null,
+            "notes": null,
+            "extractionModel": null,
+            "extractedAt": null
+        }))
+        .unwrap();
+        save_billing_record(&session_id, &billed_on, &record).unwrap();
+
+        let latest = billed_on + chrono::Duration::days(5);
+        let index = list_session_ids_between(&(latest - chrono::Duration::days(10)), &latest);
+        assert_eq!(index.get(&session_id).map(|d| d.date_naive()), Some(billed_on.date_naive()));
+        let index = list_session_ids_between(&(latest - chrono::Duration::days(2)), &latest);
+        assert!(!index.contains_key(&session_id));
+
+        let _ = fs::remove_dir_all(get_session_archive_dir(&session_id, &billed_on).unwrap());
+    }
+
     /// Helper: stages a multi-patient source session at the given date with
     /// stale combined-SOAP artifacts so split_into_siblings tests can assert
     /// the anchor cleanup behavior. Caller owns the session dir + cleanup.
@@ -2,6 +2,7 @@
 //!
 //! Implements the standard worker MCP tools plus scribe-specific tools.
 
+use crate::commands;
 use crate::config;
 use crate::session::{SessionManager, SessionState};
 use serde::{Deserialize, Serialize};
@@ -11,6 +12,8 @@ use std::io::{BufRead, BufReader};
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 use std::time::Instant;
+use tauri::{AppHandle, Manager};
+use tracing::error;
 
 use super::types::ToolResult;
 
@@ -68,6 +71,8 @@ pub fn handle_agent_identity() -> ToolResult {
             "soap_generation".to_string(),
             "emr_integration".to_string(),
             "template_management".to_string(),
+            "session_control".to_string(),
+            "archive_access".to_string(),
         ],
     };
 
@@ -514,6 +519,327 @@ fn level_priority(level: &str) -> u8 {
     }
 }
 
+// ============================================================================
+// Session Control
+// ============================================================================
+
+/// Record a failed tool execution. Logged at `error` so it lands in the
+/// `error_counter` (surfaced by `get_status`) like any other app error.
+fn tool_failure(tool: &str, err: impl std::fmt::Display) -> ToolResult {
+    error!("MCP tool {} failed: {}", tool, err);
+    ToolResult::error(format!("{} failed: {}", tool, err))
+}
+
+/// Parse tool arguments, mapping failures to an error result.
+pub fn parse_arguments<T: serde::de::DeserializeOwned>(arguments: Value) -> Result<T, ToolResult> {
+    // Tools called with no arguments send `null`; treat it as `{}`.
+    let arguments = if arguments.is_null() { json!({}) } else { arguments };
+    serde_json::from_value(arguments)
+        .map_err(|e| ToolResult::error(format!("Invalid arguments: {}", e)))
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct StartSessionParams {
+    #[serde(default)]
+    pub device_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ControlResponse {
+    ok: bool,
+    action: &'static str,
+}
+
+/// Handle the start_session tool. Runs the same code path as the UI's
+/// record button, so the frontend follows along via the usual status events.
+pub async fn handle_start_session(app: &AppHandle, params: StartSessionParams) -> ToolResult {
+    let result = commands::start_session(
+        app.clone(),
+        app.state(),
+        app.state(),
+        app.state(),
+        app.state(),
+        app.state(),
+        app.state(),
+        params.device_id,
+    )
+    .await;
+    match result {
+        Ok(()) => ToolResult::success(&ControlResponse { ok: true, action: "start_session" }),
+        Err(e) => tool_failure("start_session", e),
+    }
+}
+
+/// Handle the stop_session tool
+pub async fn handle_stop_session(app: &AppHandle) -> ToolResult {
+    let result = commands::stop_session(
+        app.clone(),
+        app.state(),
+        app.state(),
+        app.state(),
+        app.state(),
+        app.state(),
+    )
+    .await;
+    match result {
+        Ok(()) => ToolResult::success(&ControlResponse { ok: true, action: "stop_session" }),
+        Err(e) => tool_failure("stop_session", e),
+    }
+}
+
+/// Handle the start_continuous_mode tool
+pub async fn handle_start_continuous_mode(app: &AppHandle) -> ToolResult {
+    match commands::start_continuous_mode_for_app(app).await {
+        Ok(()) => ToolResult::success(&ControlResponse { ok: true, action: "start_continuous_mode" }),
+        Err(e) => tool_failure("start_continuous_mode", e),
+    }
+}
+
+/// Handle the stop_continuous_mode tool
+pub fn handle_stop_continuous_mode(app: &AppHandle) -> ToolResult {
+    match commands::stop_continuous_mode(app.state()) {
+        Ok(()) => ToolResult::success(&ControlResponse { ok: true, action: "stop_continuous_mode" }),
+        Err(e) => tool_failure("stop_continuous_mode", e),
+    }
+}
+
+// ============================================================================
+// Transcript
+// ============================================================================
+
+#[derive(Debug, Serialize)]
+struct TranscriptResponse {
+    session_id: Option<String>,
+    state: SessionState,
+    finalized_text: String,
+    draft_text: Option<String>,
+    segment_count: usize,
+}
+
+/// Handle the get_transcript tool: the live transcript of the current session.
+pub fn handle_get_transcript(session_manager: &Arc<Mutex<SessionManager>>) -> ToolResult {
+    let session = match session_manager.lock() {
+        Ok(s) => s,
+        Err(_) => return tool_failure("get_transcript", "Failed to access session state"),
+    };
+    let update = session.transcript_update();
+    ToolResult::success(&TranscriptResponse {
+        session_id: session.session_id().map(str::to_string),
+        state: session.state().clone(),
+        finalized_text: update.finalized_text,
+        draft_text: update.draft_text,
+        segment_count: update.segment_count,
+    })
+}
+
+// ============================================================================
+// Archive
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct ListSessionsParams {
+    /// YYYY-MM-DD
+    pub date: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SessionRefParams {
+    pub session_id: String,
+    /// YYYY-MM-DD (the session's archive date)
+    pub date: String,
+}
+
+/// Handle the list_sessions tool (local + server, same as the History window)
+pub async fn handle_list_sessions(app: &AppHandle, params: ListSessionsParams) -> ToolResult {
+    match commands::get_local_sessions_by_date(params.date, app.state(), app.state()).await {
+        Ok(sessions) => ToolResult::success(&json!({ "sessions": sessions })),
+        Err(e) => tool_failure("list_sessions", e),
+    }
+}
+
+/// Handle the get_soap_note tool
+pub async fn handle_get_soap_note(app: &AppHandle, params: SessionRefParams) -> ToolResult {
+    let result = commands::get_session_soap_note(
+        params.session_id.clone(),
+        params.date.clone(),
+        app.state(),
+        app.state(),
+    )
+    .await;
+    match result {
+        Ok(soap) => ToolResult::success(&json!({
+            "session_id": params.session_id,
+            "date": params.date,
+            "soap_note": soap,
+        })),
+        Err(e) => tool_failure("get_soap_note", e),
+    }
+}
+
+/// Handle the get_billing_record tool
+pub async fn handle_get_billing_record(app: &AppHandle, params: SessionRefParams) -> ToolResult {
+    let result = commands::get_session_billing(
+        params.session_id.clone(),
+        params.date,
+        app.state(),
+        app.state(),
+    )
+    .await;
+    match result {
+        Ok(Some(record)) => ToolResult::success(&record),
+        Ok(None) => ToolResult::error(format!(
+            "No billing record for session {}",
+            params.session_id
+        )),
+        Err(e) => tool_failure("get_billing_record", e),
+    }
+}
+
+/// Handle the regenerate_soap tool: re-run SOAP generation on the archived
+/// transcript with the physician's configured options and save the result.
+/// Multi-patient sessions are refused — their per-patient notes need the
+/// History window's regen flow to keep `patient_labels.json` consistent.
+pub async fn handle_regenerate_soap(app: &AppHandle, params: SessionRefParams) -> ToolResult {
+    const TOOL: &str = "regenerate_soap";
+    let details = match commands::get_local_session_details(
+        params.session_id.clone(),
+        params.date.clone(),
+        app.state(),
+        app.state(),
+    )
+    .await
+    {
+        Ok(d) => d,
+        Err(e) => return tool_failure(TOOL, e),
+    };
+    if details.patient_notes.as_ref().is_some_and(|n| n.len() > 1) {
+        return ToolResult::error(
+            "Multi-patient sessions must be regenerated from the History window",
+        );
+    }
+    let Some(transcript) = details.transcript.filter(|t| !t.trim().is_empty()) else {
+        return ToolResult::error(format!("Session {} has no transcript", params.session_id));
+    };
+
+    let cfg = config::Config::load_or_default();
+    let options = crate::llm_client::SoapOptions {
+        detail_level: cfg.soap_detail_level,
+        format: crate::llm_client::SoapFormat::from_config_str(&cfg.soap_format),
+        custom_instructions: cfg.soap_custom_instructions.clone(),
+        ..Default::default()
+    };
+
+    let soap = match commands::generate_soap_note(
+        transcript,
+        None,
+        Some(options),
+        Some(params.session_id.clone()),
+        Some(params.date.clone()),
+        None,
+        None,
+        None,
+        app.state(),
+        app.state(),
+        app.state(),
+    )
+    .await
+    {
+        Ok(s) => s,
+        Err(e) => return tool_failure(TOOL, e),
+    };
+
+    if let Err(e) = commands::save_local_soap_note(
+        params.session_id.clone(),
+        params.date.clone(),
+        soap.content.clone(),
+        Some(cfg.soap_detail_level),
+        Some(cfg.soap_format.clone()),
+        soap.extracted_patient_name.clone(),
+        soap.extracted_patient_dob.clone(),
+        app.state(),
+        app.state(),
+    ) {
+        return tool_failure(TOOL, e);
+    }
+
+    ToolResult::success(&json!({
+        "session_id": params.session_id,
+        "date": params.date,
+        "model_used": soap.model_used,
+        "generated_at": soap.generated_at,
+        "soap_note": soap.content,
+    }))
+}
+
+// ============================================================================
+// Initialize + Resources
+// ============================================================================
+
+use super::notifications::{self, CONTINUOUS_EVENTS_URI, LIVE_TRANSCRIPT_URI};
+use super::types::{ResourceContent, ResourceDefinition, ResourceReadResult, ResourcesListResult};
+
+/// MCP protocol revision implemented by this server.
+const PROTOCOL_VERSION: &str = "2025-03-26";
+
+/// Result body for the `initialize` handshake
+pub fn initialize_result() -> Value {
+    json!({
+        "protocolVersion": PROTOCOL_VERSION,
+        "capabilities": {
+            "tools": {},
+            "resources": { "subscribe": true, "listChanged": false }
+        },
+        "serverInfo": {
+            "name": "clinic-scribe",
+            "version": env!("CARGO_PKG_VERSION")
+        }
+    })
+}
+
+/// Return the list of subscribable resources
+pub fn get_resources_list() -> ResourcesListResult {
+    ResourcesListResult {
+        resources: vec![
+            ResourceDefinition {
+                uri: CONTINUOUS_EVENTS_URI.to_string(),
+                name: "continuous_mode_events".to_string(),
+                description: "Continuous mode events: encounter detected, merged, SOAP generated, billing ready".to_string(),
+                mime_type: "application/json".to_string(),
+            },
+            ResourceDefinition {
+                uri: LIVE_TRANSCRIPT_URI.to_string(),
+                name: "live_transcript".to_string(),
+                description: "Finalized transcript segments from the active session or continuous mode".to_string(),
+                mime_type: "application/json".to_string(),
+            },
+        ],
+    }
+}
+
+/// Snapshot of a resource for resources/read. None for unknown URIs.
+pub fn read_resource(
+    session_manager: &Arc<Mutex<SessionManager>>,
+    uri: &str,
+) -> Option<ResourceReadResult> {
+    let text = match uri {
+        CONTINUOUS_EVENTS_URI => {
+            json!({ "events": notifications::global().recent_events() }).to_string()
+        }
+        LIVE_TRANSCRIPT_URI => {
+            let update = session_manager.lock().ok()?.transcript_update();
+            serde_json::to_string(&update).unwrap_or_else(|_| "{}".to_string())
+        }
+        _ => return None,
+    };
+    Some(ResourceReadResult {
+        contents: vec![ResourceContent {
+            uri: uri.to_string(),
+            mime_type: "application/json".to_string(),
+            text,
+        }],
+    })
+}
+
 // ============================================================================
 // Tools List
 // ============================================================================
@@ -583,6 +909,124 @@ pub fn get_tools_list() -> ToolsListResult {
                     "required": []
                 }),
             },
+            ToolDefinition {
+                name: "start_session".to_string(),
+                description: "Starts a recording session (same as the record button)".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "device_id": {
+                            "type": "string",
+                            "description": "Input device ID (default: configured device)"
+                        }
+                    },
+                    "required": []
+                }),
+            },
+            ToolDefinition {
+                name: "stop_session".to_string(),
+                description: "Stops the current recording session and generates its transcript".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {},
+                    "required": []
+                }),
+            },
+            ToolDefinition {
+                name: "start_continuous_mode".to_string(),
+                description: "Starts continuous charting mode".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {},
+                    "required": []
+                }),
+            },
+            ToolDefinition {
+                name: "stop_continuous_mode".to_string(),
+                description: "Stops continuous charting mode, flushing any buffered encounter".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {},
+                    "required": []
+                }),
+            },
+            ToolDefinition {
+                name: "get_transcript".to_string(),
+                description: "Returns the live transcript of the current session".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {},
+                    "required": []
+                }),
+            },
+            ToolDefinition {
+                name: "list_sessions".to_string(),
+                description: "Lists archived sessions for a date".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "date": {
+                            "type": "string",
+                            "description": "Date (YYYY-MM-DD)"
+                        }
+                    },
+                    "required": ["date"]
+                }),
+            },
+            ToolDefinition {
+                name: "get_soap_note".to_string(),
+                description: "Returns the SOAP note of an archived session".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "session_id": {
+                            "type": "string",
+                            "description": "Archived session ID"
+                        },
+                        "date": {
+                            "type": "string",
+                            "description": "Session date (YYYY-MM-DD)"
+                        }
+                    },
+                    "required": ["session_id", "date"]
+                }),
+            },
+            ToolDefinition {
+                name: "get_billing_record".to_string(),
+                description: "Returns the billing record of an archived session".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "session_id": {
+                            "type": "string",
+                            "description": "Archived session ID"
+                        },
+                        "date": {
+                            "type": "string",
+                            "description": "Session date (YYYY-MM-DD)"
+                        }
+                    },
+                    "required": ["session_id", "date"]
+                }),
+            },
+            ToolDefinition {
+                name: "regenerate_soap".to_string(),
+                description: "Regenerates and saves the SOAP note of an archived single-patient session".to_string(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "session_id": {
+                            "type": "string",
+                            "description": "Archived session ID"
+                        },
+                        "date": {
+                            "type": "string",
+                            "description": "Session date (YYYY-MM-DD)"
+                        }
+                    },
+                    "required": ["session_id", "date"]
+                }),
+            },
         ],
     }
 }
@@ -11,10 +11,35 @@
 //! - `get_status` - Detailed operational status
 //! - `get_logs` - Retrieve recent log entries
 //!
-//! ### Future (V2+)
+//! ### V2 (Control + Archive)
+//! - `start_session` / `stop_session` - Drive a recording session
+//! - `start_continuous_mode` / `stop_continuous_mode` - Drive continuous charting
+//! - `get_transcript` - Live transcript of the current session
+//! - `list_sessions` - Archived sessions for a date
+//! - `get_soap_note` / `get_billing_record` - Read an archived session's outputs
+//! - `regenerate_soap` - Re-run SOAP generation for an archived session
+//!
+//! Control and archive tools call the same Tauri commands the UI uses, so
+//! the frontend stays in sync via the usual events. Execution failures are
+//! returned as `isError` tool results and logged at `error`, which feeds the
+//! `error_counter` behind `get_status`.
+//!
+//! The V2 tools require `Authorization: Bearer <mcp_auth_token>`; the V1 tools
+//! stay open. See `server::PROTECTED_TOOLS`.
+//!
+//! ### Future (V3+)
 //! - `get_active_encounters` - List active encounters
 //! - `get_templates` - List SOAP templates
 //!
+//! ## Resources
+//!
+//! Clients that `initialize` get an `Mcp-Session-Id`, can
+//! `resources/subscribe` to `scribe://events/continuous` and
+//! `scribe://transcript/live`, and receive updates over the SSE stream at
+//! `GET /mcp` instead of polling `get_status`. See `notifications`.
+//! Reading, subscribing and streaming need the same bearer token as the V2
+//! tools; `resources/list` does not.
+//!
 //! ## Usage
 //!
 //! The MCP server is started automatically when the Tauri app launches.
@@ -26,8 +51,9 @@
 //!
 //! // Spawn MCP server
 //! let mcp_session = session_manager.clone();
+//! let mcp_app = app.handle().clone();
 //! tokio::spawn(async move {
-//!     mcp::start_mcp_server(mcp_session).await;
+//!     mcp::start_mcp_server(mcp_app, mcp_session).await;
 //! });
 //! ```
 //!
@@ -49,6 +75,7 @@
 
 pub mod error_counter;
 mod handlers;
+pub mod notifications;
 mod server;
 mod types;
 
@@ -0,0 +1,243 @@
+//! Live resource updates for MCP subscribers.
+//!
+//! Two resources can be subscribed to via `resources/subscribe`:
+//!
+//! - [`CONTINUOUS_EVENTS_URI`] — every `ContinuousModeEvent` (encounter
+//!   detected/split, merge-back, SOAP generated, billing ready, …), in the
+//!   same JSON shape the frontend receives on `continuous_mode_event`.
+//! - [`LIVE_TRANSCRIPT_URI`] — finalized transcript segments from session
+//!   mode and continuous mode as they arrive.
+//!
+//! Producers call [`publish_continuous_event`] / [`publish_transcript_segment`];
+//! both are cheap no-ops when nobody is listening. The MCP server's SSE
+//! stream forwards each update as a `notifications/resources/updated`
+//! message to clients subscribed to that URI.
+//!
+//! Like `error_counter`, the hub is a process-wide global so producers don't
+//! need the MCP state threaded through to them.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use serde::Serialize;
+use serde_json::Value;
+use tokio::sync::broadcast;
+
+use crate::continuous_mode_events::ContinuousModeEvent;
+
+pub const CONTINUOUS_EVENTS_URI: &str = "scribe://events/continuous";
+pub const LIVE_TRANSCRIPT_URI: &str = "scribe://transcript/live";
+
+/// All resources that accept subscriptions.
+pub const SUBSCRIBABLE_URIS: &[&str] = &[CONTINUOUS_EVENTS_URI, LIVE_TRANSCRIPT_URI];
+
+/// Broadcast buffer per receiver. A client that falls further behind than
+/// this skips ahead (lagged updates are dropped, not queued forever).
+const CHANNEL_CAPACITY: usize = 256;
+/// Recent continuous-mode events kept for `resources/read`.
+const RECENT_EVENTS_CAP: usize = 50;
+
+/// One update on a subscribable resource.
+#[derive(Debug, Clone, Serialize)]
+pub struct ResourceUpdate {
+    pub uri: &'static str,
+    pub data: Value,
+}
+
+/// A finalized transcript segment, as pushed on [`LIVE_TRANSCRIPT_URI`].
+#[derive(Debug, Clone, Serialize)]
+pub struct TranscriptSegmentUpdate<'a> {
+    /// "session" or "continuous"
+    pub source: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<&'a str>,
+    pub text: &'a str,
+    pub start_ms: u64,
+    pub end_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speaker_id: Option<&'a str>,
+}
+
+pub struct NotificationHub {
+    tx: broadcast::Sender<ResourceUpdate>,
+    recent_events: Mutex<VecDeque<Value>>,
+}
+
+impl Default for NotificationHub {
+    fn default() -> Self {
+        let (tx, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self {
+            tx,
+            recent_events: Mutex::new(VecDeque::new()),
+        }
+    }
+}
+
+impl NotificationHub {
+    pub fn publish(&self, uri: &'static str, data: Value) {
+        if uri == CONTINUOUS_EVENTS_URI {
+            if let Ok(mut q) = self.recent_events.lock() {
+                if q.len() >= RECENT_EVENTS_CAP {
+                    q.pop_front();
+                }
+                q.push_back(data.clone());
+            }
+        }
+        // Err only means there are no receivers right now.
+        let _ = self.tx.send(ResourceUpdate { uri, data });
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ResourceUpdate> {
+        self.tx.subscribe()
+    }
+
+    /// Most recent continuous-mode events, oldest first.
+    pub fn recent_events(&self) -> Vec<Value> {
+        self.recent_events
+            .lock()
+            .map(|q| q.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+static GLOBAL: OnceLock<Arc<NotificationHub>> = OnceLock::new();
+
+/// Returns the process-wide hub, initialising it on first call.
+pub fn global() -> Arc<NotificationHub> {
+    GLOBAL
+        .get_or_init(|| Arc::new(NotificationHub::default()))
+        .clone()
+}
+
+pub fn publish_continuous_event(event: &ContinuousModeEvent) {
+    if let Ok(data) = serde_json::to_value(event) {
+        global().publish(CONTINUOUS_EVENTS_URI, data);
+    }
+}
+
+pub fn publish_transcript_segment(segment: TranscriptSegmentUpdate<'_>) {
+    if let Ok(data) = serde_json::to_value(&segment) {
+        global().publish(LIVE_TRANSCRIPT_URI, data);
+    }
+}
+
+// ============================================================================
+// Client subscriptions
+// ============================================================================
+
+/// Per-client subscription sets, keyed by the `Mcp-Session-Id` handed out
+/// from `initialize`.
+#[derive(Default)]
+pub struct SubscriptionRegistry {
+    sessions: Mutex<HashMap<String, HashSet<&'static str>>>,
+}
+
+impl SubscriptionRegistry {
+    /// Register a new client session and return its ID.
+    pub fn create_session(&self) -> String {
+        let id = uuid::Uuid::new_v4().to_string();
+        if let Ok(mut s) = self.sessions.lock() {
+            s.insert(id.clone(), HashSet::new());
+        }
+        id
+    }
+
+    pub fn has_session(&self, session_id: &str) -> bool {
+        self.sessions
+            .lock()
+            .map(|s| s.contains_key(session_id))
+            .unwrap_or(false)
+    }
+
+    pub fn subscribe(&self, session_id: &str, uri: &str) -> Result<(), String> {
+        let uri = SUBSCRIBABLE_URIS
+            .iter()
+            .copied()
+            .find(|u| *u == uri)
+            .ok_or_else(|| format!("Unknown resource: {}", uri))?;
+        let mut sessions = self
+            .sessions
+            .lock()
+            .map_err(|_| "Subscription registry unavailable".to_string())?;
+        let subs = sessions
+            .get_mut(session_id)
+            .ok_or_else(|| format!("Unknown MCP session: {}", session_id))?;
+        subs.insert(uri);
+        Ok(())
+    }
+
+    /// Returns whether the client was subscribed.
+    pub fn unsubscribe(&self, session_id: &str, uri: &str) -> bool {
+        self.sessions
+            .lock()
+            .ok()
+            .and_then(|mut s| s.get_mut(session_id).map(|subs| subs.remove(uri)))
+            .unwrap_or(false)
+    }
+
+    pub fn is_subscribed(&self, session_id: &str, uri: &str) -> bool {
+        self.sessions
+            .lock()
+            .map(|s| s.get(session_id).is_some_and(|subs| subs.contains(uri)))
+            .unwrap_or(false)
+    }
+
+    /// Drop a client session (DELETE /mcp).
+    pub fn remove_session(&self, session_id: &str) -> bool {
+        self.sessions
+            .lock()
+            .map(|mut s| s.remove(session_id).is_some())
+            .unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn publish_reaches_subscribers_and_keeps_recent_events() {
+        let hub = NotificationHub::default();
+        let mut rx = hub.subscribe();
+        hub.publish(CONTINUOUS_EVENTS_URI, serde_json::json!({"type": "started"}));
+        hub.publish(LIVE_TRANSCRIPT_URI, serde_json::json!({"text": "hello"}));
+
+        let first = rx.try_recv().unwrap();
+        assert_eq!(first.uri, CONTINUOUS_EVENTS_URI);
+        assert_eq!(first.data["type"], "started");
+        assert_eq!(rx.try_recv().unwrap().uri, LIVE_TRANSCRIPT_URI);
+
+        // Only continuous-mode events are retained for resources/read
+        assert_eq!(hub.recent_events().len(), 1);
+    }
+
+    #[test]
+    fn recent_events_are_capped() {
+        let hub = NotificationHub::default();
+        for i in 0..(RECENT_EVENTS_CAP + 5) {
+            hub.publish(CONTINUOUS_EVENTS_URI, serde_json::json!({ "n": i }));
+        }
+        let recent = hub.recent_events();
+        assert_eq!(recent.len(), RECENT_EVENTS_CAP);
+        assert_eq!(recent[0]["n"], 5);
+    }
+
+    #[test]
+    fn registry_tracks_subscriptions_per_session() {
+        let reg = SubscriptionRegistry::default();
+        let a = reg.create_session();
+        let b = reg.create_session();
+
+        reg.subscribe(&a, CONTINUOUS_EVENTS_URI).unwrap();
+        assert!(reg.is_subscribed(&a, CONTINUOUS_EVENTS_URI));
+        assert!(!reg.is_subscribed(&b, CONTINUOUS_EVENTS_URI));
+
+        assert!(reg.subscribe(&a, "scribe://nope").is_err());
+        assert!(reg.subscribe("missing", LIVE_TRANSCRIPT_URI).is_err());
+
+        assert!(reg.unsubscribe(&a, CONTINUOUS_EVENTS_URI));
+        assert!(!reg.unsubscribe(&a, CONTINUOUS_EVENTS_URI));
+        assert!(reg.remove_session(&b));
+        assert!(!reg.has_session(&b));
+    }
+}
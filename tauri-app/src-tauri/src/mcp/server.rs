@@ -1,50 +1,107 @@
 //! MCP HTTP server for the AI Scribe worker agent.
 //!
 //! Provides a JSON-RPC 2.0 endpoint on port 7101 for the IT Admin Coordinator.
+//!
+//! Follows the MCP streamable-HTTP transport: `POST /mcp` carries requests,
+//! `initialize` hands out an `Mcp-Session-Id`, and `GET /mcp` with that
+//! header opens an SSE stream of `notifications/resources/updated` messages
+//! for the resources the session subscribed to. `DELETE /mcp` ends the
+//! session.
+//!
+//! The V1 monitoring tools (`agent_identity`, `health_check`, `get_status`,
+//! `get_logs`) stay open, as do the bind address and CORS policy. Tools that
+//! read PHI or drive recording (`PROTECTED_TOOLS`), and reading or streaming
+//! the live resources, require `Authorization: Bearer <mcp_auth_token>`. The
+//! token is read from config on every protected call, so a new token applies
+//! without a restart. With no token configured those calls are refused.
 
 use axum::{
     extract::State,
-    http::StatusCode,
+    http::{
+        header::{HeaderName, AUTHORIZATION},
+        HeaderMap, HeaderValue, StatusCode,
+    },
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
     routing::{get, post},
     Json, Router,
 };
-use serde_json::Value;
+use futures_util::stream;
+use serde_json::{json, Value};
+use std::convert::Infallible;
 use std::net::SocketAddr;
 use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast::error::RecvError;
 use tower_http::cors::{Any, CorsLayer};
 use tracing::{error, info, warn};
 
+use crate::config::Config;
 use crate::session::SessionManager;
+use tauri::AppHandle;
 
 use super::handlers::{
-    self, get_tools_list, handle_agent_identity, handle_get_logs, handle_get_status,
-    handle_health_check, GetLogsParams,
+    self, get_tools_list, handle_agent_identity, handle_get_billing_record, handle_get_logs,
+    handle_get_soap_note, handle_get_status, handle_get_transcript, handle_health_check,
+    handle_list_sessions, handle_regenerate_soap, handle_start_continuous_mode,
+    handle_start_session, handle_stop_continuous_mode, handle_stop_session, parse_arguments,
+    GetLogsParams, ListSessionsParams, SessionRefParams, StartSessionParams,
 };
-use super::types::{JsonRpcRequest, JsonRpcResponse, ToolCallParams, ToolResult};
+use super::notifications::{self, SubscriptionRegistry};
+use super::types::{JsonRpcRequest, JsonRpcResponse, ResourceUriParams, ToolCallParams, ToolResult};
+
+/// Header carrying the client session handed out by `initialize`.
+const SESSION_HEADER: &str = "mcp-session-id";
+
+/// Tools that return PHI or change recording state.
+pub const PROTECTED_TOOLS: &[&str] = &[
+    "start_session",
+    "stop_session",
+    "start_continuous_mode",
+    "stop_continuous_mode",
+    "get_transcript",
+    "list_sessions",
+    "get_soap_note",
+    "get_billing_record",
+    "regenerate_soap",
+];
 
 /// Shared state for the MCP server
 #[derive(Clone)]
 pub struct McpState {
     pub session_manager: Arc<Mutex<SessionManager>>,
+    /// Used by the control/archive tools to reach the same managed state
+    /// the Tauri commands use.
+    pub app: AppHandle,
+    pub subscriptions: Arc<SubscriptionRegistry>,
 }
 
 /// Start the MCP server on port 7101
-pub async fn start_mcp_server(session_manager: Arc<Mutex<SessionManager>>) {
+pub async fn start_mcp_server(app: AppHandle, session_manager: Arc<Mutex<SessionManager>>) {
     // Initialize start time for uptime tracking
     handlers::init_start_time();
 
-    let state = McpState { session_manager };
+    let state = McpState {
+        session_manager,
+        app,
+        subscriptions: Arc::new(SubscriptionRegistry::default()),
+    };
 
     // Build router with CORS for cross-origin requests
     let app = Router::new()
-        .route("/mcp", post(mcp_handler))
+        .route(
+            "/mcp",
+            post(mcp_handler).get(mcp_event_stream).delete(mcp_end_session),
+        )
         .route("/health", get(health_endpoint))
         .with_state(state)
         .layer(
             CorsLayer::new()
                 .allow_origin(Any)
                 .allow_methods(Any)
-                .allow_headers(Any),
+                .allow_headers(Any)
+                .expose_headers([HeaderName::from_static(SESSION_HEADER)]),
         );
 
     let addr = SocketAddr::from(([0, 0, 0, 0], 7101));
@@ -77,8 +134,9 @@ async fn health_endpoint(State(state): State<McpState>) -> Json<Value> {
 /// Main MCP JSON-RPC handler
 async fn mcp_handler(
     State(state): State<McpState>,
+    headers: HeaderMap,
     Json(req): Json<JsonRpcRequest>,
-) -> (StatusCode, Json<JsonRpcResponse>) {
+) -> Response {
     // Validate JSON-RPC version
     if req.jsonrpc != "2.0" {
         return (
@@ -88,17 +146,70 @@ async fn mcp_handler(
                 -32600,
                 "Invalid JSON-RPC version",
             )),
-        );
+        )
+            .into_response();
     }
 
+    if let Some(tool) = protected_target(&req) {
+        if let Err(reason) = authorize(&headers) {
+            warn!("Rejected MCP call to {}: {}", tool, reason);
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(JsonRpcResponse::error(req.id, -32001, reason)),
+            )
+                .into_response();
+        }
+    }
+
+    let client_session = client_session_id(&headers);
+
     let response = match req.method.as_str() {
+        // Lifecycle: hand out a client session for subscriptions
+        "initialize" => {
+            let session_id = state.subscriptions.create_session();
+            info!("MCP client session started: {}", session_id);
+            let mut resp = (
+                StatusCode::OK,
+                Json(JsonRpcResponse::success(req.id, handlers::initialize_result())),
+            )
+                .into_response();
+            if let Ok(v) = HeaderValue::from_str(&session_id) {
+                resp.headers_mut().insert(SESSION_HEADER, v);
+            }
+            return resp;
+        }
+
+        // Notifications carry no id and get no response body
+        "notifications/initialized" => return StatusCode::ACCEPTED.into_response(),
+
         // MCP standard methods
         "tools/list" => {
             let result = get_tools_list();
             JsonRpcResponse::success(req.id, serde_json::to_value(result).unwrap())
         }
 
-        "tools/call" => handle_tool_call(&state, req.id.clone(), req.params),
+        "tools/call" => handle_tool_call(&state, req.id.clone(), req.params).await,
+
+        "resources/list" => {
+            let result = handlers::get_resources_list();
+            JsonRpcResponse::success(req.id, serde_json::to_value(result).unwrap())
+        }
+
+        "resources/read" => match parse_uri_params(req.params) {
+            Ok(uri) => match handlers::read_resource(&state.session_manager, &uri) {
+                Some(result) => JsonRpcResponse::success(req.id, serde_json::to_value(result).unwrap()),
+                None => JsonRpcResponse::resource_not_found(req.id, &uri),
+            },
+            Err(msg) => JsonRpcResponse::invalid_params(req.id, msg),
+        },
+
+        "resources/subscribe" => {
+            handle_subscription(&state, req.id, req.params, client_session.as_deref(), true)
+        }
+
+        "resources/unsubscribe" => {
+            handle_subscription(&state, req.id, req.params, client_session.as_deref(), false)
+        }
 
         // Unknown method
         _ => {
@@ -107,11 +218,164 @@ async fn mcp_handler(
         }
     };
 
-    (StatusCode::OK, Json(response))
+    (StatusCode::OK, Json(response)).into_response()
+}
+
+fn client_session_id(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(SESSION_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+}
+
+fn parse_uri_params(params: Option<Value>) -> Result<String, String> {
+    let params = params.ok_or("Missing params")?;
+    serde_json::from_value::<ResourceUriParams>(params)
+        .map(|p| p.uri)
+        .map_err(|e| format!("Invalid params: {}", e))
+}
+
+/// Handle resources/subscribe and resources/unsubscribe
+fn handle_subscription(
+    state: &McpState,
+    id: Value,
+    params: Option<Value>,
+    client_session: Option<&str>,
+    subscribe: bool,
+) -> JsonRpcResponse {
+    let uri = match parse_uri_params(params) {
+        Ok(uri) => uri,
+        Err(msg) => return JsonRpcResponse::invalid_params(id, msg),
+    };
+    let Some(session_id) = client_session.filter(|s| state.subscriptions.has_session(s)) else {
+        return JsonRpcResponse::error(
+            id,
+            -32600,
+            "Missing or unknown Mcp-Session-Id (call initialize first)",
+        );
+    };
+
+    if subscribe {
+        if let Err(e) = state.subscriptions.subscribe(session_id, &uri) {
+            warn!("MCP subscribe rejected: {}", e);
+            return JsonRpcResponse::resource_not_found(id, &uri);
+        }
+        info!("MCP session {} subscribed to {}", session_id, uri);
+    } else {
+        state.subscriptions.unsubscribe(session_id, &uri);
+    }
+    JsonRpcResponse::success(id, json!({}))
+}
+
+/// GET /mcp: SSE stream of resource updates for the caller's subscriptions.
+async fn mcp_event_stream(State(state): State<McpState>, headers: HeaderMap) -> Response {
+    if let Err(reason) = authorize(&headers) {
+        warn!("Rejected MCP event stream: {}", reason);
+        return (StatusCode::UNAUTHORIZED, reason).into_response();
+    }
+    let Some(session_id) =
+        client_session_id(&headers).filter(|s| state.subscriptions.has_session(s))
+    else {
+        return (
+            StatusCode::BAD_REQUEST,
+            "Missing or unknown Mcp-Session-Id (call initialize first)",
+        )
+            .into_response();
+    };
+
+    let rx = notifications::global().subscribe();
+    let registry = state.subscriptions.clone();
+    let events = stream::unfold((rx, registry, session_id), |(mut rx, registry, sid)| async move {
+        loop {
+            match rx.recv().await {
+                Ok(update) => {
+                    // Session ended via DELETE /mcp: close the stream
+                    if !registry.has_session(&sid) {
+                        return None;
+                    }
+                    if !registry.is_subscribed(&sid, update.uri) {
+                        continue;
+                    }
+                    let message = json!({
+                        "jsonrpc": "2.0",
+                        "method": "notifications/resources/updated",
+                        "params": { "uri": update.uri, "data": update.data },
+                    });
+                    let event = Event::default().event("message").data(message.to_string());
+                    return Some((Ok::<_, Infallible>(event), (rx, registry, sid)));
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("MCP subscriber {} lagged, dropped {} updates", sid, skipped);
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+
+    Sse::new(events).keep_alive(KeepAlive::default()).into_response()
+}
+
+/// DELETE /mcp: end a client session and its subscriptions.
+async fn mcp_end_session(State(state): State<McpState>, headers: HeaderMap) -> StatusCode {
+    match client_session_id(&headers) {
+        Some(sid) if state.subscriptions.remove_session(&sid) => {
+            info!("MCP client session ended: {}", sid);
+            StatusCode::NO_CONTENT
+        }
+        _ => StatusCode::NOT_FOUND,
+    }
+}
+
+/// Protected tool or resource method a request targets, if any.
+///
+/// `resources/list` only names the resources, so it stays open; reading or
+/// subscribing to them exposes the live transcript.
+fn protected_target(req: &JsonRpcRequest) -> Option<&str> {
+    match req.method.as_str() {
+        "tools/call" => {
+            let name = req.params.as_ref()?.get("name")?.as_str()?;
+            PROTECTED_TOOLS.contains(&name).then_some(name)
+        }
+        "resources/read" | "resources/subscribe" | "resources/unsubscribe" => {
+            Some(req.method.as_str())
+        }
+        _ => None,
+    }
+}
+
+/// Check the request's bearer token against the configured `mcp_auth_token`.
+fn authorize(headers: &HeaderMap) -> Result<(), &'static str> {
+    let config = Config::load_or_default();
+    let token = config.mcp_auth_token.trim();
+    if token.is_empty() {
+        return Err("Tool disabled: mcp_auth_token is not configured");
+    }
+    if bearer_matches(headers, token) {
+        Ok(())
+    } else {
+        Err("Unauthorized")
+    }
+}
+
+/// Compare the bearer token in constant time.
+fn bearer_matches(headers: &HeaderMap, expected: &str) -> bool {
+    let Some(presented) = headers
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+    else {
+        return false;
+    };
+    presented.len() == expected.len()
+        && presented
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
 }
 
 /// Handle a tools/call request
-fn handle_tool_call(state: &McpState, id: Value, params: Option<Value>) -> JsonRpcResponse {
+async fn handle_tool_call(state: &McpState, id: Value, params: Option<Value>) -> JsonRpcResponse {
     // Parse tool call params
     let tool_params: ToolCallParams = match params {
         Some(p) => match serde_json::from_value(p) {
@@ -148,6 +412,39 @@ fn handle_tool_call(state: &McpState, id: Value, params: Option<Value>) -> JsonR
             handle_get_logs(log_params)
         }
 
+        "start_session" => match parse_arguments::<StartSessionParams>(tool_params.arguments) {
+            Ok(p) => handle_start_session(&state.app, p).await,
+            Err(e) => e,
+        },
+
+        "stop_session" => handle_stop_session(&state.app).await,
+
+        "start_continuous_mode" => handle_start_continuous_mode(&state.app).await,
+
+        "stop_continuous_mode" => handle_stop_continuous_mode(&state.app),
+
+        "get_transcript" => handle_get_transcript(&state.session_manager),
+
+        "list_sessions" => match parse_arguments::<ListSessionsParams>(tool_params.arguments) {
+            Ok(p) => handle_list_sessions(&state.app, p).await,
+            Err(e) => e,
+        },
+
+        "get_soap_note" => match parse_arguments::<SessionRefParams>(tool_params.arguments) {
+            Ok(p) => handle_get_soap_note(&state.app, p).await,
+            Err(e) => e,
+        },
+
+        "get_billing_record" => match parse_arguments::<SessionRefParams>(tool_params.arguments) {
+            Ok(p) => handle_get_billing_record(&state.app, p).await,
+            Err(e) => e,
+        },
+
+        "regenerate_soap" => match parse_arguments::<SessionRefParams>(tool_params.arguments) {
+            Ok(p) => handle_regenerate_soap(&state.app, p).await,
+            Err(e) => e,
+        },
+
         _ => {
             warn!("Unknown tool: {}", tool_params.name);
             ToolResult::error(format!("Unknown tool: {}", tool_params.name))
@@ -157,3 +454,70 @@ fn handle_tool_call(state: &McpState, id: Value, params: Option<Value>) -> JsonR
     // Wrap result in JSON-RPC response
     JsonRpcResponse::success(id, serde_json::to_value(result).unwrap())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(tool: &str) -> JsonRpcRequest {
+        serde_json::from_value(serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "tools/call",
+            "params": { "name": tool, "arguments": {} }
+        }))
+        .unwrap()
+    }
+
+    fn headers(auth: &str) -> HeaderMap {
+        let mut h = HeaderMap::new();
+        h.insert(AUTHORIZATION, HeaderValue::from_str(auth).unwrap());
+        h
+    }
+
+    #[test]
+    fn only_phi_and_control_tools_are_protected() {
+        assert_eq!(
+            protected_target(&call("get_soap_note")),
+            Some("get_soap_note")
+        );
+        assert_eq!(
+            protected_target(&call("start_session")),
+            Some("start_session")
+        );
+        assert_eq!(protected_target(&call("health_check")), None);
+        assert_eq!(protected_target(&call("get_logs")), None);
+        let list: JsonRpcRequest = serde_json::from_value(
+            serde_json::json!({"jsonrpc": "2.0", "id": 1, "method": "tools/list"}),
+        )
+        .unwrap();
+        assert_eq!(protected_target(&list), None);
+    }
+
+    #[test]
+    fn resource_reads_are_protected_but_listing_is_not() {
+        let req = |method: &str| -> JsonRpcRequest {
+            serde_json::from_value(serde_json::json!({"jsonrpc": "2.0", "id": 1, "method": method}))
+                .unwrap()
+        };
+        assert_eq!(
+            protected_target(&req("resources/read")),
+            Some("resources/read")
+        );
+        assert_eq!(
+            protected_target(&req("resources/subscribe")),
+            Some("resources/subscribe")
+        );
+        assert_eq!(protected_target(&req("resources/list")), None);
+        assert_eq!(protected_target(&req("initialize")), None);
+    }
+
+    #[test]
+    fn bearer_token_must_match_exactly() {
+        assert!(bearer_matches(&headers("Bearer s3cret"), "s3cret"));
+        assert!(!bearer_matches(&headers("Bearer s3cre"), "s3cret"));
+        assert!(!bearer_matches(&headers("Bearer s3cret!"), "s3cret"));
+        assert!(!bearer_matches(&headers("Basic s3cret"), "s3cret"));
+        assert!(!bearer_matches(&HeaderMap::new(), "s3cret"));
+    }
+}
@@ -9,6 +9,8 @@ use serde_json::Value;
 #[derive(Debug, Deserialize)]
 pub struct JsonRpcRequest {
     pub jsonrpc: String,
+    /// Absent on notifications (e.g. `notifications/initialized`).
+    #[serde(default)]
     pub id: Value,
     pub method: String,
     #[serde(default)]
@@ -70,6 +72,11 @@ impl JsonRpcResponse {
         Self::error(id, -32602, message)
     }
 
+    /// Create a resource not found error (MCP-defined code)
+    pub fn resource_not_found(id: Value, uri: &str) -> Self {
+        Self::error(id, -32002, format!("Resource not found: {}", uri))
+    }
+
 }
 
 /// MCP tools/call params
@@ -136,3 +143,40 @@ pub struct ToolsListResult {
     pub tools: Vec<ToolDefinition>,
 }
 
+
+/// MCP resources/read, resources/subscribe and resources/unsubscribe params
+#[derive(Debug, Deserialize)]
+pub struct ResourceUriParams {
+    pub uri: String,
+}
+
+/// MCP resource definition
+#[derive(Debug, Serialize)]
+pub struct ResourceDefinition {
+    pub uri: String,
+    pub name: String,
+    pub description: String,
+    #[serde(rename = "mimeType")]
+    pub mime_type: String,
+}
+
+/// MCP resources/list response
+#[derive(Debug, Serialize)]
+pub struct ResourcesListResult {
+    pub resources: Vec<ResourceDefinition>,
+}
+
+/// MCP resource content item
+#[derive(Debug, Serialize)]
+pub struct ResourceContent {
+    pub uri: String,
+    #[serde(rename = "mimeType")]
+    pub mime_type: String,
+    pub text: String,
+}
+
+/// MCP resources/read response
+#[derive(Debug, Serialize)]
+pub struct ResourceReadResult {
+    pub contents: Vec<ResourceContent>,
+}
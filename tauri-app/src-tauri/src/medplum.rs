@@ -1,10 +1,13 @@
 //! Medplum EMR Integration Module
 //!
-//! Provides OAuth 2.0 authentication with PKCE and FHIR R4 resource operations
-//! for integrating with a local Medplum server.
+//! Provides OAuth 2.0 authentication with PKCE for integrating with a local
+//! Medplum server. The FHIR R4 resource operations are provided methods of
+//! `EmrBackend` (`emr_backend.rs`), which `MedplumClient` implements.
 
+use crate::emr_backend::{handle_response, EmrBackend, EMR_BACKEND_MEDPLUM};
+use async_trait::async_trait;
 use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
-use chrono::{DateTime, Duration, NaiveDate, Utc};
+use chrono::Utc;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
@@ -50,6 +53,24 @@ pub enum MedplumError {
 
     #[error("Invalid URL: {0}")]
     UrlError(String),
+
+    #[error("Server error ({0}): {1}")]
+    ServerError(u16, String),
+}
+
+impl MedplumError {
+    /// True for failures worth retrying later unchanged: the server was
+    /// unreachable, failed, or the token lapsed. Validation and permission
+    /// errors will fail the same way again.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            MedplumError::NetworkError(_)
+                | MedplumError::ServerError(..)
+                | MedplumError::TokenExpired
+                | MedplumError::NotAuthenticated
+        )
+    }
 }
 
 /// OAuth token response from Medplum
@@ -300,43 +321,19 @@ pub struct SyncResult {
     pub encounter_fhir_id: Option<String>,
 }
 
-/// Validate a FHIR resource ID per the FHIR specification.
-///
-/// FHIR IDs must match `[A-Za-z0-9\-\.]{1,64}`. This prevents path traversal
-/// and injection when IDs are interpolated into URL paths.
-fn validate_fhir_id(id: &str) -> Result<(), MedplumError> {
-    if id.is_empty() {
-        return Err(MedplumError::ValidationError(
-            "FHIR ID must not be empty".to_string(),
-        ));
-    }
-    if id.len() > 64 {
-        return Err(MedplumError::ValidationError(
-            "FHIR ID must not exceed 64 characters".to_string(),
-        ));
-    }
-    if !id
-        .chars()
-        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.')
-    {
-        return Err(MedplumError::ValidationError(
-            "FHIR ID contains invalid characters (only alphanumeric, hyphens, and dots allowed)"
-                .to_string(),
-        ));
-    }
-    Ok(())
-}
-
-/// Medplum client for API interactions
-#[derive(Debug)]
+/// Medplum client for API interactions. Clones share auth state, so an
+/// `Arc<dyn EmrBackend>` built from a clone sees later logins and refreshes.
+#[derive(Debug, Clone)]
 pub struct MedplumClient {
     http_client: reqwest::Client,
     base_url: String,
+    /// `{base_url}/fhir/R4`
+    fhir_base: String,
     client_id: String,
     auth_state: Arc<RwLock<AuthState>>,
     pending_pkce: Arc<RwLock<Option<PkceData>>>,
     /// Serializes concurrent token refresh attempts to prevent TOCTOU races
-    refresh_lock: tokio::sync::Mutex<()>,
+    refresh_lock: Arc<tokio::sync::Mutex<()>>,
 }
 
 impl MedplumClient {
@@ -368,10 +365,11 @@ impl MedplumClient {
         Ok(Self {
             http_client,
             base_url: cleaned_url.to_string(),
+            fhir_base: format!("{}/fhir/R4", cleaned_url),
             client_id: client_id.to_string(),
             auth_state: Arc::new(RwLock::new(initial_state)),
             pending_pkce: Arc::new(RwLock::new(None)),
-            refresh_lock: tokio::sync::Mutex::new(()),
+            refresh_lock: Arc::new(tokio::sync::Mutex::new(())),
         })
     }
 
@@ -541,7 +539,7 @@ impl MedplumClient {
             .send()
             .await?;
 
-        self.handle_response(response).await
+        handle_response(response).await
     }
 
     /// Refresh the access token using the refresh token
@@ -668,1382 +666,41 @@ impl MedplumClient {
             .access_token
             .ok_or_else(|| MedplumError::AuthError("Failed to get new token".to_string()))
     }
+}
 
-    // =====================
-    // FHIR Resource Methods
-    // =====================
-
-    /// Search for patients by name or MRN
-    pub async fn search_patients(&self, query: &str) -> Result<Vec<Patient>, MedplumError> {
-        let token = self.get_valid_token().await?;
-
-        let response = self
-            .http_client
-            .get(&format!(
-                "{}/fhir/R4/Patient?name:contains={}&_count=20",
-                self.base_url,
-                urlencoding::encode(query)
-            ))
-            .bearer_auth(&token)
-            .send()
-            .await?;
-
-        let bundle: serde_json::Value = self.handle_response(response).await?;
-
-        let mut patients = Vec::new();
-        if let Some(entries) = bundle["entry"].as_array() {
-            for entry in entries {
-                let resource = &entry["resource"];
-                if let Some(id) = resource["id"].as_str() {
-                    let name = self.extract_patient_name(resource);
-                    let mrn = resource["identifier"]
-                        .as_array()
-                        .and_then(|ids| {
-                            ids.iter().find(|id| {
-                                id["system"].as_str() == Some("http://hospital.example.org/mrn")
-                            })
-                        })
-                        .and_then(|id| id["value"].as_str())
-                        .map(|s| s.to_string());
-
-                    patients.push(Patient {
-                        id: id.to_string(),
-                        name,
-                        mrn,
-                        birth_date: resource["birthDate"].as_str().map(|s| s.to_string()),
-                    });
-                }
-            }
-        }
-
-        Ok(patients)
-    }
-
-    /// Extract patient name from FHIR Patient resource
-    fn extract_patient_name(&self, resource: &serde_json::Value) -> String {
-        if let Some(names) = resource["name"].as_array() {
-            if let Some(name) = names.first() {
-                let family = name["family"].as_str().unwrap_or("");
-                let given = name["given"]
-                    .as_array()
-                    .and_then(|g| g.first())
-                    .and_then(|g| g.as_str())
-                    .unwrap_or("");
-                return format!("{} {}", given, family).trim().to_string();
-            }
-        }
-        "Unknown Patient".to_string()
-    }
-
-    /// Create a placeholder patient for storing scribe sessions
-    /// This creates a patient record that can be easily identified as app-created
-    pub async fn create_placeholder_patient(&self) -> Result<Patient, MedplumError> {
-        let token = self.get_valid_token().await?;
-        let state = self.auth_state.read().await;
-
-        let practitioner_id = state
-            .practitioner_id
-            .as_ref()
-            .ok_or_else(|| MedplumError::NotAuthenticated)?;
-
-        let practitioner_name = state
-            .practitioner_name
-            .clone()
-            .unwrap_or_else(|| "Unknown Practitioner".to_string());
-
-        // Generate unique identifier for this session
-        let session_uuid = uuid::Uuid::new_v4().to_string();
-        let timestamp = Utc::now().format("%Y-%m-%d %H:%M").to_string();
-
-        // Create a placeholder patient with identifiable metadata
-        let patient = serde_json::json!({
-            "resourceType": "Patient",
-            "identifier": [{
-                "system": "urn:fabricscribe:session",
-                "value": session_uuid
-            }],
-            "name": [{
-                "use": "official",
-                "family": "Session",
-                "given": ["Scribe"],
-                "text": format!("Scribe Session - {}", timestamp)
-            }],
-            "meta": {
-                "tag": [{
-                    "system": "urn:fabricscribe",
-                    "code": "placeholder-patient"
-                }, {
-                    "system": "urn:fabricscribe:practitioner",
-                    "code": practitioner_id.clone()
-                }]
-            },
-            "generalPractitioner": [{
-                "reference": format!("Practitioner/{}", practitioner_id)
-            }],
-            "active": true
-        });
-
-        let response = self
-            .http_client
-            .post(&format!("{}/fhir/R4/Patient", self.base_url))
-            .bearer_auth(&token)
-            .json(&patient)
-            .send()
-            .await?;
-
-        let created: serde_json::Value = self.handle_response(response).await?;
-
-        let patient_id = created["id"]
-            .as_str()
-            .ok_or_else(|| MedplumError::ValidationError("No patient ID in response".to_string()))?
-            .to_string();
-
-        info!("Created placeholder patient: {} for practitioner: {}", patient_id, practitioner_name);
-
-        Ok(Patient {
-            id: patient_id,
-            name: format!("Scribe Session - {}", timestamp),
-            mrn: Some(session_uuid),
-            birth_date: None,
-        })
-    }
-
-    /// Create a new encounter for a patient
-    pub async fn create_encounter(&self, patient_id: &str) -> Result<Encounter, MedplumError> {
-        validate_fhir_id(patient_id)?;
-        let token = self.get_valid_token().await?;
-        let state = self.auth_state.read().await;
-
-        let practitioner_id = state
-            .practitioner_id
-            .as_ref()
-            .ok_or_else(|| MedplumError::NotAuthenticated)?;
-
-        // Generate unique encounter identifier
-        let encounter_uuid = uuid::Uuid::new_v4().to_string();
-        let start_time = Utc::now().to_rfc3339();
-
-        // First, get patient name
-        let patient_response = self
-            .http_client
-            .get(&format!("{}/fhir/R4/Patient/{}", self.base_url, patient_id))
-            .bearer_auth(&token)
-            .send()
-            .await?;
-
-        let patient_resource: serde_json::Value = self.handle_response(patient_response).await?;
-        let patient_name = self.extract_patient_name(&patient_resource);
-
-        // Create encounter resource
-        let encounter = serde_json::json!({
-            "resourceType": "Encounter",
-            "identifier": [{
-                "system": "urn:fabricscribe:encounter",
-                "value": encounter_uuid
-            }],
-            "status": "in-progress",
-            "class": {
-                "system": "http://terminology.hl7.org/CodeSystem/v3-ActCode",
-                "code": "AMB",
-                "display": "ambulatory"
-            },
-            "subject": {
-                "reference": format!("Patient/{}", patient_id)
-            },
-            "participant": [{
-                "type": [{
-                    "coding": [{
-                        "system": "http://terminology.hl7.org/CodeSystem/v3-ParticipationType",
-                        "code": "PPRF",
-                        "display": "primary performer"
-                    }]
-                }],
-                "individual": {
-                    "reference": format!("Practitioner/{}", practitioner_id)
-                }
-            }],
-            "period": {
-                "start": start_time
-            },
-            "meta": {
-                "tag": [{
-                    "system": "urn:fabricscribe",
-                    "code": "scribe-session"
-                }]
-            }
-        });
-
-        let response = self
-            .http_client
-            .post(&format!("{}/fhir/R4/Encounter", self.base_url))
-            .bearer_auth(&token)
-            .json(&encounter)
-            .send()
-            .await?;
-
-        let created: serde_json::Value = self.handle_response(response).await?;
-
-        // Use the server-returned ID, not the pre-generated UUID
-        let fhir_id = created["id"].as_str().unwrap_or(&encounter_uuid).to_string();
-        tracing::info!("Created encounter with FHIR ID: {} (local UUID was: {})", fhir_id, encounter_uuid);
-
-        Ok(Encounter {
-            id: fhir_id,
-            patient_id: patient_id.to_string(),
-            patient_name,
-            status: "in-progress".to_string(),
-            start_time,
-            end_time: None,
-        })
-    }
-
-    /// Upload transcript to an encounter
-    pub async fn upload_transcript(
-        &self,
-        encounter_id: &str,
-        encounter_fhir_id: &str,
-        patient_id: &str,
-        transcript: &str,
-    ) -> Result<String, MedplumError> {
-        validate_fhir_id(encounter_fhir_id)?;
-        validate_fhir_id(patient_id)?;
-        let token = self.get_valid_token().await?;
-
-        let doc_ref = serde_json::json!({
-            "resourceType": "DocumentReference",
-            "identifier": [{
-                "system": "urn:fabricscribe:encounter",
-                "value": encounter_id
-            }],
-            "status": "current",
-            "type": {
-                "coding": [{
-                    "system": "http://loinc.org",
-                    "code": "75476-2",
-                    "display": "Transcript"
-                }]
-            },
-            "category": [{
-                "coding": [{
-                    "system": "urn:fabricscribe",
-                    "code": "transcription"
-                }]
-            }],
-            "subject": {
-                "reference": format!("Patient/{}", patient_id)
-            },
-            "context": {
-                "encounter": [{
-                    "reference": format!("Encounter/{}", encounter_fhir_id)
-                }]
-            },
-            "content": [{
-                "attachment": {
-                    "contentType": "text/plain",
-                    "data": base64::engine::general_purpose::STANDARD.encode(transcript)
-                }
-            }],
-            "date": Utc::now().to_rfc3339()
-        });
-
-        let response = self
-            .http_client
-            .post(&format!("{}/fhir/R4/DocumentReference", self.base_url))
-            .bearer_auth(&token)
-            .json(&doc_ref)
-            .send()
-            .await?;
-
-        let created: serde_json::Value = self.handle_response(response).await?;
-        Ok(created["id"].as_str().unwrap_or("").to_string())
-    }
-
-    /// Upload SOAP note to an encounter
-    pub async fn upload_soap_note(
-        &self,
-        encounter_id: &str,
-        encounter_fhir_id: &str,
-        patient_id: &str,
-        soap_note: &str,
-    ) -> Result<String, MedplumError> {
-        validate_fhir_id(encounter_fhir_id)?;
-        validate_fhir_id(patient_id)?;
-        let token = self.get_valid_token().await?;
-
-        let doc_ref = serde_json::json!({
-            "resourceType": "DocumentReference",
-            "identifier": [{
-                "system": "urn:fabricscribe:encounter",
-                "value": encounter_id
-            }],
-            "status": "current",
-            "type": {
-                "coding": [{
-                    "system": "http://loinc.org",
-                    "code": "11506-3",
-                    "display": "Progress note"
-                }]
-            },
-            "category": [{
-                "coding": [{
-                    "system": "urn:fabricscribe",
-                    "code": "soap-note"
-                }]
-            }],
-            "subject": {
-                "reference": format!("Patient/{}", patient_id)
-            },
-            "context": {
-                "encounter": [{
-                    "reference": format!("Encounter/{}", encounter_fhir_id)
-                }]
-            },
-            "content": [{
-                "attachment": {
-                    "contentType": "text/plain",
-                    "data": base64::engine::general_purpose::STANDARD.encode(soap_note)
-                }
-            }],
-            "date": Utc::now().to_rfc3339()
-        });
-
-        let response = self
-            .http_client
-            .post(&format!("{}/fhir/R4/DocumentReference", self.base_url))
-            .bearer_auth(&token)
-            .json(&doc_ref)
-            .send()
-            .await?;
-
-        let created: serde_json::Value = self.handle_response(response).await?;
-        Ok(created["id"].as_str().unwrap_or("").to_string())
-    }
-
-    /// Upload audio recording as Binary + Media resources
-    pub async fn upload_audio(
-        &self,
-        encounter_id: &str,
-        encounter_fhir_id: &str,
-        patient_id: &str,
-        audio_data: &[u8],
-        content_type: &str,
-        duration_seconds: Option<u64>,
-    ) -> Result<String, MedplumError> {
-        validate_fhir_id(encounter_fhir_id)?;
-        validate_fhir_id(patient_id)?;
-        let token = self.get_valid_token().await?;
-
-        // Step 1: Upload binary audio data
-        let binary_response = self
-            .http_client
-            .post(&format!("{}/fhir/R4/Binary", self.base_url))
-            .bearer_auth(&token)
-            .header("Content-Type", content_type)
-            .body(audio_data.to_vec())
-            .send()
-            .await?;
-
-        let binary: serde_json::Value = self.handle_response(binary_response).await?;
-        let binary_id = binary["id"].as_str().unwrap_or("");
-
-        // Step 2: Create Media resource linking to Binary
-        let mut media = serde_json::json!({
-            "resourceType": "Media",
-            "identifier": [{
-                "system": "urn:fabricscribe:encounter",
-                "value": encounter_id
-            }],
-            "status": "completed",
-            "type": {
-                "coding": [{
-                    "system": "http://terminology.hl7.org/CodeSystem/media-type",
-                    "code": "audio",
-                    "display": "Audio"
-                }]
-            },
-            "subject": {
-                "reference": format!("Patient/{}", patient_id)
-            },
-            "encounter": {
-                "reference": format!("Encounter/{}", encounter_fhir_id)
-            },
-            "content": {
-                "contentType": content_type,
-                "url": format!("Binary/{}", binary_id)
-            },
-            "meta": {
-                "tag": [{
-                    "system": "urn:fabricscribe",
-                    "code": "scribe-session"
-                }]
-            }
-        });
-
-        if let Some(duration) = duration_seconds {
-            media["duration"] = serde_json::json!(duration);
-        }
-
-        let media_response = self
-            .http_client
-            .post(&format!("{}/fhir/R4/Media", self.base_url))
-            .bearer_auth(&token)
-            .json(&media)
-            .send()
-            .await?;
-
-        let created: serde_json::Value = self.handle_response(media_response).await?;
-        Ok(created["id"].as_str().unwrap_or("").to_string())
-    }
-
-    /// Complete an encounter (set status to finished)
-    pub async fn complete_encounter(&self, encounter_fhir_id: &str) -> Result<(), MedplumError> {
-        validate_fhir_id(encounter_fhir_id)?;
-        let token = self.get_valid_token().await?;
-
-        // Get current encounter
-        let get_response = self
-            .http_client
-            .get(&format!("{}/fhir/R4/Encounter/{}", self.base_url, encounter_fhir_id))
-            .bearer_auth(&token)
-            .send()
-            .await?;
-
-        let mut encounter: serde_json::Value = self.handle_response(get_response).await?;
-
-        // Update status and end time
-        encounter["status"] = serde_json::json!("finished");
-        encounter["period"]["end"] = serde_json::json!(Utc::now().to_rfc3339());
-
-        // PUT the updated encounter
-        let put_response = self
-            .http_client
-            .put(&format!("{}/fhir/R4/Encounter/{}", self.base_url, encounter_fhir_id))
-            .bearer_auth(&token)
-            .json(&encounter)
-            .send()
-            .await?;
-
-        self.handle_response::<serde_json::Value>(put_response).await?;
-        Ok(())
-    }
-
-    /// Get encounter history for the current practitioner
-    pub async fn get_encounter_history(
-        &self,
-        start_date: Option<&str>,
-        end_date: Option<&str>,
-    ) -> Result<Vec<EncounterSummary>, MedplumError> {
-        let token = self.get_valid_token().await?;
-        let state = self.auth_state.read().await;
-
-        let practitioner_id = state
-            .practitioner_id
-            .as_ref()
-            .ok_or_else(|| MedplumError::NotAuthenticated)?;
-
-        // Build query URL. `_include=Encounter:subject` pulls the referenced
-        // Patient resources into the same bundle so we can avoid N+1 HTTP GETs
-        // for patient names. `_count` is per-page; full traversal happens via
-        // Bundle.link[rel=next] in `fetch_all_pages`.
-        let mut url = format!(
-            "{}/fhir/R4/Encounter?_sort=-date&_count=100&_include=Encounter:subject",
-            self.base_url
-        );
-
-        if let Some(start) = start_date {
-            // Start date is inclusive - query for >= start day midnight UTC
-            url.push_str(&format!("&date=ge{}T00:00:00Z", start));
-        }
-        if let Some(end) = end_date {
-            // End date is inclusive - query for < next day midnight UTC
-            if let Ok(date) = NaiveDate::parse_from_str(end, "%Y-%m-%d") {
-                if let Some(next_day) = date.checked_add_signed(Duration::days(1)) {
-                    url.push_str(&format!("&date=lt{}T00:00:00Z", next_day.format("%Y-%m-%d")));
-                } else {
-                    url.push_str(&format!("&date=le{}T23:59:59Z", end));
-                }
-            } else {
-                // Fallback if parsing fails - use end of day
-                url.push_str(&format!("&date=le{}T23:59:59Z", end));
-            }
-        }
-
-        let bundle = self.fetch_all_pages(&token, &url).await?;
-        let included_patients = Self::extract_patients_from_bundle(&bundle);
-
-        let mut encounters = Vec::new();
-        let practitioner_ref = format!("Practitioner/{}", practitioner_id);
-
-        if let Some(entries) = bundle["entry"].as_array() {
-            for entry in entries {
-                let resource = &entry["resource"];
-
-                // Skip non-Encounter resources (the bundle also includes
-                // Patient resources from `_include=Encounter:subject`).
-                if resource["resourceType"].as_str() != Some("Encounter") {
-                    continue;
-                }
-
-                // Filter by practitioner (since we removed participant from URL query)
-                let is_our_encounter = resource["participant"]
-                    .as_array()
-                    .map(|participants| {
-                        participants.iter().any(|p| {
-                            p["individual"]["reference"].as_str() == Some(&practitioner_ref)
-                        })
-                    })
-                    .unwrap_or(false);
-
-                if !is_our_encounter {
-                    continue;
-                }
-
-                let fhir_id = resource["id"].as_str().unwrap_or("").to_string();
-                let encounter_id = resource["identifier"]
-                    .as_array()
-                    .and_then(|ids| ids.first())
-                    .and_then(|id| id["value"].as_str())
-                    .unwrap_or("")
-                    .to_string();
-
-                let start_time = resource["period"]["start"].as_str().unwrap_or("");
-                let end_time = resource["period"]["end"].as_str();
-
-                // Calculate duration if both times exist
-                let duration_minutes = if let (Ok(start), Some(end_str)) = (
-                    DateTime::parse_from_rfc3339(start_time),
-                    end_time,
-                ) {
-                    if let Ok(end) = DateTime::parse_from_rfc3339(end_str) {
-                        Some((end - start).num_minutes())
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                };
-
-                // Resolve via the _included Patient resource; falls back to
-                // `subject.display` then "Unknown".
-                let patient_name = resource["subject"]["reference"]
-                    .as_str()
-                    .and_then(|r| r.strip_prefix("Patient/"))
-                    .and_then(|id| included_patients.get(id))
-                    .map(|patient| self.extract_patient_name(patient))
-                    .or_else(|| {
-                        resource["subject"]["display"]
-                            .as_str()
-                            .map(|s| s.to_string())
-                    })
-                    .unwrap_or_else(|| "Unknown".to_string());
-
-                encounters.push(EncounterSummary {
-                    id: encounter_id,
-                    fhir_id,
-                    patient_name,
-                    date: start_time.to_string(),
-                    duration_minutes,
-                    has_soap_note: false, // Updated below
-                    has_audio: false,     // Updated below
-                });
-            }
-        }
-
-        // Batch query for SOAP notes and audio for all encounters
-        if !encounters.is_empty() {
-            let encounter_fhir_ids: Vec<&str> =
-                encounters.iter().map(|e| e.fhir_id.as_str()).collect();
-
-            // Query SOAP notes (DocumentReference with category=soap-note)
-            let soap_encounter_ids = self
-                .get_encounters_with_soap_notes(&token, &encounter_fhir_ids)
-                .await
-                .unwrap_or_default();
-
-            // Query audio (Media resources)
-            let audio_encounter_ids = self
-                .get_encounters_with_audio(&token, &encounter_fhir_ids)
-                .await
-                .unwrap_or_default();
-
-            // Update encounter summaries with document indicators
-            for encounter in &mut encounters {
-                encounter.has_soap_note = soap_encounter_ids.contains(&encounter.fhir_id);
-                encounter.has_audio = audio_encounter_ids.contains(&encounter.fhir_id);
-            }
-        }
-
-        Ok(encounters)
-    }
-
-    /// Get patient name from encounter subject reference.
-    ///
-    /// Slow path — issues one HTTP GET per encounter. Prefer the in-bundle
-    /// `_include=Encounter:subject` resolution (see `extract_patients_from_bundle`),
-    /// which carries the Patient resources alongside the Encounters in a single
-    /// response. This helper is retained for one-off callers (e.g. encounter
-    /// details lookup) that don't have a bundle to read from.
-    async fn get_patient_name_from_encounter(
-        &self,
-        token: &str,
-        encounter: &serde_json::Value,
-    ) -> Result<String, MedplumError> {
-        if let Some(reference) = encounter["subject"]["reference"].as_str() {
-            let response = self
-                .http_client
-                .get(&format!("{}/fhir/R4/{}", self.base_url, reference))
-                .bearer_auth(token)
-                .send()
-                .await?;
-
-            let patient: serde_json::Value = self.handle_response(response).await?;
-            return Ok(self.extract_patient_name(&patient));
-        }
-        Ok("Unknown".to_string())
-    }
-
-    /// Walk a FHIR Bundle's `entry[]` and pull included Patient resources into
-    /// a `HashMap<patient_id, patient_resource>` for O(1) lookup. Use with
-    /// `_include=Encounter:subject` on the original search to avoid N+1.
-    fn extract_patients_from_bundle(
-        bundle: &serde_json::Value,
-    ) -> std::collections::HashMap<String, serde_json::Value> {
-        let mut patients = std::collections::HashMap::new();
-        if let Some(entries) = bundle["entry"].as_array() {
-            for entry in entries {
-                let resource = &entry["resource"];
-                if resource["resourceType"].as_str() == Some("Patient") {
-                    if let Some(id) = resource["id"].as_str() {
-                        patients.insert(id.to_string(), resource.clone());
-                    }
-                }
-            }
-        }
-        patients
-    }
-
-    /// Hard cap on pages traversed when following `Bundle.link[rel=next]`. Keeps
-    /// pathological misuse bounded — a typical clinic year produces under 5,000
-    /// encounters, well under this ceiling. Adjust if real workloads exceed it.
-    const MAX_BUNDLE_PAGES: usize = 50;
-
-    /// Fetch a FHIR search URL and concatenate all `entry[]` arrays across
-    /// `Bundle.link[rel=next]` pages into one synthetic bundle. Returns the
-    /// first response's bundle (with `entry` replaced) so callers can read it
-    /// the same way they read a single-page bundle.
-    async fn fetch_all_pages(
-        &self,
-        token: &str,
-        initial_url: &str,
-    ) -> Result<serde_json::Value, MedplumError> {
-        let mut accumulated_entries: Vec<serde_json::Value> = Vec::new();
-        let mut current_url = initial_url.to_string();
-        let mut pages = 0usize;
-        let mut first_bundle: Option<serde_json::Value> = None;
-
-        loop {
-            pages += 1;
-            if pages > Self::MAX_BUNDLE_PAGES {
-                tracing::warn!(
-                    event = "medplum_pagination_capped",
-                    pages_traversed = pages - 1,
-                    "Hit MAX_BUNDLE_PAGES; remaining pages dropped",
-                );
-                break;
-            }
-            let response = self
-                .http_client
-                .get(&current_url)
-                .bearer_auth(token)
-                .send()
-                .await?;
-            let bundle: serde_json::Value = self.handle_response(response).await?;
-            if let Some(entries) = bundle["entry"].as_array() {
-                accumulated_entries.extend(entries.iter().cloned());
-            }
-            let next_url = bundle["link"]
-                .as_array()
-                .and_then(|links| {
-                    links.iter().find_map(|l| {
-                        if l["relation"].as_str() == Some("next") {
-                            l["url"].as_str().map(|s| s.to_string())
-                        } else {
-                            None
-                        }
-                    })
-                });
-            if first_bundle.is_none() {
-                first_bundle = Some(bundle);
-            }
-            match next_url {
-                Some(url) => current_url = url,
-                None => break,
-            }
-        }
-
-        let mut out = first_bundle.unwrap_or_else(|| serde_json::json!({}));
-        out["entry"] = serde_json::Value::Array(accumulated_entries);
-        Ok(out)
-    }
-
-    /// Get encounter FHIR IDs that have SOAP notes
-    async fn get_encounters_with_soap_notes(
-        &self,
-        token: &str,
-        encounter_ids: &[&str],
-    ) -> Result<std::collections::HashSet<String>, MedplumError> {
-        use std::collections::HashSet;
-
-        let mut result = HashSet::new();
-        if encounter_ids.is_empty() {
-            return Ok(result);
-        }
-
-        // Query DocumentReference resources with soap-note category
-        // FHIR search: context:encounter references and category code
-        let url = format!(
-            "{}/fhir/R4/DocumentReference?category=soap-note&_count=200&_elements=context",
-            self.base_url
-        );
-
-        let bundle = self.fetch_all_pages(token, &url).await?;
-
-        if let Some(entries) = bundle["entry"].as_array() {
-            for entry in entries {
-                // Extract encounter reference from context.encounter
-                if let Some(encounters) = entry["resource"]["context"]["encounter"].as_array() {
-                    for enc in encounters {
-                        if let Some(reference) = enc["reference"].as_str() {
-                            // Reference format: "Encounter/{id}"
-                            if let Some(id) = reference.strip_prefix("Encounter/") {
-                                if encounter_ids.contains(&id) {
-                                    result.insert(id.to_string());
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
-
-        Ok(result)
-    }
-
-    /// Get encounter FHIR IDs that have audio recordings
-    async fn get_encounters_with_audio(
-        &self,
-        token: &str,
-        encounter_ids: &[&str],
-    ) -> Result<std::collections::HashSet<String>, MedplumError> {
-        use std::collections::HashSet;
-
-        let mut result = HashSet::new();
-        if encounter_ids.is_empty() {
-            return Ok(result);
-        }
-
-        // Query Media resources
-        let url = format!(
-            "{}/fhir/R4/Media?_count=200&_elements=encounter",
-            self.base_url
-        );
-
-        let bundle = self.fetch_all_pages(token, &url).await?;
-
-        if let Some(entries) = bundle["entry"].as_array() {
-            for entry in entries {
-                // Extract encounter reference
-                if let Some(reference) = entry["resource"]["encounter"]["reference"].as_str() {
-                    // Reference format: "Encounter/{id}"
-                    if let Some(id) = reference.strip_prefix("Encounter/") {
-                        if encounter_ids.contains(&id) {
-                            result.insert(id.to_string());
-                        }
-                    }
-                }
-            }
-        }
-
-        Ok(result)
-    }
-
-    /// Get detailed encounter data including documents
-    pub async fn get_encounter_details(&self, encounter_id: &str) -> Result<EncounterDetails, MedplumError> {
-        validate_fhir_id(encounter_id)?;
-        let token = self.get_valid_token().await?;
-
-        // Fetch encounter directly by FHIR ID
-        let encounter_response = self
-            .http_client
-            .get(&format!(
-                "{}/fhir/R4/Encounter/{}",
-                self.base_url, encounter_id
-            ))
-            .bearer_auth(&token)
-            .send()
-            .await?;
-
-        let encounter: serde_json::Value = self.handle_response(encounter_response).await?;
-
-        let fhir_id = encounter["id"].as_str().unwrap_or("").to_string();
-        let start_time = encounter["period"]["start"].as_str().unwrap_or("");
-        let end_time = encounter["period"]["end"].as_str();
-
-        let duration_minutes = if let (Ok(start), Some(end_str)) = (
-            DateTime::parse_from_rfc3339(start_time),
-            end_time,
-        ) {
-            if let Ok(end) = DateTime::parse_from_rfc3339(end_str) {
-                Some((end - start).num_minutes())
-            } else {
-                None
-            }
-        } else {
-            None
-        };
-
-        // Extract patient ID from subject reference (e.g., "Patient/123" -> "123")
-        let patient_id = encounter["subject"]["reference"]
-            .as_str()
-            .and_then(|r| r.strip_prefix("Patient/"))
-            .map(|id| id.to_string());
-
-        let patient_name = self
-            .get_patient_name_from_encounter(&token, &encounter)
-            .await
-            .unwrap_or_else(|_| "Unknown".to_string());
-
-        // Fetch documents by encounter reference
-        let docs_response = self
-            .http_client
-            .get(&format!(
-                "{}/fhir/R4/DocumentReference?encounter=Encounter/{}",
-                self.base_url, encounter_id
-            ))
-            .bearer_auth(&token)
-            .send()
-            .await?;
-
-        let docs_bundle: serde_json::Value = self.handle_response(docs_response).await?;
-
-        let mut transcript = None;
-        let mut soap_note = None;
-        let mut session_info = None;
-
-        if let Some(entries) = docs_bundle["entry"].as_array() {
-            for entry in entries {
-                let resource = &entry["resource"];
-                let category = resource["category"]
-                    .as_array()
-                    .and_then(|c| c.first())
-                    .and_then(|c| c["coding"].as_array())
-                    .and_then(|c| c.first())
-                    .and_then(|c| c["code"].as_str())
-                    .unwrap_or("");
-
-                let content_data = resource["content"]
-                    .as_array()
-                    .and_then(|c| c.first())
-                    .and_then(|c| c["attachment"]["data"].as_str())
-                    .and_then(|d| {
-                        base64::engine::general_purpose::STANDARD
-                            .decode(d)
-                            .ok()
-                            .and_then(|bytes| String::from_utf8(bytes).ok())
-                    });
-
-                match category {
-                    "transcription" => transcript = content_data,
-                    "soap-note" => soap_note = content_data,
-                    "session-info" => session_info = content_data,
-                    _ => {}
-                }
-            }
-        }
-
-        // Fetch media for audio URL by encounter reference
-        let media_response = self
-            .http_client
-            .get(&format!(
-                "{}/fhir/R4/Media?encounter=Encounter/{}",
-                self.base_url, encounter_id
-            ))
-            .bearer_auth(&token)
-            .send()
-            .await?;
-
-        let media_bundle: serde_json::Value = self.handle_response(media_response).await?;
-        let audio_url = media_bundle["entry"]
-            .as_array()
-            .and_then(|e| e.first())
-            .and_then(|e| e["resource"]["content"]["url"].as_str())
-            .map(|url| format!("{}/fhir/R4/{}", self.base_url, url));
-
-        Ok(EncounterDetails {
-            summary: EncounterSummary {
-                id: encounter_id.to_string(),
-                fhir_id,
-                patient_name,
-                date: start_time.to_string(),
-                duration_minutes,
-                has_soap_note: soap_note.is_some(),
-                has_audio: audio_url.is_some(),
-            },
-            transcript,
-            soap_note,
-            audio_url,
-            session_info,
-            patient_id,
-        })
+#[async_trait]
+impl EmrBackend for MedplumClient {
+    fn backend_name(&self) -> &'static str {
+        EMR_BACKEND_MEDPLUM
     }
 
-    /// Fetch raw binary data (e.g., audio files) from Medplum
-    pub async fn get_audio_data(&self, binary_id: &str) -> Result<Vec<u8>, MedplumError> {
-        validate_fhir_id(binary_id)?;
-        let token = self.get_valid_token().await?;
-
-        let response = self
-            .http_client
-            .get(&format!("{}/fhir/R4/Binary/{}", self.base_url, binary_id))
-            .bearer_auth(&token)
-            .header("Accept", "application/octet-stream")
-            .send()
-            .await?;
-
-        match response.status() {
-            status if status.is_success() => {
-                Ok(response.bytes().await?.to_vec())
-            }
-            reqwest::StatusCode::UNAUTHORIZED => {
-                Err(MedplumError::TokenExpired)
-            }
-            reqwest::StatusCode::FORBIDDEN => {
-                let body = response.text().await.unwrap_or_default();
-                Err(MedplumError::AccessDenied(Self::truncate_error_body(&body)))
-            }
-            reqwest::StatusCode::NOT_FOUND => {
-                Err(MedplumError::NotFound(format!("Binary/{}", binary_id)))
-            }
-            _ => {
-                let body = response.text().await.unwrap_or_default();
-                Err(MedplumError::AuthError(format!(
-                    "Failed to fetch audio: {}",
-                    Self::truncate_error_body(&body)
-                )))
-            }
-        }
+    fn http(&self) -> &reqwest::Client {
+        &self.http_client
     }
 
-    /// Truncate an error body to avoid leaking PHI from server responses.
-    /// Uses `ceil_char_boundary()` for safe UTF-8 truncation (project convention).
-    fn truncate_error_body(body: &str) -> String {
-        const MAX_ERROR_BODY_LEN: usize = 200;
-        if body.len() <= MAX_ERROR_BODY_LEN {
-            body.to_string()
-        } else {
-            let boundary = body.ceil_char_boundary(MAX_ERROR_BODY_LEN);
-            format!("{}...", &body[..boundary])
-        }
+    fn fhir_base(&self) -> &str {
+        &self.fhir_base
     }
 
-    /// Handle HTTP response and convert to appropriate error
-    async fn handle_response<T: serde::de::DeserializeOwned>(
-        &self,
-        response: reqwest::Response,
-    ) -> Result<T, MedplumError> {
-        match response.status() {
-            status if status.is_success() => {
-                Ok(response.json().await?)
-            }
-            reqwest::StatusCode::UNAUTHORIZED => {
-                Err(MedplumError::TokenExpired)
-            }
-            reqwest::StatusCode::FORBIDDEN => {
-                let body = response.text().await.unwrap_or_default();
-                Err(MedplumError::AccessDenied(Self::truncate_error_body(&body)))
-            }
-            reqwest::StatusCode::NOT_FOUND => {
-                Err(MedplumError::NotFound("Resource not found".into()))
-            }
-            reqwest::StatusCode::UNPROCESSABLE_ENTITY => {
-                let body = response.text().await.unwrap_or_default();
-                Err(MedplumError::ValidationError(Self::truncate_error_body(&body)))
-            }
-            _ => {
-                let body = response.text().await.unwrap_or_default();
-                Err(MedplumError::AuthError(format!(
-                    "Unexpected response: {}",
-                    Self::truncate_error_body(&body)
-                )))
-            }
-        }
+    async fn access_token(&self) -> Result<String, MedplumError> {
+        self.get_valid_token().await
     }
 
-    // ── Longitudinal patient memory (v0.10.46+) ──────────────────────
-
-    /// Search for an existing Patient by name + DOB, or create one. Matches
-    /// strictly on normalized name AND birthDate (either both match → hit,
-    /// else miss). Returns the Medplum FHIR `Patient` resource.
-    ///
-    /// Called by `sync_continuous_session` during the confirm-patient flow.
-    pub async fn upsert_patient_by_name_dob(
-        &self,
-        name: &str,
-        dob: &str,
-    ) -> Result<Patient, MedplumError> {
-        // Extract given + family from "First Middle Last" — Medplum search
-        // matches on `name:contains` which looks at given + family + text.
-        let parts: Vec<&str> = name.split_whitespace().collect();
-        let given = parts.first().copied().unwrap_or("");
-        let family = parts.last().copied().unwrap_or("");
-        let search_query = if parts.len() >= 2 {
-            format!("{given} {family}")
-        } else {
-            name.to_string()
-        };
-
-        // 1. Search raw FHIR resources (not via search_patients — that
-        //    helper strips middle names by returning only given[0]+family,
-        //    so "Cheryl Lynn Bond" arrives here as "Cheryl Bond" and
-        //    name_matches would reject the real match on every re-confirm,
-        //    creating duplicate Patients). We query with an explicit
-        //    birthdate filter so Medplum does the first cut and we only
-        //    name-match the birthday-matching candidates.
-        let token = self.get_valid_token().await?;
-        let search_url = format!(
-            "{}/fhir/R4/Patient?name:contains={}&birthdate={}&_count=20",
-            self.base_url,
-            urlencoding::encode(&search_query),
-            urlencoding::encode(dob),
-        );
-        let response = self
-            .http_client
-            .get(&search_url)
-            .bearer_auth(&token)
-            .send()
-            .await?;
-        let bundle: serde_json::Value = self.handle_response(response).await?;
-        if let Some(entries) = bundle["entry"].as_array() {
-            for entry in entries {
-                let resource = &entry["resource"];
-                if resource["birthDate"].as_str() != Some(dob) {
-                    continue;
-                }
-                if !Self::name_resource_matches(resource, name) {
-                    continue;
-                }
-                let id = resource["id"].as_str().unwrap_or_default().to_string();
-                if id.is_empty() {
-                    continue;
-                }
-                tracing::info!(
-                    event = "medplum_patient_match",
-                    patient_id = %id,
-                    "matched existing Medplum Patient"
-                );
-                return Ok(Patient {
-                    id,
-                    name: name.to_string(),
-                    mrn: None,
-                    birth_date: Some(dob.to_string()),
-                });
-            }
-        }
-
-        // 2. No hit → POST a new FHIR Patient tagged as confirmed by FabricScribe.
-        //    `token` already acquired above for the search; reuse it.
-        let mut name_obj = serde_json::json!({
-            "use": "official",
-            "text": name,
-        });
-        if parts.len() >= 2 {
-            name_obj["given"] = serde_json::json!([given]);
-            name_obj["family"] = serde_json::json!(family);
-            if parts.len() > 2 {
-                // Middle names → extra given entries.
-                let middles: Vec<&str> = parts[1..parts.len() - 1].to_vec();
-                let mut givens = vec![given.to_string()];
-                for m in middles {
-                    givens.push(m.to_string());
-                }
-                name_obj["given"] = serde_json::json!(givens);
-            }
-        }
-        let resource = serde_json::json!({
-            "resourceType": "Patient",
-            "name": [name_obj],
-            "birthDate": dob,
-            "meta": {
-                "tag": [{
-                    "system": "urn:fabricscribe",
-                    "code": "confirmed-patient",
-                }],
-            },
-        });
-
-        let response = self
-            .http_client
-            .post(&format!("{}/fhir/R4/Patient", self.base_url))
-            .bearer_auth(&token)
-            .json(&resource)
-            .send()
-            .await?;
-        let created: serde_json::Value = self.handle_response(response).await?;
-        let id = created["id"].as_str().unwrap_or_default().to_string();
-        tracing::info!(
-            event = "medplum_patient_created",
-            patient_id = %id,
-            "created new Medplum Patient"
-        );
-        Ok(Patient {
-            id,
-            name: name.to_string(),
-            mrn: None,
-            birth_date: Some(dob.to_string()),
-        })
+    async fn is_authenticated(&self) -> bool {
+        MedplumClient::is_authenticated(self).await
     }
 
-    /// One-shot sync for a continuous-mode archived session: upsert Patient →
-    /// create Encounter → attach SOAP DocumentReference (if present) → attach
-    /// transcript DocumentReference (if present) → mark Encounter finished
-    /// with the recorded start/end period.
-    ///
-    /// `practitioner_fhir_id` (v0.10.49+) overrides the auth-state's
-    /// Practitioner reference when set — the confirm flow passes the active
-    /// physician's `medplum_practitioner_id` so FHIR Encounter participants
-    /// point at the real Practitioner even when we're using a
-    /// ClientApplication-owned access token via the profile-service proxy.
-    ///
-    /// Fail-open on document attach failures (partial success still useful).
-    /// Returns the full sync record for the tauri-side to persist in
-    /// `ArchiveMetadata.medplum_patient_id`.
-    #[allow(clippy::too_many_arguments)]
-    pub async fn sync_continuous_session(
-        &self,
-        _local_session_id: &str,
-        name: &str,
-        dob: &str,
-        soap_note: Option<&str>,
-        transcript: Option<&str>,
-        session_started_at_rfc3339: &str,
-        session_duration_ms: u64,
-        practitioner_fhir_id: Option<&str>,
-    ) -> Result<MedplumSessionSync, MedplumError> {
-        // If a Practitioner is supplied (via physician config), pin it into
-        // auth_state so create_encounter picks it up. Persists across the
-        // rest of the sync; no effect when None (falls back to auth-state
-        // as before).
-        if let Some(pid) = practitioner_fhir_id {
-            let mut state = self.auth_state.write().await;
-            state.practitioner_id = Some(pid.to_string());
-        }
-        let patient = self.upsert_patient_by_name_dob(name, dob).await?;
-        let encounter = self.create_encounter(&patient.id).await?;
-
-        let mut errors = Vec::new();
-        let mut transcript_doc_id = None;
-        let mut soap_doc_id = None;
-
-        if let Some(t) = transcript {
-            match self
-                .upload_transcript(&encounter.id, &encounter.id, &patient.id, t)
-                .await
-            {
-                Ok(id) => transcript_doc_id = Some(id),
-                Err(e) => errors.push(format!("transcript: {e}")),
-            }
-        }
-        if let Some(s) = soap_note {
-            match self
-                .upload_soap_note(&encounter.id, &encounter.id, &patient.id, s)
-                .await
-            {
-                Ok(id) => soap_doc_id = Some(id),
-                Err(e) => errors.push(format!("soap: {e}")),
-            }
-        }
-
-        // Complete encounter with period derived from session metadata so
-        // the EMR timeline reflects the actual visit window (not the API-call
-        // time). A failed complete doesn't invalidate the uploaded docs.
-        if let Err(e) = self
-            .complete_encounter_with_period(
-                &encounter.id,
-                session_started_at_rfc3339,
-                session_duration_ms,
-            )
-            .await
-        {
-            errors.push(format!("complete: {e}"));
-        }
-
-        Ok(MedplumSessionSync {
-            patient_id: patient.id,
-            encounter_id: encounter.id,
-            transcript_doc_id,
-            soap_doc_id,
-            errors,
-        })
+    async fn practitioner_id(&self) -> Option<String> {
+        self.auth_state.read().await.practitioner_id.clone()
     }
 
-    /// Fetch the last N encounters for a patient (by Medplum FHIR ID).
-    ///
-    /// Delivered with the write-side but used by the follow-up SOAP-context
-    /// injection feature. The current PR calls this only in smoke tests.
-    pub async fn fetch_encounters_for_patient(
-        &self,
-        patient_fhir_id: &str,
-        limit: usize,
-    ) -> Result<Vec<EncounterSummary>, MedplumError> {
-        validate_fhir_id(patient_fhir_id)?;
-        let token = self.get_valid_token().await?;
-
-        let url = format!(
-            "{}/fhir/R4/Encounter?subject=Patient/{}&_sort=-date&_count={}",
-            self.base_url, patient_fhir_id, limit
-        );
-        let response = self.http_client.get(&url).bearer_auth(&token).send().await?;
-        let bundle: serde_json::Value = self.handle_response(response).await?;
-
-        let mut out = Vec::new();
-        if let Some(entries) = bundle["entry"].as_array() {
-            for entry in entries {
-                let r = &entry["resource"];
-                let Some(id) = r["id"].as_str() else { continue };
-                let start = r["period"]["start"].as_str().unwrap_or("").to_string();
-                let duration_minutes = {
-                    let end = r["period"]["end"].as_str();
-                    match (
-                        chrono::DateTime::parse_from_rfc3339(&start),
-                        end.and_then(|e| chrono::DateTime::parse_from_rfc3339(e).ok()),
-                    ) {
-                        (Ok(s), Some(e)) => Some((e - s).num_minutes()),
-                        _ => None,
-                    }
-                };
-                let patient_name = r["subject"]["display"]
-                    .as_str()
-                    .unwrap_or("Unknown")
-                    .to_string();
-                out.push(EncounterSummary {
-                    id: id.to_string(),
-                    fhir_id: id.to_string(),
-                    patient_name,
-                    date: start,
-                    duration_minutes,
-                    // Doc-presence flags not computed here — the follow-up
-                    // SOAP-context PR can batch-query DocumentReference if
-                    // needed; leaving as false keeps the network footprint
-                    // minimal for the common path.
-                    has_soap_note: false,
-                    has_audio: false,
-                });
-            }
-        }
-        Ok(out)
-    }
-
-    /// Complete encounter with an explicit period (start + derived end). Used
-    /// by `sync_continuous_session` so the EMR reflects the real visit time
-    /// rather than the moment the upload happened.
-    async fn complete_encounter_with_period(
-        &self,
-        encounter_fhir_id: &str,
-        started_at: &str,
-        duration_ms: u64,
-    ) -> Result<(), MedplumError> {
-        validate_fhir_id(encounter_fhir_id)?;
-        let token = self.get_valid_token().await?;
-
-        let get_response = self
-            .http_client
-            .get(&format!(
-                "{}/fhir/R4/Encounter/{}",
-                self.base_url, encounter_fhir_id
-            ))
-            .bearer_auth(&token)
-            .send()
-            .await?;
-        let mut encounter: serde_json::Value = self.handle_response(get_response).await?;
-
-        let end_time = chrono::DateTime::parse_from_rfc3339(started_at)
-            .ok()
-            .map(|t| t + chrono::Duration::milliseconds(duration_ms as i64))
-            .map(|t| t.to_rfc3339())
-            .unwrap_or_else(|| Utc::now().to_rfc3339());
-
-        encounter["status"] = serde_json::json!("finished");
-        encounter["period"]["start"] = serde_json::json!(started_at);
-        encounter["period"]["end"] = serde_json::json!(end_time);
-
-        let put_response = self
-            .http_client
-            .put(&format!(
-                "{}/fhir/R4/Encounter/{}",
-                self.base_url, encounter_fhir_id
-            ))
-            .bearer_auth(&token)
-            .json(&encounter)
-            .send()
-            .await?;
-        self.handle_response::<serde_json::Value>(put_response).await?;
-        Ok(())
-    }
-
-    /// Loose name match for dedup: normalize both sides, then compare as-is.
-    /// "Judie Joan Guest" vs "judie joan guest" → match. "Judie Guest" vs
-    /// "Judie Joan Guest" → NO match (different specificity is treated as a
-    /// different Patient — FabricScribe's confirm dialog carries the full
-    /// display name from vision, so this is the correct conservative rule).
-    fn name_matches(a: &str, b: &str) -> bool {
-        let norm = |s: &str| -> String {
-            s.split_whitespace()
-                .map(|w| w.to_lowercase())
-                .collect::<Vec<_>>()
-                .join(" ")
-        };
-        norm(a) == norm(b)
-    }
-
-    /// Compare a FHIR Patient resource's name against a caller-supplied full
-    /// name (e.g., "Cheryl Lynn Bond"). Uses `name[0].text` as authoritative
-    /// when present — that field carries the exact clinical display string.
-    /// Falls back to reconstructing `given.join(' ') + family` ONLY when
-    /// `.text` is absent (preserves middle names across the given array,
-    /// unlike `extract_patient_name` which lossily takes only
-    /// `given[0]+family` and caused the Apr 20 dedup bug).
-    fn name_resource_matches(resource: &serde_json::Value, caller: &str) -> bool {
-        let Some(name) = resource
-            .get("name")
-            .and_then(|n| n.as_array())
-            .and_then(|a| a.first())
-        else {
-            return false;
-        };
-        if let Some(text) = name.get("text").and_then(|v| v.as_str()) {
-            return Self::name_matches(text, caller);
-        }
-        let given_joined: String = name
-            .get("given")
-            .and_then(|g| g.as_array())
-            .map(|a| {
-                a.iter()
-                    .filter_map(|v| v.as_str())
-                    .collect::<Vec<_>>()
-                    .join(" ")
-            })
-            .unwrap_or_default();
-        let family = name.get("family").and_then(|v| v.as_str()).unwrap_or("");
-        let reconstructed = format!("{given_joined} {family}")
-            .split_whitespace()
-            .collect::<Vec<_>>()
-            .join(" ");
-        Self::name_matches(&reconstructed, caller)
+    async fn set_practitioner_id(&self, practitioner_id: &str) {
+        self.auth_state.write().await.practitioner_id = Some(practitioner_id.to_string());
     }
 }
 
 /// Result of `sync_continuous_session`. Returned to the tauri-side for
-/// persistence in `ArchiveMetadata.medplum_patient_id`.
+/// persistence in `ArchiveMetadata.medplum_patient_id` / `medplum_encounter_id`.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct MedplumSessionSync {
@@ -2053,8 +710,9 @@ pub struct MedplumSessionSync {
     pub transcript_doc_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub soap_doc_id: Option<String>,
-    /// Non-fatal errors encountered during document uploads. An empty vec
-    /// means full success.
+    /// Non-fatal errors encountered during document uploads. Always empty
+    /// since the sync became a single transaction (all or nothing); kept so
+    /// results persisted by older versions still deserialize.
     #[serde(default)]
     pub errors: Vec<String>,
 }
@@ -2063,47 +721,6 @@ pub struct MedplumSessionSync {
 mod tests {
     use super::*;
 
-    #[test]
-    fn extract_patients_from_bundle_collects_included_patients() {
-        let bundle = serde_json::json!({
-            "entry": [
-                {"resource": {"resourceType": "Encounter", "id": "enc-1"}},
-                {"resource": {"resourceType": "Patient", "id": "pat-1", "name": [{"text": "Alice Smith"}]}},
-                {"resource": {"resourceType": "Encounter", "id": "enc-2"}},
-                {"resource": {"resourceType": "Patient", "id": "pat-2", "name": [{"text": "Bob Jones"}]}},
-                {"resource": {"resourceType": "Observation", "id": "obs-1"}},
-            ]
-        });
-        let patients = MedplumClient::extract_patients_from_bundle(&bundle);
-        assert_eq!(patients.len(), 2);
-        assert!(patients.contains_key("pat-1"));
-        assert!(patients.contains_key("pat-2"));
-        assert_eq!(
-            patients["pat-1"]["name"][0]["text"].as_str(),
-            Some("Alice Smith")
-        );
-    }
-
-    #[test]
-    fn extract_patients_from_bundle_handles_missing_entry() {
-        let bundle = serde_json::json!({});
-        let patients = MedplumClient::extract_patients_from_bundle(&bundle);
-        assert!(patients.is_empty());
-    }
-
-    #[test]
-    fn extract_patients_from_bundle_skips_unidentified_patients() {
-        let bundle = serde_json::json!({
-            "entry": [
-                {"resource": {"resourceType": "Patient"}},
-                {"resource": {"resourceType": "Patient", "id": "pat-ok"}},
-            ]
-        });
-        let patients = MedplumClient::extract_patients_from_bundle(&bundle);
-        assert_eq!(patients.len(), 1);
-        assert!(patients.contains_key("pat-ok"));
-    }
-
     #[test]
     fn test_pkce_generation() {
         let pkce = PkceData::new();
@@ -2173,155 +790,6 @@ mod tests {
         assert!(status.last_sync_time.is_none());
     }
 
-    #[test]
-    fn test_validate_fhir_id_valid() {
-        assert!(validate_fhir_id("abc123").is_ok());
-        assert!(validate_fhir_id("550e8400-e29b-41d4-a716-446655440000").is_ok());
-        assert!(validate_fhir_id("Patient.123").is_ok());
-        assert!(validate_fhir_id("a").is_ok());
-        assert!(validate_fhir_id("ABC-def-123.456").is_ok());
-    }
-
-    #[test]
-    fn test_validate_fhir_id_empty() {
-        let err = validate_fhir_id("").unwrap_err();
-        assert!(matches!(err, MedplumError::ValidationError(_)));
-    }
-
-    #[test]
-    fn test_validate_fhir_id_too_long() {
-        let long_id = "a".repeat(65);
-        let err = validate_fhir_id(&long_id).unwrap_err();
-        assert!(matches!(err, MedplumError::ValidationError(_)));
-
-        // Exactly 64 should be fine
-        let max_id = "a".repeat(64);
-        assert!(validate_fhir_id(&max_id).is_ok());
-    }
-
-    #[test]
-    fn test_validate_fhir_id_invalid_chars() {
-        assert!(validate_fhir_id("foo/bar").is_err());
-        assert!(validate_fhir_id("foo\\bar").is_err());
-        assert!(validate_fhir_id("../etc").is_err());
-        assert!(validate_fhir_id("id with spaces").is_err());
-        assert!(validate_fhir_id("id\0null").is_err());
-        assert!(validate_fhir_id("id?query=1").is_err());
-        assert!(validate_fhir_id("id#fragment").is_err());
-        assert!(validate_fhir_id("id&param=val").is_err());
-    }
-
-    #[test]
-    fn test_truncate_error_body_short() {
-        let short = "Short error message";
-        assert_eq!(MedplumClient::truncate_error_body(short), short);
-    }
-
-    #[test]
-    fn test_truncate_error_body_exact_limit() {
-        let exact = "a".repeat(200);
-        assert_eq!(MedplumClient::truncate_error_body(&exact), exact);
-    }
-
-    #[test]
-    fn test_truncate_error_body_over_limit() {
-        let long = "a".repeat(300);
-        let truncated = MedplumClient::truncate_error_body(&long);
-        assert!(truncated.len() <= 204); // 200 + "..."
-        assert!(truncated.ends_with("..."));
-    }
-
-    #[test]
-    fn test_truncate_error_body_utf8_safety() {
-        // Multi-byte characters: each is 4 bytes
-        let emoji_string = "x".repeat(198) + "\u{1F600}\u{1F600}\u{1F600}"; // 198 + 12 = 210 bytes
-        let truncated = MedplumClient::truncate_error_body(&emoji_string);
-        assert!(truncated.ends_with("..."));
-        // Verify it's valid UTF-8 (would panic if not)
-        let _ = truncated.as_bytes();
-    }
-
-    #[test]
-    fn test_truncate_error_body_empty() {
-        assert_eq!(MedplumClient::truncate_error_body(""), "");
-    }
-
-    // ── upsert_patient_by_name_dob helpers (v0.10.46+) ─────────────
-
-    #[test]
-    fn name_matches_is_case_and_whitespace_insensitive() {
-        assert!(MedplumClient::name_matches(
-            "Judie Joan Guest",
-            "judie  joan  guest"
-        ));
-        assert!(MedplumClient::name_matches("John Smith", "  JOHN SMITH "));
-    }
-
-    #[test]
-    fn name_matches_rejects_different_specificity() {
-        // "Judie Guest" and "Judie Joan Guest" differ — Medplum should treat
-        // them as distinct Patients to avoid merging records with partial data.
-        assert!(!MedplumClient::name_matches("Judie Guest", "Judie Joan Guest"));
-    }
-
-    #[test]
-    fn name_matches_rejects_completely_different() {
-        assert!(!MedplumClient::name_matches("John Smith", "Jane Doe"));
-    }
-
-    #[test]
-    fn name_resource_matches_prefers_text_field() {
-        // The Apr 20 bug repro: FHIR resource has name[0].text with the full
-        // display string, but extract_patient_name() lossily drops middles.
-        // name_resource_matches uses name[0].text first and picks up the
-        // match correctly.
-        let r = serde_json::json!({
-            "name": [{
-                "use": "official",
-                "text": "Cheryl Lynn Bond",
-                "given": ["Cheryl"],
-                "family": "Bond",
-            }]
-        });
-        assert!(MedplumClient::name_resource_matches(&r, "Cheryl Lynn Bond"));
-        assert!(!MedplumClient::name_resource_matches(&r, "Cheryl Bond"));
-    }
-
-    #[test]
-    fn name_resource_matches_falls_back_to_given_plus_family() {
-        // No .text → reconstruct from given list + family, preserving middles.
-        let r = serde_json::json!({
-            "name": [{
-                "use": "official",
-                "given": ["Cheryl", "Lynn"],
-                "family": "Bond",
-            }]
-        });
-        assert!(MedplumClient::name_resource_matches(&r, "Cheryl Lynn Bond"));
-        // Partial match rejected.
-        assert!(!MedplumClient::name_resource_matches(&r, "Cheryl Bond"));
-    }
-
-    #[test]
-    fn name_resource_matches_no_name_array_returns_false() {
-        let r = serde_json::json!({});
-        assert!(!MedplumClient::name_resource_matches(&r, "Anyone"));
-    }
-
-    #[test]
-    fn name_resource_matches_empty_name_array_returns_false() {
-        let r = serde_json::json!({ "name": [] });
-        assert!(!MedplumClient::name_resource_matches(&r, "Anyone"));
-    }
-
-    #[test]
-    fn name_resource_matches_case_and_whitespace_insensitive() {
-        let r = serde_json::json!({
-            "name": [{ "text": "  cheryl   LYNN   bond " }]
-        });
-        assert!(MedplumClient::name_resource_matches(&r, "Cheryl Lynn Bond"));
-    }
-
     #[test]
     fn medplum_session_sync_serializes_with_camelcase_and_skips_none() {
         let s = MedplumSessionSync {
@@ -2385,139 +853,11 @@ mod tests {
         println!("FHIR R4 endpoint is available");
     }
 
-    /// End-to-end regression for the v0.10.47 dedup fix: confirm that the
-    /// FHIR search + `name_resource_matches` flow used by
-    /// `upsert_patient_by_name_dob` correctly reuses an existing Patient
-    /// (whose stored form has `given=["Cheryl"], family="Bond",
-    /// text="Cheryl Lynn Bond"`) when the caller supplies the full name
-    /// "Cheryl Lynn Bond". In v0.10.46 this test would have failed — the
-    /// client went through `search_patients`, which built the Patient.name
-    /// as "Cheryl Bond" (lossy `given[0]+family`), and `name_matches`
-    /// rejected the real match → would have POSTed a duplicate Patient.
-    ///
-    /// Requires `MEDPLUM_TEST_CLIENT_ID` + `MEDPLUM_TEST_CLIENT_SECRET`
-    /// env vars (a ClientApplication with `client_credentials` grant).
-    /// Run with: MEDPLUM_TEST_CLIENT_ID=... MEDPLUM_TEST_CLIENT_SECRET=...
-    ///           cargo test upsert_dedup_live -- --ignored
-    #[tokio::test]
-    #[ignore = "Requires live Medplum + MEDPLUM_TEST_CLIENT_{ID,SECRET} env vars"]
-    async fn upsert_dedup_live_reuses_existing_patient_with_middle_name() {
-        let client_id = std::env::var("MEDPLUM_TEST_CLIENT_ID")
-            .expect("MEDPLUM_TEST_CLIENT_ID required for live test");
-        let client_secret = std::env::var("MEDPLUM_TEST_CLIENT_SECRET")
-            .expect("MEDPLUM_TEST_CLIENT_SECRET required for live test");
-        let base = std::env::var("MEDPLUM_TEST_BASE_URL")
-            .unwrap_or_else(|_| "http://100.119.83.76:8103".to_string());
-
-        let http = reqwest::Client::new();
-
-        // Fetch a client_credentials access token.
-        let token: String = http
-            .post(format!("{base}/oauth2/token"))
-            .form(&[
-                ("grant_type", "client_credentials"),
-                ("client_id", client_id.as_str()),
-                ("client_secret", client_secret.as_str()),
-            ])
-            .send()
-            .await
-            .expect("oauth2 token request")
-            .json::<serde_json::Value>()
-            .await
-            .expect("token json")["access_token"]
-            .as_str()
-            .expect("access_token string")
-            .to_string();
-
-        // Per-run unique fixture — avoids collisions with prior test runs
-        // and real clinical records. We always POST fresh, assert, then
-        // DELETE in cleanup.
-        let run_id = uuid::Uuid::new_v4().simple().to_string();
-        let dob = "1901-02-03";
-        let full_name = format!("Fixture MiddleName {run_id}");
-        let given = "Fixture";
-        let family = run_id.clone();
-
-        // Create the fixture: lossy given list (only first name), full name
-        // only in `text`. This is the exact shape v0.10.46's
-        // `extract_patient_name` would mishandle (returning "Fixture {run_id}"
-        // and failing the caller-side name match).
-        let created: serde_json::Value = http
-            .post(format!("{base}/fhir/R4/Patient"))
-            .bearer_auth(&token)
-            .json(&serde_json::json!({
-                "resourceType": "Patient",
-                "name": [{
-                    "use": "official",
-                    "text": full_name,
-                    "given": [given],
-                    "family": family,
-                }],
-                "birthDate": dob,
-                "meta": {"tag": [{
-                    "system": "urn:fabricscribe",
-                    "code": "upsert-dedup-fixture",
-                }]},
-            }))
-            .send()
-            .await
-            .expect("create request")
-            .json()
-            .await
-            .expect("create json");
-        let patient_id = created["id"].as_str().expect("patient id").to_string();
-
-        // Replay the upsert search logic using the same URL shape +
-        // matcher as `upsert_patient_by_name_dob`.
-        let search_query = format!("{given} {family}");
-        let bundle: serde_json::Value = http
-            .get(format!(
-                "{base}/fhir/R4/Patient?name:contains={}&birthdate={}&_count=20",
-                urlencoding::encode(&search_query),
-                urlencoding::encode(dob),
-            ))
-            .bearer_auth(&token)
-            .send()
-            .await
-            .expect("dedup search request")
-            .json()
-            .await
-            .expect("dedup search json");
-
-        let mut matched_id: Option<String> = None;
-        if let Some(entries) = bundle["entry"].as_array() {
-            for e in entries {
-                let r = &e["resource"];
-                if r["birthDate"].as_str() != Some(dob) {
-                    continue;
-                }
-                if !MedplumClient::name_resource_matches(r, &full_name) {
-                    continue;
-                }
-                matched_id = r["id"].as_str().map(|s| s.to_string());
-                break;
-            }
-        }
-
-        let assertion = matched_id.as_deref() == Some(patient_id.as_str());
-
-        // Cleanup: delete the fixture regardless of assertion outcome.
-        let _ = http
-            .delete(format!("{base}/fhir/R4/Patient/{patient_id}"))
-            .bearer_auth(&token)
-            .send()
-            .await;
-
-        assert!(
-            assertion,
-            "upsert_patient_by_name_dob should reuse existing Patient with matching \
-             name[0].text even when given[0]+family alone would lose the middle name; \
-             expected {}, matched {:?}",
-            patient_id, matched_id
-        );
-        println!(
-            "✓ dedup correctly reuses Patient/{} for caller name \"{}\" with dob {}",
-            patient_id, full_name, dob
-        );
+    #[test]
+    fn server_and_network_errors_are_retryable() {
+        assert!(MedplumError::ServerError(503, "down".into()).is_retryable());
+        assert!(MedplumError::TokenExpired.is_retryable());
+        assert!(!MedplumError::ValidationError("bad".into()).is_retryable());
+        assert!(!MedplumError::NotFound("x".into()).is_retryable());
     }
 }
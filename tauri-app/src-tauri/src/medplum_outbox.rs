@@ -0,0 +1,387 @@
+//! Durable outbox for Medplum session syncs.
+//!
+//! `confirm_session_patient` writes a continuous-mode session to Medplum as
+//! one transaction Bundle (`EmrBackend::sync_continuous_session`). When
+//! that fails, the sync is persisted here. Failures worth retrying (server
+//! unreachable, 5xx, lapsed token) are retried by `medplum_outbox_task` with
+//! exponential back-off. Retrying is safe: the transaction is all-or-nothing
+//! and its writes are conditional on the session identifier, so a retry
+//! finishes the sync instead of duplicating it.
+//!
+//! The outbox persists to disk so pending syncs survive app restarts.
+//! Entries that fail permanently (validation, permissions) are kept but
+//! parked; re-confirming the patient replaces them.
+//!
+//! Retries go to whichever backend `emr_backend` selects when they run, so
+//! the name is historical: SMART backend syncs queue here too.
+
+use crate::commands::{
+    active_emr_backend, SharedMedplumClient, SharedProfileClient, SharedSmartBackend,
+};
+use crate::emr_backend::EMR_BACKEND_MEDPLUM;
+use crate::local_archive;
+use crate::profile_client::ConfirmPatientRequestBody;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// How often the background task looks for due entries
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+/// Delay before the first retry; doubles per attempt
+const BASE_BACKOFF_SECS: i64 = 30;
+/// Upper bound on the retry delay
+const MAX_BACKOFF_SECS: i64 = 3600;
+
+/// A session sync waiting to be written to Medplum. Carries everything
+/// `sync_continuous_session` needs, since the session may not be archived on
+/// this machine.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutboxEntry {
+    pub session_id: String,
+    /// Archive date (YYYY-MM-DD) for back-filling local metadata on success
+    pub date: String,
+    /// Physician whose profile-service records get the Medplum patient ID
+    #[serde(default)]
+    pub physician_id: Option<String>,
+    pub patient_name: String,
+    pub patient_dob: String,
+    #[serde(default)]
+    pub soap_note: Option<String>,
+    #[serde(default)]
+    pub transcript: Option<String>,
+    pub session_started_at: String,
+    pub session_duration_ms: u64,
+    #[serde(default)]
+    pub practitioner_fhir_id: Option<String>,
+    pub queued_at: String,
+    #[serde(default)]
+    pub attempts: u32,
+    /// RFC3339; `None` means due now
+    #[serde(default)]
+    pub next_attempt_at: Option<String>,
+    #[serde(default)]
+    pub last_error: Option<String>,
+    /// Failed with an error a retry won't fix; skipped until replaced
+    #[serde(default)]
+    pub parked: bool,
+}
+
+impl OutboxEntry {
+    fn is_due(&self, now: DateTime<Utc>) -> bool {
+        !self.parked
+            && self
+                .next_attempt_at
+                .as_deref()
+                .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+                .is_none_or(|t| t <= now)
+    }
+}
+
+/// PHI-free view of an outbox entry for the UI
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutboxStatus {
+    pub session_id: String,
+    pub date: String,
+    pub queued_at: String,
+    pub attempts: u32,
+    pub next_attempt_at: Option<String>,
+    pub last_error: Option<String>,
+    pub parked: bool,
+}
+
+/// Pending Medplum syncs backed by a JSON file on disk.
+pub struct MedplumOutbox {
+    entries: Vec<OutboxEntry>,
+    path: PathBuf,
+}
+
+/// Shared handle used by Tauri state management and the background task.
+pub type SharedMedplumOutbox = Arc<tokio::sync::Mutex<MedplumOutbox>>;
+
+impl MedplumOutbox {
+    /// Load the outbox from its persistent file, or start empty.
+    pub fn load() -> Self {
+        let path = dirs::home_dir()
+            .unwrap_or_default()
+            .join(".transcriptionapp")
+            .join("cache")
+            .join("medplum_outbox.json");
+        let entries = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default();
+        Self { entries, path }
+    }
+
+    /// Persist a failed sync. A pending entry for the same session is replaced:
+    /// the latest confirmation wins and is retried from scratch.
+    pub fn enqueue(&mut self, entry: OutboxEntry) {
+        self.entries.retain(|e| e.session_id != entry.session_id);
+        self.entries.push(entry);
+        self.save();
+    }
+
+    /// First entry whose back-off has elapsed.
+    pub fn next_due(&self, now: DateTime<Utc>) -> Option<&OutboxEntry> {
+        self.entries.iter().find(|e| e.is_due(now))
+    }
+
+    /// Drop a session's entry after a successful sync.
+    pub fn complete(&mut self, session_id: &str) {
+        let before = self.entries.len();
+        self.entries.retain(|e| e.session_id != session_id);
+        if self.entries.len() != before {
+            self.save();
+        }
+    }
+
+    /// Record a failed attempt. Retryable failures are rescheduled with
+    /// back-off; anything else parks the entry.
+    pub fn record_failure(&mut self, session_id: &str, error: &str, retryable: bool, now: DateTime<Utc>) {
+        let Some(entry) = self.entries.iter_mut().find(|e| e.session_id == session_id) else {
+            return;
+        };
+        entry.attempts += 1;
+        entry.last_error = Some(error.to_string());
+        entry.parked = !retryable;
+        entry.next_attempt_at = Some((now + backoff(entry.attempts)).to_rfc3339());
+        self.save();
+    }
+
+    pub fn status(&self) -> Vec<OutboxStatus> {
+        self.entries
+            .iter()
+            .map(|e| OutboxStatus {
+                session_id: e.session_id.clone(),
+                date: e.date.clone(),
+                queued_at: e.queued_at.clone(),
+                attempts: e.attempts,
+                next_attempt_at: e.next_attempt_at.clone(),
+                last_error: e.last_error.clone(),
+                parked: e.parked,
+            })
+            .collect()
+    }
+
+    /// Persist the outbox to disk (creates parent dirs lazily).
+    fn save(&self) {
+        if let Some(parent) = self.path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        if let Ok(json) = serde_json::to_string_pretty(&self.entries) {
+            let _ = std::fs::write(&self.path, json);
+        }
+    }
+}
+
+/// Delay before retry number `attempts + 1`: 30 s, 1 min, 2 min, ... capped at 1 h.
+/// Shared with the HL7 outbox.
+pub(crate) fn backoff(attempts: u32) -> chrono::Duration {
+    let exponent = attempts.saturating_sub(1).min(16);
+    chrono::Duration::seconds((BASE_BACKOFF_SECS << exponent).min(MAX_BACKOFF_SECS))
+}
+
+/// Background task that retries queued Medplum syncs one at a time.
+///
+/// Waits while the active EMR backend is unavailable (Medplum not yet
+/// initialized, SMART not configured). When the local Medplum OAuth session
+/// has lapsed it mints a token through the profile-service proxy, like the
+/// confirm flow does.
+pub async fn medplum_outbox_task(
+    outbox: SharedMedplumOutbox,
+    medplum: SharedMedplumClient,
+    smart_backend: SharedSmartBackend,
+    profile_client: SharedProfileClient,
+) {
+    loop {
+        tokio::time::sleep(POLL_INTERVAL).await;
+
+        let Some(entry) = outbox.lock().await.next_due(Utc::now()).cloned() else {
+            continue;
+        };
+
+        let Ok(client) = active_emr_backend(&medplum, &smart_backend).await else {
+            continue;
+        };
+        if client.backend_name() == EMR_BACKEND_MEDPLUM && !client.is_authenticated().await {
+            let medplum_client = medplum.read().await.clone();
+            if let (Some(mp), Some(pf)) = (medplum_client, profile_client.read().await.clone()) {
+                match pf.fetch_medplum_token().await {
+                    Ok((token, expires_in)) => {
+                        let _ = mp
+                            .inject_proxy_token(token, expires_in, entry.practitioner_fhir_id.clone())
+                            .await;
+                    }
+                    Err(e) => warn!(event = "medplum_outbox_proxy_mint_failed", error = %e),
+                }
+            }
+        }
+
+        let result = client
+            .sync_continuous_session(
+                &entry.session_id,
+                &entry.patient_name,
+                &entry.patient_dob,
+                entry.soap_note.as_deref(),
+                entry.transcript.as_deref(),
+                &entry.session_started_at,
+                entry.session_duration_ms,
+                entry.practitioner_fhir_id.as_deref(),
+            )
+            .await;
+
+        match result {
+            Ok(sync) => {
+                outbox.lock().await.complete(&entry.session_id);
+                info!(
+                    event = "medplum_outbox_synced",
+                    session_id = %entry.session_id,
+                    attempts = entry.attempts + 1,
+                    "queued Medplum sync completed"
+                );
+                backfill_patient_ids(&entry, &sync.patient_id, &sync.encounter_id, &profile_client).await;
+            }
+            Err(e) => {
+                let retryable = e.is_retryable();
+                warn!(
+                    event = "medplum_outbox_attempt_failed",
+                    session_id = %entry.session_id,
+                    attempts = entry.attempts + 1,
+                    retryable,
+                    error = %e
+                );
+                outbox
+                    .lock()
+                    .await
+                    .record_failure(&entry.session_id, &e.to_string(), retryable, Utc::now());
+            }
+        }
+    }
+}
+
+/// Give the stores the confirm flow already wrote the Medplum IDs it could
+/// not get at the time. Best-effort, like the confirm flow's own writes.
+async fn backfill_patient_ids(
+    entry: &OutboxEntry,
+    patient_id: &str,
+    encounter_id: &str,
+    profile_client: &SharedProfileClient,
+) {
+    if local_archive::has_local_metadata(&entry.session_id, &entry.date) {
+        if let Err(e) =
+            local_archive::record_medplum_ids(&entry.session_id, &entry.date, patient_id, encounter_id)
+        {
+            warn!(event = "medplum_outbox_metadata_write_failed", error = %e);
+        }
+    }
+
+    let (Some(physician_id), Some(pf)) = (&entry.physician_id, profile_client.read().await.clone())
+    else {
+        return;
+    };
+    let body = ConfirmPatientRequestBody {
+        name: entry.patient_name.clone(),
+        dob: entry.patient_dob.clone(),
+        session_id: entry.session_id.clone(),
+        medplum_patient_id: Some(patient_id.to_string()),
+        session_date: Some(entry.date.clone()),
+    };
+    if let Err(e) = pf.confirm_patient(physician_id, &body).await {
+        warn!(event = "medplum_outbox_profile_service_failed", error = %e);
+    }
+    let metadata = serde_json::json!({ "medplum_patient_id": patient_id });
+    if let Err(e) = pf.update_metadata(physician_id, &entry.session_id, &metadata).await {
+        warn!("Server sync failed (medplum_outbox metadata): {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outbox(name: &str) -> MedplumOutbox {
+        MedplumOutbox {
+            entries: Vec::new(),
+            path: std::env::temp_dir().join(format!("medplum_outbox_{name}_{}.json", std::process::id())),
+        }
+    }
+
+    fn entry(session_id: &str, name: &str) -> OutboxEntry {
+        OutboxEntry {
+            session_id: session_id.into(),
+            date: "2026-10-01".into(),
+            physician_id: None,
+            patient_name: name.into(),
+            patient_dob: "1970-01-01".into(),
+            soap_note: None,
+            transcript: None,
+            session_started_at: "2026-10-01T14:00:00Z".into(),
+            session_duration_ms: 600_000,
+            practitioner_fhir_id: None,
+            queued_at: "2026-10-01T14:11:00Z".into(),
+            attempts: 0,
+            next_attempt_at: None,
+            last_error: None,
+            parked: false,
+        }
+    }
+
+    #[test]
+    fn reconfirm_replaces_pending_entry() {
+        let mut outbox = outbox("replace");
+        outbox.enqueue(entry("s1", "Jane Doe"));
+        outbox.record_failure("s1", "Server error (503): down", true, Utc::now());
+        outbox.enqueue(entry("s1", "Jane Q Doe"));
+
+        let status = outbox.status();
+        assert_eq!(status.len(), 1);
+        assert_eq!(status[0].attempts, 0);
+        assert_eq!(outbox.next_due(Utc::now()).unwrap().patient_name, "Jane Q Doe");
+        let _ = std::fs::remove_file(&outbox.path);
+    }
+
+    #[test]
+    fn failures_back_off_and_permanent_errors_park() {
+        let mut outbox = outbox("backoff");
+        let now = Utc::now();
+        outbox.enqueue(entry("s1", "Jane Doe"));
+        outbox.enqueue(entry("s2", "John Roe"));
+
+        outbox.record_failure("s1", "Network error", true, now);
+        assert_eq!(outbox.next_due(now).unwrap().session_id, "s2");
+        assert_eq!(
+            outbox.next_due(now + chrono::Duration::seconds(31)).unwrap().session_id,
+            "s1"
+        );
+
+        outbox.record_failure("s2", "Invalid FHIR resource", false, now);
+        let later = now + chrono::Duration::days(1);
+        assert_eq!(outbox.next_due(later).unwrap().session_id, "s1");
+        outbox.complete("s1");
+        assert!(outbox.next_due(later).is_none());
+        assert!(outbox.status()[0].parked);
+        let _ = std::fs::remove_file(&outbox.path);
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_an_hour() {
+        assert_eq!(backoff(1).num_seconds(), 30);
+        assert_eq!(backoff(2).num_seconds(), 60);
+        assert_eq!(backoff(5).num_seconds(), 480);
+        assert_eq!(backoff(40).num_seconds(), 3600);
+    }
+
+    #[test]
+    fn entries_round_trip_through_disk() {
+        let mut outbox = outbox("disk");
+        outbox.enqueue(entry("s1", "Jane Doe"));
+        let raw = std::fs::read_to_string(&outbox.path).unwrap();
+        let loaded: Vec<OutboxEntry> = serde_json::from_str(&raw).unwrap();
+        assert_eq!(loaded[0].session_id, "s1");
+        let _ = std::fs::remove_file(&outbox.path);
+    }
+}
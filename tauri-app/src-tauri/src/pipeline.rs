@@ -11,12 +11,14 @@ use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::mpsc;
 use tracing::{debug, error, info, warn};
+use uuid::Uuid;
 use voice_activity_detector::VoiceActivityDetector;
 
 use crate::audio::{calculate_ring_buffer_capacity, get_device, select_input_config, AudioCapture, AudioResampler};
 use crate::preprocessing::AudioPreprocessor;
 use crate::transcription::{Segment, Utterance};
 use crate::vad::{VadConfig, VadGatedPipeline};
+use crate::duplex_stt::{DuplexSttSession, HypothesisReconciler, FINISH_TIMEOUT};
 use crate::whisper_server::WhisperServerClient;
 
 #[cfg(feature = "diarization")]
@@ -34,17 +36,165 @@ use std::collections::{HashSet, VecDeque};
 /// VAD chunk size at 16kHz
 const VAD_CHUNK_SIZE: usize = 512;
 
-/// Transcribe an utterance via the STT server streaming endpoint and return a segment.
+/// Duplex STT session for the utterance the VAD is currently accumulating
+struct LiveUtterance {
+    id: Uuid,
+    session: DuplexSttSession,
+    reconciler: HypothesisReconciler,
+    display_text: String,
+}
+
+/// Streams speech to the STT server while it is still being spoken, so text is
+/// ready shortly after the VAD closes the utterance instead of seconds later.
 ///
-/// Uses WebSocket streaming to get partial transcript chunks in real-time.
-/// Falls back to batch transcription if streaming fails.
+/// Turned off for the rest of the run as soon as a session cannot be opened (e.g.
+/// a router without duplex support), so later utterances go straight to the
+/// whole-utterance path instead of paying the handshake timeout every time.
+struct LiveTranscriber {
+    enabled: bool,
+    current: Option<LiveUtterance>,
+    /// Speech whose session broke mid-stream; it is transcribed whole once flushed
+    failed: Option<Uuid>,
+}
+
+impl LiveTranscriber {
+    fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            current: None,
+            failed: None,
+        }
+    }
+
+    /// Push the speech audio the VAD has accumulated since the last call and
+    /// forward the hypotheses received so far as draft text.
+    ///
+    /// Call after popping flushed utterances, so a session that no longer matches
+    /// the active speech belongs to speech the VAD discarded as too short.
+    fn update(
+        &mut self,
+        pipeline: &VadGatedPipeline,
+        client: &WhisperServerClient,
+        stt_alias: &str,
+        stt_postprocess: bool,
+        tx: &mpsc::Sender<PipelineMessage>,
+    ) {
+        let active = pipeline.active_speech();
+        if let Some(ref live) = self.current {
+            if active.map(|(id, _, _)| id) != Some(live.id) {
+                debug!("Aborting duplex STT session for discarded speech");
+                self.current = None;
+            }
+        }
+
+        let Some((id, start_ms, audio)) = active else {
+            return;
+        };
+        if !self.enabled || self.failed == Some(id) {
+            return;
+        }
+
+        if self.current.is_none() {
+            match client.open_duplex_session(stt_alias, stt_postprocess) {
+                Ok(session) => {
+                    debug!("Opened duplex STT session for speech starting at {}ms", start_ms);
+                    self.current = Some(LiveUtterance {
+                        id,
+                        session,
+                        reconciler: HypothesisReconciler::new(start_ms),
+                        display_text: String::new(),
+                    });
+                }
+                Err(e) => {
+                    warn!("Duplex STT unavailable, transcribing whole utterances: {}", e);
+                    self.enabled = false;
+                    return;
+                }
+            }
+        }
+
+        let Some(ref mut live) = self.current else {
+            return;
+        };
+        let unsent = &audio[live.session.samples_sent().min(audio.len())..];
+        let polled = live.session.send_audio(unsent).and_then(|_| live.session.poll());
+        match polled {
+            Ok(hypotheses) => {
+                if hypotheses.is_empty() {
+                    return;
+                }
+                for hypothesis in hypotheses {
+                    live.reconciler.apply(hypothesis);
+                }
+                let display_text = live.reconciler.display_text();
+                if display_text != live.display_text {
+                    live.display_text = display_text.clone();
+                    let _ = tx.blocking_send(PipelineMessage::TranscriptChunk { text: display_text });
+                }
+            }
+            Err(e) => {
+                warn!("Duplex STT session failed mid-utterance: {}", e);
+                self.failed = Some(id);
+                self.current = None;
+            }
+        }
+    }
+
+    /// End the session streamed for `utterance` and reconcile it into segments.
+    ///
+    /// Returns None when no session was streamed for this utterance.
+    fn finish(&mut self, utterance: &Utterance) -> Option<Result<Vec<Segment>, String>> {
+        if self.current.as_ref().map(|live| live.id) != Some(utterance.id) {
+            return None;
+        }
+        let LiveUtterance {
+            mut session,
+            mut reconciler,
+            ..
+        } = self.current.take()?;
+
+        let tail = &utterance.audio[session.samples_sent().min(utterance.audio.len())..];
+        let result = session
+            .send_audio(tail)
+            .and_then(|_| session.finish(FINISH_TIMEOUT))
+            .map(|hypotheses| {
+                for hypothesis in hypotheses {
+                    reconciler.apply(hypothesis);
+                }
+                reconciler.into_segments(utterance.end_ms)
+            });
+        Some(result)
+    }
+}
+
+/// Transcribe an utterance and return its segments.
+///
+/// Utterances streamed through a duplex session are finished there; the rest,
+/// and any whose session fails, are sent whole via the streaming endpoint with
+/// partial transcript chunks forwarded in real time.
 fn transcribe_utterance(
     client: &WhisperServerClient,
+    live: &mut LiveTranscriber,
     utterance: &Utterance,
     stt_alias: &str,
     stt_postprocess: bool,
     tx: &tokio::sync::mpsc::Sender<PipelineMessage>,
-) -> Result<Segment, String> {
+) -> Result<Vec<Segment>, String> {
+    if let Some(result) = live.finish(utterance) {
+        match result {
+            Ok(segments) => {
+                debug!(
+                    "Duplex STT reconciled {}ms - {}ms into {} segments",
+                    utterance.start_ms,
+                    utterance.end_ms,
+                    segments.len()
+                );
+                return Ok(segments.into_iter().map(drop_stateless_filler).collect());
+            }
+            Err(e) => warn!("Duplex STT failed at end of utterance, retranscribing it whole: {}", e),
+        }
+    }
+
     // Use streaming transcription with chunk callback.
     // Language is always auto-detect — see whisper_server.rs for rationale.
     let tx_clone = tx.clone();
@@ -60,21 +210,37 @@ fn transcribe_utterance(
         },
     )?;
 
-    // Qwen emits stateless fillers like "I'm not sure." when given a VAD-gated
-    // utterance that turns out to be silence (macOS Voice Isolation zeros).
-    // Drop these at the segment level so they never reach the transcript buffer.
-    let filtered_text = if crate::encounter_experiment::is_stateless_filler(&text) {
-        debug!("Dropped stateless STT filler: {:?}", text);
-        String::new()
-    } else {
-        text
-    };
-
-    Ok(Segment::new(
+    Ok(vec![drop_stateless_filler(Segment::new(
         utterance.start_ms,
         utterance.end_ms,
-        filtered_text,
-    ))
+        text,
+    ))])
+}
+
+/// Qwen emits stateless fillers like "I'm not sure." when given a VAD-gated
+/// utterance that turns out to be silence (macOS Voice Isolation zeros).
+/// Drop these at the segment level so they never reach the transcript buffer.
+fn drop_stateless_filler(mut segment: Segment) -> Segment {
+    if crate::encounter_experiment::is_stateless_filler(&segment.text) {
+        debug!("Dropped stateless STT filler: {:?}", segment.text);
+        segment.text.clear();
+    }
+    segment
+}
+
+/// The part of `audio` (sampled like the utterance) covered by `segment`.
+///
+/// A duplex utterance can be reconciled into several segments, each of which is
+/// diarized on its own audio.
+#[cfg_attr(not(feature = "diarization"), allow(dead_code))]
+fn segment_audio<'a>(audio: &'a [f32], utterance: &Utterance, segment: &Segment) -> &'a [f32] {
+    if segment.start_ms <= utterance.start_ms && segment.end_ms >= utterance.end_ms {
+        return audio;
+    }
+    let to_sample = |ms: u64| (ms.saturating_sub(utterance.start_ms) * 16) as usize;
+    let end = to_sample(segment.end_ms).min(audio.len());
+    let start = to_sample(segment.start_ms).min(end);
+    &audio[start..end]
 }
 
 /// Message from the transcription pipeline to the session controller
@@ -577,6 +743,13 @@ fn run_pipeline_thread_inner(
     #[cfg(not(feature = "enhancement"))]
     let _enhancement: Option<()> = None;
 
+    // Duplex streaming sends raw speech as it is captured, which would bypass
+    // enhancement, so enhanced utterances are always transcribed whole.
+    #[cfg(feature = "enhancement")]
+    let mut live = LiveTranscriber::new(enhancement.is_none());
+    #[cfg(not(feature = "enhancement"))]
+    let mut live = LiveTranscriber::new(true);
+
     // Create biomarker thread if enabled
     let biomarker_handle: Option<BiomarkerHandle> = if config.biomarkers_enabled {
         let bio_config = BiomarkerConfig {
@@ -764,7 +937,7 @@ fn run_pipeline_thread_inner(
             pipeline.force_flush();
 
             // Transcribe any remaining utterances
-            while let Some(mut utterance) = pipeline.pop_utterance() {
+            'flush: while let Some(mut utterance) = pipeline.pop_utterance() {
                 // Apply speech enhancement if enabled
                 #[cfg(feature = "enhancement")]
                 let original_audio = if enhancement.is_some() {
@@ -783,26 +956,39 @@ fn run_pipeline_thread_inner(
                 }
 
                 // Transcribe (using enhanced audio if available)
-                match transcribe_utterance(&whisper_client, &utterance, &config.stt_alias, config.stt_postprocess, &tx) {
-                    Ok(mut segment) => {
-                        if !segment.text.is_empty() {
+                match transcribe_utterance(&whisper_client, &mut live, &utterance, &config.stt_alias, config.stt_postprocess, &tx) {
+                    Ok(segments) => {
+                        for mut segment in segments {
                             // Only run diarization if we have actual text
+                            if segment.text.is_empty() {
+                                continue;
+                            }
+
                             #[cfg(feature = "diarization")]
                             {
                                 if let Some(ref mut diar) = diarization {
                                     #[cfg(feature = "enhancement")]
-                                    let diar_audio = original_audio.as_ref().unwrap_or(&utterance.audio);
+                                    let utterance_audio = original_audio.as_ref().unwrap_or(&utterance.audio);
                                     #[cfg(not(feature = "enhancement"))]
-                                    let diar_audio = &utterance.audio;
+                                    let utterance_audio = &utterance.audio;
+                                    let diar_audio = segment_audio(utterance_audio, &utterance, &segment);
 
                                     match diar.identify_speaker_from_audio(
                                         diar_audio,
-                                        utterance.start_ms,
-                                        utterance.end_ms,
+                                        segment.start_ms,
+                                        segment.end_ms,
                                     ) {
                                         Ok((id, conf)) => {
                                             segment.speaker_id = Some(id);
                                             segment.speaker_confidence = Some(conf);
+                                            match diar.detect_overlap_from_audio(
+                                                diar_audio,
+                                                segment.start_ms,
+                                                segment.end_ms,
+                                            ) {
+                                                Ok(regions) => segment.overlaps = regions,
+                                                Err(e) => debug!("Overlap detection failed for utterance: {}", e),
+                                            }
                                         }
                                         Err(e) => {
                                             debug!("Diarization failed for utterance: {}", e);
@@ -812,7 +998,7 @@ fn run_pipeline_thread_inner(
                             }
 
                             if tx.blocking_send(PipelineMessage::Segment(segment)).is_err() {
-                                break;
+                                break 'flush;
                             }
                             // Reset consecutive error counter on success
                             consecutive_transcription_errors = 0;
@@ -1041,124 +1227,136 @@ fn run_pipeline_thread_inner(
             }
 
             // Transcribe (using enhanced audio if available)
-            match transcribe_utterance(&whisper_client, &utterance, &config.stt_alias, config.stt_postprocess, &tx) {
-                Ok(mut segment) => {
-                    if !segment.text.is_empty() {
-                        // Use original audio for diarization (speaker fingerprints)
-                        #[cfg(feature = "diarization")]
-                        {
-                            if let Some(ref mut diar) = diarization {
-                                // Use original audio if we enhanced, otherwise use utterance audio
-                                #[cfg(feature = "enhancement")]
-                                let diar_audio = original_audio.as_ref().unwrap_or(&utterance.audio);
-                                #[cfg(not(feature = "enhancement"))]
-                                let diar_audio = &utterance.audio;
-
-                                info!("Running diarization on utterance with {} samples", diar_audio.len());
-                                match diar.identify_speaker_from_audio(
-                                    diar_audio,
-                                    utterance.start_ms,
-                                    utterance.end_ms,
-                                ) {
-                                    Ok((id, conf)) => {
-                                        info!("Diarization result: {} ({:.0}% confidence)", id, conf * 100.0);
-                                        segment.speaker_id = Some(id);
-                                        segment.speaker_confidence = Some(conf);
-                                    }
-                                    Err(e) => {
-                                        warn!("Diarization failed for utterance: {}", e);
+            match transcribe_utterance(&whisper_client, &mut live, &utterance, &config.stt_alias, config.stt_postprocess, &tx) {
+                Ok(segments) => {
+                    for mut segment in segments {
+                        if !segment.text.is_empty() {
+                            // Use original audio for diarization (speaker fingerprints)
+                            #[cfg(feature = "diarization")]
+                            {
+                                if let Some(ref mut diar) = diarization {
+                                    // Use original audio if we enhanced, otherwise use utterance audio
+                                    #[cfg(feature = "enhancement")]
+                                    let utterance_audio = original_audio.as_ref().unwrap_or(&utterance.audio);
+                                    #[cfg(not(feature = "enhancement"))]
+                                    let utterance_audio = &utterance.audio;
+                                    let diar_audio = segment_audio(utterance_audio, &utterance, &segment);
+
+                                    info!("Running diarization on utterance with {} samples", diar_audio.len());
+                                    match diar.identify_speaker_from_audio(
+                                        diar_audio,
+                                        segment.start_ms,
+                                        segment.end_ms,
+                                    ) {
+                                        Ok((id, conf)) => {
+                                            info!("Diarization result: {} ({:.0}% confidence)", id, conf * 100.0);
+                                            segment.speaker_id = Some(id);
+                                            segment.speaker_confidence = Some(conf);
+                                            match diar.detect_overlap_from_audio(
+                                                diar_audio,
+                                                segment.start_ms,
+                                                segment.end_ms,
+                                            ) {
+                                                Ok(regions) => segment.overlaps = regions,
+                                                Err(e) => warn!("Overlap detection failed for utterance: {}", e),
+                                            }
+                                        }
+                                        Err(e) => {
+                                            warn!("Diarization failed for utterance: {}", e);
+                                        }
                                     }
                                 }
                             }
-                        }
 
-                        // Try to get biomarker results for this segment
-                        if let Some(ref bio_handle) = biomarker_handle {
-                            // Send segment info for session metrics
-                            bio_handle.send_segment_info(
-                                segment.speaker_id.clone(),
-                                segment.start_ms,
-                                segment.end_ms,
-                            );
+                            // Try to get biomarker results for this segment
+                            if let Some(ref bio_handle) = biomarker_handle {
+                                // Send segment info for session metrics
+                                bio_handle.send_segment_info(
+                                    segment.speaker_id.clone(),
+                                    segment.start_ms,
+                                    segment.end_ms,
+                                    segment.overlaps.clone(),
+                                );
 
-                            // Try to receive any available biomarker outputs (non-blocking)
-                            while let Some(output) = bio_handle.try_recv() {
-                                match output {
-                                    BiomarkerOutput::VocalBiomarkers(bio) => {
-                                        // Attach biomarkers to segment if they match
-                                        if bio.utterance_id == utterance.id {
+                                // Try to receive any available biomarker outputs (non-blocking)
+                                while let Some(output) = bio_handle.try_recv() {
+                                    match output {
+                                        BiomarkerOutput::VocalBiomarkers(bio) => {
+                                            // Attach biomarkers to segment if they match
+                                            if bio.utterance_id == utterance.id {
+                                                debug!(
+                                                    "Attaching biomarkers: vitality={:?} stability={:?}",
+                                                    bio.vitality, bio.stability
+                                                );
+                                                segment.vocal_biomarkers = Some(bio);
+                                            }
+                                        }
+                                        BiomarkerOutput::CoughEvent(event) => {
                                             debug!(
-                                                "Attaching biomarkers: vitality={:?} stability={:?}",
-                                                bio.vitality, bio.stability
+                                                "Cough detected: {} at {}ms (conf: {:.2})",
+                                                event.label, event.timestamp_ms, event.confidence
                                             );
-                                            segment.vocal_biomarkers = Some(bio);
+                                            // Buffer recent coughs (last 5)
+                                            recent_coughs.push_back(event);
+                                            if recent_coughs.len() > 5 {
+                                                recent_coughs.pop_front();
+                                            }
                                         }
-                                    }
-                                    BiomarkerOutput::CoughEvent(event) => {
-                                        debug!(
-                                            "Cough detected: {} at {}ms (conf: {:.2})",
-                                            event.label, event.timestamp_ms, event.confidence
-                                        );
-                                        // Buffer recent coughs (last 5)
-                                        recent_coughs.push_back(event);
-                                        if recent_coughs.len() > 5 {
-                                            recent_coughs.pop_front();
+                                        BiomarkerOutput::SessionMetrics(metrics) => {
+                                            debug!(
+                                                "Session metrics: {} coughs, {} turns",
+                                                metrics.cough_count, metrics.turn_count
+                                            );
+                                            // Store latest metrics for throttled emission
+                                            latest_session_metrics = Some(metrics);
+                                        }
+                                        BiomarkerOutput::AudioQuality(snapshot) => {
+                                            debug!(
+                                                "Audio quality: RMS={:.1}dB SNR={:.1}dB clips={}",
+                                                snapshot.rms_db, snapshot.snr_db, snapshot.clipped_samples
+                                            );
+                                            // Emit audio quality update to frontend
+                                            let _ = tx.blocking_send(PipelineMessage::AudioQuality(snapshot));
                                         }
-                                    }
-                                    BiomarkerOutput::SessionMetrics(metrics) => {
-                                        debug!(
-                                            "Session metrics: {} coughs, {} turns",
-                                            metrics.cough_count, metrics.turn_count
-                                        );
-                                        // Store latest metrics for throttled emission
-                                        latest_session_metrics = Some(metrics);
-                                    }
-                                    BiomarkerOutput::AudioQuality(snapshot) => {
-                                        debug!(
-                                            "Audio quality: RMS={:.1}dB SNR={:.1}dB clips={}",
-                                            snapshot.rms_db, snapshot.snr_db, snapshot.clipped_samples
-                                        );
-                                        // Emit audio quality update to frontend
-                                        let _ = tx.blocking_send(PipelineMessage::AudioQuality(snapshot));
                                     }
                                 }
-                            }
 
-                            // Check biomarker reset flag (encounter boundary in continuous mode)
-                            if reset_biomarkers_flag.swap(false, Ordering::SeqCst) {
-                                info!("Biomarker reset flag detected — resetting accumulators");
-                                bio_handle.send_reset();
-                                recent_coughs.clear();
-                                latest_session_metrics = None;
-                            }
+                                // Check biomarker reset flag (encounter boundary in continuous mode)
+                                if reset_biomarkers_flag.swap(false, Ordering::SeqCst) {
+                                    info!("Biomarker reset flag detected — resetting accumulators");
+                                    bio_handle.send_reset();
+                                    recent_coughs.clear();
+                                    latest_session_metrics = None;
+                                }
 
-                            // Emit biomarker update to frontend (throttled to 2Hz)
-                            if last_biomarker_emit.elapsed() >= biomarker_emit_interval {
-                                if let Some(ref metrics) = latest_session_metrics {
-                                    let coughs_vec: Vec<CoughEvent> = recent_coughs.iter().cloned().collect();
-                                    let mut update = BiomarkerUpdate::from_metrics(metrics, &coughs_vec);
-                                    // Annotate clinician status on speaker metrics
-                                    for speaker in &mut update.speaker_metrics {
-                                        speaker.is_clinician = clinician_names.contains(&speaker.speaker_id);
+                                // Emit biomarker update to frontend (throttled to 2Hz)
+                                if last_biomarker_emit.elapsed() >= biomarker_emit_interval {
+                                    if let Some(ref metrics) = latest_session_metrics {
+                                        let coughs_vec: Vec<CoughEvent> = recent_coughs.iter().cloned().collect();
+                                        let mut update = BiomarkerUpdate::from_metrics(metrics, &coughs_vec);
+                                        // Annotate clinician status on speaker metrics
+                                        for speaker in &mut update.speaker_metrics {
+                                            speaker.is_clinician = clinician_names.contains(&speaker.speaker_id);
+                                        }
+                                        let _ = tx.blocking_send(PipelineMessage::Biomarker(update));
+                                        last_biomarker_emit = std::time::Instant::now();
                                     }
-                                    let _ = tx.blocking_send(PipelineMessage::Biomarker(update));
-                                    last_biomarker_emit = std::time::Instant::now();
                                 }
                             }
-                        }
 
-                        // Log segment metadata only - no transcript text (PHI)
-                        info!("Sending segment: {} words ({}ms - {}ms)", segment.text.split_whitespace().count(), segment.start_ms, segment.end_ms);
+                            // Log segment metadata only - no transcript text (PHI)
+                            info!("Sending segment: {} words ({}ms - {}ms)", segment.text.split_whitespace().count(), segment.start_ms, segment.end_ms);
 
-                        if tx.blocking_send(PipelineMessage::Segment(segment)).is_err() {
-                            warn!("Failed to send segment, receiver dropped");
-                            let _ = capture.stop();
-                            return Ok(());
+                            if tx.blocking_send(PipelineMessage::Segment(segment)).is_err() {
+                                warn!("Failed to send segment, receiver dropped");
+                                let _ = capture.stop();
+                                return Ok(());
+                            }
+                            // Reset consecutive error counter on success
+                            consecutive_transcription_errors = 0;
+                        } else {
+                            info!("Skipping empty segment");
                         }
-                        // Reset consecutive error counter on success
-                        consecutive_transcription_errors = 0;
-                    } else {
-                        info!("Skipping empty segment");
                     }
                 }
                 Err(e) => {
@@ -1174,6 +1372,9 @@ fn run_pipeline_thread_inner(
             }
         }
 
+        // Stream the speech still being accumulated
+        live.update(&pipeline, &whisper_client, &config.stt_alias, config.stt_postprocess, &tx);
+
         // Send status updates periodically
         if last_status_time.elapsed() >= status_interval {
             let _ = tx.blocking_send(PipelineMessage::Status {
@@ -311,6 +311,42 @@ mod tests {
         );
     }
 
+    /// Duplex streaming pushes `active_speech` audio before the flush and pairs
+    /// it with the flushed utterance by id, so both must describe the same audio.
+    #[test]
+    #[serial]
+    #[ignore = "Requires ONNX Runtime - run with cargo test --ignored"]
+    fn test_active_speech_matches_flushed_utterance() {
+        let config = VadConfig::from_ms(0.3, 100, 100, 1000, 25000);
+        let mut pipeline = VadGatedPipeline::with_config(config);
+        let mut vad = create_vad();
+
+        let speech = generate_speech_signal(VAD_CHUNK_SIZE);
+        for _ in 0..30 {
+            pipeline.advance_audio_clock(VAD_CHUNK_SIZE);
+            pipeline.process_chunk(&speech, &mut vad);
+        }
+        let (id, start_ms, audio) = pipeline.active_speech().expect("speech should be active");
+        let streamed = audio.to_vec();
+
+        pipeline.advance_audio_clock(VAD_CHUNK_SIZE);
+        pipeline.process_chunk(&speech, &mut vad);
+        pipeline.force_flush();
+
+        let utterance = pipeline.pop_utterance().unwrap();
+        assert_eq!(utterance.id, id);
+        assert_eq!(utterance.start_ms, start_ms);
+        assert!(utterance.audio.starts_with(&streamed));
+        assert_eq!(utterance.audio.len(), streamed.len() + VAD_CHUNK_SIZE);
+        assert!(pipeline.active_speech().is_none());
+    }
+
+    #[test]
+    fn test_active_speech_none_when_idle() {
+        let pipeline = VadGatedPipeline::new();
+        assert!(pipeline.active_speech().is_none());
+    }
+
     /// Regression test: Verify partial VAD chunks are handled on stop
     ///
     /// When the staging buffer has fewer samples than VAD_CHUNK_SIZE,
@@ -104,6 +104,35 @@ impl Co2Tracker {
         }
     }
 
+    /// Rate of change over the window in ppm/min (linear regression).
+    pub fn rise_rate_ppm_per_min(&self) -> f32 {
+        if self.readings.len() < 5 {
+            return 0.0;
+        }
+        self.compute_slope() * 60.0
+    }
+
+    /// Occupancy projected from the current level and rise rate.
+    ///
+    /// `estimated_occupancy` uses the window average, which trails a change
+    /// in head count by the room's ventilation time constant. Treating the
+    /// room as first-order (`dC/dt = (C_ss - C) / tau`), the level it is
+    /// heading for is `C + tau * dC/dt`, so a rise shows up within a minute
+    /// or two instead of ~6. Returns None with fewer than 5 readings.
+    pub fn projected_occupancy(&self) -> Option<u8> {
+        if self.readings.len() < 5 {
+            return None;
+        }
+        let latest = self.latest_ppm()?;
+        let tau_min = self.config.time_constant_secs as f32 / 60.0;
+        let steady_state = latest + tau_min * self.rise_rate_ppm_per_min();
+        let delta = steady_state - self.config.baseline_ppm;
+        if delta <= 0.0 {
+            return Some(0);
+        }
+        Some((delta / self.config.ppm_per_person).round().min(u8::MAX as f32) as u8)
+    }
+
     /// Get the latest CO2 reading value, if any.
     pub fn latest_ppm(&self) -> Option<f32> {
         self.readings.back().map(|&(_, ppm)| ppm)
@@ -167,6 +196,7 @@ mod tests {
             baseline_ppm: 420.0,
             window_secs: 600,
             ppm_per_person: 40.0,
+            time_constant_secs: 360,
         }
     }
 
@@ -292,12 +322,43 @@ mod tests {
         assert_eq!(tracker.trend(), Co2Trend::Stable);
     }
 
+    #[test]
+    fn test_projected_occupancy_leads_average() {
+        let mut tracker = Co2Tracker::new(test_config());
+        let now = Instant::now();
+        // Two people just walked into an empty room: the room heads for
+        // 500 ppm with a 6 min time constant, 3 min in
+        for i in 0..=36 {
+            let t = i as f32 * 5.0;
+            let ppm = 420.0 + 80.0 * (1.0 - (-t / 360.0).exp());
+            tracker.add_reading(ppm, now + Duration::from_secs(i * 5));
+        }
+        assert_eq!(tracker.estimated_occupancy(), Some(0));
+        let projected = tracker.projected_occupancy().unwrap();
+        assert!(projected >= 2, "projected {}", projected);
+        assert!(tracker.rise_rate_ppm_per_min() > 5.0);
+    }
+
+    #[test]
+    fn test_projected_occupancy_steady_room() {
+        let mut tracker = Co2Tracker::new(test_config());
+        let now = Instant::now();
+        for i in 0..10 {
+            tracker.add_reading(460.0, now + Duration::from_secs(i * 30));
+        }
+        assert_eq!(tracker.projected_occupancy(), Some(1));
+        tracker = Co2Tracker::new(test_config());
+        tracker.add_reading(460.0, now);
+        assert_eq!(tracker.projected_occupancy(), None);
+    }
+
     #[test]
     fn test_window_pruning() {
         let mut tracker = Co2Tracker::new(Co2Config {
             baseline_ppm: 420.0,
             window_secs: 60, // 1 minute window
             ppm_per_person: 40.0,
+            time_constant_secs: 360,
         });
         let now = Instant::now();
 
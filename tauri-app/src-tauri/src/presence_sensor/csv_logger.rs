@@ -2,10 +2,73 @@
 //!
 //! Writes daily CSV files to `~/.transcriptionapp/mmwave/YYYY-MM-DD.csv`.
 //! Format matches `scripts/mmwave_logger.py` for backward compatibility.
+//! [`parse_log`] reads them back for replay (see `sources::csv_replay`).
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
 use tracing::{info, warn};
 
+/// Header written at the top of each daily log
+pub const LOG_HEADER: &str = "timestamp_utc,timestamp_local,presence_raw,presence_debounced,raw";
+
+/// Directory holding the daily logs
+pub fn log_dir() -> Result<PathBuf, String> {
+    Ok(dirs::home_dir()
+        .ok_or("No home directory")?
+        .join(".transcriptionapp")
+        .join("mmwave"))
+}
+
+/// Path of the log for a `YYYY-MM-DD` date (UTC, matching rotation)
+pub fn log_path_for_date(date: &str) -> Result<PathBuf, String> {
+    Ok(log_dir()?.join(format!("{}.csv", date)))
+}
+
+/// One logged mmWave reading, as read back for replay
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoggedReading {
+    pub timestamp: DateTime<Utc>,
+    /// Raw (pre-debounce) presence
+    pub raw: bool,
+}
+
+/// Parse a daily log. Header lines are skipped wherever they appear, so
+/// concatenated logs parse too; only the UTC timestamp and raw presence
+/// columns are read.
+pub fn parse_log(csv: &str) -> Result<Vec<LoggedReading>, String> {
+    let mut readings = Vec::new();
+    for (idx, line) in csv.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("timestamp_utc") {
+            continue;
+        }
+        let line_no = idx + 1;
+        // `raw` is last and may contain commas — only split off the leading columns
+        let cols: Vec<&str> = line.splitn(5, ',').collect();
+        if cols.len() < 3 {
+            return Err(format!("Line {}: expected at least 3 columns", line_no));
+        }
+        let timestamp = DateTime::parse_from_rfc3339(cols[0])
+            .map_err(|e| format!("Line {}: invalid timestamp '{}': {}", line_no, cols[0], e))?
+            .with_timezone(&Utc);
+        let raw = match cols[2].trim() {
+            "1" => true,
+            "0" => false,
+            other => return Err(format!("Line {}: invalid presence_raw '{}'", line_no, other)),
+        };
+        readings.push(LoggedReading { timestamp, raw });
+    }
+    Ok(readings)
+}
+
+/// Read and parse a daily log file.
+pub fn read_log(path: &Path) -> Result<Vec<LoggedReading>, String> {
+    let csv = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    parse_log(&csv)
+}
+
 pub struct CsvLogger {
     log_dir: PathBuf,
     current_date: String,
@@ -14,10 +77,7 @@ pub struct CsvLogger {
 
 impl CsvLogger {
     pub fn new() -> Result<Self, String> {
-        let log_dir = dirs::home_dir()
-            .ok_or("No home directory")?
-            .join(".transcriptionapp")
-            .join("mmwave");
+        let log_dir = log_dir()?;
 
         std::fs::create_dir_all(&log_dir)
             .map_err(|e| format!("Failed to create mmwave log dir: {}", e))?;
@@ -45,10 +105,7 @@ impl CsvLogger {
         if write_header {
             use std::io::Write;
             let mut f = &file;
-            let _ = writeln!(
-                f,
-                "timestamp_utc,timestamp_local,presence_raw,presence_debounced,raw"
-            );
+            let _ = writeln!(f, "{}", LOG_HEADER);
         }
 
         self.file = Some(file);
@@ -86,6 +143,8 @@ impl CsvLogger {
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+
     #[test]
     fn test_csv_line_format() {
         // Verify the format matches mmwave_logger.py
@@ -103,4 +162,31 @@ mod tests {
         assert!(line.contains(",1,1,"));
         assert!(line.contains("$JYBSS,1"));
     }
+
+    #[test]
+    fn test_parse_log() {
+        let csv = format!(
+            "{}\n\
+             2026-02-19T10:30:00.000Z,2026-02-19T13:30:00.000+0300,1,1,\"HTTP present=true\"\n\
+             2026-02-19T10:30:01.000Z,2026-02-19T13:30:01.000+0300,0,,\"$JYBSS,0, , , *\"\n\
+             {}\n\
+             2026-02-19T10:30:02.500Z,2026-02-19T13:30:02.500+0300,1,1,\"\"\n",
+            LOG_HEADER, LOG_HEADER
+        );
+        let readings = parse_log(&csv).unwrap();
+        assert_eq!(readings.len(), 3);
+        assert!(readings[0].raw);
+        assert!(!readings[1].raw);
+        assert_eq!(
+            (readings[2].timestamp - readings[0].timestamp).num_milliseconds(),
+            2500
+        );
+    }
+
+    #[test]
+    fn test_parse_log_reports_line() {
+        let err = parse_log("2026-02-19T10:30:00Z,x,maybe,1,\"\"\n").unwrap_err();
+        assert!(err.contains("Line 1"), "{}", err);
+        assert!(parse_log("not-a-time,x,1,1,\"\"\n").is_err());
+    }
 }
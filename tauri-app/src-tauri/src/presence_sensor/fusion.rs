@@ -1,18 +1,30 @@
 //! Sensor fusion engine — combines readings from multiple sensors.
 //!
-//! Currently: mmWave-only passthrough. Thermal and CO2 readings are tracked
-//! for health/staleness reporting but do NOT influence the presence decision.
+//! Each fresh sensor casts a presence vote in 0.0–1.0, and the votes are
+//! averaged using the room's reliability weights (`FusionConfig::weights`,
+//! loaded from the room's calibration profile — see [`super::room_profile`]):
 //!
-//! Future: per-room calibration profiles will enable sensor fusion. Different
-//! rooms have different ventilation (CO2 reliability), sensor placement
-//! (mmWave wall vs desk), and hardware (thermal camera may not be present).
-//! Until calibration is implemented, mmWave alone is the safest default.
+//! - **mmWave**: debounced presence, 1.0 or 0.0
+//! - **Thermal**: 1.0 when the hot-pixel count reaches the room's threshold
+//! - **CO2**: graded by elevation and trend (see [`co2_vote`]); lags ~6 min
+//!
+//! Different rooms have different ventilation (CO2 reliability), sensor
+//! placement (mmWave wall vs desk), and hardware (thermal camera may not be
+//! present), so the weights are per room. An uncalibrated room gets
+//! mmWave-only weights, which reproduces the previous passthrough behaviour.
+//!
+//! A stale sensor stops voting and lowers confidence by its share of the
+//! total weight. When no weighted sensor is fresh the result is Unknown.
+//!
+//! The head count comes from the voting thermal and CO2 sensors — see
+//! [`super::occupancy::estimate_count`].
 //!
 //! All functions are pure — given inputs, expect outputs. No mocking needed.
 
 use std::time::{Duration, Instant};
 
 use super::co2::Co2Trend;
+use super::occupancy::estimate_count;
 use super::thermal;
 use super::types::{
     FusedState, FusionConfig, OccupancyEstimate, PresenceState, SensorHealth, SensorType,
@@ -42,17 +54,16 @@ pub struct FusionInput {
 #[derive(Clone, Copy)]
 pub struct ThermalSnapshot {
     pub is_present: bool,
+    pub hot_pixels: usize,
     pub occupancy_count: u8,
     pub last_reading: Instant,
 }
 
 /// Run the fusion algorithm.
 ///
-/// Currently mmWave-only: returns the debounced mmWave state directly.
-/// Thermal and CO2 data are recorded in sensor_health for monitoring
-/// but do not affect the presence decision.
-///
-/// Future: per-room calibration will enable multi-sensor fusion.
+/// Weighted average of the fresh sensors' votes; `score >= 0.5` is Present.
+/// Confidence is the vote margin scaled by the fraction of total weight that
+/// was fresh, so a stale sensor or a disagreement both lower it.
 pub fn fuse(input: &FusionInput, config: &FusionConfig) -> FusedState {
     let thermal_stale = Duration::from_secs(config.thermal_stale_secs);
     let co2_stale = Duration::from_secs(config.co2_stale_secs);
@@ -71,37 +82,100 @@ pub fn fuse(input: &FusionInput, config: &FusionConfig) -> FusedState {
         input.now.duration_since(ts) < co2_stale
     });
 
-    // Build sensor health report (all sensors, regardless of fusion strategy)
+    // Build sensor health report (all sensors, regardless of weighting)
     let sensor_health = build_health(input, thermal_fresh, mmwave_fresh, co2_fresh);
 
-    // --- mmWave-only passthrough ---
+    // Collect votes from fresh sensors the room trusts
+    let mut votes: Vec<(SensorType, f32)> = Vec::with_capacity(3);
     if mmwave_fresh {
         let present = input.mmwave_present.unwrap_or(false);
-        let presence = if present {
-            PresenceState::Present
-        } else {
-            PresenceState::Absent
-        };
+        votes.push((SensorType::MmWave, if present { 1.0 } else { 0.0 }));
+    }
+    if let (true, Some(t)) = (thermal_fresh, input.thermal_frame.as_ref()) {
+        votes.push((
+            SensorType::Thermal,
+            thermal_vote(t.hot_pixels, config.thermal_min_hot_pixels),
+        ));
+    }
+    if co2_fresh {
+        votes.push((SensorType::Co2, co2_vote(input.co2_elevated, input.co2_trend)));
+    }
+    votes.retain(|(sensor, _)| config.weights.get(*sensor) > 0.0);
 
+    let fresh_weight: f32 = votes.iter().map(|(s, _)| config.weights.get(*s)).sum();
+    if fresh_weight <= 0.0 {
+        // No trusted sensor is fresh → Unknown
         return FusedState {
-            presence,
-            occupancy: OccupancyEstimate {
-                count: if present { Some(1) } else { Some(0) },
-                confidence: 1.0,
-                contributing_sensors: vec![SensorType::MmWave],
-            },
+            presence: PresenceState::Unknown,
+            confidence: 0.0,
+            occupancy: OccupancyEstimate::default(),
             sensor_health,
         };
     }
 
-    // No fresh mmWave → Unknown
+    let score = votes
+        .iter()
+        .map(|(s, vote)| config.weights.get(*s) * vote)
+        .sum::<f32>()
+        / fresh_weight;
+    // Ties go to Present: wrongly splitting an encounter costs more than
+    // keeping one open a little longer
+    let present = score >= 0.5;
+    let coverage = fresh_weight / config.weights.total();
+    let confidence = ((score - 0.5).abs() * 2.0 * coverage).clamp(0.0, 1.0);
+    let presence = if present {
+        PresenceState::Present
+    } else {
+        PresenceState::Absent
+    };
+
+    // Counts only from sensors that voted
+    let voted = |sensor: SensorType| votes.iter().any(|(s, _)| *s == sensor);
+    let thermal_count = input
+        .thermal_frame
+        .as_ref()
+        .filter(|_| voted(SensorType::Thermal))
+        .map(|t| t.occupancy_count);
+    let co2_count = input.co2_occupancy.filter(|_| voted(SensorType::Co2));
+    let (count, count_confidence) = estimate_count(presence, confidence, thermal_count, co2_count);
+
     FusedState {
-        presence: PresenceState::Unknown,
-        occupancy: OccupancyEstimate::default(),
+        presence,
+        confidence,
+        occupancy: OccupancyEstimate {
+            count,
+            confidence: count_confidence,
+            contributing_sensors: votes.into_iter().map(|(s, _)| s).collect(),
+        },
         sensor_health,
     }
 }
 
+/// Thermal presence vote: the frame either has enough body heat or not.
+pub fn thermal_vote(hot_pixels: usize, min_hot_pixels: usize) -> f32 {
+    if hot_pixels >= min_hot_pixels.max(1) {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+/// CO2 presence vote from elevation and trend.
+///
+/// CO2 lags occupancy by minutes, so the trend carries most of the signal:
+/// rising means someone is breathing in the room even before the level is
+/// elevated, falling from an elevated level means the room is emptying.
+pub fn co2_vote(elevated: bool, trend: Co2Trend) -> f32 {
+    match (elevated, trend) {
+        (true, Co2Trend::Rising) => 0.9,
+        (true, Co2Trend::Stable) => 0.7,
+        (true, Co2Trend::Falling) => 0.3,
+        (false, Co2Trend::Rising) => 0.6,
+        (false, Co2Trend::Stable) => 0.1,
+        (false, Co2Trend::Falling) => 0.0,
+    }
+}
+
 /// Convenience function: analyze a thermal frame and return a snapshot.
 pub fn analyze_thermal(
     frame: &[f32],
@@ -112,6 +186,7 @@ pub fn analyze_thermal(
 ) -> ThermalSnapshot {
     ThermalSnapshot {
         is_present: thermal::thermal_presence(frame, config),
+        hot_pixels: thermal::count_hot_pixels(frame, config.hot_pixel_threshold_c),
         occupancy_count: thermal::estimate_occupancy(frame, w, h, config),
         last_reading: now,
     }
@@ -155,6 +230,7 @@ fn build_health(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::presence_sensor::types::SensorWeights;
 
     fn default_config() -> FusionConfig {
         FusionConfig::default()
@@ -227,12 +303,13 @@ mod tests {
     fn test_mmwave_ignores_thermal_and_co2() {
         let n = now();
         // mmWave says absent, thermal says present, CO2 elevated —
-        // fusion should still return Absent (mmWave-only passthrough)
+        // an uncalibrated room still returns Absent (mmWave-only weights)
         let input = FusionInput {
             mmwave_present: Some(false),
             mmwave_last_reading: Some(n),
             thermal_frame: Some(ThermalSnapshot {
                 is_present: true,
+                hot_pixels: 40,
                 occupancy_count: 2,
                 last_reading: n,
             }),
@@ -268,6 +345,154 @@ mod tests {
         assert!(mmwave_health.is_some_and(|h| h.is_stale));
     }
 
+    // ===== Calibrated weights =====
+
+    fn calibrated_config() -> FusionConfig {
+        FusionConfig {
+            weights: SensorWeights {
+                mmwave: 0.4,
+                thermal: 1.0,
+                co2: 0.8,
+            },
+            thermal_min_hot_pixels: 10,
+            ..FusionConfig::default()
+        }
+    }
+
+    fn thermal(hot_pixels: usize, at: Instant) -> Option<ThermalSnapshot> {
+        Some(ThermalSnapshot {
+            is_present: hot_pixels >= 4,
+            hot_pixels,
+            occupancy_count: if hot_pixels >= 10 { 1 } else { 0 },
+            last_reading: at,
+        })
+    }
+
+    #[test]
+    fn test_thermal_outvotes_mmwave_false_positive() {
+        let n = now();
+        let input = FusionInput {
+            mmwave_present: Some(true),
+            mmwave_last_reading: Some(n),
+            thermal_frame: thermal(1, n),
+            co2_elevated: false,
+            co2_trend: Co2Trend::Stable,
+            co2_occupancy: Some(0),
+            co2_last_reading: Some(n),
+            now: n,
+        };
+        let result = fuse(&input, &calibrated_config());
+        assert_eq!(result.presence, PresenceState::Absent);
+        assert_eq!(
+            result.occupancy.contributing_sensors,
+            vec![SensorType::MmWave, SensorType::Thermal, SensorType::Co2]
+        );
+        // Disagreement keeps confidence below a unanimous decision
+        assert!(result.confidence > 0.0 && result.confidence < 1.0);
+    }
+
+    #[test]
+    fn test_unanimous_sensors_full_confidence() {
+        let n = now();
+        let input = FusionInput {
+            mmwave_present: Some(true),
+            mmwave_last_reading: Some(n),
+            thermal_frame: thermal(25, n),
+            co2_elevated: true,
+            co2_trend: Co2Trend::Rising,
+            co2_occupancy: Some(1),
+            co2_last_reading: Some(n),
+            now: n,
+        };
+        let result = fuse(&input, &calibrated_config());
+        assert_eq!(result.presence, PresenceState::Present);
+        assert!(result.confidence > 0.9, "confidence {}", result.confidence);
+    }
+
+    #[test]
+    fn test_count_from_thermal_blobs() {
+        let n = now();
+        let input = FusionInput {
+            mmwave_present: Some(true),
+            mmwave_last_reading: Some(n),
+            thermal_frame: Some(ThermalSnapshot {
+                is_present: true,
+                hot_pixels: 60,
+                occupancy_count: 3,
+                last_reading: n,
+            }),
+            co2_elevated: true,
+            co2_trend: Co2Trend::Rising,
+            co2_occupancy: Some(3),
+            co2_last_reading: Some(n),
+            now: n,
+        };
+        let result = fuse(&input, &calibrated_config());
+        assert_eq!(result.occupancy.count, Some(3));
+        assert!((result.occupancy.confidence - result.confidence).abs() < 1e-6);
+
+        // CO2 disagreeing by more than one person lowers count confidence
+        let input = FusionInput {
+            co2_occupancy: Some(1),
+            ..input
+        };
+        let result = fuse(&input, &calibrated_config());
+        assert_eq!(result.occupancy.count, Some(3));
+        assert!(result.occupancy.confidence < result.confidence);
+    }
+
+    #[test]
+    fn test_stale_mmwave_degrades_to_thermal_and_co2() {
+        let n = now();
+        let input = FusionInput {
+            mmwave_present: Some(false),
+            mmwave_last_reading: Some(n - Duration::from_secs(60)),
+            thermal_frame: thermal(25, n),
+            co2_elevated: true,
+            co2_trend: Co2Trend::Stable,
+            co2_occupancy: Some(1),
+            co2_last_reading: Some(n),
+            now: n,
+        };
+        let config = calibrated_config();
+        let result = fuse(&input, &config);
+        assert_eq!(result.presence, PresenceState::Present);
+        assert!(!result.occupancy.contributing_sensors.contains(&SensorType::MmWave));
+        // Confidence capped by the share of weight still reporting
+        let coverage = (config.weights.thermal + config.weights.co2) / config.weights.total();
+        assert!(result.confidence <= coverage);
+        assert!(result.sensor_health.iter().any(|h| h.sensor_type == SensorType::MmWave && h.is_stale));
+    }
+
+    #[test]
+    fn test_unweighted_sensor_does_not_vote() {
+        let n = now();
+        let mut config = calibrated_config();
+        config.weights.co2 = 0.0;
+        let input = FusionInput {
+            mmwave_present: None,
+            mmwave_last_reading: None,
+            thermal_frame: None,
+            co2_elevated: true,
+            co2_trend: Co2Trend::Rising,
+            co2_occupancy: Some(2),
+            co2_last_reading: Some(n),
+            now: n,
+        };
+        assert_eq!(fuse(&input, &config).presence, PresenceState::Unknown);
+    }
+
+    #[test]
+    fn test_co2_vote_follows_trend() {
+        assert!(co2_vote(true, Co2Trend::Rising) > 0.5);
+        assert!(co2_vote(true, Co2Trend::Stable) > 0.5);
+        assert!(co2_vote(false, Co2Trend::Rising) > 0.5);
+        assert!(co2_vote(true, Co2Trend::Falling) < 0.5);
+        assert!(co2_vote(false, Co2Trend::Stable) < 0.5);
+        assert_eq!(thermal_vote(9, 10), 0.0);
+        assert_eq!(thermal_vote(10, 10), 1.0);
+    }
+
     // ===== Sensor health =====
 
     #[test]
@@ -278,6 +503,7 @@ mod tests {
             mmwave_last_reading: Some(n),
             thermal_frame: Some(ThermalSnapshot {
                 is_present: true,
+                hot_pixels: 20,
                 occupancy_count: 1,
                 last_reading: n,
             }),
@@ -288,7 +514,7 @@ mod tests {
             now: n,
         };
         let result = fuse(&input, &default_config());
-        // All 3 sensors reported in health, even though only mmWave drives presence
+        // All 3 sensors reported in health, even though only mmWave is weighted
         assert_eq!(result.sensor_health.len(), 3);
         assert!(result.sensor_health.iter().all(|h| !h.is_stale));
     }
@@ -302,6 +528,7 @@ mod tests {
             mmwave_last_reading: Some(n),
             thermal_frame: Some(ThermalSnapshot {
                 is_present: false,
+                hot_pixels: 0,
                 occupancy_count: 0,
                 last_reading: old,
             }),
@@ -16,6 +16,10 @@
 //!                                        └─ Co2Tracker (trend, occupancy)
 //! ```
 //!
+//! The fusion step weights each sensor by the room's calibration profile
+//! (`room_profile`); uncalibrated rooms fall back to mmWave alone. Head
+//! counts and the encounter hints derived from them live in `occupancy`.
+//!
 //! Consumer interface is backward-compatible with the old `PresenceSensor`.
 
 pub mod absence_monitor;
@@ -23,16 +27,21 @@ pub mod co2;
 pub mod csv_logger;
 pub mod debounce;
 pub mod fusion;
+pub mod occupancy;
+pub mod room_profile;
 pub mod sensor_source;
 pub mod sources;
 pub mod thermal;
+pub mod trace;
 pub mod types;
 
 // Re-export public API for backward compatibility
 pub use sources::serial::{auto_detect_port, parse_jybss};
+pub use occupancy::{EncounterOccupancy, OccupancyHint};
+pub use room_profile::RoomProfile;
 pub use types::{
     FusedState, OccupancyEstimate, PresenceState, SensorConfig, SensorHealth, SensorStatus,
-    SensorType, SuiteConfig, SuiteStatus, ThermalConfig, Co2Config, FusionConfig,
+    SensorType, SensorWeights, SuiteConfig, SuiteStatus, ThermalConfig, Co2Config, FusionConfig,
 };
 
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -46,6 +55,7 @@ use co2::Co2Tracker;
 use csv_logger::CsvLogger;
 use debounce::DebounceFsm;
 use fusion::{analyze_thermal, fuse, FusionInput};
+use occupancy::ThermalCountSmoother;
 use sensor_source::SensorSource;
 use sources::esp32_http::Esp32HttpSource;
 use sources::serial::SerialSource;
@@ -87,6 +97,20 @@ impl PresenceSensorSuite {
             return Err("No sensor URL or serial port configured".to_string());
         }
 
+        // Create the appropriate source
+        let source: Box<dyn SensorSource> = if use_http {
+            info!("Presence sensor source: url={}", config.url);
+            Box::new(Esp32HttpSource::new(config.url.clone()))
+        } else {
+            info!("Presence sensor source: port={}", config.port);
+            Box::new(SerialSource::new(config.port.clone()))
+        };
+        Self::start_with_source(config, source)
+    }
+
+    /// Start the suite on a caller-provided source (e.g. `CsvReplaySource`
+    /// for replaying a recorded day). `config.url`/`config.port` are ignored.
+    pub fn start_with_source(config: &SuiteConfig, source: Box<dyn SensorSource>) -> Result<Self, String> {
         // Create channels
         let (state_tx, _) = watch::channel(PresenceState::Unknown);
         let state_tx = Arc::new(state_tx);
@@ -100,13 +124,6 @@ impl PresenceSensorSuite {
         // Create mpsc channel for sensor readings
         let (reading_tx, reading_rx) = mpsc::channel::<types::SensorReading>(128);
 
-        // Create and start the appropriate source
-        let source: Box<dyn SensorSource> = if use_http {
-            Box::new(Esp32HttpSource::new(config.url.clone()))
-        } else {
-            Box::new(SerialSource::new(config.port.clone()))
-        };
-
         let source_handle = source
             .start(reading_tx, status_tx.clone(), stop.clone())
             .map_err(|e| format!("Failed to start sensor source: {}", e))?;
@@ -151,14 +168,9 @@ impl PresenceSensorSuite {
             })
         };
 
-        let source_name = if use_http {
-            format!("url={}", config.url)
-        } else {
-            format!("port={}", config.port)
-        };
         info!(
             "Presence sensor suite started: {}, debounce={}s, absence_threshold={}s, csv={}",
-            source_name, config.debounce_secs, config.absence_threshold_secs, config.csv_log_enabled
+            source.name(), config.debounce_secs, config.absence_threshold_secs, config.csv_log_enabled
         );
 
         Ok(Self {
@@ -221,7 +233,7 @@ impl Drop for PresenceSensorSuite {
 // ============================================================================
 
 /// Core fusion task: receives raw sensor readings, maintains per-sensor state,
-/// runs the priority-cascade fusion algorithm, and updates watch channels.
+/// runs the weighted fusion algorithm, and updates watch channels.
 async fn fusion_task(
     mut reading_rx: mpsc::Receiver<types::SensorReading>,
     state_tx: Arc<watch::Sender<PresenceState>>,
@@ -235,6 +247,7 @@ async fn fusion_task(
 ) {
     let mut debounce_fsm = DebounceFsm::new(debounce_secs);
     let mut co2_tracker = Co2Tracker::new(co2_config);
+    let mut blob_smoother = ThermalCountSmoother::default();
 
     // Latest state per sensor
     let mut mmwave_last_reading: Option<Instant> = None;
@@ -281,13 +294,14 @@ async fn fusion_task(
                 width,
                 height,
             } => {
-                thermal_snapshot =
-                    Some(analyze_thermal(&pixels, width, height, &thermal_config, now));
+                let mut snapshot = analyze_thermal(&pixels, width, height, &thermal_config, now);
+                let raw_count = snapshot.occupancy_count;
+                snapshot.occupancy_count = blob_smoother.push(raw_count);
                 debug!(
-                    "Thermal frame: present={}, occupancy={}",
-                    thermal_snapshot.as_ref().unwrap().is_present,
-                    thermal_snapshot.as_ref().unwrap().occupancy_count
+                    "Thermal frame: present={}, occupancy={} (raw {})",
+                    snapshot.is_present, snapshot.occupancy_count, raw_count
                 );
+                thermal_snapshot = Some(snapshot);
             }
             SensorValue::Co2 {
                 ppm,
@@ -312,12 +326,19 @@ async fn fusion_task(
             thermal_frame: thermal_snapshot,
             co2_elevated: co2_tracker.is_elevated(),
             co2_trend: co2_tracker.trend(),
-            co2_occupancy: co2_tracker.estimated_occupancy(),
+            co2_occupancy: co2_tracker.projected_occupancy(),
             co2_last_reading,
             now: Instant::now(),
         };
 
         let fused = fuse(&input, &fusion_config);
+        debug!(
+            "Fused presence: {} (confidence {:.2}, count {:?}, sensors {:?})",
+            fused.presence.as_str(),
+            fused.confidence,
+            fused.occupancy.count,
+            fused.occupancy.contributing_sensors
+        );
 
         // Update watch channels only when values change
         state_tx.send_if_modified(|current| {
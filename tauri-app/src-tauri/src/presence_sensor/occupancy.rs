@@ -0,0 +1,265 @@
+//! Head-count estimation for multi-person rooms.
+//!
+//! Thermal blob counting is the primary signal — each person-sized blob in
+//! the frame is one person. Single frames flicker (two people standing close
+//! merge into one blob, someone bends behind the desk), so the count is the
+//! median of the last few frames. CO2 projected from level and rise rate
+//! corroborates it, and stands in for rooms without a thermal camera, but
+//! only resolves to about ±1 person.
+//!
+//! [`EncounterOccupancy`] turns the count stream into hints for encounter
+//! detection: someone joining an encounter already under way (possible
+//! second patient) and everyone but one person leaving (possible end of
+//! visit). Both are hints for the LLM, never split triggers on their own.
+//!
+//! All functions are pure — given inputs, expect outputs. No mocking needed.
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use super::types::{OccupancyEstimate, PresenceState};
+
+/// Thermal frames in the blob-count median (~15s at one frame per 5s)
+pub const THERMAL_SMOOTHING_FRAMES: usize = 3;
+
+/// Estimates below this confidence don't move encounter hints
+const MIN_HINT_CONFIDENCE: f32 = 0.5;
+
+/// Median of the most recent thermal blob counts
+#[derive(Debug, Clone)]
+pub struct ThermalCountSmoother {
+    recent: VecDeque<u8>,
+    capacity: usize,
+}
+
+impl ThermalCountSmoother {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            recent: VecDeque::with_capacity(capacity),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Add a frame's blob count and return the smoothed count.
+    pub fn push(&mut self, count: u8) -> u8 {
+        if self.recent.len() == self.capacity {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(count);
+        let mut sorted: Vec<u8> = self.recent.iter().copied().collect();
+        sorted.sort_unstable();
+        sorted[sorted.len() / 2]
+    }
+}
+
+impl Default for ThermalCountSmoother {
+    fn default() -> Self {
+        Self::new(THERMAL_SMOOTHING_FRAMES)
+    }
+}
+
+/// Head count and confidence for a fused presence decision.
+///
+/// `thermal` and `co2` are counts from sensors that are fresh and trusted by
+/// the room profile (None otherwise). A present room is at least one person
+/// even when the camera sees no blob. With neither count available the
+/// estimate is one person at the presence confidence (mmWave-only rooms).
+pub fn estimate_count(
+    presence: PresenceState,
+    presence_confidence: f32,
+    thermal: Option<u8>,
+    co2: Option<u8>,
+) -> (Option<u8>, f32) {
+    match presence {
+        PresenceState::Unknown => (None, 0.0),
+        PresenceState::Absent => (Some(0), presence_confidence),
+        PresenceState::Present => {
+            let (count, factor) = match (thermal, co2) {
+                (Some(t), Some(c)) => {
+                    let t = t.max(1);
+                    let factor = match t.abs_diff(c.max(1)) {
+                        0 => 1.0,
+                        1 => 0.75,
+                        _ => 0.5,
+                    };
+                    (t, factor)
+                }
+                (Some(t), None) => (t.max(1), 0.85),
+                (None, Some(c)) => (c.max(1), 0.5),
+                (None, None) => (1, 1.0),
+            };
+            (Some(count), (presence_confidence * factor).clamp(0.0, 1.0))
+        }
+    }
+}
+
+/// Occupancy change worth telling encounter detection about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum OccupancyHint {
+    /// More people than the encounter settled at — a family member joining,
+    /// or the next patient arriving before the split
+    Arrival { baseline: u8, current: u8 },
+    /// Down to one person after at least two — everyone but (likely) the
+    /// physician has left
+    OnlyOneRemains { peak: u8 },
+}
+
+/// Tracks head count across one encounter to derive [`OccupancyHint`]s.
+///
+/// The baseline is the count the encounter settles at: it follows the count
+/// up until two people are present (physician alone, then the patient comes
+/// in), and after that any increase is an arrival. Call `reset` on split.
+#[derive(Debug, Clone, Default)]
+pub struct EncounterOccupancy {
+    baseline: Option<u8>,
+    peak: u8,
+    saw_arrival: bool,
+}
+
+impl EncounterOccupancy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fold in the latest estimate and return the current hint, if any.
+    pub fn observe(&mut self, estimate: &OccupancyEstimate) -> Option<OccupancyHint> {
+        let count = estimate.count?;
+        if estimate.confidence < MIN_HINT_CONFIDENCE || count == 0 {
+            return None;
+        }
+        self.peak = self.peak.max(count);
+
+        let baseline = match self.baseline {
+            Some(b) if b >= 2 => b,
+            other => {
+                let b = other.unwrap_or(0).max(count);
+                self.baseline = Some(b);
+                b
+            }
+        };
+
+        if count > baseline {
+            self.saw_arrival = true;
+            Some(OccupancyHint::Arrival {
+                baseline,
+                current: count,
+            })
+        } else if count == 1 && self.peak >= 2 {
+            Some(OccupancyHint::OnlyOneRemains { peak: self.peak })
+        } else {
+            None
+        }
+    }
+
+    /// Whether an arrival was seen at any point in this encounter
+    pub fn saw_arrival(&self) -> bool {
+        self.saw_arrival
+    }
+
+    /// Start tracking a new encounter
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::presence_sensor::types::SensorType;
+
+    fn estimate(count: u8, confidence: f32) -> OccupancyEstimate {
+        OccupancyEstimate {
+            count: Some(count),
+            confidence,
+            contributing_sensors: vec![SensorType::Thermal],
+        }
+    }
+
+    #[test]
+    fn test_smoother_takes_median() {
+        let mut s = ThermalCountSmoother::default();
+        assert_eq!(s.push(2), 2);
+        assert_eq!(s.push(1), 2); // [2, 1] → upper median
+        assert_eq!(s.push(2), 2);
+        assert_eq!(s.push(3), 2); // one-frame blip ignored
+        assert_eq!(s.push(3), 3);
+    }
+
+    #[test]
+    fn test_count_thermal_agrees_with_co2() {
+        let (count, conf) = estimate_count(PresenceState::Present, 0.9, Some(2), Some(2));
+        assert_eq!(count, Some(2));
+        assert!((conf - 0.9).abs() < 1e-6);
+
+        let (_, off_by_one) = estimate_count(PresenceState::Present, 0.9, Some(2), Some(1));
+        let (_, thermal_only) = estimate_count(PresenceState::Present, 0.9, Some(2), None);
+        assert!(off_by_one < conf && thermal_only < conf);
+    }
+
+    #[test]
+    fn test_count_present_is_at_least_one() {
+        assert_eq!(estimate_count(PresenceState::Present, 1.0, Some(0), None).0, Some(1));
+        assert_eq!(estimate_count(PresenceState::Present, 1.0, None, Some(0)).0, Some(1));
+        assert_eq!(estimate_count(PresenceState::Present, 1.0, None, None), (Some(1), 1.0));
+    }
+
+    #[test]
+    fn test_count_absent_and_unknown() {
+        assert_eq!(estimate_count(PresenceState::Absent, 0.7, Some(1), Some(1)), (Some(0), 0.7));
+        assert_eq!(estimate_count(PresenceState::Unknown, 0.0, Some(2), None), (None, 0.0));
+    }
+
+    #[test]
+    fn test_patient_arriving_is_not_an_arrival_hint() {
+        let mut enc = EncounterOccupancy::new();
+        assert_eq!(enc.observe(&estimate(1, 0.9)), None); // physician alone
+        assert_eq!(enc.observe(&estimate(2, 0.9)), None); // patient comes in
+        assert!(!enc.saw_arrival());
+    }
+
+    #[test]
+    fn test_third_person_is_an_arrival() {
+        let mut enc = EncounterOccupancy::new();
+        enc.observe(&estimate(2, 0.9));
+        assert_eq!(
+            enc.observe(&estimate(3, 0.9)),
+            Some(OccupancyHint::Arrival { baseline: 2, current: 3 })
+        );
+        assert!(enc.saw_arrival());
+        // Back to two — arrival already recorded for the multi-patient check
+        assert_eq!(enc.observe(&estimate(2, 0.9)), None);
+        assert!(enc.saw_arrival());
+    }
+
+    #[test]
+    fn test_everyone_but_one_left() {
+        let mut enc = EncounterOccupancy::new();
+        enc.observe(&estimate(2, 0.9));
+        enc.observe(&estimate(3, 0.9));
+        assert_eq!(
+            enc.observe(&estimate(1, 0.9)),
+            Some(OccupancyHint::OnlyOneRemains { peak: 3 })
+        );
+    }
+
+    #[test]
+    fn test_low_confidence_and_empty_are_ignored() {
+        let mut enc = EncounterOccupancy::new();
+        enc.observe(&estimate(2, 0.9));
+        assert_eq!(enc.observe(&estimate(4, 0.3)), None);
+        assert_eq!(enc.observe(&estimate(0, 0.9)), None);
+        assert!(!enc.saw_arrival());
+    }
+
+    #[test]
+    fn test_reset_starts_new_encounter() {
+        let mut enc = EncounterOccupancy::new();
+        enc.observe(&estimate(2, 0.9));
+        enc.observe(&estimate(3, 0.9));
+        enc.reset();
+        assert!(!enc.saw_arrival());
+        assert_eq!(enc.observe(&estimate(1, 0.9)), None);
+    }
+}
@@ -0,0 +1,415 @@
+//! Per-room calibration profiles for sensor fusion.
+//!
+//! A profile is built at the end of a CO2 calibration run from the
+//! [`CalibrationResult`] and the trace recorded during the run. Each
+//! calibration phase has a known head count, so every sensor's presence vote
+//! can be scored against ground truth. The score is informedness
+//! (TPR + TNR − 1): 1.0 for a perfect sensor, 0.0 for one no better than
+//! chance — e.g. a desk-mounted mmWave that fires in an empty room.
+//!
+//! Stored at `~/.transcriptionapp/presence_profiles/<room_id>.json`, with the
+//! trace alongside as `<room_id>_calibration.csv` so the profile can be
+//! rebuilt when the scoring changes.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tracing::info;
+
+use super::co2::Co2Tracker;
+use super::fusion::{co2_vote, thermal_vote};
+use super::trace::{self, TraceRow};
+use super::types::{Co2Config, FusionConfig, SensorWeights, SuiteConfig};
+use crate::co2_calibration::CalibrationResult;
+
+/// Each class (occupied / empty) needs this many labelled samples before a
+/// sensor's weight is trusted over the default
+const MIN_SAMPLES_PER_CLASS: usize = 10;
+
+/// Calibrated fusion parameters for one room
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoomProfile {
+    pub room_id: String,
+    pub calibrated_at: String,
+    pub weights: SensorWeights,
+    pub thermal_min_hot_pixels: usize,
+    pub co2_baseline_ppm: f32,
+    pub co2_ppm_per_person: f32,
+    pub co2_window_secs: u64,
+    /// Ventilation time constant, from the rise rate seen when people entered
+    #[serde(default = "default_co2_time_constant_secs")]
+    pub co2_time_constant_secs: u64,
+    /// Labelled trace rows the weights were scored on
+    pub trace_rows: usize,
+}
+
+impl RoomProfile {
+    /// Build a profile from a finished calibration and its recorded trace.
+    pub fn from_calibration(result: &CalibrationResult, trace: &[TraceRow]) -> Self {
+        let defaults = SensorWeights::default();
+        let fusion_defaults = FusionConfig::default();
+        let co2_config = Co2Config {
+            baseline_ppm: result.baseline_ppm,
+            ppm_per_person: result.ppm_per_person,
+            window_secs: result.recommended_window_secs,
+            time_constant_secs: time_constant_secs(result),
+        };
+
+        let thermal_min_hot_pixels =
+            learn_hot_pixel_threshold(trace).unwrap_or(fusion_defaults.thermal_min_hot_pixels);
+
+        let mmwave_votes = labelled(trace, |r| r.mmwave);
+        let thermal_votes = labelled(trace, |r| {
+            r.hot_pixels.map(|hot| thermal_vote(hot, thermal_min_hot_pixels) >= 0.5)
+        });
+        let co2_votes = co2_votes(trace, &co2_config);
+
+        let mut weights = SensorWeights {
+            mmwave: informedness(&mmwave_votes).unwrap_or(defaults.mmwave),
+            thermal: informedness(&thermal_votes).unwrap_or(defaults.thermal),
+            co2: informedness(&co2_votes).unwrap_or(defaults.co2),
+        };
+        if weights.total() <= 0.0 {
+            // Nothing beat chance; fall back rather than report Unknown forever
+            weights = defaults;
+        }
+
+        Self {
+            room_id: result.room_id.clone(),
+            calibrated_at: result.calibrated_at.clone(),
+            weights,
+            thermal_min_hot_pixels,
+            co2_baseline_ppm: result.baseline_ppm,
+            co2_ppm_per_person: result.ppm_per_person,
+            co2_window_secs: result.recommended_window_secs,
+            co2_time_constant_secs: co2_config.time_constant_secs,
+            trace_rows: trace.iter().filter(|r| r.occupancy.is_some()).count(),
+        }
+    }
+
+    /// Overlay the calibrated values onto a suite configuration.
+    pub fn apply(&self, config: &mut SuiteConfig) {
+        config.co2.baseline_ppm = self.co2_baseline_ppm;
+        config.co2.ppm_per_person = self.co2_ppm_per_person;
+        config.co2.window_secs = self.co2_window_secs;
+        config.co2.time_constant_secs = self.co2_time_constant_secs;
+        config.fusion.weights = self.weights;
+        config.fusion.thermal_min_hot_pixels = self.thermal_min_hot_pixels;
+    }
+
+    /// Load the profile for a room, if one has been calibrated.
+    pub fn load(room_id: &str) -> Result<Option<Self>, String> {
+        Self::load_from(&profile_path(room_id)?)
+    }
+
+    pub fn load_from(path: &Path) -> Result<Option<Self>, String> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read room profile: {}", e))?;
+        let profile = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse room profile: {}", e))?;
+        Ok(Some(profile))
+    }
+
+    /// Save the profile and the trace it was built from.
+    pub fn save(&self, trace: &[TraceRow]) -> Result<PathBuf, String> {
+        let path = profile_path(&self.room_id)?;
+        self.save_to(&path, trace)?;
+        info!(
+            "Saved presence profile for room {}: weights mmwave={:.2} thermal={:.2} co2={:.2}",
+            self.room_id, self.weights.mmwave, self.weights.thermal, self.weights.co2
+        );
+        Ok(path)
+    }
+
+    pub fn save_to(&self, path: &Path, trace: &[TraceRow]) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create presence profile dir: {}", e))?;
+        }
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize room profile: {}", e))?;
+        std::fs::write(path, content).map_err(|e| format!("Failed to write room profile: {}", e))?;
+        std::fs::write(trace_path_for(path), trace::to_csv(trace))
+            .map_err(|e| format!("Failed to write calibration trace: {}", e))?;
+        Ok(())
+    }
+}
+
+fn default_co2_time_constant_secs() -> u64 {
+    Co2Config::default().time_constant_secs
+}
+
+/// One person entering an empty room first raises CO2 at
+/// `ppm_per_person / tau`, so the fastest rise seen during calibration gives
+/// the room's time constant.
+fn time_constant_secs(result: &CalibrationResult) -> u64 {
+    if result.rise_rate_ppm_per_min <= 0.0 {
+        return default_co2_time_constant_secs();
+    }
+    let tau_secs = result.ppm_per_person / result.rise_rate_ppm_per_min * 60.0;
+    (tau_secs as u64).clamp(60, 1800)
+}
+
+fn profile_path(room_id: &str) -> Result<PathBuf, String> {
+    let safe_id: String = room_id
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+        .collect();
+    if safe_id.is_empty() {
+        return Err("Room id is empty".to_string());
+    }
+    let home = dirs::home_dir().ok_or("No home directory")?;
+    Ok(home
+        .join(".transcriptionapp")
+        .join("presence_profiles")
+        .join(format!("{}.json", safe_id)))
+}
+
+fn trace_path_for(profile_path: &Path) -> PathBuf {
+    let stem = profile_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    profile_path.with_file_name(format!("{}_calibration.csv", stem))
+}
+
+/// (truly occupied, sensor voted present) for rows with both a label and a value
+fn labelled(trace: &[TraceRow], vote: impl Fn(&TraceRow) -> Option<bool>) -> Vec<(bool, bool)> {
+    trace
+        .iter()
+        .filter_map(|r| Some((r.occupancy? > 0, vote(r)?)))
+        .collect()
+}
+
+/// CO2 votes need the rolling tracker, so they're scored by replaying the trace
+fn co2_votes(trace: &[TraceRow], config: &Co2Config) -> Vec<(bool, bool)> {
+    let base = Instant::now();
+    let mut tracker = Co2Tracker::new(config.clone());
+    let mut votes = Vec::new();
+    for row in trace {
+        let Some(ppm) = row.co2_ppm else { continue };
+        tracker.add_reading(ppm, base + Duration::from_secs_f64(row.elapsed_secs.max(0.0)));
+        if let Some(occupancy) = row.occupancy {
+            votes.push((occupancy > 0, co2_vote(tracker.is_elevated(), tracker.trend()) >= 0.5));
+        }
+    }
+    votes
+}
+
+/// TPR + TNR − 1, clamped to 0..1. None without enough samples of both classes.
+fn informedness(votes: &[(bool, bool)]) -> Option<f32> {
+    let positives = votes.iter().filter(|(truth, _)| *truth).count();
+    let negatives = votes.len() - positives;
+    if positives < MIN_SAMPLES_PER_CLASS || negatives < MIN_SAMPLES_PER_CLASS {
+        return None;
+    }
+    let true_pos = votes.iter().filter(|(truth, vote)| *truth && *vote).count();
+    let true_neg = votes.iter().filter(|(truth, vote)| !*truth && !*vote).count();
+    let tpr = true_pos as f32 / positives as f32;
+    let tnr = true_neg as f32 / negatives as f32;
+    Some((tpr + tnr - 1.0).clamp(0.0, 1.0))
+}
+
+/// Hot-pixel threshold that best separates empty from occupied frames.
+/// When several thresholds tie, the middle one leaves the most margin.
+fn learn_hot_pixel_threshold(trace: &[TraceRow]) -> Option<usize> {
+    let samples: Vec<(bool, usize)> = trace
+        .iter()
+        .filter_map(|r| Some((r.occupancy? > 0, r.hot_pixels?)))
+        .collect();
+    let max_hot = samples.iter().map(|&(_, hot)| hot).max()?;
+
+    let mut best_score = f32::MIN;
+    let mut best: Vec<usize> = Vec::new();
+    for threshold in 1..=max_hot {
+        let votes: Vec<(bool, bool)> = samples.iter().map(|&(truth, hot)| (truth, hot >= threshold)).collect();
+        let score = informedness(&votes)?;
+        if score > best_score + f32::EPSILON {
+            best_score = score;
+            best.clear();
+        }
+        if (score - best_score).abs() <= f32::EPSILON {
+            best.push(threshold);
+        }
+    }
+    best.get(best.len() / 2).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::presence_sensor::types::{FusedState, PresenceState, SensorConfig, SensorType};
+
+    fn fixture(name: &str) -> Vec<TraceRow> {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/presence_traces")
+            .join(name);
+        trace::parse_trace(&std::fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    fn calibration_result() -> CalibrationResult {
+        CalibrationResult {
+            baseline_ppm: 430.0,
+            ppm_per_person: 45.0,
+            recommended_window_secs: 300,
+            rise_rate_ppm_per_min: 0.0,
+            fall_rate_ppm_per_min: 0.0,
+            phase_results: Vec::new(),
+            calibrated_at: "2026-05-04T14:00:00Z".to_string(),
+            room_id: "room-3".to_string(),
+            sensor_url: "http://sensor".to_string(),
+        }
+    }
+
+    fn suite_config() -> SuiteConfig {
+        SensorConfig {
+            port: String::new(),
+            url: "http://sensor".to_string(),
+            debounce_secs: 10,
+            absence_threshold_secs: 180,
+            csv_log_enabled: false,
+        }
+        .into()
+    }
+
+    fn calibrated_profile() -> RoomProfile {
+        RoomProfile::from_calibration(&calibration_result(), &fixture("calibration_room3.csv"))
+    }
+
+    fn accuracy(states: &[FusedState], trace: &[TraceRow]) -> f32 {
+        let scored: Vec<bool> = states
+            .iter()
+            .zip(trace)
+            .filter_map(|(s, r)| {
+                let truth = if r.occupancy? > 0 { PresenceState::Present } else { PresenceState::Absent };
+                Some(s.presence == truth)
+            })
+            .collect();
+        scored.iter().filter(|ok| **ok).count() as f32 / scored.len() as f32
+    }
+
+    #[test]
+    fn test_informedness() {
+        let perfect: Vec<(bool, bool)> = (0..20).map(|i| (i % 2 == 0, i % 2 == 0)).collect();
+        assert_eq!(informedness(&perfect), Some(1.0));
+        let always_on: Vec<(bool, bool)> = (0..20).map(|i| (i % 2 == 0, true)).collect();
+        assert_eq!(informedness(&always_on), Some(0.0));
+        let one_class: Vec<(bool, bool)> = (0..20).map(|_| (true, true)).collect();
+        assert_eq!(informedness(&one_class), None);
+    }
+
+    #[test]
+    fn test_profile_from_calibration_trace() {
+        let profile = calibrated_profile();
+        assert_eq!(profile.room_id, "room-3");
+        assert_eq!(profile.co2_baseline_ppm, 430.0);
+        assert_eq!(profile.co2_window_secs, 300);
+        assert!(profile.trace_rows > 0);
+
+        // Desk-mounted mmWave fires in the empty room; thermal separates cleanly
+        let w = profile.weights;
+        assert!(w.thermal > 0.9, "thermal weight {}", w.thermal);
+        assert!(w.mmwave < 0.6, "mmwave weight {}", w.mmwave);
+        assert!(w.thermal > w.co2 && w.co2 > 0.0, "co2 weight {}", w.co2);
+        // Threshold sits between empty-room noise and one body
+        assert!((4..=15).contains(&profile.thermal_min_hot_pixels), "{}", profile.thermal_min_hot_pixels);
+    }
+
+    #[test]
+    fn test_profile_without_labels_keeps_defaults() {
+        let unlabelled: Vec<TraceRow> = fixture("calibration_room3.csv")
+            .into_iter()
+            .map(|r| TraceRow { occupancy: None, ..r })
+            .collect();
+        let profile = RoomProfile::from_calibration(&calibration_result(), &unlabelled);
+        assert_eq!(profile.weights, SensorWeights::default());
+        assert_eq!(profile.thermal_min_hot_pixels, FusionConfig::default().thermal_min_hot_pixels);
+    }
+
+    #[test]
+    fn test_time_constant_from_rise_rate() {
+        let mut result = calibration_result();
+        assert_eq!(time_constant_secs(&result), 360);
+        result.rise_rate_ppm_per_min = 9.0; // 45 ppm/person → 5 min
+        assert_eq!(time_constant_secs(&result), 300);
+        result.rise_rate_ppm_per_min = 1000.0;
+        assert_eq!(time_constant_secs(&result), 60);
+    }
+
+    #[test]
+    fn test_profile_without_time_constant_loads() {
+        let json = r#"{"roomId":"room-3","calibratedAt":"2026-05-04T14:00:00Z",
+            "weights":{"mmwave":0.4,"thermal":1.0,"co2":0.9},"thermalMinHotPixels":11,
+            "co2BaselinePpm":430.0,"co2PpmPerPerson":45.0,"co2WindowSecs":300,"traceRows":360}"#;
+        let profile: RoomProfile = serde_json::from_str(json).unwrap();
+        assert_eq!(profile.co2_time_constant_secs, 360);
+    }
+
+    #[test]
+    fn test_apply_overlays_suite_config() {
+        let profile = calibrated_profile();
+        let mut config: SuiteConfig = suite_config();
+        profile.apply(&mut config);
+        assert_eq!(config.co2.baseline_ppm, 430.0);
+        assert_eq!(config.co2.ppm_per_person, 45.0);
+        assert_eq!(config.fusion.weights, profile.weights);
+        assert_eq!(config.fusion.thermal_min_hot_pixels, profile.thermal_min_hot_pixels);
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("room-3.json");
+        let trace = fixture("calibration_room3.csv");
+        let profile = RoomProfile::from_calibration(&calibration_result(), &trace);
+        profile.save_to(&path, &trace).unwrap();
+
+        assert_eq!(RoomProfile::load_from(&path).unwrap(), Some(profile));
+        let saved_trace = std::fs::read_to_string(dir.path().join("room-3_calibration.csv")).unwrap();
+        assert_eq!(trace::parse_trace(&saved_trace).unwrap().len(), trace.len());
+        assert_eq!(RoomProfile::load_from(&dir.path().join("missing.json")).unwrap(), None);
+    }
+
+    #[test]
+    fn test_calibrated_fusion_beats_mmwave_only_on_recorded_day() {
+        let day = fixture("clinic_session_room3.csv");
+        let base: SuiteConfig = suite_config();
+        let mut calibrated = base.clone();
+        calibrated_profile().apply(&mut calibrated);
+
+        let mmwave_only = accuracy(&trace::replay(&day, &base), &day);
+        let fused = accuracy(&trace::replay(&day, &calibrated), &day);
+        assert!(fused > 0.9, "fused accuracy {}", fused);
+        assert!(fused > mmwave_only + 0.1, "fused {} vs mmwave-only {}", fused, mmwave_only);
+    }
+
+    #[test]
+    fn test_calibrated_fusion_survives_mmwave_dropout() {
+        let day = fixture("clinic_session_room3.csv");
+        let mut config: SuiteConfig = suite_config();
+        let uncalibrated = trace::replay(&day, &config);
+        calibrated_profile().apply(&mut config);
+        let calibrated = trace::replay(&day, &config);
+
+        // Rows recorded after the mmWave bridge stopped reporting (and went stale)
+        let dropout_start = day.iter().rposition(|r| r.mmwave.is_some()).unwrap() + 5;
+        let dropout = dropout_start..day.len();
+        assert!(uncalibrated[dropout.clone()].iter().all(|s| s.presence == PresenceState::Unknown));
+
+        let weights = calibrated_profile().weights;
+        let max_confidence = (weights.thermal + weights.co2) / weights.total();
+        for (state, row) in calibrated[dropout.clone()].iter().zip(&day[dropout]) {
+            assert_ne!(state.presence, PresenceState::Unknown);
+            assert!(!state.occupancy.contributing_sensors.contains(&SensorType::MmWave));
+            if row.occupancy == Some(0) {
+                assert_eq!(state.presence, PresenceState::Absent);
+            }
+            // Lost the mmWave share of the weight
+            assert!(state.confidence <= max_confidence + f32::EPSILON);
+        }
+    }
+}
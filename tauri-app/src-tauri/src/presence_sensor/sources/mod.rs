@@ -1,5 +1,6 @@
 //! Sensor source implementations.
 
+pub mod csv_replay;
 pub mod esp32_http;
 pub mod mock;
 pub mod serial;
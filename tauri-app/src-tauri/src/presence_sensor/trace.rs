@@ -0,0 +1,219 @@
+//! Recorded multi-sensor traces for calibration and offline evaluation.
+//!
+//! A trace is a CSV of per-tick sensor values with an optional ground-truth
+//! head count:
+//!
+//! ```text
+//! elapsed_secs,occupancy,mmwave,hot_pixels,thermal_blobs,co2_ppm
+//! 0.0,0,1,1,0,431.2
+//! 5.0,0,0,,,430.8
+//! ```
+//!
+//! Empty cells mean the sensor produced no reading that tick, so it can go
+//! stale during replay exactly as it would live. Calibration runs write one
+//! (ground truth from the calibration phase) and room profiles are built from
+//! it; [`replay`] drives the fusion engine over a trace without any I/O.
+
+use std::time::{Duration, Instant};
+
+use super::co2::Co2Tracker;
+use super::debounce::DebounceFsm;
+use super::fusion::{fuse, FusionInput, ThermalSnapshot};
+use super::occupancy::ThermalCountSmoother;
+use super::types::{FusedState, SuiteConfig};
+
+/// CSV header written by [`to_csv`]
+pub const TRACE_HEADER: &str = "elapsed_secs,occupancy,mmwave,hot_pixels,thermal_blobs,co2_ppm";
+
+/// One tick of a recorded trace
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TraceRow {
+    /// Seconds since the start of the trace
+    pub elapsed_secs: f64,
+    /// Ground-truth head count, when known (calibration phase, labelled day)
+    pub occupancy: Option<u8>,
+    /// Raw (pre-debounce) mmWave presence
+    pub mmwave: Option<bool>,
+    /// Thermal pixels above the hot-pixel threshold
+    pub hot_pixels: Option<usize>,
+    /// Person-sized blobs in the thermal frame
+    pub thermal_blobs: Option<u8>,
+    pub co2_ppm: Option<f32>,
+}
+
+/// Parse a trace CSV. Columns are matched by header name, so extra columns
+/// are ignored and only `elapsed_secs` is required.
+pub fn parse_trace(csv: &str) -> Result<Vec<TraceRow>, String> {
+    let mut lines = csv.lines().enumerate().filter(|(_, l)| !l.trim().is_empty());
+    let (_, header) = lines.next().ok_or("Empty trace")?;
+    let columns: Vec<&str> = header.split(',').map(str::trim).collect();
+    let col = |name: &str| columns.iter().position(|c| *c == name);
+    let elapsed_col = col("elapsed_secs").ok_or("Trace is missing the elapsed_secs column")?;
+    let occupancy_col = col("occupancy");
+    let mmwave_col = col("mmwave");
+    let hot_col = col("hot_pixels");
+    let blobs_col = col("thermal_blobs");
+    let co2_col = col("co2_ppm");
+
+    let mut rows = Vec::new();
+    for (idx, line) in lines {
+        let line_no = idx + 1;
+        let cells: Vec<&str> = line.split(',').map(str::trim).collect();
+        let cell = |c: Option<usize>| c.and_then(|i| cells.get(i)).copied().filter(|v| !v.is_empty());
+        let bad = |name: &str, v: &str| format!("Line {}: invalid {} '{}'", line_no, name, v);
+
+        let elapsed = cell(Some(elapsed_col)).ok_or(format!("Line {}: missing elapsed_secs", line_no))?;
+        rows.push(TraceRow {
+            elapsed_secs: elapsed.parse().map_err(|_| bad("elapsed_secs", elapsed))?,
+            occupancy: cell(occupancy_col)
+                .map(|v| v.parse().map_err(|_| bad("occupancy", v)))
+                .transpose()?,
+            mmwave: cell(mmwave_col)
+                .map(|v| match v {
+                    "1" | "true" => Ok(true),
+                    "0" | "false" => Ok(false),
+                    _ => Err(bad("mmwave", v)),
+                })
+                .transpose()?,
+            hot_pixels: cell(hot_col)
+                .map(|v| v.parse().map_err(|_| bad("hot_pixels", v)))
+                .transpose()?,
+            thermal_blobs: cell(blobs_col)
+                .map(|v| v.parse().map_err(|_| bad("thermal_blobs", v)))
+                .transpose()?,
+            co2_ppm: cell(co2_col)
+                .map(|v| v.parse().map_err(|_| bad("co2_ppm", v)))
+                .transpose()?,
+        });
+    }
+    Ok(rows)
+}
+
+/// Serialize rows in the format read by [`parse_trace`].
+pub fn to_csv(rows: &[TraceRow]) -> String {
+    fn opt<T: ToString>(v: Option<T>) -> String {
+        v.map(|v| v.to_string()).unwrap_or_default()
+    }
+    let mut out = String::from(TRACE_HEADER);
+    out.push('\n');
+    for r in rows {
+        out.push_str(&format!(
+            "{:.1},{},{},{},{},{}\n",
+            r.elapsed_secs,
+            opt(r.occupancy),
+            opt(r.mmwave.map(|p| if p { "1" } else { "0" })),
+            opt(r.hot_pixels),
+            opt(r.thermal_blobs),
+            opt(r.co2_ppm.map(|p| format!("{:.1}", p))),
+        ));
+    }
+    out
+}
+
+/// Run the fusion engine over a trace, one [`FusedState`] per row.
+///
+/// Mirrors the live fusion task: mmWave goes through the debounce FSM, CO2
+/// through the rolling tracker, thermal blob counts through the median
+/// smoother, and each sensor's last-reading time only advances on ticks where
+/// it has a value.
+pub fn replay(rows: &[TraceRow], config: &SuiteConfig) -> Vec<FusedState> {
+    let base = Instant::now();
+    let mut debounce_fsm = DebounceFsm::new(config.debounce_secs);
+    let mut co2_tracker = Co2Tracker::new(config.co2.clone());
+    let mut blob_smoother = ThermalCountSmoother::default();
+    let mut mmwave_last_reading: Option<Instant> = None;
+    let mut thermal_snapshot: Option<ThermalSnapshot> = None;
+    let mut co2_last_reading: Option<Instant> = None;
+
+    rows.iter()
+        .map(|row| {
+            let now = base + Duration::from_secs_f64(row.elapsed_secs.max(0.0));
+
+            if let Some(raw) = row.mmwave {
+                debounce_fsm.process(raw, now);
+                mmwave_last_reading = Some(now);
+            }
+            if let Some(hot_pixels) = row.hot_pixels {
+                thermal_snapshot = Some(ThermalSnapshot {
+                    is_present: hot_pixels >= config.thermal.min_blob_pixels,
+                    hot_pixels,
+                    occupancy_count: blob_smoother.push(row.thermal_blobs.unwrap_or(0)),
+                    last_reading: now,
+                });
+            }
+            if let Some(ppm) = row.co2_ppm {
+                co2_tracker.add_reading(ppm, now);
+                co2_last_reading = Some(now);
+            }
+
+            let input = FusionInput {
+                mmwave_present: debounce_fsm.current(),
+                mmwave_last_reading,
+                thermal_frame: thermal_snapshot,
+                co2_elevated: co2_tracker.is_elevated(),
+                co2_trend: co2_tracker.trend(),
+                co2_occupancy: co2_tracker.projected_occupancy(),
+                co2_last_reading,
+                now,
+            };
+            fuse(&input, &config.fusion)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_trace_with_gaps() {
+        let csv = "elapsed_secs,occupancy,mmwave,hot_pixels,thermal_blobs,co2_ppm\n\
+                   0.0,0,1,1,0,431.2\n\
+                   5.0,,0,,,\n";
+        let rows = parse_trace(csv).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].occupancy, Some(0));
+        assert_eq!(rows[0].mmwave, Some(true));
+        assert_eq!(rows[0].hot_pixels, Some(1));
+        assert_eq!(rows[0].co2_ppm, Some(431.2));
+        assert_eq!(rows[1].occupancy, None);
+        assert_eq!(rows[1].mmwave, Some(false));
+        assert_eq!(rows[1].hot_pixels, None);
+        assert_eq!(rows[1].co2_ppm, None);
+    }
+
+    #[test]
+    fn test_parse_trace_matches_columns_by_name() {
+        let csv = "co2_ppm,elapsed_secs,notes\n450,10,door open\n";
+        let rows = parse_trace(csv).unwrap();
+        assert_eq!(rows[0].elapsed_secs, 10.0);
+        assert_eq!(rows[0].co2_ppm, Some(450.0));
+        assert_eq!(rows[0].mmwave, None);
+    }
+
+    #[test]
+    fn test_parse_trace_reports_line() {
+        let err = parse_trace("elapsed_secs,mmwave\n0,1\n5,maybe\n").unwrap_err();
+        assert!(err.contains("Line 3"), "{}", err);
+        assert!(parse_trace("mmwave\n1\n").is_err());
+    }
+
+    #[test]
+    fn test_csv_roundtrip() {
+        let rows = vec![
+            TraceRow {
+                elapsed_secs: 0.0,
+                occupancy: Some(1),
+                mmwave: Some(true),
+                hot_pixels: Some(22),
+                thermal_blobs: Some(1),
+                co2_ppm: Some(455.5),
+            },
+            TraceRow {
+                elapsed_secs: 5.0,
+                ..Default::default()
+            },
+        ];
+        assert_eq!(parse_trace(&to_csv(&rows)).unwrap(), rows);
+    }
+}
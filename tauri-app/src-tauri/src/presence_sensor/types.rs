@@ -1,5 +1,6 @@
 //! Shared types for the multi-sensor presence detection suite.
 
+use serde::{Deserialize, Serialize};
 use std::time::Instant;
 
 // ============================================================================
@@ -135,6 +136,9 @@ pub struct SensorHealth {
 #[derive(Debug, Clone)]
 pub struct FusedState {
     pub presence: PresenceState,
+    /// Confidence in the presence decision (0.0–1.0). Lowered when sensors
+    /// disagree or when a weighted sensor is stale.
+    pub confidence: f32,
     pub occupancy: OccupancyEstimate,
     pub sensor_health: Vec<SensorHealth>,
 }
@@ -200,6 +204,9 @@ pub struct Co2Config {
     pub window_secs: u64,
     /// Approximate CO2 contribution per person
     pub ppm_per_person: f32,
+    /// Ventilation time constant: how quickly the level settles after the
+    /// head count changes (~6 min in a typical exam room)
+    pub time_constant_secs: u64,
 }
 
 impl Default for Co2Config {
@@ -208,16 +215,57 @@ impl Default for Co2Config {
             baseline_ppm: 420.0,
             window_secs: 600, // 10 minutes
             ppm_per_person: 40.0,
+            time_constant_secs: 360,
+        }
+    }
+}
+
+/// Per-sensor reliability weights used by the fusion engine (0.0–1.0).
+///
+/// A weight of 0 means the sensor is tracked for health but never votes.
+/// The default is mmWave-only, which is what an uncalibrated room gets.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SensorWeights {
+    pub mmwave: f32,
+    pub thermal: f32,
+    pub co2: f32,
+}
+
+impl SensorWeights {
+    pub fn get(&self, sensor: SensorType) -> f32 {
+        match sensor {
+            SensorType::MmWave => self.mmwave,
+            SensorType::Thermal => self.thermal,
+            SensorType::Co2 => self.co2,
+        }
+    }
+
+    pub fn total(&self) -> f32 {
+        self.mmwave + self.thermal + self.co2
+    }
+}
+
+impl Default for SensorWeights {
+    fn default() -> Self {
+        Self {
+            mmwave: 1.0,
+            thermal: 0.0,
+            co2: 0.0,
         }
     }
 }
 
-/// Staleness thresholds for the fusion engine
+/// Staleness thresholds and sensor weighting for the fusion engine
 #[derive(Debug, Clone)]
 pub struct FusionConfig {
     pub thermal_stale_secs: u64,
     pub co2_stale_secs: u64,
     pub mmwave_stale_secs: u64,
+    /// Reliability weights (from the room's calibration profile)
+    pub weights: SensorWeights,
+    /// Hot pixels needed for the thermal camera to vote "present"
+    pub thermal_min_hot_pixels: usize,
 }
 
 impl Default for FusionConfig {
@@ -226,6 +274,8 @@ impl Default for FusionConfig {
             thermal_stale_secs: 30,
             co2_stale_secs: 120,
             mmwave_stale_secs: 10,
+            weights: SensorWeights::default(),
+            thermal_min_hot_pixels: 4,
         }
     }
 }
@@ -275,6 +325,15 @@ mod tests {
         assert_eq!(suite.co2.baseline_ppm, 420.0);
     }
 
+    #[test]
+    fn test_sensor_weights_default_is_mmwave_only() {
+        let w = SensorWeights::default();
+        assert_eq!(w.get(SensorType::MmWave), 1.0);
+        assert_eq!(w.get(SensorType::Thermal), 0.0);
+        assert_eq!(w.get(SensorType::Co2), 0.0);
+        assert_eq!(w.total(), 1.0);
+    }
+
     #[test]
     fn test_occupancy_estimate_default() {
         let est = OccupancyEstimate::default();
@@ -0,0 +1,580 @@
+//! Prior-visit context for follow-up SOAP notes.
+//!
+//! Once a session's patient has been confirmed (`patient_confirmed_at`),
+//! `encounter_pipeline::generate_and_archive_soap` asks this module for the
+//! assessment and plan of the patient's last few visits, so a follow-up note
+//! can say "BP improved since last visit" instead of reading as a first
+//! encounter. Visits are gathered in order of cost:
+//!
+//! 1. Local archive — confirmed sessions on this workstation with the same
+//!    Medplum patient ID or the same normalized name + DOB.
+//! 2. Profile-service — `PatientRecord.session_ids`, which covers visits
+//!    recorded in other rooms.
+//! 3. Medplum — encounters for the patient's FHIR ID, for visits that only
+//!    reached the EMR.
+//!
+//! The local scan runs on the blocking pool under its own time budget; remote
+//! lookups are fail-open and share one timeout. A missing or slow source just
+//! means fewer prior visits. The block is bounded per section and
+//! overall, and its layout is versioned by
+//! `llm_client::PRIOR_VISITS_CONTEXT_VERSION`.
+
+use chrono::{DateTime, Duration, Utc};
+use std::fs;
+use std::path::Path;
+use std::time::Instant;
+use tracing::{info, warn};
+
+use crate::llm_client::{is_usable_soap, SOAP_SECTION_ASSESSMENT, SOAP_SECTION_PLAN};
+use crate::local_archive::{self, ArchiveMetadata};
+use crate::patient_name_tracker::normalize_patient_name;
+use crate::server_sync::ServerSyncContext;
+
+/// Most prior visits included in one SOAP prompt
+pub const MAX_PRIOR_VISITS: usize = 3;
+/// Per-section cap (assessment or plan of one visit), in characters
+const MAX_SECTION_CHARS: usize = 600;
+/// Cap on the whole block, in characters
+pub const MAX_BLOCK_CHARS: usize = 3000;
+/// How far back the local archive is scanned
+const LOCAL_LOOKBACK_DAYS: i64 = 730;
+/// Budget for the local archive scan; it keeps what it found by then
+const LOCAL_SCAN_BUDGET: std::time::Duration = std::time::Duration::from_secs(2);
+/// Budget for the profile-service + Medplum lookups combined
+const REMOTE_FETCH_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+/// A Medplum encounter starting this close to a known visit is that visit
+const SAME_VISIT_TOLERANCE_SECS: i64 = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriorVisitSource {
+    LocalArchive,
+    ProfileService,
+    Medplum,
+}
+
+/// Assessment and plan of one earlier visit
+#[derive(Debug, Clone)]
+pub struct PriorVisit {
+    /// Archive session ID, or the Medplum Encounter ID for EMR-only visits
+    pub id: String,
+    pub started_at: DateTime<Utc>,
+    pub assessment: String,
+    pub plan: String,
+    pub source: PriorVisitSource,
+}
+
+impl PriorVisit {
+    /// Pull the assessment and plan out of an archived SOAP note. `None` for
+    /// malformed notes or notes with neither section documented.
+    pub fn from_soap(
+        id: String,
+        started_at: DateTime<Utc>,
+        soap: &str,
+        source: PriorVisitSource,
+    ) -> Option<Self> {
+        if !is_usable_soap(soap) {
+            return None;
+        }
+        let assessment = documented(crate::encounter_pipeline::extract_soap_section(
+            soap,
+            SOAP_SECTION_ASSESSMENT,
+            Some(SOAP_SECTION_PLAN),
+        ));
+        let plan = documented(crate::encounter_pipeline::extract_soap_section(
+            soap,
+            SOAP_SECTION_PLAN,
+            None,
+        ));
+        if assessment.is_empty() && plan.is_empty() {
+            return None;
+        }
+        Some(Self { id, started_at, assessment, plan, source })
+    }
+}
+
+/// Empty string for sections holding only "Not documented" placeholders
+fn documented(section: String) -> String {
+    let placeholder_only = section.lines().all(|line| {
+        let item = line.trim_start_matches(['•', '-', '*']).trim();
+        item.is_empty() || item.eq_ignore_ascii_case("not documented")
+    });
+    if placeholder_only { String::new() } else { section }
+}
+
+/// Identity of a confirmed patient, as recorded on the session's metadata
+#[derive(Debug, Clone)]
+pub struct ConfirmedPatient {
+    pub name: String,
+    pub dob: String,
+    pub medplum_patient_id: Option<String>,
+}
+
+impl ConfirmedPatient {
+    /// `None` unless the clinician has confirmed the patient's name and DOB.
+    /// Vision-extracted identity alone is never trusted with another
+    /// patient's history.
+    pub fn from_metadata(metadata: &ArchiveMetadata) -> Option<Self> {
+        metadata.patient_confirmed_at.as_ref()?;
+        let name = metadata.patient_name.as_deref().filter(|n| !n.trim().is_empty())?;
+        let dob = metadata.patient_dob.as_deref().filter(|d| !d.trim().is_empty())?;
+        Some(Self {
+            name: name.to_string(),
+            dob: dob.to_string(),
+            medplum_patient_id: metadata.medplum_patient_id.clone(),
+        })
+    }
+
+    /// True when `metadata` is another confirmed visit by this patient
+    fn matches(&self, metadata: &ArchiveMetadata) -> bool {
+        if metadata.patient_confirmed_at.is_none() {
+            return false;
+        }
+        if let (Some(a), Some(b)) = (&self.medplum_patient_id, &metadata.medplum_patient_id) {
+            return a == b;
+        }
+        metadata.patient_dob.as_deref() == Some(self.dob.as_str())
+            && metadata
+                .patient_name
+                .as_deref()
+                .is_some_and(|n| normalize_patient_name(n) == normalize_patient_name(&self.name))
+    }
+}
+
+/// Bounded prior-visits block ready for `SoapOptions::prior_visits`
+#[derive(Debug, Clone)]
+pub struct PriorVisitsContext {
+    pub block: String,
+    /// IDs of the visits that made it into `block`, most recent first
+    pub visit_ids: Vec<String>,
+}
+
+impl PriorVisitsContext {
+    /// Format visits (most recent first) relative to `visit_at`, the start of
+    /// the visit being documented. Each section is capped at
+    /// [`MAX_SECTION_CHARS`]; visits that would push the block past
+    /// [`MAX_BLOCK_CHARS`] are dropped. `None` when there is nothing to show.
+    pub fn from_visits(visits: &[PriorVisit], visit_at: DateTime<Utc>) -> Option<Self> {
+        let mut block = String::new();
+        let mut visit_ids = Vec::new();
+        for visit in visits.iter().take(MAX_PRIOR_VISITS) {
+            let days = (visit_at - visit.started_at).num_days().max(0);
+            let mut entry = format!(
+                "Visit {} — {} ({} {} before today)\n",
+                visit_ids.len() + 1,
+                visit.started_at.format("%Y-%m-%d"),
+                days,
+                if days == 1 { "day" } else { "days" },
+            );
+            if !visit.assessment.is_empty() {
+                entry.push_str("Assessment:\n");
+                entry.push_str(&truncate_section(&visit.assessment));
+                entry.push('\n');
+            }
+            if !visit.plan.is_empty() {
+                entry.push_str("Plan:\n");
+                entry.push_str(&truncate_section(&visit.plan));
+                entry.push('\n');
+            }
+            let separator = if block.is_empty() { 0 } else { 1 };
+            if block.chars().count() + separator + entry.chars().count() > MAX_BLOCK_CHARS {
+                break;
+            }
+            if separator == 1 {
+                block.push('\n');
+            }
+            block.push_str(&entry);
+            visit_ids.push(visit.id.clone());
+        }
+        if visit_ids.is_empty() {
+            return None;
+        }
+        Some(Self { block: block.trim_end().to_string(), visit_ids })
+    }
+}
+
+/// Keep whole lines up to [`MAX_SECTION_CHARS`], marking the cut with "…"
+fn truncate_section(section: &str) -> String {
+    if section.chars().count() <= MAX_SECTION_CHARS {
+        return section.to_string();
+    }
+    let mut out = String::new();
+    let mut used = 0;
+    for line in section.lines() {
+        let len = line.chars().count() + 1;
+        if used + len > MAX_SECTION_CHARS {
+            break;
+        }
+        out.push_str(line);
+        out.push('\n');
+        used += len;
+    }
+    if out.is_empty() {
+        // A single overlong item: cut it mid-line
+        out = section.chars().take(MAX_SECTION_CHARS).collect();
+        out.push('\n');
+    }
+    out.push('…');
+    out
+}
+
+/// Prior-visit context for `session_id`, or `None` when its patient is not
+/// confirmed or has no earlier documented visit.
+pub async fn load_prior_visits(
+    session_id: &str,
+    session_date: &DateTime<Utc>,
+    sources: &ServerSyncContext,
+) -> Option<PriorVisitsContext> {
+    let session_dir = local_archive::get_session_archive_dir(session_id, session_date).ok()?;
+    let metadata = local_archive::read_metadata(&session_dir).ok()?;
+    let patient = ConfirmedPatient::from_metadata(&metadata)?;
+    let visit_at = DateTime::parse_from_rfc3339(&metadata.started_at)
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or(*session_date);
+
+    let mut visits = match local_archive::get_archive_dir() {
+        Ok(archive_dir) => {
+            let (patient, session_id) = (patient.clone(), session_id.to_string());
+            let deadline = Instant::now() + LOCAL_SCAN_BUDGET;
+            tokio::task::spawn_blocking(move || {
+                find_local_prior_visits(
+                    &archive_dir,
+                    &patient,
+                    &session_id,
+                    visit_at,
+                    MAX_PRIOR_VISITS,
+                    deadline,
+                )
+            })
+            .await
+            .unwrap_or_default()
+        }
+        Err(_) => Vec::new(),
+    };
+    let local_count = visits.len();
+
+    if visits.len() < MAX_PRIOR_VISITS {
+        let remote = fetch_remote_prior_visits(&patient, session_id, visit_at, &visits, sources);
+        match tokio::time::timeout(REMOTE_FETCH_TIMEOUT, remote).await {
+            Ok(mut found) => visits.append(&mut found),
+            Err(_) => warn!(
+                event = "soap_prior_visits_timeout",
+                session_id = %session_id,
+                "Remote prior-visit lookup timed out; using local visits only"
+            ),
+        }
+    }
+    visits.sort_by_key(|v| std::cmp::Reverse(v.started_at));
+
+    let context = PriorVisitsContext::from_visits(&visits, visit_at);
+    info!(
+        event = "soap_prior_visits",
+        session_id = %session_id,
+        local = local_count,
+        remote = visits.len() - local_count,
+        included = context.as_ref().map_or(0, |c| c.visit_ids.len()),
+        "Prior-visit context loaded"
+    );
+    context
+}
+
+/// Confirmed sessions for `patient` in the archive at `archive_dir`, most
+/// recent first, started before `visit_at` and within
+/// [`LOCAL_LOOKBACK_DAYS`]. Blocking; stops at `deadline` with whatever the
+/// days scanned so far turned up.
+pub fn find_local_prior_visits(
+    archive_dir: &Path,
+    patient: &ConfirmedPatient,
+    session_id: &str,
+    visit_at: DateTime<Utc>,
+    limit: usize,
+    deadline: Instant,
+) -> Vec<PriorVisit> {
+    let mut visits = Vec::new();
+    for days_back in 0..=LOCAL_LOOKBACK_DAYS {
+        if visits.len() >= limit {
+            break;
+        }
+        if Instant::now() >= deadline {
+            warn!(
+                event = "soap_prior_visits_local_budget",
+                session_id = %session_id,
+                days_scanned = days_back,
+                "Local prior-visit scan ran out of time; using visits found so far"
+            );
+            break;
+        }
+        let date = (visit_at - Duration::days(days_back)).date_naive();
+        let date_dir = archive_dir
+            .join(date.format("%Y").to_string())
+            .join(date.format("%m").to_string())
+            .join(date.format("%d").to_string());
+        let Ok(entries) = fs::read_dir(&date_dir) else { continue };
+
+        let mut day = Vec::new();
+        for entry in entries.flatten() {
+            let dir = entry.path();
+            let Ok(metadata) = local_archive::read_metadata(&dir) else { continue };
+            if metadata.session_id == session_id || !patient.matches(&metadata) {
+                continue;
+            }
+            // Legacy multi-patient sessions share one combined soap_note.txt
+            if metadata.sibling_group_id.is_none() && metadata.patient_count.unwrap_or(1) > 1 {
+                continue;
+            }
+            let Ok(started_at) = DateTime::parse_from_rfc3339(&metadata.started_at) else { continue };
+            let started_at = started_at.with_timezone(&Utc);
+            if started_at >= visit_at {
+                continue;
+            }
+            let Ok(soap) = fs::read_to_string(dir.join("soap_note.txt")) else { continue };
+            day.extend(PriorVisit::from_soap(
+                metadata.session_id,
+                started_at,
+                &soap,
+                PriorVisitSource::LocalArchive,
+            ));
+        }
+        day.sort_by_key(|v| std::cmp::Reverse(v.started_at));
+        visits.extend(day);
+    }
+    visits.truncate(limit);
+    visits
+}
+
+/// Visits from the profile-service patient index, then Medplum, skipping
+/// anything already in `known`. Stops once [`MAX_PRIOR_VISITS`] are found.
+async fn fetch_remote_prior_visits(
+    patient: &ConfirmedPatient,
+    session_id: &str,
+    visit_at: DateTime<Utc>,
+    known: &[PriorVisit],
+    sources: &ServerSyncContext,
+) -> Vec<PriorVisit> {
+    let mut found: Vec<PriorVisit> = Vec::new();
+    let have = |found: &[PriorVisit]| known.len() + found.len();
+
+    if let (Some(client), Some(physician_id)) = (&sources.client, &sources.physician_id) {
+        match client
+            .search_patient_by_name_dob(physician_id, &patient.name, &patient.dob)
+            .await
+        {
+            Ok(Some(record)) => {
+                for sid in record.session_ids.iter().rev() {
+                    if have(&found) >= MAX_PRIOR_VISITS {
+                        break;
+                    }
+                    if sid == session_id || known.iter().chain(&found).any(|v| &v.id == sid) {
+                        continue;
+                    }
+                    let details = match client.get_session(physician_id, sid).await {
+                        Ok(d) => d,
+                        Err(e) => {
+                            warn!(session_id = %sid, error = %e, "Prior-visit session fetch failed");
+                            continue;
+                        }
+                    };
+                    if details.patient_notes.as_ref().is_some_and(|n| n.len() > 1) {
+                        continue;
+                    }
+                    let Some(soap) = details.soap_note.as_deref() else { continue };
+                    let Ok(started_at) = DateTime::parse_from_rfc3339(&details.metadata.started_at)
+                    else {
+                        continue;
+                    };
+                    let started_at = started_at.with_timezone(&Utc);
+                    if started_at >= visit_at {
+                        continue;
+                    }
+                    found.extend(PriorVisit::from_soap(
+                        sid.clone(),
+                        started_at,
+                        soap,
+                        PriorVisitSource::ProfileService,
+                    ));
+                }
+            }
+            Ok(None) => {}
+            Err(e) => warn!(error = %e, "Prior-visit patient lookup failed"),
+        }
+    }
+
+    if have(&found) >= MAX_PRIOR_VISITS {
+        return found;
+    }
+    let (Some(medplum), Some(smart_backend), Some(patient_fhir_id)) =
+        (&sources.medplum, &sources.smart_backend, &patient.medplum_patient_id)
+    else {
+        return found;
+    };
+    let Ok(client) = crate::commands::active_emr_backend(medplum, smart_backend).await else {
+        return found;
+    };
+    if !client.is_authenticated().await {
+        return found;
+    }
+    let encounters = match client
+        .fetch_encounters_for_patient(patient_fhir_id, MAX_PRIOR_VISITS + 1)
+        .await
+    {
+        Ok(e) => e,
+        Err(e) => {
+            warn!(error = %e, "Prior-visit Medplum encounter lookup failed");
+            return found;
+        }
+    };
+    let tolerance = Duration::seconds(SAME_VISIT_TOLERANCE_SECS);
+    for encounter in encounters {
+        if have(&found) >= MAX_PRIOR_VISITS {
+            break;
+        }
+        let Ok(started_at) = DateTime::parse_from_rfc3339(&encounter.date) else { continue };
+        let started_at = started_at.with_timezone(&Utc);
+        // The current session's own encounter, or a visit already found locally
+        if started_at > visit_at - tolerance
+            || known
+                .iter()
+                .chain(&found)
+                .any(|v| (v.started_at - started_at).abs() < tolerance)
+        {
+            continue;
+        }
+        match client.fetch_soap_note_for_encounter(&encounter.fhir_id).await {
+            Ok(Some(soap)) => found.extend(PriorVisit::from_soap(
+                encounter.fhir_id,
+                started_at,
+                &soap,
+                PriorVisitSource::Medplum,
+            )),
+            Ok(None) => {}
+            Err(e) => warn!(error = %e, "Prior-visit Medplum SOAP fetch failed"),
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    const SOAP: &str = "S:\n• Follow-up for blood pressure\n\nO:\n• BP 152/94\n\nA:\n• Hypertension, suboptimally controlled\n\nP:\n• Increase amlodipine to 10 mg\n• Recheck in 6 weeks";
+
+    fn at(day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 9, day, 14, 0, 0).unwrap()
+    }
+
+    fn confirmed(session_id: &str, started_at: DateTime<Utc>, name: &str) -> ArchiveMetadata {
+        let mut m = ArchiveMetadata::new(session_id);
+        m.started_at = started_at.to_rfc3339();
+        m.patient_name = Some(name.to_string());
+        m.patient_dob = Some("1961-04-02".to_string());
+        m.patient_confirmed_at = Some(started_at.to_rfc3339());
+        m
+    }
+
+    fn write_session(archive: &Path, metadata: &ArchiveMetadata, soap: &str) {
+        let started = DateTime::parse_from_rfc3339(&metadata.started_at).unwrap();
+        let dir = archive
+            .join(started.format("%Y").to_string())
+            .join(started.format("%m").to_string())
+            .join(started.format("%d").to_string())
+            .join(&metadata.session_id);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("metadata.json"), serde_json::to_string(metadata).unwrap()).unwrap();
+        fs::write(dir.join("soap_note.txt"), soap).unwrap();
+    }
+
+    #[test]
+    fn from_soap_keeps_assessment_and_plan_only() {
+        let visit = PriorVisit::from_soap("s1".into(), at(1), SOAP, PriorVisitSource::LocalArchive)
+            .unwrap();
+        assert_eq!(visit.assessment, "• Hypertension, suboptimally controlled");
+        assert!(visit.plan.starts_with("• Increase amlodipine"));
+        assert!(!visit.assessment.contains("152/94"));
+
+        let empty = "S:\n• Cough\n\nO:\n• Not documented\n\nA:\n• Not documented\n\nP:\n• Not documented";
+        assert!(PriorVisit::from_soap("s2".into(), at(1), empty, PriorVisitSource::Medplum).is_none());
+        let malformed = "SOAP generation produced malformed output";
+        assert!(PriorVisit::from_soap("s3".into(), at(1), malformed, PriorVisitSource::Medplum).is_none());
+    }
+
+    #[test]
+    fn only_confirmed_patients_match() {
+        let mut current = confirmed("cur", at(20), "Smith, John");
+        let patient = ConfirmedPatient::from_metadata(&current).unwrap();
+
+        // Name normalization follows the patient index (comma reorder, case)
+        assert!(patient.matches(&confirmed("a", at(1), "john SMITH")));
+        assert!(!patient.matches(&confirmed("b", at(1), "Jane Smith")));
+
+        let mut unconfirmed = confirmed("c", at(1), "John Smith");
+        unconfirmed.patient_confirmed_at = None;
+        assert!(!patient.matches(&unconfirmed));
+
+        // A Medplum ID on both sides decides on its own
+        current.medplum_patient_id = Some("mp-1".into());
+        let patient = ConfirmedPatient::from_metadata(&current).unwrap();
+        let mut other = confirmed("d", at(1), "John Smith");
+        other.medplum_patient_id = Some("mp-2".into());
+        assert!(!patient.matches(&other));
+
+        current.patient_confirmed_at = None;
+        assert!(ConfirmedPatient::from_metadata(&current).is_none());
+    }
+
+    #[test]
+    fn local_visits_are_newest_first_and_exclude_current_session() {
+        let archive = tempfile::tempdir().unwrap();
+        let current = confirmed("cur", at(20), "John Smith");
+        write_session(archive.path(), &current, SOAP);
+        write_session(archive.path(), &confirmed("old", at(2), "John Smith"), SOAP);
+        write_session(archive.path(), &confirmed("recent", at(12), "Smith, John"), SOAP);
+        write_session(archive.path(), &confirmed("later", at(25), "John Smith"), SOAP);
+        write_session(archive.path(), &confirmed("other", at(10), "Mary Jones"), SOAP);
+
+        let patient = ConfirmedPatient::from_metadata(&current).unwrap();
+        let deadline = Instant::now() + LOCAL_SCAN_BUDGET;
+        let visits = find_local_prior_visits(archive.path(), &patient, "cur", at(20), 3, deadline);
+        let ids: Vec<_> = visits.iter().map(|v| v.id.as_str()).collect();
+        assert_eq!(ids, ["recent", "old"]);
+
+        let visits = find_local_prior_visits(archive.path(), &patient, "cur", at(20), 1, deadline);
+        assert_eq!(visits.len(), 1);
+        assert_eq!(visits[0].id, "recent");
+    }
+
+    #[test]
+    fn local_scan_stops_at_the_deadline() {
+        let archive = tempfile::tempdir().unwrap();
+        let current = confirmed("cur", at(20), "John Smith");
+        write_session(archive.path(), &confirmed("recent", at(12), "John Smith"), SOAP);
+
+        let patient = ConfirmedPatient::from_metadata(&current).unwrap();
+        let deadline = Instant::now();
+        let visits = find_local_prior_visits(archive.path(), &patient, "cur", at(20), 3, deadline);
+        assert!(visits.is_empty());
+    }
+
+    #[test]
+    fn block_is_bounded() {
+        let long_plan: String = (0..80).map(|i| format!("• Plan item number {i}\n")).collect();
+        let visits: Vec<_> = (0..5)
+            .map(|i| PriorVisit {
+                id: format!("v{i}"),
+                started_at: at(15 - i),
+                assessment: "• Hypertension".into(),
+                plan: long_plan.trim_end().to_string(),
+                source: PriorVisitSource::LocalArchive,
+            })
+            .collect();
+
+        let context = PriorVisitsContext::from_visits(&visits, at(20)).unwrap();
+        assert!(context.block.chars().count() <= MAX_BLOCK_CHARS);
+        assert!(context.block.starts_with("Visit 1 — 2026-09-15 (5 days before today)\nAssessment:"));
+        assert!(context.block.contains('…'));
+        assert!(!context.visit_ids.is_empty() && context.visit_ids.len() <= MAX_PRIOR_VISITS);
+        assert_eq!(context.visit_ids[0], "v0");
+
+        assert!(PriorVisitsContext::from_visits(&[], at(20)).is_none());
+    }
+}
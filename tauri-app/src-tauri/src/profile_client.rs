@@ -76,6 +76,10 @@ pub struct PatientRecord {
     pub medplum_patient_id: Option<String>,
     #[serde(default)]
     pub session_ids: Vec<String>,
+    /// session_id → YYYY-MM-DD for sessions confirmed with a date. Empty on
+    /// records from servers older than this field.
+    #[serde(default)]
+    pub session_dates: std::collections::BTreeMap<String, String>,
     pub created_at: String,
     pub updated_at: String,
 }
@@ -88,6 +92,9 @@ pub struct ConfirmPatientRequestBody {
     pub session_id: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub medplum_patient_id: Option<String>,
+    /// Archive date (YYYY-MM-DD) of `session_id`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_date: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -162,6 +169,8 @@ pub struct Room {
     pub debug_storage_enabled: Option<bool>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub input_device_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config_channel: Option<crate::server_config::ConfigChannel>,
     pub created_at: String,
     pub updated_at: String,
 }
@@ -206,6 +215,29 @@ pub enum ProxyImageOutcome {
     Unconfigured,
 }
 
+/// Bytes per `PATCH /uploads/:upload_id`. A dropped connection costs at
+/// most one chunk.
+const UPLOAD_CHUNK_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Server-side state of a resumable upload (`/uploads/:upload_id`).
+#[derive(Debug, Clone, Deserialize)]
+pub struct ResumableUpload {
+    pub upload_id: String,
+    pub length: u64,
+    /// Bytes the server has acknowledged
+    pub offset: u64,
+    #[serde(default)]
+    pub completed_at: Option<String>,
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+    Sha256::digest(data)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
 pub struct ProfileClient {
     base_urls: Vec<String>,
     active_index: AtomicUsize,
@@ -365,9 +397,26 @@ impl ProfileClient {
 
     // ── Server-configurable data (prompts, billing, thresholds) ────
 
-    pub async fn get_config_version(&self) -> Result<crate::server_config::ConfigVersion> {
+    /// `/config/{path}` scoped to a room, so the server resolves the room's
+    /// config channel (stable or candidate).
+    fn config_url(&self, path: &str, room_id: Option<&str>) -> String {
+        match room_id {
+            Some(rid) => format!(
+                "{}/config/{}?room_id={}",
+                self.base_url(),
+                path,
+                urlencoding::encode(rid)
+            ),
+            None => format!("{}/config/{}", self.base_url(), path),
+        }
+    }
+
+    pub async fn get_config_version(
+        &self,
+        room_id: Option<&str>,
+    ) -> Result<crate::server_config::ConfigVersion> {
         let resp = self
-            .with_auth(self.client.get(format!("{}/config/version", self.base_url())))
+            .with_auth(self.client.get(self.config_url("version", room_id)))
             .send()
             .await?;
         if !resp.status().is_success() {
@@ -378,9 +427,12 @@ impl ProfileClient {
         Ok(resp.json().await?)
     }
 
-    pub async fn get_config_prompts(&self) -> Result<crate::server_config::PromptTemplates> {
+    pub async fn get_config_prompts(
+        &self,
+        room_id: Option<&str>,
+    ) -> Result<crate::server_config::PromptTemplates> {
         let resp = self
-            .with_auth(self.client.get(format!("{}/config/prompts", self.base_url())))
+            .with_auth(self.client.get(self.config_url("prompts", room_id)))
             .send()
             .await?;
         if !resp.status().is_success() {
@@ -391,9 +443,12 @@ impl ProfileClient {
         Ok(resp.json().await?)
     }
 
-    pub async fn get_config_billing(&self) -> Result<crate::server_config::BillingData> {
+    pub async fn get_config_billing(
+        &self,
+        room_id: Option<&str>,
+    ) -> Result<crate::server_config::BillingData> {
         let resp = self
-            .with_auth(self.client.get(format!("{}/config/billing", self.base_url())))
+            .with_auth(self.client.get(self.config_url("billing", room_id)))
             .send()
             .await?;
         if !resp.status().is_success() {
@@ -404,9 +459,12 @@ impl ProfileClient {
         Ok(resp.json().await?)
     }
 
-    pub async fn get_config_thresholds(&self) -> Result<crate::server_config::DetectionThresholds> {
+    pub async fn get_config_thresholds(
+        &self,
+        room_id: Option<&str>,
+    ) -> Result<crate::server_config::DetectionThresholds> {
         let resp = self
-            .with_auth(self.client.get(format!("{}/config/thresholds", self.base_url())))
+            .with_auth(self.client.get(self.config_url("thresholds", room_id)))
             .send()
             .await?;
         if !resp.status().is_success() {
@@ -417,9 +475,12 @@ impl ProfileClient {
         Ok(resp.json().await?)
     }
 
-    pub async fn get_config_defaults(&self) -> Result<crate::server_config::OperationalDefaults> {
+    pub async fn get_config_defaults(
+        &self,
+        room_id: Option<&str>,
+    ) -> Result<crate::server_config::OperationalDefaults> {
         let resp = self
-            .with_auth(self.client.get(format!("{}/config/defaults", self.base_url())))
+            .with_auth(self.client.get(self.config_url("defaults", room_id)))
             .send()
             .await?;
         if !resp.status().is_success() {
@@ -683,8 +744,9 @@ impl ProfileClient {
     }
 
     /// Look up an existing patient by exact (name, dob). Returns None when
-    /// no match — used by the follow-up SOAP-context injection feature; not
-    /// called from the confirm path itself (server upserts via `confirm`).
+    /// no match — used by `prior_visits` to reach the patient's
+    /// `session_ids`; not called from the confirm path itself (server upserts
+    /// via `confirm`).
     pub async fn search_patient_by_name_dob(
         &self,
         physician_id: &str,
@@ -730,41 +792,150 @@ impl ProfileClient {
         Ok(())
     }
 
-    pub async fn upload_audio(
+    /// Upload a session's audio through the resumable upload protocol,
+    /// sending `UPLOAD_CHUNK_BYTES` chunks from the server's last
+    /// acknowledged offset.
+    ///
+    /// `upload_id` continues an upload started earlier, e.g. before an app
+    /// restart; if the server no longer has it, a new upload is started (the
+    /// server hands back an unfinished one for the same file if it has one).
+    /// `on_progress` sees every acknowledged offset so the caller can persist
+    /// it.
+    pub async fn upload_audio<F, Fut>(
         &self,
         physician_id: &str,
         session_id: &str,
         audio_path: &std::path::Path,
-    ) -> Result<()> {
-        let url = format!(
-            "{}/physicians/{}/sessions/{}/audio",
-            self.base_url(), physician_id, session_id
-        );
+        upload_id: Option<&str>,
+        mut on_progress: F,
+    ) -> Result<()>
+    where
+        F: FnMut(ResumableUpload) -> Fut,
+        Fut: std::future::Future<Output = ()>,
+    {
         let file_bytes = tokio::fs::read(audio_path)
             .await
             .map_err(|e| anyhow::anyhow!("Failed to read audio file: {e}"))?;
-        let part = reqwest::multipart::Part::bytes(file_bytes)
-            .file_name("audio.wav")
-            .mime_str("audio/wav")?;
-        let form = reqwest::multipart::Form::new().part("audio", part);
+        let (file_bytes, sha256) = tokio::task::spawn_blocking(move || {
+            let sha256 = sha256_hex(&file_bytes);
+            (file_bytes, sha256)
+        })
+        .await?;
+        let length = file_bytes.len() as u64;
+
+        let resumed = match upload_id {
+            Some(id) => self
+                .get_upload(id)
+                .await?
+                .filter(|u| u.length == length),
+            None => None,
+        };
+        let mut upload = match resumed {
+            Some(upload) => upload,
+            None => {
+                self.create_audio_upload(physician_id, session_id, length, &sha256)
+                    .await?
+            }
+        };
+        on_progress(upload.clone()).await;
+
+        while upload.completed_at.is_none() {
+            let start = upload.offset.min(length) as usize;
+            let end = (upload.offset + UPLOAD_CHUNK_BYTES).min(length) as usize;
+            upload = match self
+                .patch_upload(&upload.upload_id, upload.offset, file_bytes[start..end].to_vec())
+                .await?
+            {
+                Some(upload) => upload,
+                // Offset moved under us (a chunk whose response was lost);
+                // pick up from wherever the server is
+                None => self.get_upload(&upload.upload_id).await?.ok_or_else(|| {
+                    anyhow::anyhow!("Upload {} disappeared from server", upload.upload_id)
+                })?,
+            };
+            on_progress(upload.clone()).await;
+        }
+        Ok(())
+    }
 
-        // Use a longer timeout for large audio files
+    async fn create_audio_upload(
+        &self,
+        physician_id: &str,
+        session_id: &str,
+        length: u64,
+        sha256: &str,
+    ) -> Result<ResumableUpload> {
+        let url = format!(
+            "{}/physicians/{}/sessions/{}/audio/resumable",
+            self.base_url(), physician_id, session_id
+        );
         let resp = self
             .with_auth(self.client.post(&url))
-            .multipart(form)
-            .timeout(std::time::Duration::from_secs(300))
+            .json(&serde_json::json!({ "length": length, "sha256": sha256 }))
             .send()
             .await?;
         if !resp.status().is_success() {
             let status = resp.status();
             let text = resp.text().await.unwrap_or_default();
             anyhow::bail!(
-                "Upload audio failed: {} - {}",
+                "Start audio upload failed: {} - {}",
                 status,
                 &text[..text.len().min(200)]
             );
         }
-        Ok(())
+        Ok(resp.json().await?)
+    }
+
+    /// `None` if the server doesn't know the upload (expired or restarted
+    /// with a fresh data dir).
+    async fn get_upload(&self, upload_id: &str) -> Result<Option<ResumableUpload>> {
+        let url = format!("{}/uploads/{}", self.base_url(), upload_id);
+        let resp = self.with_auth(self.client.get(&url)).send().await?;
+        if resp.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await.unwrap_or_default();
+            anyhow::bail!(
+                "Get upload failed: {} - {}",
+                status,
+                &text[..text.len().min(200)]
+            );
+        }
+        Ok(Some(resp.json().await?))
+    }
+
+    /// Send one chunk. `None` on 409, when `offset` is no longer the
+    /// server's.
+    async fn patch_upload(
+        &self,
+        upload_id: &str,
+        offset: u64,
+        chunk: Vec<u8>,
+    ) -> Result<Option<ResumableUpload>> {
+        let url = format!("{}/uploads/{}", self.base_url(), upload_id);
+        let resp = self
+            .with_auth(self.client.patch(&url))
+            .header("Upload-Offset", offset.to_string())
+            .header("Content-Type", "application/octet-stream")
+            .body(chunk)
+            .timeout(std::time::Duration::from_secs(120))
+            .send()
+            .await?;
+        if resp.status() == reqwest::StatusCode::CONFLICT {
+            return Ok(None);
+        }
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await.unwrap_or_default();
+            anyhow::bail!(
+                "Upload chunk failed: {} - {}",
+                status,
+                &text[..text.len().min(200)]
+            );
+        }
+        Ok(Some(resp.json().await?))
     }
 
     pub async fn get_session_dates(
@@ -806,6 +977,43 @@ impl ProfileClient {
         Ok(sessions)
     }
 
+    /// Ranked full-text search over the physician's archived transcripts
+    /// and SOAP notes.
+    pub async fn search_sessions(
+        &self,
+        physician_id: &str,
+        query: &str,
+        from: Option<&str>,
+        to: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<crate::archive_search::SearchHit>> {
+        let mut url = format!(
+            "{}/physicians/{}/search?q={}&limit={}",
+            self.base_url(),
+            physician_id,
+            urlencoding::encode(query),
+            limit
+        );
+        if let Some(from) = from {
+            url.push_str(&format!("&from={}", urlencoding::encode(from)));
+        }
+        if let Some(to) = to {
+            url.push_str(&format!("&to={}", urlencoding::encode(to)));
+        }
+        let resp = self.with_auth(self.client.get(&url)).send().await?;
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await.unwrap_or_default();
+            anyhow::bail!(
+                "Session search failed: {} - {}",
+                status,
+                &text[..text.len().min(200)]
+            );
+        }
+        let hits: Vec<crate::archive_search::SearchHit> = resp.json().await?;
+        Ok(hits)
+    }
+
     pub async fn get_session(
         &self,
         physician_id: &str,
@@ -997,6 +1205,18 @@ impl ProfileClient {
         }
     }
 
+    /// Serialize and upload a session's `billing.json`.
+    pub async fn upload_billing_record(
+        &self,
+        physician_id: &str,
+        session_id: &str,
+        record: &crate::billing::BillingRecord,
+    ) -> Result<()> {
+        let data = serde_json::to_vec_pretty(record)?;
+        self.upload_session_file(physician_id, session_id, "billing.json", data)
+            .await
+    }
+
     /// Upload the day log for a date
     pub async fn upload_day_log(
         &self,
@@ -0,0 +1,492 @@
+//! Offline re-diarization of archived continuous-mode encounters.
+//!
+//! Drives `diarization::offline` over a session's `segments.jsonl`: locates the
+//! run's recording, re-extracts one embedding per segment, re-clusters, and
+//! rewrites the speaker labels in `segments.jsonl` and `transcript.txt`.
+//!
+//! The first run keeps the live labelling as `segments.original.jsonl` /
+//! `transcript.original.txt` for comparison; later runs leave those alone.
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+use crate::config::Config;
+use crate::diarization::clustering::EnrolledCentroid;
+use crate::diarization::offline::{
+    rediarize, OfflineDiarizationConfig, SegmentEmbedder, SegmentSpan, SpeakerRelabel,
+};
+use crate::diarization::provider::Utterance;
+use crate::diarization::{DiarizationConfig, DiarizationError, DiarizationProvider};
+use crate::replay_bundle::ReplaySegment;
+use crate::segment_log::LOG_FILENAME as SEGMENTS_FILENAME;
+use crate::speaker_profiles::SpeakerProfileManager;
+use crate::transcript_buffer::format_speaker_label;
+
+pub const ORIGINAL_SEGMENTS_FILENAME: &str = "segments.original.jsonl";
+pub const ORIGINAL_TRANSCRIPT_FILENAME: &str = "transcript.original.txt";
+const TRANSCRIPT_FILENAME: &str = "transcript.txt";
+
+/// The recording is created just before the pipeline starts; allow for the
+/// estimated run start landing slightly before the file's timestamp.
+const RECORDING_START_SLACK_SECS: i64 = 5;
+
+/// Outcome of a re-diarization run, returned to the history window.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RediarizationSummary {
+    pub segment_count: usize,
+    /// Segments whose speaker label changed
+    pub relabeled_count: usize,
+    pub speakers_before: Vec<String>,
+    pub speakers_after: Vec<String>,
+    pub transcript_lines_rewritten: usize,
+}
+
+/// Read a session's `segments.jsonl`. Malformed lines are skipped.
+pub fn read_segments(session_dir: &Path) -> Result<Vec<ReplaySegment>, String> {
+    let path = session_dir.join(SEGMENTS_FILENAME);
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    Ok(content
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .filter_map(|l| match serde_json::from_str::<ReplaySegment>(l) {
+            Ok(s) => Some(s),
+            Err(e) => {
+                warn!("Skipping malformed segment line: {}", e);
+                None
+            }
+        })
+        .collect())
+}
+
+pub fn segment_spans(segments: &[ReplaySegment]) -> Vec<SegmentSpan> {
+    segments
+        .iter()
+        .map(|s| SegmentSpan {
+            index: s.index,
+            start_ms: s.start_ms,
+            end_ms: s.end_ms,
+        })
+        .collect()
+}
+
+/// Wall-clock time the run's audio clock started, from the first segment's
+/// receive time minus its audio-clock end.
+pub fn estimate_run_started_at(segments: &[ReplaySegment]) -> Option<DateTime<Utc>> {
+    let first = segments.first()?;
+    let received = DateTime::parse_from_rfc3339(&first.ts).ok()?.with_timezone(&Utc);
+    Some(received - chrono::Duration::milliseconds(first.end_ms as i64))
+}
+
+/// Find the `continuous_YYYYMMDD_HHMMSS.wav` recording for a run: the latest
+/// one that started at or before `run_started_at`.
+pub fn find_run_recording(recordings_dir: &Path, run_started_at: DateTime<Utc>) -> Option<PathBuf> {
+    let cutoff = run_started_at + chrono::Duration::seconds(RECORDING_START_SLACK_SECS);
+    fs::read_dir(recordings_dir)
+        .ok()?
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let stamp = name.strip_prefix("continuous_")?.strip_suffix(".wav")?;
+            let started = NaiveDateTime::parse_from_str(stamp, "%Y%m%d_%H%M%S").ok()?.and_utc();
+            (started <= cutoff).then(|| (started, entry.path()))
+        })
+        .max_by_key(|(started, _)| *started)
+        .map(|(_, path)| path)
+}
+
+/// Embeds segments by seeking into a 16kHz mono recording, so a day-long
+/// continuous recording never has to be loaded in full.
+pub struct WavSegmentEmbedder {
+    reader: hound::WavReader<BufReader<File>>,
+    provider: DiarizationProvider,
+}
+
+impl WavSegmentEmbedder {
+    pub fn new(audio_path: &Path, provider: DiarizationProvider) -> Result<Self, String> {
+        let reader = hound::WavReader::open(audio_path)
+            .map_err(|e| format!("Failed to open {}: {}", audio_path.display(), e))?;
+        let spec = reader.spec();
+        if spec.channels != 1 || spec.sample_rate != 16000 {
+            return Err(format!(
+                "Expected 16kHz mono recording, got {}Hz with {} channels",
+                spec.sample_rate, spec.channels
+            ));
+        }
+        Ok(Self { reader, provider })
+    }
+
+    fn read_range(&mut self, start_ms: u64, end_ms: u64) -> Result<Vec<f32>, DiarizationError> {
+        let total = self.reader.duration() as u64;
+        let start = (start_ms * 16).min(total);
+        let end = (end_ms * 16).min(total);
+        if end <= start {
+            return Ok(Vec::new());
+        }
+        self.reader
+            .seek(start as u32)
+            .map_err(|e| DiarizationError::InvalidAudio(e.to_string()))?;
+        let count = (end - start) as usize;
+        let spec = self.reader.spec();
+        let samples = match spec.sample_format {
+            hound::SampleFormat::Int => {
+                let max_val = (1_i64 << (spec.bits_per_sample - 1)) as f32;
+                self.reader
+                    .samples::<i32>()
+                    .take(count)
+                    .filter_map(|s| s.ok())
+                    .map(|s| s as f32 / max_val)
+                    .collect()
+            }
+            hound::SampleFormat::Float => self
+                .reader
+                .samples::<f32>()
+                .take(count)
+                .filter_map(|s| s.ok())
+                .collect(),
+        };
+        Ok(samples)
+    }
+}
+
+impl SegmentEmbedder for WavSegmentEmbedder {
+    fn embed(&mut self, start_ms: u64, end_ms: u64) -> Result<Option<Vec<f32>>, DiarizationError> {
+        let audio = self.read_range(start_ms, end_ms)?;
+        self.provider
+            .embed_utterance(&Utterance::new(&audio, start_ms, end_ms))
+    }
+}
+
+/// One transcript line as written by the splitter: "Label: text", or bare
+/// text when the segment has no speaker.
+fn transcript_line(speaker_id: Option<&str>, confidence: Option<f32>, text: &str) -> String {
+    if speaker_id.is_some() {
+        format!("{}: {}", format_speaker_label(speaker_id, confidence), text)
+    } else {
+        text.to_string()
+    }
+}
+
+/// Copy `name` to `original_name` unless a copy already exists.
+fn preserve_original(session_dir: &Path, name: &str, original_name: &str) -> Result<(), String> {
+    let src = session_dir.join(name);
+    let dest = session_dir.join(original_name);
+    if src.exists() && !dest.exists() {
+        fs::copy(&src, &dest).map_err(|e| format!("Failed to preserve {}: {}", name, e))?;
+    }
+    Ok(())
+}
+
+/// Rewrite speaker labels in `segments.jsonl` and `transcript.txt`.
+///
+/// Segment lines are edited in place so fields other than `speaker_id` /
+/// `speaker_confidence` survive untouched. Transcript lines are matched to
+/// segments in order; lines that don't correspond to a segment (manual edits,
+/// notes) are left as they are.
+pub fn apply_relabels(session_dir: &Path, relabels: &[SpeakerRelabel]) -> Result<RediarizationSummary, String> {
+    preserve_original(session_dir, SEGMENTS_FILENAME, ORIGINAL_SEGMENTS_FILENAME)?;
+    preserve_original(session_dir, TRANSCRIPT_FILENAME, ORIGINAL_TRANSCRIPT_FILENAME)?;
+
+    let by_index: HashMap<u64, &SpeakerRelabel> = relabels.iter().map(|r| (r.index, r)).collect();
+
+    let segments_path = session_dir.join(SEGMENTS_FILENAME);
+    let content = fs::read_to_string(&segments_path)
+        .map_err(|e| format!("Failed to read segments: {}", e))?;
+
+    let mut speakers_before = BTreeSet::new();
+    let mut speakers_after = BTreeSet::new();
+    let mut relabeled_count = 0;
+    let mut segment_count = 0;
+    // (old transcript line, new transcript line) per segment, in order
+    let mut line_rewrites: Vec<(String, String)> = Vec::new();
+    let mut out_lines: Vec<String> = Vec::new();
+
+    for line in content.lines().filter(|l| !l.trim().is_empty()) {
+        let mut value: serde_json::Value = match serde_json::from_str(line) {
+            Ok(v) => v,
+            Err(_) => {
+                out_lines.push(line.to_string());
+                continue;
+            }
+        };
+        let Some(relabel) = value["index"].as_u64().and_then(|i| by_index.get(&i)) else {
+            out_lines.push(line.to_string());
+            continue;
+        };
+        segment_count += 1;
+
+        let old_speaker = value["speaker_id"].as_str().map(str::to_string);
+        let old_confidence = value["speaker_confidence"].as_f64().map(|c| c as f32);
+        let text = value["text"].as_str().unwrap_or_default().to_string();
+
+        if let Some(ref s) = old_speaker {
+            speakers_before.insert(s.clone());
+        }
+        if let Some(ref s) = relabel.speaker_id {
+            speakers_after.insert(s.clone());
+        }
+        if old_speaker != relabel.speaker_id {
+            relabeled_count += 1;
+        }
+
+        line_rewrites.push((
+            transcript_line(old_speaker.as_deref(), old_confidence, &text),
+            transcript_line(relabel.speaker_id.as_deref(), relabel.speaker_confidence, &text),
+        ));
+
+        if let Some(obj) = value.as_object_mut() {
+            match (&relabel.speaker_id, relabel.speaker_confidence) {
+                (Some(id), confidence) => {
+                    obj.insert("speaker_id".into(), serde_json::json!(id));
+                    match confidence {
+                        Some(c) => obj.insert("speaker_confidence".into(), serde_json::json!(c)),
+                        None => obj.remove("speaker_confidence"),
+                    };
+                }
+                (None, _) => {
+                    obj.remove("speaker_id");
+                    obj.remove("speaker_confidence");
+                }
+            }
+        }
+        out_lines.push(
+            serde_json::to_string(&value).map_err(|e| format!("Failed to serialize segment: {}", e))?,
+        );
+    }
+
+    let mut segments_out = out_lines.join("\n");
+    segments_out.push('\n');
+    fs::write(&segments_path, segments_out).map_err(|e| format!("Failed to write segments: {}", e))?;
+
+    let transcript_path = session_dir.join(TRANSCRIPT_FILENAME);
+    let mut transcript_lines_rewritten = 0;
+    if transcript_path.exists() {
+        let transcript = fs::read_to_string(&transcript_path)
+            .map_err(|e| format!("Failed to read transcript: {}", e))?;
+        let mut pending = line_rewrites.iter().peekable();
+        let rewritten: Vec<String> = transcript
+            .lines()
+            .map(|line| match pending.peek() {
+                Some((old, new)) if line == old => {
+                    pending.next();
+                    if line != new {
+                        transcript_lines_rewritten += 1;
+                    }
+                    new.clone()
+                }
+                _ => line.to_string(),
+            })
+            .collect();
+        fs::write(&transcript_path, rewritten.join("\n"))
+            .map_err(|e| format!("Failed to write transcript: {}", e))?;
+    }
+
+    Ok(RediarizationSummary {
+        segment_count,
+        relabeled_count,
+        speakers_before: speakers_before.into_iter().collect(),
+        speakers_after: speakers_after.into_iter().collect(),
+        transcript_lines_rewritten,
+    })
+}
+
+/// Re-diarize a session directory with the given embedder and enrolled speakers.
+pub fn rediarize_session_dir(
+    session_dir: &Path,
+    embedder: &mut dyn SegmentEmbedder,
+    enrolled: &[EnrolledCentroid],
+    config: &OfflineDiarizationConfig,
+) -> Result<RediarizationSummary, String> {
+    let segments = read_segments(session_dir)?;
+    if segments.is_empty() {
+        return Err("Session has no segments to re-diarize".to_string());
+    }
+    let relabels = rediarize(&segment_spans(&segments), embedder, enrolled, config)
+        .map_err(|e| format!("Re-diarization failed: {}", e))?;
+    let summary = apply_relabels(session_dir, &relabels)?;
+    info!(
+        session_dir = %session_dir.display(),
+        segments = summary.segment_count,
+        relabeled = summary.relabeled_count,
+        speakers_before = summary.speakers_before.len(),
+        speakers_after = summary.speakers_after.len(),
+        "Re-diarization applied"
+    );
+    Ok(summary)
+}
+
+/// Production entry point: load the embedding model and enrolled profiles,
+/// locate the recording, and re-diarize the archived session.
+///
+/// `audio_override` skips the recording lookup (e.g. a recording moved out of
+/// `recordings/`).
+pub fn rediarize_archived_session(
+    session_dir: &Path,
+    config: &Config,
+    audio_override: Option<PathBuf>,
+) -> Result<RediarizationSummary, String> {
+    let segments = read_segments(session_dir)?;
+    let audio_path = match audio_override {
+        Some(path) => path,
+        None => estimate_run_started_at(&segments)
+            .and_then(|started| find_run_recording(&config.get_recordings_dir(), started))
+            .ok_or_else(|| {
+                "No recording found for this encounter (it may have been pruned by retention)".to_string()
+            })?,
+    };
+    info!("Re-diarizing {} from {}", session_dir.display(), audio_path.display());
+
+    let model_path = config
+        .get_diarization_model_path()
+        .map_err(|e| format!("Failed to get diarization model path: {}", e))?;
+    let provider = DiarizationProvider::new(DiarizationConfig {
+        model_path,
+        max_speakers: config.max_speakers,
+        n_threads: 2,
+        ..Default::default()
+    })
+    .map_err(|e| format!("Failed to initialize diarization provider: {}", e))?;
+
+    let enrolled: Vec<EnrolledCentroid> = match SpeakerProfileManager::load() {
+        Ok(manager) => manager
+            .list()
+            .iter()
+            .map(|p| EnrolledCentroid::new(p.id.clone(), p.name.clone(), p.embedding.clone(), true))
+            .collect(),
+        Err(e) => {
+            warn!("Failed to load speaker profiles, re-diarizing without enrolled speakers: {}", e);
+            Vec::new()
+        }
+    };
+
+    let offline_config = OfflineDiarizationConfig {
+        max_speakers: config.max_speakers,
+        ..Default::default()
+    };
+    let mut embedder = WavSegmentEmbedder::new(&audio_path, provider)?;
+    rediarize_session_dir(session_dir, &mut embedder, &enrolled, &offline_config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_segments(dir: &Path, lines: &[serde_json::Value]) {
+        let body: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+        fs::write(dir.join(SEGMENTS_FILENAME), body.join("\n") + "\n").unwrap();
+    }
+
+    fn relabel(index: u64, speaker: Option<&str>, confidence: Option<f32>) -> SpeakerRelabel {
+        SpeakerRelabel {
+            index,
+            speaker_id: speaker.map(str::to_string),
+            speaker_confidence: confidence,
+        }
+    }
+
+    #[test]
+    fn test_apply_relabels_rewrites_and_preserves_originals() {
+        let dir = tempfile::tempdir().unwrap();
+        write_segments(
+            dir.path(),
+            &[
+                serde_json::json!({"ts": "2026-04-01T14:00:01Z", "index": 0, "start_ms": 0, "end_ms": 900, "text": "Hi there", "speaker_id": "Speaker 1", "speaker_confidence": 0.5, "word_count": 2, "buffer_word_count": 2}),
+                serde_json::json!({"ts": "2026-04-01T14:00:02Z", "index": 1, "start_ms": 1000, "end_ms": 1900, "text": "Hello doctor", "speaker_id": "Speaker 2", "word_count": 2, "buffer_word_count": 4}),
+                serde_json::json!({"ts": "2026-04-01T14:00:03Z", "index": 2, "start_ms": 2000, "end_ms": 2100, "text": "mm", "word_count": 1, "buffer_word_count": 5}),
+            ],
+        );
+        let transcript = "Speaker 1 (50%): Hi there\nSpeaker 2: Hello doctor\nmm";
+        fs::write(dir.path().join(TRANSCRIPT_FILENAME), transcript).unwrap();
+
+        let summary = apply_relabels(
+            dir.path(),
+            &[
+                relabel(0, Some("Dr. Smith"), Some(0.9)),
+                relabel(1, Some("Speaker 1"), Some(0.8)),
+                relabel(2, None, None),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(summary.segment_count, 3);
+        assert_eq!(summary.relabeled_count, 2);
+        assert_eq!(summary.speakers_before, vec!["Speaker 1", "Speaker 2"]);
+        assert_eq!(summary.speakers_after, vec!["Dr. Smith", "Speaker 1"]);
+        assert_eq!(summary.transcript_lines_rewritten, 2);
+
+        let new_transcript = fs::read_to_string(dir.path().join(TRANSCRIPT_FILENAME)).unwrap();
+        assert_eq!(new_transcript, "Dr. Smith (90%): Hi there\nSpeaker 1 (80%): Hello doctor\nmm");
+
+        let segments = read_segments(dir.path()).unwrap();
+        assert_eq!(segments[0].speaker_id.as_deref(), Some("Dr. Smith"));
+        assert_eq!(segments[1].speaker_confidence, Some(0.8));
+        assert_eq!(segments[2].speaker_id, None);
+        // Unrelated fields are kept
+        let raw = fs::read_to_string(dir.path().join(SEGMENTS_FILENAME)).unwrap();
+        assert!(raw.contains("\"buffer_word_count\":4"));
+
+        // Originals preserved, and not overwritten by a second run
+        let original = fs::read_to_string(dir.path().join(ORIGINAL_TRANSCRIPT_FILENAME)).unwrap();
+        assert_eq!(original, transcript);
+        apply_relabels(dir.path(), &[relabel(0, Some("Speaker 9"), None)]).unwrap();
+        let original = fs::read_to_string(dir.path().join(ORIGINAL_TRANSCRIPT_FILENAME)).unwrap();
+        assert_eq!(original, transcript);
+        let original_segments = fs::read_to_string(dir.path().join(ORIGINAL_SEGMENTS_FILENAME)).unwrap();
+        assert!(original_segments.contains("\"Speaker 2\""));
+    }
+
+    #[test]
+    fn test_transcript_lines_without_matching_segment_are_kept() {
+        let dir = tempfile::tempdir().unwrap();
+        write_segments(
+            dir.path(),
+            &[serde_json::json!({"ts": "2026-04-01T14:00:01Z", "index": 4, "start_ms": 0, "end_ms": 900, "text": "Hi", "speaker_id": "Speaker 1"})],
+        );
+        fs::write(dir.path().join(TRANSCRIPT_FILENAME), "Clinician note\nSpeaker 1: Hi").unwrap();
+
+        apply_relabels(dir.path(), &[relabel(4, Some("Speaker 2"), None)]).unwrap();
+
+        let new_transcript = fs::read_to_string(dir.path().join(TRANSCRIPT_FILENAME)).unwrap();
+        assert_eq!(new_transcript, "Clinician note\nSpeaker 2: Hi");
+    }
+
+    #[test]
+    fn test_find_run_recording_picks_latest_before_run_start() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in [
+            "continuous_20260401_080000.wav",
+            "continuous_20260401_130000.wav",
+            "continuous_20260401_170000.wav",
+            "session_20260401_140000.wav",
+        ] {
+            fs::write(dir.path().join(name), b"").unwrap();
+        }
+        let started = DateTime::parse_from_rfc3339("2026-04-01T13:00:02Z").unwrap().with_timezone(&Utc);
+        let found = find_run_recording(dir.path(), started).unwrap();
+        assert!(found.ends_with("continuous_20260401_130000.wav"));
+
+        let too_early = DateTime::parse_from_rfc3339("2026-04-01T07:00:00Z").unwrap().with_timezone(&Utc);
+        assert!(find_run_recording(dir.path(), too_early).is_none());
+    }
+
+    #[test]
+    fn test_estimate_run_started_at() {
+        let segments = vec![ReplaySegment {
+            ts: "2026-04-01T13:10:00Z".to_string(),
+            index: 0,
+            start_ms: 595_000,
+            end_ms: 600_000,
+            text: "Hi".to_string(),
+            speaker_id: None,
+            speaker_confidence: None,
+        }];
+        let started = estimate_run_started_at(&segments).unwrap();
+        assert_eq!(started.to_rfc3339(), "2026-04-01T13:00:00+00:00");
+    }
+}
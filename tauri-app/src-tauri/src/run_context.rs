@@ -106,6 +106,7 @@ impl RunContext for TauriRunContext {
     fn emit_continuous_event(&self, event: &ContinuousModeEvent) {
         use tauri::Emitter;
         let _ = self.app.emit("continuous_mode_event", event);
+        crate::mcp::notifications::publish_continuous_event(event);
     }
 
     fn emit_json(&self, event_name: &str, payload: serde_json::Value) {
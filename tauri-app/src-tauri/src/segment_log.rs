@@ -10,7 +10,7 @@ use std::io::Write;
 use std::path::{Path, PathBuf};
 use tracing::warn;
 
-const LOG_FILENAME: &str = "segments.jsonl";
+pub const LOG_FILENAME: &str = "segments.jsonl";
 
 /// Appends one JSONL line per transcript segment to a session's archive folder.
 /// Created per continuous-mode run; path updates when a new session_id is assigned.
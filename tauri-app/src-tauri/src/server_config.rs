@@ -37,6 +37,8 @@ pub struct ServerConfig {
     pub defaults: OperationalDefaults,
     pub version: u64,
     pub source: ConfigSource,
+    /// Rollout channel the config was served from
+    pub channel: ConfigChannel,
 }
 
 // ── ConfigVersion ────────────────────────────────────────────────
@@ -45,6 +47,19 @@ pub struct ServerConfig {
 pub struct ConfigVersion {
     pub version: u64,
     pub updated_at: String,
+    /// Channel the server resolved for this room (absent on older servers)
+    #[serde(default)]
+    pub channel: ConfigChannel,
+}
+
+/// Staged-rollout channel. Rooms assigned to `candidate` on the profile
+/// server receive staged config ahead of the rest of the clinic.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigChannel {
+    #[default]
+    Stable,
+    Candidate,
 }
 
 // ── PromptTemplates ──────────────────────────────────────────────
@@ -76,6 +91,10 @@ pub struct PromptTemplates {
     #[serde(default)]
     pub encounter_detection_sensor_present: String,
     #[serde(default)]
+    pub encounter_detection_occupancy_arrival: String,
+    #[serde(default)]
+    pub encounter_detection_occupancy_one_remains: String,
+    #[serde(default)]
     pub clinical_content_check: String,
     #[serde(default)]
     pub multi_patient_check: String,
@@ -382,6 +401,7 @@ pub fn compiled_defaults() -> ServerConfig {
         defaults: OperationalDefaults::default(),
         version: 0,
         source: ConfigSource::CompiledDefaults,
+        channel: ConfigChannel::Stable,
     }
 }
 
@@ -400,6 +420,8 @@ struct CachedConfig {
     thresholds: DetectionThresholds,
     #[serde(default)]
     defaults: OperationalDefaults,
+    #[serde(default)]
+    channel: ConfigChannel,
 }
 
 fn save_cache(config: &ServerConfig) -> Result<()> {
@@ -419,6 +441,7 @@ fn save_cache_to(config: &ServerConfig, path: &std::path::Path) -> Result<()> {
         billing: config.billing.clone(),
         thresholds: config.thresholds.clone(),
         defaults: config.defaults.clone(),
+        channel: config.channel,
     };
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent)?;
@@ -444,17 +467,25 @@ fn load_cache_from(path: &std::path::Path) -> Option<ServerConfig> {
         thresholds: cached.thresholds,
         defaults: cached.defaults,
         source: ConfigSource::Cache,
+        channel: cached.channel,
     })
 }
 
 // ── Fetch from server ────────────────────────────────────────────
 
 /// Load server config: try server first, fall back to cache, then compiled defaults.
-pub async fn load_server_config(client: &ProfileClient) -> ServerConfig {
+///
+/// `room_id` lets the server serve the room's rollout channel; None (e.g.
+/// the mobile processor) always gets stable.
+pub async fn load_server_config(client: &ProfileClient, room_id: Option<&str>) -> ServerConfig {
     // Try fetching from server
-    match fetch_from_server(client).await {
+    match fetch_from_server(client, room_id).await {
         Ok(config) => {
-            info!(version = config.version, "Loaded server config from profile service");
+            info!(
+                version = config.version,
+                channel = ?config.channel,
+                "Loaded server config from profile service"
+            );
             if let Err(e) = save_cache(&config) {
                 warn!("Failed to cache server config: {e}");
             }
@@ -497,29 +528,22 @@ fn resolve_with_cache_path(
     }
 }
 
-async fn fetch_from_server(client: &ProfileClient) -> Result<ServerConfig> {
+async fn fetch_from_server(client: &ProfileClient, room_id: Option<&str>) -> Result<ServerConfig> {
     // Check if we have a cache and if it's still current
-    let server_version = client.get_config_version().await?;
+    let server_version = client.get_config_version(room_id).await?;
 
     if let Some(cached) = load_cache() {
-        if cached.version >= server_version.version {
-            return Ok(ServerConfig {
-                version: cached.version,
-                prompts: cached.prompts,
-                billing: cached.billing,
-                thresholds: cached.thresholds,
-                defaults: cached.defaults,
-                source: ConfigSource::Cache,
-            });
+        if cache_is_current(&cached, &server_version) {
+            return Ok(cached);
         }
     }
 
     // Fetch all four in parallel
     let (prompts_result, billing_result, thresholds_result, defaults_result) = tokio::join!(
-        client.get_config_prompts(),
-        client.get_config_billing(),
-        client.get_config_thresholds(),
-        client.get_config_defaults(),
+        client.get_config_prompts(room_id),
+        client.get_config_billing(room_id),
+        client.get_config_thresholds(room_id),
+        client.get_config_defaults(room_id),
     );
 
     // Defaults fetch falls back to compiled defaults on error (matches the
@@ -538,9 +562,17 @@ async fn fetch_from_server(client: &ProfileClient) -> Result<ServerConfig> {
         thresholds: thresholds_result?,
         defaults,
         source: ConfigSource::Server,
+        channel: server_version.channel,
     })
 }
 
+/// A cache is reusable when it is at least as new as the server and came
+/// from the same channel. A room moved between channels must refetch even
+/// though the version hasn't moved.
+fn cache_is_current(cached: &ServerConfig, server: &ConfigVersion) -> bool {
+    cached.version >= server.version && cached.channel == server.channel
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -573,6 +605,7 @@ mod tests {
             billing: BillingData::default(),
             thresholds: DetectionThresholds::default(),
             defaults: OperationalDefaults::default(),
+            channel: ConfigChannel::Stable,
         };
         let json = serde_json::to_string(&original).unwrap();
         let parsed: CachedConfig = serde_json::from_str(&json).unwrap();
@@ -612,6 +645,31 @@ mod tests {
         assert!(matches!(defaults.source, ConfigSource::CompiledDefaults));
     }
 
+    #[test]
+    fn test_cache_is_current_requires_same_channel() {
+        let version = |version: u64, channel: ConfigChannel| ConfigVersion {
+            version,
+            updated_at: String::new(),
+            channel,
+        };
+        let cached = ServerConfig {
+            version: 5,
+            ..compiled_defaults()
+        };
+        assert!(cache_is_current(&cached, &version(5, ConfigChannel::Stable)));
+        assert!(!cache_is_current(&cached, &version(6, ConfigChannel::Stable)));
+        // Room moved to the candidate channel: same version, must refetch
+        assert!(!cache_is_current(&cached, &version(5, ConfigChannel::Candidate)));
+    }
+
+    #[test]
+    fn test_config_version_back_compat_missing_channel() {
+        // Servers from before staged rollout don't send `channel`
+        let parsed: ConfigVersion =
+            serde_json::from_str(r#"{"version": 3, "updated_at": "2026-01-01T00:00:00Z"}"#).unwrap();
+        assert_eq!(parsed.channel, ConfigChannel::Stable);
+    }
+
     // ── OperationalDefaults ──────────────────────────────────────
 
     #[test]
@@ -649,11 +707,13 @@ mod tests {
             billing: BillingData::default(),
             thresholds: DetectionThresholds::default(),
             defaults: original_defaults.clone(),
+            channel: ConfigChannel::Candidate,
         };
         let json = serde_json::to_string(&original).unwrap();
         let parsed: CachedConfig = serde_json::from_str(&json).unwrap();
         assert_eq!(parsed.version, 7);
         assert_eq!(parsed.defaults, original_defaults);
+        assert_eq!(parsed.channel, ConfigChannel::Candidate);
         // Sanity-check one threshold field survives (guards against serde
         // accidentally dropping the `thresholds` section entirely).
         assert_eq!(parsed.thresholds.force_check_word_threshold, 3000);
@@ -674,6 +734,8 @@ mod tests {
         assert_eq!(parsed.version, 1);
         // Per-field equality — OperationalDefaults derives PartialEq, so this is clean.
         assert_eq!(parsed.defaults, OperationalDefaults::default());
+        // Caches from before staged rollout were always stable
+        assert_eq!(parsed.channel, ConfigChannel::Stable);
     }
 
     #[test]
@@ -741,6 +803,7 @@ mod tests {
             defaults,
             version,
             source: ConfigSource::Server,
+            channel: ConfigChannel::Stable,
         }
     }
 
@@ -759,6 +822,7 @@ mod tests {
             defaults,
             version: 77,
             source: ConfigSource::Cache,
+            channel: ConfigChannel::Stable,
         }
     }
 
@@ -17,6 +17,11 @@ pub struct ServerSyncContext {
     pub physician_name: Option<String>,
     pub room_name: Option<String>,
     pub client: Option<ProfileClient>,
+    /// Read-only here: with `smart_backend`, lets SOAP generation pull a
+    /// confirmed patient's prior visits from the active EMR backend
+    /// (`prior_visits`). Only continuous mode sets them.
+    pub medplum: Option<crate::commands::SharedMedplumClient>,
+    pub smart_backend: Option<crate::commands::SharedSmartBackend>,
 }
 
 impl ServerSyncContext {
@@ -26,6 +31,8 @@ impl ServerSyncContext {
             physician_name: None,
             room_name: None,
             client: None,
+            medplum: None,
+            smart_backend: None,
         }
     }
 
@@ -43,9 +50,22 @@ impl ServerSyncContext {
             physician_name: physician.as_ref().map(|p| p.name.clone()),
             room_name: room_config.as_ref().map(|rc| rc.room_name.clone()),
             client: client.clone(),
+            medplum: None,
+            smart_backend: None,
         }
     }
 
+    /// Attach the shared EMR clients for prior-visit lookups.
+    pub fn with_emr(
+        mut self,
+        medplum: crate::commands::SharedMedplumClient,
+        smart_backend: crate::commands::SharedSmartBackend,
+    ) -> Self {
+        self.medplum = Some(medplum);
+        self.smart_backend = Some(smart_backend);
+        self
+    }
+
     /// Fire-and-forget: upload session metadata+transcript+auxiliary files to server.
     /// Also schedules a delayed re-sync (30s) to catch late-written files
     /// (SOAP generation events in pipeline_log, replay_bundle from build_and_reset).
@@ -289,6 +309,8 @@ mod tests {
             physician_name: Some("Dr Test".to_string()),
             room_name: Some("Room 6".to_string()),
             client: None,
+            medplum: None,
+            smart_backend: None,
         };
         let mut metadata = local_archive::ArchiveMetadata::new("test-session");
         // Enrich should set the multi-user fields
@@ -321,6 +343,8 @@ mod tests {
             physician_name: Some("Dr Test".to_string()),
             room_name: Some("Room 6".to_string()),
             client: None,
+            medplum: None,
+            smart_backend: None,
         };
         let cloned = ctx.clone();
         assert_eq!(cloned.physician_id, ctx.physician_id);
@@ -345,6 +369,8 @@ mod tests {
             physician_name: None,
             room_name: None,
             client: None,
+            medplum: None,
+            smart_backend: None,
         };
         ctx.sync_session("nonexistent-session", "2026-04-15");
     }
@@ -2,6 +2,7 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::biomarkers::VocalBiomarkers;
+use crate::diarization::overlap::OverlapRegion;
 
 /// A transcribed segment of speech
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,6 +16,9 @@ pub struct Segment {
     pub speaker_confidence: Option<f32>,
     /// Vocal biomarkers (vitality, stability)
     pub vocal_biomarkers: Option<VocalBiomarkers>,
+    /// Regions where another speaker talked over `speaker_id`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub overlaps: Vec<OverlapRegion>,
     pub avg_log_prob: Option<f32>,
     pub no_speech_prob: Option<f32>,
 }
@@ -29,6 +33,7 @@ impl Segment {
             speaker_id: None,
             speaker_confidence: None,
             vocal_biomarkers: None,
+            overlaps: Vec::new(),
             avg_log_prob: None,
             no_speech_prob: None,
         }
@@ -1,5 +1,6 @@
 use std::collections::VecDeque;
 use tracing::debug;
+use uuid::Uuid;
 use voice_activity_detector::VoiceActivityDetector;
 
 use crate::transcription::Utterance;
@@ -67,6 +68,8 @@ pub struct VadGatedPipeline {
     /// Speech accumulator
     speech_buffer: Vec<f32>,
     speech_start_samples: u64,
+    /// Id of the utterance being accumulated, carried over to the flushed `Utterance`
+    speech_id: Uuid,
 
     /// Pre-roll buffer (contains samples BEFORE current chunk)
     pre_roll_buffer: VecDeque<f32>,
@@ -90,6 +93,7 @@ impl VadGatedPipeline {
             silence_samples: 0,
             speech_buffer: Vec::new(),
             speech_start_samples: 0,
+            speech_id: Uuid::new_v4(),
             pre_roll_buffer: VecDeque::with_capacity(config.pre_roll_samples),
             config,
             transcription_queue: VecDeque::new(),
@@ -150,6 +154,7 @@ impl VadGatedPipeline {
 
             // Restart immediately (speech still active)
             self.is_speech_active = true;
+            self.speech_id = Uuid::new_v4();
             // Subtract pre-roll from chunk start (same rule as normal start)
             self.speech_start_samples = chunk_start.saturating_sub(self.pre_roll_buffer.len() as u64);
             self.speech_buffer.extend(self.pre_roll_buffer.iter());
@@ -160,6 +165,7 @@ impl VadGatedPipeline {
             (false, true) => {
                 self.is_speech_active = true;
                 self.silence_samples = 0;
+                self.speech_id = Uuid::new_v4();
 
                 // Start time = chunk start minus pre-roll
                 self.speech_start_samples = chunk_start.saturating_sub(self.pre_roll_buffer.len() as u64);
@@ -234,11 +240,12 @@ impl VadGatedPipeline {
             self.speech_buffer.len()
         );
 
-        let utterance = Utterance::new(
+        let mut utterance = Utterance::new(
             std::mem::take(&mut self.speech_buffer),
             start_ms,
             end_ms,
         );
+        utterance.id = self.speech_id;
 
         self.transcription_queue.push_back(utterance);
         self.is_speech_active = false;
@@ -281,6 +288,14 @@ impl VadGatedPipeline {
     pub fn is_speech_active(&self) -> bool {
         self.is_speech_active
     }
+
+    /// The utterance being accumulated: its id, start time in ms and audio so far
+    /// (including pre-roll). The id matches the `Utterance` it is flushed as, so
+    /// callers streaming audio ahead of the flush can pair the two up.
+    pub fn active_speech(&self) -> Option<(Uuid, u64, &[f32])> {
+        self.is_speech_active
+            .then(|| (self.speech_id, self.speech_start_samples / 16, self.speech_buffer.as_slice()))
+    }
 }
 
 impl Default for VadGatedPipeline {
@@ -5,7 +5,8 @@
 //! and streaming (WebSocket) transcription modes.
 //!
 //! Streaming mode (medical-streaming alias) uses Voxtral for real-time transcription
-//! with partial chunks delivered as they're generated.
+//! with partial chunks delivered as they're generated. Duplex sessions, which push
+//! audio while the utterance is still being spoken, live in `duplex_stt`.
 //!
 //! Also retains the legacy OpenAI-compatible /v1/audio/transcriptions endpoint
 //! for backward compatibility (used by listening mode for greeting detection).
@@ -16,6 +17,8 @@ use std::time::Duration;
 use tracing::{debug, error, info, warn};
 use tungstenite::protocol::Message as WsMessage;
 
+use crate::duplex_stt::DuplexSttSession;
+
 /// Default timeout for transcription requests (2 minutes for long audio)
 const DEFAULT_TIMEOUT: Duration = Duration::from_secs(120);
 
@@ -421,6 +424,19 @@ impl WhisperServerClient {
         Err(last_error)
     }
 
+    /// Open a duplex session that takes audio while the utterance is in progress.
+    ///
+    /// Not retried: the caller already has the audio and falls back to
+    /// `transcribe_streaming_blocking` once the utterance is complete.
+    pub fn open_duplex_session(
+        &self,
+        alias: &str,
+        postprocess: bool,
+    ) -> Result<DuplexSttSession, String> {
+        let ws_url = format!("{}/v1/audio/stream", http_to_ws_url(&self.base_url));
+        DuplexSttSession::connect(&ws_url, alias, postprocess)
+    }
+
     /// Single attempt at streaming transcription via WebSocket
     fn try_streaming_transcription(
         &self,
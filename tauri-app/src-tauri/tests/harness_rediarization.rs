@@ -0,0 +1,148 @@
+//! Offline re-diarization against a recorded encounter bundle.
+//!
+//! Audio isn't part of the seed corpus, so the recorded segments are paired
+//! with synthetic embeddings: segments the live pass attributed to the
+//! enrolled clinician get the clinician's voice, every auto-detected
+//! "Speaker N" gets a single patient voice. The live labelling fragmented
+//! that patient across several IDs; the offline pass should collapse them
+//! while keeping text, segment count and the original files intact.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use transcription_app_lib::diarization::clustering::EnrolledCentroid;
+use transcription_app_lib::diarization::offline::{OfflineDiarizationConfig, SegmentEmbedder};
+use transcription_app_lib::diarization::{l2_normalize, DiarizationError};
+use transcription_app_lib::rediarization::{
+    self, ORIGINAL_SEGMENTS_FILENAME, ORIGINAL_TRANSCRIPT_FILENAME,
+};
+use transcription_app_lib::replay_bundle::ReplaySegment;
+use transcription_app_lib::segment_log::LOG_FILENAME as SEGMENTS_FILENAME;
+use transcription_app_lib::transcript_buffer::format_speaker_label;
+
+const BUNDLE: &str = "tests/fixtures/encounter_bundles/seed/2026-04-01_03ffd0eb.json";
+const CLINICIAN: &str = "Dr Zohoor";
+const DIM: usize = 256;
+
+/// Deterministic voice: unit spike on `axis` plus per-segment noise.
+fn voice(axis: usize, noise_seed: u64, noise: f32) -> Vec<f32> {
+    let mut state = noise_seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+    let mut v: Vec<f32> = (0..DIM)
+        .map(|_| {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            ((state >> 33) as f32 / (1u64 << 31) as f32 - 0.5) * noise
+        })
+        .collect();
+    v[axis] += 1.0;
+    l2_normalize(&mut v);
+    v
+}
+
+struct SyntheticEmbedder(HashMap<(u64, u64), Vec<f32>>);
+
+impl SegmentEmbedder for SyntheticEmbedder {
+    fn embed(&mut self, start_ms: u64, end_ms: u64) -> Result<Option<Vec<f32>>, DiarizationError> {
+        Ok(self.0.get(&(start_ms, end_ms)).cloned())
+    }
+}
+
+fn load_bundle_segments() -> Vec<ReplaySegment> {
+    let raw = fs::read_to_string(BUNDLE).expect("seed bundle");
+    let bundle: serde_json::Value = serde_json::from_str(&raw).unwrap();
+    serde_json::from_value(bundle["segments"].clone()).unwrap()
+}
+
+/// Lay the bundle out the way continuous mode archives an encounter.
+fn write_archive(dir: &Path, segments: &[ReplaySegment]) -> String {
+    let jsonl: Vec<String> = segments.iter().map(|s| serde_json::to_string(s).unwrap()).collect();
+    fs::write(dir.join(SEGMENTS_FILENAME), jsonl.join("\n") + "\n").unwrap();
+
+    let transcript = segments
+        .iter()
+        .map(|s| match s.speaker_id {
+            Some(_) => format!(
+                "{}: {}",
+                format_speaker_label(s.speaker_id.as_deref(), s.speaker_confidence),
+                s.text
+            ),
+            None => s.text.clone(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    fs::write(dir.join("transcript.txt"), &transcript).unwrap();
+    transcript
+}
+
+#[test]
+fn rediarization_collapses_fragmented_speakers_in_recorded_bundle() {
+    let segments = load_bundle_segments();
+    let live_speakers: std::collections::BTreeSet<_> =
+        segments.iter().filter_map(|s| s.speaker_id.clone()).collect();
+    assert!(live_speakers.len() > 2, "fixture should show live-pass fragmentation");
+
+    let dir = tempfile::tempdir().unwrap();
+    let original_transcript = write_archive(dir.path(), &segments);
+    let original_segments = fs::read_to_string(dir.path().join(SEGMENTS_FILENAME)).unwrap();
+
+    // Clinician on axis 0, patient on axis 1; unlabelled segments can't be embedded
+    let embeddings = segments
+        .iter()
+        .filter_map(|s| {
+            let axis = match s.speaker_id.as_deref() {
+                Some(CLINICIAN) => 0,
+                Some(_) => 1,
+                None => return None,
+            };
+            Some(((s.start_ms, s.end_ms), voice(axis, s.index, 0.1)))
+        })
+        .collect();
+    let mut embedder = SyntheticEmbedder(embeddings);
+    let enrolled = vec![EnrolledCentroid::new(
+        "profile-1".to_string(),
+        CLINICIAN.to_string(),
+        voice(0, 0, 0.0),
+        true,
+    )];
+
+    let summary = rediarization::rediarize_session_dir(
+        dir.path(),
+        &mut embedder,
+        &enrolled,
+        &OfflineDiarizationConfig::default(),
+    )
+    .unwrap();
+
+    assert_eq!(summary.segment_count, segments.len());
+    assert_eq!(summary.speakers_before.len(), live_speakers.len());
+    assert_eq!(summary.speakers_after, vec![CLINICIAN.to_string(), "Speaker 1".to_string()]);
+    assert!(summary.relabeled_count > 0);
+
+    // Clinician segments keep the enrolled name; everyone else is one patient
+    let rewritten = rediarization::read_segments(dir.path()).unwrap();
+    assert_eq!(rewritten.len(), segments.len());
+    for (before, after) in segments.iter().zip(&rewritten) {
+        assert_eq!(before.index, after.index);
+        assert_eq!(before.text, after.text);
+        match before.speaker_id.as_deref() {
+            Some(CLINICIAN) => assert_eq!(after.speaker_id.as_deref(), Some(CLINICIAN)),
+            Some(_) => assert_eq!(after.speaker_id.as_deref(), Some("Speaker 1")),
+            None => assert_eq!(after.speaker_id, None),
+        }
+    }
+
+    // Transcript keeps one line per segment with the new labels
+    let transcript = fs::read_to_string(dir.path().join("transcript.txt")).unwrap();
+    assert_eq!(transcript.lines().count(), original_transcript.lines().count());
+    assert!(transcript.lines().all(|l| !l.starts_with("Speaker 4")));
+
+    // Live labelling preserved for comparison
+    assert_eq!(
+        fs::read_to_string(dir.path().join(ORIGINAL_TRANSCRIPT_FILENAME)).unwrap(),
+        original_transcript
+    );
+    assert_eq!(
+        fs::read_to_string(dir.path().join(ORIGINAL_SEGMENTS_FILENAME)).unwrap(),
+        original_segments
+    );
+}
@@ -7,19 +7,25 @@
 //!   cargo run --bin detection_replay_cli -- ~/.transcriptionapp/archive/2026/03/12/
 //!   cargo run --bin detection_replay_cli -- ~/.transcriptionapp/archive/2026/03/12/ --override hybrid_confirm_window_secs=120
 //!   cargo run --bin detection_replay_cli -- --all
+//!   cargo run --bin detection_replay_cli -- --date 2026-04-15 --sensor-csv ~/.transcriptionapp/mmwave/2026-04-15.csv
 
 use std::env;
 use std::fs;
 use std::path::PathBuf;
 use std::process::ExitCode;
 
+use chrono::{DateTime, Utc};
+
 use transcription_app_lib::config::Config;
 use transcription_app_lib::continuous_mode::MIN_SENSOR_HYBRID_WORDS;
 use transcription_app_lib::encounter_detection::{
     DetectionEvalContext, DetectionOutcome, EncounterDetectionResult, evaluate_detection,
 };
+use transcription_app_lib::harness::sensor_timeline::{ReplayedSensorState, SensorTimeline};
 use transcription_app_lib::local_archive;
-use transcription_app_lib::replay_bundle::{find_replay_bundles, ReplayBundle};
+use transcription_app_lib::presence_sensor::csv_logger::read_log;
+use transcription_app_lib::presence_sensor::FusionConfig;
+use transcription_app_lib::replay_bundle::{find_replay_bundles, DetectionCheck, ReplayBundle};
 use transcription_app_lib::replay_fetch::ArchiveFetcher;
 
 fn print_usage(program: &str) {
@@ -42,7 +48,11 @@ fn print_usage(program: &str) {
     eprintln!("                      Supported: hybrid_confirm_window_secs, hybrid_min_words_for_sensor_split,");
     eprintln!("                                 merge_back_count, min_sensor_hybrid_words,");
     eprintln!("                                 sensor_continuous_present=true|false,");
-    eprintln!("                                 manual_triggered=true|false");
+    eprintln!("                                 manual_triggered=true|false,");
+    eprintln!("                                 presence_debounce_secs (with --sensor-csv)");
+    eprintln!("  --sensor-csv PATH   Recompute each check's sensor inputs from a recorded presence");
+    eprintln!("                      log (~/.transcriptionapp/mmwave/YYYY-MM-DD.csv) instead of the");
+    eprintln!("                      values captured in the bundle");
     eprintln!("  --mismatches        Only show bundles where replayed decision differs from actual");
     eprintln!("  --fail-on-mismatch  Exit non-zero if agreement drops below threshold (default: 99.0%)");
     eprintln!("  --threshold PCT     Set the agreement threshold for --fail-on-mismatch (e.g. 95.0)");
@@ -53,6 +63,7 @@ fn print_usage(program: &str) {
     eprintln!("  {} --all", program);
     eprintln!("  {} --all --override hybrid_confirm_window_secs=120", program);
     eprintln!("  {} --all --mismatches", program);
+    eprintln!("  {} --date 2026-04-15 --sensor-csv ~/.transcriptionapp/mmwave/2026-04-15.csv", program);
 }
 
 /// Parsed --override values
@@ -70,6 +81,9 @@ struct Overrides {
     /// manual triggers short-circuit the LLM and don't produce bundle checks,
     /// but exposed for completeness/debugging.
     manual_triggered: Option<bool>,
+    /// What-if: debounce used when rebuilding the sensor timeline from
+    /// `--sensor-csv`. Defaults to the app config value.
+    presence_debounce_secs: Option<u64>,
 }
 
 fn parse_override(s: &str) -> Result<(String, String), String> {
@@ -203,6 +217,32 @@ fn should_skip_precheck(
     is_hybrid && check.loop_state.sensor_triggered && check.word_count < min_words
 }
 
+/// When the bundle's encounter began: its first segment, falling back to the
+/// first detection check. Used as the split point for sensor replay.
+fn encounter_start(bundle: &ReplayBundle) -> Option<DateTime<Utc>> {
+    bundle
+        .segments
+        .first()
+        .map(|s| s.ts.as_str())
+        .or_else(|| bundle.detection_checks.first().map(|c| c.ts.as_str()))
+        .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+/// Sensor inputs for `check` recomputed from the recorded timeline. `None`
+/// when the check falls outside the log's span (or its timestamp doesn't
+/// parse) — the captured values are used as-is then.
+fn replay_sensor_inputs(
+    check: &DetectionCheck,
+    start: DateTime<Utc>,
+    timeline: &SensorTimeline,
+) -> Option<ReplayedSensorState> {
+    let check_ts = DateTime::parse_from_rfc3339(&check.ts).ok()?.with_timezone(&Utc);
+    timeline
+        .covers(check_ts)
+        .then(|| timeline.replay_check(start, check_ts))
+}
+
 /// Determine actual outcome from the bundle's split_decision and outcome fields.
 ///
 /// Intermediate checks that led to a split-then-merge-back are detected by
@@ -276,6 +316,7 @@ async fn main() -> ExitCode {
     // Parse arguments
     let mut archive_path: Option<PathBuf> = None;
     let mut date_arg: Option<String> = None;
+    let mut sensor_csv: Option<PathBuf> = None;
     let mut overrides = Overrides::default();
     let mut mismatches_only = false;
     let mut all_archives = false;
@@ -295,6 +336,14 @@ async fn main() -> ExitCode {
                 }
                 date_arg = Some(args[i].clone());
             }
+            "--sensor-csv" => {
+                i += 1;
+                if i >= args.len() {
+                    eprintln!("Error: --sensor-csv requires a path");
+                    return ExitCode::from(1);
+                }
+                sensor_csv = Some(PathBuf::from(&args[i]));
+            }
             "--mismatches" => {
                 mismatches_only = true;
             }
@@ -347,6 +396,10 @@ async fn main() -> ExitCode {
                                     .expect("Invalid bool value (use true|false)"),
                             );
                         }
+                        "presence_debounce_secs" => {
+                            overrides.presence_debounce_secs =
+                                Some(value.parse().expect("Invalid u64 value"));
+                        }
                         _ => {
                             eprintln!("Unknown override key: {}", key);
                             return ExitCode::from(1);
@@ -460,7 +513,8 @@ async fn main() -> ExitCode {
         || overrides.merge_back_count.is_some()
         || overrides.min_sensor_hybrid_words.is_some()
         || overrides.sensor_continuous_present.is_some()
-        || overrides.manual_triggered.is_some();
+        || overrides.manual_triggered.is_some()
+        || overrides.presence_debounce_secs.is_some();
     if has_any_override {
         eprintln!("Overrides active:");
         if let Some(v) = overrides.hybrid_confirm_window_secs {
@@ -481,9 +535,45 @@ async fn main() -> ExitCode {
         if let Some(v) = overrides.manual_triggered {
             eprintln!("  manual_triggered = {}", v);
         }
+        if let Some(v) = overrides.presence_debounce_secs {
+            eprintln!("  presence_debounce_secs = {}", v);
+        }
         eprintln!();
     }
 
+    // Recorded sensor log → fused presence timeline (same debounce + staleness
+    // as the live fusion task)
+    let timeline = match sensor_csv {
+        Some(ref path) => match read_log(path) {
+            Ok(readings) => {
+                let debounce_secs = overrides
+                    .presence_debounce_secs
+                    .unwrap_or_else(|| Config::load_or_default().presence_debounce_secs);
+                let timeline = SensorTimeline::from_log(
+                    &readings,
+                    debounce_secs,
+                    FusionConfig::default().mmwave_stale_secs,
+                );
+                eprintln!(
+                    "Sensor log: {} ({} readings, {} transitions, debounce {}s)",
+                    path.display(),
+                    readings.len(),
+                    timeline.transitions().len(),
+                    debounce_secs
+                );
+                eprintln!();
+                Some(timeline)
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                return ExitCode::from(1);
+            }
+        },
+        None => None,
+    };
+    let mut sensor_replayed = 0;
+    let mut sensor_changed = 0;
+
     let mut total_bundles = 0;
     let mut total_checks = 0;
     let mut matches = 0;
@@ -499,10 +589,29 @@ async fn main() -> ExitCode {
         let mut bundle_has_mismatch = false;
         let mut check_lines = Vec::new();
 
-        for (idx, check) in bundle.detection_checks.iter().enumerate() {
+        let start = encounter_start(bundle);
+        for (idx, captured) in bundle.detection_checks.iter().enumerate() {
             total_checks += 1;
             let actual = actual_outcome_str(&bundle, idx, num_checks);
 
+            // With --sensor-csv, swap in sensor inputs recomputed from the log
+            let sensor = match (&timeline, start) {
+                (Some(timeline), Some(start)) => replay_sensor_inputs(captured, start, timeline),
+                _ => None,
+            };
+            let mut sensor_note = "";
+            let replayed_check = sensor.map(|state| {
+                sensor_replayed += 1;
+                if !state.agrees_with(&captured.loop_state) {
+                    sensor_changed += 1;
+                    sensor_note = " [sensor differs]";
+                }
+                let mut c = captured.clone();
+                state.apply_to(&mut c.loop_state);
+                c
+            });
+            let check = replayed_check.as_ref().unwrap_or(captured);
+
             // Pre-check guard: sensor-triggered checks in hybrid mode with
             // insufficient words would now be skipped before evaluate_detection()
             let (replayed_str, agree) =
@@ -537,12 +646,13 @@ async fn main() -> ExitCode {
 
             let symbol = if agree { "\u{2713}" } else { "\u{2717}" };
             check_lines.push(format!(
-                "  Check {}/{}: actual={:<24} replayed={:<32} {}",
+                "  Check {}/{}: actual={:<24} replayed={:<32} {}{}",
                 idx + 1,
                 num_checks,
                 actual,
                 replayed_str,
-                symbol
+                symbol,
+                sensor_note
             ));
         }
 
@@ -567,6 +677,12 @@ async fn main() -> ExitCode {
         "Bundles: {}  Checks: {}  Match: {}  Mismatch: {}",
         total_bundles, total_checks, matches, mismatches
     );
+    if timeline.is_some() {
+        println!(
+            "Sensor replay: {} checks covered by the log, {} with different sensor inputs than captured",
+            sensor_replayed, sensor_changed
+        );
+    }
     let agreement_pct = if total_checks > 0 {
         let pct = matches as f64 / total_checks as f64 * 100.0;
         println!("Agreement: {:.1}%", pct);
@@ -1182,4 +1298,34 @@ mod tests {
         assert!(!names.contains(&"metadata.json".to_string()));
         assert!(!names.contains(&"transcript.txt".to_string()));
     }
+
+    // ---- --sensor-csv replay ----
+
+    #[test]
+    fn test_sensor_csv_replaces_captured_sensor_inputs() {
+        use transcription_app_lib::presence_sensor::csv_logger::LoggedReading;
+
+        let t0 = DateTime::parse_from_rfc3339("2026-04-15T14:00:00Z").unwrap().with_timezone(&Utc);
+        // Present for 10 min, then gone
+        let readings: Vec<LoggedReading> = (0..900)
+            .map(|s| LoggedReading {
+                timestamp: t0 + chrono::Duration::seconds(s),
+                raw: s < 600,
+            })
+            .collect();
+        let timeline = SensorTimeline::from_log(&readings, 15, 10);
+
+        // Captured by a timer check 2s after the debounced departure, with
+        // the bundle claiming no sensor activity
+        let mut check = make_check(Some(true), Some(0.9), Some(10), 800, 0, 600.0, true);
+        check.ts = (t0 + chrono::Duration::seconds(617)).to_rfc3339();
+        let state = replay_sensor_inputs(&check, t0, &timeline).expect("covered");
+        assert!(!state.agrees_with(&check.loop_state));
+        assert!(state.sensor_triggered);
+        assert!(state.sensor_absent_since.is_some());
+
+        // Outside the log: captured values are kept
+        check.ts = (t0 + chrono::Duration::seconds(3600)).to_rfc3339();
+        assert!(replay_sensor_inputs(&check, t0, &timeline).is_none());
+    }
 }
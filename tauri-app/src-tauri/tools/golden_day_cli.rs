@@ -11,9 +11,16 @@
 //!   - An extra spurious split appearing
 //!   - Encounter renumbering breaking
 //!
+//! With `--sensor-csv`, each session's detection checks are also replayed
+//! against the day's recorded presence log: the sensor inputs rebuilt from
+//! the log (debounce, absence tracking, continuous presence) must match what
+//! production captured, or hybrid-mode split decisions can't be trusted to
+//! reproduce.
+//!
 //! Usage:
 //!   cargo run --bin golden_day_cli -- 2026-04-15
 //!   cargo run --bin golden_day_cli -- --all-days --fail-on-regression
+//!   cargo run --bin golden_day_cli -- --all-days --sensor-csv ~/.transcriptionapp/mmwave/
 
 use std::collections::HashMap;
 use std::env;
@@ -21,9 +28,15 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::ExitCode;
 
+use chrono::{DateTime, Utc};
 use serde::Deserialize;
 
+use transcription_app_lib::config::Config;
+use transcription_app_lib::harness::sensor_timeline::SensorTimeline;
 use transcription_app_lib::local_archive;
+use transcription_app_lib::presence_sensor::csv_logger::read_log;
+use transcription_app_lib::presence_sensor::FusionConfig;
+use transcription_app_lib::replay_bundle::ReplayBundle;
 use transcription_app_lib::replay_fetch::ArchiveFetcher;
 
 #[derive(Debug, Deserialize)]
@@ -46,6 +59,9 @@ fn print_usage(program: &str) {
     eprintln!();
     eprintln!("Options:");
     eprintln!("  --fail-on-regression  Exit non-zero if any check fails");
+    eprintln!("  --sensor-csv PATH     Replay detection checks against a recorded presence log.");
+    eprintln!("                        PATH is a YYYY-MM-DD.csv file (single date) or a directory");
+    eprintln!("                        of daily logs (e.g. ~/.transcriptionapp/mmwave/)");
     eprintln!("  --help                Show this help");
 }
 
@@ -96,7 +112,52 @@ fn count_sessions_in_archive(date: &str) -> Result<usize, String> {
     Ok(count)
 }
 
-async fn verify_day(date: &str, labels_for_day: &[&Label], fetcher: &ArchiveFetcher) -> (u32, u32, Vec<String>) {
+/// The presence log for `date`: `PATH/YYYY-MM-DD.csv` for a directory,
+/// otherwise `PATH` itself.
+fn sensor_log_for_date(path: &Path, date: &str) -> PathBuf {
+    if path.is_dir() {
+        path.join(format!("{}.csv", date))
+    } else {
+        path.to_path_buf()
+    }
+}
+
+fn parse_ts(ts: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(ts).ok().map(|dt| dt.with_timezone(&Utc))
+}
+
+/// Count checks in `bundle` whose captured sensor inputs disagree with the
+/// timeline. Returns (covered checks, disagreeing checks).
+fn sensor_disagreements(bundle: &ReplayBundle, timeline: &SensorTimeline) -> (usize, usize) {
+    let start = bundle
+        .segments
+        .first()
+        .map(|s| s.ts.as_str())
+        .or_else(|| bundle.detection_checks.first().map(|c| c.ts.as_str()))
+        .and_then(parse_ts);
+    let Some(start) = start else {
+        return (0, 0);
+    };
+    let mut covered = 0;
+    let mut differing = 0;
+    for check in &bundle.detection_checks {
+        let Some(ts) = parse_ts(&check.ts).filter(|ts| timeline.covers(*ts)) else {
+            continue;
+        };
+        covered += 1;
+        if !timeline.replay_check(start, ts).agrees_with(&check.loop_state) {
+            differing += 1;
+        }
+    }
+    (covered, differing)
+}
+
+async fn verify_day(
+    date: &str,
+    labels_for_day: &[&Label],
+    fetcher: &ArchiveFetcher,
+    timeline: Option<&SensorTimeline>,
+) -> (u32, u32, Vec<String>) {
     let mut checks = 0;
     let mut passes = 0;
     let mut issues: Vec<String> = Vec::new();
@@ -136,6 +197,32 @@ async fn verify_day(date: &str, labels_for_day: &[&Label], fetcher: &ArchiveFetc
         ));
     }
 
+    // Check 3 (--sensor-csv): per labeled session, the recorded sensor log
+    // reproduces the sensor inputs every detection check acted on.
+    if let Some(timeline) = timeline {
+        let bundles = fetcher.list_replay_bundles_for_date(date).await.unwrap_or_default();
+        let mut replayed_checks = 0;
+        for label in labels_for_day {
+            let Some((_, bundle)) = bundles.iter().find(|(id, _)| id.ends_with(&label.session_id)) else {
+                continue;
+            };
+            checks += 1;
+            let (covered, differing) = sensor_disagreements(bundle, timeline);
+            replayed_checks += covered;
+            if differing == 0 {
+                passes += 1;
+            } else {
+                issues.push(format!(
+                    "  ✗ Session {}: {}/{} detection checks saw different sensor inputs than the log replays",
+                    &label.session_id[..8],
+                    differing,
+                    covered
+                ));
+            }
+        }
+        println!("  Sensor replay: {} detection checks covered by the log", replayed_checks);
+    }
+
     println!("  Sessions verified: {}/{}", sessions_found, labels_for_day.len());
     println!("  Checks: {}/{} passed", passes, checks);
     for issue in &issues {
@@ -156,11 +243,20 @@ async fn main() -> ExitCode {
     let mut date_arg: Option<String> = None;
     let mut all_days = false;
     let mut fail_on_regression = false;
+    let mut sensor_csv: Option<PathBuf> = None;
     let mut i = 1;
     while i < args.len() {
         match args[i].as_str() {
             "--all-days" => all_days = true,
             "--fail-on-regression" => fail_on_regression = true,
+            "--sensor-csv" => {
+                i += 1;
+                if i >= args.len() {
+                    eprintln!("Error: --sensor-csv requires a path");
+                    return ExitCode::from(1);
+                }
+                sensor_csv = Some(PathBuf::from(&args[i]));
+            }
             "--help" => { print_usage(program); return ExitCode::SUCCESS; }
             other => {
                 if other.starts_with('-') {
@@ -199,6 +295,14 @@ async fn main() -> ExitCode {
         eprintln!("Provide a date (YYYY-MM-DD) or --all-days");
         return ExitCode::from(1);
     };
+    if let Some(ref path) = sensor_csv {
+        if dates.len() > 1 && !path.is_dir() {
+            eprintln!("--sensor-csv must be a directory of daily logs when verifying several days");
+            return ExitCode::from(1);
+        }
+    }
+    let debounce_secs = Config::load_or_default().presence_debounce_secs;
+    let stale_secs = FusionConfig::default().mmwave_stale_secs;
 
     let fetcher = ArchiveFetcher::from_env().unwrap_or_else(|e| {
         eprintln!("warn: ArchiveFetcher init failed ({e}); falling back to local-only");
@@ -211,7 +315,23 @@ async fn main() -> ExitCode {
 
     for date in dates {
         let labels_for_day = by_date.get(date).unwrap();
-        let (checks, passes, issues) = verify_day(date, labels_for_day, &fetcher).await;
+        let timeline = match sensor_csv {
+            Some(ref path) => {
+                let log = sensor_log_for_date(path, date);
+                match read_log(&log) {
+                    Ok(readings) => Some(SensorTimeline::from_log(&readings, debounce_secs, stale_secs)),
+                    Err(e) => {
+                        let issue = format!("  ✗ {}: sensor log unavailable: {}", date, e);
+                        println!("{}", issue);
+                        total_checks += 1;
+                        total_issues.push(issue);
+                        None
+                    }
+                }
+            }
+            None => None,
+        };
+        let (checks, passes, issues) = verify_day(date, labels_for_day, &fetcher, timeline.as_ref()).await;
         total_checks += checks;
         total_passes += passes;
         total_issues.extend(issues);
@@ -43,6 +43,9 @@ function makeSession(overrides: Partial<LocalArchiveDetails>): LocalArchiveDetai
 
 const successResult: ConfirmPatientResult = {
   medplumSynced: true,
+  medplumQueued: false,
+  hl7Sent: false,
+  hl7Queued: false,
   profileServiceSynced: true,
   patientId: 'p-1',
   medplumPatientId: 'p-1',
@@ -182,6 +185,9 @@ describe('ConfirmPatientsBatchDialog', () => {
     const user = userEvent.setup();
     const partial: ConfirmPatientResult = {
       medplumSynced: false,
+      medplumQueued: false,
+      hl7Sent: false,
+      hl7Queued: false,
       profileServiceSynced: true,
       patientId: 'uuid-1',
       medplumPatientId: null,
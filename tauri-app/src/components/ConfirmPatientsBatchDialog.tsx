@@ -117,6 +117,9 @@ const ConfirmPatientsBatchDialog: React.FC<ConfirmPatientsBatchDialogProps> = ({
           status: 'done',
           result: {
             medplumSynced: false,
+            medplumQueued: false,
+            hl7Sent: false,
+            hl7Queued: false,
             profileServiceSynced: false,
             patientId: null,
             medplumPatientId: null,
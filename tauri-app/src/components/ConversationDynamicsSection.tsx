@@ -79,6 +79,7 @@ export default function ConversationDynamicsSection({ dynamics, expanded, onTogg
               <span className="metric-label">Overlaps</span>
               <span className="metric-value-wide">
                 {dynamics.total_overlap_count}
+                {dynamics.total_overlap_ms > 0 && ` · ${formatDuration(dynamics.total_overlap_ms)}`}
                 {dynamics.total_interruption_count > 0 && (
                   <span className="interruption-count"> ({dynamics.total_interruption_count} interr.)</span>
                 )}
@@ -23,6 +23,19 @@ const defaultPendingSettings: PendingSettings = {
   medplum_server_url: 'http://localhost:8103',
   medplum_client_id: 'test-client-id',
   medplum_auto_sync: false,
+  emr_backend: 'medplum',
+  fhir_server_url: '',
+  smart_client_id: '',
+  smart_private_key_path: '',
+  smart_key_id: '',
+  smart_scope: 'system/*.read system/*.write',
+  hl7_enabled: false,
+  hl7_host: '',
+  hl7_port: 2575,
+  hl7_message_type: 'MDM_T02',
+  hl7_sending_facility: '',
+  hl7_receiving_application: '',
+  hl7_receiving_facility: '',
   whisper_server_url: 'http://localhost:8001',
   auto_start_enabled: false,
   auto_start_require_enrolled: false,
@@ -88,6 +88,8 @@ function isSettings(obj: unknown): obj is Settings {
   // Medplum EMR settings
   if (typeof o.medplum_server_url !== 'string') return false;
   if (typeof o.medplum_auto_sync !== 'boolean') return false;
+  if (typeof o.emr_backend !== 'string') return false;
+  if (typeof o.fhir_server_url !== 'string') return false;
 
   // SOAP preferences
   if (typeof o.soap_detail_level !== 'number') return false;
@@ -294,6 +296,19 @@ describe('IPC Contracts', () => {
         medplum_server_url: 'http://localhost:8103',
         medplum_client_id: 'test-client',
         medplum_auto_sync: false,
+        emr_backend: 'medplum',
+        fhir_server_url: '',
+        smart_client_id: '',
+        smart_private_key_path: '',
+        smart_key_id: '',
+        smart_scope: 'system/*.read system/*.write',
+        hl7_enabled: false,
+        hl7_host: '',
+        hl7_port: 2575,
+        hl7_message_type: 'MDM_T02',
+        hl7_sending_facility: '',
+        hl7_receiving_application: '',
+        hl7_receiving_facility: '',
         whisper_mode: 'remote',
         whisper_server_url: 'http://localhost:8001',
         whisper_server_model: 'large-v3-turbo',
@@ -456,6 +471,19 @@ describe('IPC Contracts', () => {
         medplum_server_url: 'http://localhost:8103',
         medplum_client_id: '',
         medplum_auto_sync: false,
+        emr_backend: 'medplum',
+        fhir_server_url: '',
+        smart_client_id: '',
+        smart_private_key_path: '',
+        smart_key_id: '',
+        smart_scope: 'system/*.read system/*.write',
+        hl7_enabled: false,
+        hl7_host: '',
+        hl7_port: 2575,
+        hl7_message_type: 'MDM_T02',
+        hl7_sending_facility: '',
+        hl7_receiving_application: '',
+        hl7_receiving_facility: '',
         whisper_mode: 'remote',
         whisper_server_url: 'http://localhost:8001',
         whisper_server_model: 'large-v3-turbo',
@@ -25,6 +25,19 @@ const mockSettings = {
   medplum_server_url: 'http://localhost:8103',
   medplum_client_id: 'test-client',
   medplum_auto_sync: false,
+  emr_backend: 'medplum',
+  fhir_server_url: '',
+  smart_client_id: '',
+  smart_private_key_path: '',
+  smart_key_id: '',
+  smart_scope: 'system/*.read system/*.write',
+  hl7_enabled: false,
+  hl7_host: '',
+  hl7_port: 2575,
+  hl7_message_type: 'MDM_T02',
+  hl7_sending_facility: '',
+  hl7_receiving_application: '',
+  hl7_receiving_facility: '',
   // Whisper server settings
   whisper_mode: 'remote' as const,
   whisper_server_url: 'http://localhost:8001',
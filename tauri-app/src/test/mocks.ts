@@ -74,6 +74,19 @@ export const mockSettings: Settings = {
   medplum_server_url: 'http://localhost:8103',
   medplum_client_id: 'test-client-id',
   medplum_auto_sync: false,
+  emr_backend: 'medplum',
+  fhir_server_url: '',
+  smart_client_id: '',
+  smart_private_key_path: '',
+  smart_key_id: '',
+  smart_scope: 'system/*.read system/*.write',
+  hl7_enabled: false,
+  hl7_host: '',
+  hl7_port: 2575,
+  hl7_message_type: 'MDM_T02',
+  hl7_sending_facility: '',
+  hl7_receiving_application: '',
+  hl7_receiving_facility: '',
   // Whisper server settings
   whisper_mode: 'remote',
   whisper_server_url: 'http://localhost:8001',
@@ -94,6 +107,7 @@ export const mockSettings: Settings = {
   auto_end_silence_ms: 180000,
   // Debug storage
   debug_storage_enabled: false,
+  mcp_auth_token: '',
   // AI image generation
   image_source: 'ai',
   gemini_api_key: '',
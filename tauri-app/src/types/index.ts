@@ -75,6 +75,21 @@ export interface Settings {
   medplum_server_url: string;
   medplum_client_id: string;
   medplum_auto_sync: boolean;
+  // EMR backend selection (ADR-0035) and SMART Backend Services settings
+  emr_backend: 'medplum' | 'smart_backend';
+  fhir_server_url: string;
+  smart_client_id: string;
+  smart_private_key_path: string;
+  smart_key_id: string;
+  smart_scope: string;
+  // HL7 v2 outbound interface (ADR-0036)
+  hl7_enabled: boolean;
+  hl7_host: string;
+  hl7_port: number;
+  hl7_message_type: 'MDM_T02' | 'ORU_R01';
+  hl7_sending_facility: string;
+  hl7_receiving_application: string;
+  hl7_receiving_facility: string;
   // Whisper server settings (remote transcription only - local mode removed)
   whisper_mode: 'remote';  // Always 'remote' - local mode no longer supported
   whisper_server_url: string;
@@ -101,6 +116,8 @@ export interface Settings {
   debug_storage_enabled: boolean;
   // Pharmacotherapy refactor service (MacBook-hosted; powers the Clinical Assistant window)
   pharm_service_url: string;
+  // MCP control/PHI tool token (empty = those tools disabled)
+  mcp_auth_token: string;
   // AI image generation
   image_source: ImageSource;
   gemini_api_key: string;
@@ -320,6 +337,7 @@ export interface ConversationDynamics {
   speaker_turns: SpeakerTurnStats[];
   silence: SilenceStats;
   total_overlap_count: number;
+  total_overlap_ms: number;
   total_interruption_count: number;
   mean_response_latency_ms: number;
   engagement_score: number | null;
@@ -809,6 +827,10 @@ export interface LocalArchiveMetadata {
   patient_confirmed_at?: string | null;
   /** Medplum FHIR Patient ID linked to this session (v0.10.46+) */
   medplum_patient_id?: string | null;
+  /** Medplum FHIR Encounter ID created during patient confirmation */
+  medplum_encounter_id?: string | null;
+  /** References of structured FHIR resources already uploaded for this session */
+  medplum_structured_resource_ids?: string[] | null;
   physician_id?: string | null;
   physician_name?: string | null;
   room_name?: string | null;
@@ -828,6 +850,8 @@ export interface PatientRecord {
   dob: string;
   medplumPatientId?: string | null;
   sessionIds: string[];
+  /** sessionId → YYYY-MM-DD, for sessions confirmed with a date. */
+  sessionDates?: Record<string, string>;
   createdAt: string;
   updatedAt: string;
 }
@@ -835,6 +859,12 @@ export interface PatientRecord {
 /** Outcome of `confirm_session_patient` — per-store sync status for inline display (v0.10.46+). */
 export interface ConfirmPatientResult {
   medplumSynced: boolean;
+  /** Medplum write failed transiently and is queued for background retry */
+  medplumQueued: boolean;
+  /** HL7 v2 receiver acknowledged the note */
+  hl7Sent: boolean;
+  /** HL7 message not acknowledged yet; queued for background retry */
+  hl7Queued: boolean;
   profileServiceSynced: boolean;
   patientId: string | null;
   medplumPatientId: string | null;
@@ -842,6 +872,93 @@ export interface ConfirmPatientResult {
   errors: string[];
 }
 
+/** Pending Medplum session sync from `medplum_outbox_status` (no PHI) */
+export interface MedplumOutboxStatus {
+  sessionId: string;
+  date: string;
+  queuedAt: string;
+  attempts: number;
+  nextAttemptAt: string | null;
+  lastError: string | null;
+  /** Failed permanently; re-confirm the patient to retry */
+  parked: boolean;
+}
+
+/** Outcome of `hl7_send_session` */
+export interface Hl7SendResult {
+  acknowledged: boolean;
+  /** Not acknowledged yet; the HL7 outbox will retry it */
+  queued: boolean;
+  error: string | null;
+}
+
+/** Unacknowledged HL7 message from `hl7_outbox_status` (no PHI) */
+export interface Hl7OutboxStatus {
+  sessionId: string;
+  date: string;
+  controlId: string;
+  messageType: string;
+  queuedAt: string;
+  attempts: number;
+  nextAttemptAt: string | null;
+  lastError: string | null;
+  /** Rejected by the receiver; send the session again to retry */
+  parked: boolean;
+}
+
+/** Condition draft for the session's resolved OHIP diagnostic code */
+export interface ConditionDraft {
+  ohipCode: string;
+  description: string;
+  /** ICD-10 category, when the OHIP code maps to exactly one */
+  icd10Code?: string;
+  evidence?: string;
+}
+
+/** MedicationStatement draft for one confirmed medication */
+export interface MedicationStatementDraft {
+  name: string;
+  dose?: string;
+  frequency?: string;
+}
+
+export type VitalSign =
+  | 'blood_pressure'
+  | 'heart_rate'
+  | 'respiratory_rate'
+  | 'body_temperature'
+  | 'oxygen_saturation'
+  | 'body_weight'
+  | 'body_height'
+  | 'bmi';
+
+/** Vital-sign Observation draft read from the SOAP Objective section */
+export interface VitalSignDraft {
+  kind: VitalSign;
+  /** Value, or systolic pressure for blood pressure */
+  value: number;
+  /** Diastolic pressure (blood pressure only) */
+  diastolic?: number;
+  /** UCUM unit code, e.g. `mm[Hg]`, `/min`, `Cel` */
+  unit: string;
+  sourceText: string;
+}
+
+/** Output of `preview_structured_resources`; reviewed, then passed to
+ *  `medplum_upload_structured_resources`. */
+export interface StructuredResourcesPreview {
+  conditions: ConditionDraft[];
+  medicationStatements: MedicationStatementDraft[];
+  observations: VitalSignDraft[];
+}
+
+/** Outcome of `medplum_upload_structured_resources` */
+export interface StructuredResourcesUploadResult {
+  /** `<ResourceType>/<id>` per created resource */
+  created: string[];
+  errors: string[];
+}
+
 /** A single patient's SOAP note within a multi-patient encounter */
 export interface ArchivedPatientNote {
   index: number;
@@ -860,6 +977,35 @@ export interface LocalArchiveDetails {
   patientNotes: ArchivedPatientNote[] | null;
 }
 
+/** Snippet of a `search_archive` hit */
+export interface ArchiveSearchSnippet {
+  field: 'transcript' | 'soap_note';
+  text: string;
+  /** `[start, end)` character offsets of matched terms within `text` */
+  highlights: [number, number][];
+}
+
+/** Ranked result of `search_archive` (local archive + server index) */
+export interface ArchiveSearchHit {
+  session_id: string;
+  date: string;
+  started_at?: string;
+  patient_name?: string;
+  encounter_number?: number;
+  score: number;
+  snippets: ArchiveSearchSnippet[];
+}
+
+/** Result of `rediarize_local_session` (offline second-pass speaker labelling) */
+export interface RediarizationSummary {
+  segmentCount: number;
+  /** Segments whose speaker label changed */
+  relabeledCount: number;
+  speakersBefore: string[];
+  speakersAfter: string[];
+  transcriptLinesRewritten: number;
+}
+
 // ============================================================================
 // Session Feedback Types
 // ============================================================================
@@ -980,6 +1126,7 @@ export type ContinuousModeEventType =
   | 'encounter_detected'
   | 'soap_generated'
   | 'soap_failed'
+  | 'billing_ready'
   | 'checking'
   | 'error'
   | 'stopped'
@@ -1008,6 +1155,10 @@ export interface ContinuousModeEvent {
   outcome?: string;
   /** Buffer word count at shadow decision time */
   buffer_words?: number;
+  /** Number of billing codes extracted (for billing_ready events) */
+  codes_count?: number;
+  /** Billing total in cents (for billing_ready events) */
+  total_amount_cents?: number;
   /** LLM confidence for shadow decision */
   confidence?: number;
   /** ISO timestamp when sleep mode will end (for sleep_started events) */
@@ -1165,6 +1316,8 @@ export interface BillingCode {
   afterHoursPremiumCents: number;
   /** Quantity (default 1). For add-on codes like G385A, can be >1. */
   quantity?: number;
+  /** Set when an annual frequency limit reduced or suppressed this code */
+  limitReason?: string;
 }
 
 /** Time-based billing entry (Q310A-Q313A) */
@@ -1225,6 +1378,24 @@ export interface BillingRecord {
   suggestions?: UpgradeSuggestion[];
   /** Audit log of upgrades the clinician applied */
   appliedUpgrades?: AppliedUpgrade[];
+  /** Codes removed because the patient's annual limit was already reached */
+  suppressedCodes?: BillingCode[];
+  /** MOH payment outcome, set once a remittance advice has been reconciled */
+  remittance?: RemittanceOutcome;
+}
+
+export type RemittanceStatus = 'paid' | 'adjusted' | 'rejected';
+
+/** Remittance result stamped onto a billing record by RA reconciliation */
+export interface RemittanceOutcome {
+  status: RemittanceStatus;
+  claimNumber: string;
+  paymentDate: string | null;
+  amountSubmittedCents: number;
+  /** Negative when the RA carries a recovery */
+  amountPaidCents: number;
+  explanatoryCodes: string[];
+  reconciledAt: string;
 }
 
 /** Search result from diagnostic code lookup */
@@ -1279,6 +1450,9 @@ export interface BillingDaySummary {
   confirmedCount: number;
   draftCount: number;
   capStatus: DailyCapStatus;
+  /** Amount MOH actually paid (reconciled RAs only) */
+  totalPaidCents: number;
+  reconciledCount: number;
 }
 
 /** Monthly (28-day rolling) billing summary */
@@ -1295,6 +1469,73 @@ export interface BillingMonthSummary {
   indirectAdminRatio: number;
   adminRatio: number;
   capStatus: MonthlyCapStatus;
+  /** Actual revenue from reconciled RAs */
+  totalPaidCents: number;
+  reconciledCount: number;
+}
+
+/** Provider identity for the OHIP claims batch header (export_ohip_claim_file) */
+export interface ClaimBatchHeader {
+  providerNumber: string;
+  groupNumber?: string;
+  specialtyCode?: string;
+  mohOfficeCode: string;
+  batchSequence?: number;
+}
+
+/** Patient health-card data supplied at claim export time (never persisted) */
+export interface PatientHealthCard {
+  healthNumber: string;
+  versionCode?: string;
+  birthDate?: string | null;
+}
+
+/** Validation problems for one encounter left out of a claims batch */
+export interface EncounterClaimErrors {
+  sessionId: string;
+  date: string;
+  patientName: string | null;
+  errors: string[];
+}
+
+/** Result of export_ohip_claim_file */
+export interface ClaimBatchExport {
+  contents: string;
+  batchId: string;
+  claimCount: number;
+  itemCount: number;
+  totalFeeSubmittedCents: number;
+  skippedDraftCount: number;
+  errors: EncounterClaimErrors[];
+}
+
+/** RA claim matched to an archived encounter */
+export interface ReconciledClaim {
+  sessionId: string;
+  date: string;
+  patientName: string | null;
+  outcome: RemittanceOutcome;
+}
+
+/** RA claim with no matching archived encounter */
+export interface UnmatchedRemittanceClaim {
+  claimNumber: string;
+  accountingNumber: string;
+  serviceDate: string | null;
+  serviceCodes: string[];
+  amountPaidCents: number;
+}
+
+/** Result of reconcile_remittance_advice */
+export interface ReconciliationReport {
+  paymentDate: string | null;
+  matched: ReconciledClaim[];
+  unmatched: UnmatchedRemittanceClaim[];
+  paidCount: number;
+  adjustedCount: number;
+  rejectedCount: number;
+  totalPaidCents: number;
+  totalSubmittedCents: number;
 }
 
 /** OHIP code search result from backend */